@@ -1,6 +1,14 @@
 //! Application actions/events that drive state changes.
 
-use crate::client::models::{Album, Artist, Genre, Playlist, Song, StructuredLyrics};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+use crate::client::models::{
+    Album, AlbumInfo, Artist, ArtistIndex, Genre, NowPlayingEntry, Playlist, Song, StructuredLyrics,
+};
+use crate::hidden::HideTarget;
+use crate::player::upnp::Renderer;
+use crate::ui::components::server_status::ServerStatus;
 
 /// Actions that can be dispatched to update application state.
 #[derive(Debug, Clone, PartialEq)]
@@ -23,10 +31,14 @@ pub enum Action {
     SwitchTab(Tab),
     NextTab,
     PrevTab,
+    CycleFocus(isize), // Move the focus along the panels on screen
+    CountDigit(u8),    // Digit of a count prefix (or a tab number)
 
     // Mouse
     MouseClick(u16, u16),
     MouseDoubleClick(u16, u16),
+    MouseDrag(u16, u16), // Pointer moved with the left button held
+    MouseRelease(u16, u16),
     MouseScroll(i16, u16, u16), // (delta, x, y) - positive delta = down, negative = up
 
     // Search
@@ -35,6 +47,58 @@ pub enum Action {
     SearchInput(char),
     SearchBackspace,
     SearchSubmit,
+    SearchAppendToQueue, // Queue the selected result without closing search
+    SearchPlayNext,      // Insert the selected result after the current song
+
+    // Quick filter (library list or queue, whichever is focused)
+    OpenFilter,
+    CloseFilter, // Stop typing, keep the filter applied
+    ClearFilter,
+    FilterInput(char),
+    FilterBackspace,
+
+    GoToRandomAlbum,
+    QueueRandomSongs,
+
+    // Songs tab source and filter popup
+    CloseSongsFilter,
+    SongsFilterNext,
+    SongsFilterPrev,
+    SongsFilterCycle,
+    SongsFilterInput(char),
+    SongsFilterBackspace,
+    SongsFilterApply,
+
+    // Add to playlist popup
+    OpenPlaylistPicker,
+    PlaylistPickerBack,
+    PlaylistPickerNext,
+    PlaylistPickerPrev,
+    PlaylistPickerInput(char),
+    PlaylistPickerBackspace,
+    PlaylistPickerChoose,
+
+    // M3U files
+    ExportM3u, // Save the selected playlist, or the queue, as an .m3u8 file
+    OpenImportPrompt,
+    CloseImportPrompt,
+    ImportPromptInput(char),
+    ImportPromptBackspace,
+    ImportM3u,
+    M3uMatched {
+        name: String,
+        song_ids: Vec<String>,
+        unmatched: Vec<String>, // Lines of the file not found in the library
+    },
+
+    // Year picker
+    OpenYearPicker,
+    CloseYearPicker,
+    YearPickerNext,
+    YearPickerPrev,
+    YearPickerSelect,
+    YearPickerInput(char),
+    YearPickerBackspace,
 
     // Playback controls
     PlayPause,
@@ -48,42 +112,94 @@ pub enum Action {
     SeekTo(u32), // Seek to absolute position in seconds
     VolumeUp,
     VolumeDown,
-    SetVolume(u8), // Set volume to specific value (0-100)
+    VolumeUpFine,   // Raise the volume by 1%
+    VolumeDownFine, // Lower the volume by 1%
+    ToggleMute,     // Mute, or bring back the volume from before muting
+    SetVolume(u8),  // Set volume to specific value (0-100)
+    SpeedUp,
+    SpeedDown,
+    SetSpeed(f32), // Set playback speed (1.0 = normal)
     ToggleShuffle,
     CycleRepeat,
+    ToggleConsume,
+    ToggleRemainingTime,   // Show time remaining instead of elapsed
     SetRepeat(RepeatMode), // Set specific repeat mode
+    CycleOutput,           // Switch to the next output: local, network sink, then each renderer
+    RenderersFound(Vec<Renderer>),
 
     // Queue management
-    AddToQueue(Song),
-    AddAlbumToQueue(Vec<Song>),
+    AddToQueue(Song, QueueSource),
+    AddAlbumToQueue(Vec<Song>, QueueSource),
     AppendToQueue, // Add selected item to queue without playing
     ClearQueue,
     RemoveFromQueue(usize),
     RemoveSelectedFromQueue, // Remove currently selected item from queue
+    RemoveSourceFromQueue,   // Remove every entry queued from the selected entry's source
     PlayFromQueue(usize),
     MoveQueueItem(usize, isize), // Move item up (-1) or down (+1)
+    ToggleQueueMark,             // Mark the selected queue item to move with the others marked
+    OpenMovePrompt,              // Ask where to move the marked (or selected) queue items
+    CloseMovePrompt,
+    MovePromptInput(char),
+    MovePromptBackspace,
+    MoveQueueItems, // Move them to the position typed
+    DedupQueue,
+    SortQueue(QueueSort),
+
+    // Hiding items from the library (asks for confirmation first)
+    HideSelected,
+    UnhideAll,
+    ConfirmYes,
+    ConfirmNo,
+
+    // Album header actions (album or playlist songs view)
+    PlayAlbum,
+    ShuffleAlbum,
+    QueueAlbum,
+    ToggleAlbumStar,
 
     // Library actions
     LoadArtists,
     LoadAlbums,
     LoadAlbum(String),
     LoadArtist(String),
+    CycleListSource, // Albums tab: newest -> recent -> frequent; Songs tab: random -> added -> ...
+    GoToPlayingAlbum, // Open the album of the song now playing
+    GoToPlayingArtist, // Open the artist of the song now playing
+    CopyTrack(CopyTarget), // Copy something about the song now playing
+    OpenExternal(ExternalTarget), // Hand the song now playing or the queue to the external player
+    Sleeping,        // The system is about to suspend
+    Woke,            // The system resumed from suspend
+    RatePlayingTrack(u8), // 1-5 stars, 0 clears
     LoadPlaylists,
     LoadPlaylist(String),
     LoadSongs,
     LoadGenres,
     LoadGenreAlbums(String),
     LoadFavorites,
+    LibraryLoadFailed(Tab, String), // A tab's list couldn't be fetched
     RefreshLibrary,
 
     // API responses
-    ArtistsLoaded(Vec<Artist>),
+    ArtistsLoaded(Vec<ArtistIndex>),
     AlbumsLoaded(Vec<Album>),
+    CompilationsLoaded(Vec<Album>), // The Albums tab's compilations, gathered from every album
     AlbumLoaded(Album, Vec<Song>),
+    SimilarAlbumsLoaded(String, Vec<Album>), // Album ID, top albums of similar artists
+    StepSimilarAlbum(isize),                 // Pick the next (or previous) similar album
     ArtistLoaded(Artist, Vec<Album>),
     PlaylistsLoaded(Vec<Playlist>),
     PlaylistLoaded(Playlist, Vec<Song>),
-    SongsLoaded(Vec<Song>),
+    LibrarySongsLoaded(Vec<Song>), // Every song in the library, for smart playlists
+    LibrarySongsFailed(String),
+    SongPageLoaded {
+        list: SongList,
+        offset: u32,
+        songs: Vec<Song>,
+    },
+    SongPageFailed(SongList, String),
+    PlaylistEditDone(Option<String>), // The error, if the server refused the change
+    GenreSongsFetched(String, Vec<Song>), // Every song of a genre, to queue shuffled
     GenresLoaded(Vec<Genre>),
     GenreAlbumsLoaded(String, Vec<Album>),
     FavoritesLoaded {
@@ -99,13 +215,28 @@ pub enum Action {
 
     // Media annotation
     ToggleStar,
+    StarFailed(Starred, String), // Undo a star change the server refused
     Scrobble,
 
     // Lyrics
     ToggleLyrics,
+    LyricsScrollUp,
+    LyricsScrollDown,
+    LyricsFollow,       // Stop picking a line and follow playback again
+    LyricsSeekToLine,   // Seek to the start of the picked synced line
+    LyricsDelay(i64),   // Shift the current song's lyrics by this many ms
+    ExportLyrics,       // Save the current lyrics as an .lrc file
+    CycleLyricsVersion, // Show the song's lyrics in the next language or from the next source
     LoadLyrics(String),
     LyricsLoaded(String, Vec<StructuredLyrics>),
 
+    // Library sorting
+    CycleSort,
+    ReverseSort,
+    SortByColumn(usize), // Sort by a column of the current list, or reverse it if already sorted by it
+    ToggleGroup, // Collapse or expand the focused list's selected group (artist letter or queue album)
+    ToggleGroups, // Collapse or expand every group of the focused list
+
     // Navigation enhancements
     JumpToTop,
     JumpToBottom,
@@ -116,27 +247,113 @@ pub enum Action {
     // Overlays
     ShowHelp,
     HideHelp,
+    HelpScroll(isize),
+    HelpPage(isize), // Scroll the help by whole pages
+    HelpTop,
+    HelpBottom,
+    HelpFilterStart,
+    HelpFilterInput(char),
+    HelpFilterBackspace,
+    HelpFilterDone,  // Keep the filter and go back to scrolling
+    HelpFilterClear, // Drop the filter
     ShowTrackInfo,
     HideTrackInfo,
+    ToggleFullscreen, // Full-screen now playing view
+    ToggleQueuePanel,
+    ToggleQueueGrouping,  // Group the queue by album, or stop
+    ResizeSidePanel(i16), // Widen (positive) or narrow the queue or lyrics panel, in percent
 
     // Album art
     LoadAlbumArt(String),
-    AlbumArtLoaded(String, Vec<u8>),
+    AlbumArtLoaded(String, Option<Box<DynamicImage>>), // Decoded and scaled, None on failure
+
+    // User avatars (keyed by username)
+    LoadAvatar(String),
+    AvatarLoaded(String, Vec<u8>),
 
     // Player state updates
     PlayerProgress(f64),
     PlayerStateChanged(PlayerState),
     TrackEnded,
 
-    // Errors
+    // Notifications
     Error(String),
-    ClearError,
+    DismissToasts,
+    ShowMessages, // Message history popup
+    HideMessages,
+    MessagesNext,
+    MessagesPrev,
+
+    // Password prompt
+    LoginInput(char),
+    LoginBackspace,
+    LoginSubmit,
+    LoginCancel,
+
+    // Album info popup, opened by ShowTrackInfo while an album is open
+    HideAlbumInfo,
+    ScrollAlbumInfo(i16),               // Lines down (negative = up)
+    AlbumInfoLoaded(String, AlbumInfo), // Album ID, info
+    AlbumInfoFailed(String, String),    // Album ID, error
+    AlbumInfoCover(String, Option<Box<DynamicImage>>), // Album ID, decoded large cover
+
+    // Server status popup
+    ShowServerStatus,
+    HideServerStatus,
+    RefreshServerStatus,
+    ServerStatusLoaded(Box<ServerStatus>),
+    ServerStatusFailed(String),
+
+    // Other listeners popup
+    ShowListeners,
+    HideListeners,
+    RefreshListeners,
+    ListenersLoaded(Vec<NowPlayingEntry>),
+    ListenersFailed(String),
+    ListenersNext,
+    ListenersPrev,
+    ListenersGoToAlbum,
+    ListenersQueue,
+
+    // Log viewer
+    ShowLog,
+    HideLog,
+    LogNext,
+    LogPrev,
+    LogTop,
+    LogBottom,
+    LogCycleLevel, // Raise the minimum level shown, wrapping back to all
+    LogSearchStart,
+    LogSearchInput(char),
+    LogSearchBackspace,
+    LogSearchDone,  // Keep the search and go back to scrolling
+    LogSearchClear, // Drop the search
 
     // No-op
     None,
 }
 
 /// Current playback state
+/// What to copy about a song.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyTarget {
+    /// "Artist – Title"
+    ArtistTitle,
+    /// Link streaming it, credentials included
+    StreamUrl,
+    /// Link to its album in the server's web interface
+    WebUrl,
+}
+
+/// What to hand to the external player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalTarget {
+    /// The song now playing, as a stream URL
+    Track,
+    /// The queue from the song now playing on, as an M3U file
+    Queue,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[allow(dead_code)]
 pub enum PlayerState {
@@ -147,6 +364,34 @@ pub enum PlayerState {
     Buffering,
 }
 
+impl Action {
+    /// Whether a count prefix repeats the action.
+    pub fn takes_count(&self) -> bool {
+        matches!(
+            self,
+            Self::NavigateUp
+                | Self::NavigateDown
+                | Self::NavigateLeft
+                | Self::NavigateRight
+                | Self::NextTab
+                | Self::PrevTab
+                | Self::ScrollHalfPageDown
+                | Self::ScrollHalfPageUp
+                | Self::NextTrack
+                | Self::PreviousTrack
+                | Self::SeekForward
+                | Self::SeekBackward
+                | Self::SeekForwardLarge
+                | Self::SeekBackwardLarge
+                | Self::VolumeUp
+                | Self::VolumeDown
+                | Self::VolumeUpFine
+                | Self::VolumeDownFine
+                | Self::MoveQueueItem(..)
+        )
+    }
+}
+
 /// Repeat mode for playback
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum RepeatMode {
@@ -166,8 +411,99 @@ impl RepeatMode {
     }
 }
 
+/// Sort orders for the queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueSort {
+    ArtistAlbumTrack,
+    Title,
+}
+
+/// Where a queue entry was added from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueSource {
+    Album {
+        id: String,
+        name: String,
+    },
+    Playlist {
+        id: String,
+        name: String,
+    },
+    Artist {
+        id: String,
+        name: String,
+    },
+    Genre {
+        name: String,
+    },
+    Random,
+    Songs,
+    Favorites,
+    History,
+    Search,
+    /// What another user of the server was playing
+    Listener {
+        username: String,
+    },
+}
+
+impl QueueSource {
+    /// Short label shown next to queue entries.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Album { name, .. } => name.clone(),
+            Self::Playlist { name, .. } => format!("playlist: {}", name),
+            Self::Artist { name, .. } => format!("artist: {}", name),
+            Self::Genre { name } => format!("genre: {}", name),
+            Self::Random => "random".to_string(),
+            Self::Songs => "songs".to_string(),
+            Self::Favorites => "favorites".to_string(),
+            Self::History => "history".to_string(),
+            Self::Search => "search".to_string(),
+            Self::Listener { username } => format!("from {}", username),
+        }
+    }
+}
+
+/// A song or album whose star was toggled, as it was before.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Starred {
+    Song(Box<Song>),
+    Album(Box<Album>),
+}
+
+/// An action waiting for the user to confirm it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Confirm {
+    Hide(HideTarget),
+    UnhideAll,
+}
+
+impl Confirm {
+    /// Question shown in the confirmation popup.
+    pub fn prompt(&self) -> String {
+        match self {
+            Self::Hide(target) => format!(
+                "Hide {} from all views? It stays on the server and can be restored with U.",
+                target.describe()
+            ),
+            Self::UnhideAll => String::from("Show all hidden songs and albums again?"),
+        }
+    }
+}
+
+/// Song lists that are loaded a page at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SongList {
+    Songs,
+    FavoriteSongs,
+    GenreSongs,
+    SearchSongs,
+}
+
 /// Application tabs
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Tab {
     #[default]
     Artists,
@@ -176,6 +512,7 @@ pub enum Tab {
     Playlists,
     Genres,
     Favorites,
+    History,
 }
 
 impl Tab {
@@ -187,6 +524,7 @@ impl Tab {
             Tab::Playlists,
             Tab::Genres,
             Tab::Favorites,
+            Tab::History,
         ]
     }
 
@@ -198,41 +536,7 @@ impl Tab {
             Self::Playlists => "Playlists",
             Self::Genres => "Genres",
             Self::Favorites => "Favorites",
-        }
-    }
-
-    pub fn index(&self) -> usize {
-        match self {
-            Self::Artists => 0,
-            Self::Albums => 1,
-            Self::Songs => 2,
-            Self::Playlists => 3,
-            Self::Genres => 4,
-            Self::Favorites => 5,
-        }
-    }
-
-    /// Get the next tab (wraps around).
-    pub fn next(&self) -> Tab {
-        match self {
-            Self::Artists => Self::Albums,
-            Self::Albums => Self::Songs,
-            Self::Songs => Self::Playlists,
-            Self::Playlists => Self::Genres,
-            Self::Genres => Self::Favorites,
-            Self::Favorites => Self::Artists,
-        }
-    }
-
-    /// Get the previous tab (wraps around).
-    pub fn prev(&self) -> Tab {
-        match self {
-            Self::Artists => Self::Favorites,
-            Self::Albums => Self::Artists,
-            Self::Songs => Self::Albums,
-            Self::Playlists => Self::Songs,
-            Self::Genres => Self::Playlists,
-            Self::Favorites => Self::Genres,
+            Self::History => "History",
         }
     }
 }
@@ -1,35 +1,125 @@
 //! Main application state and logic.
 
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use chrono::Timelike;
 use color_eyre::Result;
+use image::DynamicImage;
 use ratatui::layout::Rect;
 use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+
+use crate::action::{
+    Action, Confirm, CopyTarget, ExternalTarget, PlayerState, QueueSource, RepeatMode, SongList,
+    Starred, Tab,
+};
+use crate::client::api::ApiClientError;
+use crate::client::models::{Album, Playlist, Role, Song, User};
+use crate::client::paging::{
+    AlbumListSongs, GenreSongs, PagedSongs, RandomSongs, SearchSongs, SongSource, StarredSongs,
+    PAGE_SIZE,
+};
+use crate::client::{Auth, SubsonicClient, TlsOptions};
+use crate::clipboard::Clipboard;
+use crate::config::{
+    Config, LyricsProvider, M3uLocations, OutputMode, ReplayGainMode, VisualizerMode,
+};
+use crate::count::PendingCount;
+use crate::external;
+use crate::focus::Focus;
+use crate::hidden::{HiddenItems, HideTarget};
+use crate::m3u;
+use crate::player::loudness::normalization;
+use crate::player::silence::Silence;
+use crate::player::upnp::{self, Renderer};
+use crate::player::{
+    DeviceOptions, Loudness, Output, Player, PlayerEvent, StreamCache, TrackKey, MAX_SPEED,
+    MIN_SPEED,
+};
+use crate::shuffle;
+use crate::smart;
+use crate::sort::{AlbumList, SongsSource};
+use crate::state::LibraryStats;
+use crate::ui::components::filter::Filterable;
+use crate::ui::components::library::{top_album, PlaylistEdit};
+use crate::ui::components::playlist_picker::PlaylistChoice;
+use crate::ui::components::search::SEARCH_PAGE_SIZE;
+use crate::ui::components::server_status::ServerStatus;
+use crate::ui::hit::{Control, HitRegion, HitRegions};
+use crate::ui::icons::Icons;
+use crate::ui::{
+    AlbumInfoState, AvatarCache, HelpState, ImportPromptState, LibraryState, ListenersState,
+    LogViewerState, LoginState, LyricsState, MovePromptState, NowPlayingState, PlaylistPickerState,
+    QueueState, SearchState, ServerStatusState, SongsFilterState, ToastState, VisualizerState,
+    YearPickerState,
+};
+
+/// Most songs fetched when queueing a whole genre.
+const GENRE_QUEUE_LIMIT: usize = 2000;
+
+/// Search results an imported M3U entry is looked for in.
+const IMPORT_SEARCH_SIZE: u32 = 20;
+
+/// Songs requested at a time when fetching the whole library.
+const LIBRARY_PAGE_SIZE: u32 = 500;
+
+/// Most upcoming tracks downloaded ahead of time.
+const MAX_PRECACHE: usize = 3;
+
+/// Format the server is asked to transcode tracks that can't be decoded to.
+const FALLBACK_FORMAT: &str = "mp3";
+
+/// Step the queue and lyrics panels are resized by, in percent.
+pub const SIDE_PANEL_STEP: i16 = 5;
+
+/// Narrowest and widest the queue and lyrics panels can be, in percent.
+const SIDE_PANEL_RANGE: (u16, u16) = (15, 70);
+
+/// Gap between starting the loads of successive tabs on startup.
+const LOAD_STAGGER: Duration = Duration::from_millis(150);
+
+/// Wait before the first ping of a server that went away, doubled for each
+/// one after, up to `MAX_RECONNECT_DELAY`.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Similar artists whose top albums are suggested below an album's songs.
+const SIMILAR_ARTISTS: u32 = 6;
+
+/// How long a playlist reorder waits for further moves before it is sent.
+const PLAYLIST_MOVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Size, in pixels, of the cover in the album info popup.
+const LARGE_COVER_SIZE: u32 = 600;
+
+/// Decode album art, scaling down covers the server sent larger than the
+/// `size` asked for, so drawing them stays cheap.
+fn decode_album_art(bytes: &[u8], size: u32) -> Option<DynamicImage> {
+    match image::load_from_memory(bytes) {
+        Ok(image) if image.width() > size || image.height() > size => {
+            Some(image.thumbnail(size, size))
+        }
+        Ok(image) => Some(image),
+        Err(e) => {
+            tracing::warn!("Failed to decode album art: {}", e);
+            None
+        }
+    }
+}
 
-use crate::action::{Action, PlayerState, RepeatMode, Tab};
-use crate::client::models::Song;
-use crate::client::SubsonicClient;
-use crate::config::Config;
-use crate::player::{Player, PlayerEvent};
-use crate::ui::{LibraryState, LyricsState, NowPlayingState, QueueState, SearchState};
-
-/// UI layout areas for mouse click detection.
-#[derive(Debug, Default, Clone)]
-pub struct UiLayout {
-    /// Tab bar area
-    pub tabs: Rect,
-    /// Library panel area
-    pub library: Rect,
-    /// Queue panel area (if visible)
-    pub queue: Option<Rect>,
-    /// Now playing bar area
-    pub now_playing: Rect,
-    /// Progress bar area within now playing
-    pub progress_bar: Rect,
-    /// Volume bar area within now playing
-    pub volume_bar: Rect,
-    /// Playback controls area within now playing
-    pub controls: Rect,
+/// Whether the server can be reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connection {
+    Online,
+    /// Requests fail on the way; the server is pinged until it answers
+    Reconnecting {
+        attempt: u32,
+        next_try: Instant,
+    },
 }
 
 /// Main application state.
@@ -37,6 +127,9 @@ pub struct App {
     /// Whether the app should quit
     pub should_quit: bool,
 
+    /// Whether the screen is out of date and should be drawn
+    pub redraw: bool,
+
     /// Configuration
     pub config: Config,
 
@@ -55,26 +148,110 @@ pub struct App {
     /// Now playing state
     pub now_playing: NowPlayingState,
 
+    /// Avatars of other users on the server
+    pub avatars: AvatarCache,
+
+    /// Album art being fetched in the background, by cover art ID
+    album_art_loads: HashMap<String, AbortHandle>,
+
+    /// Tab lists being fetched in the background
+    tab_loads: HashMap<Tab, AbortHandle>,
+
     /// Search state
     pub search: SearchState,
 
     /// Lyrics state
     pub lyrics: LyricsState,
 
-    /// Help overlay visible
-    pub show_help: bool,
+    /// Spectrum visualizer bars
+    pub visualizer: VisualizerState,
+
+    /// Help overlay
+    pub help: HelpState,
 
     /// Track info popup visible
     pub show_track_info: bool,
 
-    /// Error message to display
-    pub error_message: Option<String>,
+    /// Full-screen now playing view shown instead of the library layout
+    pub fullscreen: bool,
+
+    /// Year range picker for the Albums tab
+    pub year_picker: YearPickerState,
+
+    /// Source and filter popup for the Songs tab
+    pub songs_filter: SongsFilterState,
+
+    /// Popup for adding the selection to a playlist
+    pub playlist_picker: PlaylistPickerState,
+
+    /// Popup asking for the M3U file to import
+    pub import_prompt: ImportPromptState,
+    pub move_prompt: MovePromptState,
+
+    /// Toast notifications and message history
+    pub toasts: ToastState,
+
+    /// Count prefix typed so far
+    pub count: PendingCount,
+
+    /// Volume to go back to when unmuting, while muted
+    unmuted_volume: Option<u8>,
+
+    /// Renderers found on the network, to cast to
+    renderers: Vec<Renderer>,
+
+    /// Where the sound goes
+    output: Output,
+
+    /// Log file overlay
+    pub log_viewer: LogViewerState,
+
+    /// Server status popup
+    pub server_status: ServerStatusState,
+
+    /// Popup showing what other users are listening to
+    pub listeners: ListenersState,
+
+    /// Popup with the notes and cover of the open album
+    pub album_info: AlbumInfoState,
+
+    /// Account signed in with, once its roles are known
+    pub user: Option<User>,
+
+    /// Password prompt
+    pub login: LoginState,
+
+    /// Action waiting for a yes/no answer
+    pub confirm: Option<Confirm>,
+
+    /// System clipboard, for copying track details
+    clipboard: Clipboard,
+
+    /// Whether the server answered the last request
+    pub connection: Connection,
+
+    /// Reload the library once the server answers, as it couldn't be loaded
+    refresh_on_reconnect: bool,
+
+    /// Certificates for the server, loaded on startup
+    tls: TlsOptions,
+
+    /// Changes to the open playlist still to be made on the server, in
+    /// order; the first is being sent
+    playlist_edits: VecDeque<PlaylistEdit>,
+
+    /// Reorder of the open playlist held back while songs keep moving, and
+    /// when to send it
+    playlist_move: Option<(PlaylistEdit, Instant)>,
 
     /// Action sender for async operations
     pub action_tx: mpsc::UnboundedSender<Action>,
 
-    /// Focus mode (0 = library, 1 = queue)
-    pub focus: u8,
+    /// Writes the playback history off the action loop
+    history_writer: crate::history::Writer,
+
+    /// Panel with the keyboard focus
+    pub focus: Focus,
 
     /// Terminal width for mouse click detection
     pub terminal_width: Option<u16>,
@@ -82,112 +259,366 @@ pub struct App {
     /// Terminal height for mouse click detection
     pub terminal_height: Option<u16>,
 
-    /// UI layout areas for mouse detection
-    pub layout: UiLayout,
+    /// Screen regions taking mouse input, registered by the last render
+    pub hit_regions: HitRegions,
 
     /// Last volume scroll time for debouncing
     last_volume_scroll: Option<Instant>,
+
+    /// Bar being dragged with the mouse, with its area when the drag started
+    drag: Option<(HitRegion, Rect)>,
+
+    /// Library totals for the greeting, cached across sessions
+    pub library_stats: LibraryStats,
+
+    /// Loudness measured for tracks without ReplayGain tags, keyed by song ID
+    loudness: HashMap<String, Loudness>,
+
+    /// Track cache the player fills, checked for what's still in it
+    track_cache: Option<StreamCache>,
+
+    /// Songs that couldn't be decoded, streamed transcoded for the rest
+    /// of the session
+    transcoded: HashSet<String>,
+
+    /// Whether playback was paused by the sound device going away
+    paused_by_device: bool,
+
+    /// Whether playback was paused for the system going to sleep
+    paused_for_sleep: bool,
+
+    /// Every song in the library, which smart playlists pick from
+    library_songs: Option<Vec<Song>>,
+
+    /// Whether the library's songs are being fetched
+    fetching_library_songs: bool,
+
+    /// Compilations in the library, kept as finding them goes through every
+    /// album
+    compilations: Option<Vec<Album>>,
+
+    /// Smart playlist to open once the library's songs arrive
+    smart_playlist_waiting: Option<String>,
 }
 
 impl App {
     /// Create a new application instance.
     pub fn new(config: Config, action_tx: mpsc::UnboundedSender<Action>) -> Self {
         let (width, height) = crossterm::terminal::size().unwrap_or((80, 24));
+        let mut library = LibraryState::new();
+        if !config.ui.song_columns.is_empty() {
+            library.song_columns = config.ui.song_columns.clone();
+        }
+        library.icons = Icons::for_set(config.ui.icons);
+        library.tabs = config.ui.tabs.clone();
+        library.tab = library.tabs.first().copied().unwrap_or_default();
+        let mut queue = QueueState::new();
+        queue.marquee.enabled = config.ui.marquee;
+        let mut now_playing = NowPlayingState::new(crate::ui::image_picker(&config.ui));
+        now_playing.marquee.enabled = config.ui.marquee;
+        now_playing.max_volume = config.player.max_volume;
+        now_playing.icons = library.icons;
+        queue.icons = library.icons;
         Self {
             should_quit: false,
+            redraw: true,
             config,
             client: None,
             player: None,
-            library: LibraryState::new(),
-            queue: QueueState::new(),
-            now_playing: NowPlayingState::new(),
+            library,
+            queue,
+            now_playing,
+            avatars: AvatarCache::new(),
+            album_art_loads: HashMap::new(),
+            tab_loads: HashMap::new(),
             search: SearchState::new(),
             lyrics: LyricsState::new(),
-            show_help: false,
+            visualizer: VisualizerState::default(),
+            help: HelpState::default(),
             show_track_info: false,
-            error_message: None,
+            fullscreen: false,
+            year_picker: YearPickerState::default(),
+            songs_filter: SongsFilterState::default(),
+            playlist_picker: PlaylistPickerState::default(),
+            import_prompt: ImportPromptState::default(),
+            move_prompt: MovePromptState::default(),
+            toasts: ToastState::default(),
+            count: PendingCount::default(),
+            unmuted_volume: None,
+            renderers: Vec::new(),
+            output: Output::Local,
+            log_viewer: LogViewerState::default(),
+            server_status: ServerStatusState::default(),
+            listeners: ListenersState::default(),
+            album_info: AlbumInfoState::default(),
+            user: None,
+            login: LoginState::default(),
+            confirm: None,
+            clipboard: Clipboard::default(),
+            connection: Connection::Online,
+            refresh_on_reconnect: false,
+            tls: TlsOptions::default(),
+            playlist_edits: VecDeque::new(),
+            playlist_move: None,
             action_tx,
-            focus: 0,
+            history_writer: crate::history::Writer::spawn(),
+            focus: Focus::Library,
             terminal_width: Some(width),
             terminal_height: Some(height),
-            layout: UiLayout::default(),
+            hit_regions: HitRegions::default(),
             last_volume_scroll: None,
+            drag: None,
+            library_stats: LibraryStats::default(),
+            loudness: HashMap::new(),
+            track_cache: None,
+            transcoded: HashSet::new(),
+            paused_by_device: false,
+            paused_for_sleep: false,
+            library_songs: None,
+            fetching_library_songs: false,
+            compilations: None,
+            smart_playlist_waiting: None,
         }
     }
 
     /// Initialize the application.
     pub async fn init(&mut self) -> Result<()> {
-        // Initialize the API client
-        if self.config.is_valid() {
-            let auth = if let Some(api_key) = &self.config.server.api_key {
-                crate::client::Auth::from_api_key(api_key)
-            } else if let Some(password) = &self.config.server.password {
-                crate::client::Auth::from_password(&self.config.server.username, password)
-            } else {
-                return Err(color_eyre::eyre::eyre!("No password or API key configured"));
-            };
-
-            let mut client = SubsonicClient::new(&self.config.server.url, auth);
-
-            // Test connection
-            match client.ping().await {
-                Ok(_) => {
-                    tracing::info!("Connected to server: {}", self.config.server.url);
-
-                    // Check for OpenSubsonic extensions
-                    if let Ok(extensions) = client.get_open_subsonic_extensions().await {
-                        tracing::info!("OpenSubsonic extensions: {:?}", extensions);
-                    }
+        // Certificates for self-signed servers, shared with the player
+        self.tls = self.config.server.tls().unwrap_or_else(|e| {
+            tracing::error!("TLS setup failed: {}", e);
+            self.toasts.error(format!("TLS setup failed: {}", e));
+            TlsOptions::default()
+        });
 
-                    self.client = Some(client);
-                }
-                Err(e) => {
-                    self.error_message = Some(format!("Failed to connect: {}", e));
-                    tracing::error!("Failed to connect to server: {}", e);
-                }
-            }
+        // Initialize the API client, asking for the password if it's missing
+        if self.config.is_valid() {
+            self.connect().await;
+        } else if self.config.needs_password() {
+            self.login.open(&self.config.server.username, None);
         } else {
-            self.error_message = Some(String::from(
+            self.toasts.error(String::from(
                 "Invalid configuration. Please configure server URL and credentials.",
             ));
         }
 
         // Initialize the audio player
-        match Player::new() {
+        let cache = StreamCache::open(self.config.player.cache_mb).unwrap_or_else(|e| {
+            tracing::warn!("Track cache unavailable: {}", e);
+            None
+        });
+        self.track_cache = cache.clone();
+        let fade = Duration::from_millis(self.config.player.fade_ms.into());
+        let network = self.config.player.network_output.clone();
+        let device = DeviceOptions {
+            match_track: self.config.player.output_mode == OutputMode::MatchTrack,
+            sample_rate: self.config.player.sample_rate,
+            resampler: self.config.player.resampler,
+        };
+        let player = &self.config.player;
+        let silence = player.skip_silence.then(|| {
+            Silence::new(
+                player.silence_threshold_db,
+                Duration::from_secs(player.silence_seconds.into()),
+            )
+        });
+        let output = network.clone().map_or(Output::Local, Output::Network);
+        match Player::new(cache, self.tls.clone(), fade, network, device, silence) {
             Ok(player) => {
                 self.player = Some(player);
+                self.output = output;
             }
             Err(e) => {
                 tracing::error!("Failed to initialize audio player: {}", e);
-                self.error_message = Some(format!("Audio player error: {}", e));
+                self.toasts.error(format!("Audio player error: {}", e));
+            }
+        }
+
+        // Load playback history
+        match crate::history::load() {
+            Ok(history) => self.library.set_history(history),
+            Err(e) => tracing::warn!("Failed to load playback history: {}", e),
+        }
+
+        // Load measured loudness for normalizing untagged tracks
+        if self.config.player.replay_gain != ReplayGainMode::Off {
+            match crate::player::loudness::load() {
+                Ok(loudness) => self.loudness = loudness,
+                Err(e) => tracing::warn!("Failed to load loudness cache: {}", e),
+            }
+        }
+
+        // Load per-song lyrics timing adjustments
+        match crate::lyrics_offsets::load() {
+            Ok(delays) => self.lyrics.delays = delays,
+            Err(e) => tracing::warn!("Failed to load lyrics offsets: {}", e),
+        }
+        self.lyrics.lang = self.config.lyrics.lang.clone();
+
+        // Restore persisted UI state
+        let mut library_view = None;
+        let mut saved_volume = None;
+        match crate::state::load() {
+            Ok(state) => {
+                self.queue.consume = state.consume;
+                self.library_stats = state.library_stats;
+                self.library.sorts = state.sorts;
+                self.library.album_list = state.album_list;
+                self.library.songs_source = state.songs_source;
+                self.library.songs_filter = state.songs_filter;
+                self.now_playing.show_remaining = state.show_remaining;
+                self.queue.visible = state.queue_visible.unwrap_or(self.config.ui.show_queue);
+                self.queue.grouped = state.queue_grouped.unwrap_or(self.config.ui.group_queue);
+                if let Some(width) = state.queue_width {
+                    self.queue.width = width.clamp(SIDE_PANEL_RANGE.0, SIDE_PANEL_RANGE.1);
+                }
+                if let Some(width) = state.lyrics_width {
+                    self.lyrics.width = width.clamp(SIDE_PANEL_RANGE.0, SIDE_PANEL_RANGE.1);
+                }
+                if state.lyrics_lang.is_some() {
+                    self.lyrics.lang = state.lyrics_lang;
+                }
+                self.library.set_hidden(state.hidden);
+                self.library.new_since = state.newest_album;
+                self.library.newest_album = state.newest_album;
+                saved_volume = state.volume;
+                library_view = state.library_view;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load saved state: {}", e);
+                self.toasts
+                    .warn(format!("Failed to load saved state: {}", e));
             }
         }
+        self.update_greeting();
 
-        // Set initial volume from config
-        self.now_playing.volume = self.config.player.volume;
+        // Set initial volume from the last session, or the config
+        let volume = saved_volume
+            .unwrap_or(self.config.player.volume)
+            .min(self.config.player.max_volume);
+        self.now_playing.volume = volume;
         if let Some(player) = &self.player {
-            let _ = player.set_volume(self.config.player.volume as f32 / 100.0);
+            let _ = player.set_volume(volume as f32 / 100.0);
         }
 
-        // Load initial data
-        self.action_tx.send(Action::LoadArtists)?;
-        self.action_tx.send(Action::LoadAlbums)?;
-        self.action_tx.send(Action::LoadPlaylists)?;
-        self.action_tx.send(Action::LoadSongs)?;
-        self.action_tx.send(Action::LoadGenres)?;
-        self.action_tx.send(Action::LoadFavorites)?;
+        if let Some(view) = library_view {
+            self.restore_library_view(view)?;
+        }
+
+        // Load initial data, starting with the tab in view
+        self.load_library();
 
         Ok(())
     }
 
+    /// Connect to the server with the configured credentials, asking for the
+    /// password again if the server rejects it.
+    async fn connect(&mut self) {
+        let server = &self.config.server;
+        let auth = match (&server.api_key, &server.password) {
+            (Some(api_key), _) if !api_key.is_empty() => Auth::from_api_key(api_key),
+            (_, Some(password)) if server.legacy_auth => {
+                Auth::from_legacy_password(&server.username, password)
+            }
+            (_, Some(password)) => Auth::from_password(&server.username, password),
+            _ => return,
+        };
+        let mut client = SubsonicClient::new(&server.url, auth).with_network(
+            Duration::from_secs(server.connect_timeout_secs),
+            Duration::from_secs(server.timeout_secs),
+            server.retries,
+            &self.tls,
+        );
+
+        // Test connection
+        match client.connect().await {
+            Ok(_) => {
+                tracing::info!(
+                    "Connected to server: {} ({} auth)",
+                    self.config.server.url,
+                    client.auth_mode()
+                );
+                if client.uses_legacy_auth() && !self.config.server.legacy_auth {
+                    self.toasts.info(String::from(
+                        "Server doesn't support token auth, using legacy password auth",
+                    ));
+                }
+
+                // Check for OpenSubsonic extensions
+                if let Ok(extensions) = client.get_open_subsonic_extensions().await {
+                    tracing::info!("OpenSubsonic extensions: {:?}", extensions);
+                }
+
+                // Roles decide which actions are offered; the avatar goes
+                // in the tab bar
+                let username = self.config.server.username.clone();
+                if !username.is_empty() {
+                    match client.get_user(&username).await {
+                        Ok(user) => self.user = Some(user),
+                        Err(e) => tracing::warn!("Failed to get account roles: {}", e),
+                    }
+                    let _ = self.action_tx.send(Action::LoadAvatar(username));
+                }
+
+                self.client = Some(client);
+            }
+            Err(e) if e.is_transient() => {
+                // Keep trying, and load the library once it answers
+                tracing::warn!("Server unreachable, will retry: {}", e);
+                self.client = Some(client);
+                self.refresh_on_reconnect = true;
+                self.check_connection();
+            }
+            Err(e @ ApiClientError::WrongCredentials) if self.config.server.password.is_some() => {
+                tracing::error!("Failed to connect to server: {}", e);
+                self.login.open(
+                    &self.config.server.username,
+                    Some(String::from("Wrong username or password")),
+                );
+            }
+            Err(e) => {
+                self.toasts.error(format!("Failed to connect: {}", e));
+                tracing::error!("Failed to connect to server: {}", e);
+            }
+        }
+    }
+
     /// Handle an action and update state.
     pub async fn handle_action(&mut self, action: Action) -> Result<()> {
         match action {
             Action::Quit => {
                 self.should_quit = true;
+                self.send_playlist_move();
+                self.save_state();
+                self.history_writer.finish();
             }
 
             Action::Tick => {
+                // Only what changed on screen asks for a redraw
+                let shown = (
+                    self.connection,
+                    self.now_playing.position,
+                    self.now_playing.state,
+                );
+                let mut redraw = self.toasts.expire(Instant::now());
+                let counting = self.count.get().is_some();
+                if let Some(tab) = self.count.expire(Instant::now(), &self.library.tabs) {
+                    self.action_tx.send(Action::SwitchTab(tab))?;
+                }
+                redraw |= counting && self.count.get().is_none();
+                if self
+                    .playlist_move
+                    .as_ref()
+                    .is_some_and(|(_, at)| *at <= Instant::now())
+                {
+                    self.send_playlist_move();
+                }
+                self.check_connection();
+                self.library.restore_pending_selection();
+                if self.log_viewer.visible {
+                    self.log_viewer.refresh(&Config::log_path());
+                    redraw = true;
+                }
+
                 // Update player progress - collect events first to avoid borrow issues
                 let events: Vec<_> = if let Some(player) = &mut self.player {
                     let mut events = Vec::new();
@@ -199,6 +630,10 @@ impl App {
                     Vec::new()
                 };
 
+                // Progress only matters once the shown second changes
+                redraw |= events
+                    .iter()
+                    .any(|event| !matches!(event, PlayerEvent::Progress { .. }));
                 for event in events {
                     self.handle_player_event(event).await?;
                 }
@@ -211,14 +646,53 @@ impl App {
 
                 // Update lyrics position if visible
                 if self.lyrics.visible {
-                    let position_ms = (self.now_playing.position as u64) * 1000;
-                    self.lyrics.update_position(position_ms);
+                    self.lyrics.update_position(self.audible_position_ms());
+                }
+
+                self.update_visualizer();
+                let buffering = self.now_playing.state == PlayerState::Buffering;
+                if buffering {
+                    self.now_playing.spinner = self.now_playing.spinner.wrapping_add(1);
+                }
+                self.now_playing.marquee.tick();
+                self.queue.marquee.tick();
+
+                // Keep the idle greeting in step with the time of day
+                if self.now_playing.current_song.is_none() {
+                    self.update_greeting();
                 }
 
                 // Check for debounced search
                 if self.search.active && self.search.should_search() {
                     self.perform_search().await?;
+                    redraw = true;
+                }
+
+                // Fetch the next page of the visible song list ahead of the cursor
+                if let Some(list) = self.library.active_paged_list() {
+                    if self.library.wants_page(list) {
+                        self.request_song_page(list);
+                    }
+                }
+                if self.search.active && self.search.wants_song_page() {
+                    self.request_song_page(SongList::SearchSongs);
                 }
+
+                // Animations (visualizer, scrolling titles, lyrics) move on
+                // every tick while playing
+                let playing = self.now_playing.state == PlayerState::Playing;
+                let animated = self.config.ui.visualizer != VisualizerMode::Off
+                    || self.config.ui.marquee
+                    || self.lyrics.visible;
+                self.redraw |= redraw
+                    || (playing && animated)
+                    || buffering
+                    || shown
+                        != (
+                            self.connection,
+                            self.now_playing.position,
+                            self.now_playing.state,
+                        );
             }
 
             Action::Render => {
@@ -235,37 +709,55 @@ impl App {
             Action::NavigateUp => {
                 if self.search.active {
                     self.search.select_previous();
-                } else if self.focus == 0 {
-                    self.library.select_previous();
                 } else {
-                    self.queue.select_previous();
+                    match self.focus {
+                        Focus::Library => {
+                            self.library.unpick_similar();
+                            self.library.select_previous();
+                        }
+                        Focus::Queue => self.queue.select_previous(),
+                        Focus::Lyrics => self.lyrics.scroll_up(),
+                    }
                 }
             }
 
             Action::NavigateDown => {
                 if self.search.active {
                     self.search.select_next();
-                } else if self.focus == 0 {
-                    self.library.select_next();
                 } else {
-                    self.queue.select_next();
+                    match self.focus {
+                        Focus::Library => {
+                            self.library.unpick_similar();
+                            self.library.select_next();
+                        }
+                        Focus::Queue => self.queue.select_next(),
+                        Focus::Lyrics => self.lyrics.scroll_down(),
+                    }
                 }
             }
 
             Action::NavigateLeft => {
                 if self.search.active {
                     self.search.prev_section();
-                } else if self.focus == 1 {
-                    // Moving from queue to library
-                    self.focus = 0;
+                } else if self.focus != Focus::Library {
+                    // Moving from the panel beside back to the library
+                    self.focus = Focus::Library;
                     // If in favorites, start at rightmost section
                     if self.library.tab == Tab::Favorites && self.library.view_depth == 0 {
                         self.library.favorites_section = 2;
                     }
+                    if self.library.tab == Tab::Genres && self.library.view_depth == 1 {
+                        self.library.genre_section = 1;
+                    }
                 } else if self.library.tab == Tab::Favorites && self.library.view_depth == 0 {
                     // In favorites, try to move to previous section
                     self.library.prev_favorites_section();
                     // If already at leftmost, stay there (no wrap)
+                } else if self.library.tab == Tab::Genres
+                    && self.library.view_depth == 1
+                    && self.library.genre_section == 1
+                {
+                    self.library.toggle_genre_section();
                 }
             }
 
@@ -274,180 +766,176 @@ impl App {
                     self.search.next_section();
                 } else if self.library.tab == Tab::Favorites && self.library.view_depth == 0 {
                     // In favorites, try to move to next section
-                    if !self.library.next_favorites_section() && self.queue.visible {
-                        // At rightmost section, move to queue
-                        self.focus = 1;
+                    if !self.library.next_favorites_section() {
+                        // At rightmost section, move to the panel beside
+                        self.focus = self.side_panel().unwrap_or(self.focus);
                     }
-                } else if self.queue.visible {
-                    self.focus = 1;
+                } else if self.library.tab == Tab::Genres
+                    && self.library.view_depth == 1
+                    && self.library.genre_section == 0
+                {
+                    self.library.toggle_genre_section();
+                } else if let Some(panel) = self.side_panel() {
+                    self.focus = panel;
                 }
             }
 
             Action::MouseClick(x, y) => {
                 // Don't handle mouse clicks when overlays are active
-                if self.search.active || self.show_help || self.show_track_info {
+                if self.search.active
+                    || self.help.visible
+                    || self.show_track_info
+                    || self.year_picker.visible
+                    || self.songs_filter.visible
+                    || self.playlist_picker.visible
+                    || self.import_prompt.visible
+                    || self.move_prompt.visible
+                    || self.login.visible
+                    || self.toasts.show_history
+                    || self.log_viewer.visible
+                    || self.server_status.visible
+                    || self.listeners.visible
+                    || self.album_info.visible
+                {
                     return Ok(());
                 }
 
-                // Check if click is on tabs (inside the border, row 1 of the tab area)
-                if y == self.layout.tabs.y + 1 {
-                    // Account for left border (1 char) and calculate based on tab title positions
-                    // Tab format: " Title1 | Title2 | Title3 ..." with divider " | " (3 chars)
-                    let click_x = x.saturating_sub(self.layout.tabs.x + 1); // +1 for left border
-
-                    let tabs = Tab::all();
-                    let mut pos: u16 = 0;
-                    for &tab in tabs {
-                        let title_len = tab.title().len() as u16;
-                        // Each tab takes: space + title + space = title_len + 2, then divider "|" + space
-                        let tab_width = title_len + 2; // " Title "
-
-                        if click_x >= pos && click_x < pos + tab_width {
-                            self.library.tab = tab;
-                            self.library.view_depth = 0;
-                            self.focus = 0;
-                            if tab == Tab::Favorites {
-                                self.library.favorites_section = 0;
-                            }
-                            break;
-                        }
-                        pos += tab_width + 1; // +1 for the "|" divider
+                self.drag = None;
+                let Some((region, area)) = self.hit_regions.at(x, y) else {
+                    return Ok(());
+                };
+                match region {
+                    HitRegion::Tab(tab) => {
+                        self.library.switch_tab(tab);
+                        self.focus = Focus::Library;
                     }
-                }
-                // Check if click is on volume bar
-                else if y == self.layout.volume_bar.y
-                    && x >= self.layout.volume_bar.x
-                    && x < self.layout.volume_bar.x + self.layout.volume_bar.width
-                {
-                    // Calculate volume based on click position within the bar
-                    // Volume bar is "━━━━━━━━━━" - 10 chars directly
-                    let bar_width = self.layout.volume_bar.width;
-                    let click_offset = x.saturating_sub(self.layout.volume_bar.x);
-                    // Map click position to 0-100%
-                    let new_volume =
-                        (((click_offset as u32 + 1) * 100) / bar_width as u32).min(100) as u8;
-                    self.now_playing.volume = new_volume;
-                    if let Some(player) = &self.player {
-                        player.set_volume(new_volume as f32 / 100.0)?;
+                    HitRegion::VolumeBar => {
+                        self.set_volume_from_bar(x, area)?;
+                        self.drag = Some((region, area));
                     }
-                }
-                // Check if click is on playback controls
-                else if y == self.layout.controls.y
-                    && x >= self.layout.controls.x
-                    && x < self.layout.controls.x + self.layout.controls.width
-                {
-                    // Controls layout: "󰒮 ▶ 󰒭 󰒟 󰑖" (nerd font icons are 2 cols each)
-                    // Adjusted positions based on testing:
-                    // 0-2:   prev (󰒮=2 + space=1)
-                    // 3-4:   play/pause (=2)
-                    // 5-6:   next (space=1 + 󰒭=2) - shortened
-                    // 7-8:   shuffle (space=1 + 󰒟=2)
-                    // 9+:    repeat (space=1 + 󰑖=2)
-                    let click_offset = x.saturating_sub(self.layout.controls.x);
-
-                    if click_offset < 3 {
-                        // Previous track
-                        self.action_tx.send(Action::PreviousTrack)?;
-                    } else if click_offset < 5 {
-                        // Play/Pause
-                        self.action_tx.send(Action::PlayPause)?;
-                    } else if click_offset < 7 {
-                        // Next track
-                        self.action_tx.send(Action::NextTrack)?;
-                    } else if click_offset < 9 {
-                        // Toggle shuffle
-                        self.action_tx.send(Action::ToggleShuffle)?;
-                    } else {
-                        // Cycle repeat
-                        self.action_tx.send(Action::CycleRepeat)?;
+                    HitRegion::Control(control) => {
+                        let action = match control {
+                            Control::Previous => Action::PreviousTrack,
+                            Control::PlayPause => Action::PlayPause,
+                            Control::Next => Action::NextTrack,
+                            Control::Shuffle => Action::ToggleShuffle,
+                            Control::Repeat => Action::CycleRepeat,
+                        };
+                        self.action_tx.send(action)?;
                     }
-                }
-                // Check if click is on progress bar (for seeking)
-                else if y >= self.layout.progress_bar.y
-                    && y < self.layout.progress_bar.y + self.layout.progress_bar.height
-                    && x >= self.layout.progress_bar.x
-                    && x < self.layout.progress_bar.x + self.layout.progress_bar.width
-                {
-                    // Calculate seek position based on click position
-                    let click_offset = x.saturating_sub(self.layout.progress_bar.x);
-                    let ratio = click_offset as f64 / self.layout.progress_bar.width as f64;
-                    let seek_pos = (ratio * self.now_playing.duration as f64) as u32;
-                    if let Some(player) = &self.player {
-                        player.seek(std::time::Duration::from_secs(seek_pos as u64))?;
-                        self.now_playing.position = seek_pos;
+                    // Seeks when the button is released, so dragging doesn't seek
+                    // at every step
+                    HitRegion::ProgressBar if self.now_playing.duration > 0 => {
+                        self.now_playing.seek_preview = Some(self.bar_position(x, area));
+                        self.drag = Some((region, area));
                     }
-                }
-                // Check if click is on library
-                else if y >= self.layout.library.y
-                    && y < self.layout.library.y + self.layout.library.height
-                    && x >= self.layout.library.x
-                    && x < self.layout.library.x + self.layout.library.width
-                {
-                    self.focus = 0;
-                    // Calculate which item was clicked (accounting for border and title)
-                    let item_y = y.saturating_sub(self.layout.library.y + 1); // +1 for border
-                    self.library
-                        .active_list_state()
-                        .select(Some(item_y as usize));
-                }
-                // Check if click is on queue
-                else if let Some(queue_area) = self.layout.queue {
-                    if y >= queue_area.y
-                        && y < queue_area.y + queue_area.height
-                        && x >= queue_area.x
-                        && x < queue_area.x + queue_area.width
-                    {
-                        self.focus = 1;
+                    HitRegion::ProgressBar => {}
+                    // Toggles between elapsed and remaining time
+                    HitRegion::TimeDisplay => self.action_tx.send(Action::ToggleRemainingTime)?,
+                    HitRegion::Library => {
+                        self.focus = Focus::Library;
+                        // Calculate which item was clicked (accounting for border and title)
+                        let item_y = y.saturating_sub(area.y + 1); // +1 for border
+                        self.library.select_row(item_y as usize);
+                    }
+                    HitRegion::Queue => {
+                        self.focus = Focus::Queue;
                         // Calculate which item was clicked (accounting for border and title)
-                        let item_y = y.saturating_sub(queue_area.y + 1); // +1 for border
-                        self.queue.list_state.select(Some(item_y as usize));
+                        let item_y = y.saturating_sub(area.y + 1); // +1 for border
+                        self.queue.select_row(item_y as usize);
+                    }
+                    HitRegion::Lyrics => self.focus = Focus::Lyrics,
+                    HitRegion::SortHeader(column) => {
+                        self.focus = Focus::Library;
+                        self.action_tx.send(Action::SortByColumn(column))?;
                     }
+                    HitRegion::NowPlayingTitle => {
+                        if self.now_playing.current_song.is_some() {
+                            self.show_track_info = true;
+                        }
+                    }
+                    HitRegion::Account => self.action_tx.send(Action::ShowServerStatus)?,
                 }
             }
 
             Action::MouseDoubleClick(x, y) => {
+                // The first click on the title opened the track info; the
+                // second goes to the full-screen toggle below instead
+                if self.show_track_info
+                    && matches!(
+                        self.hit_regions.at(x, y),
+                        Some((HitRegion::NowPlayingTitle, _))
+                    )
+                {
+                    self.show_track_info = false;
+                }
+
                 // Don't handle mouse clicks when overlays are active
-                if self.search.active || self.show_help || self.show_track_info {
+                if self.search.active
+                    || self.help.visible
+                    || self.show_track_info
+                    || self.year_picker.visible
+                    || self.songs_filter.visible
+                    || self.playlist_picker.visible
+                    || self.import_prompt.visible
+                    || self.move_prompt.visible
+                    || self.login.visible
+                    || self.toasts.show_history
+                    || self.log_viewer.visible
+                    || self.server_status.visible
+                    || self.listeners.visible
+                    || self.album_info.visible
+                {
                     return Ok(());
                 }
 
-                // Double-click on library item -> select and play
-                if y >= self.layout.library.y
-                    && y < self.layout.library.y + self.layout.library.height
-                    && x >= self.layout.library.x
-                    && x < self.layout.library.x + self.layout.library.width
-                {
-                    self.focus = 0;
-                    let item_y = y.saturating_sub(self.layout.library.y + 1);
-                    self.library
-                        .active_list_state()
-                        .select(Some(item_y as usize));
-                    self.handle_library_select().await?;
+                // Double-click anywhere in the full-screen view, or on the now
+                // playing title, toggles the full-screen view
+                let hit = self.hit_regions.at(x, y);
+                if self.fullscreen || matches!(hit, Some((HitRegion::NowPlayingTitle, _))) {
+                    self.fullscreen = !self.fullscreen;
+                    return Ok(());
                 }
-                // Double-click on queue item -> play that item
-                else if let Some(queue_area) = self.layout.queue {
-                    if y >= queue_area.y
-                        && y < queue_area.y + queue_area.height
-                        && x >= queue_area.x
-                        && x < queue_area.x + queue_area.width
-                    {
-                        self.focus = 1;
-                        let item_y = y.saturating_sub(queue_area.y + 1);
-                        let idx = item_y as usize;
-                        if idx < self.queue.len() {
-                            self.queue.list_state.select(Some(idx));
+                match hit {
+                    // Double-click on library item -> select and play
+                    Some((HitRegion::Library, area)) => {
+                        self.focus = Focus::Library;
+                        let item_y = y.saturating_sub(area.y + 1);
+                        self.library.select_row(item_y as usize);
+                        self.handle_library_select().await?;
+                    }
+                    // Double-click on queue item -> play that item
+                    Some((HitRegion::Queue, area)) => {
+                        self.focus = Focus::Queue;
+                        let item_y = y.saturating_sub(area.y + 1);
+                        if let Some(idx) = self.queue.select_row(item_y as usize) {
                             self.play_from_queue(idx)?;
                         }
                     }
+                    _ => {}
+                }
+            }
+
+            Action::MouseDrag(x, _) => match self.drag {
+                Some((HitRegion::ProgressBar, area)) => {
+                    self.now_playing.seek_preview = Some(self.bar_position(x, area));
+                }
+                Some((HitRegion::VolumeBar, area)) => self.set_volume_from_bar(x, area)?,
+                _ => {}
+            },
+
+            Action::MouseRelease(x, _) => {
+                // No preview left means the song changed during the drag
+                if let Some((HitRegion::ProgressBar, area)) = self.drag.take() {
+                    if self.now_playing.seek_preview.is_some() {
+                        self.seek_to_click(x, area)?;
+                    }
                 }
             }
 
             Action::MouseScroll(delta, x, y) => {
                 // Check if scrolling on volume bar
-                if y == self.layout.volume_bar.y
-                    && x >= self.layout.volume_bar.x
-                    && x < self.layout.volume_bar.x + self.layout.volume_bar.width
-                {
+                if matches!(self.hit_regions.at(x, y), Some((HitRegion::VolumeBar, _))) {
                     // Debounce volume scroll events (ignore if less than 50ms since last scroll)
                     let now = Instant::now();
                     let should_process = self
@@ -456,34 +944,34 @@ impl App {
 
                     if should_process {
                         self.last_volume_scroll = Some(now);
-                        // Adjust volume: scroll up = increase, scroll down = decrease (5% per scroll)
-                        let change = if delta < 0 { 5i16 } else { -5i16 };
-                        let new_volume =
-                            (self.now_playing.volume as i16 + change).clamp(0, 100) as u8;
-                        self.now_playing.volume = new_volume;
-                        if let Some(player) = &self.player {
-                            player.set_volume(new_volume as f32 / 100.0)?;
-                        }
+                        // Adjust volume: scroll up = increase, scroll down = decrease
+                        let step = self.config.player.volume_step;
+                        let volume = if delta < 0 {
+                            self.now_playing.volume.saturating_add(step)
+                        } else {
+                            self.now_playing.volume.saturating_sub(step)
+                        };
+                        self.set_volume(volume)?;
                     }
-                } else if !self.search.active {
+                } else if !self.search.active && !self.fullscreen {
                     // Scroll the focused panel (3 items per scroll event)
                     let scroll_amount = 3;
                     if delta > 0 {
                         // Scroll down
                         for _ in 0..scroll_amount {
-                            if self.focus == 0 {
-                                self.library.select_next();
-                            } else {
-                                self.queue.select_next();
+                            match self.focus {
+                                Focus::Library => self.library.select_next(),
+                                Focus::Queue => self.queue.select_next(),
+                                Focus::Lyrics => self.lyrics.scroll_down(),
                             }
                         }
                     } else {
                         // Scroll up
                         for _ in 0..scroll_amount {
-                            if self.focus == 0 {
-                                self.library.select_previous();
-                            } else {
-                                self.queue.select_previous();
+                            match self.focus {
+                                Focus::Library => self.library.select_previous(),
+                                Focus::Queue => self.queue.select_previous(),
+                                Focus::Lyrics => self.lyrics.scroll_up(),
                             }
                         }
                     }
@@ -493,7 +981,11 @@ impl App {
             Action::Select => {
                 if self.search.active {
                     self.handle_search_select().await?;
-                } else if self.focus == 0 {
+                } else if self.focus == Focus::Library
+                    && self.library.selected_similar_album().is_some()
+                {
+                    self.open_similar_album()?;
+                } else if self.focus == Focus::Library {
                     self.handle_library_select().await?;
                 } else {
                     self.handle_queue_select()?;
@@ -503,41 +995,38 @@ impl App {
             Action::Back => {
                 if self.search.active {
                     self.search.close();
+                } else if self.focus == Focus::Queue && !self.queue.filter.is_empty() {
+                    self.queue.filter.clear();
+                } else if self.focus == Focus::Queue && !self.queue.marked.is_empty() {
+                    self.queue.marked.clear();
+                } else if self.focus == Focus::Library && self.library.unpick_similar() {
+                    // Back to the album's songs
+                } else if self.focus == Focus::Library && !self.library.filter.is_empty() {
+                    self.library.filter.clear();
                 } else if self.library.view_depth > 0 {
                     self.library.go_back();
                 }
             }
 
             Action::SwitchTab(tab) => {
-                self.library.tab = tab;
-                self.library.view_depth = 0;
-                self.focus = 0; // Always focus library when switching tabs
-                                // Reset favorites section to artists when switching to favorites
-                if tab == Tab::Favorites {
-                    self.library.favorites_section = 0;
-                }
+                self.library.switch_tab(tab);
+                self.focus = Focus::Library; // Always focus library when switching tabs
             }
 
+            Action::CountDigit(digit) => self.count.push(digit, Instant::now()),
+
             Action::NextTab => {
-                let next = self.library.tab.next();
-                self.library.tab = next;
-                self.library.view_depth = 0;
-                self.focus = 0;
-                if next == Tab::Favorites {
-                    self.library.favorites_section = 0;
-                }
+                self.library.switch_tab(self.library.step_tab(1));
+                self.focus = Focus::Library;
             }
 
             Action::PrevTab => {
-                let prev = self.library.tab.prev();
-                self.library.tab = prev;
-                self.library.view_depth = 0;
-                self.focus = 0;
-                if prev == Tab::Favorites {
-                    self.library.favorites_section = 0;
-                }
+                self.library.switch_tab(self.library.step_tab(-1));
+                self.focus = Focus::Library;
             }
 
+            Action::CycleFocus(step) => self.focus = self.focus.step(&self.panels(), step),
+
             // Search
             Action::OpenSearch => {
                 self.search.open();
@@ -555,10 +1044,59 @@ impl App {
                 self.search.backspace();
             }
 
+            Action::SearchAppendToQueue | Action::SearchPlayNext => {
+                let next = matches!(action, Action::SearchPlayNext);
+                self.queue_search_selection(next).await?;
+            }
+
             Action::SearchSubmit => {
                 self.perform_search().await?;
             }
 
+            // Quick filter
+            Action::OpenFilter => {
+                if self.focus == Focus::Queue {
+                    self.queue.filtering = true;
+                } else {
+                    self.library.filtering = true;
+                }
+            }
+
+            Action::CloseFilter => {
+                self.queue.filtering = false;
+                self.library.filtering = false;
+            }
+
+            Action::ClearFilter => {
+                if self.focus == Focus::Queue {
+                    self.queue.filter.clear();
+                } else {
+                    self.library.filter.clear();
+                }
+                self.queue.filtering = false;
+                self.library.filtering = false;
+            }
+
+            Action::FilterInput(c) => {
+                if self.focus == Focus::Queue {
+                    self.queue.filter.push(c);
+                    self.queue.refresh_filter();
+                } else {
+                    self.library.filter.push(c);
+                    self.library.refresh_filter();
+                }
+            }
+
+            Action::FilterBackspace => {
+                if self.focus == Focus::Queue {
+                    self.queue.filter.pop();
+                    self.queue.refresh_filter();
+                } else {
+                    self.library.filter.pop();
+                    self.library.refresh_filter();
+                }
+            }
+
             // Playback controls
             Action::PlayPause => {
                 self.toggle_play_pause()?;
@@ -569,6 +1107,7 @@ impl App {
                     player.stop()?;
                 }
                 self.now_playing.clear();
+                self.library.playing_id = None;
             }
 
             Action::NextTrack => {
@@ -596,25 +1135,37 @@ impl App {
             }
 
             Action::VolumeUp => {
-                let new_vol = (self.now_playing.volume as i32 + 5).min(100) as u8;
-                self.now_playing.volume = new_vol;
-                if let Some(player) = &self.player {
-                    player.set_volume(new_vol as f32 / 100.0)?;
-                }
+                let step = self.config.player.volume_step;
+                self.set_volume(self.now_playing.volume.saturating_add(step))?;
             }
 
             Action::VolumeDown => {
-                let new_vol = (self.now_playing.volume as i32 - 5).max(0) as u8;
-                self.now_playing.volume = new_vol;
-                if let Some(player) = &self.player {
-                    player.set_volume(new_vol as f32 / 100.0)?;
-                }
+                let step = self.config.player.volume_step;
+                self.set_volume(self.now_playing.volume.saturating_sub(step))?;
+            }
+
+            Action::VolumeUpFine => {
+                self.set_volume(self.now_playing.volume.saturating_add(1))?;
+            }
+
+            Action::VolumeDownFine => {
+                self.set_volume(self.now_playing.volume.saturating_sub(1))?;
             }
 
+            Action::ToggleMute => match self.unmuted_volume.take() {
+                Some(volume) => self.set_volume(volume)?,
+                None if self.now_playing.volume > 0 => {
+                    let volume = self.now_playing.volume;
+                    self.set_volume(0)?;
+                    self.unmuted_volume = Some(volume);
+                }
+                None => {}
+            },
+
             Action::ToggleShuffle => {
                 self.now_playing.shuffle = !self.now_playing.shuffle;
                 if self.now_playing.shuffle {
-                    self.queue.shuffle();
+                    self.queue.shuffle(&self.config.shuffle);
                 }
             }
 
@@ -622,33 +1173,114 @@ impl App {
                 self.now_playing.repeat = self.now_playing.repeat.next();
             }
 
+            Action::CycleOutput => {
+                let outputs = self.outputs();
+                let next = outputs
+                    .iter()
+                    .position(|output| *output == self.output)
+                    .map_or(0, |index| index + 1);
+                if let Some(output) = outputs.get(next) {
+                    self.switch_output(output.clone())?;
+                } else if self.renderers.is_empty() {
+                    self.toasts
+                        .info(String::from("Looking for renderers on the network…"));
+                    let tx = self.action_tx.clone();
+                    tokio::task::spawn_blocking(move || {
+                        let renderers = upnp::discover().unwrap_or_else(|e| {
+                            tracing::warn!("Renderer discovery failed: {}", e);
+                            Vec::new()
+                        });
+                        let _ = tx.send(Action::RenderersFound(renderers));
+                    });
+                } else {
+                    // Past the last renderer: back to this computer, and look
+                    // for renderers afresh next time
+                    self.renderers.clear();
+                    self.switch_output(Output::Local)?;
+                }
+            }
+
+            Action::RenderersFound(renderers) => {
+                if renderers.is_empty() {
+                    self.toasts
+                        .error(String::from("No renderers found on the network"));
+                } else {
+                    let first = Output::Renderer(renderers[0].clone());
+                    self.renderers = renderers;
+                    self.switch_output(first)?;
+                }
+            }
+
+            Action::ToggleConsume => {
+                self.queue.consume = !self.queue.consume;
+                self.save_state();
+            }
+
+            Action::ToggleRemainingTime => {
+                self.now_playing.show_remaining = !self.now_playing.show_remaining;
+                self.save_state();
+            }
+
+            Action::ToggleQueuePanel => {
+                self.queue.visible = !self.queue.visible;
+                self.keep_focus_on_screen();
+                self.save_state();
+            }
+
+            Action::ToggleQueueGrouping => {
+                self.queue.toggle_grouped();
+                self.save_state();
+            }
+
+            Action::ResizeSidePanel(step) => {
+                // Resize whichever panel is beside the library
+                let width = if self.lyrics.visible {
+                    &mut self.lyrics.width
+                } else if self.queue.visible {
+                    &mut self.queue.width
+                } else {
+                    return Ok(());
+                };
+                *width = width
+                    .saturating_add_signed(step)
+                    .clamp(SIDE_PANEL_RANGE.0, SIDE_PANEL_RANGE.1);
+                self.save_state();
+            }
+
             Action::SetRepeat(mode) => {
                 self.now_playing.repeat = mode;
             }
 
-            Action::SetVolume(vol) => {
-                self.now_playing.volume = vol.min(100);
-                if let Some(player) = &self.player {
-                    player.set_volume(vol as f32 / 100.0)?;
-                }
+            Action::SetVolume(vol) => self.set_volume(vol)?,
+
+            Action::SpeedUp => {
+                self.set_speed(self.now_playing.speed + 0.1)?;
+            }
+
+            Action::SpeedDown => {
+                self.set_speed(self.now_playing.speed - 0.1)?;
+            }
+
+            Action::SetSpeed(speed) => {
+                self.set_speed(speed)?;
             }
 
             Action::SeekTo(pos_secs) => {
                 let duration = self.now_playing.duration;
                 let new_pos = pos_secs.min(duration);
-                self.now_playing.position = new_pos;
+                self.now_playing.set_position(new_pos);
                 if let Some(player) = &self.player {
                     player.seek(Duration::from_secs(new_pos as u64))?;
                 }
             }
 
             // Queue management
-            Action::AddToQueue(song) => {
-                self.queue.add(song);
+            Action::AddToQueue(song, source) => {
+                self.queue.add(song, source);
             }
 
-            Action::AddAlbumToQueue(songs) => {
-                self.queue.add_all(songs);
+            Action::AddAlbumToQueue(songs, source) => {
+                self.queue.add_all(songs, source);
             }
 
             Action::ClearQueue => {
@@ -656,27 +1288,163 @@ impl App {
             }
 
             Action::RemoveSelectedFromQueue => {
-                // Only remove if queue is focused
-                if self.focus == 1 {
+                if self.focus == Focus::Queue {
                     self.queue.remove_selected();
+                } else if self.client.is_some() && self.library.selected_playlist_song().is_some() {
+                    // Remove from the open playlist
+                    if !self.allows(Role::Playlist) {
+                        return Ok(());
+                    }
+                    if let Some(edit) = self.library.remove_playlist_song() {
+                        self.queue_playlist_edit(edit);
+                    }
+                }
+            }
+
+            Action::HideSelected => {
+                let target = if self.focus == Focus::Queue {
+                    self.queue.selected_song().map(|song| HideTarget::Song {
+                        id: song.id.clone(),
+                        title: song.title.clone(),
+                    })
+                } else {
+                    self.library.selected_hide_target()
+                };
+                self.confirm = target.map(Confirm::Hide);
+            }
+
+            Action::UnhideAll => {
+                if !self.library.hidden.is_empty() {
+                    self.confirm = Some(Confirm::UnhideAll);
+                }
+            }
+
+            Action::ConfirmYes => match self.confirm.take() {
+                Some(Confirm::Hide(target)) => {
+                    self.library.hide(target);
+                    let hidden = &self.library.hidden;
+                    self.queue.retain(|song, _| !hidden.hides_song(song));
+                    self.save_state();
+                }
+                Some(Confirm::UnhideAll) => {
+                    self.library.hidden = HiddenItems::default();
+                    self.save_state();
+                    // Hidden items were dropped as lists loaded, so fetch them again
+                    self.action_tx.send(Action::RefreshLibrary)?;
+                }
+                None => {}
+            },
+
+            Action::ConfirmNo => {
+                self.confirm = None;
+            }
+
+            Action::RemoveSourceFromQueue => {
+                if self.focus == Focus::Queue {
+                    self.queue.remove_selected_source();
                 }
             }
 
             Action::AppendToQueue => {
-                self.append_selected_to_queue().await?;
+                if self.focus == Focus::Library && self.library.selected_similar_album().is_some() {
+                    self.queue_similar_album().await;
+                } else {
+                    self.append_selected_to_queue().await?;
+                }
             }
 
             Action::MoveQueueItem(_index, direction) => {
                 // Use current selection instead of passed index
-                if self.focus == 1 {
+                if self.focus == Focus::Queue && !self.queue.marked.is_empty() {
+                    self.queue.step_marked(direction > 0);
+                } else if self.focus == Focus::Queue {
                     if let Some(idx) = self.queue.selected() {
                         self.move_queue_item(idx, direction);
                     }
+                } else if self.client.is_some() && self.library.selected_playlist_song().is_some() {
+                    // Reorder the open playlist
+                    if !self.allows(Role::Playlist) {
+                        return Ok(());
+                    }
+                    if let Some(edit) = self.library.move_playlist_song(direction) {
+                        self.hold_playlist_move(edit);
+                    }
                 }
             }
 
-            Action::RemoveFromQueue(index) => {
-                self.queue.remove(index);
+            Action::ToggleQueueMark => {
+                if self.focus == Focus::Queue {
+                    self.queue.toggle_mark();
+                }
+            }
+
+            Action::OpenMovePrompt => {
+                let rows = self.queue.marked_or_selected();
+                if self.focus == Focus::Queue && !rows.is_empty() {
+                    self.move_prompt.open(rows, self.queue.len());
+                }
+            }
+            Action::CloseMovePrompt => self.move_prompt.close(),
+            Action::MovePromptInput(c) => self.move_prompt.input(c),
+            Action::MovePromptBackspace => {
+                self.move_prompt.position.pop();
+            }
+            Action::MoveQueueItems => {
+                // Leave the prompt open to fix a position out of range
+                if let Some(row) = self.move_prompt.row() {
+                    self.queue.move_rows(&self.move_prompt.rows, row);
+                    self.queue.marked.clear();
+                    self.move_prompt.close();
+                }
+            }
+
+            Action::DedupQueue => {
+                let removed = self.queue.dedup();
+                tracing::info!("Removed {} duplicate songs from the queue", removed);
+            }
+
+            Action::SortQueue(sort) => {
+                self.queue.sort(sort);
+            }
+
+            Action::ShuffleAlbum
+                if self.focus == Focus::Library
+                    && self.library.tab == Tab::Genres
+                    && self.library.view_depth == 1 =>
+            {
+                if let Some(genre) = &self.library.selected_genre {
+                    self.queue_genre_shuffled(genre.value.clone());
+                }
+            }
+
+            Action::PlayAlbum | Action::ShuffleAlbum | Action::QueueAlbum => {
+                if self.focus == Focus::Library && self.library.album_songs_visible() {
+                    let mut songs = self.library.album_songs.clone();
+                    let source = self.library.album_songs_source();
+                    if matches!(action, Action::QueueAlbum) {
+                        self.queue.add_all(songs, source);
+                    } else if !songs.is_empty() {
+                        if matches!(action, Action::ShuffleAlbum) {
+                            shuffle::shuffle(&mut songs, &self.config.shuffle);
+                        }
+                        self.queue.clear();
+                        self.queue.add_all(songs, source);
+                        self.play_from_queue(0)?;
+                    }
+                }
+            }
+
+            Action::ToggleAlbumStar => {
+                if self.focus == Focus::Library
+                    && self.library.album_songs_visible()
+                    && self.library.tab != Tab::Playlists
+                {
+                    self.toggle_album_star();
+                }
+            }
+
+            Action::RemoveFromQueue(index) => {
+                self.queue.remove(index);
             }
 
             Action::PlayFromQueue(index) => {
@@ -685,23 +1453,281 @@ impl App {
 
             // Library loading
             Action::LoadArtists => {
-                self.load_artists().await?;
+                self.load_artists(Duration::ZERO);
             }
 
             Action::LoadAlbums => {
-                self.load_albums().await?;
+                self.load_albums(Duration::ZERO);
             }
 
             Action::LoadAlbum(id) => {
                 self.load_album(&id).await?;
             }
 
+            Action::CycleListSource => {
+                if self.focus == Focus::Library
+                    && self.library.tab == Tab::Albums
+                    && self.library.view_depth == 0
+                {
+                    self.library.album_list = self.library.album_list.next();
+                    self.library.filter.clear();
+                    self.library.albums_state.select(None);
+                    self.save_state();
+                    self.action_tx.send(Action::LoadAlbums)?;
+                } else if self.focus == Focus::Library && self.library.tab == Tab::Songs {
+                    self.library.songs_source = self.library.songs_source.next();
+                    self.reload_songs();
+                }
+            }
+
+            Action::OpenPlaylistPicker => {
+                if self.allows(Role::Playlist) {
+                    self.open_playlist_picker().await?;
+                }
+            }
+            Action::ExportM3u => {
+                if self.allows(Role::Download) {
+                    self.export_m3u().await?;
+                }
+            }
+            Action::OpenImportPrompt => {
+                if self.allows(Role::Playlist) {
+                    match self.config.m3u_dir() {
+                        Ok(dir) => self.import_prompt.open(&dir),
+                        Err(_) => self.import_prompt.open(Path::new("")),
+                    }
+                }
+            }
+            Action::CloseImportPrompt => self.import_prompt.close(),
+            Action::ImportPromptInput(c) => self.import_prompt.path.push(c),
+            Action::ImportPromptBackspace => {
+                self.import_prompt.path.pop();
+            }
+            Action::ImportM3u => self.import_m3u(),
+            Action::M3uMatched {
+                name,
+                song_ids,
+                unmatched,
+            } => {
+                for line in &unmatched {
+                    tracing::warn!("Not found in the library: {}", line);
+                }
+                if !unmatched.is_empty() {
+                    let mut shown: Vec<&str> =
+                        unmatched.iter().take(3).map(String::as_str).collect();
+                    if unmatched.len() > shown.len() {
+                        shown.push("…");
+                    }
+                    self.toasts.warn(format!(
+                        "{} of {} tracks in {} not found: {}",
+                        unmatched.len(),
+                        unmatched.len() + song_ids.len(),
+                        name,
+                        shown.join(", ")
+                    ));
+                }
+                if !song_ids.is_empty() {
+                    let label = match song_ids.len() {
+                        1 => format!("1 song from {}", name),
+                        n => format!("{} songs from {}", n, name),
+                    };
+                    self.playlist_picker
+                        .open(label, song_ids, &self.library.playlists);
+                    self.playlist_picker.start_new(name);
+                }
+            }
+            Action::PlaylistPickerBack => {
+                if self.playlist_picker.new_name.is_some() {
+                    self.playlist_picker.new_name = None;
+                } else {
+                    self.playlist_picker.close();
+                }
+            }
+            Action::PlaylistPickerNext => self.playlist_picker.select_next(),
+            Action::PlaylistPickerPrev => self.playlist_picker.select_prev(),
+            Action::PlaylistPickerInput(c) => {
+                if let Some(name) = self.playlist_picker.new_name.as_mut() {
+                    name.push(c);
+                }
+            }
+            Action::PlaylistPickerBackspace => {
+                if let Some(name) = self.playlist_picker.new_name.as_mut() {
+                    name.pop();
+                }
+            }
+            Action::PlaylistPickerChoose => {
+                if let Some(choice) = self.playlist_picker.choose() {
+                    let song_ids = std::mem::take(&mut self.playlist_picker.song_ids);
+                    self.playlist_picker.close();
+                    self.add_to_playlist(choice, song_ids).await?;
+                }
+            }
+
+            Action::CloseSongsFilter => self.songs_filter.visible = false,
+            Action::SongsFilterNext => self.songs_filter.select_next(),
+            Action::SongsFilterPrev => self.songs_filter.select_prev(),
+            Action::SongsFilterInput(c) => self.songs_filter.push(c),
+            Action::SongsFilterBackspace => self.songs_filter.pop(),
+
+            Action::SongsFilterCycle => {
+                if self.songs_filter.row == 0 {
+                    self.songs_filter.source = self.songs_filter.source.next();
+                }
+            }
+
+            Action::SongsFilterApply => {
+                if let Some(mut filter) = self.songs_filter.filter() {
+                    // Genre names are matched exactly by the server, so fix up the case
+                    if let Some(genre) = &mut filter.genre {
+                        if let Some(known) = self
+                            .library
+                            .genres
+                            .iter()
+                            .find(|g| g.value.eq_ignore_ascii_case(genre))
+                        {
+                            *genre = known.value.clone();
+                        }
+                    }
+                    self.songs_filter.visible = false;
+                    self.library.songs_source = self.songs_filter.source;
+                    self.library.songs_filter = filter;
+                    self.reload_songs();
+                }
+            }
+
+            Action::OpenYearPicker => {
+                if self.focus == Focus::Library
+                    && self.library.tab == Tab::Albums
+                    && self.library.view_depth == 0
+                {
+                    self.year_picker.open();
+                } else if self.focus == Focus::Library && self.library.tab == Tab::Songs {
+                    self.songs_filter
+                        .open(self.library.songs_source, &self.library.songs_filter);
+                }
+            }
+
+            Action::CloseYearPicker => {
+                if self.year_picker.editing {
+                    self.year_picker.editing = false;
+                } else {
+                    self.year_picker.close();
+                }
+            }
+
+            Action::YearPickerNext => self.year_picker.select_next(),
+            Action::YearPickerPrev => self.year_picker.select_prev(),
+
+            Action::YearPickerInput(c) => {
+                if c.is_ascii_digit() || c == '-' || c == ' ' {
+                    self.year_picker.input.push(c);
+                }
+            }
+
+            Action::YearPickerBackspace => {
+                self.year_picker.input.pop();
+            }
+
+            Action::YearPickerSelect => {
+                if self.year_picker.custom_selected() && !self.year_picker.editing {
+                    self.year_picker.editing = true;
+                } else if let Some((from, to)) = self.year_picker.selected_range() {
+                    self.year_picker.close();
+                    self.library.album_list = AlbumList::ByYear { from, to };
+                    self.library.filter.clear();
+                    self.library.albums_state.select(None);
+                    self.save_state();
+                    self.action_tx.send(Action::LoadAlbums)?;
+                }
+            }
+
+            Action::RatePlayingTrack(rating) => {
+                self.rate_playing_track(rating).await?;
+            }
+
+            Action::Sleeping => {
+                tracing::info!("System going to sleep");
+                self.paused_for_sleep = self.now_playing.state == PlayerState::Playing;
+                if self.paused_for_sleep {
+                    self.toggle_play_pause()?;
+                }
+            }
+            Action::Woke => self.woke()?,
+
+            Action::OpenExternal(target) => {
+                if self.allows(Role::Stream) {
+                    self.open_external(target)?;
+                }
+            }
+
+            Action::CopyTrack(target) => {
+                // A stream URL hands out the file itself
+                if target == CopyTarget::StreamUrl && !self.allows(Role::Download) {
+                    return Ok(());
+                }
+                let Some(song) = &self.now_playing.current_song else {
+                    return Ok(());
+                };
+                let text = match target {
+                    CopyTarget::ArtistTitle => {
+                        format!("{} – {}", song.display_artist(), song.title)
+                    }
+                    // Clipboard managers keep what's copied, so the URL
+                    // goes without the credentials
+                    CopyTarget::StreamUrl => match &self.client {
+                        Some(client) => client.public_stream_url(&song.id),
+                        None => return Ok(()),
+                    },
+                    CopyTarget::WebUrl => match &self.client {
+                        Some(client) => client.web_url(song.album_id.as_deref()),
+                        None => return Ok(()),
+                    },
+                };
+                match self.clipboard.set_text(text) {
+                    Ok(()) => self.toasts.info(match target {
+                        CopyTarget::ArtistTitle => "Copied artist and title",
+                        CopyTarget::StreamUrl => {
+                            "Copied stream URL (without credentials, so it needs signing in)"
+                        }
+                        CopyTarget::WebUrl => "Copied web URL",
+                    }),
+                    Err(e) => self.toasts.error(format!("Failed to copy: {}", e)),
+                }
+            }
+
+            Action::GoToPlayingAlbum | Action::GoToPlayingArtist => {
+                let song = self.now_playing.current_song.as_ref();
+                let target = if matches!(action, Action::GoToPlayingAlbum) {
+                    song.and_then(|s| s.album_id.clone())
+                        .map(|id| (Tab::Albums, Action::LoadAlbum(id)))
+                } else {
+                    song.and_then(|s| s.artist_id.clone())
+                        .map(|id| (Tab::Artists, Action::LoadArtist(id)))
+                };
+
+                if let Some((tab, load)) = target {
+                    self.show_track_info = false;
+                    self.library.switch_tab(tab);
+                    self.focus = Focus::Library;
+                    self.library.loading = true;
+                    self.action_tx.send(load)?;
+                }
+            }
+
+            Action::GoToRandomAlbum => {
+                self.go_to_random_album().await?;
+            }
+
+            Action::QueueRandomSongs => {
+                self.queue_random_songs().await?;
+            }
+
             Action::LoadArtist(id) => {
                 self.load_artist(&id).await?;
             }
 
             Action::LoadPlaylists => {
-                self.load_playlists().await?;
+                self.load_playlists(Duration::ZERO);
             }
 
             Action::LoadPlaylist(id) => {
@@ -709,11 +1735,11 @@ impl App {
             }
 
             Action::LoadSongs => {
-                self.load_songs().await?;
+                self.load_songs();
             }
 
             Action::LoadGenres => {
-                self.load_genres().await?;
+                self.load_genres(Duration::ZERO);
             }
 
             Action::LoadGenreAlbums(genre) => {
@@ -721,32 +1747,89 @@ impl App {
             }
 
             Action::LoadFavorites => {
-                self.load_favorites().await?;
+                self.load_favorites(Duration::ZERO);
+            }
+
+            Action::LoginInput(c) => self.login.password.push(c),
+            Action::LoginBackspace => {
+                self.login.password.pop();
+            }
+            Action::LoginSubmit => {
+                if self.login.password.is_empty() {
+                    return Ok(());
+                }
+                self.config.server.password = Some(std::mem::take(&mut self.login.password));
+                self.login.close();
+                self.connect().await;
+                if self.client.is_some() && !self.refresh_on_reconnect {
+                    self.action_tx.send(Action::RefreshLibrary)?;
+                }
+            }
+            Action::LoginCancel => {
+                self.login.close();
+                self.toasts
+                    .warn(String::from("Not connected: no password given"));
             }
 
             Action::RefreshLibrary => {
-                self.action_tx.send(Action::LoadArtists)?;
-                self.action_tx.send(Action::LoadAlbums)?;
-                self.action_tx.send(Action::LoadPlaylists)?;
-                self.action_tx.send(Action::LoadSongs)?;
-                self.action_tx.send(Action::LoadGenres)?;
-                self.action_tx.send(Action::LoadFavorites)?;
+                // Smart playlists pick from a fresh list next time
+                self.library_songs = None;
+                self.compilations = None;
+                smart::clear_library();
+                self.load_library();
+            }
+
+            Action::LibraryLoadFailed(tab, msg) => {
+                self.library.loading_tabs.remove(&tab);
+                tracing::error!("Failed to load {}: {}", tab.title(), msg);
+                self.toasts.error(format!(
+                    "Failed to load {}: {}",
+                    tab.title().to_lowercase(),
+                    msg
+                ));
             }
 
             // API responses (these are typically sent from async tasks)
-            Action::ArtistsLoaded(artists) => {
-                self.library.set_artists(artists);
-                self.library.loading = false;
+            Action::ArtistsLoaded(index) => {
+                let albums = index
+                    .iter()
+                    .flat_map(|group| &group.artist)
+                    .map(|a| a.album_count.unwrap_or(0).max(0) as u32)
+                    .sum();
+                self.library.set_artist_index(index);
+                self.library.loading_tabs.remove(&Tab::Artists);
+                if albums != self.library_stats.albums {
+                    self.library_stats.albums = albums;
+                    self.update_greeting();
+                    self.save_state();
+                }
             }
 
             Action::AlbumsLoaded(albums) => {
                 self.library.set_albums(albums);
-                self.library.loading = false;
+                self.library.loading_tabs.remove(&Tab::Albums);
+            }
+
+            Action::CompilationsLoaded(albums) => {
+                self.compilations = Some(albums.clone());
+                self.action_tx.send(Action::AlbumsLoaded(albums))?;
             }
 
             Action::AlbumLoaded(album, songs) => {
+                self.load_similar_albums(&album);
                 self.library.enter_album(album, songs);
                 self.library.loading = false;
+                self.load_library_album_art()?;
+            }
+
+            Action::SimilarAlbumsLoaded(id, albums) => {
+                self.library.set_similar_albums(id, albums);
+            }
+
+            Action::StepSimilarAlbum(delta) => {
+                if self.focus == Focus::Library {
+                    self.library.step_similar(delta);
+                }
             }
 
             Action::ArtistLoaded(artist, albums) => {
@@ -754,9 +1837,10 @@ impl App {
                 self.library.loading = false;
             }
 
-            Action::PlaylistsLoaded(playlists) => {
+            Action::PlaylistsLoaded(mut playlists) => {
+                playlists.splice(0..0, smart::entries(&self.config.smart_playlists));
                 self.library.set_playlists(playlists);
-                self.library.loading = false;
+                self.library.loading_tabs.remove(&Tab::Playlists);
             }
 
             Action::PlaylistLoaded(playlist, songs) => {
@@ -784,16 +1868,86 @@ impl App {
                     songs,
                 );
                 self.library.loading = false;
+                self.load_library_album_art()?;
             }
 
-            Action::SongsLoaded(songs) => {
-                self.library.set_songs(songs);
-                self.library.loading = false;
+            Action::LibrarySongsLoaded(songs) => {
+                self.fetching_library_songs = false;
+                self.library_songs = Some(songs);
+                if let Some(id) = self.smart_playlist_waiting.take() {
+                    self.load_playlist(&id).await?;
+                }
+            }
+
+            Action::LibrarySongsFailed(msg) => {
+                self.fetching_library_songs = false;
+                if self.smart_playlist_waiting.take().is_some() {
+                    self.library.loading = false;
+                }
+                self.toasts
+                    .error(format!("Failed to load the library's songs: {}", msg));
+            }
+
+            Action::SongPageLoaded {
+                list,
+                offset,
+                songs,
+            } => {
+                if list == SongList::SearchSongs {
+                    self.search
+                        .add_song_page(offset, songs, &self.library.hidden);
+                } else {
+                    self.library.add_song_page(list, offset, songs);
+                }
+                if list == SongList::Songs && offset == 0 {
+                    self.library.loading_tabs.remove(&Tab::Songs);
+                }
+            }
+
+            Action::PlaylistEditDone(error) => {
+                let Some(edit) = self.playlist_edits.pop_front() else {
+                    return Ok(());
+                };
+                match error {
+                    None if self.playlist_edits.is_empty() => {
+                        // Update the song counts
+                        self.action_tx.send(Action::LoadPlaylists)?;
+                    }
+                    None => self.send_playlist_edit(),
+                    Some(e) => {
+                        // The changes after it were made on top of it
+                        self.playlist_edits.clear();
+                        self.playlist_move = None;
+                        self.library.undo_playlist_edit(edit);
+                        self.toasts
+                            .error(format!("Failed to update playlist: {}", e));
+                    }
+                }
+            }
+
+            Action::SongPageFailed(list, msg) => {
+                if let Some(pages) = self.song_pages_mut(list) {
+                    pages.page_failed();
+                }
+                if list == SongList::Songs {
+                    self.library.loading_tabs.remove(&Tab::Songs);
+                }
+                tracing::error!("Failed to load songs: {}", msg);
+                self.toasts.error(format!("Failed to load songs: {}", msg));
             }
 
             Action::GenresLoaded(genres) => {
+                let songs = genres
+                    .iter()
+                    .map(|g| g.song_count.unwrap_or(0).max(0) as u32)
+                    .sum();
                 self.library.set_genres(genres);
-                self.library.loading = false;
+                self.library.loading_tabs.remove(&Tab::Genres);
+                if songs != self.library_stats.songs {
+                    self.library_stats.songs = songs;
+                    self.update_greeting();
+                    self.save_state();
+                }
             }
 
             Action::GenreAlbumsLoaded(genre_name, albums) => {
@@ -811,6 +1965,15 @@ impl App {
                     });
                 self.library.enter_genre(genre, albums);
                 self.library.loading = false;
+                self.request_song_page(SongList::GenreSongs);
+            }
+
+            Action::GenreSongsFetched(genre, mut songs) => {
+                let hidden = &self.library.hidden;
+                songs.retain(|song| !hidden.hides_song(song));
+                shuffle::shuffle(&mut songs, &self.config.shuffle);
+                self.queue
+                    .add_all(songs, QueueSource::Genre { name: genre });
             }
 
             Action::FavoritesLoaded {
@@ -819,7 +1982,7 @@ impl App {
                 songs,
             } => {
                 self.library.set_favorites(artists, albums, songs);
-                self.library.loading = false;
+                self.library.loading_tabs.remove(&Tab::Favorites);
             }
 
             Action::SearchResults {
@@ -827,12 +1990,51 @@ impl App {
                 albums,
                 songs,
             } => {
-                self.search.set_results(artists, albums, songs);
+                let counts = [artists.len(), albums.len()];
+                let fetched = songs.len();
+                let hidden = &self.library.hidden;
+                let albums = albums.into_iter().filter(|a| !hidden.hides_album(a));
+                let songs = songs.into_iter().filter(|s| !hidden.hides_song(s));
+                // search3 doesn't cover playlists, so match the loaded ones by name
+                let query = self.search.last_searched_query().to_lowercase();
+                let playlists = self
+                    .library
+                    .playlists
+                    .iter()
+                    .filter(|p| p.matches_filter(&query))
+                    .cloned();
+                self.search.set_results(
+                    artists,
+                    albums.collect(),
+                    songs.collect(),
+                    playlists.collect(),
+                );
+                for (section, count) in counts.into_iter().enumerate() {
+                    self.search.page_fetched(section, count);
+                }
+                // Further song results come through the shared paged source
+                let query = self.search.last_searched_query().to_string();
+                let mut pages = PagedSongs::new(Arc::new(SearchSongs { query }));
+                pages.page_loaded(0, fetched, self.search.songs.len());
+                self.search.songs_pages = Some(pages);
             }
 
             // Media annotation
-            Action::ToggleStar => {
-                self.toggle_star().await?;
+            Action::ToggleStar => self.toggle_star(),
+
+            Action::StarFailed(before, e) => {
+                let (verb, kind) = match &before {
+                    Starred::Song(song) if song.starred.is_some() => ("unstar", "song"),
+                    Starred::Song(_) => ("star", "song"),
+                    Starred::Album(album) if album.starred.is_some() => ("unstar", "album"),
+                    Starred::Album(_) => ("star", "album"),
+                };
+                match &before {
+                    Starred::Song(song) => self.show_song_star(song),
+                    Starred::Album(album) => self.show_album_star(album),
+                }
+                self.toasts
+                    .error(format!("Failed to {} {}: {}", verb, kind, e));
             }
 
             Action::Scrobble => {
@@ -842,6 +2044,11 @@ impl App {
             // Lyrics
             Action::ToggleLyrics => {
                 self.lyrics.toggle();
+                // The panel takes the keys as it opens
+                if self.lyrics.visible {
+                    self.focus = Focus::Lyrics;
+                }
+                self.keep_focus_on_screen();
                 // Load lyrics if becoming visible and we have a current song
                 if self.lyrics.visible {
                     if let Some(song) = &self.now_playing.current_song {
@@ -859,13 +2066,50 @@ impl App {
                 self.load_lyrics(&song_id).await?;
             }
 
+            Action::LyricsScrollUp => self.lyrics.scroll_up(),
+            Action::LyricsScrollDown => self.lyrics.scroll_down(),
+            Action::LyricsFollow => self.lyrics.browsing = false,
+
+            Action::CycleLyricsVersion => {
+                if self.lyrics.cycle_version().is_some() {
+                    self.lyrics.update_position(self.audible_position_ms());
+                    self.save_state();
+                }
+            }
+
+            Action::LyricsSeekToLine => {
+                if let Some(position_ms) = self.lyrics.selected_line_position() {
+                    let position_ms = position_ms.min(self.now_playing.duration as u64 * 1000);
+                    self.now_playing.position = (position_ms / 1000) as u32;
+                    self.now_playing.position_ms = position_ms;
+                    if let Some(player) = &self.player {
+                        player.seek(Duration::from_millis(position_ms))?;
+                    }
+                    self.lyrics.browsing = false;
+                    self.lyrics.update_position(self.audible_position_ms());
+                }
+            }
+
+            Action::LyricsDelay(delta_ms) => {
+                if self.lyrics.nudge_delay(delta_ms) {
+                    self.lyrics.update_position(self.audible_position_ms());
+                    if let Err(e) = crate::lyrics_offsets::save(&self.lyrics.delays) {
+                        tracing::warn!("Failed to save lyrics offsets: {}", e);
+                    }
+                }
+            }
+
+            Action::ExportLyrics => {
+                self.export_lyrics();
+            }
+
             Action::LyricsLoaded(song_id, lyrics) => {
                 self.lyrics.set_lyrics(song_id, lyrics);
             }
 
             // Navigation enhancements
             Action::JumpToTop => {
-                if self.focus == 0 {
+                if self.focus == Focus::Library {
                     self.library.jump_to_top();
                 } else {
                     self.queue.jump_to_top();
@@ -873,13 +2117,55 @@ impl App {
             }
 
             Action::JumpToBottom => {
-                if self.focus == 0 {
+                if self.focus == Focus::Library {
                     self.library.jump_to_bottom();
                 } else {
                     self.queue.jump_to_bottom();
                 }
             }
 
+            Action::CycleSort => {
+                if self.focus == Focus::Library {
+                    if let Some(reload) = self.library.cycle_sort() {
+                        if reload {
+                            // Server order is only known by fetching the list again
+                            let load = match self.library.tab {
+                                Tab::Artists => Action::LoadArtists,
+                                Tab::Albums => Action::LoadAlbums,
+                                Tab::Songs => Action::LoadSongs,
+                                _ => Action::LoadPlaylists,
+                            };
+                            self.action_tx.send(load)?;
+                        }
+                        self.save_state();
+                    }
+                }
+            }
+
+            Action::ReverseSort => {
+                if self.focus == Focus::Library && self.library.reverse_sort() {
+                    self.save_state();
+                }
+            }
+
+            Action::SortByColumn(column) => {
+                if self.library.sort_by_column(column) {
+                    self.save_state();
+                }
+            }
+
+            Action::ToggleGroup => match self.focus {
+                Focus::Library => self.library.toggle_artist_group(),
+                Focus::Queue => self.queue.toggle_album_group(),
+                Focus::Lyrics => {}
+            },
+
+            Action::ToggleGroups => match self.focus {
+                Focus::Library => self.library.toggle_artist_groups(),
+                Focus::Queue => self.queue.toggle_album_groups(),
+                Focus::Lyrics => {}
+            },
+
             Action::JumpToCurrentTrack => {
                 self.queue.jump_to_current();
             }
@@ -887,7 +2173,7 @@ impl App {
             Action::ScrollHalfPageDown => {
                 // Use a default page size of 20 lines
                 const PAGE_SIZE: usize = 20;
-                if self.focus == 0 {
+                if self.focus == Focus::Library {
                     self.library.scroll_half_page_down(PAGE_SIZE);
                 } else {
                     self.queue.scroll_half_page_down(PAGE_SIZE);
@@ -896,7 +2182,7 @@ impl App {
 
             Action::ScrollHalfPageUp => {
                 const PAGE_SIZE: usize = 20;
-                if self.focus == 0 {
+                if self.focus == Focus::Library {
                     self.library.scroll_half_page_up(PAGE_SIZE);
                 } else {
                     self.queue.scroll_half_page_up(PAGE_SIZE);
@@ -904,16 +2190,42 @@ impl App {
             }
 
             // Overlays
-            Action::ShowHelp => {
-                self.show_help = true;
+            Action::ShowHelp => self.help.open(),
+            Action::HideHelp => self.help.close(),
+            Action::HelpScroll(delta) => self.help.scroll_by(delta),
+            Action::HelpPage(delta) => self.help.scroll_pages(delta),
+            Action::HelpTop => self.help.scroll = 0,
+            Action::HelpBottom => self.help.scroll = usize::MAX,
+            Action::HelpFilterStart => self.help.filtering = true,
+            Action::HelpFilterInput(c) => {
+                self.help.filter.push(c);
+                self.help.scroll = 0;
             }
-
-            Action::HideHelp => {
-                self.show_help = false;
+            Action::HelpFilterBackspace => {
+                self.help.filter.pop();
+                self.help.scroll = 0;
+            }
+            Action::HelpFilterDone => self.help.filtering = false,
+            Action::HelpFilterClear => {
+                self.help.filtering = false;
+                self.help.filter.clear();
+                self.help.scroll = 0;
             }
 
             Action::ShowTrackInfo => {
-                self.show_track_info = true;
+                // While an album is open, show the album's info instead
+                if self.focus == Focus::Library
+                    && self.library.album_songs_visible()
+                    && self.library.tab != Tab::Playlists
+                {
+                    self.show_album_info();
+                } else {
+                    self.show_track_info = true;
+                }
+            }
+
+            Action::ToggleFullscreen => {
+                self.fullscreen = !self.fullscreen;
             }
 
             Action::HideTrackInfo => {
@@ -922,19 +2234,44 @@ impl App {
 
             // Album art loading
             Action::LoadAlbumArt(id) => {
-                self.load_album_art(&id).await?;
+                self.load_album_art(id);
             }
 
-            Action::AlbumArtLoaded(id, data) => {
-                // Only apply if it matches the current song's cover art
+            Action::AlbumArtLoaded(id, image) => {
+                self.album_art_loads.remove(&id);
+                let Some(image) = image else {
+                    return Ok(());
+                };
+                // Only apply where the cover is still shown: the album
+                // header, and the current song
+                if self.library.album_art_needed() == Some(id.as_str()) {
+                    self.library.set_album_art(
+                        &id,
+                        (*image).clone(),
+                        self.now_playing.picker.as_ref(),
+                    );
+                }
                 if self.now_playing.album_art_id.as_deref() == Some(&id) {
-                    self.now_playing.set_album_art(&data);
+                    self.now_playing.set_album_art(*image);
+                }
+            }
+
+            // User avatars
+            Action::LoadAvatar(username) => {
+                if self.avatars.start_fetch(&username) {
+                    self.load_avatar(username).await?;
                 }
             }
 
+            Action::AvatarLoaded(username, data) => {
+                self.avatars
+                    .insert(username, &data, self.now_playing.picker.as_ref());
+            }
+
             // Player events
             Action::PlayerProgress(progress) => {
-                self.now_playing.position = (progress * self.now_playing.duration as f64) as u32;
+                self.now_playing
+                    .set_position((progress * self.now_playing.duration as f64) as u32);
             }
 
             Action::PlayerStateChanged(state) => {
@@ -945,13 +2282,144 @@ impl App {
                 self.handle_track_ended()?;
             }
 
-            // Errors
+            // Notifications
             Action::Error(msg) => {
-                self.error_message = Some(msg);
+                self.toasts.error(msg);
+            }
+
+            Action::DismissToasts => {
+                self.toasts.dismiss();
+            }
+
+            Action::ShowMessages => {
+                self.toasts.open_history();
+            }
+
+            Action::HideMessages => {
+                self.toasts.show_history = false;
+            }
+
+            Action::MessagesNext => self.toasts.history_next(),
+            Action::MessagesPrev => self.toasts.history_prev(),
+
+            // Server status popup
+            Action::ShowServerStatus => {
+                self.server_status.visible = true;
+                self.refresh_server_status()?;
+            }
+            Action::HideServerStatus => self.server_status.visible = false,
+
+            Action::ShowListeners => {
+                self.listeners.visible = true;
+                self.refresh_listeners();
+            }
+            Action::HideListeners => self.listeners.visible = false,
+            Action::RefreshListeners => self.refresh_listeners(),
+            Action::ListenersLoaded(mut entries) => {
+                // Leave out this player's own song
+                let username = &self.config.server.username;
+                let playing = self.now_playing.current_song.as_ref().map(|s| &s.id);
+                entries.retain(|e| !(&e.username == username && Some(&e.song.id) == playing));
+                for entry in &entries {
+                    self.action_tx
+                        .send(Action::LoadAvatar(entry.username.clone()))?;
+                }
+                self.listeners.set_entries(entries);
+            }
+            Action::ListenersFailed(e) => {
+                self.listeners.loading = false;
+                self.listeners.error = Some(e);
+            }
+            Action::HideAlbumInfo => self.album_info.visible = false,
+            Action::ScrollAlbumInfo(lines) => {
+                if lines > 0 {
+                    self.album_info.scroll_down();
+                } else {
+                    self.album_info.scroll_up();
+                }
+            }
+            Action::AlbumInfoLoaded(id, info) => {
+                if self.album_info.album_id() == Some(id.as_str()) {
+                    self.album_info.loading = false;
+                    self.album_info.info = Some(info);
+                }
+            }
+            Action::AlbumInfoFailed(id, e) => {
+                if self.album_info.album_id() == Some(id.as_str()) {
+                    self.album_info.loading = false;
+                    self.album_info.error = Some(e);
+                }
+            }
+            Action::AlbumInfoCover(id, image) => {
+                if let (Some(image), Some(picker)) = (image, &self.now_playing.picker) {
+                    if self.album_info.album_id() == Some(id.as_str()) {
+                        self.album_info.cover = Some(picker.new_resize_protocol(*image));
+                    }
+                }
+            }
+            Action::ListenersNext => self.listeners.select_next(),
+            Action::ListenersPrev => self.listeners.select_prev(),
+            Action::ListenersGoToAlbum => {
+                let album_id = self
+                    .listeners
+                    .selected()
+                    .and_then(|e| e.song.album_id.clone());
+                if let Some(id) = album_id {
+                    self.listeners.visible = false;
+                    self.library.switch_tab(Tab::Albums);
+                    self.focus = Focus::Library;
+                    self.library.loading = true;
+                    self.action_tx.send(Action::LoadAlbum(id))?;
+                }
+            }
+            Action::ListenersQueue => {
+                if let Some(entry) = self.listeners.selected().cloned() {
+                    self.toasts
+                        .info(format!("Added {} to the queue", entry.song.title));
+                    self.queue.add(
+                        entry.song,
+                        QueueSource::Listener {
+                            username: entry.username,
+                        },
+                    );
+                }
+            }
+            Action::RefreshServerStatus => self.refresh_server_status()?,
+            Action::ServerStatusLoaded(status) => {
+                self.server_status.loading = false;
+                self.server_status.error = None;
+                if let Some(user) = &status.user {
+                    self.user = Some(user.clone());
+                }
+                self.server_status.status = Some(*status);
+            }
+            Action::ServerStatusFailed(e) => {
+                self.server_status.loading = false;
+                self.server_status.error = Some(e);
             }
 
-            Action::ClearError => {
-                self.error_message = None;
+            // Log viewer
+            Action::ShowLog => self.log_viewer.open(&Config::log_path()),
+            Action::HideLog => self.log_viewer.close(),
+            Action::LogNext => self.log_viewer.select_next(),
+            Action::LogPrev => self.log_viewer.select_prev(),
+            Action::LogTop => self.log_viewer.select_first(),
+            Action::LogBottom => self.log_viewer.select_last(),
+            Action::LogCycleLevel => self.log_viewer.cycle_level(),
+            Action::LogSearchStart => self.log_viewer.searching = true,
+            Action::LogSearchInput(c) => {
+                self.log_viewer.search.push(c);
+                self.log_viewer.select_last();
+            }
+            Action::LogSearchBackspace => {
+                self.log_viewer.search.pop();
+                self.log_viewer.select_last();
+            }
+            Action::LogSearchDone => self.log_viewer.searching = false,
+            Action::LogSearchClear => {
+                self.log_viewer.searching = false;
+                self.log_viewer.search.clear();
+                self.log_viewer.select_last();
             }
 
             Action::None => {}
@@ -962,24 +2430,126 @@ impl App {
 
     /// Handle player events.
     async fn handle_player_event(&mut self, event: PlayerEvent) -> Result<()> {
+        // Events from a stream that has since been replaced, stopped, or seeked are stale
+        let current_generation = self.player.as_ref().map(|p| p.generation());
+
         match event {
             PlayerEvent::StateChanged(state) => {
                 self.now_playing.state = state;
             }
-            PlayerEvent::Progress { position, duration } => {
-                self.now_playing.position = position.as_secs() as u32;
-                self.now_playing.duration = duration.as_secs() as u32;
-            }
-            PlayerEvent::TrackEnded => {
-                self.handle_track_ended()?;
+            PlayerEvent::Progress {
+                position,
+                duration,
+                generation,
+            } => {
+                if Some(generation) == current_generation {
+                    self.now_playing.position = position.as_secs() as u32;
+                    self.now_playing.position_ms = position.as_millis() as u64;
+                    self.now_playing.duration = duration.as_secs() as u32;
+                }
             }
-            PlayerEvent::Error(msg) => {
-                self.error_message = Some(msg);
+            PlayerEvent::BufferProgress {
+                buffered,
+                generation,
+            } => {
+                if Some(generation) == current_generation {
+                    self.now_playing.buffered = buffered;
+                }
+            }
+            PlayerEvent::TrackEnded { generation } => {
+                if Some(generation) == current_generation {
+                    self.handle_track_ended()?;
+                } else {
+                    tracing::debug!("Ignoring stale TrackEnded (generation {})", generation);
+                }
+            }
+            PlayerEvent::LoudnessMeasured { song_id, loudness } => {
+                self.loudness.insert(song_id, loudness);
+                if let Err(e) = crate::player::loudness::save(&self.loudness) {
+                    tracing::warn!("Failed to save loudness cache: {}", e);
+                }
+            }
+            PlayerEvent::Cached { song_id } => {
+                self.queue.cached.insert(song_id);
+                // Storing a track may have evicted others
+                if let (Some(cache), Some(client)) = (&self.track_cache, &self.client) {
+                    let evicted: Vec<String> = self
+                        .queue
+                        .cached
+                        .iter()
+                        .filter(|id| cache.path(&self.cache_key(client, id)).is_none())
+                        .cloned()
+                        .collect();
+                    for id in evicted {
+                        self.queue.cached.remove(&id);
+                    }
+                }
+            }
+            PlayerEvent::DeviceLost { name } => {
+                self.paused_by_device = self.now_playing.state == PlayerState::Playing;
+                self.now_playing.state = PlayerState::Paused;
+                self.toasts
+                    .warn(format!("{} disconnected, playback paused", name));
+            }
+            PlayerEvent::DeviceChanged { name } => {
+                let paused = std::mem::take(&mut self.paused_by_device);
+                if paused && self.config.player.resume_on_new_device {
+                    self.toggle_play_pause()?;
+                    self.toasts.info(format!("Playing on {}", name));
+                } else if paused {
+                    self.toasts
+                        .info(format!("Sound now goes to {}; press Space to resume", name));
+                }
+            }
+            PlayerEvent::DecodeFailed {
+                song_id,
+                error,
+                generation,
+            } => {
+                if Some(generation) == current_generation {
+                    self.handle_decode_failure(song_id, error.to_string())?;
+                }
+            }
+            PlayerEvent::Error(msg) => {
+                self.toasts.error(msg);
             }
         }
         Ok(())
     }
 
+    /// Handle a track that couldn't be decoded: stream it again transcoded
+    /// by the server, or if that was already tried, mark it unsupported in
+    /// the queue and move on.
+    fn handle_decode_failure(&mut self, song_id: String, error: String) -> Result<()> {
+        let Some(song) = self
+            .now_playing
+            .current_song
+            .clone()
+            .filter(|song| song.id == song_id)
+        else {
+            return Ok(());
+        };
+        // The player dropped its cached copy
+        self.queue.cached.remove(&song_id);
+        if self.transcoded.insert(song_id.clone()) {
+            self.toasts.warn(format!(
+                "Can't play {} ({}), trying {} from the server",
+                song.title,
+                error,
+                FALLBACK_FORMAT.to_uppercase()
+            ));
+            return self.start_playback(&song);
+        }
+
+        self.toasts
+            .error(format!("Can't play {}: {}", song.title, error));
+        self.queue.unsupported.insert(song_id);
+        if self.queue.next_song().is_some() {
+            self.play_next()?;
+        }
+        Ok(())
+    }
+
     /// Handle track ended - play next or stop.
     fn handle_track_ended(&mut self) -> Result<()> {
         match self.now_playing.repeat {
@@ -991,7 +2561,7 @@ impl App {
             }
             RepeatMode::All => {
                 // Play next, loop back to beginning
-                if self.queue.advance().is_none() {
+                if self.advance_queue().is_none() {
                     // Reached end, go back to beginning
                     if let Some(song) = self.queue.play_index(0).cloned() {
                         self.play_song(song)?;
@@ -1002,7 +2572,7 @@ impl App {
             }
             RepeatMode::Off => {
                 // Play next or stop
-                if let Some(song) = self.queue.advance().cloned() {
+                if let Some(song) = self.advance_queue().cloned() {
                     self.play_song(song)?;
                 } else {
                     self.now_playing.state = PlayerState::Stopped;
@@ -1012,11 +2582,270 @@ impl App {
         Ok(())
     }
 
-    /// Play a song.
-    fn play_song(&mut self, song: Song) -> Result<()> {
+    /// Persist state that should survive restarts.
+    fn save_state(&self) {
+        let state = crate::state::State {
+            consume: self.queue.consume,
+            library_stats: self.library_stats,
+            sorts: self.library.sorts,
+            hidden: self.library.hidden.clone(),
+            album_list: self.library.album_list,
+            songs_source: self.library.songs_source,
+            songs_filter: self.library.songs_filter.clone(),
+            show_remaining: self.now_playing.show_remaining,
+            queue_visible: Some(self.queue.visible),
+            queue_width: Some(self.queue.width),
+            queue_grouped: Some(self.queue.grouped),
+            lyrics_width: Some(self.lyrics.width),
+            lyrics_lang: self.lyrics.lang.clone(),
+            library_view: Some(self.library.view()),
+            // Muting lasts for the session
+            volume: Some(self.unmuted_volume.unwrap_or(self.now_playing.volume)),
+            newest_album: self.library.newest_album,
+        };
+        if let Err(e) = crate::state::save(&state) {
+            tracing::warn!("Failed to save state: {}", e);
+        }
+    }
+
+    /// Reopen the library view of the last session: switch to its tab, load the
+    /// items it drilled into, and select its item once the list is there.
+    fn restore_library_view(&mut self, view: crate::state::LibraryView) -> Result<()> {
+        // The tab may have been taken off the tab bar since
+        if !self.library.tabs.contains(&view.tab) {
+            return Ok(());
+        }
+        self.library.tab = view.tab;
+        let mut loads = Vec::new();
+        match view.tab {
+            Tab::Artists | Tab::Favorites => {
+                loads.extend(view.artist_id.map(Action::LoadArtist));
+                loads.extend(view.album_id.clone().map(Action::LoadAlbum));
+            }
+            Tab::Albums => loads.extend(view.album_id.clone().map(Action::LoadAlbum)),
+            Tab::Playlists => loads.extend(view.album_id.clone().map(Action::LoadPlaylist)),
+            Tab::Genres => {
+                loads.extend(view.genre.map(Action::LoadGenreAlbums));
+                loads.extend(view.album_id.clone().map(Action::LoadAlbum));
+            }
+            Tab::Songs | Tab::History => {}
+        }
+
+        // Depth the library ends up at, as set by `enter_album` and friends
+        let depth = match (&view.album_id, view.tab) {
+            (Some(_), Tab::Albums) => 1,
+            (Some(_), _) => 2,
+            (None, _) if !loads.is_empty() => 1,
+            (None, _) => 0,
+        };
+        if !loads.is_empty() {
+            self.library.loading = true;
+        }
+        for load in loads {
+            self.action_tx.send(load)?;
+        }
+        self.library.pending_selection = view.selected_id.map(|id| (depth, id));
+        Ok(())
+    }
+
+    /// Set the volume (0-100, or up to `player.max_volume`). Changing it
+    /// while muted forgets the volume from before muting.
+    fn set_volume(&mut self, volume: u8) -> Result<()> {
+        let volume = volume.min(self.config.player.max_volume);
+        self.now_playing.volume = volume;
+        self.unmuted_volume = None;
+        if let Some(player) = &self.player {
+            player.set_volume(volume as f32 / 100.0)?;
+        }
+        Ok(())
+    }
+
+    /// Set playback speed, rounded to a hundredth and clamped to the supported range.
+    fn set_speed(&mut self, speed: f32) -> Result<()> {
+        let speed = ((speed * 100.0).round() / 100.0).clamp(MIN_SPEED, MAX_SPEED);
+        self.now_playing.speed = speed;
+        if let Some(player) = &self.player {
+            player.set_speed(speed)?;
+        }
+        Ok(())
+    }
+
+    /// Get the panel shown beside the library, if any: the lyrics when
+    /// they're open, otherwise the queue.
+    fn side_panel(&self) -> Option<Focus> {
+        if self.lyrics.visible {
+            Some(Focus::Lyrics)
+        } else if self.queue.visible {
+            Some(Focus::Queue)
+        } else {
+            None
+        }
+    }
+
+    /// Get the panels on screen that can take the focus, left to right.
+    fn panels(&self) -> Vec<Focus> {
+        std::iter::once(Focus::Library)
+            .chain(self.side_panel())
+            .collect()
+    }
+
+    /// Move the focus back to the library if its panel went off screen.
+    fn keep_focus_on_screen(&mut self) {
+        if !self.panels().contains(&self.focus) {
+            self.focus = Focus::Library;
+        }
+    }
+
+    /// Get the playback position the listener is hearing, compensating for output latency.
+    fn audible_position_ms(&self) -> u64 {
+        self.now_playing
+            .position_ms
+            .saturating_sub(self.config.player.audio_latency_ms as u64)
+    }
+
+    /// Recompute the idle greeting from the clock and cached library stats.
+    fn update_greeting(&mut self) {
+        let config = &self.config.ui.greeting;
+        self.now_playing.greeting = config.enabled.then(|| {
+            let name = config
+                .name
+                .as_deref()
+                .unwrap_or(&self.config.server.username);
+            crate::ui::greeting::greeting(
+                config,
+                name,
+                &self.library_stats,
+                chrono::Local::now().hour(),
+            )
+        });
+    }
+
+    /// Move past the finished track, removing it first in consume mode.
+    fn advance_queue(&mut self) -> Option<&Song> {
+        if self.queue.consume {
+            self.queue.consume_current()
+        } else {
+            self.queue.advance()
+        }
+    }
+
+    /// Get the name of where the sound goes, unless it's this computer.
+    pub fn output_name(&self) -> Option<&str> {
+        match &self.output {
+            Output::Local => None,
+            Output::Network(address) => Some(address),
+            Output::Renderer(renderer) => Some(&renderer.name),
+        }
+    }
+
+    /// Outputs `w` steps through: this computer, the network sink if one is
+    /// set up, then the renderers found.
+    fn outputs(&self) -> Vec<Output> {
+        let network = self.config.player.network_output.clone();
+        std::iter::once(Output::Local)
+            .chain(network.map(Output::Network))
+            .chain(self.renderers.iter().cloned().map(Output::Renderer))
+            .collect()
+    }
+
+    /// Move playback to another output, carrying on from the same spot in
+    /// the current track.
+    fn switch_output(&mut self, output: Output) -> Result<()> {
+        let Some(player) = &mut self.player else {
+            return Ok(());
+        };
+        player.set_output(output.clone())?;
+        player.set_volume(self.now_playing.volume as f32 / 100.0)?;
+        self.toasts.info(match &output {
+            Output::Local => String::from("Playing on this computer"),
+            Output::Network(address) => format!("Streaming to {}", address),
+            Output::Renderer(renderer) => format!("Casting to {}", renderer.name),
+        });
+        self.output = output;
+
+        let state = self.now_playing.state;
+        let Some(song) = self.now_playing.current_song.clone() else {
+            return Ok(());
+        };
+        if state == PlayerState::Stopped {
+            return Ok(());
+        }
+        let position = Duration::from_millis(self.now_playing.position_ms);
+        self.start_playback(&song)?;
+        if let Some(player) = &self.player {
+            if !position.is_zero() {
+                player.seek(position)?;
+            }
+            if state == PlayerState::Paused {
+                player.pause()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Have the player start on a song.
+    fn start_playback(&self, song: &Song) -> Result<()> {
         if let (Some(player), Some(client)) = (&self.player, &self.client) {
-            let url = client.stream_url(&song.id);
+            let url = self.stream_url(client, &song.id);
+            let hls_url = (self.config.player.hls && !self.transcoded.contains(&song.id))
+                .then(|| client.hls_url(&song.id, self.config.player.max_bitrate));
+            let normalize = normalization(
+                self.config.player.replay_gain,
+                song,
+                self.loudness.get(&song.id),
+            );
+            player.play(url, hls_url, song.clone(), normalize)?;
+        }
+        Ok(())
+    }
+
+    /// Get the URL a song is streamed from: transcoded if it couldn't be
+    /// decoded before.
+    fn stream_url(&self, client: &SubsonicClient, song_id: &str) -> String {
+        if self.transcoded.contains(song_id) {
+            client.transcoded_stream_url(song_id, FALLBACK_FORMAT)
+        } else {
+            client.stream_url(song_id)
+        }
+    }
+
+    /// Get what a song's cached copy is stored under, as streamed now.
+    fn cache_key(&self, client: &SubsonicClient, song_id: &str) -> TrackKey {
+        TrackKey::for_url(song_id, &self.stream_url(client, song_id))
+    }
+
+    /// Download the tracks after the one now playing into the track cache,
+    /// so skipping to them is instant.
+    fn precache_upcoming(&self) -> Result<()> {
+        let count = (self.config.player.precache as usize).min(MAX_PRECACHE);
+        let (Some(player), Some(client), Some(current)) =
+            (&self.player, &self.client, self.queue.current_index)
+        else {
+            return Ok(());
+        };
+        if count == 0 || self.config.player.cache_mb == 0 {
+            return Ok(());
+        }
+        let songs: Vec<(String, String)> = self
+            .queue
+            .songs
+            .iter()
+            .skip(current + 1)
+            .take(count)
+            .map(|song| (song.id.clone(), self.stream_url(client, &song.id)))
+            .collect();
+        if !songs.is_empty() {
+            player.precache(songs)?;
+        }
+        Ok(())
+    }
 
+    /// Play a song.
+    fn play_song(&mut self, song: Song) -> Result<()> {
+        if !self.allows(Role::Stream) {
+            return Ok(());
+        }
+        if self.player.is_some() && self.client.is_some() {
             // Trigger album art loading if we have cover art
             if let Some(cover_art_id) = &song.cover_art {
                 self.action_tx
@@ -1033,7 +2862,16 @@ impl App {
             }
 
             self.now_playing.set_song(song.clone());
-            player.play(url, song)?;
+            self.library.playing_id = Some(song.id.clone());
+            self.start_playback(&song)?;
+            self.precache_upcoming()?;
+
+            // Record the track in the playback history
+            crate::history::record(&mut self.library.history, song);
+            if self.library.history_state.selected().is_none() {
+                self.library.history_state.select(Some(0));
+            }
+            self.history_writer.save(&self.library.history);
         }
         Ok(())
     }
@@ -1093,7 +2931,11 @@ impl App {
         match self.library.tab {
             Tab::Artists => {
                 if self.library.view_depth == 0 {
-                    // Select artist -> load albums
+                    // Select artist -> load albums, unless its letter is
+                    // collapsed and needs expanding first
+                    if self.library.expand_selected_artist_group() {
+                        return Ok(());
+                    }
                     if let Some(artist) = self.library.selected_artist_item().cloned() {
                         self.library.loading = true;
                         self.action_tx.send(Action::LoadArtist(artist.id))?;
@@ -1109,7 +2951,10 @@ impl App {
                     if let Some(_song) = self.library.selected_song_item().cloned() {
                         // Add all songs from album to queue and play selected
                         self.queue.clear();
-                        self.queue.add_all(self.library.album_songs.clone());
+                        self.queue.add_all(
+                            self.library.album_songs.clone(),
+                            self.library.album_songs_source(),
+                        );
                         if let Some(idx) = self.library.album_songs_state.selected() {
                             self.play_from_queue(idx)?;
                         }
@@ -1128,7 +2973,10 @@ impl App {
                     if let Some(_song) = self.library.selected_song_item().cloned() {
                         // Add all songs from album to queue and play selected
                         self.queue.clear();
-                        self.queue.add_all(self.library.album_songs.clone());
+                        self.queue.add_all(
+                            self.library.album_songs.clone(),
+                            self.library.album_songs_source(),
+                        );
                         if let Some(idx) = self.library.album_songs_state.selected() {
                             self.play_from_queue(idx)?;
                         }
@@ -1138,7 +2986,7 @@ impl App {
             Tab::Songs => {
                 // Select song -> play
                 if let Some(song) = self.library.selected_song_item().cloned() {
-                    self.queue.add(song.clone());
+                    self.queue.add(song, QueueSource::Songs);
                     let idx = self.queue.len() - 1;
                     self.play_from_queue(idx)?;
                 }
@@ -1155,7 +3003,10 @@ impl App {
                     if let Some(_song) = self.library.selected_song_item().cloned() {
                         // Add all songs from playlist to queue and play selected
                         self.queue.clear();
-                        self.queue.add_all(self.library.album_songs.clone());
+                        self.queue.add_all(
+                            self.library.album_songs.clone(),
+                            self.library.album_songs_source(),
+                        );
                         if let Some(idx) = self.library.album_songs_state.selected() {
                             self.play_from_queue(idx)?;
                         }
@@ -1169,6 +3020,13 @@ impl App {
                         self.library.loading = true;
                         self.action_tx.send(Action::LoadGenreAlbums(genre.value))?;
                     }
+                } else if self.library.view_depth == 1 && self.library.genre_section == 1 {
+                    // Select song -> play
+                    if let Some(song) = self.library.selected_genre_song().cloned() {
+                        self.queue.add(song, self.library.genre_source());
+                        let idx = self.queue.len() - 1;
+                        self.play_from_queue(idx)?;
+                    }
                 } else if self.library.view_depth == 1 {
                     // Select album -> load songs
                     if let Some(album) = self.library.selected_genre_album_item().cloned() {
@@ -1180,7 +3038,10 @@ impl App {
                     if let Some(_song) = self.library.selected_song_item().cloned() {
                         // Add all songs from album to queue and play selected
                         self.queue.clear();
-                        self.queue.add_all(self.library.album_songs.clone());
+                        self.queue.add_all(
+                            self.library.album_songs.clone(),
+                            self.library.album_songs_source(),
+                        );
                         if let Some(idx) = self.library.album_songs_state.selected() {
                             self.play_from_queue(idx)?;
                         }
@@ -1208,7 +3069,7 @@ impl App {
                         _ => {
                             // Select song -> play
                             if let Some(song) = self.library.selected_favorite_song().cloned() {
-                                self.queue.add(song.clone());
+                                self.queue.add(song, QueueSource::Favorites);
                                 let idx = self.queue.len() - 1;
                                 self.play_from_queue(idx)?;
                             }
@@ -1224,13 +3085,24 @@ impl App {
                     // Depth 2: Select song -> play
                     if let Some(_song) = self.library.selected_song_item().cloned() {
                         self.queue.clear();
-                        self.queue.add_all(self.library.album_songs.clone());
+                        self.queue.add_all(
+                            self.library.album_songs.clone(),
+                            self.library.album_songs_source(),
+                        );
                         if let Some(idx) = self.library.album_songs_state.selected() {
                             self.play_from_queue(idx)?;
                         }
                     }
                 }
             }
+            Tab::History => {
+                // Select past track -> replay
+                if let Some(entry) = self.library.selected_history_item().cloned() {
+                    self.queue.add(entry.song, QueueSource::History);
+                    let idx = self.queue.len() - 1;
+                    self.play_from_queue(idx)?;
+                }
+            }
         }
         Ok(())
     }
@@ -1245,22 +3117,28 @@ impl App {
 
     /// Append selected item to queue without playing.
     async fn append_selected_to_queue(&mut self) -> Result<()> {
-        if self.focus == 0 {
+        if self.focus == Focus::Library {
             // Library focused
             match self.library.tab {
                 Tab::Artists => {
                     if self.library.view_depth == 2 {
                         // Add single song from album
                         if let Some(song) = self.library.selected_song_item().cloned() {
-                            self.queue.add(song);
+                            self.queue.add(song, self.library.album_songs_source());
                         }
                     } else if self.library.view_depth == 1 {
                         // Add all songs from selected album
                         if let Some(album) = self.library.selected_album_item().cloned() {
                             // Need to load album songs first
                             if let Some(client) = &self.client {
-                                if let Ok((_album, songs)) = client.get_album(&album.id).await {
-                                    self.queue.add_all(songs);
+                                if let Ok((album, songs)) = client.get_album(&album.id).await {
+                                    self.queue.add_all(
+                                        songs,
+                                        QueueSource::Album {
+                                            id: album.id,
+                                            name: album.name,
+                                        },
+                                    );
                                 }
                             }
                         }
@@ -1271,39 +3149,61 @@ impl App {
                         // Add all songs from selected album
                         if let Some(album) = self.library.selected_album_item().cloned() {
                             if let Some(client) = &self.client {
-                                if let Ok((_album, songs)) = client.get_album(&album.id).await {
-                                    self.queue.add_all(songs);
+                                if let Ok((album, songs)) = client.get_album(&album.id).await {
+                                    self.queue.add_all(
+                                        songs,
+                                        QueueSource::Album {
+                                            id: album.id,
+                                            name: album.name,
+                                        },
+                                    );
                                 }
                             }
                         }
                     } else {
                         // Add single song
                         if let Some(song) = self.library.selected_song_item().cloned() {
-                            self.queue.add(song);
+                            self.queue.add(song, self.library.album_songs_source());
                         }
                     }
                 }
                 Tab::Songs => {
                     if let Some(song) = self.library.selected_song_item().cloned() {
-                        self.queue.add(song);
+                        self.queue.add(song, QueueSource::Songs);
                     }
                 }
                 Tab::Playlists => {
                     if self.library.view_depth == 0 {
                         // Add all songs from playlist
                         if let Some(playlist) = self.library.selected_playlist_item().cloned() {
-                            if let Some(client) = &self.client {
-                                if let Ok((_playlist, songs)) =
+                            if smart::is_smart(&playlist.id) {
+                                if let Some((playlist, songs)) = self.smart_playlist(&playlist.id) {
+                                    self.queue.add_all(
+                                        songs,
+                                        QueueSource::Playlist {
+                                            id: playlist.id,
+                                            name: playlist.name,
+                                        },
+                                    );
+                                }
+                            } else if let Some(client) = &self.client {
+                                if let Ok((playlist, songs)) =
                                     client.get_playlist(&playlist.id).await
                                 {
-                                    self.queue.add_all(songs);
+                                    self.queue.add_all(
+                                        songs,
+                                        QueueSource::Playlist {
+                                            id: playlist.id,
+                                            name: playlist.name,
+                                        },
+                                    );
                                 }
                             }
                         }
                     } else {
                         // Add single song
                         if let Some(song) = self.library.selected_song_item().cloned() {
-                            self.queue.add(song);
+                            self.queue.add(song, self.library.album_songs_source());
                         }
                     }
                 }
@@ -1311,14 +3211,25 @@ impl App {
                     if self.library.view_depth == 2 {
                         // Add single song from album
                         if let Some(song) = self.library.selected_song_item().cloned() {
-                            self.queue.add(song);
+                            self.queue.add(song, self.library.album_songs_source());
+                        }
+                    } else if self.library.view_depth == 1 && self.library.genre_section == 1 {
+                        // Add single song
+                        if let Some(song) = self.library.selected_genre_song().cloned() {
+                            self.queue.add(song, self.library.genre_source());
                         }
                     } else if self.library.view_depth == 1 {
                         // Add all songs from selected album
                         if let Some(album) = self.library.selected_genre_album_item().cloned() {
                             if let Some(client) = &self.client {
-                                if let Ok((_album, songs)) = client.get_album(&album.id).await {
-                                    self.queue.add_all(songs);
+                                if let Ok((album, songs)) = client.get_album(&album.id).await {
+                                    self.queue.add_all(
+                                        songs,
+                                        QueueSource::Album {
+                                            id: album.id,
+                                            name: album.name,
+                                        },
+                                    );
                                 }
                             }
                         }
@@ -1334,14 +3245,18 @@ impl App {
                                     self.library.selected_favorite_artist().cloned()
                                 {
                                     if let Some(client) = &self.client {
-                                        if let Ok((_artist, albums)) =
+                                        if let Ok((artist, albums)) =
                                             client.get_artist(&artist.id).await
                                         {
+                                            let source = QueueSource::Artist {
+                                                id: artist.id,
+                                                name: artist.name,
+                                            };
                                             for album in albums {
                                                 if let Ok((_album, songs)) =
                                                     client.get_album(&album.id).await
                                                 {
-                                                    self.queue.add_all(songs);
+                                                    self.queue.add_all(songs, source.clone());
                                                 }
                                             }
                                         }
@@ -1353,10 +3268,16 @@ impl App {
                                 if let Some(album) = self.library.selected_favorite_album().cloned()
                                 {
                                     if let Some(client) = &self.client {
-                                        if let Ok((_album, songs)) =
+                                        if let Ok((album, songs)) =
                                             client.get_album(&album.id).await
                                         {
-                                            self.queue.add_all(songs);
+                                            self.queue.add_all(
+                                                songs,
+                                                QueueSource::Album {
+                                                    id: album.id,
+                                                    name: album.name,
+                                                },
+                                            );
                                         }
                                     }
                                 }
@@ -1364,7 +3285,7 @@ impl App {
                             _ => {
                                 // Add single song
                                 if let Some(song) = self.library.selected_favorite_song().cloned() {
-                                    self.queue.add(song);
+                                    self.queue.add(song, QueueSource::Favorites);
                                 }
                             }
                         }
@@ -1372,18 +3293,30 @@ impl App {
                         // Add all songs from selected album
                         if let Some(album) = self.library.selected_album_item().cloned() {
                             if let Some(client) = &self.client {
-                                if let Ok((_album, songs)) = client.get_album(&album.id).await {
-                                    self.queue.add_all(songs);
+                                if let Ok((album, songs)) = client.get_album(&album.id).await {
+                                    self.queue.add_all(
+                                        songs,
+                                        QueueSource::Album {
+                                            id: album.id,
+                                            name: album.name,
+                                        },
+                                    );
                                 }
                             }
                         }
                     } else {
                         // Add single song from album
                         if let Some(song) = self.library.selected_song_item().cloned() {
-                            self.queue.add(song);
+                            self.queue.add(song, self.library.album_songs_source());
                         }
                     }
                 }
+                Tab::History => {
+                    // Re-queue past track
+                    if let Some(entry) = self.library.selected_history_item().cloned() {
+                        self.queue.add(entry.song, QueueSource::History);
+                    }
+                }
             }
         }
         Ok(())
@@ -1398,19 +3331,7 @@ impl App {
         };
 
         if new_index != index && index < self.queue.songs.len() {
-            let song = self.queue.songs.remove(index);
-            self.queue.songs.insert(new_index, song);
-
-            // Update current_index if needed
-            if let Some(current) = self.queue.current_index {
-                if current == index {
-                    self.queue.current_index = Some(new_index);
-                } else if direction < 0 && current >= new_index && current < index {
-                    self.queue.current_index = Some(current + 1);
-                } else if direction > 0 && current > index && current <= new_index {
-                    self.queue.current_index = Some(current - 1);
-                }
-            }
+            self.queue.move_item(index, new_index);
 
             // Update selection to follow the moved item
             self.queue.list_state.select(Some(new_index));
@@ -1419,7 +3340,9 @@ impl App {
 
     /// Handle selection in the search view.
     async fn handle_search_select(&mut self) -> Result<()> {
-        if let Some(artist) = self.search.selected_artist().cloned() {
+        if let Some(section) = self.search.load_more_selected() {
+            self.load_more_search_results(section).await;
+        } else if let Some(artist) = self.search.selected_artist().cloned() {
             self.search.close();
             self.library.tab = Tab::Artists;
             self.library.loading = true;
@@ -1431,13 +3354,126 @@ impl App {
             self.action_tx.send(Action::LoadAlbum(album.id))?;
         } else if let Some(song) = self.search.selected_song().cloned() {
             self.search.close();
-            self.queue.add(song.clone());
+            self.queue.add(song, QueueSource::Search);
             let idx = self.queue.len() - 1;
             self.play_from_queue(idx)?;
+        } else if let Some(playlist) = self.search.selected_playlist().cloned() {
+            self.search.close();
+            self.library.tab = Tab::Playlists;
+            self.library.loading = true;
+            self.action_tx.send(Action::LoadPlaylist(playlist.id))?;
+        }
+        Ok(())
+    }
+
+    /// Queue the selected search result, keeping search open.
+    /// Artists are queued with their whole discography.
+    async fn queue_search_selection(&mut self, next: bool) -> Result<()> {
+        let Some(client) = &self.client else {
+            return Ok(());
+        };
+
+        let (name, songs) = if let Some(artist) = self.search.selected_artist().cloned() {
+            let mut songs = Vec::new();
+            let albums = match client.get_artist(&artist.id).await {
+                Ok((_, albums)) => albums,
+                Err(e) => {
+                    self.toasts.error(format!("Failed to load artist: {}", e));
+                    return Ok(());
+                }
+            };
+            for album in albums {
+                match client.get_album(&album.id).await {
+                    Ok((_, album_songs)) => songs.extend(album_songs),
+                    Err(e) => {
+                        self.toasts.error(format!("Failed to load album: {}", e));
+                        return Ok(());
+                    }
+                }
+            }
+            (artist.name, songs)
+        } else if let Some(album) = self.search.selected_album().cloned() {
+            match client.get_album(&album.id).await {
+                Ok((_, songs)) => (album.name, songs),
+                Err(e) => {
+                    self.toasts.error(format!("Failed to load album: {}", e));
+                    return Ok(());
+                }
+            }
+        } else if let Some(song) = self.search.selected_song().cloned() {
+            (song.title.clone(), vec![song])
+        } else if let Some(playlist) = self.search.selected_playlist().cloned() {
+            match client.get_playlist(&playlist.id).await {
+                Ok((_, songs)) => (playlist.name, songs),
+                Err(e) => {
+                    self.toasts.error(format!("Failed to load playlist: {}", e));
+                    return Ok(());
+                }
+            }
+        } else {
+            return Ok(());
+        };
+
+        let hidden = &self.library.hidden;
+        let songs: Vec<Song> = songs
+            .into_iter()
+            .filter(|song| !hidden.hides_song(song))
+            .collect();
+        let count = songs.len();
+        if next {
+            self.queue.insert_next(songs, QueueSource::Search);
+        } else {
+            self.queue.add_all(songs, QueueSource::Search);
         }
+
+        let songs = if count == 1 { "song" } else { "songs" };
+        self.search.status = Some(if next {
+            format!("{}: {} {} to play next", name, count, songs)
+        } else {
+            format!("{}: {} {} added to queue", name, count, songs)
+        });
         Ok(())
     }
 
+    /// Fetch the next page of one search results section and append it.
+    async fn load_more_search_results(&mut self, section: usize) {
+        let Some(client) = &self.client else {
+            return;
+        };
+        let query = self.search.last_searched_query().to_string();
+        let offset = Some(self.search.fetched[section]);
+        let size = Some(SEARCH_PAGE_SIZE);
+        let hidden = &self.library.hidden;
+
+        let fetched = match section {
+            0 => client
+                .search_artists(&query, size, offset)
+                .await
+                .map(|artists| {
+                    let count = artists.len();
+                    self.search.artists.extend(artists);
+                    count
+                }),
+            1 => client
+                .search_albums(&query, size, offset)
+                .await
+                .map(|albums| {
+                    let count = albums.len();
+                    self.search
+                        .albums
+                        .extend(albums.into_iter().filter(|a| !hidden.hides_album(a)));
+                    count
+                }),
+            // Song results page through their cursor as the list scrolls
+            _ => return,
+        };
+
+        match fetched {
+            Ok(count) => self.search.page_fetched(section, count),
+            Err(e) => self.toasts.error(format!("Search failed: {}", e)),
+        }
+    }
+
     /// Perform a search.
     async fn perform_search(&mut self) -> Result<()> {
         if self.search.query.is_empty() {
@@ -1449,7 +3485,8 @@ impl App {
         self.search.mark_search_started();
 
         if let Some(client) = &self.client {
-            match client.search(&query, Some(20), Some(20), Some(20)).await {
+            let size = Some(SEARCH_PAGE_SIZE);
+            match client.search(&query, size, size, Some(PAGE_SIZE)).await {
                 Ok((artists, albums, songs)) => {
                     self.action_tx.send(Action::SearchResults {
                         artists,
@@ -1459,7 +3496,7 @@ impl App {
                 }
                 Err(e) => {
                     self.search.searching = false;
-                    self.error_message = Some(format!("Search failed: {}", e));
+                    self.toasts.error(format!("Search failed: {}", e));
                 }
             }
         }
@@ -1468,35 +3505,143 @@ impl App {
     }
 
     /// Load artists from the server.
-    async fn load_artists(&mut self) -> Result<()> {
+    fn load_artists(&mut self, delay: Duration) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.spawn_load(Tab::Artists, delay, async move {
+            client.get_artists().await.map(Action::ArtistsLoaded)
+        });
+    }
+
+    /// Load albums from the server.
+    fn load_albums(&mut self, delay: Duration) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let list = self.library.album_list;
+        if let (AlbumList::Compilations, Some(albums)) = (list, &self.compilations) {
+            // Drop the list that was loading, which would land on top
+            if let Some(old) = self.tab_loads.remove(&Tab::Albums) {
+                old.abort();
+            }
+            self.library.loading_tabs.remove(&Tab::Albums);
+            self.library.set_albums(albums.clone());
+            return;
+        }
+        self.spawn_load(Tab::Albums, delay, async move {
+            let albums = match list {
+                AlbumList::ByYear { from, to } => {
+                    client.get_albums_by_year(from, to, Some(500), None).await
+                }
+                // Compilations can't be asked for, so go through every album
+                AlbumList::Compilations => {
+                    let mut compilations = Vec::new();
+                    let mut offset = 0;
+                    loop {
+                        let page = client
+                            .get_album_list(list.list_type(), Some(LIBRARY_PAGE_SIZE), Some(offset))
+                            .await?;
+                        let done = page.len() < LIBRARY_PAGE_SIZE as usize;
+                        offset += page.len() as u32;
+                        compilations.extend(page.into_iter().filter(Album::is_compilation));
+                        if done {
+                            return Ok(Action::CompilationsLoaded(compilations));
+                        }
+                    }
+                }
+                list => {
+                    client
+                        .get_album_list(list.list_type(), Some(100), None)
+                        .await
+                }
+            };
+            albums.map(Action::AlbumsLoaded)
+        });
+    }
+
+    /// Load every tab's list in the background: the tab in view first, then
+    /// the rest of the tab bar and the tabs left off it (which other views
+    /// still open), staggered so they don't all hit the server at once.
+    fn load_library(&mut self) {
+        let mut order = vec![self.library.tab];
+        for tab in self.library.tabs.iter().chain(Tab::all()) {
+            if !order.contains(tab) {
+                order.push(*tab);
+            }
+        }
+        for (i, tab) in order.into_iter().enumerate() {
+            let delay = LOAD_STAGGER * i as u32;
+            match tab {
+                Tab::Artists => self.load_artists(delay),
+                Tab::Albums => self.load_albums(delay),
+                Tab::Songs => self.load_songs(),
+                Tab::Playlists => self.load_playlists(delay),
+                Tab::Genres => self.load_genres(delay),
+                Tab::Favorites => self.load_favorites(delay),
+                Tab::History => {}
+            }
+        }
+    }
+
+    /// Fetch a tab's list in the background after `delay`, marking the tab
+    /// as loading until it arrives. An older load of the tab is cancelled, so
+    /// it can't arrive last and overwrite the newer list.
+    fn spawn_load(
+        &mut self,
+        tab: Tab,
+        delay: Duration,
+        load: impl Future<Output = Result<Action, ApiClientError>> + Send + 'static,
+    ) {
+        self.library.loading_tabs.insert(tab);
+        let action_tx = self.action_tx.clone();
+        let task = tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let action = load
+                .await
+                .unwrap_or_else(|e| Action::LibraryLoadFailed(tab, e.to_string()));
+            let _ = action_tx.send(action);
+        });
+        if let Some(old) = self.tab_loads.insert(tab, task.abort_handle()) {
+            old.abort();
+        }
+    }
+
+    /// Open a random album from the library.
+    async fn go_to_random_album(&mut self) -> Result<()> {
         if let Some(client) = &self.client {
-            self.library.loading = true;
-            match client.get_artists().await {
-                Ok(artists) => {
-                    self.action_tx.send(Action::ArtistsLoaded(artists))?;
+            match client.get_album_list("random", Some(1), None).await {
+                Ok(albums) => {
+                    if let Some(album) = albums.into_iter().next() {
+                        self.library.switch_tab(Tab::Albums);
+                        self.focus = Focus::Library;
+                        self.library.loading = true;
+                        self.action_tx.send(Action::LoadAlbum(album.id))?;
+                    }
                 }
                 Err(e) => {
-                    self.library.loading = false;
-                    tracing::error!("Failed to load artists: {}", e);
-                    self.error_message = Some(format!("Failed to load artists: {}", e));
+                    self.toasts
+                        .error(format!("Failed to pick a random album: {}", e));
                 }
             }
         }
         Ok(())
     }
 
-    /// Load albums from the server.
-    async fn load_albums(&mut self) -> Result<()> {
+    /// Add random songs to the queue, limited to the genre or years being browsed.
+    async fn queue_random_songs(&mut self) -> Result<()> {
         if let Some(client) = &self.client {
-            self.library.loading = true;
-            match client.get_album_list("newest", Some(100), None).await {
-                Ok(albums) => {
-                    self.action_tx.send(Action::AlbumsLoaded(albums))?;
+            let filter = self.library.random_filter();
+            let size = self.config.player.random_songs;
+            match client.get_random_songs(Some(size), &filter).await {
+                Ok(mut songs) => {
+                    let hidden = &self.library.hidden;
+                    songs.retain(|song| !hidden.hides_song(song));
+                    self.queue.add_all(songs, QueueSource::Random);
                 }
                 Err(e) => {
-                    self.library.loading = false;
-                    tracing::error!("Failed to load albums: {}", e);
-                    self.error_message = Some(format!("Failed to load albums: {}", e));
+                    self.toasts
+                        .error(format!("Failed to load random songs: {}", e));
                 }
             }
         }
@@ -1512,13 +3657,83 @@ impl App {
                 }
                 Err(e) => {
                     self.library.loading = false;
-                    self.error_message = Some(format!("Failed to load album: {}", e));
+                    self.toasts.error(format!("Failed to load album: {}", e));
                 }
             }
         }
         Ok(())
     }
 
+    /// Look for the top albums of artists like the one of `album`, to suggest
+    /// below its songs.
+    fn load_similar_albums(&self, album: &Album) {
+        if !self.config.ui.similar_albums || !self.library.similar_albums_needed(&album.id) {
+            return;
+        }
+        let (Some(client), Some(artist_id)) = (self.client.clone(), album.artist_id.clone()) else {
+            return;
+        };
+        let album_id = album.id.clone();
+        let action_tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let info = match client.get_artist_info(&artist_id, SIMILAR_ARTISTS).await {
+                Ok(info) => info,
+                Err(e) => {
+                    tracing::debug!("No similar artists for {}: {}", artist_id, e);
+                    return;
+                }
+            };
+            let artists = info.similar_artist.iter().take(SIMILAR_ARTISTS as usize);
+            let albums =
+                futures::future::join_all(artists.map(|artist| client.get_artist(&artist.id)))
+                    .await
+                    .into_iter()
+                    .filter_map(|result| result.ok())
+                    .filter_map(|(_, albums)| top_album(albums))
+                    .collect();
+            let _ = action_tx.send(Action::SimilarAlbumsLoaded(album_id, albums));
+        });
+    }
+
+    /// Open the similar album picked below an album's songs.
+    fn open_similar_album(&mut self) -> Result<()> {
+        let Some(id) = self
+            .library
+            .selected_similar_album()
+            .map(|album| album.id.clone())
+        else {
+            return Ok(());
+        };
+        self.library.switch_tab(Tab::Albums);
+        self.library.loading = true;
+        self.action_tx.send(Action::LoadAlbum(id))?;
+        Ok(())
+    }
+
+    /// Add the songs of the similar album picked below an album's songs to
+    /// the queue.
+    async fn queue_similar_album(&mut self) {
+        let (Some(client), Some(album)) = (&self.client, self.library.selected_similar_album())
+        else {
+            return;
+        };
+        match client.get_album(&album.id).await {
+            Ok((album, mut songs)) => {
+                songs.retain(|song| !self.library.hidden.hides_song(song));
+                self.toasts
+                    .info(format!("Added {} to the queue", album.name));
+                self.queue.add_all(
+                    songs,
+                    QueueSource::Album {
+                        id: album.id,
+                        name: album.name,
+                    },
+                );
+            }
+            Err(e) => self.toasts.error(format!("Failed to load album: {}", e)),
+        }
+    }
+
     /// Load a specific artist.
     async fn load_artist(&mut self, id: &str) -> Result<()> {
         if let Some(client) = &self.client {
@@ -1528,7 +3743,7 @@ impl App {
                 }
                 Err(e) => {
                     self.library.loading = false;
-                    self.error_message = Some(format!("Failed to load artist: {}", e));
+                    self.toasts.error(format!("Failed to load artist: {}", e));
                 }
             }
         }
@@ -1536,72 +3751,275 @@ impl App {
     }
 
     /// Load playlists from the server.
-    async fn load_playlists(&mut self) -> Result<()> {
-        if let Some(client) = &self.client {
-            match client.get_playlists().await {
-                Ok(playlists) => {
-                    self.action_tx.send(Action::PlaylistsLoaded(playlists))?;
-                }
-                Err(e) => {
-                    tracing::error!("Failed to load playlists: {}", e);
-                    self.error_message = Some(format!("Failed to load playlists: {}", e));
-                }
-            }
-        }
-        Ok(())
+    fn load_playlists(&mut self, delay: Duration) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.spawn_load(Tab::Playlists, delay, async move {
+            client.get_playlists().await.map(Action::PlaylistsLoaded)
+        });
     }
 
     /// Load a specific playlist.
     async fn load_playlist(&mut self, id: &str) -> Result<()> {
-        if let Some(client) = &self.client {
-            match client.get_playlist(id).await {
-                Ok((playlist, songs)) => {
+        if smart::is_smart(id) {
+            match self.smart_playlist(id) {
+                Some((playlist, songs)) => {
                     self.action_tx
                         .send(Action::PlaylistLoaded(playlist, songs))?;
                 }
-                Err(e) => {
-                    self.library.loading = false;
-                    self.error_message = Some(format!("Failed to load playlist: {}", e));
+                // Opened once the library's songs arrive
+                None if self.fetching_library_songs => {
+                    self.smart_playlist_waiting = Some(id.to_string());
                 }
+                None => self.library.loading = false,
             }
+            return Ok(());
         }
-        Ok(())
-    }
-
-    /// Load random songs for the Songs tab.
-    async fn load_songs(&mut self) -> Result<()> {
         if let Some(client) = &self.client {
-            self.library.loading = true;
-            match client.get_random_songs(Some(100)).await {
-                Ok(songs) => {
-                    self.action_tx.send(Action::SongsLoaded(songs))?;
+            match client.get_playlist(id).await {
+                Ok((playlist, songs)) => {
+                    self.action_tx
+                        .send(Action::PlaylistLoaded(playlist, songs))?;
                 }
                 Err(e) => {
                     self.library.loading = false;
-                    tracing::error!("Failed to load songs: {}", e);
-                    self.error_message = Some(format!("Failed to load songs: {}", e));
+                    self.toasts.error(format!("Failed to load playlist: {}", e));
                 }
             }
         }
         Ok(())
     }
 
-    /// Load genres from the server.
-    async fn load_genres(&mut self) -> Result<()> {
-        if let Some(client) = &self.client {
-            self.library.loading = true;
-            match client.get_genres().await {
-                Ok(genres) => {
-                    self.action_tx.send(Action::GenresLoaded(genres))?;
+    /// Pick a smart playlist's songs from the library's. Returns `None` while
+    /// the library's songs are still being fetched, or if the rules are bad.
+    fn smart_playlist(&mut self, id: &str) -> Option<(Playlist, Vec<Song>)> {
+        let index = smart::index(id)?;
+        let Some(config) = self.config.smart_playlists.get(index) else {
+            self.toasts
+                .error(String::from("Smart playlist no longer exists"));
+            return None;
+        };
+        let Some(library) = &self.library_songs else {
+            self.fetch_library_songs();
+            return None;
+        };
+        match smart::evaluate(index, config, library) {
+            Ok((playlist, mut songs)) => {
+                let hidden = &self.library.hidden;
+                songs.retain(|song| !hidden.hides_song(song));
+                Some((playlist, songs))
+            }
+            Err(e) => {
+                self.toasts.error(e);
+                None
+            }
+        }
+    }
+
+    /// Fetch every song in the library in the background, for smart
+    /// playlists. The list is kept on disk until the library is refreshed.
+    fn fetch_library_songs(&mut self) {
+        if self.fetching_library_songs {
+            return;
+        }
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.fetching_library_songs = true;
+        self.toasts.info(String::from(
+            "Fetching the library's songs for smart playlists",
+        ));
+        let action_tx = self.action_tx.clone();
+
+        tokio::spawn(async move {
+            if let Some(songs) = smart::load_library() {
+                let _ = action_tx.send(Action::LibrarySongsLoaded(songs));
+                return;
+            }
+            // An empty search matches every song
+            let source = SearchSongs {
+                query: String::new(),
+            };
+            let mut songs = Vec::new();
+            loop {
+                let offset = songs.len() as u32;
+                match source.fetch_page(&client, offset, LIBRARY_PAGE_SIZE).await {
+                    Ok(page) => {
+                        let done = page.len() < LIBRARY_PAGE_SIZE as usize;
+                        songs.extend(page);
+                        if done {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        let _ = action_tx.send(Action::LibrarySongsFailed(e.to_string()));
+                        return;
+                    }
                 }
-                Err(e) => {
-                    self.library.loading = false;
-                    tracing::error!("Failed to load genres: {}", e);
-                    self.error_message = Some(format!("Failed to load genres: {}", e));
+            }
+            if let Err(e) = smart::save_library(&songs) {
+                tracing::warn!("Failed to save the library's songs: {}", e);
+            }
+            let _ = action_tx.send(Action::LibrarySongsLoaded(songs));
+        });
+    }
+
+    /// Load the Songs tab from its chosen source.
+    fn load_songs(&mut self) {
+        if self.client.is_some() {
+            let source: Arc<dyn SongSource> = match self.library.songs_source {
+                SongsSource::Random => Arc::new(RandomSongs {
+                    filter: self.library.songs_filter.clone(),
+                }),
+                SongsSource::RecentlyAdded => Arc::new(AlbumListSongs::new("newest")),
+                SongsSource::Starred => Arc::new(StarredSongs::default()),
+                SongsSource::TopRated => Arc::new(AlbumListSongs::new("highest")),
+            };
+            self.library.loading_tabs.insert(Tab::Songs);
+            self.library.songs_pages = Some(PagedSongs::new(source));
+            self.request_song_page(SongList::Songs);
+        }
+    }
+
+    /// Replace the Songs tab list after its source or filter changed.
+    fn reload_songs(&mut self) {
+        self.library.songs.clear();
+        self.library.filter.clear();
+        self.library.songs_state.select(None);
+        self.save_state();
+        self.load_songs();
+    }
+
+    /// Fetch every song of a genre in the background, to be queued shuffled.
+    fn queue_genre_shuffled(&self, genre: String) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let action_tx = self.action_tx.clone();
+
+        tokio::spawn(async move {
+            let source = GenreSongs {
+                genre: genre.clone(),
+            };
+            let mut songs = Vec::new();
+            loop {
+                let offset = songs.len() as u32;
+                match source.fetch_page(&client, offset, PAGE_SIZE).await {
+                    Ok(page) => {
+                        let done = page.len() < PAGE_SIZE as usize;
+                        songs.extend(page);
+                        if done || songs.len() >= GENRE_QUEUE_LIMIT {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        let _ = action_tx.send(Action::Error(format!(
+                            "Failed to load songs for {}: {}",
+                            genre, e
+                        )));
+                        return;
+                    }
                 }
             }
+            let _ = action_tx.send(Action::GenreSongsFetched(genre, songs));
+        });
+    }
+
+    /// Fetch the next page of a paged song list in the background.
+    fn request_song_page(&mut self, list: SongList) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let Some(pages) = self.song_pages_mut(list) else {
+            return;
+        };
+
+        pages.start_loading();
+        let source = pages.source();
+        let offset = pages.next_offset();
+        let action_tx = self.action_tx.clone();
+
+        tokio::spawn(async move {
+            let action = match source.fetch_page(&client, offset, PAGE_SIZE).await {
+                Ok(songs) => Action::SongPageLoaded {
+                    list,
+                    offset,
+                    songs,
+                },
+                Err(e) => Action::SongPageFailed(list, e.to_string()),
+            };
+            let _ = action_tx.send(action);
+        });
+    }
+
+    /// Get the pagination cursor for a paged song list, in the library or search.
+    fn song_pages_mut(&mut self, list: SongList) -> Option<&mut PagedSongs> {
+        match list {
+            SongList::SearchSongs => self.search.songs_pages.as_mut(),
+            _ => self.library.pages_mut(list),
         }
-        Ok(())
+    }
+
+    /// Hold a reorder of the open playlist back, folded into the one already
+    /// held, so a song moved several places goes to the server once.
+    fn hold_playlist_move(&mut self, edit: PlaylistEdit) {
+        let edit = match self.playlist_move.take() {
+            Some((held, _)) if held.playlist_id == edit.playlist_id => held.then_move(edit),
+            Some((held, _)) => {
+                self.queue_playlist_edit(held);
+                edit
+            }
+            None => edit,
+        };
+        self.playlist_move = Some((edit, Instant::now() + PLAYLIST_MOVE_DELAY));
+    }
+
+    /// Send the held reorder of the open playlist, if any.
+    fn send_playlist_move(&mut self) {
+        if let Some((edit, _)) = self.playlist_move.take() {
+            self.queue_playlist_edit(edit);
+        }
+    }
+
+    /// Send a change to the open playlist once the ones before it are made.
+    fn queue_playlist_edit(&mut self, edit: PlaylistEdit) {
+        // The held reorder was made before this change
+        if let Some((held, _)) = self.playlist_move.take() {
+            self.playlist_edits.push_back(held);
+        }
+        self.playlist_edits.push_back(edit);
+        if self.playlist_edits.len() == 1 {
+            self.send_playlist_edit();
+        }
+    }
+
+    /// Send the first waiting playlist change.
+    fn send_playlist_edit(&mut self) {
+        let (Some(client), Some(edit)) = (self.client.clone(), self.playlist_edits.front()) else {
+            return;
+        };
+        let edit = edit.clone();
+        let action_tx = self.action_tx.clone();
+
+        tokio::spawn(async move {
+            let result = client
+                .update_playlist(&edit.playlist_id, &edit.add, &edit.remove)
+                .await;
+            let _ = action_tx.send(Action::PlaylistEditDone(
+                result.err().map(|e| e.to_string()),
+            ));
+        });
+    }
+
+    /// Load genres from the server.
+    fn load_genres(&mut self, delay: Duration) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.spawn_load(Tab::Genres, delay, async move {
+            client.get_genres().await.map(Action::GenresLoaded)
+        });
     }
 
     /// Load albums for a specific genre.
@@ -1614,7 +4032,8 @@ impl App {
                 }
                 Err(e) => {
                     self.library.loading = false;
-                    self.error_message = Some(format!("Failed to load genre albums: {}", e));
+                    self.toasts
+                        .error(format!("Failed to load genre albums: {}", e));
                 }
             }
         }
@@ -1622,58 +4041,401 @@ impl App {
     }
 
     /// Load starred (favorite) items from the server.
-    async fn load_favorites(&mut self) -> Result<()> {
-        if let Some(client) = &self.client {
-            self.library.loading = true;
-            match client.get_starred().await {
-                Ok((artists, albums, songs)) => {
-                    self.action_tx.send(Action::FavoritesLoaded {
-                        artists,
-                        albums,
-                        songs,
-                    })?;
+    fn load_favorites(&mut self, delay: Duration) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        self.spawn_load(Tab::Favorites, delay, async move {
+            let (artists, albums, songs) = client.get_starred().await?;
+            Ok(Action::FavoritesLoaded {
+                artists,
+                albums,
+                songs,
+            })
+        });
+    }
+
+    /// Request cover art for the album header if it isn't loaded yet.
+    fn load_library_album_art(&self) -> Result<()> {
+        if let Some(id) = self.library.album_art_needed() {
+            self.action_tx.send(Action::LoadAlbumArt(id.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Open the playlist picker for the selected song, or the songs of the
+    /// selected album.
+    async fn open_playlist_picker(&mut self) -> Result<()> {
+        let target = if self.focus == Focus::Queue {
+            self.queue.selected_song().map(|song| HideTarget::Song {
+                id: song.id.clone(),
+                title: song.title.clone(),
+            })
+        } else {
+            self.library.selected_hide_target()
+        };
+        let Some(target) = target else {
+            self.toasts
+                .warn("Select a song or album to add to a playlist");
+            return Ok(());
+        };
+
+        let song_ids = match &target {
+            HideTarget::Song { id, .. } => vec![id.clone()],
+            HideTarget::Album { id, .. } => {
+                let Some(client) = &self.client else {
+                    return Ok(());
+                };
+                match client.get_album(id).await {
+                    Ok((_, songs)) => songs
+                        .into_iter()
+                        .filter(|song| !self.library.hidden.hides_song(song))
+                        .map(|song| song.id)
+                        .collect(),
+                    Err(e) => {
+                        self.toasts.error(format!("Failed to load album: {}", e));
+                        return Ok(());
+                    }
                 }
+            }
+        };
+        self.playlist_picker
+            .open(target.describe(), song_ids, &self.library.playlists);
+        Ok(())
+    }
+
+    /// Save the selected or open playlist, or the queue anywhere else, as an
+    /// `.m3u8` file in the M3U directory.
+    async fn export_m3u(&mut self) -> Result<()> {
+        let Some(client) = self.client.clone() else {
+            return Ok(());
+        };
+        let playlist_id = if self.library.tab == Tab::Playlists && self.focus == Focus::Library {
+            if self.library.view_depth == 0 {
+                self.library.selected_playlist_item().map(|p| p.id.clone())
+            } else {
+                self.library.selected_album.as_ref().map(|a| a.id.clone())
+            }
+        } else {
+            None
+        };
+        let (name, songs) = match playlist_id {
+            Some(id) if smart::is_smart(&id) => match self.smart_playlist(&id) {
+                Some((playlist, songs)) => (playlist.name, songs),
+                None => return Ok(()),
+            },
+            Some(id) => match client.get_playlist(&id).await {
+                Ok((playlist, songs)) => (playlist.name, songs),
                 Err(e) => {
-                    self.library.loading = false;
-                    tracing::error!("Failed to load favorites: {}", e);
-                    self.error_message = Some(format!("Failed to load favorites: {}", e));
+                    self.toasts.error(format!("Failed to load playlist: {}", e));
+                    return Ok(());
+                }
+            },
+            None => (String::from("Queue"), self.queue.songs.clone()),
+        };
+        if songs.is_empty() {
+            self.toasts.warn(format!("{} has no songs to export", name));
+            return Ok(());
+        }
+
+        let locations = self.config.m3u.locations;
+        let cache = match locations {
+            M3uLocations::Stream | M3uLocations::Signed => None,
+            M3uLocations::Cache => StreamCache::open(self.config.player.cache_mb)
+                .ok()
+                .flatten(),
+        };
+        let contents = m3u::format(&songs, |song| {
+            let cached = cache
+                .as_ref()
+                .and_then(|cache| cache.path(&self.cache_key(&client, &song.id)));
+            match cached {
+                Some(path) => path.display().to_string(),
+                None if locations == M3uLocations::Signed => client.stream_url(&song.id),
+                None => client.public_stream_url(&song.id),
+            }
+        });
+        match self
+            .config
+            .m3u_dir()
+            .and_then(|dir| m3u::export(&dir, &name, &contents))
+        {
+            Ok(path) => self
+                .toasts
+                .info(format!("Saved {} to {}", name, path.display())),
+            Err(e) => self
+                .toasts
+                .error(format!("Failed to export {}: {}", name, e)),
+        }
+        Ok(())
+    }
+
+    /// Check the server and the stream after the system wakes, since both
+    /// may have gone stale while it slept.
+    fn woke(&mut self) -> Result<()> {
+        tracing::info!("System woke from sleep");
+        // A failed ping starts reconnecting
+        if let Some(client) = self.client.clone() {
+            tokio::spawn(async move {
+                if let Err(e) = client.ping().await {
+                    tracing::warn!("Server unreachable after sleep: {}", e);
+                }
+            });
+        }
+        match self.now_playing.state {
+            // The download was cut off; start it again with a fresh URL
+            PlayerState::Buffering => {
+                if let Some(song) = self.now_playing.current_song.clone() {
+                    self.start_playback(&song)?;
+                }
+            }
+            // Rebuild the track's sink where it stopped. The sound device
+            // itself is watched by the player, which reopens it if it went
+            // away while sleeping
+            PlayerState::Paused => {
+                if let Some(player) = &self.player {
+                    player.seek(Duration::from_millis(self.now_playing.position_ms))?;
+                }
+            }
+            PlayerState::Playing | PlayerState::Stopped => {}
+        }
+        if std::mem::take(&mut self.paused_for_sleep) {
+            self.toasts.info("Paused for sleep; press Space to play on");
+        }
+        Ok(())
+    }
+
+    /// Hand the song now playing, or the queue from it on, to the external
+    /// player, pausing here if the config says to.
+    fn open_external(&mut self, target: ExternalTarget) -> Result<()> {
+        let Some(client) = self.client.clone() else {
+            return Ok(());
+        };
+        let handed = match target {
+            ExternalTarget::Track => match &self.now_playing.current_song {
+                Some(song) => external::write_queue(std::slice::from_ref(song), |song| {
+                    client.stream_url(&song.id)
+                })
+                .map(|path| (path.display().to_string(), song.title.clone())),
+                None => {
+                    self.toasts.warn("Nothing is playing");
+                    return Ok(());
+                }
+            },
+            ExternalTarget::Queue => {
+                let start = self.queue.current_index.unwrap_or(0);
+                let songs = self.queue.songs.get(start..).unwrap_or_default();
+                if songs.is_empty() {
+                    self.toasts.warn("The queue is empty");
+                    return Ok(());
                 }
+                external::write_queue(songs, |song| client.stream_url(&song.id))
+                    .map(|path| (path.display().to_string(), format!("{} songs", songs.len())))
             }
+        };
+
+        let config = &self.config.external_player;
+        match handed.and_then(|(location, what)| {
+            external::launch(&config.command, &location).map(|()| what)
+        }) {
+            Ok(what) => {
+                self.toasts.info(format!(
+                    "Playing {} in {}",
+                    what,
+                    config.command.first().map_or("", String::as_str)
+                ));
+                if config.pause && self.now_playing.state == PlayerState::Playing {
+                    self.toggle_play_pause()?;
+                }
+            }
+            Err(e) => self
+                .toasts
+                .error(format!("Failed to open external player: {}", e)),
         }
         Ok(())
     }
 
-    /// Toggle star on the current song (from now playing, library, queue, or search).
-    async fn toggle_star(&mut self) -> Result<()> {
+    /// Find the tracks of the M3U file named in the import prompt in the
+    /// library, in the background. The songs found are then offered to the
+    /// playlist picker.
+    fn import_m3u(&mut self) {
+        let Some(path) = self.import_prompt.expanded_path() else {
+            return;
+        };
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        // Leave the prompt open to fix a wrong path
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.toasts
+                    .error(format!("Failed to read {}: {}", path.display(), e));
+                return;
+            }
+        };
+        self.import_prompt.close();
+
+        let name = path.file_stem().map_or_else(
+            || path.display().to_string(),
+            |stem| stem.to_string_lossy().into_owned(),
+        );
+        let entries = m3u::parse(&text);
+        if entries.is_empty() {
+            self.toasts.warn(format!("No tracks in {}", name));
+            return;
+        }
+        self.toasts.info(format!(
+            "Looking for the {} tracks of {} in the library",
+            entries.len(),
+            name
+        ));
+        let action_tx = self.action_tx.clone();
+
+        tokio::spawn(async move {
+            let mut song_ids = Vec::new();
+            let mut unmatched = Vec::new();
+            for entry in entries {
+                let source = SearchSongs {
+                    query: entry.title.clone(),
+                };
+                let found = match source.fetch_page(&client, 0, IMPORT_SEARCH_SIZE).await {
+                    Ok(songs) => entry.best_match(&songs).map(|song| song.id.clone()),
+                    Err(e) => {
+                        tracing::warn!("Failed to search for {}: {}", entry.title, e);
+                        None
+                    }
+                };
+                match found {
+                    Some(id) => song_ids.push(id),
+                    None => unmatched.push(entry.location),
+                }
+            }
+            let _ = action_tx.send(Action::M3uMatched {
+                name,
+                song_ids,
+                unmatched,
+            });
+        });
+    }
+
+    /// Add songs to a playlist, or create one holding them.
+    async fn add_to_playlist(
+        &mut self,
+        choice: PlaylistChoice,
+        song_ids: Vec<String>,
+    ) -> Result<()> {
+        let Some(client) = &self.client else {
+            return Ok(());
+        };
+        let (result, name) = match &choice {
+            PlaylistChoice::Existing { id, name } => {
+                (client.update_playlist(id, &song_ids, &[]).await, name)
+            }
+            PlaylistChoice::New(name) => (client.create_playlist(name, &song_ids).await, name),
+        };
+
+        let songs = match song_ids.len() {
+            1 => String::from("1 song"),
+            n => format!("{} songs", n),
+        };
+        match result {
+            Ok(()) => {
+                self.toasts.info(format!("Added {} to \"{}\"", songs, name));
+                self.action_tx.send(Action::LoadPlaylists)?;
+                // Show the new songs if the playlist is open
+                if let PlaylistChoice::Existing { id, .. } = &choice {
+                    if self.library.tab == Tab::Playlists
+                        && self.library.selected_album.as_ref().map(|a| &a.id) == Some(id)
+                    {
+                        self.action_tx.send(Action::LoadPlaylist(id.clone()))?;
+                    }
+                }
+            }
+            Err(e) => {
+                self.toasts
+                    .error(format!("Failed to add {} to \"{}\": {}", songs, name, e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Toggle star on the album whose songs are shown. The change shows at
+    /// once and is undone if the server refuses it.
+    fn toggle_album_star(&mut self) {
+        let (Some(album), Some(client)) =
+            (self.library.selected_album.clone(), self.client.clone())
+        else {
+            return;
+        };
+        let mut starred = album.clone();
+        starred.starred = match album.starred {
+            Some(_) => None,
+            None => Some(chrono::Utc::now().to_rfc3339()),
+        };
+        self.show_album_star(&starred);
+
+        let action_tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let result = if album.starred.is_some() {
+                client.unstar(None, Some(&album.id), None).await
+            } else {
+                client.star(None, Some(&album.id), None).await
+            };
+            if let Err(e) = result {
+                let _ = action_tx.send(Action::StarFailed(
+                    Starred::Album(Box::new(album)),
+                    e.to_string(),
+                ));
+            }
+        });
+    }
+
+    /// Show an album's star state everywhere it is listed.
+    fn show_album_star(&mut self, starred: &Album) {
+        let albums = self
+            .library
+            .selected_album
+            .iter_mut()
+            .chain(self.library.albums.iter_mut())
+            .chain(self.library.artist_albums.iter_mut())
+            .chain(self.library.genre_albums.iter_mut());
+        for album in albums.filter(|album| album.id == starred.id) {
+            album.starred = starred.starred.clone();
+        }
+        self.library.patch_favorite_albums(starred);
+    }
+
+    /// Toggle star on the current song (from now playing, library, queue, or
+    /// search). The change shows at once and is undone if the server refuses it.
+    fn toggle_star(&mut self) {
         // Determine which song to star based on context
-        let song_info: Option<(String, bool)> = if self.search.active {
+        let song: Option<&Song> = if self.show_track_info {
+            // Track info popup - the song it shows
+            None
+        } else if self.search.active {
             // Search view - get selected song
-            self.search
-                .selected_song()
-                .map(|s| (s.id.clone(), s.starred.is_some()))
-        } else if self.focus == 1 {
+            self.search.selected_song()
+        } else if self.focus == Focus::Queue {
             // Queue view - get selected song
-            self.queue
-                .selected_song()
-                .map(|s| (s.id.clone(), s.starred.is_some()))
-        } else if self.focus == 0 {
+            self.queue.selected_song()
+        } else if self.focus == Focus::Library {
             // Library view - check if we're viewing songs
             match self.library.tab {
-                Tab::Songs => self
-                    .library
-                    .selected_song_item()
-                    .map(|s| (s.id.clone(), s.starred.is_some())),
-                Tab::Favorites if self.library.favorites_section == 2 => self
-                    .library
-                    .selected_favorite_song()
-                    .map(|s| (s.id.clone(), s.starred.is_some())),
+                Tab::Songs => self.library.selected_song_item(),
+                Tab::Favorites if self.library.favorites_section == 2 => {
+                    self.library.selected_favorite_song()
+                }
+                Tab::History => self.library.selected_history_item().map(|e| &e.song),
+                Tab::Genres if self.library.view_depth == 1 && self.library.genre_section == 1 => {
+                    self.library.selected_genre_song()
+                }
                 _ if self.library.view_depth > 0 => {
                     // Album/playlist song view
                     self.library
                         .album_songs_state
                         .selected()
                         .and_then(|i| self.library.album_songs.get(i))
-                        .map(|s| (s.id.clone(), s.starred.is_some()))
                 }
                 _ => None,
             }
@@ -1682,79 +4444,120 @@ impl App {
         };
 
         // Fall back to now playing if no song selected in current context
-        let song_info = song_info.or_else(|| {
-            self.now_playing
-                .current_song
-                .as_ref()
-                .map(|s| (s.id.clone(), s.starred.is_some()))
-        });
-
-        if let Some((song_id, is_starred)) = song_info {
-            if let Some(client) = &self.client {
-                let result = if is_starred {
-                    client.unstar(Some(&song_id), None, None).await
-                } else {
-                    client.star(Some(&song_id), None, None).await
-                };
-
-                match result {
-                    Ok(()) => {
-                        let new_starred = if is_starred {
-                            None
-                        } else {
-                            Some(chrono::Utc::now().to_rfc3339())
-                        };
-
-                        // Update local state in all places where this song might appear
-                        if let Some(song) = self.now_playing.current_song.as_mut() {
-                            if song.id == song_id {
-                                song.starred = new_starred.clone();
-                            }
-                        }
-
-                        // Update in library songs
-                        for song in &mut self.library.songs {
-                            if song.id == song_id {
-                                song.starred = new_starred.clone();
-                            }
-                        }
-
-                        // Update in album_songs
-                        for song in &mut self.library.album_songs {
-                            if song.id == song_id {
-                                song.starred = new_starred.clone();
-                            }
-                        }
+        let song = song.or(self.now_playing.current_song.as_ref()).cloned();
+        let (Some(song), Some(client)) = (song, self.client.clone()) else {
+            return;
+        };
+        let mut starred = song.clone();
+        starred.starred = match song.starred {
+            Some(_) => None,
+            None => Some(chrono::Utc::now().to_rfc3339()),
+        };
+        self.show_song_star(&starred);
 
-                        // Update in queue
-                        for song in &mut self.queue.songs {
-                            if song.id == song_id {
-                                song.starred = new_starred.clone();
-                            }
-                        }
+        let action_tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let result = if song.starred.is_some() {
+                client.unstar(Some(&song.id), None, None).await
+            } else {
+                client.star(Some(&song.id), None, None).await
+            };
+            if let Err(e) = result {
+                let _ = action_tx.send(Action::StarFailed(
+                    Starred::Song(Box::new(song)),
+                    e.to_string(),
+                ));
+            }
+        });
+    }
 
-                        // Update in search results
-                        for song in &mut self.search.songs {
-                            if song.id == song_id {
-                                song.starred = new_starred.clone();
-                            }
-                        }
+    /// Show a song's star state everywhere it is listed.
+    fn show_song_star(&mut self, starred: &Song) {
+        self.update_song(&starred.id, |song| song.starred = starred.starred.clone());
+        self.library.patch_favorite_songs(starred);
+    }
 
-                        // Refresh favorites list to reflect the change
-                        self.action_tx.send(Action::LoadFavorites)?;
-                    }
-                    Err(e) => {
-                        let action = if is_starred { "unstar" } else { "star" };
-                        self.error_message = Some(format!("Failed to {} song: {}", action, e));
-                    }
+    /// Follow the server going away and coming back. While it is away, it is
+    /// pinged with growing delays and errors stay out of the toasts.
+    fn check_connection(&mut self) {
+        let Some(client) = &self.client else {
+            return;
+        };
+        let now = Instant::now();
+        match (self.connection, client.is_reachable()) {
+            (Connection::Online, false) => {
+                tracing::warn!("Lost connection to the server");
+                self.connection = Connection::Reconnecting {
+                    attempt: 0,
+                    next_try: now + RECONNECT_DELAY,
+                };
+            }
+            (Connection::Reconnecting { .. }, true) => {
+                tracing::info!("Reconnected to the server");
+                self.connection = Connection::Online;
+                self.toasts.info("Reconnected to the server");
+                if std::mem::take(&mut self.refresh_on_reconnect) {
+                    let _ = self.action_tx.send(Action::RefreshLibrary);
                 }
             }
+            (Connection::Reconnecting { attempt, next_try }, false) if now >= next_try => {
+                // A successful ping marks the client reachable again
+                let client = client.clone();
+                tokio::spawn(async move {
+                    let _ = client.ping().await;
+                });
+                let attempt = attempt + 1;
+                self.connection = Connection::Reconnecting {
+                    attempt,
+                    next_try: now
+                        + (RECONNECT_DELAY * 2u32.pow(attempt.min(5))).min(MAX_RECONNECT_DELAY),
+                };
+            }
+            _ => {}
+        }
+        self.toasts.quiet_errors = self.connection != Connection::Online;
+    }
+
+    /// Rate the song now playing.
+    async fn rate_playing_track(&mut self, rating: u8) -> Result<()> {
+        let (Some(client), Some(song)) = (&self.client, &self.now_playing.current_song) else {
+            return Ok(());
+        };
+        let song_id = song.id.clone();
+        match client.set_rating(&song_id, rating).await {
+            Ok(()) => {
+                let rating = (rating > 0).then_some(i32::from(rating));
+                self.update_song(&song_id, |song| song.user_rating = rating);
+            }
+            Err(e) => self.toasts.error(format!("Failed to rate song: {}", e)),
         }
         Ok(())
     }
 
+    /// Apply a change to a song everywhere it is shown.
+    fn update_song(&mut self, id: &str, update: impl Fn(&mut Song)) {
+        let songs = self
+            .now_playing
+            .current_song
+            .iter_mut()
+            .chain(self.library.songs.iter_mut())
+            .chain(self.library.history.iter_mut().map(|entry| &mut entry.song))
+            .chain(self.library.genre_songs.iter_mut())
+            .chain(self.library.favorites_songs.iter_mut())
+            .chain(self.library.album_songs.iter_mut())
+            .chain(self.queue.songs.iter_mut())
+            .chain(self.search.songs.iter_mut())
+            .chain(self.library_songs.iter_mut().flatten());
+        for song in songs.filter(|song| song.id == id) {
+            update(song);
+        }
+    }
+
     /// Scrobble the current song.
     async fn scrobble(&mut self) -> Result<()> {
+        if self.user.as_ref().is_some_and(|u| !u.has(Role::Scrobble)) {
+            return Ok(());
+        }
         if let Some(song) = self.now_playing.current_song.as_ref() {
             if let Some(client) = &self.client {
                 tracing::info!("Scrobbling: {}", song.title);
@@ -1777,7 +4580,7 @@ impl App {
             (self.now_playing.position + delta_secs as u32).min(self.now_playing.duration)
         };
 
-        self.now_playing.position = new_pos;
+        self.now_playing.set_position(new_pos);
 
         if let Some(player) = &self.player {
             player.seek(Duration::from_secs(new_pos as u64))?;
@@ -1786,44 +4589,379 @@ impl App {
         Ok(())
     }
 
-    /// Load album art for a cover art ID.
-    async fn load_album_art(&mut self, id: &str) -> Result<()> {
-        if let Some(client) = &self.client {
-            let url = client.cover_art_url(id, Some(300));
-            let id_owned = id.to_string();
+    /// Fetch, decode and scale album art in the background. Loads of covers
+    /// no longer shown are cancelled, so skipping through tracks quickly
+    /// doesn't leave a backlog of stale downloads.
+    fn load_album_art(&mut self, id: String) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        if self.now_playing.picker.is_none() || !self.config.ui.show_album_art {
+            return;
+        }
+
+        let mut wanted = Vec::new();
+        if self.now_playing.album_art.is_none() {
+            wanted.extend(self.now_playing.album_art_id.as_deref());
+        }
+        wanted.extend(self.library.album_art_needed());
+        self.album_art_loads.retain(|load_id, load| {
+            let keep = wanted.contains(&load_id.as_str());
+            if !keep {
+                load.abort();
+            }
+            keep
+        });
+        if !wanted.contains(&id.as_str()) || self.album_art_loads.contains_key(&id) {
+            return;
+        }
 
-            // Fetch in background
-            match reqwest::get(&url).await {
-                Ok(response) => {
+        let action_tx = self.action_tx.clone();
+        let load_id = id.clone();
+        let size = self.config.ui.album_art_size;
+        let task = tokio::spawn(async move {
+            let url = client.cover_art_url(&id, Some(size));
+            let bytes = match client.fetch(&url).await {
+                Ok(response) => response.bytes().await,
+                Err(e) => Err(e),
+            };
+            let image = match bytes {
+                Ok(bytes) => tokio::task::spawn_blocking(move || decode_album_art(&bytes, size))
+                    .await
+                    .ok()
+                    .flatten(),
+                Err(e) => {
+                    tracing::warn!("Failed to load album art: {}", e);
+                    None
+                }
+            };
+            let _ = action_tx.send(Action::AlbumArtLoaded(id, image.map(Box::new)));
+        });
+        self.album_art_loads.insert(load_id, task.abort_handle());
+    }
+
+    /// Load a user's avatar.
+    async fn load_avatar(&mut self, username: String) -> Result<()> {
+        if let Some(client) = &self.client {
+            let url = client.avatar_url(&username);
+
+            match client.fetch(&url).await {
+                // Servers answer with a JSON error when the user has no avatar
+                Ok(response)
+                    if response
+                        .headers()
+                        .get(reqwest::header::CONTENT_TYPE)
+                        .and_then(|v| v.to_str().ok())
+                        .is_some_and(|v| v.starts_with("image/")) =>
+                {
                     if let Ok(bytes) = response.bytes().await {
                         self.action_tx
-                            .send(Action::AlbumArtLoaded(id_owned, bytes.to_vec()))?;
+                            .send(Action::AvatarLoaded(username, bytes.to_vec()))?;
+                    } else {
+                        self.avatars.mark_missing(username);
                     }
                 }
+                Ok(_) => self.avatars.mark_missing(username),
                 Err(e) => {
-                    tracing::warn!("Failed to load album art: {}", e);
+                    tracing::warn!("Failed to load avatar: {}", e);
+                    self.avatars.mark_missing(username);
                 }
             }
         }
         Ok(())
     }
 
-    /// Load lyrics for a song.
+    /// Update the visualizer bars from what is playing, if they are on screen.
+    fn update_visualizer(&mut self) {
+        let shown = match self.config.ui.visualizer {
+            VisualizerMode::Off => false,
+            VisualizerMode::Bar => !self.fullscreen,
+            VisualizerMode::Fullscreen => self.fullscreen,
+        };
+        if !shown {
+            return;
+        }
+        match &self.player {
+            Some(player) if self.now_playing.state == PlayerState::Playing => {
+                // Show what is being heard, like the lyrics
+                let latency = Duration::from_millis(self.config.player.audio_latency_ms as u64);
+                let levels = player.spectrum(self.visualizer.bar_count(), latency);
+                self.visualizer.update(&levels);
+            }
+            _ => self.visualizer.decay(),
+        }
+    }
+
+    /// Ask the server what everyone is playing, for the listeners popup.
+    fn refresh_listeners(&mut self) {
+        let Some(client) = self.client.clone() else {
+            self.listeners.error = Some(String::from("not connected"));
+            return;
+        };
+        if self.listeners.loading {
+            return;
+        }
+        self.listeners.loading = true;
+
+        let action_tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let action = match client.get_now_playing().await {
+                Ok(entries) => Action::ListenersLoaded(entries),
+                Err(e) => Action::ListenersFailed(e.to_string()),
+            };
+            let _ = action_tx.send(action);
+        });
+    }
+
+    /// Open the info popup of the open album, fetching its notes and large
+    /// cover if they aren't there from last time.
+    fn show_album_info(&mut self) {
+        let Some(album) = self.library.selected_album.clone() else {
+            return;
+        };
+        if !self.album_info.open(album.clone()) {
+            return;
+        }
+        let Some(client) = self.client.clone() else {
+            self.album_info.loading = false;
+            self.album_info.error = Some(String::from("not connected"));
+            return;
+        };
+
+        let show_cover = self.now_playing.picker.is_some() && self.config.ui.show_album_art;
+        let action_tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let info = client.get_album_info(&album.id).await;
+            // Last.fm's cover if there is one, the server's otherwise
+            let cover_url = info
+                .as_ref()
+                .ok()
+                .and_then(|info| info.large_image_url.clone())
+                .filter(|url| !url.is_empty())
+                .or_else(|| {
+                    let id = album.cover_art.as_deref()?;
+                    Some(client.cover_art_url(id, Some(LARGE_COVER_SIZE)))
+                });
+            let _ = action_tx.send(match info {
+                Ok(info) => Action::AlbumInfoLoaded(album.id.clone(), info),
+                Err(e) => Action::AlbumInfoFailed(album.id.clone(), e.to_string()),
+            });
+
+            let Some(url) = cover_url.filter(|_| show_cover) else {
+                return;
+            };
+            let image = match client.fetch(&url).await {
+                Ok(response) => match response.bytes().await {
+                    Ok(bytes) => tokio::task::spawn_blocking(move || {
+                        decode_album_art(&bytes, LARGE_COVER_SIZE)
+                    })
+                    .await
+                    .ok()
+                    .flatten(),
+                    Err(e) => {
+                        tracing::warn!("Failed to load album cover: {}", e);
+                        None
+                    }
+                },
+                Err(e) => {
+                    tracing::warn!("Failed to load album cover: {}", e);
+                    None
+                }
+            };
+            let _ = action_tx.send(Action::AlbumInfoCover(album.id, image.map(Box::new)));
+        });
+    }
+
+    /// Check whether the account may do something, explaining in a toast
+    /// if it may not. Everything is allowed until the roles are known.
+    fn allows(&mut self, role: Role) -> bool {
+        let allowed = self.user.as_ref().is_none_or(|user| user.has(role));
+        if !allowed {
+            self.toasts.warn(role.denied());
+        }
+        allowed
+    }
+
+    /// Ask the server about itself for the server status popup.
+    fn refresh_server_status(&mut self) -> Result<()> {
+        let Some(client) = self.client.clone() else {
+            self.server_status.error = Some(String::from("not connected"));
+            return Ok(());
+        };
+        if self.server_status.loading {
+            return Ok(());
+        }
+        self.server_status.loading = true;
+
+        let username = self.config.server.username.clone();
+        let action_tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let start = Instant::now();
+            let info = match client.server_info().await {
+                Ok(info) => info,
+                Err(e) => {
+                    let _ = action_tx.send(Action::ServerStatusFailed(e.to_string()));
+                    return;
+                }
+            };
+            let latency = start.elapsed();
+
+            let extensions = if info.open_subsonic {
+                let mut client = client.clone();
+                client
+                    .get_open_subsonic_extensions()
+                    .await
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            let status = ServerStatus {
+                info,
+                auth_mode: client.auth_mode(),
+                latency,
+                extensions,
+                license: client.get_license().await.ok(),
+                scan: client.get_scan_status().await.ok(),
+                user: match username.is_empty() {
+                    true => None,
+                    false => client.get_user(&username).await.ok(),
+                },
+            };
+            let _ = action_tx.send(Action::ServerStatusLoaded(Box::new(status)));
+        });
+        Ok(())
+    }
+
+    /// Get the point of the track, in seconds, under column `x` of the progress
+    /// bar. Columns past either end of the bar count as its first or last column.
+    fn bar_position(&self, x: u16, bar: Rect) -> u32 {
+        let offset = x.saturating_sub(bar.x).min(bar.width.saturating_sub(1));
+        let ratio = offset as f64 / bar.width as f64;
+        (ratio * self.now_playing.duration as f64) as u32
+    }
+
+    /// Set the volume matching column `x` of the volume bar. Columns left of
+    /// the bar mute, columns right of it give full volume.
+    fn set_volume_from_bar(&mut self, x: u16, bar: Rect) -> Result<()> {
+        let new_volume = if x < bar.x {
+            0
+        } else {
+            let offset = (x - bar.x).min(bar.width - 1);
+            let max = self.config.player.max_volume as u32;
+            (((offset as u32 + 1) * max) / bar.width as u32).min(max) as u8
+        };
+        self.set_volume(new_volume)
+    }
+
+    /// Seek to the point of the track matching a click on the progress bar.
+    fn seek_to_click(&mut self, x: u16, bar: Rect) -> Result<()> {
+        self.now_playing.seek_preview = None;
+        let seek_pos = self.bar_position(x, bar);
+        if let Some(player) = &self.player {
+            player.seek(std::time::Duration::from_secs(seek_pos as u64))?;
+            self.now_playing.set_position(seek_pos);
+        }
+        Ok(())
+    }
+
+    /// Load lyrics for a song, from the on-disk cache when they were fetched before.
     async fn load_lyrics(&mut self, song_id: &str) -> Result<()> {
+        let song_id_owned = song_id.to_string();
+        if let Some(cached) = crate::lyrics_cache::get(song_id).filter(|l| !l.is_empty()) {
+            self.action_tx
+                .send(Action::LyricsLoaded(song_id_owned, cached))?;
+            return Ok(());
+        }
+
         if let Some(client) = &self.client {
-            let song_id_owned = song_id.to_string();
             match client.get_lyrics_by_song_id(song_id).await {
-                Ok(lyrics) => {
+                Ok(lyrics) if !lyrics.is_empty() => {
+                    if let Err(e) = crate::lyrics_cache::put(song_id, &lyrics) {
+                        tracing::warn!("Failed to cache lyrics: {}", e);
+                    }
                     self.action_tx
                         .send(Action::LyricsLoaded(song_id_owned, lyrics))?;
                 }
+                Ok(_) => self.load_external_lyrics(song_id_owned)?,
                 Err(e) => {
                     tracing::warn!("Failed to load lyrics: {}", e);
-                    self.action_tx
-                        .send(Action::LyricsLoaded(song_id_owned, vec![]))?;
+                    self.load_external_lyrics(song_id_owned)?;
                 }
             }
         }
         Ok(())
     }
+
+    /// Export the lyrics shown for the current song as an .lrc file.
+    fn export_lyrics(&mut self) {
+        let song = self
+            .now_playing
+            .current_song
+            .as_ref()
+            .filter(|song| self.lyrics.song_id.as_ref() == Some(&song.id));
+        let (Some(song), Some(lyrics)) = (song, &self.lyrics.lyrics) else {
+            return;
+        };
+
+        let result = self.config.lyrics_export_dir().and_then(|dir| {
+            crate::lyrics_cache::export(&dir, song, lyrics, self.lyrics.delay_ms())
+        });
+        match result {
+            Ok(path) => self
+                .toasts
+                .info(format!("Saved lyrics to {}", path.display())),
+            Err(e) => self.toasts.error(format!("Failed to export lyrics: {}", e)),
+        }
+    }
+
+    /// Look up lyrics the server doesn't have with the configured external providers.
+    /// Results, including finding nothing, are cached so each song is looked up once.
+    fn load_external_lyrics(&self, song_id: String) -> Result<()> {
+        let providers = self.config.lyrics.providers.clone();
+        let song = self
+            .now_playing
+            .current_song
+            .clone()
+            .filter(|song| song.id == song_id);
+
+        let Some(song) = song.filter(|_| !providers.is_empty()) else {
+            self.action_tx.send(Action::LyricsLoaded(song_id, vec![]))?;
+            return Ok(());
+        };
+        if let Some(cached) = crate::lyrics_cache::get(&song_id) {
+            self.action_tx.send(Action::LyricsLoaded(song_id, cached))?;
+            return Ok(());
+        }
+
+        let action_tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let mut found = Vec::new();
+            let mut failed = false;
+            for provider in providers {
+                let result = match provider {
+                    LyricsProvider::Lrclib => crate::client::lrclib::fetch(&song).await,
+                };
+                match result {
+                    Ok(Some(lyrics)) => {
+                        found.push(lyrics);
+                        break;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::warn!("Failed to fetch lyrics from {:?}: {}", provider, e);
+                        failed = true;
+                    }
+                }
+            }
+
+            // Don't remember a miss that may just have been a network error
+            if !found.is_empty() || !failed {
+                if let Err(e) = crate::lyrics_cache::put(&song_id, &found) {
+                    tracing::warn!("Failed to cache lyrics: {}", e);
+                }
+            }
+            let _ = action_tx.send(Action::LyricsLoaded(song_id, found));
+        });
+        Ok(())
+    }
 }
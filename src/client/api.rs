@@ -1,11 +1,17 @@
 //! OpenSubsonic API client implementation.
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
 use color_eyre::Result;
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::auth::Auth;
 use super::models::*;
+use super::tls::TlsOptions;
 
 /// API client errors.
 #[derive(Debug, Error)]
@@ -19,10 +25,110 @@ pub enum ApiClientError {
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
 
-    #[error("Server returned failed status: {message}")]
+    #[error("Invalid response: HTTP {status}: {body}")]
+    Http { status: u16, body: String },
+
+    #[error("Wrong username, password or API key (check the [server] config)")]
+    WrongCredentials,
+
+    #[error("Server doesn't support token authentication (set server.legacy_auth = true)")]
+    TokenAuthUnsupported,
+
+    #[error("Not allowed: {0} (ask the server admin for this permission)")]
+    NotAuthorized(String),
+
+    #[error("Not found: {0} (it may have been removed, press R to refresh)")]
+    NotFound(String),
+
+    #[error("Server error {code}: {message}")]
     ServerError { code: i32, message: String },
 }
 
+impl ApiClientError {
+    /// Map a Subsonic error code to an error, keeping the server's message
+    /// where it says more than the code.
+    fn from_code(code: i32, message: String) -> Self {
+        match code {
+            // 44 is an invalid API key (OpenSubsonic)
+            40 | 44 => Self::WrongCredentials,
+            41 => Self::TokenAuthUnsupported,
+            50 => Self::NotAuthorized(message),
+            70 => Self::NotFound(message),
+            _ => Self::ServerError { code, message },
+        }
+    }
+
+    /// Check if the request never reached the server, so it is safe to send again.
+    fn is_connect(&self) -> bool {
+        matches!(self, Self::Request(e) if e.is_connect())
+    }
+
+    /// Check if the server couldn't be reached or was briefly unavailable,
+    /// so trying again later may work.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Request(e) => e.is_connect() || e.is_timeout(),
+            Self::Http { status, .. } => matches!(status, 429 | 502 | 503 | 504),
+            _ => false,
+        }
+    }
+}
+
+/// Check if sending a request to an endpoint twice does no harm, so it can be
+/// retried even if the first attempt may have reached the server.
+fn is_idempotent(endpoint: &str) -> bool {
+    endpoint.starts_with("get")
+        || endpoint.starts_with("search")
+        || matches!(endpoint, "ping" | "star" | "unstar" | "setRating")
+}
+
+/// Wait before the first retry of a failed request, doubled for each one after.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest wait between retries of a failed request.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(8);
+
+/// Bitrates (in kbps) a track is offered at over HLS.
+const HLS_BITRATES: [u32; 4] = [64, 128, 192, 320];
+
+/// Optional limits for `getRandomSongs`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RandomFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub genre: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_year: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_year: Option<i32>,
+}
+
+impl RandomFilter {
+    /// Short description for panel titles, `None` when nothing is filtered.
+    pub fn describe(&self) -> Option<String> {
+        let years = match (self.from_year, self.to_year) {
+            (Some(from), Some(to)) if from == to => Some(from.to_string()),
+            (Some(from), Some(to)) => Some(format!("{}–{}", from, to)),
+            _ => None,
+        };
+        match (&self.genre, years) {
+            (Some(genre), Some(years)) => Some(format!("{}, {}", genre, years)),
+            (Some(genre), None) => Some(genre.clone()),
+            (None, years) => years,
+        }
+    }
+}
+
+/// What a server reports about itself in every response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerInfo {
+    /// Subsonic API version
+    pub api_version: String,
+    /// Server software, e.g. "navidrome" (OpenSubsonic servers)
+    pub server_type: Option<String>,
+    pub server_version: Option<String>,
+    pub open_subsonic: bool,
+}
+
 /// OpenSubsonic API client.
 #[derive(Debug, Clone)]
 pub struct SubsonicClient {
@@ -46,6 +152,15 @@ pub struct SubsonicClient {
 
     /// Server extensions (if OpenSubsonic)
     extensions: Vec<String>,
+
+    /// Server software, e.g. "navidrome", if it said
+    server_type: Option<String>,
+
+    /// Times to retry a request failing on the way
+    retries: u32,
+
+    /// Whether the last request got through, shared by the clones
+    reachable: Arc<AtomicBool>,
 }
 
 impl SubsonicClient {
@@ -53,7 +168,7 @@ impl SubsonicClient {
     pub fn new(base_url: impl Into<String>, auth: Auth) -> Self {
         Self {
             client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
+                .timeout(Duration::from_secs(30))
                 .build()
                 .unwrap_or_else(|_| Client::new()),
             base_url: base_url.into().trim_end_matches('/').to_string(),
@@ -62,13 +177,64 @@ impl SubsonicClient {
             api_version: String::from("1.16.1"),
             is_open_subsonic: false,
             extensions: Vec::new(),
+            server_type: None,
+            retries: 0,
+            reachable: Arc::new(AtomicBool::new(true)),
         }
     }
 
+    /// Set the connect and read timeouts, the certificates to trust and
+    /// present, and how many times to retry requests that fail on the way.
+    pub fn with_network(
+        mut self,
+        connect_timeout: Duration,
+        timeout: Duration,
+        retries: u32,
+        tls: &TlsOptions,
+    ) -> Self {
+        let builder = Client::builder()
+            .connect_timeout(connect_timeout)
+            .read_timeout(timeout);
+        self.client = tls.apply(builder).build().unwrap_or_else(|e| {
+            tracing::warn!("Failed to set up HTTP client: {}", e);
+            Client::new()
+        });
+        self.retries = retries;
+        self
+    }
+
+    /// Fetch a URL built by this client, such as cover art or an avatar,
+    /// with its connection settings.
+    pub async fn fetch(&self, url: &str) -> reqwest::Result<reqwest::Response> {
+        self.client.get(url).send().await
+    }
+
+    /// Check if the last request got through to the server.
+    pub fn is_reachable(&self) -> bool {
+        self.reachable.load(Ordering::Relaxed)
+    }
+
     /// Build the URL for an API endpoint with query parameters.
     fn build_url(&self, endpoint: &str, params: &[(&str, &str)]) -> String {
-        let mut url = format!("{}/rest/{}", self.base_url, endpoint);
+        self.build_url_with(endpoint, params, true)
+    }
+
+    /// Build the URL of an API endpoint, signed with the credentials only
+    /// when `signed` is set.
+    fn build_url_with(&self, endpoint: &str, params: &[(&str, &str)], signed: bool) -> String {
+        format!(
+            "{}?{}",
+            self.endpoint_url(endpoint),
+            self.query(params, signed)
+        )
+    }
 
+    fn endpoint_url(&self, endpoint: &str) -> String {
+        format!("{}/rest/{}", self.base_url, endpoint)
+    }
+
+    /// Build the encoded parameters of a request, for a URL or a form body.
+    fn query(&self, params: &[(&str, &str)], signed: bool) -> String {
         // Add common parameters
         let mut query_parts: Vec<String> = vec![
             format!("v={}", self.api_version),
@@ -77,8 +243,10 @@ impl SubsonicClient {
         ];
 
         // Add auth parameters
-        for (key, value) in self.auth.query_params() {
-            query_parts.push(format!("{}={}", key, urlencoding::encode(&value)));
+        if signed {
+            for (key, value) in self.auth.query_params() {
+                query_parts.push(format!("{}={}", key, urlencoding::encode(&value)));
+            }
         }
 
         // Add endpoint-specific parameters
@@ -86,9 +254,7 @@ impl SubsonicClient {
             query_parts.push(format!("{}={}", key, urlencoding::encode(value)));
         }
 
-        url.push('?');
-        url.push_str(&query_parts.join("&"));
-        url
+        query_parts.join("&")
     }
 
     /// Make a GET request to an API endpoint.
@@ -97,23 +263,94 @@ impl SubsonicClient {
         endpoint: &str,
         params: &[(&str, &str)],
     ) -> Result<T, ApiClientError> {
-        let url = self.build_url(endpoint, params);
+        self.get_body(endpoint, params)
+            .await?
+            .data
+            .ok_or_else(|| ApiClientError::InvalidResponse(String::from("Missing response data")))
+    }
+
+    /// Make a POST request to an API endpoint, with the parameters in a form
+    /// body rather than the URL, for requests carrying long lists of them.
+    async fn post<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T, ApiClientError> {
+        self.request_body(endpoint, params, true)
+            .await?
+            .data
+            .ok_or_else(|| ApiClientError::InvalidResponse(String::from("Missing response data")))
+    }
 
-        let response = self.client.get(&url).send().await?;
+    /// Make a GET request to an API endpoint, keeping the whole response body.
+    async fn get_body<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<ResponseBody<T>, ApiClientError> {
+        self.request_body(endpoint, params, false).await
+    }
+
+    /// Make a request to an API endpoint, keeping the whole response body.
+    /// Requests failing on the way are retried with increasing delays.
+    async fn request_body<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+        post: bool,
+    ) -> Result<ResponseBody<T>, ApiClientError> {
+        let mut attempt = 0;
+        loop {
+            let result = self.request_once(endpoint, params, post).await;
+            match &result {
+                Err(e) if e.is_transient() => {
+                    let retry = is_idempotent(endpoint) || e.is_connect();
+                    if retry && attempt < self.retries {
+                        tracing::debug!("Retrying {} after error: {}", endpoint, e);
+                        let delay = RETRY_DELAY.saturating_mul(2u32.saturating_pow(attempt));
+                        tokio::time::sleep(delay.min(MAX_RETRY_DELAY)).await;
+                        attempt += 1;
+                        continue;
+                    }
+                    self.reachable.store(false, Ordering::Relaxed);
+                }
+                _ => self.reachable.store(true, Ordering::Relaxed),
+            }
+            return result;
+        }
+    }
+
+    /// Make a single request to an API endpoint, as a form POST when `post`
+    /// is set.
+    async fn request_once<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+        post: bool,
+    ) -> Result<ResponseBody<T>, ApiClientError> {
+        let request = if post {
+            self.client
+                .post(self.endpoint_url(endpoint))
+                .header(
+                    reqwest::header::CONTENT_TYPE,
+                    "application/x-www-form-urlencoded",
+                )
+                .body(self.query(params, true))
+        } else {
+            self.client.get(self.build_url(endpoint, params))
+        };
+
+        let response = request.send().await?;
 
         // Check HTTP status before parsing - handles proxy errors, server issues, etc.
         let status = response.status();
         if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
-            return Err(ApiClientError::InvalidResponse(format!(
-                "HTTP {}: {}",
-                status.as_u16(),
-                if body.len() > 200 {
-                    &body[..200]
-                } else {
-                    &body
-                }
-            )));
+            return Err(ApiClientError::Http {
+                status: status.as_u16(),
+                // Cut by characters, as a byte cut could split one
+                body: body.chars().take(200).collect(),
+            });
         }
 
         let text = response.text().await?;
@@ -130,22 +367,19 @@ impl SubsonicClient {
         // Check for errors
         if parsed.subsonic_response.status != "ok" {
             if let Some(error) = parsed.subsonic_response.error {
-                return Err(ApiClientError::ServerError {
-                    code: error.code,
-                    message: error
+                return Err(ApiClientError::from_code(
+                    error.code,
+                    error
                         .message
                         .unwrap_or_else(|| String::from("Unknown error")),
-                });
+                ));
             }
             return Err(ApiClientError::InvalidResponse(String::from(
                 "Server returned failed status without error details",
             )));
         }
 
-        parsed
-            .subsonic_response
-            .data
-            .ok_or_else(|| ApiClientError::InvalidResponse(String::from("Missing response data")))
+        Ok(parsed.subsonic_response)
     }
 
     /// Get the streaming URL for a song.
@@ -153,6 +387,44 @@ impl SubsonicClient {
         self.build_url("stream", &[("id", id)])
     }
 
+    /// Get the streaming URL for a song without the credentials, safe to
+    /// hand out: whoever opens it has to sign in to the server.
+    pub fn public_stream_url(&self, id: &str) -> String {
+        self.build_url_with("stream", &[("id", id)], false)
+    }
+
+    /// Get the streaming URL for a song transcoded by the server to
+    /// `format`, e.g. "mp3".
+    pub fn transcoded_stream_url(&self, id: &str, format: &str) -> String {
+        self.build_url("stream", &[("id", id), ("format", format)])
+    }
+
+    /// Get the HLS playlist URL for a song, offering it at each of the
+    /// [`HLS_BITRATES`] up to `max_bitrate` (in kbps, 0 = no limit).
+    pub fn hls_url(&self, id: &str, max_bitrate: u32) -> String {
+        let bitrates: Vec<String> = HLS_BITRATES
+            .iter()
+            .filter(|&&bitrate| max_bitrate == 0 || bitrate <= max_bitrate)
+            .map(u32::to_string)
+            .collect();
+        let mut params = vec![("id", id)];
+        params.extend(bitrates.iter().map(|bitrate| ("bitRate", bitrate.as_str())));
+        self.build_url("hls.m3u8", &params)
+    }
+
+    /// Get a link to an album in the server's web interface, or to the
+    /// interface itself on servers whose links aren't known.
+    pub fn web_url(&self, album_id: Option<&str>) -> String {
+        let navidrome = self
+            .server_type
+            .as_deref()
+            .is_some_and(|server| server.eq_ignore_ascii_case("navidrome"));
+        match album_id {
+            Some(id) if navidrome => format!("{}/app/#/album/{}/show", self.base_url, id),
+            _ => self.base_url.clone(),
+        }
+    }
+
     /// Get the cover art URL for an item.
     pub fn cover_art_url(&self, id: &str, size: Option<u32>) -> String {
         let size_str;
@@ -165,6 +437,11 @@ impl SubsonicClient {
         self.build_url("getCoverArt", &params)
     }
 
+    /// Get the avatar image URL for a user.
+    pub fn avatar_url(&self, username: &str) -> String {
+        self.build_url("getAvatar", &[("username", username)])
+    }
+
     // =========================================================================
     // System endpoints
     // =========================================================================
@@ -175,6 +452,54 @@ impl SubsonicClient {
         Ok(())
     }
 
+    /// Ping the server, remembering what software it runs. Servers that
+    /// don't support token auth are retried with legacy password auth.
+    pub async fn connect(&mut self) -> Result<(), ApiClientError> {
+        let info = match (self.server_info().await, self.auth.legacy_fallback()) {
+            (Err(ApiClientError::TokenAuthUnsupported), Some(legacy)) => {
+                tracing::info!("Token auth not supported, falling back to legacy auth");
+                self.auth = legacy;
+                self.server_info().await?
+            }
+            (result, _) => result?,
+        };
+        self.server_type = info.server_type;
+        Ok(())
+    }
+
+    /// Short name of the authentication mode in use.
+    pub fn auth_mode(&self) -> &'static str {
+        self.auth.mode()
+    }
+
+    /// Check if the password is sent instead of a token.
+    pub fn uses_legacy_auth(&self) -> bool {
+        matches!(self.auth, Auth::Password { .. })
+    }
+
+    /// Ping the server and get what it reports about itself.
+    pub async fn server_info(&self) -> Result<ServerInfo, ApiClientError> {
+        let body: ResponseBody<PingResponse> = self.get_body("ping", &[]).await?;
+        Ok(ServerInfo {
+            api_version: body.version,
+            server_type: body.server_type,
+            server_version: body.server_version,
+            open_subsonic: body.open_subsonic.unwrap_or(false),
+        })
+    }
+
+    /// Get the server's license status.
+    pub async fn get_license(&self) -> Result<License, ApiClientError> {
+        let response: LicenseResponse = self.get("getLicense", &[]).await?;
+        Ok(response.license)
+    }
+
+    /// Get the status of the library scan.
+    pub async fn get_scan_status(&self) -> Result<ScanStatus, ApiClientError> {
+        let response: ScanStatusResponse = self.get("getScanStatus", &[]).await?;
+        Ok(response.scan_status)
+    }
+
     /// Check if the server supports OpenSubsonic and get extensions.
     pub async fn get_open_subsonic_extensions(
         &mut self,
@@ -207,18 +532,11 @@ impl SubsonicClient {
     // Browsing endpoints
     // =========================================================================
 
-    /// Get all artists.
-    pub async fn get_artists(&self) -> Result<Vec<Artist>, ApiClientError> {
+    /// Get all artists, grouped by their index letter.
+    pub async fn get_artists(&self) -> Result<Vec<ArtistIndex>, ApiClientError> {
         let response: ArtistsResponse = self.get("getArtists", &[]).await?;
 
-        let artists: Vec<Artist> = response
-            .artists
-            .index
-            .into_iter()
-            .flat_map(|idx| idx.artist)
-            .collect();
-
-        Ok(artists)
+        Ok(response.artists.index)
     }
 
     /// Get an artist by ID.
@@ -228,6 +546,20 @@ impl SubsonicClient {
         Ok((response.artist.artist, response.artist.album))
     }
 
+    /// Get an artist's biography and up to `count` similar artists from the
+    /// library.
+    pub async fn get_artist_info(
+        &self,
+        id: &str,
+        count: u32,
+    ) -> Result<ArtistInfo, ApiClientError> {
+        let count = count.to_string();
+        let response: ArtistInfoResponse = self
+            .get("getArtistInfo2", &[("id", id), ("count", &count)])
+            .await?;
+        Ok(response.artist_info2)
+    }
+
     /// Get an album by ID.
     pub async fn get_album(&self, id: &str) -> Result<(Album, Vec<Song>), ApiClientError> {
         let response: AlbumResponse = self.get("getAlbum", &[("id", id)]).await?;
@@ -235,6 +567,12 @@ impl SubsonicClient {
         Ok((response.album.album, response.album.song))
     }
 
+    /// Get an album's notes and links.
+    pub async fn get_album_info(&self, id: &str) -> Result<AlbumInfo, ApiClientError> {
+        let response: AlbumInfoResponse = self.get("getAlbumInfo2", &[("id", id)]).await?;
+        Ok(response.album_info)
+    }
+
     /// Get album list.
     pub async fn get_album_list(
         &self,
@@ -259,12 +597,58 @@ impl SubsonicClient {
         Ok(response.album_list2.album)
     }
 
-    /// Get random songs.
-    pub async fn get_random_songs(&self, size: Option<u32>) -> Result<Vec<Song>, ApiClientError> {
+    /// Get albums released between two years, inclusive.
+    /// The server lists them newest first when `from_year` is after `to_year`.
+    pub async fn get_albums_by_year(
+        &self,
+        from_year: i32,
+        to_year: i32,
+        size: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<Album>, ApiClientError> {
         let size_str = size.unwrap_or(50).to_string();
+        let offset_str = offset.unwrap_or(0).to_string();
+        let from_str = from_year.to_string();
+        let to_str = to_year.to_string();
 
-        let response: RandomSongsResponse =
-            self.get("getRandomSongs", &[("size", &size_str)]).await?;
+        let response: AlbumListResponse = self
+            .get(
+                "getAlbumList2",
+                &[
+                    ("type", "byYear"),
+                    ("fromYear", &from_str),
+                    ("toYear", &to_str),
+                    ("size", &size_str),
+                    ("offset", &offset_str),
+                ],
+            )
+            .await?;
+
+        Ok(response.album_list2.album)
+    }
+
+    /// Get random songs, optionally limited to a genre and year range.
+    pub async fn get_random_songs(
+        &self,
+        size: Option<u32>,
+        filter: &RandomFilter,
+    ) -> Result<Vec<Song>, ApiClientError> {
+        let size_str = size.unwrap_or(50).to_string();
+        let from_str = filter.from_year.map(|year| year.to_string());
+        let to_str = filter.to_year.map(|year| year.to_string());
+
+        let mut params = vec![("size", size_str.as_str())];
+        if let Some(genre) = &filter.genre {
+            params.push(("genre", genre));
+        }
+        if let Some(from) = &from_str {
+            params.push(("fromYear", from));
+        }
+        if let Some(to) = &to_str {
+            params.push(("toYear", to));
+        }
+
+        let response: RandomSongsResponse = self.get("getRandomSongs", &params).await?;
 
         Ok(response.random_songs.song)
     }
@@ -300,6 +684,30 @@ impl SubsonicClient {
         Ok(response.album_list2.album)
     }
 
+    /// Get songs in a genre.
+    pub async fn get_songs_by_genre(
+        &self,
+        genre: &str,
+        count: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<Song>, ApiClientError> {
+        let count_str = count.unwrap_or(100).to_string();
+        let offset_str = offset.unwrap_or(0).to_string();
+
+        let response: SongsByGenreResponse = self
+            .get(
+                "getSongsByGenre",
+                &[
+                    ("genre", genre),
+                    ("count", &count_str),
+                    ("offset", &offset_str),
+                ],
+            )
+            .await?;
+
+        Ok(response.songs_by_genre.song)
+    }
+
     /// Get starred (favorite) items.
     pub async fn get_starred(
         &self,
@@ -313,6 +721,18 @@ impl SubsonicClient {
         ))
     }
 
+    /// Get an account's details and roles.
+    pub async fn get_user(&self, username: &str) -> Result<User, ApiClientError> {
+        let response: UserResponse = self.get("getUser", &[("username", username)]).await?;
+        Ok(response.user)
+    }
+
+    /// Get what every user is playing right now.
+    pub async fn get_now_playing(&self) -> Result<Vec<NowPlayingEntry>, ApiClientError> {
+        let response: NowPlayingResponse = self.get("getNowPlaying", &[]).await?;
+        Ok(response.now_playing.entry)
+    }
+
     // =========================================================================
     // Playlist endpoints
     // =========================================================================
@@ -329,6 +749,36 @@ impl SubsonicClient {
         Ok((response.playlist.playlist, response.playlist.entry))
     }
 
+    /// Create a playlist holding the given songs.
+    pub async fn create_playlist(
+        &self,
+        name: &str,
+        song_ids: &[String],
+    ) -> Result<(), ApiClientError> {
+        let mut params = vec![("name", name)];
+        params.extend(song_ids.iter().map(|id| ("songId", id.as_str())));
+
+        let _: PingResponse = self.get("createPlaylist", &params).await?;
+        Ok(())
+    }
+
+    /// Add songs to a playlist and remove the entries at the given positions.
+    /// Sent as a form POST, as reordering can list every song of the playlist.
+    pub async fn update_playlist(
+        &self,
+        id: &str,
+        add: &[String],
+        remove: &[usize],
+    ) -> Result<(), ApiClientError> {
+        let remove: Vec<String> = remove.iter().map(|i| i.to_string()).collect();
+        let mut params = vec![("playlistId", id)];
+        params.extend(add.iter().map(|id| ("songIdToAdd", id.as_str())));
+        params.extend(remove.iter().map(|i| ("songIndexToRemove", i.as_str())));
+
+        let _: PingResponse = self.post("updatePlaylist", &params).await?;
+        Ok(())
+    }
+
     // =========================================================================
     // Search endpoints
     // =========================================================================
@@ -364,6 +814,84 @@ impl SubsonicClient {
         ))
     }
 
+    /// Search for artists only, starting at an offset.
+    pub async fn search_artists(
+        &self,
+        query: &str,
+        count: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<Artist>, ApiClientError> {
+        let count_str = count.unwrap_or(20).to_string();
+        let offset_str = offset.unwrap_or(0).to_string();
+
+        let response: SearchResponse = self
+            .get(
+                "search3",
+                &[
+                    ("query", query),
+                    ("artistCount", &count_str),
+                    ("artistOffset", &offset_str),
+                    ("albumCount", "0"),
+                    ("songCount", "0"),
+                ],
+            )
+            .await?;
+
+        Ok(response.search_result3.artist)
+    }
+
+    /// Search for albums only, starting at an offset.
+    pub async fn search_albums(
+        &self,
+        query: &str,
+        count: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<Album>, ApiClientError> {
+        let count_str = count.unwrap_or(20).to_string();
+        let offset_str = offset.unwrap_or(0).to_string();
+
+        let response: SearchResponse = self
+            .get(
+                "search3",
+                &[
+                    ("query", query),
+                    ("artistCount", "0"),
+                    ("albumCount", &count_str),
+                    ("albumOffset", &offset_str),
+                    ("songCount", "0"),
+                ],
+            )
+            .await?;
+
+        Ok(response.search_result3.album)
+    }
+
+    /// Search for songs only, starting at an offset.
+    pub async fn search_songs(
+        &self,
+        query: &str,
+        count: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<Song>, ApiClientError> {
+        let count_str = count.unwrap_or(20).to_string();
+        let offset_str = offset.unwrap_or(0).to_string();
+
+        let response: SearchResponse = self
+            .get(
+                "search3",
+                &[
+                    ("query", query),
+                    ("artistCount", "0"),
+                    ("albumCount", "0"),
+                    ("songCount", &count_str),
+                    ("songOffset", &offset_str),
+                ],
+            )
+            .await?;
+
+        Ok(response.search_result3.song)
+    }
+
     // =========================================================================
     // Media annotation endpoints
     // =========================================================================
@@ -412,6 +940,15 @@ impl SubsonicClient {
         Ok(())
     }
 
+    /// Rate a song from 1 to 5 stars, or clear its rating with 0.
+    pub async fn set_rating(&self, id: &str, rating: u8) -> Result<(), ApiClientError> {
+        let rating = rating.to_string();
+        let _: PingResponse = self
+            .get("setRating", &[("id", id), ("rating", &rating)])
+            .await?;
+        Ok(())
+    }
+
     /// Scrobble a song (report playback).
     pub async fn scrobble(&self, id: &str, submission: bool) -> Result<(), ApiClientError> {
         let submission_str = submission.to_string();
@@ -434,3 +971,39 @@ impl SubsonicClient {
         Ok(response.lyrics_list.structured_lyrics)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_policy() {
+        assert!(is_idempotent("getAlbum"));
+        assert!(is_idempotent("search3"));
+        assert!(is_idempotent("star"));
+        assert!(!is_idempotent("scrobble"));
+        assert!(!is_idempotent("updatePlaylist"));
+
+        let http = |status| ApiClientError::Http {
+            status,
+            body: String::new(),
+        };
+        assert!(http(503).is_transient());
+        assert!(!http(404).is_transient());
+        assert!(!ApiClientError::WrongCredentials.is_transient());
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let error = |code| ApiClientError::from_code(code, String::from("message"));
+        assert!(matches!(error(40), ApiClientError::WrongCredentials));
+        assert!(matches!(error(44), ApiClientError::WrongCredentials));
+        assert!(matches!(error(41), ApiClientError::TokenAuthUnsupported));
+        assert!(matches!(error(50), ApiClientError::NotAuthorized(_)));
+        assert!(matches!(error(70), ApiClientError::NotFound(_)));
+        assert!(matches!(
+            error(0),
+            ApiClientError::ServerError { code: 0, .. }
+        ));
+    }
+}
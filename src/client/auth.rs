@@ -3,8 +3,9 @@
 use md5::{Digest, Md5};
 use rand::Rng;
 
-/// Authentication credentials for API requests.
-#[derive(Debug, Clone)]
+/// Authentication credentials for API requests. `Debug` leaves the secrets
+/// out, so they can't end up in logs.
+#[derive(Clone)]
 #[allow(dead_code)]
 pub enum Auth {
     /// Token-based authentication (recommended for API 1.13.0+)
@@ -12,6 +13,8 @@ pub enum Auth {
         username: String,
         token: String,
         salt: String,
+        /// Kept to fall back to legacy auth on servers without token support
+        password: String,
     },
     /// API key authentication (OpenSubsonic extension)
     ApiKey { api_key: String },
@@ -30,6 +33,7 @@ impl Auth {
             username,
             token,
             salt,
+            password: password.to_string(),
         }
     }
 
@@ -40,9 +44,12 @@ impl Auth {
         }
     }
 
-    /// Create legacy password authentication.
-    #[allow(dead_code)]
+    /// Create legacy password authentication, logging a warning.
     pub fn from_legacy_password(username: impl Into<String>, password: impl Into<String>) -> Self {
+        tracing::warn!(
+            "Using legacy password auth: the password is sent, merely hex-encoded, \
+             in every request and stream or cover URL"
+        );
         Self::Password {
             username: username.into(),
             password: password.into(),
@@ -56,6 +63,7 @@ impl Auth {
                 username,
                 token,
                 salt,
+                ..
             } => vec![
                 ("u", username.clone()),
                 ("t", token.clone()),
@@ -63,11 +71,35 @@ impl Auth {
             ],
             Self::ApiKey { api_key } => vec![("apiKey", api_key.clone())],
             Self::Password { username, password } => {
-                vec![("u", username.clone()), ("p", password.clone())]
+                vec![("u", username.clone()), ("p", encode_password(password))]
             }
         }
     }
 
+    /// Legacy password auth with the same credentials, for servers that
+    /// don't support tokens (error 41). `None` for API keys.
+    ///
+    /// Legacy auth is no protection: the `enc:` hex is trivially reversed,
+    /// and the password goes out in every URL, stream and cover URLs
+    /// included, where anything that sees the URL can recover it.
+    pub fn legacy_fallback(&self) -> Option<Self> {
+        match self {
+            Self::Token {
+                username, password, ..
+            } => Some(Self::from_legacy_password(username, password)),
+            _ => None,
+        }
+    }
+
+    /// Short name of the authentication mode.
+    pub fn mode(&self) -> &'static str {
+        match self {
+            Self::Token { .. } => "token",
+            Self::ApiKey { .. } => "API key",
+            Self::Password { .. } => "legacy password",
+        }
+    }
+
     /// Regenerate the salt and token for token-based auth.
     /// This should be called before each request for maximum security.
     #[allow(dead_code)]
@@ -86,6 +118,28 @@ impl Auth {
     }
 }
 
+impl std::fmt::Debug for Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Token { username, .. } | Self::Password { username, .. } => f
+                .debug_struct("Auth")
+                .field("mode", &self.mode())
+                .field("username", username)
+                .finish_non_exhaustive(),
+            Self::ApiKey { .. } => f
+                .debug_struct("Auth")
+                .field("mode", &self.mode())
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Hex-encode a password for legacy auth, as the API expects.
+fn encode_password(password: &str) -> String {
+    let hex: String = password.bytes().map(|b| format!("{:02x}", b)).collect();
+    format!("enc:{}", hex)
+}
+
 /// Generate a random salt string.
 fn generate_salt() -> String {
     let mut rng = rand::thread_rng();
@@ -142,6 +196,23 @@ mod tests {
         assert_eq!(params[2].0, "s");
     }
 
+    #[test]
+    fn test_legacy_fallback() {
+        let auth = Auth::from_password("testuser", "sesame")
+            .legacy_fallback()
+            .unwrap();
+        assert_eq!(auth.mode(), "legacy password");
+        assert!(!format!("{:?}", auth).contains("sesame"));
+        assert_eq!(
+            auth.query_params(),
+            vec![
+                ("u", String::from("testuser")),
+                ("p", String::from("enc:736573616d65")),
+            ]
+        );
+        assert!(Auth::from_api_key("key").legacy_fallback().is_none());
+    }
+
     #[test]
     fn test_auth_api_key() {
         let auth = Auth::from_api_key("my-api-key");
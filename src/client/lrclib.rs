@@ -0,0 +1,161 @@
+//! Lyrics lookup on LRCLIB (lrclib.net), for songs the server has no lyrics for.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::models::{LyricLine, Song, StructuredLyrics};
+
+const SEARCH_URL: &str = "https://lrclib.net/api/search";
+
+/// Largest difference in length (seconds) for a result to count as the same recording.
+const DURATION_TOLERANCE: f64 = 3.0;
+
+/// A track returned by the LRCLIB search endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LrclibTrack {
+    duration: Option<f64>,
+    #[serde(default)]
+    instrumental: bool,
+    plain_lyrics: Option<String>,
+    synced_lyrics: Option<String>,
+}
+
+/// Look up lyrics for a song, preferring synced ones.
+/// Returns `None` if LRCLIB has nothing matching the song's length.
+pub async fn fetch(song: &Song) -> Result<Option<StructuredLyrics>, reqwest::Error> {
+    let Some(artist) = song.artist.as_deref() else {
+        return Ok(None);
+    };
+
+    let client = reqwest::Client::builder()
+        .user_agent(concat!(
+            "subsonic-tui/",
+            env!("CARGO_PKG_VERSION"),
+            " (https://github.com/imaviso/subsonic-tui)"
+        ))
+        .timeout(Duration::from_secs(10))
+        .build()?;
+
+    let mut query = vec![("track_name", song.title.as_str()), ("artist_name", artist)];
+    if let Some(album) = song.album.as_deref() {
+        query.push(("album_name", album));
+    }
+
+    let tracks: Vec<LrclibTrack> = client
+        .get(SEARCH_URL)
+        .query(&query)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // Results for other recordings of the song would be out of sync
+    let matching = tracks
+        .into_iter()
+        .filter(|track| match (track.duration, song.duration) {
+            (Some(found), Some(expected)) => (found - expected as f64).abs() <= DURATION_TOLERANCE,
+            _ => true,
+        });
+
+    let mut plain = None;
+    for track in matching.filter(|track| !track.instrumental) {
+        if let Some(synced) = track.synced_lyrics.as_deref().filter(|l| !l.is_empty()) {
+            let (line, offset) = parse_lrc(synced);
+            return Ok(Some(lyrics(song, true, offset, line)));
+        }
+        if plain.is_none() {
+            plain = track.plain_lyrics.filter(|l| !l.is_empty());
+        }
+    }
+
+    Ok(plain.map(|text| {
+        let line = text
+            .lines()
+            .map(|value| LyricLine {
+                start: None,
+                value: value.to_string(),
+            })
+            .collect();
+        lyrics(song, false, 0, line)
+    }))
+}
+
+fn lyrics(song: &Song, synced: bool, offset: i64, line: Vec<LyricLine>) -> StructuredLyrics {
+    StructuredLyrics {
+        display_artist: song.artist.clone(),
+        display_title: Some(song.title.clone()),
+        lang: String::from("und"),
+        synced,
+        offset,
+        line,
+    }
+}
+
+/// Parse LRC text into timed lines, sorted by time, and its `[offset:]` tag.
+/// A line with several timestamps is repeated at each of them.
+pub fn parse_lrc(text: &str) -> (Vec<LyricLine>, i64) {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+
+    for raw in text.lines() {
+        let mut rest = raw.trim();
+        let mut starts = Vec::new();
+
+        while let Some(tag) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+            let (tag, after) = tag;
+            if let Some(start) = parse_timestamp(tag) {
+                starts.push(start);
+            } else if let Some(value) = tag.strip_prefix("offset:") {
+                // Positive offsets show lyrics sooner, the same as `StructuredLyrics::offset`
+                offset = value.trim().parse().unwrap_or(0);
+            }
+            rest = after;
+        }
+
+        let value = rest.trim().to_string();
+        lines.extend(starts.into_iter().map(|start| LyricLine {
+            start: Some(start),
+            value: value.clone(),
+        }));
+    }
+
+    lines.sort_by_key(|line| line.start);
+    (lines, offset)
+}
+
+/// Parse an LRC timestamp (`mm:ss`, `mm:ss.xx` or `mm:ss.xxx`) into milliseconds.
+fn parse_timestamp(tag: &str) -> Option<i64> {
+    let (minutes, seconds) = tag.split_once(':')?;
+    let minutes: i64 = minutes.parse().ok()?;
+    let seconds: f64 = seconds.parse().ok()?;
+    Some(minutes * 60_000 + (seconds * 1000.0).round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lrc() {
+        let (lines, offset) = parse_lrc(
+            "[ar:Someone]\n[offset:+250]\n[00:12.50]First\n[01:02.345][00:05.00]Chorus\n\n",
+        );
+        let timed: Vec<(Option<i64>, &str)> = lines
+            .iter()
+            .map(|line| (line.start, line.value.as_str()))
+            .collect();
+
+        assert_eq!(offset, 250);
+        assert_eq!(
+            timed,
+            vec![
+                (Some(5_000), "Chorus"),
+                (Some(12_500), "First"),
+                (Some(62_345), "Chorus"),
+            ]
+        );
+    }
+}
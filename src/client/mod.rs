@@ -2,7 +2,11 @@
 
 pub mod api;
 pub mod auth;
+pub mod lrclib;
 pub mod models;
+pub mod paging;
+pub mod tls;
 
 pub use api::SubsonicClient;
 pub use auth::Auth;
+pub use tls::TlsOptions;
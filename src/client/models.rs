@@ -67,9 +67,8 @@ pub struct ArtistsData {
     pub ignored_articles: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
 pub struct ArtistIndex {
     pub name: String,
     #[serde(default)]
@@ -106,6 +105,25 @@ pub struct ArtistWithAlbums {
     pub album: Vec<Album>,
 }
 
+/// Response for getArtistInfo2 endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtistInfoResponse {
+    pub artist_info2: ArtistInfo,
+}
+
+/// Biography and similar artists, usually from Last.fm.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtistInfo {
+    /// Biography, as HTML
+    pub biography: Option<String>,
+    pub last_fm_url: Option<String>,
+    /// Artists like this one that are in the library, most alike first
+    #[serde(default)]
+    pub similar_artist: Vec<Artist>,
+}
+
 // ============================================================================
 // Albums
 // ============================================================================
@@ -149,6 +167,34 @@ pub struct Album {
     pub display_artist: Option<String>,
 }
 
+/// Artist compilations are credited to.
+pub const VARIOUS_ARTISTS: &str = "Various Artists";
+
+impl Album {
+    /// Whether the album is a compilation: flagged as one by the server, or
+    /// credited to various artists.
+    pub fn is_compilation(&self) -> bool {
+        self.is_compilation == Some(true)
+            || self
+                .artist
+                .as_deref()
+                .is_some_and(|artist| artist.eq_ignore_ascii_case(VARIOUS_ARTISTS))
+    }
+
+    /// Artist to show and group the album under: "Various Artists" for a
+    /// compilation, otherwise the album's display artist, or failing that
+    /// the artist the server credits it to.
+    pub fn album_artist(&self) -> Option<&str> {
+        if self.is_compilation() {
+            return Some(VARIOUS_ARTISTS);
+        }
+        self.display_artist
+            .as_deref()
+            .filter(|artist| !artist.is_empty())
+            .or(self.artist.as_deref())
+    }
+}
+
 /// Response for getAlbum endpoint.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -165,6 +211,26 @@ pub struct AlbumWithSongs {
     pub song: Vec<Song>,
 }
 
+/// Response for getAlbumInfo2 endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumInfoResponse {
+    pub album_info: AlbumInfo,
+}
+
+/// Notes and links for an album, usually from Last.fm.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumInfo {
+    /// Description, as HTML
+    pub notes: Option<String>,
+    pub music_brainz_id: Option<String>,
+    pub last_fm_url: Option<String>,
+    pub small_image_url: Option<String>,
+    pub medium_image_url: Option<String>,
+    pub large_image_url: Option<String>,
+}
+
 // ============================================================================
 // Songs
 // ============================================================================
@@ -327,7 +393,7 @@ pub struct ExtensionsResponse {
     pub open_subsonic_extensions: Vec<OpenSubsonicExtension>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[allow(dead_code)]
 pub struct OpenSubsonicExtension {
@@ -335,6 +401,128 @@ pub struct OpenSubsonicExtension {
     pub versions: Vec<i32>,
 }
 
+/// Response for getLicense endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LicenseResponse {
+    pub license: License,
+}
+
+/// Server license status.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct License {
+    pub valid: bool,
+    pub email: Option<String>,
+    pub license_expires: Option<String>,
+    pub trial_expires: Option<String>,
+}
+
+/// Response for getScanStatus endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanStatusResponse {
+    pub scan_status: ScanStatus,
+}
+
+/// Library scan status.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanStatus {
+    pub scanning: bool,
+    /// Number of files scanned so far
+    pub count: Option<i64>,
+    /// Time the last scan finished (OpenSubsonic servers)
+    pub last_scan: Option<String>,
+}
+
+/// Response for getUser endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserResponse {
+    pub user: User,
+}
+
+/// The account the client is signed in with. Roles the server leaves out
+/// count as allowed.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    pub username: String,
+    pub admin_role: Option<bool>,
+    pub stream_role: Option<bool>,
+    pub download_role: Option<bool>,
+    pub playlist_role: Option<bool>,
+    pub share_role: Option<bool>,
+    pub jukebox_role: Option<bool>,
+    pub scrobbling_enabled: Option<bool>,
+}
+
+/// Something an account may or may not be allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Stream,
+    Download,
+    Playlist,
+    Share,
+    Jukebox,
+    Scrobble,
+}
+
+impl Role {
+    pub const ALL: [Role; 6] = [
+        Self::Stream,
+        Self::Download,
+        Self::Playlist,
+        Self::Share,
+        Self::Jukebox,
+        Self::Scrobble,
+    ];
+
+    /// Short name, as listed in the server status popup.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Stream => "stream",
+            Self::Download => "download",
+            Self::Playlist => "playlists",
+            Self::Share => "share",
+            Self::Jukebox => "jukebox",
+            Self::Scrobble => "scrobble",
+        }
+    }
+
+    /// Explain why an action is unavailable.
+    pub fn denied(self) -> &'static str {
+        match self {
+            Self::Stream => "Your account isn't allowed to stream music",
+            Self::Download => "Your account isn't allowed to download songs",
+            Self::Playlist => "Your account isn't allowed to edit playlists",
+            Self::Share => "Your account isn't allowed to share",
+            Self::Jukebox => "Your account isn't allowed to use the jukebox",
+            Self::Scrobble => "Scrobbling is turned off for your account",
+        }
+    }
+}
+
+impl User {
+    /// Check whether the account may do something.
+    pub fn has(&self, role: Role) -> bool {
+        let allowed = match role {
+            Role::Stream => self.stream_role,
+            Role::Download => self.download_role,
+            Role::Playlist => self.playlist_role,
+            Role::Share => self.share_role,
+            Role::Jukebox => self.jukebox_role,
+            Role::Scrobble => self.scrobbling_enabled,
+        };
+        allowed.unwrap_or(true)
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.admin_role == Some(true)
+    }
+}
+
 // ============================================================================
 // Common Types
 // ============================================================================
@@ -411,6 +599,24 @@ pub struct RandomSongsData {
     pub song: Vec<Song>,
 }
 
+// ============================================================================
+// Songs by Genre
+// ============================================================================
+
+/// Response for getSongsByGenre endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SongsByGenreResponse {
+    pub songs_by_genre: SongsByGenreData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SongsByGenreData {
+    #[serde(default)]
+    pub song: Vec<Song>,
+}
+
 // ============================================================================
 // Starred (Favorites)
 // ============================================================================
@@ -433,6 +639,36 @@ pub struct StarredData {
     pub song: Vec<Song>,
 }
 
+// ============================================================================
+// Now Playing
+// ============================================================================
+
+/// Response for getNowPlaying endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NowPlayingResponse {
+    pub now_playing: NowPlayingData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NowPlayingData {
+    #[serde(default)]
+    pub entry: Vec<NowPlayingEntry>,
+}
+
+/// A song being played on the server, with who is playing it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NowPlayingEntry {
+    #[serde(flatten)]
+    pub song: Song,
+    pub username: String,
+    /// Minutes since the song started
+    #[serde(default)]
+    pub minutes_ago: i64,
+    pub player_name: Option<String>,
+}
+
 // ============================================================================
 // Lyrics (OpenSubsonic)
 // ============================================================================
@@ -452,7 +688,7 @@ pub struct LyricsList {
 }
 
 /// Structured lyrics with optional sync.
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StructuredLyrics {
     /// Display name for the lyrics source
@@ -471,7 +707,7 @@ pub struct StructuredLyrics {
 }
 
 /// A single line of lyrics.
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LyricLine {
     /// Start time in milliseconds (only for synced lyrics)
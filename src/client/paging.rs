@@ -0,0 +1,299 @@
+//! Paged song sources shared by infinite-scroll lists.
+
+use std::sync::{Arc, Mutex};
+
+use futures::future::BoxFuture;
+
+use super::api::{ApiClientError, RandomFilter, SubsonicClient};
+use super::models::Song;
+
+/// Number of songs requested per page.
+pub const PAGE_SIZE: u32 = 100;
+
+/// Fetch the next page once the selection is this close to the end of the list.
+pub const PREFETCH_MARGIN: usize = 20;
+
+/// A server-side list of songs that can be fetched a page at a time.
+pub trait SongSource: std::fmt::Debug + Send + Sync {
+    /// Fetch up to `size` songs starting at `offset`.
+    fn fetch_page<'a>(
+        &'a self,
+        client: &'a SubsonicClient,
+        offset: u32,
+        size: u32,
+    ) -> BoxFuture<'a, Result<Vec<Song>, ApiClientError>>;
+}
+
+/// Random songs. Every page is a fresh random sample, so the list never ends.
+#[derive(Debug, Default)]
+pub struct RandomSongs {
+    pub filter: RandomFilter,
+}
+
+impl SongSource for RandomSongs {
+    fn fetch_page<'a>(
+        &'a self,
+        client: &'a SubsonicClient,
+        _offset: u32,
+        size: u32,
+    ) -> BoxFuture<'a, Result<Vec<Song>, ApiClientError>> {
+        Box::pin(client.get_random_songs(Some(size), &self.filter))
+    }
+}
+
+/// Songs in a genre.
+#[derive(Debug)]
+pub struct GenreSongs {
+    pub genre: String,
+}
+
+impl SongSource for GenreSongs {
+    fn fetch_page<'a>(
+        &'a self,
+        client: &'a SubsonicClient,
+        offset: u32,
+        size: u32,
+    ) -> BoxFuture<'a, Result<Vec<Song>, ApiClientError>> {
+        Box::pin(client.get_songs_by_genre(&self.genre, Some(size), Some(offset)))
+    }
+}
+
+/// Starred songs. `getStarred2` is not paged, so the songs are fetched once
+/// and pages sliced from them.
+#[derive(Debug, Default)]
+pub struct StarredSongs {
+    songs: Mutex<Option<Arc<Vec<Song>>>>,
+}
+
+impl StarredSongs {
+    /// Page through starred songs already fetched.
+    pub fn with_songs(songs: Vec<Song>) -> Self {
+        Self {
+            songs: Mutex::new(Some(Arc::new(songs))),
+        }
+    }
+}
+
+impl SongSource for StarredSongs {
+    fn fetch_page<'a>(
+        &'a self,
+        client: &'a SubsonicClient,
+        offset: u32,
+        size: u32,
+    ) -> BoxFuture<'a, Result<Vec<Song>, ApiClientError>> {
+        Box::pin(async move {
+            let fetched = self.songs.lock().unwrap().clone();
+            let songs = match fetched {
+                Some(songs) => songs,
+                None => {
+                    let (_, _, songs) = client.get_starred().await?;
+                    let songs = Arc::new(songs);
+                    *self.songs.lock().unwrap() = Some(Arc::clone(&songs));
+                    songs
+                }
+            };
+            Ok(songs
+                .iter()
+                .skip(offset as usize)
+                .take(size as usize)
+                .cloned()
+                .collect())
+        })
+    }
+}
+
+/// Songs of the albums in a `getAlbumList2` list, album by album.
+/// Gives song lists the server has no endpoint for, such as recently added.
+#[derive(Debug)]
+pub struct AlbumListSongs {
+    list_type: &'static str,
+
+    /// Album offset the next page continues from
+    next_album: Mutex<u32>,
+}
+
+impl AlbumListSongs {
+    /// Albums requested at a time while filling a page.
+    const ALBUMS_PER_REQUEST: u32 = 10;
+
+    pub fn new(list_type: &'static str) -> Self {
+        Self {
+            list_type,
+            next_album: Mutex::new(0),
+        }
+    }
+}
+
+impl SongSource for AlbumListSongs {
+    fn fetch_page<'a>(
+        &'a self,
+        client: &'a SubsonicClient,
+        offset: u32,
+        size: u32,
+    ) -> BoxFuture<'a, Result<Vec<Song>, ApiClientError>> {
+        Box::pin(async move {
+            // Song offsets don't map to albums, so continue from where the last page stopped
+            let mut next_album = if offset == 0 {
+                0
+            } else {
+                *self.next_album.lock().unwrap()
+            };
+
+            let mut songs = Vec::new();
+            while songs.len() < size as usize {
+                let albums = client
+                    .get_album_list(
+                        self.list_type,
+                        Some(Self::ALBUMS_PER_REQUEST),
+                        Some(next_album),
+                    )
+                    .await?;
+                next_album += albums.len() as u32;
+                for album in &albums {
+                    let (_, album_songs) = client.get_album(&album.id).await?;
+                    songs.extend(album_songs);
+                }
+                if albums.len() < Self::ALBUMS_PER_REQUEST as usize {
+                    break;
+                }
+            }
+
+            *self.next_album.lock().unwrap() = next_album;
+            Ok(songs)
+        })
+    }
+}
+
+/// Songs matching a search query.
+#[derive(Debug)]
+pub struct SearchSongs {
+    pub query: String,
+}
+
+impl SongSource for SearchSongs {
+    fn fetch_page<'a>(
+        &'a self,
+        client: &'a SubsonicClient,
+        offset: u32,
+        size: u32,
+    ) -> BoxFuture<'a, Result<Vec<Song>, ApiClientError>> {
+        Box::pin(client.search_songs(&self.query, Some(size), Some(offset)))
+    }
+}
+
+/// Pagination cursor over a song source.
+#[derive(Debug, Clone)]
+pub struct PagedSongs {
+    /// Where pages come from
+    source: Arc<dyn SongSource>,
+
+    /// Offset of the next page to request
+    next_offset: u32,
+
+    /// Whether the source has no more songs
+    exhausted: bool,
+
+    /// Whether a page request is in flight
+    loading: bool,
+}
+
+impl PagedSongs {
+    /// Create a cursor at the start of a source.
+    pub fn new(source: Arc<dyn SongSource>) -> Self {
+        Self {
+            source,
+            next_offset: 0,
+            exhausted: false,
+            loading: false,
+        }
+    }
+
+    /// Get the source to fetch from.
+    pub fn source(&self) -> Arc<dyn SongSource> {
+        Arc::clone(&self.source)
+    }
+
+    /// Get the offset of the next page.
+    pub fn next_offset(&self) -> u32 {
+        self.next_offset
+    }
+
+    /// Whether the source has been read to the end.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Check if the next page should be fetched for the given list position.
+    pub fn should_prefetch(&self, loaded: usize, selected: Option<usize>) -> bool {
+        if self.loading || self.exhausted {
+            return false;
+        }
+        match selected {
+            Some(i) => i + PREFETCH_MARGIN >= loaded,
+            None => loaded == 0,
+        }
+    }
+
+    /// Mark a page request as started.
+    pub fn start_loading(&mut self) {
+        self.loading = true;
+    }
+
+    /// Record a fetched page. `added` is how many new songs were kept.
+    /// Returns false if the page does not belong to this cursor's current position.
+    pub fn page_loaded(&mut self, offset: u32, fetched: usize, added: usize) -> bool {
+        if offset != self.next_offset {
+            return false;
+        }
+        self.loading = false;
+        self.next_offset += fetched as u32;
+        // A short page or a page with nothing new means we've reached the end
+        self.exhausted = fetched < PAGE_SIZE as usize || added == 0;
+        true
+    }
+
+    /// Record a failed page request. No further pages are requested until the list is reloaded.
+    pub fn page_failed(&mut self) {
+        self.loading = false;
+        self.exhausted = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor() -> PagedSongs {
+        PagedSongs::new(Arc::new(RandomSongs::default()))
+    }
+
+    #[test]
+    fn test_prefetch_near_end() {
+        let mut pages = cursor();
+        assert!(pages.should_prefetch(0, None));
+
+        pages.start_loading();
+        assert!(!pages.should_prefetch(0, None));
+
+        assert!(pages.page_loaded(0, 100, 100));
+        assert!(!pages.should_prefetch(100, Some(0)));
+        assert!(pages.should_prefetch(100, Some(80)));
+    }
+
+    #[test]
+    fn test_short_page_exhausts() {
+        let mut pages = cursor();
+        pages.start_loading();
+        assert!(pages.page_loaded(0, 42, 42));
+        assert!(pages.is_exhausted());
+        assert!(!pages.should_prefetch(42, Some(41)));
+    }
+
+    #[test]
+    fn test_stale_page_ignored() {
+        let mut pages = cursor();
+        pages.start_loading();
+        assert!(!pages.page_loaded(100, 100, 100));
+        assert_eq!(pages.next_offset(), 0);
+    }
+}
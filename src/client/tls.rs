@@ -0,0 +1,89 @@
+//! TLS options for servers behind self-signed or private-CA certificates.
+
+use std::path::Path;
+
+use color_eyre::eyre::WrapErr;
+use color_eyre::Result;
+use reqwest::{Certificate, Identity};
+
+/// Certificates to trust and present when connecting to the server.
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    /// Skip certificate verification entirely
+    pub accept_invalid_certs: bool,
+
+    /// Extra CA certificates to trust
+    pub ca_certs: Vec<Certificate>,
+
+    /// Client certificate and key, for servers that ask for one
+    pub identity: Option<Identity>,
+}
+
+impl TlsOptions {
+    /// Load the CA bundle and client certificate from PEM files. The client
+    /// key may be in the certificate file or in a file of its own.
+    pub fn load(
+        accept_invalid_certs: bool,
+        ca_cert_path: Option<&str>,
+        client_cert_path: Option<&str>,
+        client_key_path: Option<&str>,
+    ) -> Result<Self> {
+        let ca_certs = match ca_cert_path {
+            Some(path) => Certificate::from_pem_bundle(&read_pem(path)?)
+                .wrap_err_with(|| format!("Invalid CA certificate {}", path))?,
+            None => Vec::new(),
+        };
+
+        let identity = match client_cert_path {
+            Some(path) => {
+                let mut pem = read_pem(path)?;
+                if let Some(key_path) = client_key_path {
+                    pem.push(b'\n');
+                    pem.extend(read_pem(key_path)?);
+                }
+                Some(
+                    Identity::from_pem(&pem)
+                        .wrap_err_with(|| format!("Invalid client certificate {}", path))?,
+                )
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            accept_invalid_certs,
+            ca_certs,
+            identity,
+        })
+    }
+
+    /// Apply the options to an async client builder.
+    pub fn apply(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        builder = builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        for cert in &self.ca_certs {
+            builder = builder.add_root_certificate(cert.clone());
+        }
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(identity.clone());
+        }
+        builder
+    }
+
+    /// Apply the options to a blocking client builder.
+    pub fn apply_blocking(
+        &self,
+        mut builder: reqwest::blocking::ClientBuilder,
+    ) -> reqwest::blocking::ClientBuilder {
+        builder = builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        for cert in &self.ca_certs {
+            builder = builder.add_root_certificate(cert.clone());
+        }
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(identity.clone());
+        }
+        builder
+    }
+}
+
+fn read_pem(path: &str) -> Result<Vec<u8>> {
+    std::fs::read(Path::new(path)).wrap_err_with(|| format!("Failed to read {}", path))
+}
@@ -0,0 +1,19 @@
+//! System clipboard access.
+
+/// The system clipboard, opened on first use. It is kept open because on X11
+/// the copied text is only available while its owner is alive.
+#[derive(Default)]
+pub struct Clipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    /// Put `text` on the clipboard.
+    pub fn set_text(&mut self, text: String) -> Result<(), arboard::Error> {
+        let clipboard = match &mut self.inner {
+            Some(clipboard) => clipboard,
+            None => self.inner.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)
+    }
+}
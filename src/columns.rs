@@ -0,0 +1,220 @@
+//! Configurable columns for song tables.
+
+use ratatui::layout::Constraint;
+use serde::{Deserialize, Serialize};
+
+use crate::client::models::Song;
+
+/// A column that can be shown in song tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SongColumn {
+    Track,
+    Title,
+    Artist,
+    Album,
+    Duration,
+    Year,
+    Bitrate,
+    Format,
+    PlayCount,
+    Rating,
+    DateAdded,
+    Bpm,
+    ChannelCount,
+    SamplingRate,
+    BitDepth,
+}
+
+impl SongColumn {
+    /// Width used when the config doesn't give one.
+    fn default_constraint(self) -> Constraint {
+        match self {
+            Self::Track => Constraint::Length(3),
+            Self::Title => Constraint::Percentage(50),
+            Self::Artist | Self::Album => Constraint::Percentage(35),
+            Self::Duration => Constraint::Length(6),
+            Self::Year => Constraint::Length(4),
+            Self::Bitrate => Constraint::Length(8),
+            Self::Format => Constraint::Length(5),
+            Self::PlayCount => Constraint::Length(5),
+            Self::Rating => Constraint::Length(5),
+            Self::DateAdded => Constraint::Length(10),
+            Self::Bpm => Constraint::Length(4),
+            Self::ChannelCount => Constraint::Length(6),
+            Self::SamplingRate => Constraint::Length(5),
+            Self::BitDepth => Constraint::Length(4),
+        }
+    }
+
+    /// Get the column's header label.
+    pub fn header(self) -> &'static str {
+        match self {
+            Self::Track => "#",
+            Self::Title => "Title",
+            Self::Artist => "Artist",
+            Self::Album => "Album",
+            Self::Duration => "Time",
+            Self::Year => "Year",
+            Self::Bitrate => "Bitrate",
+            Self::Format => "Type",
+            Self::PlayCount => "Plays",
+            Self::Rating => "Stars",
+            Self::DateAdded => "Added",
+            Self::Bpm => "BPM",
+            Self::ChannelCount => "Chan",
+            Self::SamplingRate => "kHz",
+            Self::BitDepth => "Bits",
+        }
+    }
+
+    /// Get the cell text for a song at `index` in its list.
+    pub fn text(self, song: &Song, index: usize) -> String {
+        match self {
+            Self::Track => format!("{:02}", song.track.unwrap_or(index as i32 + 1)),
+            Self::Title => song.title.clone(),
+            Self::Artist => song.display_artist().to_string(),
+            Self::Album => song.album.clone().unwrap_or_default(),
+            Self::Duration => song.duration_string(),
+            Self::Year => song.year.map(|y| y.to_string()).unwrap_or_default(),
+            Self::Bitrate => song
+                .bit_rate
+                .map(|b| format!("{}kbps", b))
+                .unwrap_or_default(),
+            Self::Format => song.suffix.clone().unwrap_or_default(),
+            Self::PlayCount => song.play_count.map(|c| c.to_string()).unwrap_or_default(),
+            Self::Rating => "★".repeat(song.user_rating.unwrap_or(0).clamp(0, 5) as usize),
+            // Dates are ISO 8601; only the day is worth the space
+            Self::DateAdded => song
+                .created
+                .as_deref()
+                .map(|d| d.chars().take(10).collect())
+                .unwrap_or_default(),
+            Self::Bpm => song
+                .bpm
+                .filter(|&bpm| bpm > 0)
+                .map(|bpm| bpm.to_string())
+                .unwrap_or_default(),
+            Self::ChannelCount => match song.channel_count {
+                Some(1) => String::from("mono"),
+                Some(2) => String::from("stereo"),
+                Some(n) if n > 0 => n.to_string(),
+                _ => String::new(),
+            },
+            // At most one decimal, so 11025 Hz fits as "11"
+            Self::SamplingRate => song
+                .sampling_rate
+                .map(|rate| ((rate as f64 / 100.0).round() / 10.0).to_string())
+                .unwrap_or_default(),
+            Self::BitDepth => song.bit_depth.map(|b| b.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+/// A song table column with an optional width override.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSpec {
+    pub column: SongColumn,
+
+    /// Fixed width in cells
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u16>,
+
+    /// Share of the table width in percent (ignored if `width` is set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u16>,
+}
+
+impl ColumnSpec {
+    /// Get the layout constraint for this column.
+    pub fn constraint(&self) -> Constraint {
+        match (self.width, self.percent) {
+            (Some(width), _) => Constraint::Length(width),
+            (None, Some(percent)) => Constraint::Percentage(percent.min(100)),
+            (None, None) => self.column.default_constraint(),
+        }
+    }
+}
+
+impl From<SongColumn> for ColumnSpec {
+    fn from(column: SongColumn) -> Self {
+        Self {
+            column,
+            width: None,
+            percent: None,
+        }
+    }
+}
+
+/// Columns shown when none are configured: Track | Title | Artist | Duration.
+pub fn default_song_columns() -> Vec<ColumnSpec> {
+    [
+        SongColumn::Track,
+        SongColumn::Title,
+        SongColumn::Artist,
+        SongColumn::Duration,
+    ]
+    .into_iter()
+    .map(ColumnSpec::from)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_spec_from_toml() {
+        #[derive(Deserialize)]
+        struct Columns {
+            song_columns: Vec<ColumnSpec>,
+        }
+
+        let parsed: Columns = toml::from_str(
+            r#"
+            song_columns = [
+                { column = "track" },
+                { column = "title", percent = 60 },
+                { column = "play_count", width = 3 },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        let constraints: Vec<Constraint> =
+            parsed.song_columns.iter().map(|c| c.constraint()).collect();
+        assert_eq!(
+            constraints,
+            vec![
+                Constraint::Length(3),
+                Constraint::Percentage(60),
+                Constraint::Length(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_audio_columns() {
+        let song = |id: &str, bpm: i32, rate: i32, bits: i32| -> Song {
+            serde_json::from_value(serde_json::json!({
+                "id": id, "title": id, "bpm": bpm, "samplingRate": rate,
+                "bitDepth": bits, "channelCount": 2,
+            }))
+            .unwrap()
+        };
+        let hi_res = song("hi", 128, 96_000, 24);
+        assert_eq!(SongColumn::Bpm.text(&hi_res, 0), "128");
+        assert_eq!(SongColumn::SamplingRate.text(&hi_res, 0), "96");
+        assert_eq!(SongColumn::BitDepth.text(&hi_res, 0), "24");
+        assert_eq!(SongColumn::ChannelCount.text(&hi_res, 0), "stereo");
+        assert_eq!(SongColumn::Bpm.text(&song("none", 0, 0, 0), 0), "");
+        assert_eq!(
+            SongColumn::SamplingRate.text(&song("cd", 0, 44_100, 16), 0),
+            "44.1"
+        );
+        assert_eq!(
+            SongColumn::SamplingRate.text(&song("low", 0, 11_025, 8), 0),
+            "11"
+        );
+    }
+}
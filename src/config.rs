@@ -5,6 +5,10 @@ use std::path::PathBuf;
 use color_eyre::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::action::Tab;
+use crate::client::TlsOptions;
+use crate::columns::{default_song_columns, ColumnSpec};
+
 /// Application configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -18,6 +22,37 @@ pub struct Config {
     /// UI configuration
     #[serde(default)]
     pub ui: UiConfig,
+
+    /// Lyrics configuration
+    #[serde(default)]
+    pub lyrics: LyricsConfig,
+
+    /// M3U playlist import and export configuration
+    #[serde(default)]
+    pub m3u: M3uConfig,
+
+    /// External player the current track or the queue can be handed to
+    #[serde(default)]
+    pub external_player: ExternalPlayerConfig,
+
+    /// Shuffling configuration
+    #[serde(default)]
+    pub shuffle: ShuffleConfig,
+
+    /// Smart playlists, listed in the Playlists tab
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub smart_playlists: Vec<SmartPlaylistConfig>,
+}
+
+/// A playlist of the library's songs that meet some rules, such as
+/// `genre = Jazz AND year >= 1990 AND starred`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartPlaylistConfig {
+    /// Name shown in the Playlists tab
+    pub name: String,
+
+    /// Conditions joined by `AND`
+    pub rules: String,
 }
 
 /// Server connection configuration.
@@ -29,13 +64,59 @@ pub struct ServerConfig {
     /// Username for authentication
     pub username: String,
 
-    /// Password (optional, will be prompted if not provided)
+    /// Password (optional, asked for on startup if not provided)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub password: Option<String>,
 
     /// API key for OpenSubsonic servers (optional)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
+
+    /// Seconds to wait for the server to accept a connection
+    #[serde(default = "default_connect_timeout")]
+    pub connect_timeout_secs: u64,
+
+    /// Seconds to wait for the server to send more of a response
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+
+    /// Times to retry a request that fails on the way (the server being
+    /// unreachable, a timeout, or a 502/503/504 from a proxy)
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+
+    /// Send the password (hex-encoded) instead of a token, for old servers.
+    /// Without it, servers rejecting token auth are detected on connect.
+    #[serde(default)]
+    pub legacy_auth: bool,
+
+    /// Accept any certificate, e.g. a self-signed one (insecure)
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+
+    /// PEM file with extra CA certificates to trust
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert_path: Option<String>,
+
+    /// PEM file with a client certificate, for servers that require one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert_path: Option<String>,
+
+    /// PEM file with the client certificate's key, if not in the certificate file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key_path: Option<String>,
+}
+
+impl ServerConfig {
+    /// Load the certificates named in the config.
+    pub fn tls(&self) -> Result<TlsOptions> {
+        TlsOptions::load(
+            self.accept_invalid_certs,
+            self.ca_cert_path.as_deref(),
+            self.client_cert_path.as_deref(),
+            self.client_key_path.as_deref(),
+        )
+    }
 }
 
 /// Player configuration.
@@ -45,6 +126,14 @@ pub struct PlayerConfig {
     #[serde(default = "default_volume")]
     pub volume: u8,
 
+    /// Highest volume in percent; above 100 the sound is amplified
+    #[serde(default = "default_max_volume")]
+    pub max_volume: u8,
+
+    /// Volume change of the `+`/`-` keys, in percent
+    #[serde(default = "default_volume_step")]
+    pub volume_step: u8,
+
     /// Enable gapless playback
     #[serde(default = "default_true")]
     pub gapless: bool,
@@ -56,6 +145,115 @@ pub struct PlayerConfig {
     /// Maximum bitrate for streaming (0 = no limit)
     #[serde(default)]
     pub max_bitrate: u32,
+
+    /// Audio output latency in milliseconds (e.g. Bluetooth delay), used to
+    /// keep synced lyrics and the visualizer in step with what is actually heard
+    #[serde(default)]
+    pub audio_latency_ms: u32,
+
+    /// Stream over HLS on servers that offer it (`hls.m3u8`), picking the
+    /// bitrate the connection keeps up with; useful on mobile data
+    #[serde(default)]
+    pub hls: bool,
+
+    /// Send the sound as raw PCM (48 kHz, 16-bit, stereo) to
+    /// `tcp://host:port` or `udp://host:port`, e.g. a Snapcast server,
+    /// instead of the sound card. Playback starts there; `w` switches
+    /// between it and the other outputs
+    #[serde(default)]
+    pub network_output: Option<String>,
+
+    /// Length in milliseconds of the fades when playback starts, pauses,
+    /// resumes, stops, seeks, or changes track (0 = cut in and out)
+    #[serde(default)]
+    pub fade_ms: u32,
+
+    /// Loudness normalization using ReplayGain tags (or a measured estimate)
+    #[serde(default)]
+    pub replay_gain: ReplayGainMode,
+
+    /// Size limit of the on-disk track cache in MB (0 = no caching)
+    #[serde(default)]
+    pub cache_mb: u64,
+
+    /// Upcoming queue tracks downloaded into the track cache while one
+    /// plays (0 = off, at most 3; needs `cache_mb`)
+    #[serde(default)]
+    pub precache: u32,
+
+    /// Number of songs added when filling the queue with random songs
+    #[serde(default = "default_random_songs")]
+    pub random_songs: u32,
+
+    /// Resume on the new sound device after the one playing went away,
+    /// instead of staying paused
+    #[serde(default)]
+    pub resume_on_new_device: bool,
+
+    /// How the sound device is shared with other programs
+    #[serde(default)]
+    pub output_mode: OutputMode,
+
+    /// Sample rate in Hz to open the sound device at and resample tracks
+    /// to (unset = the device's own rate)
+    #[serde(default)]
+    pub sample_rate: Option<u32>,
+
+    /// How carefully tracks are resampled to the device's rate
+    #[serde(default)]
+    pub resampler: ResamplerQuality,
+
+    /// Skip silences quieter than `silence_threshold_db` once they've
+    /// lasted `silence_seconds`
+    #[serde(default)]
+    pub skip_silence: bool,
+
+    /// Level in dB (relative to full scale) below which sound counts as
+    /// silence
+    #[serde(default = "default_silence_threshold_db")]
+    pub silence_threshold_db: i32,
+
+    /// Seconds of silence played before the rest is skipped
+    #[serde(default = "default_silence_seconds")]
+    pub silence_seconds: u32,
+}
+
+/// How the sound device is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputMode {
+    /// At the device's own format, mixed with other programs' sound
+    #[default]
+    Shared,
+    /// At each track's sample rate, where the device supports it, so
+    /// hi-res tracks aren't resampled. Samples are still decoded to 16 bits
+    /// and shared with other programs, and the volume is still applied.
+    #[serde(alias = "exclusive")]
+    MatchTrack,
+}
+
+/// How tracks are resampled to the output's sample rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResamplerQuality {
+    /// Linear interpolation
+    Fast,
+    /// Cubic interpolation
+    #[default]
+    Balanced,
+    /// Windowed sinc filter, which also keeps out aliasing when lowering
+    /// the rate
+    Best,
+}
+
+/// Which ReplayGain value to normalize with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayGainMode {
+    #[default]
+    Off,
+    Track,
+    Album,
 }
 
 /// UI configuration.
@@ -69,19 +267,365 @@ pub struct UiConfig {
     #[serde(default = "default_true")]
     pub show_album_art: bool,
 
+    /// Terminal graphics protocol for images, instead of detecting it
+    #[serde(default)]
+    pub image_protocol: ImageProtocol,
+
+    /// Size of the album art requested from the server, in pixels
+    #[serde(default = "default_album_art_size")]
+    pub album_art_size: u32,
+
     /// Color theme
     #[serde(default)]
     pub theme: String,
+
+    /// Greeting shown while nothing is playing
+    #[serde(default)]
+    pub greeting: GreetingConfig,
+
+    /// Tabs shown in the tab bar, in order; number keys follow this order
+    #[serde(default = "default_tabs")]
+    pub tabs: Vec<Tab>,
+
+    /// Columns shown in song tables, in order
+    #[serde(default = "default_song_columns")]
+    pub song_columns: Vec<ColumnSpec>,
+
+    /// Show the status bar with the current mode and key hints
+    #[serde(default = "default_true")]
+    pub show_status_bar: bool,
+
+    /// Where to show the spectrum visualizer
+    #[serde(default)]
+    pub visualizer: VisualizerMode,
+
+    /// Scroll titles too long for the now playing bar and queue instead of cutting them off
+    #[serde(default)]
+    pub marquee: bool,
+
+    /// Group the queue by album under headers, which can be collapsed
+    #[serde(default)]
+    pub group_queue: bool,
+
+    /// Suggest albums by similar artists below an album's songs
+    #[serde(default = "default_true")]
+    pub similar_albums: bool,
+
+    /// Icons to draw the UI with
+    #[serde(default)]
+    pub icons: IconSet,
+
+    /// Milliseconds between checks on the player and background work
+    #[serde(default = "default_tick_rate")]
+    pub tick_rate_ms: u64,
+
+    /// Terminal window title while a track plays, with `{artist}`, `{title}`,
+    /// `{album}` and `{state}` filled in (empty = leave the title alone)
+    #[serde(default = "default_title")]
+    pub title: String,
+}
+
+/// Which icons the UI is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconSet {
+    /// Plain ASCII on the Linux console or without a UTF-8 locale, Nerd Font otherwise
+    #[default]
+    Auto,
+    /// Nerd Font glyphs (needs a patched font)
+    Nerd,
+    /// Plain ASCII
+    Ascii,
+}
+
+/// Terminal graphics protocol used to draw images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageProtocol {
+    /// Whatever the terminal reports supporting
+    #[default]
+    Auto,
+    Sixel,
+    Kitty,
+    Iterm2,
+    /// Unicode half blocks, which work in any color terminal
+    Halfblocks,
+}
+
+/// Where the spectrum visualizer is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VisualizerMode {
+    #[default]
+    Off,
+    /// Next to the now playing bar
+    Bar,
+    /// In the full-screen now playing view
+    Fullscreen,
+}
+
+/// Lyrics configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LyricsConfig {
+    /// External providers asked, in order, when the server has no lyrics for a song
+    #[serde(default)]
+    pub providers: Vec<LyricsProvider>,
+
+    /// Directory lyrics are exported to as `.lrc` files
+    /// (defaults to the `lyrics` folder in the data directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export_dir: Option<PathBuf>,
+
+    /// Language code (e.g. "eng") shown when a song has lyrics in several,
+    /// until another is picked in the lyrics panel
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+}
+
+/// M3U playlist import and export configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct M3uConfig {
+    /// Directory playlists are exported to and imported from
+    /// (defaults to the `playlists` folder in the data directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+
+    /// What exported playlists point at
+    #[serde(default)]
+    pub locations: M3uLocations,
+}
+
+/// What the entries of an exported playlist point at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum M3uLocations {
+    /// The server's stream URLs without credentials, so files can be
+    /// shared; players opening them have to sign in
+    #[default]
+    Stream,
+    /// The server's stream URLs with your credentials, which play anywhere
+    /// but let whoever has the file into your account
+    Signed,
+    /// Tracks in the local track cache, or stream URLs (without
+    /// credentials) for those not cached
+    Cache,
+}
+
+/// External player configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalPlayerConfig {
+    /// Program and arguments to run; the path of an M3U file of the track
+    /// or the queue is added as the last argument
+    #[serde(default = "default_external_command")]
+    pub command: Vec<String>,
+
+    /// Pause playback here once the external player has started
+    #[serde(default = "default_true")]
+    pub pause: bool,
+}
+
+/// Shuffling that can favor well-liked songs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShuffleConfig {
+    /// Bring songs up sooner the higher they're rated, the more they've
+    /// been played, and if they're starred
+    #[serde(default)]
+    pub weighted: bool,
+
+    /// How many times as likely the best-liked songs are to come up next
+    /// as the least-liked
+    #[serde(default = "default_shuffle_favor")]
+    pub favor: u32,
+
+    /// How the chance grows from the least-liked songs to the best-liked
+    #[serde(default)]
+    pub curve: ShuffleCurve,
+}
+
+/// How a song's chance of coming up grows with how well-liked it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShuffleCurve {
+    /// Evenly
+    Linear,
+    /// Slowly for middling songs, steeply for favorites
+    #[default]
+    Exponential,
+}
+
+/// External lyrics provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LyricsProvider {
+    /// lrclib.net
+    Lrclib,
+}
+
+/// Greeting configuration. The phrases and templates can be replaced to
+/// localize the greeting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GreetingConfig {
+    /// Show the greeting
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Name to greet (defaults to the server username)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Phrase used from 05:00 to 11:59
+    #[serde(default = "default_morning")]
+    pub morning: String,
+
+    /// Phrase used from 12:00 to 16:59
+    #[serde(default = "default_afternoon")]
+    pub afternoon: String,
+
+    /// Phrase used from 17:00 to 21:59
+    #[serde(default = "default_evening")]
+    pub evening: String,
+
+    /// Phrase used from 22:00 to 04:59
+    #[serde(default = "default_night")]
+    pub night: String,
+
+    /// How the name is added to the phrase ({greeting}, {name})
+    #[serde(default = "default_with_name")]
+    pub with_name: String,
+
+    /// How the library totals are added ({greeting}, {totals})
+    #[serde(default = "default_with_totals")]
+    pub with_totals: String,
+
+    /// A library of one album ({count})
+    #[serde(default = "default_album")]
+    pub album: String,
+
+    /// A library of any other number of albums ({count})
+    #[serde(default = "default_albums")]
+    pub albums: String,
+
+    /// A library of one song ({count})
+    #[serde(default = "default_song")]
+    pub song: String,
+
+    /// A library of any other number of songs ({count})
+    #[serde(default = "default_songs")]
+    pub songs: String,
+
+    /// Put between the album and song totals
+    #[serde(default = "default_totals_separator")]
+    pub totals_separator: String,
+}
+
+fn default_connect_timeout() -> u64 {
+    10
+}
+
+fn default_timeout() -> u64 {
+    30
+}
+
+fn default_retries() -> u32 {
+    2
+}
+
+fn default_tick_rate() -> u64 {
+    100
+}
+
+fn default_title() -> String {
+    String::from("{artist} – {title} [{state}]")
+}
+
+fn default_album_art_size() -> u32 {
+    300
 }
 
 fn default_volume() -> u8 {
     80
 }
 
+fn default_max_volume() -> u8 {
+    100
+}
+
+fn default_volume_step() -> u8 {
+    5
+}
+
+fn default_random_songs() -> u32 {
+    50
+}
+
+fn default_silence_threshold_db() -> i32 {
+    -50
+}
+
+fn default_silence_seconds() -> u32 {
+    2
+}
+
+fn default_shuffle_favor() -> u32 {
+    4
+}
+
+fn default_external_command() -> Vec<String> {
+    vec![String::from("mpv"), String::from("--no-video")]
+}
+
+fn default_tabs() -> Vec<Tab> {
+    Tab::all().to_vec()
+}
+
 fn default_true() -> bool {
     true
 }
 
+fn default_morning() -> String {
+    String::from("Good morning")
+}
+
+fn default_afternoon() -> String {
+    String::from("Good afternoon")
+}
+
+fn default_evening() -> String {
+    String::from("Good evening")
+}
+
+fn default_night() -> String {
+    String::from("Good night")
+}
+
+fn default_with_name() -> String {
+    String::from("{greeting}, {name}")
+}
+
+fn default_with_totals() -> String {
+    String::from("{greeting} — {totals}")
+}
+
+fn default_album() -> String {
+    String::from("{count} album")
+}
+
+fn default_albums() -> String {
+    String::from("{count} albums")
+}
+
+fn default_song() -> String {
+    String::from("{count} song")
+}
+
+fn default_songs() -> String {
+    String::from("{count} songs")
+}
+
+fn default_totals_separator() -> String {
+    String::from(", ")
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -90,9 +634,22 @@ impl Default for Config {
                 username: String::new(),
                 password: None,
                 api_key: None,
+                connect_timeout_secs: default_connect_timeout(),
+                timeout_secs: default_timeout(),
+                retries: default_retries(),
+                legacy_auth: false,
+                accept_invalid_certs: false,
+                ca_cert_path: None,
+                client_cert_path: None,
+                client_key_path: None,
             },
             player: PlayerConfig::default(),
             ui: UiConfig::default(),
+            lyrics: LyricsConfig::default(),
+            m3u: M3uConfig::default(),
+            external_player: ExternalPlayerConfig::default(),
+            shuffle: ShuffleConfig::default(),
+            smart_playlists: Vec::new(),
         }
     }
 }
@@ -101,9 +658,26 @@ impl Default for PlayerConfig {
     fn default() -> Self {
         Self {
             volume: default_volume(),
+            max_volume: default_max_volume(),
+            volume_step: default_volume_step(),
             gapless: true,
             format: None,
             max_bitrate: 0,
+            audio_latency_ms: 0,
+            hls: false,
+            network_output: None,
+            fade_ms: 0,
+            replay_gain: ReplayGainMode::Off,
+            cache_mb: 0,
+            precache: 0,
+            random_songs: default_random_songs(),
+            resume_on_new_device: false,
+            output_mode: OutputMode::Shared,
+            sample_rate: None,
+            resampler: ResamplerQuality::default(),
+            skip_silence: false,
+            silence_threshold_db: default_silence_threshold_db(),
+            silence_seconds: default_silence_seconds(),
         }
     }
 }
@@ -113,7 +687,59 @@ impl Default for UiConfig {
         Self {
             show_queue: true,
             show_album_art: true,
+            image_protocol: ImageProtocol::default(),
+            album_art_size: default_album_art_size(),
             theme: String::from("default"),
+            greeting: GreetingConfig::default(),
+            tabs: default_tabs(),
+            song_columns: default_song_columns(),
+            show_status_bar: true,
+            visualizer: VisualizerMode::default(),
+            marquee: false,
+            group_queue: false,
+            similar_albums: true,
+            icons: IconSet::default(),
+            tick_rate_ms: default_tick_rate(),
+            title: default_title(),
+        }
+    }
+}
+
+impl Default for GreetingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            name: None,
+            morning: default_morning(),
+            afternoon: default_afternoon(),
+            evening: default_evening(),
+            night: default_night(),
+            with_name: default_with_name(),
+            with_totals: default_with_totals(),
+            album: default_album(),
+            albums: default_albums(),
+            song: default_song(),
+            songs: default_songs(),
+            totals_separator: default_totals_separator(),
+        }
+    }
+}
+
+impl Default for ExternalPlayerConfig {
+    fn default() -> Self {
+        Self {
+            command: default_external_command(),
+            pause: true,
+        }
+    }
+}
+
+impl Default for ShuffleConfig {
+    fn default() -> Self {
+        Self {
+            weighted: false,
+            favor: default_shuffle_favor(),
+            curve: ShuffleCurve::default(),
         }
     }
 }
@@ -127,6 +753,46 @@ impl Config {
         Ok(config_dir.join("subsonic-tui").join("config.toml"))
     }
 
+    /// Get the data directory for persisted application state.
+    pub fn data_dir() -> Result<PathBuf> {
+        let data_dir = dirs::data_dir()
+            .ok_or_else(|| color_eyre::eyre::eyre!("Could not determine data directory"))?;
+
+        Ok(data_dir.join("subsonic-tui"))
+    }
+
+    /// Get the cache directory for downloaded data.
+    pub fn cache_dir() -> Result<PathBuf> {
+        let cache_dir = dirs::cache_dir()
+            .ok_or_else(|| color_eyre::eyre::eyre!("Could not determine cache directory"))?;
+
+        Ok(cache_dir.join("subsonic-tui"))
+    }
+
+    /// Get the directory lyrics are exported to.
+    pub fn lyrics_export_dir(&self) -> Result<PathBuf> {
+        match &self.lyrics.export_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(Self::data_dir()?.join("lyrics")),
+        }
+    }
+
+    /// Get the directory M3U playlists are exported to and imported from.
+    pub fn m3u_dir(&self) -> Result<PathBuf> {
+        match &self.m3u.dir {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(Self::data_dir()?.join("playlists")),
+        }
+    }
+
+    /// Get the log file path.
+    pub fn log_path() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("subsonic-tui")
+            .join("subsonic-tui.log")
+    }
+
     /// Load configuration from file.
     pub fn load() -> Result<Self> {
         let path = Self::config_path()?;
@@ -138,8 +804,26 @@ impl Config {
         let contents = std::fs::read_to_string(&path)?;
         let mut config: Config = toml::from_str(&contents)?;
 
-        // Clamp volume to valid range (0-100)
-        config.player.volume = config.player.volume.min(100);
+        // Clamp volume to valid range (0-100, or up to 200 when amplified)
+        config.player.max_volume = config.player.max_volume.clamp(100, 200);
+        config.player.volume = config.player.volume.min(config.player.max_volume);
+        config.player.volume_step = config.player.volume_step.clamp(1, 100);
+        // Pausing and skipping wait for the fade
+        config.player.fade_ms = config.player.fade_ms.min(1000);
+        // Retries of a request failing for good would hold up what's behind it
+        config.server.retries = config.server.retries.min(10);
+        // Each tab shows once, and the tab bar is never empty
+        let mut tabs = Vec::new();
+        for tab in std::mem::take(&mut config.ui.tabs) {
+            if !tabs.contains(&tab) {
+                tabs.push(tab);
+            }
+        }
+        config.ui.tabs = if tabs.is_empty() {
+            default_tabs()
+        } else {
+            tabs
+        };
 
         Ok(config)
     }
@@ -173,4 +857,14 @@ impl Config {
 
         valid_url && valid_auth
     }
+
+    /// Check if the password is all that's missing, so it can be asked for.
+    pub fn needs_password(&self) -> bool {
+        let valid_url =
+            self.server.url.starts_with("http://") || self.server.url.starts_with("https://");
+        valid_url
+            && !self.server.username.is_empty()
+            && self.server.api_key.as_ref().is_none_or(|k| k.is_empty())
+            && self.server.password.as_ref().is_none_or(|p| p.is_empty())
+    }
 }
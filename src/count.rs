@@ -0,0 +1,126 @@
+//! Vim-style count prefixes: digits typed before a key repeat it, e.g. `5j`
+//! moves down five rows and `3n` skips three tracks.
+//!
+//! Digits also switch to the tab at that place in the tab bar, so a lone
+//! tab number waits for the next key: one that takes a count uses it as
+//! one, anything else (or a moment without keys) switches tabs.
+
+use std::time::{Duration, Instant};
+
+use crate::action::{Action, Tab};
+
+/// Largest count, so a slip of the finger can't queue thousands of actions.
+const MAX_COUNT: usize = 999;
+
+/// How long a count waits for the key it applies to.
+const COUNT_TIMEOUT: Duration = Duration::from_millis(600);
+
+/// Digits typed so far, waiting for the key they apply to.
+#[derive(Debug, Default)]
+pub struct PendingCount {
+    count: Option<usize>,
+
+    /// When the last digit was typed
+    typed_at: Option<Instant>,
+}
+
+impl PendingCount {
+    /// Get the count typed so far.
+    pub fn get(&self) -> Option<usize> {
+        self.count
+    }
+
+    /// Add a digit. A leading zero is ignored.
+    pub fn push(&mut self, digit: u8, now: Instant) {
+        if self.count.is_none() && digit == 0 {
+            return;
+        }
+        let count = self.count.unwrap_or(0) * 10 + usize::from(digit);
+        self.count = Some(count.min(MAX_COUNT));
+        self.typed_at = Some(now);
+    }
+
+    /// Settle the count with the action of the key typed after it. Returns
+    /// how many times to run the action, and the tab to switch to first if
+    /// the count was a tab number the action doesn't take.
+    pub fn take(&mut self, action: &Action, tabs: &[Tab]) -> (usize, Option<Tab>) {
+        if matches!(action, Action::CountDigit(_)) {
+            return (1, None);
+        }
+        self.typed_at = None;
+        match self.count.take() {
+            Some(count) if action.takes_count() => (count, None),
+            Some(count) => (1, tab_number(count, tabs)),
+            None => (1, None),
+        }
+    }
+
+    /// Drop a count no key followed in time. Returns the tab to switch to if
+    /// it was a tab number.
+    pub fn expire(&mut self, now: Instant, tabs: &[Tab]) -> Option<Tab> {
+        let typed_at = self.typed_at?;
+        if now.duration_since(typed_at) < COUNT_TIMEOUT {
+            return None;
+        }
+        self.typed_at = None;
+        self.count.take().and_then(|count| tab_number(count, tabs))
+    }
+}
+
+/// Get the tab switched to by a number key, counting along the tab bar.
+fn tab_number(count: usize, tabs: &[Tab]) -> Option<Tab> {
+    tabs.get(count.checked_sub(1)?).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_count() {
+        let now = Instant::now();
+        let mut count = PendingCount::default();
+        count.push(0, now);
+        assert_eq!(count.get(), None);
+        count.push(1, now);
+        count.push(2, now);
+        assert_eq!(count.get(), Some(12));
+        assert_eq!(count.take(&Action::CountDigit(3), Tab::all()), (1, None));
+        assert_eq!(count.take(&Action::NavigateDown, Tab::all()), (12, None));
+        assert_eq!(count.get(), None);
+        assert_eq!(count.take(&Action::NavigateDown, Tab::all()), (1, None));
+
+        // A tab number the next key doesn't take switches tabs
+        count.push(3, now);
+        assert_eq!(
+            count.take(&Action::ToggleShuffle, Tab::all()),
+            (1, Some(Tab::Songs))
+        );
+        count.push(9, now);
+        assert_eq!(count.take(&Action::ToggleShuffle, Tab::all()), (1, None));
+
+        for _ in 0..5 {
+            count.push(9, now);
+        }
+        assert_eq!(count.get(), Some(MAX_COUNT));
+        assert_eq!(count.expire(now + COUNT_TIMEOUT, Tab::all()), None);
+        assert_eq!(count.get(), None);
+
+        count.push(2, now);
+        assert_eq!(count.expire(now, Tab::all()), None);
+        assert_eq!(
+            count.expire(now + COUNT_TIMEOUT, Tab::all()),
+            Some(Tab::Albums)
+        );
+
+        // Numbers follow the configured tab bar
+        let tabs = [Tab::History, Tab::Albums];
+        count.push(1, now);
+        assert_eq!(
+            count.take(&Action::ToggleShuffle, &tabs),
+            (1, Some(Tab::History))
+        );
+        count.push(3, now);
+        assert_eq!(count.take(&Action::ToggleShuffle, &tabs), (1, None));
+    }
+}
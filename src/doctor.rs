@@ -0,0 +1,249 @@
+//! `subsonic-tui doctor`: check the setup and print a readable report.
+//!
+//! Covers the usual suspects behind "it doesn't work": the config file, the
+//! server connection and credentials, OpenSubsonic support, the audio device,
+//! terminal graphics, and the directories the app writes to.
+
+use std::path::Path;
+use std::time::Duration;
+
+use ratatui_image::picker::{Picker, ProtocolType};
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+
+use crate::client::api::ApiClientError;
+use crate::client::{Auth, SubsonicClient};
+use crate::config::{Config, ImageProtocol};
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+    Skip,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Ok => "  ok  ",
+            Self::Warn => " warn ",
+            Self::Fail => " FAIL ",
+            Self::Skip => " skip ",
+        }
+    }
+}
+
+/// Collects and prints check results.
+#[derive(Debug, Default)]
+struct Report {
+    failures: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn check(&mut self, status: Status, name: &str, detail: impl AsRef<str>) {
+        match status {
+            Status::Fail => self.failures += 1,
+            Status::Warn => self.warnings += 1,
+            Status::Ok | Status::Skip => {}
+        }
+        println!("[{}] {:<14} {}", status.label(), name, detail.as_ref());
+    }
+}
+
+/// Run every check and print the report.
+/// `config_error` is the error from loading the config file, if it failed.
+pub async fn run(config: &Config, config_error: Option<String>) -> bool {
+    let mut report = Report::default();
+    println!("subsonic-tui {} doctor\n", env!("CARGO_PKG_VERSION"));
+
+    // Config file
+    match (Config::config_path(), config_error) {
+        (_, Some(e)) => report.check(Status::Fail, "Config", format!("invalid: {}", e)),
+        (Ok(path), None) if path.exists() => {
+            report.check(Status::Ok, "Config", path.display().to_string())
+        }
+        (Ok(path), None) => report.check(
+            Status::Warn,
+            "Config",
+            format!("{} not found, using defaults", path.display()),
+        ),
+        (Err(e), None) => report.check(Status::Fail, "Config", e.to_string()),
+    }
+
+    // Server connection
+    if config.is_valid() {
+        check_server(&mut report, config).await;
+    } else {
+        report.check(
+            Status::Fail,
+            "Server config",
+            "set server.url (http:// or https://) and username + password or api_key",
+        );
+        report.check(Status::Skip, "Server", "no valid server configured");
+    }
+
+    // Audio output
+    match rodio::OutputStream::try_default() {
+        Ok(_) => {
+            let name = rodio::cpal::default_host()
+                .default_output_device()
+                .and_then(|device| device.name().ok())
+                .unwrap_or_else(|| String::from("default device"));
+            report.check(Status::Ok, "Audio", name);
+        }
+        Err(e) => report.check(Status::Fail, "Audio", format!("no output device: {}", e)),
+    }
+
+    // Terminal graphics for album art
+    if !config.ui.show_album_art {
+        report.check(Status::Skip, "Graphics", "album art turned off");
+    } else if config.ui.image_protocol != ImageProtocol::Auto {
+        report.check(
+            Status::Ok,
+            "Graphics",
+            format!("{:?} protocol (from config)", config.ui.image_protocol),
+        );
+    } else {
+        match Picker::from_query_stdio() {
+            Ok(picker) if picker.protocol_type() == ProtocolType::Halfblocks => report.check(
+                Status::Warn,
+                "Graphics",
+                "no image protocol detected, album art uses half blocks (see ui.image_protocol)",
+            ),
+            Ok(picker) => report.check(
+                Status::Ok,
+                "Graphics",
+                format!("{:?} protocol", picker.protocol_type()),
+            ),
+            Err(e) => report.check(
+                Status::Warn,
+                "Graphics",
+                format!(
+                    "terminal query failed ({}), album art disabled (see ui.image_protocol)",
+                    e
+                ),
+            ),
+        }
+    }
+
+    // Directories the app writes to
+    for (name, dir) in [
+        ("Data dir", Config::data_dir()),
+        ("Cache dir", Config::cache_dir()),
+    ] {
+        match dir {
+            Ok(dir) => match check_writable(&dir) {
+                Ok(()) => report.check(Status::Ok, name, dir.display().to_string()),
+                Err(e) => report.check(
+                    Status::Fail,
+                    name,
+                    format!("{} not writable: {}", dir.display(), e),
+                ),
+            },
+            Err(e) => report.check(Status::Fail, name, e.to_string()),
+        }
+    }
+
+    println!();
+    match (report.failures, report.warnings) {
+        (0, 0) => println!("Everything looks good."),
+        (0, warnings) => println!("No problems found ({} warning(s)).", warnings),
+        (failures, _) => println!("{} problem(s) found.", failures),
+    }
+
+    report.failures == 0
+}
+
+/// Check that the server answers and accepts the credentials, then list its extensions.
+async fn check_server(report: &mut Report, config: &Config) {
+    let auth = match (&config.server.api_key, &config.server.password) {
+        (Some(api_key), _) if !api_key.is_empty() => Auth::from_api_key(api_key),
+        (_, Some(password)) if config.server.legacy_auth => {
+            Auth::from_legacy_password(&config.server.username, password)
+        }
+        (_, Some(password)) => Auth::from_password(&config.server.username, password),
+        _ => return,
+    };
+    let tls = match config.server.tls() {
+        Ok(tls) => {
+            if tls.accept_invalid_certs {
+                report.check(Status::Warn, "TLS", "certificate checks are off");
+            } else if !tls.ca_certs.is_empty() || tls.identity.is_some() {
+                report.check(Status::Ok, "TLS", "certificates loaded");
+            }
+            tls
+        }
+        Err(e) => {
+            report.check(Status::Fail, "TLS", format!("{:#}", e));
+            report.check(Status::Skip, "Server", "TLS not set up");
+            return;
+        }
+    };
+    let mut client = SubsonicClient::new(&config.server.url, auth).with_network(
+        Duration::from_secs(config.server.connect_timeout_secs),
+        Duration::from_secs(config.server.timeout_secs),
+        config.server.retries,
+        &tls,
+    );
+
+    match client.connect().await {
+        Ok(()) => {
+            report.check(Status::Ok, "Server", &config.server.url);
+            if client.uses_legacy_auth() && !config.server.legacy_auth {
+                report.check(
+                    Status::Warn,
+                    "Auth",
+                    "token auth not supported, fell back to legacy password (set server.legacy_auth)",
+                );
+            } else {
+                report.check(
+                    Status::Ok,
+                    "Auth",
+                    format!("credentials accepted ({})", client.auth_mode()),
+                );
+            }
+        }
+        Err(ApiClientError::Request(e)) => {
+            report.check(Status::Fail, "Server", format!("unreachable: {}", e));
+            report.check(Status::Skip, "Auth", "server unreachable");
+            return;
+        }
+        Err(e @ (ApiClientError::InvalidResponse(_) | ApiClientError::Http { .. })) => {
+            report.check(
+                Status::Fail,
+                "Server",
+                format!("not a Subsonic API (check the URL): {}", e),
+            );
+            report.check(Status::Skip, "Auth", "server not usable");
+            return;
+        }
+        Err(e) => {
+            report.check(Status::Ok, "Server", &config.server.url);
+            report.check(Status::Fail, "Auth", format!("rejected: {}", e));
+            return;
+        }
+    }
+
+    match client.get_open_subsonic_extensions().await {
+        Ok(extensions) if !extensions.is_empty() => {
+            let names: Vec<&str> = extensions.iter().map(|e| e.name.as_str()).collect();
+            report.check(Status::Ok, "OpenSubsonic", names.join(", "));
+        }
+        Ok(_) => report.check(Status::Ok, "OpenSubsonic", "supported, no extensions"),
+        Err(_) => report.check(
+            Status::Warn,
+            "OpenSubsonic",
+            "not supported, synced lyrics unavailable",
+        ),
+    }
+}
+
+/// Create a directory if needed and check a file can be written in it.
+fn check_writable(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(".doctor-probe");
+    std::fs::write(&probe, b"ok")?;
+    std::fs::remove_file(probe)
+}
@@ -0,0 +1,64 @@
+//! Handing the current track or the queue to another player, such as mpv
+//! for gapless playback, as an M3U file of stream URLs.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use color_eyre::{eyre::eyre, Result};
+
+use crate::client::models::Song;
+use crate::config::Config;
+use crate::m3u;
+
+/// Name of the M3U file songs are handed over in, replaced each time.
+const QUEUE_FILE: &str = "external.m3u8";
+
+/// Start `command` with `target` as its last argument. Its output is thrown
+/// away so it can't draw over the interface.
+pub fn launch(command: &[String], target: &str) -> Result<()> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| eyre!("No external player command set"))?;
+    let mut child = Command::new(program)
+        .args(args)
+        .arg(target)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| eyre!("Couldn't start {}: {}", program, e))?;
+
+    // Reap it once it exits
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+/// Write songs to the M3U file handed to the external player and return
+/// its path. Stream URLs carry the credentials, so they go in a file only
+/// the user can read rather than on a command line anyone can see.
+pub fn write_queue(songs: &[Song], location: impl Fn(&Song) -> String) -> Result<PathBuf> {
+    let dir = Config::cache_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(QUEUE_FILE);
+
+    // A fresh file, so the permissions apply and a link left in its place
+    // isn't followed
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    options
+        .open(&path)?
+        .write_all(m3u::format(songs, location).as_bytes())?;
+    Ok(path)
+}
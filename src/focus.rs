@@ -0,0 +1,45 @@
+//! Which panel takes the keys: the library, or the queue or lyrics panel
+//! beside it. Tab and Shift+Tab cycle through the panels on screen.
+
+use crate::ui::components::filter::step_in;
+
+/// Panel with the keyboard focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Focus {
+    #[default]
+    Library,
+    Queue,
+    Lyrics,
+}
+
+impl Focus {
+    /// Get the panel `step` places along `panels` from this one, wrapping
+    /// around. From a panel not on screen, goes to the first.
+    pub fn step(self, panels: &[Focus], step: isize) -> Focus {
+        step_in(panels, self, step)
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Library => "Library",
+            Self::Queue => "Queue",
+            Self::Lyrics => "Lyrics",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step() {
+        let panels = [Focus::Library, Focus::Lyrics];
+        assert_eq!(Focus::Library.step(&panels, 1), Focus::Lyrics);
+        assert_eq!(Focus::Lyrics.step(&panels, 1), Focus::Library);
+        assert_eq!(Focus::Library.step(&panels, -1), Focus::Lyrics);
+        // The queue went off screen when the lyrics opened
+        assert_eq!(Focus::Queue.step(&panels, 1), Focus::Library);
+        assert_eq!(Focus::Library.step(&[Focus::Library], 1), Focus::Library);
+    }
+}
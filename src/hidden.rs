@@ -0,0 +1,95 @@
+//! Songs and albums the user has hidden from the library.
+//!
+//! The Subsonic API has no way to delete media, so hiding is local: hidden items
+//! are dropped from every list as it loads and persisted with the app state.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::models::{Album, Song};
+
+/// An item that can be hidden.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HideTarget {
+    Song { id: String, title: String },
+    Album { id: String, name: String },
+}
+
+impl HideTarget {
+    /// Describe the item for a confirmation prompt.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Song { title, .. } => format!("song \"{}\"", title),
+            Self::Album { name, .. } => format!("album \"{}\"", name),
+        }
+    }
+}
+
+/// IDs of hidden songs and albums.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HiddenItems {
+    #[serde(default)]
+    songs: BTreeSet<String>,
+
+    #[serde(default)]
+    albums: BTreeSet<String>,
+}
+
+impl HiddenItems {
+    /// Hide an item.
+    pub fn hide(&mut self, target: HideTarget) {
+        match target {
+            HideTarget::Song { id, .. } => self.songs.insert(id),
+            HideTarget::Album { id, .. } => self.albums.insert(id),
+        };
+    }
+
+    /// Check if a song is hidden, directly or through its album.
+    pub fn hides_song(&self, song: &Song) -> bool {
+        self.songs.contains(&song.id)
+            || song
+                .album_id
+                .as_ref()
+                .is_some_and(|id| self.albums.contains(id))
+    }
+
+    /// Check if an album is hidden.
+    pub fn hides_album(&self, album: &Album) -> bool {
+        self.albums.contains(&album.id)
+    }
+
+    /// Number of hidden items.
+    pub fn len(&self) -> usize {
+        self.songs.len() + self.albums.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hidden_album_hides_its_songs() {
+        let song: Song = serde_json::from_value(serde_json::json!({
+            "id": "s1",
+            "title": "Song",
+            "albumId": "a1",
+        }))
+        .unwrap();
+
+        let mut hidden = HiddenItems::default();
+        assert!(!hidden.hides_song(&song));
+
+        hidden.hide(HideTarget::Album {
+            id: "a1".to_string(),
+            name: "Album".to_string(),
+        });
+        assert!(hidden.hides_song(&song));
+        assert_eq!(hidden.len(), 1);
+    }
+}
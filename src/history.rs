@@ -0,0 +1,125 @@
+//! Playback history persisted to disk.
+
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::client::models::Song;
+use crate::config::Config;
+
+/// Maximum number of entries kept in the history file.
+const MAX_ENTRIES: usize = 500;
+
+/// A single played track.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// The song that was played
+    pub song: Song,
+
+    /// When playback started (RFC 3339)
+    pub played_at: String,
+}
+
+impl HistoryEntry {
+    /// Create an entry for a song played now.
+    pub fn now(song: Song) -> Self {
+        Self {
+            song,
+            played_at: chrono::Local::now().to_rfc3339(),
+        }
+    }
+
+    /// Get a short display string for when the track was played.
+    pub fn played_at_string(&self) -> String {
+        chrono::DateTime::parse_from_rfc3339(&self.played_at)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%b %d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Get the history file path.
+fn history_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join("history.json"))
+}
+
+/// Load the playback history (most recent first).
+pub fn load() -> Result<Vec<HistoryEntry>> {
+    let path = history_path()?;
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = std::fs::read_to_string(&path)?;
+    let entries: Vec<HistoryEntry> = serde_json::from_str(&contents)?;
+
+    Ok(entries)
+}
+
+/// Save the playback history, keeping only the most recent entries.
+pub fn save(entries: &[HistoryEntry]) -> Result<()> {
+    let path = history_path()?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let entries = &entries[..entries.len().min(MAX_ENTRIES)];
+    std::fs::write(&path, serde_json::to_string(entries)?)?;
+
+    Ok(())
+}
+
+/// Saves the history on a thread of its own, so starting a track never
+/// waits on the disk. Copies sent while a write is going are folded into
+/// the newest one.
+pub struct Writer {
+    tx: Option<mpsc::Sender<Vec<HistoryEntry>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Writer {
+    pub fn spawn() -> Self {
+        let (tx, rx) = mpsc::channel::<Vec<HistoryEntry>>();
+        let thread = std::thread::spawn(move || {
+            while let Ok(entries) = rx.recv() {
+                let entries = rx.try_iter().last().unwrap_or(entries);
+                if let Err(e) = save(&entries) {
+                    tracing::warn!("Failed to save playback history: {}", e);
+                }
+            }
+        });
+        Self {
+            tx: Some(tx),
+            thread: Some(thread),
+        }
+    }
+
+    /// Queue a copy of the history to be written.
+    pub fn save(&self, entries: &[HistoryEntry]) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(entries[..entries.len().min(MAX_ENTRIES)].to_vec());
+        }
+    }
+
+    /// Wait for the last queued copy to be written.
+    pub fn finish(&mut self) {
+        self.tx = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Record a played song at the front of the history.
+pub fn record(entries: &mut Vec<HistoryEntry>, song: Song) {
+    entries.insert(0, HistoryEntry::now(song));
+    entries.truncate(MAX_ENTRIES);
+}
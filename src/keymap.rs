@@ -0,0 +1,649 @@
+//! Key bindings of the main view and the panels and popups that take the
+//! keyboard over from it. Key presses are looked up here, and the help
+//! overlay lists the same tables, so it always shows what the keys do.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::action::{Action, CopyTarget, ExternalTarget, QueueSort};
+use crate::app::SIDE_PANEL_STEP;
+use crate::ui::components::lyrics::LYRICS_DELAY_STEP_MS;
+
+/// A key, with the modifiers it is pressed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Check whether a key press is this key. A key without modifiers takes
+    /// any, so capitals typed with Shift still match.
+    fn matches(&self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        self.code == code && modifiers.contains(self.modifiers)
+    }
+
+    /// Get the digit this key types, if it is one without modifiers.
+    pub fn digit(&self) -> Option<u32> {
+        match self.code {
+            KeyCode::Char(c) if self.modifiers.is_empty() => c.to_digit(10),
+            _ => None,
+        }
+    }
+
+    /// Get the key as shown in the help, such as "Ctrl+d", "↑" or "Space".
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => String::from("Space"),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => String::from("Enter"),
+            KeyCode::Esc => String::from("Esc"),
+            KeyCode::Backspace => String::from("Backspace"),
+            KeyCode::Delete => String::from("Delete"),
+            KeyCode::Tab => String::from("Tab"),
+            KeyCode::BackTab => String::from("Shift+Tab"),
+            KeyCode::Up => String::from("↑"),
+            KeyCode::Down => String::from("↓"),
+            KeyCode::Left => String::from("←"),
+            KeyCode::Right => String::from("→"),
+            code => format!("{:?}", code),
+        };
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            format!("Ctrl+{}", key)
+        } else if self.modifiers.contains(KeyModifiers::ALT) {
+            format!("Alt+{}", key)
+        } else {
+            key
+        }
+    }
+}
+
+/// Keys bound to an action, and what it does.
+pub struct Binding {
+    pub keys: &'static [Key],
+    pub action: Action,
+    pub description: &'static str,
+}
+
+/// Bindings listed together under a heading in the help.
+pub struct Keymap {
+    pub title: &'static str,
+    pub bindings: &'static [Binding],
+}
+
+/// Find the action a key press is bound to in some keymaps. A key with
+/// modifiers wins over the same key without, so Ctrl+d isn't taken for d;
+/// otherwise the first keymap to bind the key wins.
+pub fn lookup(keymaps: &[Keymap], code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+    let find = |modified: bool| {
+        keymaps
+            .iter()
+            .flat_map(|keymap| keymap.bindings)
+            .find(|binding| {
+                binding
+                    .keys
+                    .iter()
+                    .any(|key| key.modifiers.is_empty() != modified && key.matches(code, modifiers))
+            })
+    };
+    find(true)
+        .or_else(|| find(false))
+        .map(|binding| binding.action.clone())
+}
+
+const fn key(c: char) -> Key {
+    code(KeyCode::Char(c))
+}
+
+const fn code(code: KeyCode) -> Key {
+    Key {
+        code,
+        modifiers: KeyModifiers::NONE,
+    }
+}
+
+const fn ctrl(key: Key) -> Key {
+    Key {
+        code: key.code,
+        modifiers: KeyModifiers::CONTROL,
+    }
+}
+
+const fn alt(key: Key) -> Key {
+    Key {
+        code: key.code,
+        modifiers: KeyModifiers::ALT,
+    }
+}
+
+const fn bind(keys: &'static [Key], action: Action, description: &'static str) -> Binding {
+    Binding {
+        keys,
+        action,
+        description,
+    }
+}
+
+/// Tab numbers. A count before a key is typed the same way, so a lone
+/// number only switches tabs if the next key doesn't take a count.
+const TAB_NUMBER: &str = "Switch to that tab; before a key, repeat it (5j, 3n)";
+
+pub const NAVIGATION: Keymap = Keymap {
+    title: "Navigation",
+    bindings: &[
+        bind(
+            &[key('k'), code(KeyCode::Up)],
+            Action::NavigateUp,
+            "Move up",
+        ),
+        bind(
+            &[key('j'), code(KeyCode::Down)],
+            Action::NavigateDown,
+            "Move down",
+        ),
+        bind(
+            &[key('h'), code(KeyCode::Left)],
+            Action::NavigateLeft,
+            "Focus left / go back",
+        ),
+        bind(
+            &[key('l'), code(KeyCode::Right)],
+            Action::NavigateRight,
+            "Focus right / open",
+        ),
+        bind(&[code(KeyCode::Enter)], Action::Select, "Select item"),
+        bind(
+            &[code(KeyCode::Esc), code(KeyCode::Backspace)],
+            Action::Back,
+            "Go back",
+        ),
+        bind(&[key('g')], Action::JumpToTop, "Jump to top"),
+        bind(&[key('G')], Action::JumpToBottom, "Jump to bottom"),
+        bind(
+            &[ctrl(key('d'))],
+            Action::ScrollHalfPageDown,
+            "Scroll half page down",
+        ),
+        bind(
+            &[ctrl(key('u'))],
+            Action::ScrollHalfPageUp,
+            "Scroll half page up",
+        ),
+        bind(&[key('1')], Action::CountDigit(1), TAB_NUMBER),
+        bind(&[key('2')], Action::CountDigit(2), TAB_NUMBER),
+        bind(&[key('3')], Action::CountDigit(3), TAB_NUMBER),
+        bind(&[key('4')], Action::CountDigit(4), TAB_NUMBER),
+        bind(&[key('5')], Action::CountDigit(5), TAB_NUMBER),
+        bind(&[key('6')], Action::CountDigit(6), TAB_NUMBER),
+        bind(&[key('7')], Action::CountDigit(7), TAB_NUMBER),
+        bind(&[key('8')], Action::CountDigit(8), TAB_NUMBER),
+        bind(&[key('9')], Action::CountDigit(9), TAB_NUMBER),
+    ],
+};
+
+/// Keys shared by the main view, the focused lyrics panel and the full-screen view.
+pub const PANELS: Keymap = Keymap {
+    title: "Tabs & Panels",
+    bindings: &[
+        bind(
+            &[code(KeyCode::Tab)],
+            Action::CycleFocus(1),
+            "Focus next panel",
+        ),
+        bind(
+            &[code(KeyCode::BackTab)],
+            Action::CycleFocus(-1),
+            "Focus previous panel",
+        ),
+        bind(
+            &[alt(key('h')), alt(code(KeyCode::Left))],
+            Action::PrevTab,
+            "Previous tab",
+        ),
+        bind(
+            &[alt(key('l')), alt(code(KeyCode::Right))],
+            Action::NextTab,
+            "Next tab",
+        ),
+        bind(
+            &[ctrl(code(KeyCode::Left))],
+            Action::ResizeSidePanel(SIDE_PANEL_STEP),
+            "Widen queue or lyrics panel",
+        ),
+        bind(
+            &[ctrl(code(KeyCode::Right))],
+            Action::ResizeSidePanel(-SIDE_PANEL_STEP),
+            "Narrow queue or lyrics panel",
+        ),
+        bind(&[key('q'), ctrl(key('c'))], Action::Quit, "Quit"),
+    ],
+};
+
+/// Playback keys, which work wherever [`PANELS`] do.
+pub const PLAYBACK: Keymap = Keymap {
+    title: "Playback",
+    bindings: &[
+        bind(&[key(' ')], Action::PlayPause, "Play/Pause"),
+        bind(&[key('n')], Action::NextTrack, "Next track"),
+        bind(&[key('p')], Action::PreviousTrack, "Previous track"),
+        bind(
+            &[key(','), key('<')],
+            Action::SeekBackward,
+            "Seek backward (10s)",
+        ),
+        bind(
+            &[key('.'), key('>')],
+            Action::SeekForward,
+            "Seek forward (10s)",
+        ),
+        bind(
+            &[key('[')],
+            Action::SeekBackwardLarge,
+            "Seek backward (1 min)",
+        ),
+        bind(
+            &[key(']')],
+            Action::SeekForwardLarge,
+            "Seek forward (1 min)",
+        ),
+        bind(&[key('=')], Action::VolumeUp, "Volume up"),
+        bind(&[key('-')], Action::VolumeDown, "Volume down"),
+        bind(&[key('+')], Action::VolumeUpFine, "Volume up by 1%"),
+        bind(&[key('_')], Action::VolumeDownFine, "Volume down by 1%"),
+        bind(&[key('0')], Action::ToggleMute, "Mute/unmute"),
+        bind(&[key('{')], Action::SpeedDown, "Playback speed down"),
+        bind(&[key('}')], Action::SpeedUp, "Playback speed up"),
+        bind(&[key('s')], Action::ToggleShuffle, "Toggle shuffle"),
+        bind(&[key('r')], Action::CycleRepeat, "Cycle repeat mode"),
+        bind(
+            &[key('w')],
+            Action::CycleOutput,
+            "Switch output: local, network, renderers",
+        ),
+        bind(
+            &[key('t')],
+            Action::ToggleRemainingTime,
+            "Show elapsed/remaining time",
+        ),
+    ],
+};
+
+pub const LIBRARY: Keymap = Keymap {
+    title: "Queue & Library",
+    bindings: &[
+        bind(
+            &[key('a')],
+            Action::AppendToQueue,
+            "Add to queue (without playing)",
+        ),
+        bind(&[key('c')], Action::ClearQueue, "Clear queue"),
+        bind(&[key('C')], Action::ToggleConsume, "Toggle consume mode"),
+        bind(
+            &[key('D')],
+            Action::DedupQueue,
+            "Remove duplicate songs from queue",
+        ),
+        bind(
+            &[key('S')],
+            Action::SortQueue(QueueSort::ArtistAlbumTrack),
+            "Sort queue by artist/album/track",
+        ),
+        bind(
+            &[key('T')],
+            Action::SortQueue(QueueSort::Title),
+            "Sort queue by title",
+        ),
+        bind(
+            &[key('d'), code(KeyCode::Delete)],
+            Action::RemoveSelectedFromQueue,
+            "Remove selected from queue/playlist",
+        ),
+        bind(
+            &[key('X')],
+            Action::RemoveSourceFromQueue,
+            "Remove all from selected's album/playlist",
+        ),
+        bind(&[key('P')], Action::PlayAlbum, "Play open album"),
+        bind(
+            &[key('Z')],
+            Action::ShuffleAlbum,
+            "Shuffle open album (in a genre: queue it all shuffled)",
+        ),
+        bind(&[key('A')], Action::QueueAlbum, "Queue open album"),
+        bind(
+            &[key('F')],
+            Action::ToggleAlbumStar,
+            "Toggle star on open album",
+        ),
+        bind(
+            &[key(';')],
+            Action::StepSimilarAlbum(1),
+            "Pick next similar album (Enter: open, a: queue, Esc: back)",
+        ),
+        bind(
+            &[key(':')],
+            Action::StepSimilarAlbum(-1),
+            "Pick previous similar album",
+        ),
+        bind(
+            &[ctrl(key('p'))],
+            Action::OpenPlaylistPicker,
+            "Add selection to a playlist",
+        ),
+        bind(
+            &[key('E')],
+            Action::ExportM3u,
+            "Export playlist (or the queue) as .m3u8",
+        ),
+        bind(
+            &[ctrl(key('o'))],
+            Action::OpenImportPrompt,
+            "Import an .m3u/.m3u8 file",
+        ),
+        bind(
+            &[key('e')],
+            Action::OpenExternal(ExternalTarget::Track),
+            "Open track in external player",
+        ),
+        bind(
+            &[ctrl(key('e'))],
+            Action::OpenExternal(ExternalTarget::Queue),
+            "Open queue in external player",
+        ),
+        bind(
+            &[key('o')],
+            Action::JumpToCurrentTrack,
+            "Jump to current track in queue",
+        ),
+        bind(
+            &[key('O')],
+            Action::CycleSort,
+            "Cycle sort order of library list",
+        ),
+        bind(&[key('V')], Action::ReverseSort, "Reverse sort order"),
+        bind(
+            &[key('b')],
+            Action::ToggleGroup,
+            "Collapse/expand artist letter or queue album",
+        ),
+        bind(&[key('B')], Action::ToggleGroups, "Collapse/expand all"),
+        bind(
+            &[key('m')],
+            Action::CycleListSource,
+            "Cycle Albums/Songs tab source",
+        ),
+        bind(
+            &[key('y')],
+            Action::OpenYearPicker,
+            "Albums: pick years; Songs: source/filter",
+        ),
+        bind(&[key('z')], Action::GoToRandomAlbum, "Open a random album"),
+        bind(
+            &[ctrl(key('r'))],
+            Action::QueueRandomSongs,
+            "Queue random songs (open genre/years)",
+        ),
+        // The index is filled in by the app
+        bind(
+            &[key('J')],
+            Action::MoveQueueItem(0, 1),
+            "Move queue/playlist item down",
+        ),
+        bind(
+            &[key('K')],
+            Action::MoveQueueItem(0, -1),
+            "Move queue/playlist item up",
+        ),
+        bind(
+            &[key('u')],
+            Action::ToggleQueueMark,
+            "Mark queue item to move with others",
+        ),
+        bind(
+            &[key('#')],
+            Action::OpenMovePrompt,
+            "Move queue item(s) to a position",
+        ),
+        bind(
+            &[key('*')],
+            Action::ToggleStar,
+            "Toggle star on current song",
+        ),
+        bind(&[key('R')], Action::RefreshLibrary, "Refresh library"),
+    ],
+};
+
+/// Hiding songs and albums from every view; both ask before doing it.
+pub const HIDING: Keymap = Keymap {
+    title: "Hiding",
+    bindings: &[
+        bind(
+            &[key('H')],
+            Action::HideSelected,
+            "Hide selected song/album from all views",
+        ),
+        bind(&[key('U')], Action::UnhideAll, "Unhide everything"),
+    ],
+};
+
+pub const OTHER: Keymap = Keymap {
+    title: "Other",
+    bindings: &[
+        bind(&[key('/')], Action::OpenSearch, "Search"),
+        bind(
+            &[key('f')],
+            Action::OpenFilter,
+            "Filter focused list (Esc clears)",
+        ),
+        bind(
+            &[key('L')],
+            Action::ToggleLyrics,
+            "Toggle lyrics panel (takes the focus)",
+        ),
+        bind(
+            &[key('i')],
+            Action::ShowTrackInfo,
+            "Show track info (album info in an album)",
+        ),
+        bind(
+            &[key('v')],
+            Action::ToggleFullscreen,
+            "Full-screen now playing",
+        ),
+        bind(&[key('Q')], Action::ToggleQueuePanel, "Toggle queue panel"),
+        bind(
+            &[ctrl(key('g'))],
+            Action::ToggleQueueGrouping,
+            "Group queue by album",
+        ),
+        bind(&[key('?')], Action::ShowHelp, "Show this help"),
+        bind(&[key('x')], Action::DismissToasts, "Dismiss notifications"),
+        bind(&[key('M')], Action::ShowMessages, "Message history"),
+        bind(
+            &[key('~')],
+            Action::ShowLog,
+            "Log viewer (l: level, /: search)",
+        ),
+        bind(
+            &[key('I')],
+            Action::ShowServerStatus,
+            "Server status (r: refresh)",
+        ),
+        bind(
+            &[key('W')],
+            Action::ShowListeners,
+            "What others are listening to (a: queue)",
+        ),
+    ],
+};
+
+/// Keys of the lyrics panel while it has the focus, on top of [`PANELS`] and [`PLAYBACK`].
+pub const LYRICS: Keymap = Keymap {
+    title: "Lyrics Panel",
+    bindings: &[
+        bind(
+            &[key('L'), code(KeyCode::Esc)],
+            Action::ToggleLyrics,
+            "Close (Esc first goes back to the playing line)",
+        ),
+        bind(
+            &[key('k'), code(KeyCode::Up)],
+            Action::LyricsScrollUp,
+            "Scroll up",
+        ),
+        bind(
+            &[key('j'), code(KeyCode::Down)],
+            Action::LyricsScrollDown,
+            "Scroll down",
+        ),
+        bind(
+            &[code(KeyCode::Enter)],
+            Action::LyricsSeekToLine,
+            "Jump to the selected synced line",
+        ),
+        bind(
+            &[key('(')],
+            Action::LyricsDelay(-LYRICS_DELAY_STEP_MS),
+            "Shift lyrics earlier (100ms)",
+        ),
+        bind(
+            &[key(')')],
+            Action::LyricsDelay(LYRICS_DELAY_STEP_MS),
+            "Shift lyrics later (100ms)",
+        ),
+        bind(&[key('e')], Action::ExportLyrics, "Export lyrics as .lrc"),
+        bind(
+            &[key('l')],
+            Action::CycleLyricsVersion,
+            "Next language or source",
+        ),
+        bind(
+            &[key('h'), code(KeyCode::Left)],
+            Action::NavigateLeft,
+            "Back to the library",
+        ),
+    ],
+};
+
+/// Keys of the full-screen view, on top of [`PANELS`] and [`PLAYBACK`].
+pub const FULLSCREEN: Keymap = Keymap {
+    title: "Full-screen View",
+    bindings: &[
+        bind(
+            &[key('v'), code(KeyCode::Esc)],
+            Action::ToggleFullscreen,
+            "Leave the full-screen view",
+        ),
+        bind(&[key('L')], Action::ToggleLyrics, "Show/hide lyrics"),
+    ],
+};
+
+pub const TRACK_INFO: Keymap = Keymap {
+    title: "Track Info",
+    bindings: &[
+        bind(
+            &[code(KeyCode::Esc), key('i'), key('q')],
+            Action::HideTrackInfo,
+            "Close",
+        ),
+        bind(&[key('a')], Action::GoToPlayingAlbum, "Go to playing album"),
+        bind(
+            &[key('r')],
+            Action::GoToPlayingArtist,
+            "Go to playing artist",
+        ),
+        bind(&[key('*')], Action::ToggleStar, "Star playing track"),
+        bind(
+            &[key('0')],
+            Action::RatePlayingTrack(0),
+            "Rate playing track",
+        ),
+        bind(
+            &[key('1')],
+            Action::RatePlayingTrack(1),
+            "Rate playing track",
+        ),
+        bind(
+            &[key('2')],
+            Action::RatePlayingTrack(2),
+            "Rate playing track",
+        ),
+        bind(
+            &[key('3')],
+            Action::RatePlayingTrack(3),
+            "Rate playing track",
+        ),
+        bind(
+            &[key('4')],
+            Action::RatePlayingTrack(4),
+            "Rate playing track",
+        ),
+        bind(
+            &[key('5')],
+            Action::RatePlayingTrack(5),
+            "Rate playing track",
+        ),
+        bind(
+            &[key('c')],
+            Action::CopyTrack(CopyTarget::ArtistTitle),
+            "Copy artist – title",
+        ),
+        bind(
+            &[key('u')],
+            Action::CopyTrack(CopyTarget::StreamUrl),
+            "Copy stream URL",
+        ),
+        bind(
+            &[key('w')],
+            Action::CopyTrack(CopyTarget::WebUrl),
+            "Copy web URL",
+        ),
+    ],
+};
+
+/// Keys while searching. Other characters are typed into the query.
+pub const SEARCH: Keymap = Keymap {
+    title: "Search",
+    bindings: &[
+        bind(&[code(KeyCode::Esc)], Action::CloseSearch, "Close search"),
+        bind(
+            &[code(KeyCode::Enter)],
+            Action::Select,
+            "Open selected result",
+        ),
+        bind(
+            &[ctrl(key('a'))],
+            Action::SearchAppendToQueue,
+            "Queue selected result",
+        ),
+        bind(
+            &[ctrl(key('n'))],
+            Action::SearchPlayNext,
+            "Play selected result next",
+        ),
+        bind(&[code(KeyCode::Up)], Action::NavigateUp, "Move up"),
+        bind(&[code(KeyCode::Down)], Action::NavigateDown, "Move down"),
+        bind(
+            &[code(KeyCode::Tab), code(KeyCode::Right)],
+            Action::NavigateRight,
+            "Next section",
+        ),
+        bind(
+            &[code(KeyCode::BackTab), code(KeyCode::Left)],
+            Action::NavigateLeft,
+            "Previous section",
+        ),
+        bind(
+            &[code(KeyCode::Backspace)],
+            Action::SearchBackspace,
+            "Delete last character",
+        ),
+    ],
+};
+
+/// Keys of the main view.
+pub const MAIN: &[Keymap] = &[NAVIGATION, PANELS, PLAYBACK, LIBRARY, HIDING, OTHER];
+
+/// Every keymap, in the order the help lists them.
+pub const HELP: &[Keymap] = &[
+    NAVIGATION, PANELS, PLAYBACK, LIBRARY, HIDING, OTHER, LYRICS, FULLSCREEN, TRACK_INFO, SEARCH,
+];
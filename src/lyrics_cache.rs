@@ -0,0 +1,141 @@
+//! Lyrics kept on disk: fetched lyrics are cached so each song is looked up
+//! once (and still shows offline), and can be exported as `.lrc` files.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+
+use crate::client::models::{Song, StructuredLyrics};
+use crate::config::Config;
+use crate::player::cache::checksum;
+
+/// Get the cache file path for a song's lyrics.
+fn cache_path(song_id: &str) -> Result<PathBuf> {
+    // Song IDs are server-defined, so hash them into safe file names
+    let key = checksum(song_id.as_bytes());
+    Ok(Config::cache_dir()?
+        .join("lyrics")
+        .join(format!("{}.json", key)))
+}
+
+/// Get cached lyrics for a song. An empty list means none were found last time.
+pub fn get(song_id: &str) -> Option<Vec<StructuredLyrics>> {
+    let contents = std::fs::read_to_string(cache_path(song_id).ok()?).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Cache a song's lyrics (an empty list records that none were found).
+pub fn put(song_id: &str, lyrics: &[StructuredLyrics]) -> Result<()> {
+    let path = cache_path(song_id)?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(&path, serde_json::to_string(lyrics)?)?;
+
+    Ok(())
+}
+
+/// Write lyrics to `<dir>/<artist> - <title>.lrc` and return the file path.
+pub fn export(
+    dir: &Path,
+    song: &Song,
+    lyrics: &StructuredLyrics,
+    delay_ms: i64,
+) -> Result<PathBuf> {
+    let name = match &song.artist {
+        Some(artist) => format!("{} - {}", artist, song.title),
+        None => song.title.clone(),
+    };
+    // Keep the name usable as a single path component on any platform
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.lrc", name.trim()));
+    std::fs::write(&path, format_lrc(song, lyrics, delay_ms))?;
+
+    Ok(path)
+}
+
+/// Format lyrics as LRC. The lyrics offset and the song's delay are applied to
+/// the timestamps, so the file plays in step without an `[offset:]` tag.
+pub fn format_lrc(song: &Song, lyrics: &StructuredLyrics, delay_ms: i64) -> String {
+    let mut lrc = String::new();
+    if let Some(artist) = &song.artist {
+        let _ = writeln!(lrc, "[ar:{}]", artist);
+    }
+    let _ = writeln!(lrc, "[ti:{}]", song.title);
+    if let Some(album) = &song.album {
+        let _ = writeln!(lrc, "[al:{}]", album);
+    }
+
+    for line in &lyrics.line {
+        match line.start.filter(|_| lyrics.synced) {
+            Some(start) => {
+                let ms = (start - lyrics.offset + delay_ms).max(0);
+                let _ = writeln!(
+                    lrc,
+                    "[{:02}:{:02}.{:02}]{}",
+                    ms / 60_000,
+                    ms / 1000 % 60,
+                    ms % 1000 / 10,
+                    line.value
+                );
+            }
+            None => {
+                let _ = writeln!(lrc, "{}", line.value);
+            }
+        }
+    }
+
+    lrc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::lrclib::parse_lrc;
+    use crate::client::models::LyricLine;
+
+    #[test]
+    fn test_format_lrc_applies_offset_and_delay() {
+        let song: Song = serde_json::from_value(
+            serde_json::json!({ "id": "1", "title": "Song", "artist": "Artist" }),
+        )
+        .unwrap();
+        let lyrics = StructuredLyrics {
+            display_artist: None,
+            display_title: None,
+            lang: String::from("eng"),
+            synced: true,
+            offset: 500,
+            line: vec![
+                LyricLine {
+                    start: Some(1_000),
+                    value: String::from("First"),
+                },
+                LyricLine {
+                    start: Some(65_430),
+                    value: String::from("Second"),
+                },
+            ],
+        };
+
+        let lrc = format_lrc(&song, &lyrics, 200);
+        assert!(lrc.starts_with("[ar:Artist]\n[ti:Song]\n"));
+
+        let (lines, offset) = parse_lrc(&lrc);
+        let starts: Vec<Option<i64>> = lines.iter().map(|line| line.start).collect();
+        assert_eq!(offset, 0);
+        assert_eq!(starts, vec![Some(700), Some(65_130)]);
+    }
+}
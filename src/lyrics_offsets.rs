@@ -0,0 +1,40 @@
+//! Per-song lyrics timing adjustments persisted to disk.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use color_eyre::Result;
+
+use crate::config::Config;
+
+/// Get the lyrics offsets file path.
+fn offsets_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join("lyrics_offsets.json"))
+}
+
+/// Load lyrics delays in milliseconds, keyed by song ID.
+pub fn load() -> Result<HashMap<String, i64>> {
+    let path = offsets_path()?;
+
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let contents = std::fs::read_to_string(&path)?;
+    let offsets: HashMap<String, i64> = serde_json::from_str(&contents)?;
+
+    Ok(offsets)
+}
+
+/// Save lyrics delays.
+pub fn save(offsets: &HashMap<String, i64>) -> Result<()> {
+    let path = offsets_path()?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(&path, serde_json::to_string(offsets)?)?;
+
+    Ok(())
+}
@@ -0,0 +1,228 @@
+//! M3U playlists: the queue or a playlist exported as an `.m3u8` file, and
+//! files from other players imported by finding their entries in the library.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+
+use crate::client::models::Song;
+
+/// Longest difference in length, in seconds, between an entry and a song
+/// found for it with a different artist.
+const DURATION_TOLERANCE: i32 = 3;
+
+/// A track listed in an M3U file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Entry {
+    /// The line naming the file or URL, reported when nothing matches
+    pub location: String,
+    pub artist: Option<String>,
+    pub title: String,
+    /// Length in seconds
+    pub duration: Option<i32>,
+}
+
+impl Entry {
+    /// Pick the song this entry names out of search results: the same title
+    /// by the same artist, or with the same length when the artist is
+    /// unknown or spelled differently.
+    pub fn best_match<'a>(&self, songs: &'a [Song]) -> Option<&'a Song> {
+        let title = normalize(&self.title);
+        let candidates: Vec<&Song> = songs
+            .iter()
+            .filter(|song| normalize(&song.title) == title)
+            .collect();
+        let artist = self.artist.as_deref().map(normalize);
+        let by_artist = artist.as_ref().and_then(|artist| {
+            candidates
+                .iter()
+                .find(|song| song.artist.as_deref().map(normalize).as_ref() == Some(artist))
+        });
+        let by_duration = self.duration.and_then(|duration| {
+            candidates.iter().find(|song| {
+                song.duration
+                    .is_some_and(|length| (length - duration).abs() <= DURATION_TOLERANCE)
+            })
+        });
+        by_artist
+            .or(by_duration)
+            .or_else(|| {
+                // Only the title to go on: take it if it's unambiguous
+                (self.artist.is_none() && self.duration.is_none() && candidates.len() == 1)
+                    .then(|| &candidates[0])
+            })
+            .copied()
+    }
+}
+
+/// Lowercase text and drop punctuation, so "Don't Stop" matches "Dont stop".
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Read the entries of an M3U file. Extended info (`#EXTINF`) gives the
+/// artist, title and length; without it they're guessed from the file name.
+pub fn parse(text: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut info: Option<(Option<i32>, String)> = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(extinf) = line.strip_prefix("#EXTINF:") {
+            info = extinf.split_once(',').map(|(duration, name)| {
+                let duration = duration
+                    .split_whitespace()
+                    .next()
+                    .and_then(|d| d.parse().ok());
+                (duration.filter(|&d| d > 0), name.trim().to_string())
+            });
+        } else if !line.is_empty() && !line.starts_with('#') {
+            let (duration, name) = match info.take() {
+                Some((duration, name)) if !name.is_empty() => (duration, name),
+                _ => (None, name_from_location(line)),
+            };
+            let (artist, title) = match name.split_once(" - ") {
+                Some((artist, title)) => (Some(artist.trim().to_string()), title.trim()),
+                None => (None, name.as_str()),
+            };
+            entries.push(Entry {
+                location: line.to_string(),
+                artist,
+                title: title.to_string(),
+                duration,
+            });
+        }
+    }
+    entries
+}
+
+/// Guess a track's name from its file: `Music/Artist/01 - Title.flac`
+/// gives `Title`, `Artist - Title.mp3` gives `Artist - Title`.
+fn name_from_location(location: &str) -> String {
+    let file = location
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(location)
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(location);
+    let stem = match file.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() && extension.len() <= 4 => stem,
+        _ => file,
+    };
+    // Drop a leading track number
+    let trimmed = stem.trim_start_matches(|c: char| c.is_ascii_digit());
+    let trimmed = if trimmed.len() < stem.len() {
+        trimmed.trim_start_matches(['.', '-', '_', ' '])
+    } else {
+        stem
+    };
+    let name = if trimmed.is_empty() { stem } else { trimmed };
+    name.replace('_', " ")
+}
+
+/// Format songs as an extended M3U playlist, each pointing at `location`.
+pub fn format(songs: &[Song], location: impl Fn(&Song) -> String) -> String {
+    let mut m3u = String::from("#EXTM3U\n");
+    for song in songs {
+        let _ = writeln!(
+            m3u,
+            "#EXTINF:{},{} - {}",
+            song.duration.unwrap_or(-1),
+            song.display_artist(),
+            song.title
+        );
+        let _ = writeln!(m3u, "{}", location(song));
+    }
+    m3u
+}
+
+/// Write a playlist to `<dir>/<name>.m3u8` and return the file path.
+pub fn export(dir: &Path, name: &str, contents: &str) -> Result<PathBuf> {
+    // Keep the name usable as a single path component on any platform
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.m3u8", name.trim()));
+    std::fs::write(&path, contents)?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let entries = parse(
+            "#EXTM3U\n\
+             #EXTINF:245,Miles Davis - So What\n\
+             https://music.example/rest/stream?id=1\n\
+             \n\
+             /music/Bill Evans/03 - Peace_Piece.flac\n\
+             #EXTINF:-1,\n\
+             C:\\Music\\Nardis.mp3\n",
+        );
+        assert_eq!(
+            entries[0],
+            Entry {
+                location: String::from("https://music.example/rest/stream?id=1"),
+                artist: Some(String::from("Miles Davis")),
+                title: String::from("So What"),
+                duration: Some(245),
+            }
+        );
+        assert_eq!(entries[1].title, "Peace Piece");
+        assert_eq!(entries[1].artist, None);
+        assert_eq!(entries[2].title, "Nardis");
+        assert_eq!(entries[2].duration, None);
+        assert_eq!(entries.len(), 3);
+    }
+
+    #[test]
+    fn test_best_match() {
+        let song = |id: &str, title: &str, artist: &str, duration: i32| -> Song {
+            serde_json::from_value(serde_json::json!({
+                "id": id, "title": title, "artist": artist, "duration": duration,
+            }))
+            .unwrap()
+        };
+        let songs = [
+            song("1", "So What", "Miles Davis Quintet", 560),
+            song("2", "So what?", "Miles Davis", 545),
+            song("3", "So What (Live)", "Miles Davis", 600),
+        ];
+        let entry = |artist: Option<&str>, duration: Option<i32>| Entry {
+            artist: artist.map(String::from),
+            title: String::from("So What"),
+            duration,
+            ..Entry::default()
+        };
+        let id = |entry: Entry| entry.best_match(&songs).map(|song| song.id.clone());
+
+        assert_eq!(id(entry(Some("miles davis"), None)).as_deref(), Some("2"));
+        assert_eq!(id(entry(Some("Miles"), Some(561))).as_deref(), Some("1"));
+        assert_eq!(id(entry(Some("Coltrane"), None)), None);
+        // Two songs with the title and nothing else to tell them apart
+        assert_eq!(id(entry(None, None)), None);
+
+        let m3u = format(&songs[..1], |song| format!("song-{}", song.id));
+        assert_eq!(
+            m3u,
+            "#EXTM3U\n#EXTINF:560,Miles Davis Quintet - So What\nsong-1\n"
+        );
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::time::{Duration, Instant};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use color_eyre::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers, MouseEventKind};
 use tokio::sync::mpsc;
@@ -10,15 +10,43 @@ use tokio::sync::mpsc;
 mod action;
 mod app;
 mod client;
+mod clipboard;
+mod columns;
 mod config;
+mod count;
+mod doctor;
+mod external;
+mod focus;
+mod hidden;
+mod history;
+mod keymap;
+mod lyrics_cache;
+mod lyrics_offsets;
+mod m3u;
 mod mpris;
 mod player;
+mod shuffle;
+mod sleep;
+mod smart;
+mod sort;
+mod state;
 mod tui;
 mod ui;
 
-use action::{Action, PlayerState, RepeatMode, Tab};
+use action::{Action, PlayerState, RepeatMode};
 use app::App;
 use config::Config;
+use focus::Focus;
+
+/// Longest gap between the clicks of a double-click.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+/// Furthest the pointer may move between the clicks of a double-click, in cells.
+const DOUBLE_CLICK_DISTANCE: u16 = 1;
+
+/// Longest time between redraws, for anything that changes without an
+/// action, like the time-of-day greeting.
+const HEARTBEAT: Duration = Duration::from_secs(1);
 
 /// State for double-click detection.
 #[derive(Default)]
@@ -36,6 +64,7 @@ struct MprisState {
     volume: u8,
     shuffle: bool,
     repeat: RepeatMode,
+    speed: f32,
 }
 
 /// Command-line arguments.
@@ -58,6 +87,16 @@ struct Args {
     /// Password (overrides config)
     #[arg(short, long)]
     password: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Subcommands run instead of the player.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check the config, server connection, audio, and terminal, and print a report
+    Doctor,
 }
 
 #[tokio::main]
@@ -66,10 +105,7 @@ async fn main() -> Result<()> {
     tui::install_hooks()?;
 
     // Initialize logging
-    let log_file = dirs::cache_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("subsonic-tui")
-        .join("subsonic-tui.log");
+    let log_file = Config::log_path();
 
     if let Some(parent) = log_file.parent() {
         std::fs::create_dir_all(parent)?;
@@ -94,7 +130,9 @@ async fn main() -> Result<()> {
     let args = Args::parse();
 
     // Load configuration
-    let mut config = Config::load().unwrap_or_default();
+    let loaded = Config::load();
+    let config_error = loaded.as_ref().err().map(|e| e.to_string());
+    let mut config = loaded.unwrap_or_default();
 
     // Apply command-line overrides
     if let Some(server) = args.server {
@@ -107,6 +145,11 @@ async fn main() -> Result<()> {
         config.server.password = Some(password);
     }
 
+    if let Some(Command::Doctor) = args.command {
+        let healthy = doctor::run(&config, config_error).await;
+        std::process::exit(if healthy { 0 } else { 1 });
+    }
+
     // Create action channel
     let (action_tx, mut action_rx) = mpsc::unbounded_channel::<Action>();
 
@@ -125,6 +168,10 @@ async fn main() -> Result<()> {
         }
     };
 
+    // Pause before the system sleeps
+    let fade = Duration::from_millis(app.config.player.fade_ms.into());
+    sleep::watch(action_tx.clone(), fade);
+
     // Initialize terminal
     let mut terminal = tui::init()?;
 
@@ -137,12 +184,20 @@ async fn main() -> Result<()> {
     // Track state for mouse double-click detection
     let mut click_state = ClickState::default();
 
+    // Window title last set
+    let mut window_title = String::new();
+
     // Main event loop
-    let tick_rate = Duration::from_millis(100);
+    let tick_rate = Duration::from_millis(app.config.ui.tick_rate_ms.max(10));
+    let mut last_draw = Instant::now();
 
     loop {
-        // Render UI
-        terminal.draw(|frame| ui::render(frame, &mut app))?;
+        // Render UI when something changed, or for the heartbeat
+        if app.redraw || last_draw.elapsed() >= HEARTBEAT {
+            terminal.draw(|frame| ui::render(frame, &mut app))?;
+            app.redraw = false;
+            last_draw = Instant::now();
+        }
 
         // Handle MPRIS events
         if let Some(ref mut handle) = mpris_handle {
@@ -169,7 +224,7 @@ async fn main() -> Result<()> {
                         Action::SeekTo(pos_secs)
                     }
                     mpris::MprisEvent::SetVolume(vol) => {
-                        // Convert 0.0-1.0 to 0-100
+                        // Convert 0.0-1.0 (more when amplified) to percent
                         let vol_percent = (vol * 100.0) as u8;
                         Action::SetVolume(vol_percent)
                     }
@@ -183,6 +238,18 @@ async fn main() -> Result<()> {
                             Action::None
                         }
                     }
+                    mpris::MprisEvent::SetRate(rate) => {
+                        // A rate of zero means pause, per the MPRIS spec
+                        if rate <= 0.0 {
+                            if app.now_playing.state == PlayerState::Playing {
+                                Action::PlayPause
+                            } else {
+                                Action::None
+                            }
+                        } else {
+                            Action::SetSpeed(rate as f32)
+                        }
+                    }
                     mpris::MprisEvent::Raise => Action::None,
                     mpris::MprisEvent::Quit => Action::Quit,
                 };
@@ -195,11 +262,15 @@ async fn main() -> Result<()> {
         // Handle events with timeout
         if event::poll(tick_rate)? {
             match event::read()? {
-                Event::Key(key) => {
-                    if key.kind == KeyEventKind::Press {
-                        let action = handle_key_event(key.code, key.modifiers, &app);
-                        if action != Action::None {
-                            action_tx.send(action)?;
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    let action = handle_key_event(key.code, key.modifiers, &app);
+                    let (count, tab) = app.count.take(&action, &app.library.tabs);
+                    if let Some(tab) = tab {
+                        action_tx.send(Action::SwitchTab(tab))?;
+                    }
+                    if action != Action::None {
+                        for _ in 0..count {
+                            action_tx.send(action.clone())?;
                         }
                     }
                 }
@@ -219,8 +290,12 @@ async fn main() -> Result<()> {
         // Send tick action
         action_tx.send(Action::Tick)?;
 
-        // Process all pending actions
+        // Process all pending actions; ticks decide for themselves if
+        // anything changed
         while let Ok(action) = action_rx.try_recv() {
+            if action != Action::Tick {
+                app.redraw = true;
+            }
             app.handle_action(action).await?;
         }
 
@@ -229,6 +304,19 @@ async fn main() -> Result<()> {
             sync_mpris_state(&app, &mut mpris_state, handle);
         }
 
+        // Announce track and state changes in the window title
+        if !app.config.ui.title.is_empty() {
+            let title = ui::title::window_title(
+                &app.config.ui.title,
+                app.now_playing.current_song.as_ref(),
+                app.now_playing.state,
+            );
+            if title != window_title {
+                tui::set_title(&title)?;
+                window_title = title;
+            }
+        }
+
         // Check if we should quit
         if app.should_quit {
             break;
@@ -243,143 +331,266 @@ async fn main() -> Result<()> {
 
 /// Map key events to actions.
 fn handle_key_event(code: KeyCode, modifiers: KeyModifiers, app: &App) -> Action {
+    // Handle the password prompt
+    if app.login.visible {
+        return match code {
+            KeyCode::Enter => Action::LoginSubmit,
+            KeyCode::Esc => Action::LoginCancel,
+            KeyCode::Backspace => Action::LoginBackspace,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
+            KeyCode::Char(c) => Action::LoginInput(c),
+            _ => Action::None,
+        };
+    }
+
     // Handle search mode separately
     if app.search.active {
         return handle_search_key(code, modifiers);
     }
 
+    // Handle quick filter input
+    if app.library.filtering || app.queue.filtering {
+        return handle_filter_key(code);
+    }
+
+    // Handle year picker popup
+    if app.year_picker.visible {
+        return handle_year_picker_key(code, app.year_picker.editing);
+    }
+
+    // Handle songs filter popup
+    if app.songs_filter.visible {
+        return handle_songs_filter_key(code);
+    }
+
+    // Handle add to playlist popup
+    if app.playlist_picker.visible {
+        return handle_playlist_picker_key(code, app.playlist_picker.new_name.is_some());
+    }
+
+    // Handle the M3U import prompt
+    if app.import_prompt.visible {
+        return match code {
+            KeyCode::Esc => Action::CloseImportPrompt,
+            KeyCode::Enter => Action::ImportM3u,
+            KeyCode::Backspace => Action::ImportPromptBackspace,
+            KeyCode::Char(c) => Action::ImportPromptInput(c),
+            _ => Action::None,
+        };
+    }
+
+    // Handle the queue move prompt
+    if app.move_prompt.visible {
+        return match code {
+            KeyCode::Esc => Action::CloseMovePrompt,
+            KeyCode::Enter => Action::MoveQueueItems,
+            KeyCode::Backspace => Action::MovePromptBackspace,
+            KeyCode::Char(c) => Action::MovePromptInput(c),
+            _ => Action::None,
+        };
+    }
+
+    // Handle confirmation popup
+    if app.confirm.is_some() {
+        return match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => Action::ConfirmYes,
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc | KeyCode::Char('q') => {
+                Action::ConfirmNo
+            }
+            _ => Action::None,
+        };
+    }
+
     // Handle help overlay
-    if app.show_help {
+    if app.help.visible {
+        if app.help.filtering {
+            return match code {
+                KeyCode::Esc => Action::HelpFilterClear,
+                KeyCode::Enter => Action::HelpFilterDone,
+                KeyCode::Backspace => Action::HelpFilterBackspace,
+                KeyCode::Char(c) => Action::HelpFilterInput(c),
+                _ => Action::None,
+            };
+        }
         return match code {
             KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => Action::HideHelp,
+            KeyCode::Down | KeyCode::Char('j') => Action::HelpScroll(1),
+            KeyCode::Up | KeyCode::Char('k') => Action::HelpScroll(-1),
+            KeyCode::PageDown => Action::HelpPage(1),
+            KeyCode::PageUp => Action::HelpPage(-1),
+            KeyCode::Char('d') if modifiers.contains(KeyModifiers::CONTROL) => Action::HelpPage(1),
+            KeyCode::Char('u') if modifiers.contains(KeyModifiers::CONTROL) => Action::HelpPage(-1),
+            KeyCode::Char('g') | KeyCode::Home => Action::HelpTop,
+            KeyCode::Char('G') | KeyCode::End => Action::HelpBottom,
+            KeyCode::Char('/') => Action::HelpFilterStart,
             _ => Action::None,
         };
     }
 
-    // Handle track info popup
-    if app.show_track_info {
+    // Handle message history popup
+    if app.toasts.show_history {
         return match code {
-            KeyCode::Esc | KeyCode::Char('i') | KeyCode::Char('q') => Action::HideTrackInfo,
+            KeyCode::Esc | KeyCode::Char('M') | KeyCode::Char('q') => Action::HideMessages,
+            KeyCode::Down | KeyCode::Char('j') => Action::MessagesNext,
+            KeyCode::Up | KeyCode::Char('k') => Action::MessagesPrev,
             _ => Action::None,
         };
     }
 
-    // Handle lyrics panel navigation
-    if app.lyrics.visible {
-        match code {
-            KeyCode::Char('L') | KeyCode::Esc => return Action::ToggleLyrics,
-            KeyCode::Char('q') => return Action::Quit,
-            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Action::Quit,
-            // Allow playback controls while lyrics are open
-            KeyCode::Char(' ') => return Action::PlayPause,
-            KeyCode::Char('n') => return Action::NextTrack,
-            KeyCode::Char('p') => return Action::PreviousTrack,
-            KeyCode::Char('.') | KeyCode::Char('>') => return Action::SeekForward,
-            KeyCode::Char(',') | KeyCode::Char('<') => return Action::SeekBackward,
-            KeyCode::Char(']') => return Action::SeekForwardLarge,
-            KeyCode::Char('[') => return Action::SeekBackwardLarge,
-            KeyCode::Char('+') | KeyCode::Char('=') => return Action::VolumeUp,
-            KeyCode::Char('-') => return Action::VolumeDown,
-            _ => return Action::None,
+    // Handle log viewer
+    if app.log_viewer.visible {
+        if app.log_viewer.searching {
+            return match code {
+                KeyCode::Esc => Action::LogSearchClear,
+                KeyCode::Enter => Action::LogSearchDone,
+                KeyCode::Backspace => Action::LogSearchBackspace,
+                KeyCode::Char(c) => Action::LogSearchInput(c),
+                _ => Action::None,
+            };
         }
+        return match code {
+            KeyCode::Esc | KeyCode::Char('~') | KeyCode::Char('q') => Action::HideLog,
+            KeyCode::Down | KeyCode::Char('j') => Action::LogNext,
+            KeyCode::Up | KeyCode::Char('k') => Action::LogPrev,
+            KeyCode::Char('g') | KeyCode::Home => Action::LogTop,
+            KeyCode::Char('G') | KeyCode::End => Action::LogBottom,
+            KeyCode::Char('l') => Action::LogCycleLevel,
+            KeyCode::Char('/') => Action::LogSearchStart,
+            _ => Action::None,
+        };
     }
 
-    // Global keys
-    match code {
-        KeyCode::Char('q') => return Action::Quit,
-        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Action::Quit,
-        _ => {}
+    // Handle server status popup
+    if app.server_status.visible {
+        return match code {
+            KeyCode::Esc | KeyCode::Char('I') | KeyCode::Char('q') => Action::HideServerStatus,
+            KeyCode::Char('r') => Action::RefreshServerStatus,
+            _ => Action::None,
+        };
     }
 
-    // Normal mode keys
-    match code {
-        // Navigation
-        KeyCode::Up | KeyCode::Char('k') => Action::NavigateUp,
-        KeyCode::Down | KeyCode::Char('j') => Action::NavigateDown,
-        KeyCode::Left | KeyCode::Char('h') => Action::NavigateLeft,
-        KeyCode::Right | KeyCode::Char('l') => Action::NavigateRight,
-        KeyCode::Enter => Action::Select,
-        KeyCode::Esc | KeyCode::Backspace => Action::Back,
-
-        // Vim-style jump navigation
-        KeyCode::Char('g') => Action::JumpToTop,
-        KeyCode::Char('G') => Action::JumpToBottom,
-        KeyCode::Char('d') if modifiers.contains(KeyModifiers::CONTROL) => {
-            Action::ScrollHalfPageDown
+    // Handle other listeners popup
+    if app.listeners.visible {
+        return match code {
+            KeyCode::Esc | KeyCode::Char('W') | KeyCode::Char('q') => Action::HideListeners,
+            KeyCode::Down | KeyCode::Char('j') => Action::ListenersNext,
+            KeyCode::Up | KeyCode::Char('k') => Action::ListenersPrev,
+            KeyCode::Enter => Action::ListenersGoToAlbum,
+            KeyCode::Char('a') => Action::ListenersQueue,
+            KeyCode::Char('r') => Action::RefreshListeners,
+            _ => Action::None,
+        };
+    }
+
+    // Handle album info popup
+    if app.album_info.visible {
+        return match code {
+            KeyCode::Esc | KeyCode::Char('i') | KeyCode::Char('q') => Action::HideAlbumInfo,
+            KeyCode::Down | KeyCode::Char('j') => Action::ScrollAlbumInfo(1),
+            KeyCode::Up | KeyCode::Char('k') => Action::ScrollAlbumInfo(-1),
+            _ => Action::None,
+        };
+    }
+
+    // Handle track info popup
+    if app.show_track_info {
+        return keymap::lookup(&[keymap::TRACK_INFO], code, modifiers).unwrap_or(Action::None);
+    }
+
+    // The full-screen view and the focused lyrics panel take the keyboard,
+    // leaving the playback keys working
+    let lyrics = app.lyrics.visible && (app.focus == Focus::Lyrics || app.fullscreen);
+    if app.fullscreen || lyrics {
+        // Esc first stops browsing the lyrics and follows the song again
+        if lyrics && app.lyrics.browsing && code == KeyCode::Esc {
+            return Action::LyricsFollow;
         }
-        KeyCode::Char('u') if modifiers.contains(KeyModifiers::CONTROL) => Action::ScrollHalfPageUp,
-
-        // Tab switching
-        KeyCode::Char('1') => Action::SwitchTab(Tab::Artists),
-        KeyCode::Char('2') => Action::SwitchTab(Tab::Albums),
-        KeyCode::Char('3') => Action::SwitchTab(Tab::Songs),
-        KeyCode::Char('4') => Action::SwitchTab(Tab::Playlists),
-        KeyCode::Char('5') => Action::SwitchTab(Tab::Genres),
-        KeyCode::Char('6') => Action::SwitchTab(Tab::Favorites),
-        KeyCode::Tab => Action::NextTab,
-        KeyCode::BackTab => Action::PrevTab,
-
-        // Search
-        KeyCode::Char('/') => Action::OpenSearch,
-
-        // Playback
-        KeyCode::Char(' ') => Action::PlayPause,
-        KeyCode::Char('n') => Action::NextTrack,
-        KeyCode::Char('p') => Action::PreviousTrack,
-        KeyCode::Char('s') => Action::ToggleShuffle,
-        KeyCode::Char('r') => Action::CycleRepeat,
-        KeyCode::Char('.') | KeyCode::Char('>') => Action::SeekForward,
-        KeyCode::Char(',') | KeyCode::Char('<') => Action::SeekBackward,
-        KeyCode::Char(']') => Action::SeekForwardLarge,
-        KeyCode::Char('[') => Action::SeekBackwardLarge,
-
-        // Volume
-        KeyCode::Char('+') | KeyCode::Char('=') => Action::VolumeUp,
-        KeyCode::Char('-') => Action::VolumeDown,
-
-        // Queue
-        KeyCode::Char('a') => Action::AppendToQueue,
-        KeyCode::Char('c') => Action::ClearQueue,
-        KeyCode::Char('d') | KeyCode::Delete => Action::RemoveSelectedFromQueue,
-        KeyCode::Char('o') => Action::JumpToCurrentTrack,
-        KeyCode::Char('J') => Action::MoveQueueItem(0, 1), // Move down (index set in app.rs)
-        KeyCode::Char('K') => Action::MoveQueueItem(0, -1), // Move up (index set in app.rs)
-
-        // Star
-        KeyCode::Char('*') => Action::ToggleStar,
-
-        // Lyrics
-        KeyCode::Char('L') => Action::ToggleLyrics,
-
-        // Help
-        KeyCode::Char('?') => Action::ShowHelp,
-
-        // Track info
-        KeyCode::Char('i') => Action::ShowTrackInfo,
-
-        // Refresh
-        KeyCode::Char('R') => Action::RefreshLibrary,
-
-        // Clear error
-        KeyCode::Char('x') => Action::ClearError,
+        let keymaps: &[keymap::Keymap] = match (app.fullscreen, lyrics) {
+            (true, true) => &[
+                keymap::FULLSCREEN,
+                keymap::LYRICS,
+                keymap::PANELS,
+                keymap::PLAYBACK,
+            ],
+            (true, false) => &[keymap::FULLSCREEN, keymap::PANELS, keymap::PLAYBACK],
+            (false, _) => &[keymap::LYRICS, keymap::PANELS, keymap::PLAYBACK],
+        };
+        return match keymap::lookup(keymaps, code, modifiers) {
+            // The full-screen view has no library to go back to
+            Some(Action::NavigateLeft) if app.fullscreen => Action::None,
+            action => action.unwrap_or(Action::None),
+        };
+    }
 
-        _ => Action::None,
+    // 0 is mute unless it continues a count
+    if code == KeyCode::Char('0') && app.count.get().is_some() {
+        return Action::CountDigit(0);
     }
+    keymap::lookup(keymap::MAIN, code, modifiers).unwrap_or(Action::None)
 }
 
 /// Handle key events in search mode.
-fn handle_search_key(code: KeyCode, _modifiers: KeyModifiers) -> Action {
+fn handle_search_key(code: KeyCode, modifiers: KeyModifiers) -> Action {
+    match keymap::lookup(&[keymap::SEARCH], code, modifiers) {
+        Some(action) => action,
+        None => match code {
+            KeyCode::Char(c) => Action::SearchInput(c),
+            _ => Action::None,
+        },
+    }
+}
+
+/// Handle key events while typing a quick filter.
+fn handle_filter_key(code: KeyCode) -> Action {
     match code {
-        KeyCode::Esc => Action::CloseSearch,
-        KeyCode::Enter => Action::Select, // Select result item (or submit search if no results)
-        KeyCode::Backspace => Action::SearchBackspace,
+        KeyCode::Esc => Action::ClearFilter,
+        KeyCode::Enter => Action::CloseFilter,
+        KeyCode::Backspace => Action::FilterBackspace,
         KeyCode::Up => Action::NavigateUp,
         KeyCode::Down => Action::NavigateDown,
-        KeyCode::Left => Action::NavigateLeft,
-        KeyCode::Right => Action::NavigateRight,
-        KeyCode::Tab => Action::NavigateRight,
-        KeyCode::BackTab => Action::NavigateLeft,
-        KeyCode::Char(c) => Action::SearchInput(c),
+        KeyCode::Char(c) => Action::FilterInput(c),
+        _ => Action::None,
+    }
+}
+
+/// Handle keys in the year picker popup.
+fn handle_year_picker_key(code: KeyCode, editing: bool) -> Action {
+    match code {
+        KeyCode::Esc => Action::CloseYearPicker,
+        KeyCode::Enter => Action::YearPickerSelect,
+        KeyCode::Backspace if editing => Action::YearPickerBackspace,
+        KeyCode::Char(c) if editing => Action::YearPickerInput(c),
+        KeyCode::Up | KeyCode::Char('k') => Action::YearPickerPrev,
+        KeyCode::Down | KeyCode::Char('j') => Action::YearPickerNext,
+        KeyCode::Char('q') | KeyCode::Char('y') => Action::CloseYearPicker,
+        _ => Action::None,
+    }
+}
+
+/// Handle keys in the Songs tab source and filter popup.
+fn handle_songs_filter_key(code: KeyCode) -> Action {
+    match code {
+        KeyCode::Esc => Action::CloseSongsFilter,
+        KeyCode::Enter => Action::SongsFilterApply,
+        KeyCode::Up | KeyCode::BackTab => Action::SongsFilterPrev,
+        KeyCode::Down | KeyCode::Tab => Action::SongsFilterNext,
+        KeyCode::Left | KeyCode::Right => Action::SongsFilterCycle,
+        KeyCode::Backspace => Action::SongsFilterBackspace,
+        KeyCode::Char(c) => Action::SongsFilterInput(c),
+        _ => Action::None,
+    }
+}
+
+/// Handle keys in the add to playlist popup.
+fn handle_playlist_picker_key(code: KeyCode, naming: bool) -> Action {
+    match code {
+        KeyCode::Esc => Action::PlaylistPickerBack,
+        KeyCode::Enter => Action::PlaylistPickerChoose,
+        KeyCode::Backspace if naming => Action::PlaylistPickerBackspace,
+        KeyCode::Char(c) if naming => Action::PlaylistPickerInput(c),
+        KeyCode::Up | KeyCode::Char('k') => Action::PlaylistPickerPrev,
+        KeyCode::Down | KeyCode::Char('j') => Action::PlaylistPickerNext,
+        KeyCode::Char('q') => Action::PlaylistPickerBack,
         _ => Action::None,
     }
 }
@@ -391,9 +602,11 @@ fn handle_mouse_event(mouse: crossterm::event::MouseEvent, click_state: &mut Cli
             let pos = (mouse.column, mouse.row);
             let now = Instant::now();
 
-            // Check for double-click (within 400ms and same position)
+            // Check for double-click (quick enough and at about the same position)
             let is_double_click = if let Some(last) = click_state.last_click {
-                now.duration_since(last) < Duration::from_millis(400) && click_state.last_pos == pos
+                now.duration_since(last) < DOUBLE_CLICK_TIME
+                    && click_state.last_pos.0.abs_diff(pos.0) <= DOUBLE_CLICK_DISTANCE
+                    && click_state.last_pos.1.abs_diff(pos.1) <= DOUBLE_CLICK_DISTANCE
             } else {
                 false
             };
@@ -410,6 +623,14 @@ fn handle_mouse_event(mouse: crossterm::event::MouseEvent, click_state: &mut Cli
                 Action::MouseClick(mouse.column, mouse.row)
             }
         }
+        MouseEventKind::Drag(crossterm::event::MouseButton::Left) => {
+            // A press that turns into a drag doesn't start a double-click
+            click_state.last_click = None;
+            Action::MouseDrag(mouse.column, mouse.row)
+        }
+        MouseEventKind::Up(crossterm::event::MouseButton::Left) => {
+            Action::MouseRelease(mouse.column, mouse.row)
+        }
         MouseEventKind::ScrollUp => Action::MouseScroll(-1, mouse.column, mouse.row),
         MouseEventKind::ScrollDown => Action::MouseScroll(1, mouse.column, mouse.row),
         _ => Action::None,
@@ -480,6 +701,12 @@ fn sync_mpris_state(app: &App, state: &mut MprisState, handle: &mpris::MprisHand
         let _ = handle.set_shuffle(now_playing.shuffle);
     }
 
+    // Check if playback speed changed
+    if now_playing.speed != state.speed {
+        state.speed = now_playing.speed;
+        let _ = handle.set_rate(now_playing.speed as f64);
+    }
+
     // Check if repeat changed
     if now_playing.repeat != state.repeat {
         state.repeat = now_playing.repeat;
@@ -492,3 +719,53 @@ fn sync_mpris_state(app: &App, state: &mut MprisState, handle: &mpris::MprisHand
     let _ = handle.set_can_go_next(can_go_next);
     let _ = handle.set_can_go_previous(can_go_previous);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keymap::Keymap;
+
+    /// Check that every key of some keymaps does what the help says it does.
+    fn assert_keys_bound(keymaps: &[Keymap], app: &App) {
+        for keymap in keymaps {
+            for binding in keymap.bindings {
+                for key in binding.keys {
+                    assert_eq!(
+                        handle_key_event(key.code, key.modifiers, app),
+                        binding.action,
+                        "{} is listed under {} as '{}'",
+                        key.label(),
+                        keymap.title,
+                        binding.description
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_keys_do_what_help_says() {
+        let (action_tx, _action_rx) = mpsc::unbounded_channel();
+        let mut app = App::new(Config::default(), action_tx);
+        assert_keys_bound(keymap::MAIN, &app);
+
+        app.lyrics.visible = true;
+        app.focus = Focus::Lyrics;
+        assert_keys_bound(&[keymap::LYRICS, keymap::PANELS, keymap::PLAYBACK], &app);
+
+        app.lyrics.visible = false;
+        app.fullscreen = true;
+        assert_keys_bound(
+            &[keymap::FULLSCREEN, keymap::PANELS, keymap::PLAYBACK],
+            &app,
+        );
+
+        app.fullscreen = false;
+        app.show_track_info = true;
+        assert_keys_bound(&[keymap::TRACK_INFO], &app);
+
+        app.show_track_info = false;
+        app.search.active = true;
+        assert_keys_bound(&[keymap::SEARCH], &app);
+    }
+}
@@ -12,6 +12,8 @@ use std::time::Duration;
 use mpris_server::{LoopStatus, Metadata, PlaybackStatus, Player, Time};
 use tokio::sync::mpsc;
 
+use crate::player::{MAX_SPEED, MIN_SPEED};
+
 /// MPRIS event sent from the MPRIS server to the app.
 #[derive(Debug, Clone)]
 #[allow(dead_code)] // Some variants reserved for future use
@@ -27,6 +29,7 @@ pub enum MprisEvent {
     SetVolume(f64),   // 0.0 to 1.0
     SetLoopStatus(LoopStatus),
     SetShuffle(bool),
+    SetRate(f64),
     Raise,
     Quit,
 }
@@ -48,6 +51,7 @@ pub enum MprisCommand {
     SetVolume(f64),
     SetLoopStatus(LoopStatus),
     SetShuffle(bool),
+    SetRate(f64),
     Seeked(Duration),
     SetCanGoNext(bool),
     SetCanGoPrevious(bool),
@@ -140,6 +144,11 @@ impl MprisHandle {
         self.send(MprisCommand::SetShuffle(shuffle))
     }
 
+    /// Update playback rate.
+    pub fn set_rate(&self, rate: f64) -> Result<(), String> {
+        self.send(MprisCommand::SetRate(rate))
+    }
+
     /// Emit seeked signal.
     pub fn seeked(&self, position: Duration) -> Result<(), String> {
         self.send(MprisCommand::Seeked(position))
@@ -199,6 +208,9 @@ fn run_mpris_thread(
             .can_control(true)
             .can_quit(true)
             .can_raise(false)
+            .rate(1.0)
+            .minimum_rate(MIN_SPEED as f64)
+            .maximum_rate(MAX_SPEED as f64)
             .build()
             .await
         {
@@ -265,6 +277,11 @@ fn run_mpris_thread(
             let _ = tx.send(MprisEvent::SetShuffle(shuffle));
         });
 
+        let tx = event_tx.clone();
+        player.connect_set_rate(move |_, rate| {
+            let _ = tx.send(MprisEvent::SetRate(rate));
+        });
+
         player.connect_raise(move |_| {
             // We don't support raise
         });
@@ -323,6 +340,11 @@ fn run_mpris_thread(
                                 tracing::warn!("Failed to set shuffle: {}", e);
                             }
                         }
+                        Some(MprisCommand::SetRate(rate)) => {
+                            if let Err(e) = player.set_rate(rate).await {
+                                tracing::warn!("Failed to set rate: {}", e);
+                            }
+                        }
                         Some(MprisCommand::Seeked(pos)) => {
                             if let Err(e) = player.seeked(Time::from_micros(pos.as_micros() as i64)).await {
                                 tracing::warn!("Failed to emit seeked signal: {}", e);
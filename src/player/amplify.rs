@@ -0,0 +1,106 @@
+//! Volume amplification above 100%, for quiet recordings.
+//!
+//! The sink's volume can't usefully go past full scale: samples beyond it
+//! are cut off hard and crackle. Amplification is applied to the samples
+//! instead, rounding off peaks that would clip.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::Source;
+
+/// Level above which amplified samples are rounded off rather than scaled.
+const KNEE: f32 = 0.8;
+
+/// Amplification shared between the player and the source playing.
+#[derive(Debug)]
+pub struct Gain(AtomicU32);
+
+impl Default for Gain {
+    fn default() -> Self {
+        Self(AtomicU32::new(1.0f32.to_bits()))
+    }
+}
+
+impl Gain {
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    /// Set the amplification (1.0 = none).
+    pub fn set(&self, gain: f32) {
+        self.0.store(gain.max(1.0).to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// A source amplified by a [`Gain`], soft-clipped so peaks don't crackle.
+pub struct Amplify<S> {
+    source: S,
+    gain: Arc<Gain>,
+}
+
+impl<S> Amplify<S> {
+    pub fn new(source: S, gain: Arc<Gain>) -> Self {
+        Self { source, gain }
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for Amplify<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.source.next()?;
+        let gain = self.gain.get();
+        if gain <= 1.0 {
+            return Some(sample);
+        }
+        let amplified = soft_clip(sample as f32 / 32768.0 * gain);
+        Some((amplified * 32767.0) as i16)
+    }
+}
+
+impl<S: Source<Item = i16>> Source for Amplify<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+/// Pass samples up to the knee through unchanged and bend louder ones
+/// smoothly towards full scale, which they never reach.
+fn soft_clip(sample: f32) -> f32 {
+    let level = sample.abs();
+    if level <= KNEE {
+        return sample;
+    }
+    let headroom = 1.0 - KNEE;
+    let level = KNEE + headroom * ((level - KNEE) / headroom).tanh();
+    level.copysign(sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_soft_clip() {
+        assert_eq!(soft_clip(0.5), 0.5);
+        assert_eq!(soft_clip(-KNEE), -KNEE);
+        let loud = soft_clip(1.5);
+        assert!(loud > KNEE && loud < 1.0);
+        assert_eq!(soft_clip(-1.5), -loud);
+        assert!(soft_clip(3.0) > loud);
+    }
+}
@@ -1,26 +1,56 @@
 //! Audio playback backend using rodio.
 
+use std::collections::HashSet;
 use std::io::{Cursor, Read, Seek, SeekFrom};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
 
 use color_eyre::Result;
-use rodio::{OutputStream, Sink, Source};
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::{
+    cpal, OutputStream, OutputStreamHandle, Sink, Source, StreamError, SupportedStreamConfig,
+};
 use symphonia::core::audio::{SampleBuffer, SignalSpec};
 use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
 use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
 use symphonia::core::io::{MediaSource, MediaSourceStream};
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
-use symphonia::core::units::Time;
+use symphonia::core::units::{Time, TimeBase};
 use symphonia::default::get_codecs;
 use symphonia::default::get_probe;
+use thiserror::Error;
 use tokio::sync::mpsc;
 
 use crate::action::PlayerState;
 use crate::client::models::Song;
 
+use super::amplify::{Amplify, Gain};
+use super::cache::{StreamCache, TrackKey};
+use super::fade::{Fade, Fader};
+use super::hls;
+use super::loudness::{self, Loudness, Normalize};
+use super::network::{self, NetworkOutput};
+use super::resample::Resampler;
+use super::silence::{Silence, SkipSilence};
+use super::upnp::Renderer;
+use super::visualizer::{SampleTap, Tap};
+use crate::client::TlsOptions;
+use crate::config::ResamplerQuality;
+
+/// Attempts at downloading a track before giving up on incomplete responses.
+const FETCH_ATTEMPTS: u32 = 5;
+
+/// Wait before resuming an interrupted download, growing with each attempt.
+const FETCH_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Bytes read from a stream download at a time.
+const FETCH_CHUNK: usize = 64 * 1024;
+
+/// How often the sound devices are listed, to notice ours going away.
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 /// A wrapper around a byte buffer that implements `MediaSource` with proper byte length.
 /// This is needed because rodio's `ReadSeekSource` returns `None` for `byte_len()`,
 /// which causes symphonia to treat some formats as unseekable.
@@ -61,6 +91,26 @@ impl MediaSource for SeekableSource {
     }
 }
 
+/// Why a track couldn't be decoded.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum DecodeError {
+    /// The file isn't in a container symphonia can read
+    #[error("can't read {container} file: {error}")]
+    Container { container: String, error: String },
+
+    /// The container holds audio in a codec there's no decoder for
+    #[error("no decoder for {codec} audio: {error}")]
+    Codec { codec: String, error: String },
+
+    /// Decoding broke off partway through
+    #[error("{codec} audio is damaged at {}:{:02}", .at.as_secs() / 60, .at.as_secs() % 60)]
+    Damaged { codec: String, at: Duration },
+}
+
+/// Where a source reports decoding giving up partway through, which would
+/// otherwise look like the track ending.
+type DecodeFailure = Arc<Mutex<Option<DecodeError>>>;
+
 /// A symphonia-based audio source that supports proper seeking.
 struct SymphoniaSource {
     format: Box<dyn FormatReader>,
@@ -70,30 +120,55 @@ struct SymphoniaSource {
     current_frame_offset: usize,
     spec: SignalSpec,
     total_duration: Option<Time>,
+    /// Short name of the codec, for errors
+    codec: &'static str,
+    time_base: Option<TimeBase>,
+    /// Timestamp of the last packet read
+    timestamp: u64,
+    failure: DecodeFailure,
 }
 
 impl SymphoniaSource {
-    fn new(data: Vec<u8>) -> Result<Self> {
+    /// Open a track. `suffix`, the file type the server reports, helps
+    /// probing and names the container when it can't be read.
+    fn new(
+        data: Vec<u8>,
+        suffix: Option<&str>,
+        failure: DecodeFailure,
+    ) -> Result<Self, DecodeError> {
         let source = SeekableSource::new(data);
         let mss = MediaSourceStream::new(Box::new(source), Default::default());
 
-        let hint = Hint::new();
+        let mut hint = Hint::new();
+        if let Some(suffix) = suffix {
+            hint.with_extension(suffix);
+        }
         let format_opts = FormatOptions {
             enable_gapless: true,
             ..Default::default()
         };
         let metadata_opts = MetadataOptions::default();
 
+        let container = || suffix.unwrap_or("unknown").to_string();
         let probed = get_probe()
             .format(&hint, mss, &format_opts, &metadata_opts)
-            .map_err(|e| color_eyre::eyre::eyre!("Failed to probe format: {}", e))?;
+            .map_err(|e| DecodeError::Container {
+                container: container(),
+                error: e.to_string(),
+            })?;
 
         let track = probed
             .format
             .tracks()
             .iter()
             .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
-            .ok_or_else(|| color_eyre::eyre::eyre!("No supported audio track found"))?;
+            .ok_or_else(|| DecodeError::Container {
+                container: container(),
+                error: String::from("no audio track found"),
+            })?;
+        let codec = get_codecs()
+            .get_codec(track.codec_params.codec)
+            .map_or("unknown", |descriptor| descriptor.short_name);
 
         let track_id = track.id;
         let total_duration = track
@@ -102,9 +177,13 @@ impl SymphoniaSource {
             .zip(track.codec_params.n_frames)
             .map(|(base, frames)| base.calc_time(frames));
 
+        let time_base = track.codec_params.time_base;
         let decoder = get_codecs()
             .make(&track.codec_params, &DecoderOptions::default())
-            .map_err(|e| color_eyre::eyre::eyre!("Failed to create decoder: {}", e))?;
+            .map_err(|e| DecodeError::Codec {
+                codec: codec.to_string(),
+                error: e.to_string(),
+            })?;
 
         let mut source = Self {
             format: probed.format,
@@ -117,6 +196,10 @@ impl SymphoniaSource {
             current_frame_offset: 0,
             spec: SignalSpec::new(44100, symphonia::core::audio::Channels::FRONT_LEFT),
             total_duration,
+            codec,
+            time_base,
+            timestamp: 0,
+            failure,
         };
 
         // Decode first frame to get proper spec
@@ -157,6 +240,7 @@ impl SymphoniaSource {
             if packet.track_id() != self.track_id {
                 continue;
             }
+            self.timestamp = packet.ts();
 
             match self.decoder.decode(&packet) {
                 Ok(decoded) => {
@@ -172,9 +256,18 @@ impl SymphoniaSource {
                     self.current_frame_offset = 0;
                     return true;
                 }
-                Err(_) => {
+                Err(e) => {
                     retries += 1;
                     if retries >= MAX_RETRIES {
+                        tracing::warn!("Giving up decoding {}: {}", self.codec, e);
+                        let at = self.time_base.map_or(Duration::ZERO, |base| {
+                            let time = base.calc_time(self.timestamp);
+                            Duration::from_secs_f64(time.seconds as f64 + time.frac)
+                        });
+                        *self.failure.lock().unwrap() = Some(DecodeError::Damaged {
+                            codec: self.codec.to_string(),
+                            at,
+                        });
                         return false;
                     }
                 }
@@ -222,17 +315,56 @@ impl Source for SymphoniaSource {
 }
 
 /// Messages sent to the player thread.
+///
+/// Commands that replace or reposition the playing stream carry a generation ID.
+/// Events tied to a stream are tagged with the generation that produced them, so
+/// the app can discard events that were already queued before the stream changed.
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum PlayerCommand {
-    Play(String, Song),
+    Play(String, Option<String>, Song, u64, Normalize),
     Pause,
     Resume,
-    Stop,
+    Stop(u64),
     SetVolume(f32),
-    Seek(Duration),
+    SetSpeed(f32),
+    Seek(Duration, u64),
+    /// Download tracks into the cache ahead of playing them, as
+    /// (song ID, stream URL)
+    Precache(Vec<(String, String)>),
+    /// Play into the network address if given, otherwise the sound device
+    SetSpeakers(Option<String>),
+}
+
+/// How the sound device is opened, and tracks resampled for it.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceOptions {
+    /// Reopen the device at each track's own sample rate, so nothing is
+    /// resampled, where the device allows it
+    pub match_track: bool,
+    /// Rate to open the device at, instead of its own
+    pub sample_rate: Option<u32>,
+    /// How tracks are resampled to the device's rate
+    pub resampler: ResamplerQuality,
+}
+
+/// Where the player's sound comes out.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Output {
+    /// This computer's speakers
+    Local,
+    /// Raw PCM sent to a `tcp://` or `udp://` address (see [`NetworkOutput`])
+    Network(String),
+    /// A UPnP/DLNA renderer, playing the stream itself
+    Renderer(Renderer),
 }
 
+/// Slowest supported playback speed.
+pub const MIN_SPEED: f32 = 0.5;
+
+/// Fastest supported playback speed.
+pub const MAX_SPEED: f32 = 2.0;
+
 /// Messages sent from the player thread.
 #[derive(Debug, Clone)]
 pub enum PlayerEvent {
@@ -240,29 +372,99 @@ pub enum PlayerEvent {
     Progress {
         position: Duration,
         duration: Duration,
+        generation: u64,
+    },
+    TrackEnded {
+        generation: u64,
+    },
+    /// Share of the track downloaded so far (0.0 to 1.0)
+    BufferProgress {
+        buffered: f32,
+        generation: u64,
+    },
+    /// Loudness estimated for a track played with `Normalize::Analyze`
+    LoudnessMeasured {
+        song_id: String,
+        loudness: Loudness,
+    },
+    /// A track is in the stream cache, so it starts without downloading
+    Cached {
+        song_id: String,
+    },
+    /// The sound device went away (headphones unplugged, speaker turned
+    /// off); playback is paused
+    DeviceLost {
+        name: String,
+    },
+    /// Sound now goes to another device, after the one before went away.
+    /// Playback stays paused.
+    DeviceChanged {
+        name: String,
+    },
+    /// A track couldn't be decoded, from the start or partway through
+    DecodeFailed {
+        song_id: String,
+        error: DecodeError,
+        generation: u64,
     },
-    TrackEnded,
     Error(String),
 }
 
 /// Audio player that runs in a separate thread.
 pub struct Player {
     command_tx: mpsc::UnboundedSender<PlayerCommand>,
+    event_tx: mpsc::UnboundedSender<PlayerEvent>,
     event_rx: mpsc::UnboundedReceiver<PlayerEvent>,
+    /// Commands for the renderer cast to, instead of the local player
+    renderer_tx: Option<mpsc::UnboundedSender<PlayerCommand>>,
+    /// Network address the local player sends to, `None` for the sound device
+    network: Option<String>,
     state: Arc<PlayerStateShared>,
+    /// Generation of the most recent play/stop/seek command
+    generation: AtomicU64,
 }
 
+/// Attempts at seeking on a renderer, which refuse until the track starts.
+const RENDERER_SEEK_ATTEMPTS: u32 = 10;
+
+/// How often a renderer is asked how far it got.
+const RENDERER_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Shared player state accessible from multiple threads.
 struct PlayerStateShared {
     is_playing: AtomicBool,
     position_ms: AtomicU64,
     duration_ms: AtomicU64,
     volume: AtomicU64,
+    /// Most recent samples played, for the visualizer
+    samples: Arc<SampleTap>,
+    /// Amplification of volumes above 100%
+    amplification: Arc<Gain>,
+    /// Fades in and out of the playing stream
+    fader: Arc<Fader>,
+    /// Set when the playing stream stops decoding partway through
+    decode_failure: DecodeFailure,
+    /// Converts tracks to the output's sample rate
+    resampler: Resampler,
+    /// Long silences to skip, if any
+    silence: Option<Arc<Silence>>,
 }
 
 impl Player {
-    /// Create a new audio player.
-    pub fn new() -> Result<Self> {
+    /// Create a new audio player, optionally caching downloaded tracks on disk.
+    /// Streams are fetched with the same certificates as the API client, and
+    /// faded in and out over `fade`. Sound goes to the network address in
+    /// `network` (see [`NetworkOutput`]) if given, otherwise to the default
+    /// sound device, opened as `device` says. Silences are skipped as
+    /// `silence` says, if given.
+    pub fn new(
+        cache: Option<StreamCache>,
+        tls: TlsOptions,
+        fade: Duration,
+        network: Option<String>,
+        device: DeviceOptions,
+        silence: Option<Silence>,
+    ) -> Result<Self> {
         let (command_tx, command_rx) = mpsc::unbounded_channel();
         let (event_tx, event_rx) = mpsc::unbounded_channel();
 
@@ -271,60 +473,162 @@ impl Player {
             position_ms: AtomicU64::new(0),
             duration_ms: AtomicU64::new(0),
             volume: AtomicU64::new(80),
+            samples: Arc::default(),
+            amplification: Arc::default(),
+            fader: Arc::new(Fader::new(fade)),
+            decode_failure: Arc::default(),
+            resampler: Resampler::new(device.resampler),
+            silence: silence.map(Arc::new),
         });
 
         let state_clone = Arc::clone(&state);
+        let thread_event_tx = event_tx.clone();
+        let thread_network = network.clone();
 
         // Spawn the player thread
         std::thread::spawn(move || {
-            if let Err(e) = run_player_thread(command_rx, event_tx, state_clone) {
+            if let Err(e) = run_player_thread(
+                command_rx,
+                thread_event_tx,
+                state_clone,
+                cache,
+                tls,
+                thread_network,
+                device,
+            ) {
                 tracing::error!("Player thread error: {}", e);
             }
         });
 
         Ok(Self {
             command_tx,
+            event_tx,
             event_rx,
+            renderer_tx: None,
+            network,
             state,
+            generation: AtomicU64::new(0),
         })
     }
 
-    /// Play a song from a URL.
-    pub fn play(&self, url: String, song: Song) -> Result<()> {
-        self.command_tx.send(PlayerCommand::Play(url, song))?;
+    /// Download upcoming tracks into the stream cache in the background,
+    /// as (song ID, stream URL). Does nothing without a cache.
+    pub fn precache(&self, songs: Vec<(String, String)>) -> Result<()> {
+        // Even while casting, the tracks are for playing here later
+        self.command_tx.send(PlayerCommand::Precache(songs))?;
+        Ok(())
+    }
+
+    /// Send a command to the output playing.
+    fn send(&self, command: PlayerCommand) -> Result<()> {
+        self.renderer_tx
+            .as_ref()
+            .unwrap_or(&self.command_tx)
+            .send(command)?;
+        Ok(())
+    }
+
+    /// Switch where the sound comes out, stopping what plays on the old
+    /// output. Leaving a renderer stops it.
+    pub fn set_output(&mut self, output: Output) -> Result<()> {
+        self.stop()?;
+        self.renderer_tx = match output {
+            Output::Local => {
+                self.set_speakers(None)?;
+                None
+            }
+            Output::Network(address) => {
+                self.set_speakers(Some(address))?;
+                None
+            }
+            Output::Renderer(renderer) => {
+                let (command_tx, command_rx) = mpsc::unbounded_channel();
+                let event_tx = self.event_tx.clone();
+                let state = Arc::clone(&self.state);
+                std::thread::spawn(move || {
+                    run_renderer_thread(renderer, command_rx, event_tx, state);
+                });
+                Some(command_tx)
+            }
+        };
+        Ok(())
+    }
+
+    /// Have the local player play into the network address if given,
+    /// otherwise the sound device, unless it already does.
+    fn set_speakers(&mut self, network: Option<String>) -> Result<()> {
+        if network != self.network {
+            self.command_tx
+                .send(PlayerCommand::SetSpeakers(network.clone()))?;
+            self.network = network;
+        }
+        Ok(())
+    }
+
+    /// Start a new command generation, invalidating events from earlier ones.
+    fn next_generation(&self) -> u64 {
+        self.generation.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Get the generation of the most recent play/stop/seek command.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Play a song from a URL, or over HLS from `hls_url` if given and the
+    /// server offers it.
+    pub fn play(
+        &self,
+        url: String,
+        hls_url: Option<String>,
+        song: Song,
+        normalize: Normalize,
+    ) -> Result<()> {
+        let generation = self.next_generation();
+        self.send(PlayerCommand::Play(
+            url, hls_url, song, generation, normalize,
+        ))?;
         Ok(())
     }
 
     /// Pause playback.
     pub fn pause(&self) -> Result<()> {
-        self.command_tx.send(PlayerCommand::Pause)?;
+        self.send(PlayerCommand::Pause)?;
         Ok(())
     }
 
     /// Resume playback.
     pub fn resume(&self) -> Result<()> {
-        self.command_tx.send(PlayerCommand::Resume)?;
+        self.send(PlayerCommand::Resume)?;
         Ok(())
     }
 
     /// Stop playback.
     pub fn stop(&self) -> Result<()> {
-        self.command_tx.send(PlayerCommand::Stop)?;
+        let generation = self.next_generation();
+        self.send(PlayerCommand::Stop(generation))?;
         Ok(())
     }
 
-    /// Set volume (0.0 to 1.0).
+    /// Set volume (1.0 = 100%, above that amplified).
     pub fn set_volume(&self, volume: f32) -> Result<()> {
-        self.command_tx.send(PlayerCommand::SetVolume(volume))?;
+        self.send(PlayerCommand::SetVolume(volume))?;
         self.state
             .volume
             .store((volume * 100.0) as u64, Ordering::SeqCst);
         Ok(())
     }
 
+    /// Set playback speed (1.0 = normal), clamped to the supported range.
+    pub fn set_speed(&self, speed: f32) -> Result<()> {
+        self.send(PlayerCommand::SetSpeed(speed.clamp(MIN_SPEED, MAX_SPEED)))?;
+        Ok(())
+    }
+
     /// Seek to a position.
     pub fn seek(&self, position: Duration) -> Result<()> {
-        self.command_tx.send(PlayerCommand::Seek(position))?;
+        let generation = self.next_generation();
+        self.send(PlayerCommand::Seek(position, generation))?;
         Ok(())
     }
 
@@ -356,6 +660,141 @@ impl Player {
     pub fn duration_ms(&self) -> u64 {
         self.state.duration_ms.load(Ordering::SeqCst)
     }
+
+    /// Get `bars` spectrum levels (0.0 to 1.0) of what was played `delay` ago.
+    pub fn spectrum(&self, bars: usize, delay: Duration) -> Vec<f32> {
+        self.state.samples.spectrum(bars, delay)
+    }
+}
+
+/// What the local player's sinks play into.
+enum Speakers {
+    /// The default sound device
+    Device {
+        // Sound stops when the stream is dropped
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+        /// Name of the device, empty if it couldn't be told
+        name: String,
+        /// Sample rate the device was asked to open at, if not its default
+        requested: Option<u32>,
+        /// Sample rate it was opened at, if known
+        rate: Option<u32>,
+    },
+    /// Raw PCM sent over the network
+    Network(NetworkOutput),
+}
+
+impl Speakers {
+    /// Send sound to the network address if given, otherwise open the
+    /// sound device as [`Speakers::open_device`] does.
+    fn open(network: Option<&str>, requested: Option<u32>) -> Result<Self> {
+        match network {
+            Some(address) => Ok(Self::Network(NetworkOutput::connect(address)?)),
+            None => Self::open_device(requested),
+        }
+    }
+
+    /// Open the default sound device, or any other that works. With a
+    /// sample rate, the device is opened at it if it can be, and shared at
+    /// its default format otherwise.
+    fn open_device(requested: Option<u32>) -> Result<Self> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or(StreamError::NoDevice)?;
+        let mut name = device.name().unwrap_or_default();
+
+        let matched = requested.and_then(|rate| {
+            let Some(config) = matching_config(&device, rate) else {
+                tracing::info!("{} can't play {} Hz, sharing it", name, rate);
+                return None;
+            };
+            match OutputStream::try_from_device_config(&device, config) {
+                Ok(opened) => {
+                    tracing::info!("Opened {} at {} Hz", name, rate);
+                    Some((opened, Some(rate)))
+                }
+                Err(e) => {
+                    tracing::warn!("Can't open {} at {} Hz ({}), sharing it", name, rate, e);
+                    None
+                }
+            }
+        });
+        let ((stream, handle), rate) = match matched {
+            Some(opened) => opened,
+            None => match OutputStream::try_from_device(&device) {
+                Ok(opened) => {
+                    let config = device.default_output_config().ok();
+                    (opened, config.map(|config| config.sample_rate().0))
+                }
+                Err(e) => {
+                    tracing::warn!("Can't open {} ({}), trying the other devices", name, e);
+                    let (opened, other, rate) = open_any_device()?;
+                    name = other;
+                    (opened, rate)
+                }
+            },
+        };
+        Ok(Self::Device {
+            _stream: stream,
+            handle,
+            name,
+            requested,
+            rate,
+        })
+    }
+
+    /// Sample rate sound comes out at, if known.
+    fn rate(&self) -> Option<u32> {
+        match self {
+            Self::Device { rate, .. } => *rate,
+            Self::Network(_) => Some(network::SAMPLE_RATE),
+        }
+    }
+
+    fn new_sink(&self) -> Result<Sink> {
+        Ok(match self {
+            Self::Device { handle, .. } => Sink::try_new(handle)?,
+            Self::Network(output) => output.new_sink(),
+        })
+    }
+}
+
+/// Open the first sound device that works, with its name and the sample
+/// rate it plays at, if known.
+fn open_any_device() -> Result<((OutputStream, OutputStreamHandle), String, Option<u32>)> {
+    let devices = cpal::default_host()
+        .output_devices()
+        .map_err(|_| StreamError::NoDevice)?;
+    for device in devices {
+        if let Ok(opened) = OutputStream::try_from_device(&device) {
+            let rate = device
+                .default_output_config()
+                .ok()
+                .map(|config| config.sample_rate().0);
+            return Ok((opened, device.name().unwrap_or_default(), rate));
+        }
+    }
+    Err(StreamError::NoDevice.into())
+}
+
+/// Find a config the device can play a sample rate at, in its own sample
+/// format if it can. Samples are decoded to 16 bits, so the bit depth is
+/// left to the device.
+fn matching_config(device: &cpal::Device, rate: u32) -> Option<SupportedStreamConfig> {
+    let own_format = device.default_output_config().ok()?.sample_format();
+    device
+        .supported_output_configs()
+        .ok()?
+        .filter(|config| (config.min_sample_rate().0..=config.max_sample_rate().0).contains(&rate))
+        // Stereo if offered
+        .min_by_key(|config| {
+            (
+                config.sample_format() != own_format,
+                config.channels().abs_diff(2),
+            )
+        })
+        .map(|config| config.with_sample_rate(cpal::SampleRate(rate)))
 }
 
 /// Run the player thread.
@@ -363,31 +802,72 @@ fn run_player_thread(
     mut command_rx: mpsc::UnboundedReceiver<PlayerCommand>,
     event_tx: mpsc::UnboundedSender<PlayerEvent>,
     state: Arc<PlayerStateShared>,
+    cache: Option<StreamCache>,
+    tls: TlsOptions,
+    network: Option<String>,
+    options: DeviceOptions,
 ) -> Result<()> {
+    let http = tls
+        .apply_blocking(reqwest::blocking::Client::builder().timeout(Duration::from_secs(60)))
+        .build()?;
+
     // Initialize audio output
-    let (_stream, stream_handle) = OutputStream::try_default()?;
-    let sink = Arc::new(Mutex::new(Sink::try_new(&stream_handle)?));
+    let mut speakers = Speakers::open(network.as_deref(), options.sample_rate)?;
+    state.resampler.set_rate(speakers.rate());
+    // Devices listed in the background, and whether ours went away
+    let mut device_watch = matches!(speakers, Speakers::Device { .. }).then(watch_devices);
+    let mut device_lost = false;
+    // Tracks being downloaded ahead of time
+    let precaching: Arc<Precaching> = Arc::default();
+    let sink = Arc::new(Mutex::new(speakers.new_sink()?));
 
     let mut current_duration: Option<Duration> = None;
-    let mut current_audio_data: Option<Vec<u8>> = None;
+    let mut current_track: Option<LoadedTrack> = None;
     let mut current_volume: f32 = 0.8;
+    let mut current_speed: f32 = 1.0;
+    // Loudness normalization factor for the current track
+    let mut current_gain: f32 = 1.0;
     // Flag to prevent false TrackEnded events during seek operations
     let mut is_seeking: bool = false;
     // Track the last known play time for accurate position tracking
     let mut last_tick_time: Option<std::time::Instant> = None;
+    // Generation of the last play/stop/seek command, used to tag stream events
+    let mut generation: u64 = 0;
 
     loop {
         // Check for commands (non-blocking)
         match command_rx.try_recv() {
             Ok(cmd) => match cmd {
-                PlayerCommand::Play(url, song) => {
+                PlayerCommand::Play(url, hls_url, song, command_generation, normalize) => {
+                    generation = command_generation;
+                    current_gain = match normalize {
+                        Normalize::Gain(gain) => gain,
+                        Normalize::Off | Normalize::Analyze => 1.0,
+                    };
+
                     // Stop current playback
+                    fade_out(&state, &sink);
                     {
                         let s = sink.lock().unwrap();
                         s.stop();
                     }
+                    // Following the tracks, reopen the device at this one's rate
+                    if let Speakers::Device { requested, .. } = &speakers {
+                        let rate = song.sampling_rate.map(|rate| rate as u32);
+                        if options.match_track && rate.is_some() && rate != *requested {
+                            match Speakers::open_device(rate) {
+                                Ok(device) => {
+                                    speakers = device;
+                                    state.resampler.set_rate(speakers.rate());
+                                }
+                                Err(e) => tracing::warn!("Failed to reopen sound device: {}", e),
+                            }
+                        }
+                    }
+
                     // Create new sink after stop
-                    *sink.lock().unwrap() = Sink::try_new(&stream_handle)?;
+                    *sink.lock().unwrap() = speakers.new_sink()?;
+                    state.fader.fade_in();
 
                     // Get duration from song metadata
                     current_duration = song.duration.map(|d| Duration::from_secs(d as u64));
@@ -398,46 +878,105 @@ fn run_player_thread(
                             .store(dur.as_millis() as u64, Ordering::SeqCst);
                     }
 
-                    // Fetch and decode the audio stream
-                    match fetch_audio_data(&url) {
-                        Ok(audio_data) => {
-                            current_audio_data = Some(audio_data.clone());
-                            if let Err(e) =
-                                play_audio_data(&audio_data, &sink, current_volume, Duration::ZERO)
+                    // Fetch and decode the audio stream, taking over a
+                    // precache of it rather than downloading it twice
+                    let _ = event_tx.send(PlayerEvent::StateChanged(PlayerState::Buffering));
+                    precaching.claim(&song.id);
+                    let progress = |buffered| {
+                        let _ = event_tx.send(PlayerEvent::BufferProgress {
+                            buffered,
+                            generation,
+                        });
+                    };
+                    let cache_key = TrackKey::for_url(&song.id, &url);
+                    match load_audio_data(
+                        &http,
+                        &url,
+                        hls_url.as_deref(),
+                        &cache_key,
+                        cache.as_ref(),
+                        progress,
+                    ) {
+                        Ok(data) => {
+                            progress(1.0);
+                            if cache
+                                .as_ref()
+                                .is_some_and(|cache| cache.path(&cache_key).is_some())
                             {
-                                let _ = event_tx.send(PlayerEvent::Error(e.to_string()));
-                            } else {
-                                state.is_playing.store(true, Ordering::SeqCst);
-                                state.position_ms.store(0, Ordering::SeqCst);
-                                last_tick_time = Some(std::time::Instant::now());
-                                let _ =
-                                    event_tx.send(PlayerEvent::StateChanged(PlayerState::Playing));
+                                let _ = event_tx.send(PlayerEvent::Cached {
+                                    song_id: song.id.clone(),
+                                });
+                            }
+                            let track = LoadedTrack {
+                                song_id: song.id.clone(),
+                                cache_key,
+                                suffix: song.suffix.clone(),
+                                data,
+                            };
+                            if normalize == Normalize::Analyze {
+                                spawn_loudness_analysis(track.clone(), event_tx.clone());
+                            }
+                            let played = play_audio_data(
+                                &track,
+                                &sink,
+                                current_volume,
+                                current_gain,
+                                current_speed,
+                                Duration::ZERO,
+                                &state,
+                            );
+                            match played {
+                                Ok(()) => {
+                                    current_track = Some(track);
+                                    state.is_playing.store(true, Ordering::SeqCst);
+                                    state.position_ms.store(0, Ordering::SeqCst);
+                                    last_tick_time = Some(std::time::Instant::now());
+                                    let _ = event_tx
+                                        .send(PlayerEvent::StateChanged(PlayerState::Playing));
+                                }
+                                Err(e) => {
+                                    current_track = None;
+                                    let event = match e.downcast::<DecodeError>() {
+                                        Ok(error) => {
+                                            decode_failed(&track, error, generation, cache.as_ref())
+                                        }
+                                        Err(e) => PlayerEvent::Error(e.to_string()),
+                                    };
+                                    let _ = event_tx.send(event);
+                                    let _ = event_tx
+                                        .send(PlayerEvent::StateChanged(PlayerState::Stopped));
+                                }
                             }
                         }
                         Err(e) => {
                             let _ = event_tx.send(PlayerEvent::Error(e.to_string()));
+                            let _ = event_tx.send(PlayerEvent::StateChanged(PlayerState::Stopped));
                         }
                     }
                 }
                 PlayerCommand::Pause => {
+                    fade_out(&state, &sink);
                     sink.lock().unwrap().pause();
                     state.is_playing.store(false, Ordering::SeqCst);
                     last_tick_time = None; // Stop tracking time while paused
                     let _ = event_tx.send(PlayerEvent::StateChanged(PlayerState::Paused));
                 }
                 PlayerCommand::Resume => {
+                    state.fader.fade_in();
                     sink.lock().unwrap().play();
                     state.is_playing.store(true, Ordering::SeqCst);
                     last_tick_time = Some(std::time::Instant::now()); // Resume tracking
                     let _ = event_tx.send(PlayerEvent::StateChanged(PlayerState::Playing));
                 }
-                PlayerCommand::Stop => {
+                PlayerCommand::Stop(command_generation) => {
+                    generation = command_generation;
+                    fade_out(&state, &sink);
                     {
                         let s = sink.lock().unwrap();
                         s.stop();
                     }
-                    *sink.lock().unwrap() = Sink::try_new(&stream_handle)?;
-                    current_audio_data = None;
+                    *sink.lock().unwrap() = speakers.new_sink()?;
+                    current_track = None;
                     state.is_playing.store(false, Ordering::SeqCst);
                     state.position_ms.store(0, Ordering::SeqCst);
                     last_tick_time = None;
@@ -445,27 +984,87 @@ fn run_player_thread(
                 }
                 PlayerCommand::SetVolume(vol) => {
                     current_volume = vol;
-                    sink.lock().unwrap().set_volume(linear_to_log_volume(vol));
+                    state.amplification.set(vol);
+                    sink.lock()
+                        .unwrap()
+                        .set_volume(linear_to_log_volume(vol) * current_gain);
+                }
+                PlayerCommand::SetSpeed(speed) => {
+                    current_speed = speed;
+                    sink.lock().unwrap().set_speed(speed);
+                }
+                PlayerCommand::Precache(songs) => {
+                    if let Some(cache) = &cache {
+                        let mut wanted = Vec::new();
+                        for (song_id, url) in songs {
+                            if cache.path(&TrackKey::for_url(&song_id, &url)).is_some() {
+                                let _ = event_tx.send(PlayerEvent::Cached { song_id });
+                            } else if precaching.want(&song_id) {
+                                wanted.push((song_id, url));
+                            }
+                        }
+                        if !wanted.is_empty() {
+                            spawn_precache(
+                                wanted,
+                                http.clone(),
+                                cache.clone(),
+                                Arc::clone(&precaching),
+                                event_tx.clone(),
+                            );
+                        }
+                    }
+                }
+                PlayerCommand::SetSpeakers(network) => {
+                    match Speakers::open(network.as_deref(), options.sample_rate)
+                        .and_then(|opened| Ok((opened.new_sink()?, opened)))
+                    {
+                        Ok((new_sink, opened)) => {
+                            speakers = opened;
+                            state.resampler.set_rate(speakers.rate());
+                            *sink.lock().unwrap() = new_sink;
+                            device_watch =
+                                matches!(speakers, Speakers::Device { .. }).then(watch_devices);
+                            device_lost = false;
+                        }
+                        Err(e) => {
+                            tracing::error!("Failed to switch output: {}", e);
+                            let _ = event_tx.send(PlayerEvent::Error(format!(
+                                "Failed to switch output: {}",
+                                e
+                            )));
+                        }
+                    }
                 }
-                PlayerCommand::Seek(position) => {
+                PlayerCommand::Seek(position, command_generation) => {
+                    generation = command_generation;
+
                     // Since our SymphoniaSource supports seeking, we recreate it with
                     // the new position. This is fast because symphonia seeks directly
                     // to the position in the compressed stream.
-                    if let Some(ref audio_data) = current_audio_data {
+                    if let Some(ref track) = current_track {
                         // Remember if we were playing before seek
                         let was_playing = state.is_playing.load(Ordering::SeqCst);
 
                         // Set seeking flag to prevent false TrackEnded events
                         is_seeking = true;
 
+                        fade_out(&state, &sink);
                         {
                             let s = sink.lock().unwrap();
                             s.stop();
                         }
-                        *sink.lock().unwrap() = Sink::try_new(&stream_handle)?;
-
-                        if let Err(e) = play_audio_data(audio_data, &sink, current_volume, position)
-                        {
+                        *sink.lock().unwrap() = speakers.new_sink()?;
+                        state.fader.fade_in();
+
+                        if let Err(e) = play_audio_data(
+                            track,
+                            &sink,
+                            current_volume,
+                            current_gain,
+                            current_speed,
+                            position,
+                            &state,
+                        ) {
                             let _ =
                                 event_tx.send(PlayerEvent::Error(format!("Seek failed: {}", e)));
                         } else {
@@ -497,10 +1096,18 @@ fn run_player_thread(
             }
         }
 
-        // Check if track ended (but not during seek operations)
+        // Check if track ended (but not during seek operations), or only
+        // stopped decoding
         if !is_seeking && sink.lock().unwrap().empty() && state.is_playing.load(Ordering::SeqCst) {
             state.is_playing.store(false, Ordering::SeqCst);
-            let _ = event_tx.send(PlayerEvent::TrackEnded);
+            let failure = state.decode_failure.lock().unwrap().take();
+            let event = match (failure, &current_track) {
+                (Some(error), Some(track)) => {
+                    decode_failed(track, error, generation, cache.as_ref())
+                }
+                _ => PlayerEvent::TrackEnded { generation },
+            };
+            let _ = event_tx.send(event);
         }
 
         // Reset seeking flag after track-end check
@@ -509,7 +1116,14 @@ fn run_player_thread(
         // Update progress based on actual elapsed time
         if state.is_playing.load(Ordering::SeqCst) {
             if let Some(last_time) = last_tick_time {
-                let elapsed_ms = last_time.elapsed().as_millis() as u64;
+                // Position is in track time, so it advances faster or slower with speed
+                let elapsed_ms = (last_time.elapsed().as_millis() as f32 * current_speed) as u64;
+                // Plus any silence skipped over
+                let skipped_ms = state
+                    .silence
+                    .as_ref()
+                    .map_or(0, |silence| silence.take_skipped().as_millis() as u64);
+                let elapsed_ms = elapsed_ms + skipped_ms;
                 let current = state.position_ms.load(Ordering::SeqCst);
                 let duration_ms = state.duration_ms.load(Ordering::SeqCst);
 
@@ -522,6 +1136,7 @@ fn run_player_thread(
                     let _ = event_tx.send(PlayerEvent::Progress {
                         position: Duration::from_millis(new_position),
                         duration: dur,
+                        generation,
                     });
                 }
             } else {
@@ -530,6 +1145,62 @@ fn run_player_thread(
             }
         }
 
+        // Follow the sound device going away and another taking over
+        let devices = device_watch
+            .as_ref()
+            .and_then(|watch| watch.try_iter().last());
+        if let Some(devices) = devices {
+            if let Speakers::Device { name, .. } = &speakers {
+                if !device_lost && !name.is_empty() && !devices.has(name) {
+                    tracing::warn!("Sound device {} went away", name);
+                    device_lost = true;
+                    sink.lock().unwrap().pause();
+                    if state.is_playing.swap(false, Ordering::SeqCst) {
+                        last_tick_time = None;
+                        let _ = event_tx.send(PlayerEvent::StateChanged(PlayerState::Paused));
+                    }
+                    let _ = event_tx.send(PlayerEvent::DeviceLost { name: name.clone() });
+                }
+
+                if device_lost && devices.has_default {
+                    match Speakers::open_device(options.sample_rate)
+                        .and_then(|device| Ok((device.new_sink()?, device)))
+                    {
+                        Ok((new_sink, device)) => {
+                            speakers = device;
+                            state.resampler.set_rate(speakers.rate());
+                            device_lost = false;
+                            *sink.lock().unwrap() = new_sink;
+                            // Pick the track up where it stopped, paused
+                            if let Some(track) = &current_track {
+                                let position =
+                                    Duration::from_millis(state.position_ms.load(Ordering::SeqCst));
+                                if let Err(e) = play_audio_data(
+                                    track,
+                                    &sink,
+                                    current_volume,
+                                    current_gain,
+                                    current_speed,
+                                    position,
+                                    &state,
+                                ) {
+                                    tracing::warn!("Failed to reload track: {}", e);
+                                }
+                                sink.lock().unwrap().pause();
+                            }
+                            if let Speakers::Device { name, .. } = &speakers {
+                                tracing::info!("Sound now goes to {}", name);
+                                let _ = event_tx
+                                    .send(PlayerEvent::DeviceChanged { name: name.clone() });
+                            }
+                        }
+                        // Still lost, so tried again at the next check
+                        Err(e) => tracing::warn!("Failed to open sound device: {}", e),
+                    }
+                }
+            }
+        }
+
         // Sleep to avoid busy waiting
         std::thread::sleep(Duration::from_millis(100));
     }
@@ -537,17 +1208,301 @@ fn run_player_thread(
     Ok(())
 }
 
-/// Fetch audio data from URL with timeout.
-fn fetch_audio_data(url: &str) -> Result<Vec<u8>> {
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(60))
-        .build()?;
-    let response = client.get(url).send()?;
-    let bytes = response.bytes()?;
-    Ok(bytes.to_vec())
+/// The sound devices connected at one look.
+struct Devices {
+    /// Names of the output devices, `None` if they couldn't be listed
+    names: Option<Vec<String>>,
+    /// Whether there is a default output device
+    has_default: bool,
+}
+
+impl Devices {
+    /// Check whether a device is connected. Counts as connected if the
+    /// devices couldn't be listed.
+    fn has(&self, name: &str) -> bool {
+        self.names
+            .as_ref()
+            .is_none_or(|names| names.iter().any(|n| n == name))
+    }
+}
+
+/// List the sound devices every [`DEVICE_POLL_INTERVAL`] on a thread of
+/// their own, as listing opens each device on some hosts (ALSA, PulseAudio)
+/// and can hold up the player. Stops once the receiver is dropped.
+fn watch_devices() -> std::sync::mpsc::Receiver<Devices> {
+    let (devices_tx, devices_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(DEVICE_POLL_INTERVAL);
+        let host = cpal::default_host();
+        let names = host
+            .output_devices()
+            .ok()
+            .map(|devices| devices.filter_map(|device| device.name().ok()).collect());
+        let devices = Devices {
+            names,
+            has_default: host.default_output_device().is_some(),
+        };
+        if devices_tx.send(devices).is_err() {
+            break;
+        }
+    });
+    devices_rx
+}
+
+/// Run the thread controlling a renderer while casting to it. The renderer
+/// streams the track itself; its progress is polled, and it stopping on its
+/// own means the track ended.
+fn run_renderer_thread(
+    renderer: Renderer,
+    mut command_rx: mpsc::UnboundedReceiver<PlayerCommand>,
+    event_tx: mpsc::UnboundedSender<PlayerEvent>,
+    state: Arc<PlayerStateShared>,
+) {
+    let http = match reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+    {
+        Ok(http) => http,
+        Err(e) => {
+            let _ = event_tx.send(PlayerEvent::Error(e.to_string()));
+            return;
+        }
+    };
+    let mut generation: u64 = 0;
+    let mut current_duration: Option<Duration> = None;
+    // Whether the renderer was seen playing the current track
+    let mut started = false;
+    let mut last_poll = Instant::now();
+
+    loop {
+        let command = match command_rx.try_recv() {
+            Ok(command) => Some(command),
+            Err(mpsc::error::TryRecvError::Empty) => None,
+            Err(mpsc::error::TryRecvError::Disconnected) => {
+                // Switched to another output
+                let _ = renderer.stop(&http);
+                break;
+            }
+        };
+        let result = match command {
+            Some(PlayerCommand::Play(url, _, song, command_generation, _)) => {
+                generation = command_generation;
+                started = false;
+                current_duration = song.duration.map(|d| Duration::from_secs(d as u64));
+                state.duration_ms.store(
+                    current_duration.unwrap_or_default().as_millis() as u64,
+                    Ordering::SeqCst,
+                );
+                state.position_ms.store(0, Ordering::SeqCst);
+                let _ = event_tx.send(PlayerEvent::StateChanged(PlayerState::Buffering));
+                let result = renderer
+                    .set_uri(&http, &url, &song)
+                    .and_then(|()| renderer.play(&http));
+                let playing = result.is_ok();
+                state.is_playing.store(playing, Ordering::SeqCst);
+                let _ = event_tx.send(PlayerEvent::StateChanged(if playing {
+                    PlayerState::Playing
+                } else {
+                    PlayerState::Stopped
+                }));
+                result
+            }
+            Some(PlayerCommand::Pause) => renderer.pause(&http).map(|()| {
+                state.is_playing.store(false, Ordering::SeqCst);
+                let _ = event_tx.send(PlayerEvent::StateChanged(PlayerState::Paused));
+            }),
+            Some(PlayerCommand::Resume) => renderer.play(&http).map(|()| {
+                state.is_playing.store(true, Ordering::SeqCst);
+                let _ = event_tx.send(PlayerEvent::StateChanged(PlayerState::Playing));
+            }),
+            Some(PlayerCommand::Stop(command_generation)) => {
+                generation = command_generation;
+                state.is_playing.store(false, Ordering::SeqCst);
+                state.position_ms.store(0, Ordering::SeqCst);
+                let _ = event_tx.send(PlayerEvent::StateChanged(PlayerState::Stopped));
+                renderer.stop(&http)
+            }
+            Some(PlayerCommand::SetVolume(vol)) => {
+                // Renderers can't amplify, so 100% is as loud as it gets
+                renderer.set_volume(&http, (vol.min(1.0) * 100.0).round() as u8)
+            }
+            // Renderers play at normal speed
+            Some(PlayerCommand::SetSpeed(_)) => Ok(()),
+            // Only ever sent to the local player
+            Some(PlayerCommand::Precache(_) | PlayerCommand::SetSpeakers(_)) => Ok(()),
+            Some(PlayerCommand::Seek(position, command_generation)) => {
+                generation = command_generation;
+                let mut attempt = 1;
+                let result = loop {
+                    match renderer.seek(&http, position) {
+                        Err(_) if attempt < RENDERER_SEEK_ATTEMPTS => {
+                            std::thread::sleep(Duration::from_millis(300));
+                            attempt += 1;
+                        }
+                        result => break result,
+                    }
+                };
+                result.map(|()| {
+                    state
+                        .position_ms
+                        .store(position.as_millis() as u64, Ordering::SeqCst);
+                })
+            }
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            let _ = event_tx.send(PlayerEvent::Error(format!("{}: {}", renderer.name, e)));
+        }
+
+        if state.is_playing.load(Ordering::SeqCst) && last_poll.elapsed() >= RENDERER_POLL_INTERVAL
+        {
+            last_poll = Instant::now();
+            match renderer.state(&http).as_deref() {
+                Ok("PLAYING") => {
+                    started = true;
+                    if let Ok(Some(position)) = renderer.position(&http) {
+                        state
+                            .position_ms
+                            .store(position.as_millis() as u64, Ordering::SeqCst);
+                        if let Some(dur) = current_duration {
+                            let _ = event_tx.send(PlayerEvent::Progress {
+                                position,
+                                duration: dur,
+                                generation,
+                            });
+                        }
+                    }
+                }
+                Ok("STOPPED") | Ok("NO_MEDIA_PRESENT") if started => {
+                    started = false;
+                    state.is_playing.store(false, Ordering::SeqCst);
+                    let _ = event_tx.send(PlayerEvent::TrackEnded { generation });
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to poll {}: {}", renderer.name, e),
+            }
+        }
+
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Get a track's audio data from the cache, or download (and cache) it.
+/// HLS downloads, when asked for, fall back to the plain stream on servers
+/// without HLS, and aren't cached: the bitrate the connection allowed then
+/// shouldn't stick to the track.
+fn load_audio_data(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    hls_url: Option<&str>,
+    key: &TrackKey,
+    cache: Option<&StreamCache>,
+    progress: impl Fn(f32),
+) -> Result<Vec<u8>> {
+    if let Some(data) = cache.and_then(|cache| cache.get(key)) {
+        return Ok(data);
+    }
+    if let Some(hls_url) = hls_url {
+        match hls::fetch(client, hls_url, &progress) {
+            Ok(data) => return Ok(data),
+            Err(e) => tracing::warn!("HLS download failed ({}), streaming instead", e),
+        }
+    }
+
+    let data = fetch_audio_data(client, url, progress)?;
+    if let Some(cache) = cache {
+        if let Err(e) = cache.insert(key, &data) {
+            tracing::warn!("Failed to cache track {}: {}", key, e);
+        }
+    }
+    Ok(data)
+}
+
+/// Fetch audio data from URL. Downloads cut short (a flaky connection, a
+/// dropped body) pick up where they stopped.
+pub(super) fn fetch_audio_data(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    progress: impl Fn(f32),
+) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut attempt = 1;
+    loop {
+        let error = match fetch_rest(client, url, &mut bytes, &progress) {
+            Ok(()) => return Ok(bytes),
+            Err(e) => e,
+        };
+        // The server turning the request down won't change on retrying
+        let refused = error
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(reqwest::Error::is_status);
+        if refused || attempt == FETCH_ATTEMPTS {
+            return Err(error);
+        }
+        tracing::warn!(
+            "Download interrupted after {} bytes ({}), resuming",
+            bytes.len(),
+            error
+        );
+        std::thread::sleep(FETCH_RETRY_DELAY * attempt);
+        attempt += 1;
+    }
+}
+
+/// Download the rest of a track onto `bytes`, asking only for the missing
+/// part if some was already fetched. Reports the share downloaded whenever
+/// it grows by a percent.
+fn fetch_rest(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    bytes: &mut Vec<u8>,
+    progress: &impl Fn(f32),
+) -> Result<()> {
+    let mut request = client.get(url);
+    if !bytes.is_empty() {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", bytes.len()));
+    }
+    let mut response = request.send()?.error_for_status()?;
+    // Servers that ignore the range (e.g. when transcoding) send it all again
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        bytes.clear();
+    }
+    let expected = response
+        .content_length()
+        .map(|len| len + bytes.len() as u64);
+    bytes.reserve(expected.unwrap_or(0).saturating_sub(bytes.len() as u64) as usize);
+
+    let mut chunk = vec![0; FETCH_CHUNK];
+    let mut reported = 0;
+    loop {
+        let n = match response.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        bytes.extend_from_slice(&chunk[..n]);
+        if let Some(expected) = expected.filter(|&expected| expected > 0) {
+            let percent = (bytes.len() as u64 * 100 / expected).min(100);
+            if percent > reported {
+                reported = percent;
+                progress(percent as f32 / 100.0);
+            }
+        }
+    }
+
+    match expected {
+        Some(expected) if bytes.len() as u64 != expected => Err(color_eyre::eyre::eyre!(
+            "Incomplete download: got {} of {} bytes",
+            bytes.len(),
+            expected
+        )),
+        _ => Ok(()),
+    }
 }
 
 /// Convert linear volume (0.0-1.0) to logarithmic/perceptual volume.
+/// Volumes above 1.0 are left to [`Amplify`].
 /// Human hearing perceives loudness logarithmically, so we need to convert
 /// the linear slider position to an exponential amplitude scale.
 /// Uses a curve that feels natural: amplitude = volume^2.5
@@ -563,16 +1518,169 @@ fn linear_to_log_volume(linear: f32) -> f32 {
     }
 }
 
+/// Fade the playing stream out, waiting until it is silent.
+fn fade_out(state: &PlayerStateShared, sink: &Mutex<Sink>) {
+    let playing = {
+        let s = sink.lock().unwrap();
+        !s.is_paused() && !s.empty()
+    };
+    if state.fader.length().is_zero() || !playing {
+        return;
+    }
+    state.fader.fade_out();
+    state.fader.wait_silent();
+}
+
+/// A downloaded track, kept for seeking.
+#[derive(Clone)]
+struct LoadedTrack {
+    song_id: String,
+    /// What its cached copy is stored under
+    cache_key: TrackKey,
+    /// File type the server reports
+    suffix: Option<String>,
+    data: Vec<u8>,
+}
+
+/// Report a track that couldn't be decoded, dropping its cached copy so
+/// it's fetched again (perhaps transcoded) next time.
+fn decode_failed(
+    track: &LoadedTrack,
+    error: DecodeError,
+    generation: u64,
+    cache: Option<&StreamCache>,
+) -> PlayerEvent {
+    tracing::warn!("Can't decode track {}: {}", track.song_id, error);
+    if let Some(cache) = cache {
+        cache.remove(&track.cache_key);
+    }
+    PlayerEvent::DecodeFailed {
+        song_id: track.song_id.clone(),
+        error,
+        generation,
+    }
+}
+
+/// Tracks waiting to be precached and those downloading, shared between the
+/// player and the precache threads so playing a track takes over its
+/// precache instead of downloading it a second time.
+#[derive(Default)]
+struct Precaching {
+    tracks: Mutex<PrecacheTracks>,
+
+    /// Signalled as each download finishes
+    finished: Condvar,
+}
+
+#[derive(Default)]
+struct PrecacheTracks {
+    waiting: HashSet<String>,
+    fetching: HashSet<String>,
+}
+
+impl Precaching {
+    /// Ask for a track to be precached. Returns false if it already is.
+    fn want(&self, song_id: &str) -> bool {
+        let mut tracks = self.tracks.lock().unwrap();
+        !tracks.fetching.contains(song_id) && tracks.waiting.insert(song_id.to_string())
+    }
+
+    /// Start downloading a waiting track. Returns false if the player took
+    /// it over in the meantime.
+    fn start(&self, song_id: &str) -> bool {
+        let mut tracks = self.tracks.lock().unwrap();
+        tracks.waiting.remove(song_id) && tracks.fetching.insert(song_id.to_string())
+    }
+
+    fn finish(&self, song_id: &str) {
+        self.tracks.lock().unwrap().fetching.remove(song_id);
+        self.finished.notify_all();
+    }
+
+    /// Take a track over for playing: drop it if it's still waiting, or
+    /// wait for its download to finish and land in the cache.
+    fn claim(&self, song_id: &str) {
+        let mut tracks = self.tracks.lock().unwrap();
+        tracks.waiting.remove(song_id);
+        while tracks.fetching.contains(song_id) {
+            tracks = self.finished.wait(tracks).unwrap();
+        }
+    }
+}
+
+/// Download tracks into the cache one after another in the background,
+/// reporting each as it's stored.
+fn spawn_precache(
+    songs: Vec<(String, String)>,
+    http: reqwest::blocking::Client,
+    cache: StreamCache,
+    precaching: Arc<Precaching>,
+    event_tx: mpsc::UnboundedSender<PlayerEvent>,
+) {
+    std::thread::spawn(move || {
+        for (song_id, url) in songs {
+            if !precaching.start(&song_id) {
+                continue;
+            }
+            match fetch_audio_data(&http, &url, |_| {}) {
+                Ok(data) => match cache.insert(&TrackKey::for_url(&song_id, &url), &data) {
+                    Ok(()) => {
+                        tracing::debug!("Precached track {}", song_id);
+                        let _ = event_tx.send(PlayerEvent::Cached {
+                            song_id: song_id.clone(),
+                        });
+                    }
+                    Err(e) => tracing::warn!("Failed to cache track {}: {}", song_id, e),
+                },
+                Err(e) => tracing::warn!("Failed to precache track {}: {}", song_id, e),
+            }
+            precaching.finish(&song_id);
+        }
+    });
+}
+
+/// Measure a track's loudness in the background and report it as an event.
+fn spawn_loudness_analysis(track: LoadedTrack, event_tx: mpsc::UnboundedSender<PlayerEvent>) {
+    std::thread::spawn(move || {
+        let source = match SymphoniaSource::new(track.data, track.suffix.as_deref(), Arc::default())
+        {
+            Ok(source) => source,
+            Err(e) => {
+                tracing::warn!("Loudness analysis failed: {}", e);
+                return;
+            }
+        };
+        let (channels, sample_rate) = (source.channels(), source.sample_rate());
+        if let Some(loudness) = loudness::analyze(source, channels, sample_rate) {
+            let _ = event_tx.send(PlayerEvent::LoudnessMeasured {
+                song_id: track.song_id,
+                loudness,
+            });
+        }
+    });
+}
+
 /// Play audio data with optional seek position.
 /// Uses SymphoniaSource directly to ensure proper seeking support.
 fn play_audio_data(
-    audio_data: &[u8],
+    track: &LoadedTrack,
     sink: &Arc<Mutex<Sink>>,
     volume: f32,
+    gain: f32,
+    speed: f32,
     seek_to: Duration,
+    state: &PlayerStateShared,
 ) -> Result<()> {
     // Create our custom symphonia source with proper byte_len() support
-    let mut source = SymphoniaSource::new(audio_data.to_vec())?;
+    *state.decode_failure.lock().unwrap() = None;
+    if let Some(silence) = &state.silence {
+        silence.take_skipped();
+    }
+    let mut source = SymphoniaSource::new(
+        track.data.clone(),
+        track.suffix.as_deref(),
+        Arc::clone(&state.decode_failure),
+    )?;
 
     // If we need to seek, do it before appending to sink
     if seek_to > Duration::ZERO {
@@ -580,9 +1688,60 @@ fn play_audio_data(
     }
 
     let s = sink.lock().unwrap();
-    s.append(source);
-    s.set_volume(linear_to_log_volume(volume));
+    let source = SkipSilence::new(source, state.silence.clone());
+    let source = state.resampler.apply(source);
+    let source = Amplify::new(source, Arc::clone(&state.amplification));
+    let source = Fade::new(source, Arc::clone(&state.fader));
+    s.append(Tap::new(source, Arc::clone(&state.samples)));
+    s.set_volume(linear_to_log_volume(volume) * gain);
+    s.set_speed(speed);
     s.play();
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precache_claim() {
+        let precaching = Arc::new(Precaching::default());
+        assert!(precaching.want("a"));
+        assert!(!precaching.want("a"));
+        assert!(precaching.want("b"));
+
+        // Playing a waiting track takes it away from the precache thread
+        precaching.claim("b");
+        assert!(!precaching.start("b"));
+
+        // Playing a track being precached waits for the download
+        assert!(precaching.start("a"));
+        assert!(!precaching.want("a"));
+        let worker = Arc::clone(&precaching);
+        let download = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            worker.finish("a");
+        });
+        precaching.claim("a");
+        assert!(precaching.tracks.lock().unwrap().fetching.is_empty());
+        download.join().unwrap();
+    }
+
+    #[test]
+    fn test_decode_error() {
+        let error = SymphoniaSource::new(vec![0; 4096], Some("wma"), Arc::default())
+            .err()
+            .unwrap();
+        assert!(matches!(
+            &error,
+            DecodeError::Container { container, .. } if container == "wma"
+        ));
+
+        let error = DecodeError::Damaged {
+            codec: String::from("flac"),
+            at: Duration::from_secs(125),
+        };
+        assert_eq!(error.to_string(), "flac audio is damaged at 2:05");
+    }
+}
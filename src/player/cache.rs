@@ -0,0 +1,255 @@
+//! On-disk cache of downloaded tracks.
+//!
+//! Each track is stored next to a small JSON entry recording its size and MD5
+//! checksum. Entries are verified when read: a truncated or corrupt file is
+//! dropped and the track downloaded again, rather than handed to the decoder
+//! where it would fail partway through.
+
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use color_eyre::Result;
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Integrity record stored beside each cached track.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Entry {
+    /// Size of the audio file in bytes
+    size: u64,
+
+    /// MD5 checksum of the audio file, hex encoded
+    md5: String,
+}
+
+impl Entry {
+    fn for_data(data: &[u8]) -> Self {
+        Self {
+            size: data.len() as u64,
+            md5: checksum(data),
+        }
+    }
+}
+
+/// What a cached track is stored under: its song, and the format and
+/// bitrate the server was asked to stream it at, so a transcoded copy never
+/// stands in for the original or the other way round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackKey {
+    song_id: String,
+    format: Option<String>,
+    max_bitrate: Option<String>,
+}
+
+impl TrackKey {
+    /// Get the key of a track streamed from `url`, reading the format and
+    /// bitrate asked for from its query.
+    pub fn for_url(song_id: &str, url: &str) -> Self {
+        let mut key = Self {
+            song_id: song_id.to_string(),
+            format: None,
+            max_bitrate: None,
+        };
+        if let Ok(url) = reqwest::Url::parse(url) {
+            for (name, value) in url.query_pairs() {
+                match name.as_ref() {
+                    "format" => key.format = Some(value.into_owned()),
+                    "maxBitRate" => key.max_bitrate = Some(value.into_owned()),
+                    _ => {}
+                }
+            }
+        }
+        key
+    }
+
+    /// Get the name the track's files are stored under.
+    fn file_name(&self) -> String {
+        // Song IDs are server-defined, so hash them into safe file names.
+        // The original file goes under the song ID alone.
+        match (&self.format, &self.max_bitrate) {
+            (None, None) => checksum(self.song_id.as_bytes()),
+            (format, bitrate) => checksum(
+                format!(
+                    "{}\n{}\n{}",
+                    self.song_id,
+                    format.as_deref().unwrap_or_default(),
+                    bitrate.as_deref().unwrap_or_default()
+                )
+                .as_bytes(),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for TrackKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.song_id)?;
+        if let Some(format) = &self.format {
+            write!(f, " as {}", format)?;
+        }
+        if let Some(bitrate) = &self.max_bitrate {
+            write!(f, " at {} kbps", bitrate)?;
+        }
+        Ok(())
+    }
+}
+
+/// Tracks cached on disk, keyed by song, format and bitrate.
+#[derive(Debug, Clone)]
+pub struct StreamCache {
+    dir: PathBuf,
+
+    /// Size the cache is trimmed to after each insert
+    max_bytes: u64,
+}
+
+impl StreamCache {
+    /// Create a cache in `dir` holding up to `max_bytes` of audio.
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self { dir, max_bytes }
+    }
+
+    /// Open the cache in the user's cache directory.
+    /// Returns `None` when caching is disabled (`max_mb` is 0).
+    pub fn open(max_mb: u64) -> Result<Option<Self>> {
+        if max_mb == 0 {
+            return Ok(None);
+        }
+        let dir = Config::cache_dir()?.join("tracks");
+        std::fs::create_dir_all(&dir)?;
+        Ok(Some(Self::new(dir, max_mb * 1024 * 1024)))
+    }
+
+    /// Paths of a track's audio file and integrity entry.
+    fn paths(&self, key: &TrackKey) -> (PathBuf, PathBuf) {
+        let key = key.file_name();
+        (
+            self.dir.join(format!("{}.audio", key)),
+            self.dir.join(format!("{}.json", key)),
+        )
+    }
+
+    /// Read a cached track, verifying its size and checksum.
+    /// A corrupt entry is removed so the track gets downloaded again.
+    pub fn get(&self, key: &TrackKey) -> Option<Vec<u8>> {
+        let (audio_path, entry_path) = self.paths(key);
+        if !audio_path.exists() {
+            return None;
+        }
+
+        let entry: Option<Entry> = std::fs::read_to_string(&entry_path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok());
+        let data = std::fs::read(&audio_path).ok();
+
+        match (entry, data) {
+            (Some(entry), Some(data)) if entry == Entry::for_data(&data) => {
+                // Mark as recently used so eviction keeps it
+                if let Ok(file) = std::fs::File::options().write(true).open(&audio_path) {
+                    let _ = file.set_modified(SystemTime::now());
+                }
+                Some(data)
+            }
+            _ => {
+                tracing::warn!("Cached track {} failed verification, discarding", key);
+                let _ = std::fs::remove_file(&audio_path);
+                let _ = std::fs::remove_file(&entry_path);
+                None
+            }
+        }
+    }
+
+    /// Get the file of a cached track, without verifying it.
+    pub fn path(&self, key: &TrackKey) -> Option<PathBuf> {
+        let (audio_path, entry_path) = self.paths(key);
+        (audio_path.exists() && entry_path.exists()).then_some(audio_path)
+    }
+
+    /// Store a downloaded track and trim the cache to its size limit.
+    pub fn insert(&self, key: &TrackKey, data: &[u8]) -> Result<()> {
+        let (audio_path, entry_path) = self.paths(key);
+
+        // Write the entry last, so a track interrupted mid-write never verifies
+        let _ = std::fs::remove_file(&entry_path);
+        std::fs::write(&audio_path, data)?;
+        std::fs::write(&entry_path, serde_json::to_string(&Entry::for_data(data))?)?;
+
+        self.evict()
+    }
+
+    /// Forget a cached track, such as one that turned out not to play.
+    pub fn remove(&self, key: &TrackKey) {
+        let (audio_path, entry_path) = self.paths(key);
+        let _ = std::fs::remove_file(entry_path);
+        let _ = std::fs::remove_file(audio_path);
+    }
+
+    /// Remove least recently used tracks until the cache fits its size limit.
+    fn evict(&self) -> Result<()> {
+        let mut tracks: Vec<(SystemTime, u64, PathBuf)> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "audio"))
+            .filter_map(|path| {
+                let metadata = path.metadata().ok()?;
+                Some((metadata.modified().ok()?, metadata.len(), path))
+            })
+            .collect();
+
+        let mut total: u64 = tracks.iter().map(|(_, size, _)| size).sum();
+        tracks.sort_by_key(|(modified, _, _)| *modified);
+
+        for (_, size, path) in tracks {
+            if total <= self.max_bytes {
+                break;
+            }
+            std::fs::remove_file(&path)?;
+            let _ = std::fs::remove_file(path.with_extension("json"));
+            total -= size;
+        }
+
+        Ok(())
+    }
+}
+
+/// Hex-encoded MD5 of some bytes, also used to turn server-defined IDs into
+/// safe file names.
+pub fn checksum(data: &[u8]) -> String {
+    format!("{:x}", Md5::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corrupt_entry_is_discarded() {
+        let dir = std::env::temp_dir().join(format!("subsonic-tui-cache-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cache = StreamCache::new(dir.clone(), u64::MAX);
+
+        let key = TrackKey::for_url("song-1", "https://music.example/rest/stream?id=song-1");
+        cache.insert(&key, b"complete audio data").unwrap();
+        assert_eq!(
+            cache.get(&key).as_deref(),
+            Some(&b"complete audio data"[..])
+        );
+
+        // A transcoded copy is a different track
+        let mp3 = TrackKey::for_url(
+            "song-1",
+            "https://music.example/rest/stream?id=song-1&format=mp3",
+        );
+        assert_eq!(cache.path(&mp3), None);
+
+        // Truncate the file as an interrupted write would
+        let (audio_path, entry_path) = cache.paths(&key);
+        std::fs::write(&audio_path, b"complete").unwrap();
+        assert_eq!(cache.get(&key), None);
+        assert!(!audio_path.exists() && !entry_path.exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
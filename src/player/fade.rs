@@ -0,0 +1,188 @@
+//! Short fades when playback starts, pauses, resumes, or stops, so the sound
+//! doesn't cut in or out with a click.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use rodio::Source;
+
+/// Time allowed past a fade's length for the source to reach silence, as
+/// the device takes samples a buffer at a time.
+const SILENCE_MARGIN: Duration = Duration::from_millis(100);
+
+/// Whether the playing source should be heard, shared between the player
+/// and the source.
+#[derive(Debug)]
+pub struct Fader {
+    audible: AtomicBool,
+
+    /// Length of a fade (zero = no fading)
+    length: Duration,
+
+    /// Fade-outs asked for so far
+    fade_outs: AtomicU64,
+
+    /// Last fade-out the source reached silence on
+    silenced: Mutex<u64>,
+
+    /// Signalled as the source reaches silence
+    silent: Condvar,
+}
+
+impl Fader {
+    pub fn new(length: Duration) -> Self {
+        Self {
+            audible: AtomicBool::new(true),
+            length,
+            fade_outs: AtomicU64::new(0),
+            silenced: Mutex::new(0),
+            silent: Condvar::new(),
+        }
+    }
+
+    pub fn length(&self) -> Duration {
+        self.length
+    }
+
+    pub fn fade_in(&self) {
+        self.audible.store(true, Ordering::Relaxed);
+    }
+
+    pub fn fade_out(&self) {
+        self.fade_outs.fetch_add(1, Ordering::SeqCst);
+        self.audible.store(false, Ordering::Relaxed);
+    }
+
+    /// Wait for the source to fade out to silence. Gives up a little after
+    /// a fade's length, in case nothing is playing the source. Returns
+    /// whether it went silent.
+    pub fn wait_silent(&self) -> bool {
+        let wanted = self.fade_outs.load(Ordering::SeqCst);
+        let silenced = self.silenced.lock().unwrap();
+        let (silenced, _) = self
+            .silent
+            .wait_timeout_while(silenced, self.length + SILENCE_MARGIN, |silenced| {
+                *silenced < wanted
+            })
+            .unwrap();
+        *silenced >= wanted
+    }
+
+    /// Note that the source reached silence on the latest fade-out.
+    fn silenced(&self, fade_out: u64) {
+        *self.silenced.lock().unwrap() = fade_out;
+        self.silent.notify_all();
+    }
+}
+
+/// A source faded in or out as its [`Fader`] says. Sources start silent and
+/// fade in.
+pub struct Fade<S> {
+    source: S,
+    fader: Arc<Fader>,
+
+    /// Current level (0.0 to 1.0)
+    level: f32,
+
+    /// Level change per sample
+    step: f32,
+
+    /// Last fade-out this source reported reaching silence on
+    silenced: u64,
+}
+
+impl<S: Source<Item = i16>> Fade<S> {
+    pub fn new(source: S, fader: Arc<Fader>) -> Self {
+        let samples = fader.length.as_secs_f32()
+            * source.sample_rate() as f32
+            * source.channels().max(1) as f32;
+        let (level, step) = if samples >= 1.0 {
+            (0.0, 1.0 / samples)
+        } else {
+            (1.0, 1.0)
+        };
+        Self {
+            source,
+            fader,
+            level,
+            step,
+            silenced: 0,
+        }
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for Fade<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.source.next()?;
+        self.level = if self.fader.audible.load(Ordering::Relaxed) {
+            (self.level + self.step).min(1.0)
+        } else {
+            (self.level - self.step).max(0.0)
+        };
+        if self.level <= 0.0 {
+            // Let a player waiting on the fade-out go on
+            let fade_out = self.fader.fade_outs.load(Ordering::SeqCst);
+            if fade_out > self.silenced && !self.fader.audible.load(Ordering::Relaxed) {
+                self.silenced = fade_out;
+                self.fader.silenced(fade_out);
+            }
+        }
+        if self.level >= 1.0 {
+            return Some(sample);
+        }
+        Some((sample as f32 * self.level) as i16)
+    }
+}
+
+impl<S: Source<Item = i16>> Source for Fade<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn test_fade_ramps() {
+        // 10 samples to fade over
+        let fader = Arc::new(Fader::new(Duration::from_millis(10)));
+        let source = SamplesBuffer::new(1, 1000, vec![1000i16; 40]);
+        let mut fade = Fade::new(source, Arc::clone(&fader));
+
+        // Starts silent and rises to full level
+        let rising: Vec<i16> = fade.by_ref().take(12).collect();
+        assert!(rising[0] <= 100);
+        assert!(rising.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(rising[11], 1000);
+
+        // Falls back to silence, and lets the player know
+        fader.fade_out();
+        let falling: Vec<i16> = fade.by_ref().take(12).collect();
+        assert!(falling.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(falling[11], 0);
+        assert!(fader.wait_silent());
+
+        // A fade-out nothing plays through gives up
+        fader.fade_in();
+        fader.fade_out();
+        assert!(!fader.wait_silent());
+    }
+}
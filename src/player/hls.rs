@@ -0,0 +1,267 @@
+//! HLS (`hls.m3u8`) downloads that adapt the bitrate to the connection.
+//!
+//! The server offers the track at a few bitrates, each cut into the same
+//! short segments. Segments are fetched one after another from the best
+//! bitrate the measured throughput keeps up with, and the audio is pulled out
+//! of their MPEG transport stream so the decoder can play it.
+
+use std::time::Instant;
+
+use color_eyre::eyre::eyre;
+use color_eyre::Result;
+use reqwest::Url;
+
+use super::backend::fetch_audio_data;
+
+/// Share of the measured throughput a bitrate may use, leaving room for
+/// the connection to slow down.
+const HEADROOM: f64 = 0.8;
+
+/// Size of an MPEG transport stream packet.
+const TS_PACKET: usize = 188;
+
+/// First byte of every transport stream packet.
+const TS_SYNC: u8 = 0x47;
+
+/// One bitrate of a track.
+#[derive(Debug, Clone, PartialEq)]
+struct Variant {
+    /// Bits per second
+    bandwidth: u64,
+    url: Url,
+}
+
+/// Download a track over HLS, returning its audio. Reports the share of
+/// segments fetched as they arrive.
+pub fn fetch(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    progress: impl Fn(f32),
+) -> Result<Vec<u8>> {
+    let url = Url::parse(url)?;
+    let playlist = fetch_playlist(client, &url)?;
+    let mut variants = parse_variants(&playlist, &url);
+    if variants.is_empty() {
+        // Not a master playlist: the segments are listed right here
+        variants.push(Variant {
+            bandwidth: 0,
+            url: url.clone(),
+        });
+    }
+    variants.sort_by_key(|variant| variant.bandwidth);
+
+    // Segment lists, fetched the first time a variant is picked
+    let mut segments: Vec<Option<Vec<Url>>> = vec![None; variants.len()];
+    let mut throughput = None;
+    let mut audio = Vec::new();
+    let mut index = 0;
+    loop {
+        let picked = pick_variant(&variants, throughput);
+        let list = match &mut segments[picked] {
+            Some(list) => list,
+            slot => {
+                let variant = &variants[picked].url;
+                slot.insert(parse_segments(&fetch_playlist(client, variant)?, variant))
+            }
+        };
+        let Some(segment) = list.get(index) else {
+            break;
+        };
+        let total = list.len();
+
+        let started = Instant::now();
+        let data = fetch_audio_data(client, segment.as_str(), |_| {})?;
+        let elapsed = started.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            let measured = data.len() as f64 * 8.0 / elapsed;
+            throughput = Some(throughput.map_or(measured, |previous| (previous + measured) / 2.0));
+        }
+
+        if is_transport_stream(&data) {
+            audio.extend(elementary_stream(&data));
+        } else {
+            audio.extend(data);
+        }
+        index += 1;
+        progress(index as f32 / total as f32);
+    }
+
+    if audio.is_empty() {
+        return Err(eyre!("HLS playlist has no segments"));
+    }
+    Ok(audio)
+}
+
+/// Fetch a playlist, making sure it is one: servers without HLS answer
+/// with an error document instead.
+fn fetch_playlist(client: &reqwest::blocking::Client, url: &Url) -> Result<String> {
+    let text = client.get(url.clone()).send()?.error_for_status()?.text()?;
+    if !text.trim_start().starts_with("#EXTM3U") {
+        return Err(eyre!("Server did not return an HLS playlist"));
+    }
+    Ok(text)
+}
+
+/// Get the variants listed in a master playlist.
+fn parse_variants(playlist: &str, base: &Url) -> Vec<Variant> {
+    let mut variants = Vec::new();
+    let mut bandwidth = None;
+    for line in playlist.lines().map(str::trim) {
+        if let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            bandwidth = Some(
+                attributes
+                    .split(',')
+                    .filter_map(|attribute| attribute.trim().strip_prefix("BANDWIDTH="))
+                    .find_map(|value| value.parse().ok())
+                    .unwrap_or(0),
+            );
+        } else if !line.is_empty() && !line.starts_with('#') {
+            if let (Some(bandwidth), Some(url)) = (bandwidth.take(), resolve(base, line)) {
+                variants.push(Variant { bandwidth, url });
+            }
+        }
+    }
+    variants
+}
+
+/// Get the segments listed in a media playlist.
+fn parse_segments(playlist: &str, base: &Url) -> Vec<Url> {
+    playlist
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| resolve(base, line))
+        .collect()
+}
+
+/// Resolve a playlist entry against the playlist's URL. Links back to the
+/// same server get the playlist's credentials where they leave them out.
+fn resolve(base: &Url, entry: &str) -> Option<Url> {
+    let mut url = base.join(entry).ok()?;
+    if url.origin() == base.origin() {
+        let present: Vec<String> = url.query_pairs().map(|(key, _)| key.into_owned()).collect();
+        let missing: Vec<_> = base
+            .query_pairs()
+            .filter(|(key, _)| !present.iter().any(|present| present == key))
+            .collect();
+        url.query_pairs_mut().extend_pairs(missing);
+    }
+    Some(url)
+}
+
+/// Pick the best variant the throughput (in bits per second) keeps up
+/// with, or the lowest before anything was measured. Variants are sorted
+/// by bandwidth.
+fn pick_variant(variants: &[Variant], throughput: Option<f64>) -> usize {
+    let Some(throughput) = throughput else {
+        return 0;
+    };
+    variants
+        .iter()
+        .rposition(|variant| variant.bandwidth as f64 <= throughput * HEADROOM)
+        .unwrap_or(0)
+}
+
+/// Check whether a segment is an MPEG transport stream.
+fn is_transport_stream(data: &[u8]) -> bool {
+    data.len() >= TS_PACKET && data.len().is_multiple_of(TS_PACKET) && data[0] == TS_SYNC
+}
+
+/// Pull the audio (AAC or MP3 frames) out of a transport stream. The audio
+/// stream is the first one whose packets carry MPEG audio.
+fn elementary_stream(data: &[u8]) -> Vec<u8> {
+    let mut audio = Vec::new();
+    let mut audio_pid = None;
+    for packet in data.chunks_exact(TS_PACKET) {
+        if packet[0] != TS_SYNC {
+            continue;
+        }
+        let unit_start = packet[1] & 0x40 != 0;
+        let pid = u16::from(packet[1] & 0x1f) << 8 | u16::from(packet[2]);
+        let adaptation = packet[3] >> 4 & 0x3;
+        if adaptation & 0x1 == 0 {
+            continue;
+        }
+        let mut start = 4;
+        if adaptation & 0x2 != 0 {
+            start += 1 + usize::from(packet[4]);
+        }
+        let Some(payload) = packet.get(start..) else {
+            continue;
+        };
+
+        if unit_start {
+            // A PES header: start code, stream id, length, flags, header length
+            let pes_audio = payload.len() >= 9
+                && payload[..3] == [0, 0, 1]
+                && (0xc0..=0xdf).contains(&payload[3]);
+            if audio_pid.is_none() && pes_audio {
+                audio_pid = Some(pid);
+            }
+            if audio_pid == Some(pid) && pes_audio {
+                let header = 9 + usize::from(payload[8]);
+                audio.extend_from_slice(payload.get(header..).unwrap_or_default());
+            }
+        } else if audio_pid == Some(pid) {
+            audio.extend_from_slice(payload);
+        }
+    }
+    audio
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_variants() {
+        let base = Url::parse("https://music.example/rest/hls.m3u8?id=1&u=me&t=x").unwrap();
+        let playlist = "#EXTM3U\n\
+            #EXT-X-STREAM-INF:PROGRAM-ID=1, BANDWIDTH=320000\n\
+            hls.m3u8?id=1&bitRate=320\n\
+            #EXT-X-STREAM-INF:PROGRAM-ID=1, BANDWIDTH=64000\n\
+            https://cdn.example/64.m3u8\n";
+        let variants = parse_variants(playlist, &base);
+        assert_eq!(variants.len(), 2);
+        assert_eq!(variants[0].bandwidth, 320_000);
+        assert_eq!(
+            variants[0].url.as_str(),
+            "https://music.example/rest/hls.m3u8?id=1&bitRate=320&u=me&t=x"
+        );
+        assert_eq!(variants[1].url.as_str(), "https://cdn.example/64.m3u8");
+        assert!(parse_variants("#EXTM3U\n#EXTINF:10,\nsegment.ts\n", &base).is_empty());
+
+        let mut sorted = variants;
+        sorted.sort_by_key(|variant| variant.bandwidth);
+        assert_eq!(pick_variant(&sorted, None), 0);
+        assert_eq!(pick_variant(&sorted, Some(100_000.0)), 0);
+        assert_eq!(pick_variant(&sorted, Some(1_000_000.0)), 1);
+    }
+
+    #[test]
+    fn test_elementary_stream() {
+        let packet = |pid: u8, unit_start: bool, payload: &[u8]| {
+            let mut packet = vec![TS_SYNC, if unit_start { 0x40 } else { 0 }, pid, 0x10];
+            packet.extend_from_slice(payload);
+            packet.resize(TS_PACKET, 0xff);
+            packet
+        };
+        let mut stream = packet(0, true, &[0, 0, 0xb0]);
+        stream.extend(packet(
+            0x42,
+            true,
+            &[0, 0, 1, 0xc0, 0, 0, 0x80, 0x80, 2, 9, 9, 0xaa],
+        ));
+        // Padding in an adaptation field
+        let mut padded = vec![TS_SYNC, 0, 0x42, 0x30, TS_PACKET as u8 - 6, 0];
+        padded.resize(TS_PACKET - 1, 0);
+        padded.push(0xbb);
+        stream.extend(padded);
+
+        assert!(is_transport_stream(&stream));
+        let audio = elementary_stream(&stream);
+        assert_eq!(audio[0], 0xaa);
+        assert_eq!(audio.len(), TS_PACKET - 4 - 11 + 1);
+        assert_eq!(audio.last(), Some(&0xbb));
+    }
+}
@@ -0,0 +1,202 @@
+//! Loudness normalization.
+//!
+//! Tracks with ReplayGain tags are normalized from the server-provided values.
+//! Tracks without them get a quick loudness estimate on first play (EBU R128
+//! gating without the K-weighting filter), cached so later plays can use it.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::client::models::Song;
+use crate::config::{Config, ReplayGainMode};
+
+/// Loudness the estimated gain brings tracks to, matching ReplayGain 2.0.
+const REFERENCE_LUFS: f64 = -18.0;
+
+/// Length of the blocks loudness is measured over.
+const BLOCK_SECONDS: f64 = 0.4;
+
+/// Blocks quieter than this are silence and ignored.
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+
+/// Blocks this far below the ungated average are ignored.
+const RELATIVE_GATE_LU: f64 = 10.0;
+
+/// Estimated loudness of a track without ReplayGain tags.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Loudness {
+    /// Gain to reach the reference loudness, in dB
+    pub gain_db: f32,
+
+    /// Peak sample amplitude (1.0 = full scale)
+    pub peak: f32,
+}
+
+/// How the player should normalize a track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Normalize {
+    /// Play as-is
+    Off,
+    /// Scale the volume by a linear factor
+    Gain(f32),
+    /// Play as-is and measure the loudness for next time
+    Analyze,
+}
+
+/// Decide how to normalize a song, preferring its ReplayGain tags over a cached estimate.
+pub fn normalization(mode: ReplayGainMode, song: &Song, cached: Option<&Loudness>) -> Normalize {
+    if mode == ReplayGainMode::Off {
+        return Normalize::Off;
+    }
+
+    let tagged = song.replay_gain.as_ref().and_then(|rg| {
+        let (gain, peak) = match mode {
+            ReplayGainMode::Album => (
+                rg.album_gain.or(rg.track_gain),
+                rg.album_peak.or(rg.track_peak),
+            ),
+            _ => (rg.track_gain, rg.track_peak),
+        };
+        gain.map(|gain| (gain + rg.base_gain.unwrap_or(0.0), peak))
+    });
+
+    match (tagged, cached) {
+        (Some((gain_db, peak)), _) => {
+            Normalize::Gain(gain_factor(gain_db as f32, peak.map(|p| p as f32)))
+        }
+        (None, Some(loudness)) => {
+            Normalize::Gain(gain_factor(loudness.gain_db, Some(loudness.peak)))
+        }
+        (None, None) => Normalize::Analyze,
+    }
+}
+
+/// Convert a gain in dB to a linear factor, limited so the peak doesn't clip.
+fn gain_factor(gain_db: f32, peak: Option<f32>) -> f32 {
+    let factor = 10f32.powf(gain_db / 20.0);
+    match peak {
+        Some(peak) if peak > 0.0 => factor.min(1.0 / peak),
+        _ => factor,
+    }
+}
+
+/// Estimate the loudness of interleaved samples.
+/// Returns `None` for silent or empty audio.
+pub fn analyze(
+    samples: impl Iterator<Item = i16>,
+    channels: u16,
+    sample_rate: u32,
+) -> Option<Loudness> {
+    let channels = channels.max(1) as usize;
+    let block_len = ((sample_rate as f64 * BLOCK_SECONDS) as usize).max(1) * channels;
+
+    let mut blocks: Vec<f64> = Vec::new();
+    let mut sum_squares = 0.0;
+    let mut count = 0;
+    let mut peak: f32 = 0.0;
+
+    for sample in samples {
+        let value = sample as f64 / 32768.0;
+        sum_squares += value * value;
+        peak = peak.max(value.abs() as f32);
+        count += 1;
+
+        if count == block_len {
+            // Mean square per channel, summed over channels
+            blocks.push(sum_squares / (block_len / channels) as f64);
+            sum_squares = 0.0;
+            count = 0;
+        }
+    }
+
+    let lufs = |power: f64| -0.691 + 10.0 * power.log10();
+    let mean = |blocks: &[f64]| blocks.iter().sum::<f64>() / blocks.len() as f64;
+
+    let blocks: Vec<f64> = blocks
+        .into_iter()
+        .filter(|&power| power > 0.0 && lufs(power) > ABSOLUTE_GATE_LUFS)
+        .collect();
+    if blocks.is_empty() {
+        return None;
+    }
+
+    let threshold = lufs(mean(&blocks)) - RELATIVE_GATE_LU;
+    let gated: Vec<f64> = blocks
+        .into_iter()
+        .filter(|&power| lufs(power) > threshold)
+        .collect();
+    let loudness = lufs(mean(&gated));
+
+    Some(Loudness {
+        gain_db: (REFERENCE_LUFS - loudness) as f32,
+        peak,
+    })
+}
+
+/// Get the loudness cache file path.
+fn cache_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join("loudness.json"))
+}
+
+/// Load cached loudness estimates, keyed by song ID.
+pub fn load() -> Result<HashMap<String, Loudness>> {
+    let path = cache_path()?;
+
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let contents = std::fs::read_to_string(&path)?;
+    let cache: HashMap<String, Loudness> = serde_json::from_str(&contents)?;
+
+    Ok(cache)
+}
+
+/// Save cached loudness estimates.
+pub fn save(cache: &HashMap<String, Loudness>) -> Result<()> {
+    let path = cache_path()?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(&path, serde_json::to_string(cache)?)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_full_scale_square_wave() {
+        // One second of a full-scale stereo square wave: 0 dBFS mean square, -0.691 + 3 LUFS
+        let samples = (0..44100 * 2).map(|i| {
+            if (i / 2) % 2 == 0 {
+                i16::MAX
+            } else {
+                -i16::MAX
+            }
+        });
+        let loudness = analyze(samples, 2, 44100).unwrap();
+
+        assert!((loudness.gain_db - (-18.0 - 2.32)).abs() < 0.05);
+        assert!(loudness.peak > 0.99);
+    }
+
+    #[test]
+    fn test_analyze_silence() {
+        assert_eq!(analyze(std::iter::repeat_n(0, 44100), 1, 44100), None);
+    }
+
+    #[test]
+    fn test_gain_limited_by_peak() {
+        // +6 dB would double the amplitude, but the peak only leaves room for 1.25x
+        assert!((gain_factor(6.0, Some(0.8)) - 1.25).abs() < 1e-6);
+        assert!((gain_factor(-6.0, None) - 0.501).abs() < 1e-3);
+    }
+}
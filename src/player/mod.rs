@@ -1,5 +1,17 @@
 //! Audio player module.
 
+pub mod amplify;
 pub mod backend;
+pub mod cache;
+pub mod fade;
+pub mod hls;
+pub mod loudness;
+pub mod network;
+pub mod resample;
+pub mod silence;
+pub mod upnp;
+pub mod visualizer;
 
-pub use backend::{Player, PlayerEvent};
+pub use backend::{DeviceOptions, Output, Player, PlayerEvent, MAX_SPEED, MIN_SPEED};
+pub use cache::{StreamCache, TrackKey};
+pub use loudness::Loudness;
@@ -0,0 +1,183 @@
+//! Audio sent over the network as raw PCM instead of to a sound card, for
+//! feeding a Snapcast server (or any TCP/UDP sink) from a headless box.
+//!
+//! The stream is 48 kHz, 16-bit little-endian stereo, Snapcast's default
+//! sample format, sent in real time whether or not anything is playing.
+
+use std::io::Write;
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre::eyre;
+use color_eyre::Result;
+use rodio::queue::SourcesQueueOutput;
+use rodio::source::UniformSourceIterator;
+use rodio::Sink;
+
+/// Sample rate of the stream.
+pub const SAMPLE_RATE: u32 = 48_000;
+
+/// Channels of the stream.
+const CHANNELS: u16 = 2;
+
+/// Audio sent at a time.
+const CHUNK: Duration = Duration::from_millis(20);
+
+/// Longest wait for connecting or sending before giving up on the connection.
+const IO_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Wait before connecting again after the connection drops.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Where the stream goes, parsed from `tcp://host:port` or `udp://host:port`.
+#[derive(Debug, Clone, PartialEq)]
+enum Address {
+    Tcp(String),
+    Udp(String),
+}
+
+impl Address {
+    fn parse(address: &str) -> Result<Self> {
+        match address.split_once("://") {
+            Some(("tcp", host)) if !host.is_empty() => Ok(Self::Tcp(host.to_string())),
+            Some(("udp", host)) if !host.is_empty() => Ok(Self::Udp(host.to_string())),
+            _ => Err(eyre!(
+                "Invalid network output '{}' (expected tcp://host:port or udp://host:port)",
+                address
+            )),
+        }
+    }
+}
+
+/// Connection to the address the stream goes to.
+enum Connection {
+    Tcp(TcpStream),
+    Udp(UdpSocket),
+}
+
+impl Connection {
+    fn open(address: &Address) -> std::io::Result<Self> {
+        match address {
+            Address::Tcp(host) => {
+                let addr = host.to_socket_addrs()?.next().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::NotFound, "host not found")
+                })?;
+                let stream = TcpStream::connect_timeout(&addr, IO_TIMEOUT)?;
+                stream.set_nodelay(true)?;
+                // A server that stops reading counts as a dropped connection
+                stream.set_write_timeout(Some(IO_TIMEOUT))?;
+                Ok(Self::Tcp(stream))
+            }
+            Address::Udp(host) => {
+                let socket = UdpSocket::bind("0.0.0.0:0")?;
+                socket.connect(host)?;
+                Ok(Self::Udp(socket))
+            }
+        }
+    }
+
+    fn send(&mut self, data: &[u8]) -> std::io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.write_all(data),
+            Self::Udp(socket) => socket.send(data).map(drop),
+        }
+    }
+}
+
+type Feed = UniformSourceIterator<SourcesQueueOutput<f32>, i16>;
+
+/// Network output the player's sinks play into.
+pub struct NetworkOutput {
+    /// Samples of the newest sink, sent by the streaming thread
+    feed: Arc<Mutex<Option<Feed>>>,
+}
+
+impl NetworkOutput {
+    /// Start streaming to an address, connecting (and reconnecting) in the
+    /// background.
+    pub fn connect(address: &str) -> Result<Self> {
+        let address = Address::parse(address)?;
+        let feed = Arc::new(Mutex::new(None));
+        let thread_feed = Arc::clone(&feed);
+        std::thread::spawn(move || stream_audio(&address, thread_feed));
+        Ok(Self { feed })
+    }
+
+    /// Create a sink whose sound is streamed, replacing the previous one.
+    pub fn new_sink(&self) -> Sink {
+        let (sink, output) = Sink::new_idle();
+        *self.feed.lock().unwrap() =
+            Some(UniformSourceIterator::new(output, CHANNELS, SAMPLE_RATE));
+        sink
+    }
+}
+
+/// Send the feed's samples in real time, silence when there is no feed,
+/// until the output is dropped. Audio played while disconnected is lost.
+fn stream_audio(address: &Address, feed: Arc<Mutex<Option<Feed>>>) {
+    let samples = (SAMPLE_RATE as u128 * CHUNK.as_millis() / 1000) as usize * CHANNELS as usize;
+    let mut chunk = Vec::with_capacity(samples * 2);
+    let mut connection: Option<Connection> = None;
+    let mut retry_at = Instant::now();
+    let mut next_chunk = Instant::now();
+
+    while Arc::strong_count(&feed) > 1 {
+        chunk.clear();
+        {
+            let mut feed = feed.lock().unwrap();
+            for _ in 0..samples {
+                let sample = feed.as_mut().and_then(Iterator::next).unwrap_or(0);
+                chunk.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+
+        if connection.is_none() && Instant::now() >= retry_at {
+            match Connection::open(address) {
+                Ok(opened) => {
+                    tracing::info!("Streaming audio to {:?}", address);
+                    connection = Some(opened);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to connect to {:?}: {}", address, e);
+                    retry_at = Instant::now() + RECONNECT_DELAY;
+                }
+            }
+        }
+        if let Some(open) = &mut connection {
+            if let Err(e) = open.send(&chunk) {
+                tracing::warn!("Lost connection to {:?}: {}", address, e);
+                connection = None;
+                retry_at = Instant::now() + RECONNECT_DELAY;
+            }
+        }
+
+        // Keep to real time, starting over after falling far behind
+        next_chunk += CHUNK;
+        let now = Instant::now();
+        match next_chunk.checked_duration_since(now) {
+            Some(wait) => std::thread::sleep(wait),
+            None if now - next_chunk > RECONNECT_DELAY => next_chunk = now,
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            Address::parse("tcp://snapserver.local:4953").unwrap(),
+            Address::Tcp(String::from("snapserver.local:4953"))
+        );
+        assert_eq!(
+            Address::parse("udp://10.0.0.2:5000").unwrap(),
+            Address::Udp(String::from("10.0.0.2:5000"))
+        );
+        assert!(Address::parse("snapserver.local:4953").is_err());
+        assert!(Address::parse("tcp://").is_err());
+    }
+}
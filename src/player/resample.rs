@@ -0,0 +1,321 @@
+//! Sample rate conversion to the output's rate, done here rather than left
+//! to rodio, whose conversion is linear only.
+//!
+//! Sources reach the sink already at the output rate, so rodio passes them
+//! through untouched.
+
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use rodio::Source;
+
+use crate::config::ResamplerQuality;
+
+/// Input frames either side of the output position the sinc filter reads.
+const SINC_TAPS: usize = 16;
+
+/// Fractions of a frame between two input frames the filter weights are
+/// worked out at, ahead of time. Weights in between are interpolated.
+const PHASES: usize = 256;
+
+/// Rate new sources are resampled to and how, shared between the player
+/// and the sources it plays.
+#[derive(Debug)]
+pub struct Resampler {
+    quality: ResamplerQuality,
+
+    /// Output rate in Hz (0 = unknown, sources play at their own rate)
+    rate: AtomicU32,
+}
+
+impl Resampler {
+    pub fn new(quality: ResamplerQuality) -> Self {
+        Self {
+            quality,
+            rate: AtomicU32::new(0),
+        }
+    }
+
+    /// Set the rate of the output sources go to, when it's known.
+    pub fn set_rate(&self, rate: Option<u32>) {
+        self.rate.store(rate.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Resample a source to the output rate.
+    pub fn apply<S: Source<Item = i16>>(&self, source: S) -> Resample<S> {
+        let rate = self.rate.load(Ordering::Relaxed);
+        Resample::new(source, (rate > 0).then_some(rate), self.quality)
+    }
+}
+
+/// A source converted to another sample rate.
+pub struct Resample<S> {
+    source: S,
+    quality: ResamplerQuality,
+    channels: usize,
+
+    /// Output rate, `None` if it's the source's own
+    rate: Option<u32>,
+
+    /// Input rate
+    from: u32,
+
+    /// Filter weights at each of `PHASES + 1` fractions of a frame past the
+    /// output position, a row of `2 * taps` each
+    table: Vec<f64>,
+
+    /// Input samples around the output position, interleaved
+    window: VecDeque<f32>,
+
+    /// Position of the next output frame: whole frames into `window`, plus
+    /// `phase` over the output rate. Kept as whole numbers so the position
+    /// doesn't drift over a long track.
+    position: usize,
+    phase: u32,
+
+    /// Frame of `window` the source ended at, silence from there on
+    end: Option<usize>,
+
+    /// Filter weights of the frames around the position
+    weights: Vec<f64>,
+
+    /// Output frame being handed out, and the next sample of it
+    frame: Vec<i16>,
+    next: usize,
+}
+
+impl<S: Source<Item = i16>> Resample<S> {
+    pub fn new(source: S, rate: Option<u32>, quality: ResamplerQuality) -> Self {
+        let from = source.sample_rate();
+        let rate = rate.filter(|&rate| rate != from && from > 0);
+        let channels = source.channels().max(1) as usize;
+        // Keep below the lower rate's Nyquist frequency
+        let table = match rate {
+            Some(to) => weight_table(quality, (to as f64 / from as f64).min(1.0)),
+            None => Vec::new(),
+        };
+        // Silence before the start, so the first output frame lines up with
+        // the first input frame
+        let lead = taps(quality) - 1;
+        Self {
+            source,
+            quality,
+            channels,
+            rate,
+            from,
+            table,
+            window: VecDeque::from(vec![0.0; lead * channels]),
+            position: lead,
+            phase: 0,
+            end: None,
+            weights: Vec::with_capacity(2 * taps(quality)),
+            frame: Vec::with_capacity(channels),
+            next: 0,
+        }
+    }
+
+    fn frames(&self) -> usize {
+        self.window.len() / self.channels
+    }
+
+    /// Add the source's next frame to the window, or silence once it's over.
+    fn read_frame(&mut self) {
+        for _ in 0..self.channels {
+            let sample = match self.end {
+                Some(_) => None,
+                None => self.source.next(),
+            };
+            if sample.is_none() && self.end.is_none() {
+                self.end = Some(self.frames());
+            }
+            self.window
+                .push_back(sample.map_or(0.0, |s| s as f32 / 32768.0));
+        }
+    }
+
+    /// Work out the next output frame, or `None` past the end of the source.
+    fn resample_frame(&mut self) -> Option<()> {
+        let taps = taps(self.quality);
+        let to = self.rate?;
+        let center = self.position;
+        while self.frames() <= center + taps {
+            self.read_frame();
+        }
+        if self.end.is_some_and(|end| center >= end) {
+            return None;
+        }
+
+        let first = center + 1 - taps;
+        // Between the table's two rows either side of the position
+        let phase = self.phase as f64 / to as f64 * PHASES as f64;
+        let row = (phase as usize).min(PHASES - 1);
+        let blend = phase - row as f64;
+        let width = 2 * taps;
+        let (below, above) = self.table[row * width..(row + 2) * width].split_at(width);
+        self.weights.clear();
+        self.weights
+            .extend(below.iter().zip(above).map(|(a, b)| a + (b - a) * blend));
+        let total: f64 = self.weights.iter().sum();
+        self.frame.clear();
+        for channel in 0..self.channels {
+            let sum: f64 = self
+                .weights
+                .iter()
+                .enumerate()
+                .map(|(k, w)| w * self.window[(first + k) * self.channels + channel] as f64)
+                .sum();
+            let level = if total == 0.0 { 0.0 } else { sum / total };
+            self.frame
+                .push((level * 32768.0).clamp(-32768.0, 32767.0) as i16);
+        }
+
+        // Move on, dropping frames the filter no longer reaches
+        let phase = self.phase as u64 + self.from as u64;
+        self.position += (phase / to as u64) as usize;
+        self.phase = (phase % to as u64) as u32;
+        let done = (self.position + 1 - taps).min(self.frames());
+        self.window.drain(..done * self.channels);
+        self.position -= done;
+        self.end = self.end.map(|end| end.saturating_sub(done));
+        Some(())
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for Resample<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if self.rate.is_none() {
+            return self.source.next();
+        }
+        if self.next >= self.frame.len() {
+            self.resample_frame()?;
+            self.next = 0;
+        }
+        self.next += 1;
+        Some(self.frame[self.next - 1])
+    }
+}
+
+impl<S: Source<Item = i16>> Source for Resample<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        match self.rate {
+            Some(_) => None,
+            None => self.source.current_frame_len(),
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.rate.unwrap_or_else(|| self.source.sample_rate())
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+/// Input frames either side of the output position a filter reads.
+fn taps(quality: ResamplerQuality) -> usize {
+    match quality {
+        ResamplerQuality::Fast => 1,
+        ResamplerQuality::Balanced => 2,
+        ResamplerQuality::Best => SINC_TAPS,
+    }
+}
+
+/// Filter weights of the frames around the output position, at each of
+/// `PHASES + 1` evenly spaced fractions of a frame past it.
+fn weight_table(quality: ResamplerQuality, cutoff: f64) -> Vec<f64> {
+    let taps = taps(quality);
+    (0..=PHASES)
+        .flat_map(|phase| {
+            let fraction = phase as f64 / PHASES as f64;
+            (0..2 * taps)
+                .map(move |k| weight(quality, (taps - 1) as f64 - k as f64 + fraction, cutoff))
+        })
+        .collect()
+}
+
+/// Weight of the input frame `x` frames from the output position.
+fn weight(quality: ResamplerQuality, x: f64, cutoff: f64) -> f64 {
+    let x = x.abs();
+    match quality {
+        // Linear
+        ResamplerQuality::Fast => (1.0 - x).max(0.0),
+        // Catmull-Rom cubic
+        ResamplerQuality::Balanced if x < 1.0 => (1.5 * x - 2.5) * x * x + 1.0,
+        ResamplerQuality::Balanced if x < 2.0 => ((-0.5 * x + 2.5) * x - 4.0) * x + 2.0,
+        ResamplerQuality::Balanced => 0.0,
+        // Low-pass sinc in a Blackman window
+        ResamplerQuality::Best if x < SINC_TAPS as f64 => {
+            let t = PI * x / SINC_TAPS as f64;
+            let window = 0.42 + 0.5 * t.cos() + 0.08 * (2.0 * t).cos();
+            cutoff * sinc(cutoff * x) * window
+        }
+        ResamplerQuality::Best => 0.0,
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn test_resample() {
+        let steady = || SamplesBuffer::new(2, 44_100, vec![8000i16; 2 * 4410]);
+
+        let same = Resample::new(steady(), Some(44_100), ResamplerQuality::Best);
+        assert_eq!(same.sample_rate(), 44_100);
+        assert_eq!(same.count(), 2 * 4410);
+
+        for quality in [
+            ResamplerQuality::Fast,
+            ResamplerQuality::Balanced,
+            ResamplerQuality::Best,
+        ] {
+            let source = Resample::new(steady(), Some(48_000), quality);
+            assert_eq!(source.sample_rate(), 48_000);
+            let samples: Vec<i16> = source.collect();
+            assert_eq!(samples.len(), 2 * 4800);
+            // A steady level stays steady away from the edges
+            let middle = &samples[2 * 100..2 * 4700];
+            assert!(middle.iter().all(|&s| (s - 8000).abs() <= 1), "{quality:?}");
+        }
+    }
+
+    #[test]
+    fn test_resample_tone() {
+        // A 1 kHz tone comes out as the same tone at the new rate
+        let tone = |rate: u32, i: usize| (2.0 * PI * 1000.0 * i as f64 / rate as f64).sin();
+        let samples: Vec<i16> = (0..4410)
+            .map(|i| (tone(44_100, i) * 16000.0) as i16)
+            .collect();
+        let source = Resample::new(
+            SamplesBuffer::new(1, 44_100, samples),
+            Some(48_000),
+            ResamplerQuality::Best,
+        );
+        let resampled: Vec<i16> = source.collect();
+        for (i, &s) in resampled.iter().enumerate().take(4700).skip(100) {
+            assert!(
+                (s as f64 - tone(48_000, i) * 16000.0).abs() < 20.0,
+                "{i}: {s}"
+            );
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Skipping long silences, such as dead air in podcasts or the gaps
+//! between songs on live albums.
+//!
+//! A silence plays for a while as it is, so songs still get a pause
+//! between them, and the rest of it is skipped.
+//!
+//! Samples are pulled from the audio output's callback, so skipping can't
+//! decode through minutes of dead air at once: each frame played skips at
+//! most [`MAX_SKIP_FRAMES`], and a long silence passes quickly rather than
+//! instantly.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::Source;
+
+/// Most frames of silence skipped for each frame played.
+const MAX_SKIP_FRAMES: u64 = 64;
+
+/// When a silence is skipped, shared between the player and the source
+/// playing.
+#[derive(Debug)]
+pub struct Silence {
+    /// Loudest sample counted as silent
+    threshold: u16,
+
+    /// Silence played before the rest is skipped
+    grace: Duration,
+
+    /// Track time skipped and not yet taken by the player, in milliseconds
+    skipped_ms: AtomicU64,
+}
+
+impl Silence {
+    /// Skip silences quieter than `threshold_db` (relative to full scale)
+    /// once they've lasted `grace`.
+    pub fn new(threshold_db: i32, grace: Duration) -> Self {
+        let level = 10f32.powf(threshold_db.min(0) as f32 / 20.0);
+        Self {
+            threshold: (level * i16::MAX as f32) as u16,
+            grace,
+            skipped_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Track time skipped since last asked.
+    pub fn take_skipped(&self) -> Duration {
+        Duration::from_millis(self.skipped_ms.swap(0, Ordering::Relaxed))
+    }
+}
+
+/// A source with long silences cut short, if given a [`Silence`].
+pub struct SkipSilence<S> {
+    source: S,
+    silence: Option<Arc<Silence>>,
+    channels: usize,
+
+    /// Frames of silence allowed to play
+    grace_frames: u64,
+
+    /// Frames of silence in a row so far
+    quiet_frames: u64,
+
+    /// Frames skipped and not yet counted in whole milliseconds, times 1000
+    skipped_units: u64,
+
+    /// Frame being handed out, and the next sample of it
+    frame: Vec<i16>,
+    next: usize,
+}
+
+impl<S: Source<Item = i16>> SkipSilence<S> {
+    pub fn new(source: S, silence: Option<Arc<Silence>>) -> Self {
+        let channels = source.channels().max(1) as usize;
+        let grace_frames = silence.as_ref().map_or(0, |silence| {
+            (silence.grace.as_secs_f64() * source.sample_rate() as f64) as u64
+        });
+        Self {
+            source,
+            silence,
+            channels,
+            grace_frames,
+            quiet_frames: 0,
+            skipped_units: 0,
+            frame: Vec::with_capacity(channels),
+            next: 0,
+        }
+    }
+
+    /// Read the next frame to play, skipping silence past the grace period,
+    /// up to [`MAX_SKIP_FRAMES`] of it. Returns `None` at the end of the
+    /// source.
+    fn read_frame(&mut self, threshold: u16) -> Option<()> {
+        let mut skipped = 0u64;
+        let result = loop {
+            self.frame.clear();
+            self.frame.extend(self.source.by_ref().take(self.channels));
+            if self.frame.is_empty() {
+                break None;
+            }
+            if self.frame.iter().any(|s| s.unsigned_abs() > threshold) {
+                self.quiet_frames = 0;
+                break Some(());
+            }
+            self.quiet_frames += 1;
+            if self.quiet_frames <= self.grace_frames || skipped == MAX_SKIP_FRAMES {
+                break Some(());
+            }
+            skipped += 1;
+        };
+
+        if skipped > 0 {
+            if let Some(silence) = &self.silence {
+                let rate = self.source.sample_rate().max(1) as u64;
+                self.skipped_units += skipped * 1000;
+                silence
+                    .skipped_ms
+                    .fetch_add(self.skipped_units / rate, Ordering::Relaxed);
+                self.skipped_units %= rate;
+            }
+        }
+        result
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for SkipSilence<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let Some(threshold) = self.silence.as_ref().map(|silence| silence.threshold) else {
+            return self.source.next();
+        };
+        if self.next >= self.frame.len() {
+            self.read_frame(threshold)?;
+            self.next = 0;
+        }
+        self.next += 1;
+        Some(self.frame[self.next - 1])
+    }
+}
+
+impl<S: Source<Item = i16>> Source for SkipSilence<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        match self.silence {
+            Some(_) => None,
+            None => self.source.current_frame_len(),
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn test_skip_silence() {
+        // 1s of sound, 5s of silence, 1s of sound, in mono at 1 kHz
+        let mut samples = vec![5000i16; 1000];
+        samples.extend([3; 5000]);
+        samples.extend([-5000; 1000]);
+        let silence = Arc::new(Silence::new(-50, Duration::from_secs(2)));
+
+        let source = SamplesBuffer::new(1, 1000, samples.clone());
+        let played: Vec<i16> = SkipSilence::new(source, Some(Arc::clone(&silence))).collect();
+        // The last 3s of silence go by a frame played per MAX_SKIP_FRAMES skipped
+        let kept = 3000 / (MAX_SKIP_FRAMES as usize + 1);
+        assert_eq!(played.len(), 4000 + kept);
+        assert_eq!(played[2999 + kept], 3);
+        assert_eq!(played[3000 + kept], -5000);
+        assert_eq!(
+            silence.take_skipped(),
+            Duration::from_millis(3000 - kept as u64)
+        );
+        assert_eq!(silence.take_skipped(), Duration::ZERO);
+
+        let source = SamplesBuffer::new(1, 1000, samples);
+        assert_eq!(SkipSilence::new(source, None).count(), 7000);
+    }
+}
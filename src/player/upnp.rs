@@ -0,0 +1,341 @@
+//! Casting to UPnP/DLNA media renderers (smart speakers, TVs, streamers) on
+//! the local network.
+//!
+//! Renderers are found with an SSDP search, then handed the track's stream
+//! URL and controlled over SOAP: they fetch and play the audio themselves.
+
+use std::net::UdpSocket;
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre::eyre;
+use color_eyre::Result;
+use reqwest::blocking::Client;
+use reqwest::Url;
+
+use crate::client::models::Song;
+
+/// SSDP multicast address devices listen for searches on.
+const SSDP_ADDR: &str = "239.255.255.250:1900";
+
+/// Device type searched for.
+const MEDIA_RENDERER: &str = "urn:schemas-upnp-org:device:MediaRenderer:1";
+
+/// Service playing, pausing and seeking.
+const AV_TRANSPORT: &str = "urn:schemas-upnp-org:service:AVTransport:1";
+
+/// Service setting the volume.
+const RENDERING_CONTROL: &str = "urn:schemas-upnp-org:service:RenderingControl:1";
+
+/// How long to wait for renderers to answer a search.
+const DISCOVERY_TIME: Duration = Duration::from_secs(2);
+
+/// A media renderer found on the network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Renderer {
+    /// Name the renderer goes by
+    pub name: String,
+
+    /// Control URL of its AVTransport service
+    av_transport: String,
+
+    /// Control URL of its RenderingControl service, if it has one
+    rendering_control: Option<String>,
+}
+
+impl Renderer {
+    /// Load a track, to be started with [`Renderer::play`].
+    pub fn set_uri(&self, client: &Client, url: &str, song: &Song) -> Result<()> {
+        let metadata = didl_metadata(url, song);
+        self.transport(
+            client,
+            "SetAVTransportURI",
+            &[("CurrentURI", url), ("CurrentURIMetaData", &metadata)],
+        )
+        .map(drop)
+    }
+
+    pub fn play(&self, client: &Client) -> Result<()> {
+        self.transport(client, "Play", &[("Speed", "1")]).map(drop)
+    }
+
+    pub fn pause(&self, client: &Client) -> Result<()> {
+        self.transport(client, "Pause", &[]).map(drop)
+    }
+
+    pub fn stop(&self, client: &Client) -> Result<()> {
+        self.transport(client, "Stop", &[]).map(drop)
+    }
+
+    pub fn seek(&self, client: &Client, position: Duration) -> Result<()> {
+        let target = format_time(position);
+        self.transport(client, "Seek", &[("Unit", "REL_TIME"), ("Target", &target)])
+            .map(drop)
+    }
+
+    /// Set the volume (0-100). Renderers without volume control are left
+    /// alone.
+    pub fn set_volume(&self, client: &Client, volume: u8) -> Result<()> {
+        let Some(control) = &self.rendering_control else {
+            return Ok(());
+        };
+        let volume = volume.min(100).to_string();
+        call(
+            client,
+            control,
+            RENDERING_CONTROL,
+            "SetVolume",
+            &[("Channel", "Master"), ("DesiredVolume", &volume)],
+        )
+        .map(drop)
+    }
+
+    /// Get the transport state, e.g. `PLAYING` or `STOPPED`.
+    pub fn state(&self, client: &Client) -> Result<String> {
+        let response = self.transport(client, "GetTransportInfo", &[])?;
+        tag_text(&response, "CurrentTransportState")
+            .ok_or_else(|| eyre!("Renderer did not report its state"))
+    }
+
+    /// Get the position in the current track.
+    pub fn position(&self, client: &Client) -> Result<Option<Duration>> {
+        let response = self.transport(client, "GetPositionInfo", &[])?;
+        Ok(tag_text(&response, "RelTime").and_then(|time| parse_time(&time)))
+    }
+
+    fn transport(&self, client: &Client, action: &str, args: &[(&str, &str)]) -> Result<String> {
+        call(client, &self.av_transport, AV_TRANSPORT, action, args)
+    }
+}
+
+/// Search the network for media renderers.
+pub fn discover() -> Result<Vec<Renderer>> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    let search = format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: {}\r\nMAN: \"ssdp:discover\"\r\nMX: {}\r\nST: {}\r\n\r\n",
+        SSDP_ADDR,
+        DISCOVERY_TIME.as_secs(),
+        MEDIA_RENDERER
+    );
+    socket.send_to(search.as_bytes(), SSDP_ADDR)?;
+
+    // Every renderer answering gives the URL of its description
+    let mut locations = Vec::new();
+    let deadline = Instant::now() + DISCOVERY_TIME;
+    let mut buf = [0; 2048];
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        socket.set_read_timeout(Some(remaining.max(Duration::from_millis(1))))?;
+        let Ok(len) = socket.recv(&mut buf) else {
+            break;
+        };
+        let reply = String::from_utf8_lossy(&buf[..len]);
+        if let Some(location) = header(&reply, "location") {
+            if !locations.contains(&location) {
+                locations.push(location);
+            }
+        }
+    }
+
+    let client = Client::builder().timeout(DISCOVERY_TIME).build()?;
+    let renderers = locations
+        .iter()
+        .filter_map(|location| {
+            let description = client.get(location).send().ok()?.text().ok()?;
+            parse_description(&description, &Url::parse(location).ok()?)
+        })
+        .collect();
+    Ok(renderers)
+}
+
+/// Get a header from an SSDP reply.
+fn header(reply: &str, name: &str) -> Option<String> {
+    reply.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().to_string())
+    })
+}
+
+/// Read a renderer's name and control URLs from its device description.
+fn parse_description(xml: &str, location: &Url) -> Option<Renderer> {
+    let base = tag_text(xml, "URLBase")
+        .and_then(|base| Url::parse(&base).ok())
+        .unwrap_or_else(|| location.clone());
+    let control_url = |service: &str| {
+        xml.split("<service>")
+            .skip(1)
+            .find(|block| tag_text(block, "serviceType").as_deref() == Some(service))
+            .and_then(|block| tag_text(block, "controlURL"))
+            .and_then(|url| base.join(&url).ok())
+            .map(String::from)
+    };
+    Some(Renderer {
+        name: tag_text(xml, "friendlyName").unwrap_or_else(|| location.to_string()),
+        av_transport: control_url(AV_TRANSPORT)?,
+        rendering_control: control_url(RENDERING_CONTROL),
+    })
+}
+
+/// Call an action of a renderer's service.
+fn call(
+    client: &Client,
+    control_url: &str,
+    service: &str,
+    action: &str,
+    args: &[(&str, &str)],
+) -> Result<String> {
+    let mut body = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+         <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+         s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+         <s:Body><u:{action} xmlns:u=\"{service}\"><InstanceID>0</InstanceID>"
+    );
+    for (name, value) in args {
+        body.push_str(&format!("<{name}>{}</{name}>", escape(value)));
+    }
+    body.push_str(&format!("</u:{action}></s:Body></s:Envelope>"));
+
+    let response = client
+        .post(control_url)
+        .header("Content-Type", "text/xml; charset=\"utf-8\"")
+        .header("SOAPAction", format!("\"{}#{}\"", service, action))
+        .body(body)
+        .send()?;
+    let status = response.status();
+    let text = response.text()?;
+    if !status.is_success() {
+        let reason = tag_text(&text, "errorDescription").unwrap_or_else(|| status.to_string());
+        return Err(eyre!("{} failed: {}", action, reason));
+    }
+    Ok(text)
+}
+
+/// Describe a track for the renderer's display, as DIDL-Lite.
+fn didl_metadata(url: &str, song: &Song) -> String {
+    let mime = song.content_type.as_deref().unwrap_or("audio/mpeg");
+    format!(
+        "<DIDL-Lite xmlns=\"urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/\" \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+         xmlns:upnp=\"urn:schemas-upnp-org:metadata-1-0/upnp/\">\
+         <item id=\"{}\" parentID=\"-1\" restricted=\"1\">\
+         <dc:title>{}</dc:title><upnp:artist>{}</upnp:artist><upnp:album>{}</upnp:album>\
+         <upnp:class>object.item.audioItem.musicTrack</upnp:class>\
+         <res protocolInfo=\"http-get:*:{}:*\">{}</res></item></DIDL-Lite>",
+        escape(&song.id),
+        escape(&song.title),
+        escape(song.display_artist()),
+        escape(song.album.as_deref().unwrap_or_default()),
+        escape(mime),
+        escape(url)
+    )
+}
+
+/// Get the text of the first element with a name, with or without a
+/// namespace prefix.
+fn tag_text(xml: &str, name: &str) -> Option<String> {
+    let mut rest = xml;
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open + 1..];
+        let end = rest.find('>')?;
+        let tag = &rest[..end];
+        let tag_name = tag.split_whitespace().next().unwrap_or_default();
+        let local = tag_name.rsplit(':').next().unwrap_or_default();
+        if local == name && !tag.ends_with('/') {
+            let content = &rest[end + 1..];
+            let close = content.find(&format!("</{}>", tag_name))?;
+            return Some(unescape(content[..close].trim()));
+        }
+        rest = &rest[end + 1..];
+    }
+    None
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Format a position as UPnP's `H:MM:SS`.
+fn format_time(position: Duration) -> String {
+    let secs = position.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Parse a UPnP `H:MM:SS` time, which may have a fraction of a second.
+fn parse_time(time: &str) -> Option<Duration> {
+    let mut parts = time.split(':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+    Some(Duration::from_secs(hours * 3600 + minutes * 60) + Duration::from_secs_f64(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_description() {
+        let location = Url::parse("http://192.168.1.20:49152/description.xml").unwrap();
+        let xml = r#"<?xml version="1.0"?>
+            <root xmlns="urn:schemas-upnp-org:device-1-0">
+              <device>
+                <friendlyName>Living Room &amp; Kitchen</friendlyName>
+                <serviceList>
+                  <service>
+                    <serviceType>urn:schemas-upnp-org:service:RenderingControl:1</serviceType>
+                    <controlURL>/upnp/control/rendercontrol1</controlURL>
+                  </service>
+                  <service>
+                    <serviceType>urn:schemas-upnp-org:service:AVTransport:1</serviceType>
+                    <controlURL>upnp/control/avtransport1</controlURL>
+                  </service>
+                </serviceList>
+              </device>
+            </root>"#;
+        let renderer = parse_description(xml, &location).unwrap();
+        assert_eq!(renderer.name, "Living Room & Kitchen");
+        assert_eq!(
+            renderer.av_transport,
+            "http://192.168.1.20:49152/upnp/control/avtransport1"
+        );
+        assert_eq!(
+            renderer.rendering_control.as_deref(),
+            Some("http://192.168.1.20:49152/upnp/control/rendercontrol1")
+        );
+        assert_eq!(parse_description("<root></root>", &location), None);
+
+        let reply = "HTTP/1.1 200 OK\r\nLOCATION: http://10.0.0.5/desc.xml\r\n\r\n";
+        assert_eq!(
+            header(reply, "location").as_deref(),
+            Some("http://10.0.0.5/desc.xml")
+        );
+        let response = "<s:Body><u:GetTransportInfoResponse>\
+            <CurrentTransportState>PLAYING</CurrentTransportState>";
+        assert_eq!(
+            tag_text(response, "CurrentTransportState").as_deref(),
+            Some("PLAYING")
+        );
+    }
+
+    #[test]
+    fn test_time() {
+        assert_eq!(format_time(Duration::from_secs(3725)), "1:02:05");
+        assert_eq!(parse_time("1:02:05"), Some(Duration::from_secs(3725)));
+        assert_eq!(parse_time("0:00:01.500"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_time("NOT_IMPLEMENTED"), None);
+    }
+}
@@ -0,0 +1,257 @@
+//! Spectrum of the audio being played, for the visualizer.
+//!
+//! Decoded samples pass through a [`Tap`] on their way to the output, which
+//! keeps the most recent ones (mixed down to mono) in a shared [`SampleTap`].
+//! The UI takes a spectrum of those each frame with a small radix-2 FFT,
+//! reaching back by the output latency so the bars match what is heard.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rodio::Source;
+
+/// Samples the spectrum is taken over (a power of two).
+pub const FFT_SIZE: usize = 1024;
+
+/// Samples a [`Tap`] collects before handing them over, to keep locking rare.
+const FLUSH_SAMPLES: usize = 256;
+
+/// Lowest frequency shown, in Hz.
+const MIN_FREQ: f32 = 50.0;
+
+/// Highest frequency shown, in Hz.
+const MAX_FREQ: f32 = 16_000.0;
+
+/// Range of levels shown, in dB below full scale.
+const DB_RANGE: f32 = 60.0;
+
+/// The most recent samples played, shared between the player and the UI.
+#[derive(Debug, Default)]
+pub struct SampleTap {
+    inner: Mutex<TapBuffer>,
+}
+
+#[derive(Debug, Default)]
+struct TapBuffer {
+    samples: VecDeque<f32>,
+    sample_rate: u32,
+
+    /// How far behind the newest sample the spectrum is taken
+    delay: Duration,
+}
+
+impl TapBuffer {
+    /// Number of samples the delay spans at the current sample rate.
+    fn delay_samples(&self) -> usize {
+        (self.delay.as_secs_f64() * self.sample_rate as f64) as usize
+    }
+}
+
+impl SampleTap {
+    fn push(&self, samples: &[f32], sample_rate: u32) {
+        let mut buffer = self.inner.lock().unwrap();
+        buffer.sample_rate = sample_rate;
+        buffer.samples.extend(samples);
+        let keep = FFT_SIZE + buffer.delay_samples();
+        let excess = buffer.samples.len().saturating_sub(keep);
+        buffer.samples.drain(..excess);
+    }
+
+    /// Get `bars` levels (0.0 to 1.0) from low to high frequencies, of the
+    /// samples played `delay` ago.
+    pub fn spectrum(&self, bars: usize, delay: Duration) -> Vec<f32> {
+        let mut buffer = self.inner.lock().unwrap();
+        buffer.delay = delay;
+        let end = buffer.samples.len().saturating_sub(buffer.delay_samples());
+        let samples: Vec<f32> = buffer.samples.range(..end).copied().collect();
+        spectrum(&samples, buffer.sample_rate, bars)
+    }
+}
+
+/// A source that copies the samples it passes on into a [`SampleTap`].
+pub struct Tap<S> {
+    source: S,
+    tap: Arc<SampleTap>,
+    pending: Vec<f32>,
+    frame_sum: f32,
+    frame_channel: u16,
+}
+
+impl<S> Tap<S> {
+    pub fn new(source: S, tap: Arc<SampleTap>) -> Self {
+        Self {
+            source,
+            tap,
+            pending: Vec::with_capacity(FLUSH_SAMPLES),
+            frame_sum: 0.0,
+            frame_channel: 0,
+        }
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for Tap<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.source.next()?;
+
+        // Mix each frame down to mono
+        let channels = self.source.channels().max(1);
+        self.frame_sum += sample as f32 / 32768.0;
+        self.frame_channel += 1;
+        if self.frame_channel >= channels {
+            self.pending.push(self.frame_sum / channels as f32);
+            self.frame_sum = 0.0;
+            self.frame_channel = 0;
+        }
+
+        if self.pending.len() >= FLUSH_SAMPLES {
+            self.tap.push(&self.pending, self.source.sample_rate());
+            self.pending.clear();
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source<Item = i16>> Source for Tap<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+/// Get `bars` levels (0.0 to 1.0) over log-spaced frequency bands of the last
+/// [`FFT_SIZE`] samples. All zero until enough samples have been played.
+pub fn spectrum(samples: &[f32], sample_rate: u32, bars: usize) -> Vec<f32> {
+    if bars == 0 || samples.len() < FFT_SIZE || sample_rate == 0 {
+        return vec![0.0; bars];
+    }
+
+    // Hann window over the most recent samples
+    let samples = &samples[samples.len() - FFT_SIZE..];
+    let mut re: Vec<f32> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let w = 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / FFT_SIZE as f32).cos();
+            s * w
+        })
+        .collect();
+    let mut im = vec![0.0; FFT_SIZE];
+    fft(&mut re, &mut im);
+
+    let bin_hz = sample_rate as f32 / FFT_SIZE as f32;
+    let max_freq = MAX_FREQ.min(sample_rate as f32 / 2.0);
+    // A full-scale sine peaks at a quarter of the window size with a Hann window
+    let full_scale = FFT_SIZE as f32 / 4.0;
+
+    (0..bars)
+        .map(|bar| {
+            let band = |i: usize| MIN_FREQ * (max_freq / MIN_FREQ).powf(i as f32 / bars as f32);
+            let lo = ((band(bar) / bin_hz) as usize).max(1);
+            let hi = ((band(bar + 1) / bin_hz) as usize).clamp(lo, FFT_SIZE / 2 - 1);
+            let peak = (lo..=hi)
+                .map(|k| (re[k] * re[k] + im[k] * im[k]).sqrt())
+                .fold(0.0, f32::max);
+            let db = 20.0 * (peak / full_scale).max(1e-9).log10();
+            ((db + DB_RANGE) / DB_RANGE).clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+/// In-place iterative radix-2 FFT. The length must be a power of two.
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -std::f32::consts::TAU / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spectrum_peaks_at_tone() {
+        let rate = 44_100;
+        let tone: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| (std::f32::consts::TAU * 1000.0 * i as f32 / rate as f32).sin())
+            .collect();
+
+        let bars = spectrum(&tone, rate, 16);
+        let loudest = (0..bars.len())
+            .max_by(|&a, &b| bars[a].total_cmp(&bars[b]))
+            .unwrap();
+
+        // 1 kHz falls in band 8 of 16 log-spaced bands between 50 Hz and 16 kHz
+        assert_eq!(loudest, 8);
+        assert!(bars[loudest] > 0.9);
+        assert!(bars[0] < 0.5 && bars[15] < 0.5);
+
+        assert_eq!(spectrum(&tone[..100], rate, 4), vec![0.0; 4]);
+    }
+
+    #[test]
+    fn test_spectrum_delayed() {
+        let rate = 1000;
+        let tone: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| (std::f32::consts::TAU * 100.0 * i as f32 / rate as f32).sin())
+            .collect();
+        let silence = [0.0; FFT_SIZE];
+        let delay = Duration::from_millis(FFT_SIZE as u64);
+        let tap = SampleTap::default();
+
+        // Until the delay is known, only the newest samples are kept
+        tap.push(&tone, rate);
+        tap.push(&silence, rate);
+        assert!(tap.spectrum(4, delay).iter().all(|&l| l == 0.0));
+
+        // The tone is still heard while the speakers play it late
+        tap.push(&tone, rate);
+        tap.push(&silence, rate);
+        assert!(tap.spectrum(4, delay).iter().any(|&l| l > 0.9));
+        assert!(tap.spectrum(4, Duration::ZERO).iter().all(|&l| l < 0.5));
+    }
+}
@@ -0,0 +1,118 @@
+//! Shuffling, optionally weighted so well-liked songs come up sooner: those
+//! rated higher, played more, or starred.
+
+use rand::Rng;
+
+use crate::client::models::Song;
+use crate::config::{ShuffleConfig, ShuffleCurve};
+
+/// Shuffle songs in place.
+pub fn shuffle(songs: &mut Vec<Song>, config: &ShuffleConfig) {
+    let order = order(&songs.iter().collect::<Vec<_>>(), config);
+    let mut taken: Vec<Option<Song>> = songs.drain(..).map(Some).collect();
+    songs.extend(order.into_iter().filter_map(|i| taken[i].take()));
+}
+
+/// A shuffled order of songs, as indices into `songs`.
+pub fn order(songs: &[&Song], config: &ShuffleConfig) -> Vec<usize> {
+    order_with(songs, config, &mut rand::thread_rng())
+}
+
+fn order_with(songs: &[&Song], config: &ShuffleConfig, rng: &mut impl Rng) -> Vec<usize> {
+    let most_plays = songs
+        .iter()
+        .filter_map(|song| song.play_count)
+        .max()
+        .unwrap_or(0);
+
+    // Weighted sampling without replacement (Efraimidis–Spirakis): each
+    // song draws a key, with heavier songs likely to draw smaller ones
+    let mut keys: Vec<(f64, usize)> = songs
+        .iter()
+        .enumerate()
+        .map(|(i, song)| {
+            let weight = if config.weighted {
+                weight(score(song, most_plays), config)
+            } else {
+                1.0
+            };
+            let draw = 1.0 - rng.gen::<f64>();
+            (-draw.ln() / weight, i)
+        })
+        .collect();
+    keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+    keys.into_iter().map(|(_, i)| i).collect()
+}
+
+/// How well-liked a song is, from 0.0 to 1.0: its rating (unrated counts
+/// as middling), plays compared to the most played of the songs, and
+/// being starred, counted equally.
+fn score(song: &Song, most_plays: i64) -> f64 {
+    let rating = song
+        .user_rating
+        .map_or(0.5, |rating| (rating.clamp(1, 5) - 1) as f64 / 4.0);
+    let plays = match song.play_count {
+        Some(plays) if most_plays > 0 => {
+            (1.0 + plays.max(0) as f64).ln() / (1.0 + most_plays as f64).ln()
+        }
+        _ => 0.0,
+    };
+    let starred = if song.starred.is_some() { 1.0 } else { 0.0 };
+    (rating + plays + starred) / 3.0
+}
+
+/// Chance of a song coming up, relative to the least-liked.
+fn weight(score: f64, config: &ShuffleConfig) -> f64 {
+    let favor = config.favor.max(1) as f64;
+    match config.curve {
+        ShuffleCurve::Linear => 1.0 + (favor - 1.0) * score,
+        ShuffleCurve::Exponential => favor.powf(score),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn song(value: serde_json::Value) -> Song {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_weighted_order() {
+        let songs = [
+            song(serde_json::json!({ "id": "a", "title": "A", "userRating": 1, "playCount": 0 })),
+            song(serde_json::json!({ "id": "b", "title": "B" })),
+            song(serde_json::json!({
+                "id": "c",
+                "title": "C",
+                "userRating": 5,
+                "playCount": 40,
+                "starred": "2024-01-01T00:00:00Z",
+            })),
+            song(serde_json::json!({ "id": "d", "title": "D", "userRating": 2, "playCount": 3 })),
+        ];
+        let songs: Vec<&Song> = songs.iter().collect();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut config = ShuffleConfig::default();
+
+        let mut order = order_with(&songs, &config, &mut rng);
+        order.sort_unstable();
+        assert_eq!(order, [0, 1, 2, 3]);
+
+        assert_eq!(score(songs[0], 40), 0.0);
+        assert_eq!(score(songs[2], 40), 1.0);
+
+        config.weighted = true;
+        config.favor = 50;
+        let firsts = (0..200)
+            .filter(|_| order_with(&songs, &config, &mut rng)[0] == 2)
+            .count();
+        assert!(firsts > 120, "favorite came first {firsts} times");
+
+        config.curve = ShuffleCurve::Linear;
+        assert_eq!(weight(0.5, &config), 25.5);
+    }
+}
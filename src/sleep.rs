@@ -0,0 +1,71 @@
+//! Pausing around system sleep, through logind on Linux.
+//!
+//! A delay inhibitor holds off suspend until playback is paused; on waking,
+//! the app checks the server and reopens the stream before playing on.
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::sync::mpsc;
+use zbus::zvariant::OwnedFd;
+
+use crate::action::Action;
+
+/// Time given to pause, beyond the fade out, before letting the system sleep.
+const PAUSE_MARGIN: Duration = Duration::from_millis(500);
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait LoginManager {
+    /// Hold off `what` (e.g. "sleep") until the returned descriptor is closed.
+    fn inhibit(&self, what: &str, who: &str, why: &str, mode: &str) -> zbus::Result<OwnedFd>;
+
+    /// Sent with `start` true before sleeping and false after waking.
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+}
+
+/// Watch for the system going to sleep and waking in the background,
+/// sending `Action::Sleeping` and `Action::Woke`. Sleep is held off for
+/// `fade`, the pause's fade out, and a margin. Does nothing on systems
+/// without logind.
+pub fn watch(action_tx: mpsc::UnboundedSender<Action>, fade: Duration) {
+    tokio::spawn(async move {
+        if let Err(e) = run(action_tx, fade + PAUSE_MARGIN).await {
+            tracing::info!("Not watching for system sleep: {}", e);
+        }
+    });
+}
+
+async fn run(action_tx: mpsc::UnboundedSender<Action>, grace: Duration) -> zbus::Result<()> {
+    let connection = zbus::Connection::system().await?;
+    let manager = LoginManagerProxy::new(&connection).await?;
+    let mut signals = manager.receive_prepare_for_sleep().await?;
+    let inhibit = || {
+        manager.inhibit(
+            "sleep",
+            "subsonic-tui",
+            "Pause playback before sleeping",
+            "delay",
+        )
+    };
+    let mut inhibitor = Some(inhibit().await?);
+
+    while let Some(signal) = signals.next().await {
+        if *signal.args()?.start() {
+            let _ = action_tx.send(Action::Sleeping);
+            tokio::time::sleep(grace).await;
+            // Closing the descriptor lets the system go to sleep
+            inhibitor = None;
+        } else {
+            let _ = action_tx.send(Action::Woke);
+            if inhibitor.is_none() {
+                inhibitor = inhibit().await.ok();
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,384 @@
+//! Smart playlists: songs picked by rules in the config, such as
+//! `genre = Jazz AND year >= 1990 AND starred`, matched against a local copy
+//! of the library's song list.
+//!
+//! They're listed in the Playlists tab with the server's playlists, under
+//! IDs of their own so they're never sent to the server.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use color_eyre::Result;
+
+use crate::client::models::{Playlist, Song};
+use crate::config::{Config, SmartPlaylistConfig};
+
+/// Start of the IDs smart playlists are listed under.
+const ID_PREFIX: &str = "smart:";
+
+/// How long the library's song list kept on disk is used before it's
+/// fetched again, to pick up songs added since.
+const LIBRARY_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A song property rules compare.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Title,
+    Artist,
+    Album,
+    Genre,
+    Year,
+    Rating,
+    PlayCount,
+    /// Length in seconds
+    Duration,
+    Starred,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "title" => Self::Title,
+            "artist" => Self::Artist,
+            "album" => Self::Album,
+            "genre" => Self::Genre,
+            "year" => Self::Year,
+            "rating" => Self::Rating,
+            "play_count" | "plays" => Self::PlayCount,
+            "duration" => Self::Duration,
+            "starred" => Self::Starred,
+            _ => return None,
+        })
+    }
+
+    /// Get a numeric property of a song; unknown numbers count as 0.
+    fn number(self, song: &Song) -> i64 {
+        match self {
+            Self::Year => song.year.unwrap_or(0).into(),
+            Self::Rating => song.user_rating.unwrap_or(0).into(),
+            Self::PlayCount => song.play_count.unwrap_or(0),
+            Self::Duration => song.duration.unwrap_or(0).into(),
+            Self::Starred => song.starred.is_some().into(),
+            Self::Title | Self::Artist | Self::Album | Self::Genre => 0,
+        }
+    }
+
+    /// Get a text property of a song. Songs can have several genres.
+    fn texts(self, song: &Song) -> Vec<&str> {
+        match self {
+            Self::Title => vec![&song.title],
+            Self::Artist => song.artist.as_deref().into_iter().collect(),
+            Self::Album => song.album.as_deref().into_iter().collect(),
+            Self::Genre => song
+                .genre
+                .as_deref()
+                .into_iter()
+                .chain(song.genres.iter().map(|genre| genre.name.as_str()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn is_text(self) -> bool {
+        matches!(self, Self::Title | Self::Artist | Self::Album | Self::Genre)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Text containing the value
+    Contains,
+}
+
+/// Operators, longest first so `>=` isn't read as `>`.
+const OPS: [(&str, Op); 7] = [
+    (">=", Op::Ge),
+    ("<=", Op::Le),
+    ("!=", Op::Ne),
+    ("=", Op::Eq),
+    (">", Op::Gt),
+    ("<", Op::Lt),
+    ("~", Op::Contains),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Text(String),
+    Number(i64),
+}
+
+/// One comparison of a rule.
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    field: Field,
+    op: Op,
+    value: Value,
+}
+
+impl Condition {
+    fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let Some((at, symbol, op)) = OPS
+            .iter()
+            .filter_map(|&(symbol, op)| Some((text.find(symbol)?, symbol, op)))
+            .min_by_key(|&(at, symbol, _)| (at, usize::MAX - symbol.len()))
+        else {
+            // A bare field is a yes/no check, like `starred`
+            return match Field::parse(text) {
+                Some(Field::Starred) => Ok(Self {
+                    field: Field::Starred,
+                    op: Op::Eq,
+                    value: Value::Number(1),
+                }),
+                _ => Err(format!("'{}' is not a comparison", text)),
+            };
+        };
+
+        let name = text[..at].trim();
+        let field = Field::parse(name).ok_or_else(|| format!("Unknown field '{}'", name))?;
+        let raw = text[at + symbol.len()..].trim().trim_matches('"');
+        let value = if field.is_text() {
+            if !matches!(op, Op::Eq | Op::Ne | Op::Contains) {
+                return Err(format!("'{}' can't compare {}", symbol, name));
+            }
+            Value::Text(raw.to_lowercase())
+        } else if field == Field::Starred {
+            match raw.to_lowercase().as_str() {
+                "true" | "yes" => Value::Number(1),
+                "false" | "no" => Value::Number(0),
+                _ => return Err(format!("starred must be true or false, not '{}'", raw)),
+            }
+        } else {
+            Value::Number(
+                raw.parse()
+                    .map_err(|_| format!("{} needs a number, not '{}'", name, raw))?,
+            )
+        };
+        if op == Op::Contains && !field.is_text() {
+            return Err(format!("'~' can't compare {}", name));
+        }
+        Ok(Self { field, op, value })
+    }
+
+    fn matches(&self, song: &Song) -> bool {
+        match &self.value {
+            Value::Text(value) => {
+                let texts = self.field.texts(song);
+                let hit = texts.iter().any(|text| {
+                    let text = text.to_lowercase();
+                    match self.op {
+                        Op::Contains => text.contains(value.as_str()),
+                        _ => text == *value,
+                    }
+                });
+                hit != (self.op == Op::Ne)
+            }
+            Value::Number(value) => {
+                let number = self.field.number(song);
+                match self.op {
+                    Op::Eq => number == *value,
+                    Op::Ne => number != *value,
+                    Op::Lt => number < *value,
+                    Op::Le => number <= *value,
+                    Op::Gt => number > *value,
+                    Op::Ge => number >= *value,
+                    Op::Contains => false,
+                }
+            }
+        }
+    }
+}
+
+/// Conditions a song must all meet, joined by `AND`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rules(Vec<Condition>);
+
+impl Rules {
+    pub fn parse(rules: &str) -> Result<Self, String> {
+        let conditions = split_and(rules)
+            .into_iter()
+            .map(Condition::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if conditions.is_empty() {
+            return Err(String::from("No rules given"));
+        }
+        Ok(Self(conditions))
+    }
+
+    pub fn matches(&self, song: &Song) -> bool {
+        self.0.iter().all(|condition| condition.matches(song))
+    }
+}
+
+/// Split rules at each `AND` outside quotes, in any case.
+fn split_and(rules: &str) -> Vec<&str> {
+    const AND: &str = " AND ";
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in rules.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if !quoted
+            && i >= start
+            && rules
+                .get(i..i + AND.len())
+                .is_some_and(|word| word.eq_ignore_ascii_case(AND))
+        {
+            parts.push(&rules[start..i]);
+            start = i + AND.len();
+        }
+    }
+    parts.push(&rules[start..]);
+    parts.retain(|part| !part.trim().is_empty());
+    parts
+}
+
+/// Get the ID a smart playlist is listed under.
+fn playlist_id(index: usize) -> String {
+    format!("{}{}", ID_PREFIX, index)
+}
+
+/// Get which smart playlist an ID is, if it is one.
+pub fn index(id: &str) -> Option<usize> {
+    id.strip_prefix(ID_PREFIX)?.parse().ok()
+}
+
+/// Check whether a playlist is a smart playlist rather than the server's.
+pub fn is_smart(id: &str) -> bool {
+    index(id).is_some()
+}
+
+/// Build the Playlists tab entry of a smart playlist.
+fn entry(index: usize, config: &SmartPlaylistConfig, songs: Option<&[Song]>) -> Playlist {
+    Playlist {
+        id: playlist_id(index),
+        name: config.name.clone(),
+        comment: Some(config.rules.clone()),
+        owner: Some(String::from("smart playlist")),
+        public: None,
+        song_count: songs.map(|songs| songs.len() as i32),
+        duration: songs.map(|songs| songs.iter().filter_map(|song| song.duration).sum()),
+        created: None,
+        changed: None,
+        cover_art: songs
+            .and_then(|songs| songs.first())
+            .and_then(|song| song.cover_art.clone()),
+    }
+}
+
+/// Get the Playlists tab entries of the smart playlists in the config.
+pub fn entries(configs: &[SmartPlaylistConfig]) -> Vec<Playlist> {
+    configs
+        .iter()
+        .enumerate()
+        .map(|(index, config)| entry(index, config, None))
+        .collect()
+}
+
+/// Pick a smart playlist's songs from the library's, sorted by artist,
+/// album and track.
+pub fn evaluate(
+    index: usize,
+    config: &SmartPlaylistConfig,
+    library: &[Song],
+) -> Result<(Playlist, Vec<Song>), String> {
+    let rules = Rules::parse(&config.rules)
+        .map_err(|e| format!("Bad rules in smart playlist '{}': {}", config.name, e))?;
+    let mut songs: Vec<Song> = library
+        .iter()
+        .filter(|song| rules.matches(song))
+        .cloned()
+        .collect();
+    songs.sort_by_cached_key(|song| {
+        (
+            song.display_artist().to_lowercase(),
+            song.album.as_deref().unwrap_or_default().to_lowercase(),
+            song.disc_number.unwrap_or(0),
+            song.track.unwrap_or(0),
+        )
+    });
+    Ok((entry(index, config, Some(&songs)), songs))
+}
+
+/// Get the path of the library's song list kept on disk.
+fn library_path() -> Result<PathBuf> {
+    Ok(Config::cache_dir()?.join("library.json"))
+}
+
+/// Get the library's song list from disk, if it was saved lately.
+pub fn load_library() -> Option<Vec<Song>> {
+    let path = library_path().ok()?;
+    let saved = std::fs::metadata(&path).ok()?.modified().ok()?;
+    let age = SystemTime::now().duration_since(saved).unwrap_or_default();
+    if age > LIBRARY_MAX_AGE {
+        return None;
+    }
+    let contents = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Save the library's song list to disk.
+pub fn save_library(songs: &[Song]) -> Result<()> {
+    let path = library_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string(songs)?)?;
+    Ok(())
+}
+
+/// Forget the saved song list, so it's fetched again.
+pub fn clear_library() {
+    if let Ok(path) = library_path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(value: serde_json::Value) -> Song {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_rules() {
+        let rules = Rules::parse("genre = Jazz AND year >= 1990 AND starred").unwrap();
+        let jazz = song(serde_json::json!({
+            "id": "1", "title": "So What", "genre": "jazz", "year": 1995,
+            "starred": "2024-01-01T00:00:00Z",
+        }));
+        let old = song(serde_json::json!({
+            "id": "2", "title": "Blue in Green", "genre": "Jazz", "year": 1959,
+            "starred": "2024-01-01T00:00:00Z",
+        }));
+        let unstarred = song(serde_json::json!({
+            "id": "3", "title": "Freddie", "genre": "Jazz", "year": 1999,
+        }));
+        assert!(rules.matches(&jazz));
+        assert!(!rules.matches(&old));
+        assert!(!rules.matches(&unstarred));
+
+        let rules = Rules::parse(r#"title ~ "green AND blue" AND starred = no"#).unwrap();
+        assert_eq!(rules.0.len(), 2);
+        assert_eq!(Rules::parse("genre = Jazz and starred").unwrap().0.len(), 2);
+        assert!(Rules::parse("artist != Miles Davis AND rating>3").is_ok());
+
+        assert!(Rules::parse("").is_err());
+        assert!(Rules::parse("mood = calm").is_err());
+        assert!(Rules::parse("year ~ 19").is_err());
+        assert!(Rules::parse("title > x").is_err());
+        assert!(Rules::parse("year >= soon").is_err());
+
+        assert_eq!(index(&playlist_id(3)), Some(3));
+        assert_eq!(index("800f3c2a"), None);
+    }
+}
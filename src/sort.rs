@@ -0,0 +1,539 @@
+//! Sort orders for library lists.
+
+use std::cmp::Reverse;
+
+use serde::{Deserialize, Serialize};
+
+use crate::action::Tab;
+use crate::client::models::{Album, Artist, Playlist, Song};
+use crate::columns::SongColumn;
+
+/// Sort order for the Artists tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtistSort {
+    #[default]
+    Default,
+    Name,
+    AlbumCount,
+}
+
+impl ArtistSort {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Default => Self::Name,
+            Self::Name => Self::AlbumCount,
+            Self::AlbumCount => Self::Default,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Default => "",
+            Self::Name => "name",
+            Self::AlbumCount => "album count",
+        }
+    }
+
+    /// Whether the order goes from high to low.
+    pub fn descending(self) -> bool {
+        self == Self::AlbumCount
+    }
+
+    pub fn apply(self, artists: &mut [Artist]) {
+        match self {
+            Self::Default => {}
+            Self::Name => artists.sort_by_cached_key(|a| sort_name(a.sort_name.as_ref(), &a.name)),
+            Self::AlbumCount => artists.sort_by_key(|a| Reverse(a.album_count.unwrap_or(0))),
+        }
+    }
+}
+
+/// Sort order for the Albums tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlbumSort {
+    #[default]
+    Default,
+    Name,
+    Artist,
+    Year,
+    DateAdded,
+    PlayCount,
+}
+
+impl AlbumSort {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Default => Self::Name,
+            Self::Name => Self::Artist,
+            Self::Artist => Self::Year,
+            Self::Year => Self::DateAdded,
+            Self::DateAdded => Self::PlayCount,
+            Self::PlayCount => Self::Default,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Default => "",
+            Self::Name => "name",
+            Self::Artist => "artist",
+            Self::Year => "year",
+            Self::DateAdded => "date added",
+            Self::PlayCount => "play count",
+        }
+    }
+
+    /// Whether the order goes from high to low (or new to old).
+    pub fn descending(self) -> bool {
+        matches!(self, Self::Year | Self::DateAdded | Self::PlayCount)
+    }
+
+    pub fn apply(self, albums: &mut [Album]) {
+        match self {
+            Self::Default => {}
+            Self::Name => albums.sort_by_cached_key(|a| sort_name(a.sort_name.as_ref(), &a.name)),
+            Self::Artist => albums.sort_by_cached_key(|a| {
+                (
+                    a.album_artist().unwrap_or_default().to_lowercase(),
+                    a.year.unwrap_or(0),
+                )
+            }),
+            Self::Year => albums.sort_by_key(|a| Reverse(a.year.unwrap_or(0))),
+            Self::DateAdded => albums.sort_by(|a, b| b.created.cmp(&a.created)),
+            Self::PlayCount => albums.sort_by_key(|a| Reverse(a.play_count.unwrap_or(0))),
+        }
+    }
+}
+
+/// Sort order for the Songs tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SongSort {
+    #[default]
+    Default,
+    Title,
+    Artist,
+    Album,
+    Duration,
+    Bpm,
+    SamplingRate,
+    BitDepth,
+    ChannelCount,
+}
+
+impl SongSort {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Default => Self::Title,
+            Self::Title => Self::Artist,
+            Self::Artist => Self::Album,
+            Self::Album => Self::Duration,
+            Self::Duration => Self::Bpm,
+            Self::Bpm => Self::SamplingRate,
+            Self::SamplingRate => Self::BitDepth,
+            Self::BitDepth => Self::ChannelCount,
+            Self::ChannelCount => Self::Default,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Default => "",
+            Self::Title => "title",
+            Self::Artist => "artist",
+            Self::Album => "album",
+            Self::Duration => "duration",
+            Self::Bpm => "BPM",
+            Self::SamplingRate => "sample rate",
+            Self::BitDepth => "bit depth",
+            Self::ChannelCount => "channels",
+        }
+    }
+
+    /// Whether the order goes from high to low.
+    pub fn descending(self) -> bool {
+        matches!(
+            self,
+            Self::SamplingRate | Self::BitDepth | Self::ChannelCount
+        )
+    }
+
+    /// Get the sort picked by a column header, if the column is sortable.
+    pub fn for_column(column: SongColumn) -> Option<Self> {
+        match column {
+            SongColumn::Title => Some(Self::Title),
+            SongColumn::Artist => Some(Self::Artist),
+            SongColumn::Album => Some(Self::Album),
+            SongColumn::Duration => Some(Self::Duration),
+            SongColumn::Bpm => Some(Self::Bpm),
+            SongColumn::SamplingRate => Some(Self::SamplingRate),
+            SongColumn::BitDepth => Some(Self::BitDepth),
+            SongColumn::ChannelCount => Some(Self::ChannelCount),
+            _ => None,
+        }
+    }
+
+    pub fn apply(self, songs: &mut [Song]) {
+        match self {
+            Self::Default => {}
+            Self::Title => songs.sort_by_cached_key(|s| s.title.to_lowercase()),
+            Self::Artist => songs.sort_by_cached_key(|s| {
+                (
+                    s.display_artist().to_lowercase(),
+                    s.display_album().to_lowercase(),
+                    s.disc_number.unwrap_or(0),
+                    s.track.unwrap_or(0),
+                )
+            }),
+            Self::Album => songs.sort_by_cached_key(|s| {
+                (
+                    s.display_album().to_lowercase(),
+                    s.disc_number.unwrap_or(0),
+                    s.track.unwrap_or(0),
+                )
+            }),
+            Self::Duration => songs.sort_by_key(|s| s.duration.unwrap_or(0)),
+            // Slowest first; songs without a tempo go last
+            Self::Bpm => songs.sort_by_key(|s| s.bpm.filter(|&bpm| bpm > 0).unwrap_or(i32::MAX)),
+            // Hi-res first, by sample rate or bit depth with the other breaking ties
+            Self::SamplingRate => songs
+                .sort_by_key(|s| Reverse((s.sampling_rate.unwrap_or(0), s.bit_depth.unwrap_or(0)))),
+            Self::BitDepth => songs
+                .sort_by_key(|s| Reverse((s.bit_depth.unwrap_or(0), s.sampling_rate.unwrap_or(0)))),
+            Self::ChannelCount => songs.sort_by_key(|s| Reverse(s.channel_count.unwrap_or(0))),
+        }
+    }
+}
+
+/// Sort order for the Playlists tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaylistSort {
+    #[default]
+    Default,
+    Name,
+    SongCount,
+    Changed,
+}
+
+impl PlaylistSort {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Default => Self::Name,
+            Self::Name => Self::SongCount,
+            Self::SongCount => Self::Changed,
+            Self::Changed => Self::Default,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Default => "",
+            Self::Name => "name",
+            Self::SongCount => "song count",
+            Self::Changed => "last changed",
+        }
+    }
+
+    /// Whether the order goes from high to low (or new to old).
+    pub fn descending(self) -> bool {
+        matches!(self, Self::SongCount | Self::Changed)
+    }
+
+    pub fn apply(self, playlists: &mut [Playlist]) {
+        match self {
+            Self::Default => {}
+            Self::Name => playlists.sort_by_cached_key(|p| p.name.to_lowercase()),
+            Self::SongCount => playlists.sort_by_key(|p| Reverse(p.song_count.unwrap_or(0))),
+            Self::Changed => playlists.sort_by(|a, b| b.changed.cmp(&a.changed)),
+        }
+    }
+}
+
+/// Which server album list the Albums tab shows (`getAlbumList2` type).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlbumList {
+    #[default]
+    Newest,
+    Recent,
+    Frequent,
+    /// Every compilation in the library, gone through by name
+    Compilations,
+    /// Albums released between two years, inclusive
+    ByYear {
+        from: i32,
+        to: i32,
+    },
+}
+
+impl AlbumList {
+    /// Cycle through the fixed lists. A year range is left for the newest list.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Newest => Self::Recent,
+            Self::Recent => Self::Frequent,
+            Self::Frequent => Self::Compilations,
+            Self::Compilations | Self::ByYear { .. } => Self::Newest,
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Self::Newest => String::from("Albums"),
+            Self::Recent => String::from("Recently Played"),
+            Self::Frequent => String::from("Most Played"),
+            Self::Compilations => String::from("Compilations"),
+            Self::ByYear { from, to } if from == to => format!("Albums from {}", from),
+            Self::ByYear { from, to } => format!("Albums {}–{}", from, to),
+        }
+    }
+
+    /// List type passed to `getAlbumList2`.
+    pub fn list_type(self) -> &'static str {
+        match self {
+            Self::Newest => "newest",
+            Self::Recent => "recent",
+            Self::Frequent => "frequent",
+            Self::Compilations => "alphabeticalByName",
+            Self::ByYear { .. } => "byYear",
+        }
+    }
+}
+
+/// Where the Songs tab gets its songs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SongsSource {
+    #[default]
+    Random,
+    RecentlyAdded,
+    Starred,
+    TopRated,
+}
+
+impl SongsSource {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Random => Self::RecentlyAdded,
+            Self::RecentlyAdded => Self::Starred,
+            Self::Starred => Self::TopRated,
+            Self::TopRated => Self::Random,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Random => "Random Songs",
+            Self::RecentlyAdded => "Recently Added",
+            Self::Starred => "Starred Songs",
+            Self::TopRated => "Top Rated",
+        }
+    }
+}
+
+/// Sort order of any sortable tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabSort {
+    Artists(ArtistSort),
+    Albums(AlbumSort),
+    Songs(SongSort),
+    Playlists(PlaylistSort),
+}
+
+impl TabSort {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Artists(sort) => Self::Artists(sort.next()),
+            Self::Albums(sort) => Self::Albums(sort.next()),
+            Self::Songs(sort) => Self::Songs(sort.next()),
+            Self::Playlists(sort) => Self::Playlists(sort.next()),
+        }
+    }
+
+    /// Whether this is the server's order.
+    pub fn is_default(self) -> bool {
+        match self {
+            Self::Artists(sort) => sort == ArtistSort::Default,
+            Self::Albums(sort) => sort == AlbumSort::Default,
+            Self::Songs(sort) => sort == SongSort::Default,
+            Self::Playlists(sort) => sort == PlaylistSort::Default,
+        }
+    }
+
+    /// Whether the order goes from high to low when not reversed.
+    pub fn descending(self) -> bool {
+        match self {
+            Self::Artists(sort) => sort.descending(),
+            Self::Albums(sort) => sort.descending(),
+            Self::Songs(sort) => sort.descending(),
+            Self::Playlists(sort) => sort.descending(),
+        }
+    }
+}
+
+/// Chosen sort order for each sortable tab. `Default` keeps the server's order.
+/// Saved in the state file, like the other choices made while browsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct LibrarySorts {
+    #[serde(default)]
+    pub artists: ArtistSort,
+    #[serde(default)]
+    pub albums: AlbumSort,
+    #[serde(default)]
+    pub songs: SongSort,
+    #[serde(default)]
+    pub playlists: PlaylistSort,
+    /// Tabs whose sort order is turned around
+    #[serde(default)]
+    pub reversed: ReversedSorts,
+}
+
+/// Which tabs show their sort order reversed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ReversedSorts {
+    #[serde(default)]
+    pub artists: bool,
+    #[serde(default)]
+    pub albums: bool,
+    #[serde(default)]
+    pub songs: bool,
+    #[serde(default)]
+    pub playlists: bool,
+}
+
+impl ReversedSorts {
+    fn get_mut(&mut self, tab: Tab) -> Option<&mut bool> {
+        match tab {
+            Tab::Artists => Some(&mut self.artists),
+            Tab::Albums => Some(&mut self.albums),
+            Tab::Songs => Some(&mut self.songs),
+            Tab::Playlists => Some(&mut self.playlists),
+            _ => None,
+        }
+    }
+}
+
+impl LibrarySorts {
+    /// Get the sort order of a tab, `None` if the tab isn't sortable.
+    pub fn get(&self, tab: Tab) -> Option<TabSort> {
+        match tab {
+            Tab::Artists => Some(TabSort::Artists(self.artists)),
+            Tab::Albums => Some(TabSort::Albums(self.albums)),
+            Tab::Songs => Some(TabSort::Songs(self.songs)),
+            Tab::Playlists => Some(TabSort::Playlists(self.playlists)),
+            _ => None,
+        }
+    }
+
+    /// Change the sort order of a tab, which starts out not reversed.
+    pub fn set(&mut self, sort: TabSort) {
+        let tab = match sort {
+            TabSort::Artists(sort) => {
+                self.artists = sort;
+                Tab::Artists
+            }
+            TabSort::Albums(sort) => {
+                self.albums = sort;
+                Tab::Albums
+            }
+            TabSort::Songs(sort) => {
+                self.songs = sort;
+                Tab::Songs
+            }
+            TabSort::Playlists(sort) => {
+                self.playlists = sort;
+                Tab::Playlists
+            }
+        };
+        if let Some(reversed) = self.reversed.get_mut(tab) {
+            *reversed = false;
+        }
+    }
+
+    /// Whether a tab's sort order is reversed.
+    pub fn is_reversed(&self, tab: Tab) -> bool {
+        match tab {
+            Tab::Artists => self.reversed.artists,
+            Tab::Albums => self.reversed.albums,
+            Tab::Songs => self.reversed.songs,
+            Tab::Playlists => self.reversed.playlists,
+            _ => false,
+        }
+    }
+
+    /// Turn a tab's sort order around.
+    pub fn toggle_reversed(&mut self, tab: Tab) {
+        if let Some(reversed) = self.reversed.get_mut(tab) {
+            *reversed = !*reversed;
+        }
+    }
+
+    pub fn apply_artists(&self, artists: &mut [Artist]) {
+        self.artists.apply(artists);
+        if self.reversed.artists {
+            artists.reverse();
+        }
+    }
+
+    pub fn apply_albums(&self, albums: &mut [Album]) {
+        self.albums.apply(albums);
+        if self.reversed.albums {
+            albums.reverse();
+        }
+    }
+
+    pub fn apply_songs(&self, songs: &mut [Song]) {
+        self.songs.apply(songs);
+        if self.reversed.songs {
+            songs.reverse();
+        }
+    }
+
+    pub fn apply_playlists(&self, playlists: &mut [Playlist]) {
+        self.playlists.apply(playlists);
+        if self.reversed.playlists {
+            playlists.reverse();
+        }
+    }
+}
+
+/// Case-insensitive sort key, preferring the server-provided sort name.
+fn sort_name(sort_name: Option<&String>, name: &str) -> String {
+    sort_name
+        .filter(|s| !s.is_empty())
+        .map(String::as_str)
+        .unwrap_or(name)
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_sorts() {
+        let song = |id: &str, bpm: i32, rate: i32, bits: i32| -> Song {
+            serde_json::from_value(serde_json::json!({
+                "id": id, "title": id, "bpm": bpm, "samplingRate": rate, "bitDepth": bits,
+            }))
+            .unwrap()
+        };
+        let mut songs = vec![
+            song("cd", 90, 44_100, 16),
+            song("none", 0, 44_100, 24),
+            song("hi", 128, 96_000, 24),
+        ];
+        let ids = |songs: &[Song]| -> Vec<String> { songs.iter().map(|s| s.id.clone()).collect() };
+        SongSort::Bpm.apply(&mut songs);
+        assert_eq!(ids(&songs), ["cd", "hi", "none"]);
+        SongSort::SamplingRate.apply(&mut songs);
+        assert_eq!(ids(&songs), ["hi", "none", "cd"]);
+        assert_eq!(
+            SongSort::for_column(SongColumn::BitDepth),
+            Some(SongSort::BitDepth)
+        );
+    }
+}
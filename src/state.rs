@@ -0,0 +1,144 @@
+//! Application state persisted between sessions.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::action::Tab;
+use crate::client::api::RandomFilter;
+use crate::config::Config;
+use crate::hidden::HiddenItems;
+use crate::sort::{AlbumList, LibrarySorts, SongsSource};
+
+/// Settings toggled at runtime that should survive a restart.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct State {
+    /// Remove tracks from the queue once they finish playing
+    #[serde(default)]
+    pub consume: bool,
+
+    /// Library totals from the last session, shown before the library loads
+    #[serde(default)]
+    pub library_stats: LibraryStats,
+
+    /// Sort order chosen for each library tab
+    #[serde(default)]
+    pub sorts: LibrarySorts,
+
+    /// Songs and albums hidden from the library
+    #[serde(default)]
+    pub hidden: HiddenItems,
+
+    /// Album list shown in the Albums tab
+    #[serde(default)]
+    pub album_list: AlbumList,
+
+    /// Where the Songs tab gets its songs
+    #[serde(default)]
+    pub songs_source: SongsSource,
+
+    /// Genre and years random songs are limited to
+    #[serde(default)]
+    pub songs_filter: RandomFilter,
+
+    /// Show the time remaining instead of elapsed next to the progress bar
+    #[serde(default)]
+    pub show_remaining: bool,
+
+    /// Whether the queue panel is shown (unset = `ui.show_queue` from the config)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_visible: Option<bool>,
+
+    /// Width of the queue panel in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_width: Option<u16>,
+
+    /// Whether the queue is grouped by album (unset = `ui.group_queue` from the config)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_grouped: Option<bool>,
+
+    /// Width of the lyrics panel in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lyrics_width: Option<u16>,
+
+    /// Language last picked in the lyrics panel (unset = `lyrics.lang` from the config)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lyrics_lang: Option<String>,
+
+    /// Library view open when the app was closed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library_view: Option<LibraryView>,
+
+    /// Volume when the app was closed (unset = `player.volume` from the config)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<u8>,
+
+    /// When the newest album seen so far was added, to badge albums added
+    /// after it as new
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub newest_album: Option<DateTime<Utc>>,
+}
+
+/// A library tab and the items drilled into, by ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryView {
+    pub tab: Tab,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artist_id: Option<String>,
+
+    /// Album (or playlist) whose songs are open
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub genre: Option<String>,
+
+    /// Selected item of the open list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_id: Option<String>,
+}
+
+/// Approximate library totals (0 = unknown).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryStats {
+    /// Number of albums, summed over artists
+    pub albums: u32,
+
+    /// Number of songs, summed over genres
+    pub songs: u32,
+}
+
+/// Get the state file path.
+fn state_path() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join("state.json"))
+}
+
+/// Load the saved state, or defaults if none exists.
+pub fn load() -> Result<State> {
+    let path = state_path()?;
+
+    if !path.exists() {
+        return Ok(State::default());
+    }
+
+    let contents = std::fs::read_to_string(&path)?;
+    let state: State = serde_json::from_str(&contents)?;
+
+    Ok(state)
+}
+
+/// Save the state.
+pub fn save(state: &State) -> Result<()> {
+    let path = state_path()?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(&path, serde_json::to_string_pretty(state)?)?;
+
+    Ok(())
+}
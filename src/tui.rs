@@ -1,12 +1,14 @@
 //! Terminal setup and teardown utilities.
 
-use std::io::{stdout, Stdout};
+use std::io::{stdout, Stdout, Write};
 
 use color_eyre::Result;
 use crossterm::{
     cursor,
     event::{DisableMouseCapture, EnableMouseCapture},
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
+    },
     ExecutableCommand,
 };
 use ratatui::prelude::*;
@@ -14,8 +16,16 @@ use ratatui::prelude::*;
 /// A type alias for the terminal type used in this application.
 pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 
+/// Save the window title on the terminal's title stack (xterm's XTWINOPS),
+/// so it can be put back on exit.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+
+/// Bring back the window title saved with [`PUSH_TITLE`].
+const POP_TITLE: &str = "\x1b[23;0t";
+
 /// Initialize the terminal for TUI rendering.
 pub fn init() -> Result<Tui> {
+    write!(stdout(), "{}", PUSH_TITLE)?;
     stdout().execute(EnterAlternateScreen)?;
     stdout().execute(EnableMouseCapture)?;
     stdout().execute(cursor::Hide)?;
@@ -32,11 +42,19 @@ pub fn restore() -> Result<()> {
     stdout().execute(cursor::Show)?;
     stdout().execute(DisableMouseCapture)?;
     stdout().execute(LeaveAlternateScreen)?;
+    write!(stdout(), "{}", POP_TITLE)?;
+    stdout().flush()?;
     disable_raw_mode()?;
 
     Ok(())
 }
 
+/// Set the terminal window title (OSC 0).
+pub fn set_title(title: &str) -> Result<()> {
+    stdout().execute(SetTitle(title))?;
+    Ok(())
+}
+
 /// Install panic and error hooks that restore the terminal before printing errors.
 pub fn install_hooks() -> Result<()> {
     let (panic_hook, eyre_hook) = color_eyre::config::HookBuilder::default()
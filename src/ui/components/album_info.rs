@@ -0,0 +1,207 @@
+//! Popup with an album's notes, Last.fm link and large cover.
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use ratatui_image::{protocol::StatefulProtocol, StatefulImage};
+
+use crate::client::models::{Album, AlbumInfo};
+
+/// Album info popup state.
+#[derive(Default)]
+pub struct AlbumInfoState {
+    /// Whether the popup is open
+    pub visible: bool,
+
+    /// Album shown
+    pub album: Option<Album>,
+
+    /// Whether the info is being fetched
+    pub loading: bool,
+
+    /// Info the server returned
+    pub info: Option<AlbumInfo>,
+
+    /// Error from fetching the info
+    pub error: Option<String>,
+
+    /// Large cover, once loaded
+    pub cover: Option<StatefulProtocol>,
+
+    /// Lines scrolled down the notes
+    pub scroll: u16,
+}
+
+impl std::fmt::Debug for AlbumInfoState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlbumInfoState")
+            .field("visible", &self.visible)
+            .field("album", &self.album.as_ref().map(|album| &album.id))
+            .field("loading", &self.loading)
+            .finish()
+    }
+}
+
+impl AlbumInfoState {
+    /// Open the popup for an album. Returns whether its info needs fetching,
+    /// which it doesn't if the popup was last opened for the same album.
+    pub fn open(&mut self, album: Album) -> bool {
+        self.visible = true;
+        self.scroll = 0;
+        if self.album_id() == Some(album.id.as_str()) && (self.loading || self.info.is_some()) {
+            return false;
+        }
+        self.album = Some(album);
+        self.loading = true;
+        self.info = None;
+        self.error = None;
+        self.cover = None;
+        true
+    }
+
+    /// ID of the album shown.
+    pub fn album_id(&self) -> Option<&str> {
+        self.album.as_ref().map(|album| album.id.as_str())
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_add(1);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+}
+
+/// Turn the HTML of album notes into plain text: tags dropped, common
+/// entities decoded, and runs of whitespace collapsed.
+pub fn plain_notes(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Render the album info popup.
+pub fn render_album_info(frame: &mut Frame, area: Rect, state: &mut AlbumInfoState) {
+    let width = (area.width * 7 / 10).max(50).min(area.width);
+    let height = (area.height * 7 / 10).max(12).min(area.height);
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Album info")
+        .title_bottom(" j/k: scroll  Esc: close ")
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Cover on the left, about square (cells are twice as tall as wide)
+    let text_area = match state.cover.as_mut() {
+        Some(cover) => {
+            let cover_width = (inner.height * 2).min(inner.width / 2);
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(cover_width + 1), Constraint::Min(0)])
+                .split(inner);
+            let cover_area = Rect {
+                width: cover_width,
+                ..columns[0]
+            };
+            frame.render_stateful_widget(StatefulImage::default(), cover_area, cover);
+            columns[1]
+        }
+        None => inner,
+    };
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines = Vec::new();
+    if let Some(album) = &state.album {
+        lines.push(Line::from(Span::styled(
+            album.name.clone(),
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        )));
+        if let Some(artist) = album.album_artist() {
+            lines.push(Line::from(Span::styled(
+                artist.to_string(),
+                Style::default().fg(Color::Cyan),
+            )));
+        }
+    }
+
+    let info = state.info.as_ref();
+    if let Some(url) = info.and_then(|info| info.last_fm_url.as_ref()) {
+        lines.push(Line::from(vec![
+            Span::styled("Last.fm ", dim),
+            Span::styled(url.clone(), Style::default().fg(Color::Blue)),
+        ]));
+    }
+    lines.push(Line::default());
+
+    let notes = info
+        .and_then(|info| info.notes.as_deref())
+        .map(plain_notes)
+        .filter(|notes| !notes.is_empty());
+    match (notes, &state.error) {
+        (Some(notes), _) => lines.push(Line::from(notes)),
+        (None, Some(error)) => lines.push(Line::from(Span::styled(
+            format!("Couldn't ask the server: {}", error),
+            Style::default().fg(Color::Red),
+        ))),
+        (None, None) if state.loading => lines.push(Line::from(Span::styled("Loading…", dim))),
+        (None, None) => lines.push(Line::from(Span::styled(
+            "The server has no notes for this album",
+            dim,
+        ))),
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .scroll((state.scroll, 0)),
+        text_area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_notes() {
+        let html = "Kind of Blue is a studio album by  Miles Davis &amp; his sextet.\n\
+            <a href=\"https://www.last.fm/music/Miles+Davis/Kind+of+Blue\">Read more on Last.fm</a>.";
+        assert_eq!(
+            plain_notes(html),
+            "Kind of Blue is a studio album by Miles Davis & his sextet. Read more on Last.fm."
+        );
+        assert_eq!(plain_notes("<p></p>"), "");
+    }
+}
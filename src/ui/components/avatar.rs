@@ -0,0 +1,75 @@
+//! User avatar thumbnails for multi-user views.
+
+use std::collections::HashMap;
+
+use ratatui::{layout::Rect, Frame};
+use ratatui_image::{picker::Picker, protocol::StatefulProtocol, StatefulImage};
+
+/// Fetch state of a single user's avatar.
+enum Avatar {
+    Loading,
+    Loaded(StatefulProtocol),
+    /// The user has no avatar, or it couldn't be decoded
+    Missing,
+}
+
+/// Avatars fetched so far, kept for the whole session.
+#[derive(Default)]
+pub struct AvatarCache {
+    avatars: HashMap<String, Avatar>,
+}
+
+impl AvatarCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether a user's avatar still needs fetching, marking it as loading if so.
+    pub fn start_fetch(&mut self, username: &str) -> bool {
+        if self.avatars.contains_key(username) {
+            return false;
+        }
+        self.avatars.insert(username.to_string(), Avatar::Loading);
+        true
+    }
+
+    /// Store fetched avatar data. Without terminal graphics nothing can be shown,
+    /// so the avatar is recorded as missing to avoid fetching it again.
+    pub fn insert(&mut self, username: String, image_data: &[u8], picker: Option<&Picker>) {
+        let avatar = picker
+            .zip(image::load_from_memory(image_data).ok())
+            .map(|(picker, image)| Avatar::Loaded(picker.new_resize_protocol(image)))
+            .unwrap_or(Avatar::Missing);
+        self.avatars.insert(username, avatar);
+    }
+
+    /// Record that a user's avatar couldn't be fetched.
+    pub fn mark_missing(&mut self, username: String) {
+        self.avatars.insert(username, Avatar::Missing);
+    }
+
+    /// Get a user's avatar if it has loaded.
+    pub fn get_mut(&mut self, username: &str) -> Option<&mut StatefulProtocol> {
+        match self.avatars.get_mut(username) {
+            Some(Avatar::Loaded(protocol)) => Some(protocol),
+            _ => None,
+        }
+    }
+}
+
+/// Render a user's avatar thumbnail into `area`.
+/// Returns false (leaving the area untouched) if there is no avatar to show.
+pub fn render_avatar(
+    frame: &mut Frame,
+    area: Rect,
+    avatars: &mut AvatarCache,
+    username: &str,
+) -> bool {
+    match avatars.get_mut(username) {
+        Some(protocol) => {
+            frame.render_stateful_widget(StatefulImage::default(), area, protocol);
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,143 @@
+//! Quick filter for narrowing displayed lists by text.
+
+use crate::client::models::{Album, Artist, Genre, Playlist, Song};
+use crate::history::HistoryEntry;
+
+/// An item that can be matched against a quick filter.
+pub trait Filterable {
+    /// Check if any of the item's display fields contain the (lowercase) query.
+    fn matches_filter(&self, query: &str) -> bool;
+}
+
+fn contains(field: &str, query: &str) -> bool {
+    field.to_lowercase().contains(query)
+}
+
+impl Filterable for Artist {
+    fn matches_filter(&self, query: &str) -> bool {
+        contains(&self.name, query)
+    }
+}
+
+impl Filterable for Album {
+    fn matches_filter(&self, query: &str) -> bool {
+        contains(&self.name, query)
+            || self.artist.as_deref().is_some_and(|a| contains(a, query))
+            || self.album_artist().is_some_and(|a| contains(a, query))
+    }
+}
+
+impl Filterable for Song {
+    fn matches_filter(&self, query: &str) -> bool {
+        contains(&self.title, query)
+            || self.artist.as_deref().is_some_and(|a| contains(a, query))
+            || self.album.as_deref().is_some_and(|a| contains(a, query))
+    }
+}
+
+impl Filterable for Playlist {
+    fn matches_filter(&self, query: &str) -> bool {
+        contains(&self.name, query)
+    }
+}
+
+impl Filterable for Genre {
+    fn matches_filter(&self, query: &str) -> bool {
+        contains(&self.value, query)
+    }
+}
+
+impl Filterable for HistoryEntry {
+    fn matches_filter(&self, query: &str) -> bool {
+        self.song.matches_filter(query)
+    }
+}
+
+/// Get the indices of items matching the filter (all items if the filter is empty).
+pub fn visible_indices<T: Filterable>(items: &[T], filter: &str) -> Vec<usize> {
+    if filter.is_empty() {
+        return (0..items.len()).collect();
+    }
+
+    let query = filter.to_lowercase();
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.matches_filter(&query))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Append the active filter to a panel title, with a cursor while it is being typed.
+pub fn filter_title(title: String, filter: &str, editing: bool) -> String {
+    if editing {
+        format!("{} · filter: {}_", title, filter)
+    } else if !filter.is_empty() {
+        format!("{} · filter: {}", title, filter)
+    } else {
+        title
+    }
+}
+
+/// Map an item index to its row among the visible items.
+pub fn to_visible(visible: &[usize], index: Option<usize>) -> Option<usize> {
+    index.and_then(|index| visible.iter().position(|&i| i == index))
+}
+
+/// Map a row among the visible items back to its item index.
+pub fn from_visible(visible: &[usize], row: Option<usize>) -> Option<usize> {
+    row.and_then(|row| visible.get(row).copied())
+}
+
+/// Get the visible row to select after moving `delta` rows from the current selection.
+/// Wraps around at either end when `wrap` is set, otherwise clamps.
+pub fn step(visible: &[usize], index: Option<usize>, delta: isize, wrap: bool) -> Option<usize> {
+    if visible.is_empty() {
+        return None;
+    }
+
+    let last = visible.len() as isize - 1;
+    let row = match to_visible(visible, index) {
+        Some(row) => {
+            let target = row as isize + delta;
+            if wrap && target > last {
+                0
+            } else if wrap && target < 0 {
+                last
+            } else {
+                target.clamp(0, last)
+            }
+        }
+        None => 0,
+    };
+    Some(visible[row as usize])
+}
+
+/// Get the item `step` places along `items` from `current`, wrapping around,
+/// such as the next tab or panel. From an item not in `items`, goes to the
+/// first; with no items, stays put.
+pub fn step_in<T: PartialEq + Copy>(items: &[T], current: T, step: isize) -> T {
+    let len = items.len() as isize;
+    match items.iter().position(|item| *item == current) {
+        _ if len == 0 => current,
+        Some(i) => items[(i as isize + step).rem_euclid(len) as usize],
+        None => items[0],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_skips_hidden_items() {
+        let visible = vec![1, 4, 7];
+        assert_eq!(step(&visible, Some(4), 1, true), Some(7));
+        assert_eq!(step(&visible, Some(7), 1, true), Some(1));
+        assert_eq!(step(&visible, Some(1), -1, true), Some(7));
+        assert_eq!(step(&visible, Some(4), 10, false), Some(7));
+        // A hidden selection moves to the first match
+        assert_eq!(step(&visible, Some(3), 1, true), Some(1));
+        assert_eq!(step(&[], Some(3), 1, true), None);
+    }
+}
@@ -0,0 +1,260 @@
+//! Help overlay listing the shortcuts, scrollable and filterable.
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::filter::filter_title;
+use crate::keymap::{self, Key, Keymap};
+use crate::ui::centered_rect;
+use crate::ui::scrollbar::render_scrollbar;
+
+/// Mouse actions, listed after the keys.
+const MOUSE: &[(&str, &str)] = &[
+    ("Click", "Select item / Switch focus"),
+    ("Double-click", "Play item"),
+    ("Click tab", "Switch to tab"),
+    ("Click header", "Sort by column / reverse"),
+    ("Drag progress", "Seek in track"),
+    ("Drag volume", "Set volume"),
+    ("Click title", "Track info"),
+    ("2x title", "Full-screen now playing"),
+    ("Click ctrl", "Playback controls"),
+    ("Scroll", "Navigate list"),
+    ("Scroll vol", "Adjust volume"),
+];
+
+/// A heading and its (keys, description) rows.
+type Section = (&'static str, Vec<(String, &'static str)>);
+
+/// Get every section of the help: the keymaps, then the mouse.
+fn sections() -> Vec<Section> {
+    keymap::HELP
+        .iter()
+        .map(|keymap| (keymap.title, rows(keymap)))
+        .chain([(
+            "Mouse",
+            MOUSE
+                .iter()
+                .map(|&(keys, description)| (keys.to_string(), description))
+                .collect(),
+        )])
+        .collect()
+}
+
+/// Get the rows of a keymap. Bindings next to each other with the same
+/// description share a row, like the tab numbers.
+fn rows(keymap: &Keymap) -> Vec<(String, &'static str)> {
+    let mut rows: Vec<(Vec<Key>, &'static str)> = Vec::new();
+    for binding in keymap.bindings {
+        match rows.last_mut() {
+            Some((keys, description)) if *description == binding.description => {
+                keys.extend(binding.keys)
+            }
+            _ => rows.push((binding.keys.to_vec(), binding.description)),
+        }
+    }
+    rows.into_iter()
+        .map(|(keys, description)| (keys_label(&keys), description))
+        .collect()
+}
+
+/// Show keys as "d/Delete", or a run of digits as "1-9".
+fn keys_label(keys: &[Key]) -> String {
+    let digits: Option<Vec<u32>> = keys.iter().map(Key::digit).collect();
+    match digits {
+        Some(digits) if digits.len() > 2 && digits.windows(2).all(|d| d[1] == d[0] + 1) => {
+            format!("{}-{}", digits[0], digits[digits.len() - 1])
+        }
+        _ => keys.iter().map(Key::label).collect::<Vec<_>>().join("/"),
+    }
+}
+
+/// Width of the keys column.
+const KEYS_WIDTH: usize = 20;
+
+/// Help overlay state.
+#[derive(Debug, Default)]
+pub struct HelpState {
+    /// Whether the overlay is open
+    pub visible: bool,
+
+    /// Lines scrolled past the top
+    pub scroll: usize,
+
+    /// Only show shortcuts containing this text
+    pub filter: String,
+
+    /// Whether the filter is being typed
+    pub filtering: bool,
+
+    /// Lines that fit in the overlay, as of the last render
+    page: usize,
+}
+
+impl HelpState {
+    pub fn open(&mut self) {
+        self.visible = true;
+        self.scroll = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.filtering = false;
+        self.filter.clear();
+    }
+
+    /// Scroll by `delta` lines; the render keeps it in range.
+    pub fn scroll_by(&mut self, delta: isize) {
+        self.scroll = self.scroll.saturating_add_signed(delta);
+    }
+
+    /// Scroll by `delta` pages.
+    pub fn scroll_pages(&mut self, delta: isize) {
+        self.scroll_by(delta * self.page.max(1) as isize);
+    }
+}
+
+/// Get the sections with the shortcuts matching `filter` (case-insensitive,
+/// against the keys, description or section title).
+pub fn matching_shortcuts(filter: &str) -> Vec<Section> {
+    let filter = filter.to_lowercase();
+    sections()
+        .into_iter()
+        .filter_map(|(title, entries)| {
+            let whole_section = title.to_lowercase().contains(&filter);
+            let entries: Vec<_> = entries
+                .into_iter()
+                .filter(|(keys, description)| {
+                    whole_section
+                        || keys.to_lowercase().contains(&filter)
+                        || description.to_lowercase().contains(&filter)
+                })
+                .collect();
+            (!entries.is_empty()).then_some((title, entries))
+        })
+        .collect()
+}
+
+/// Render the help overlay.
+pub fn render_help(frame: &mut Frame, area: Rect, state: &mut HelpState) {
+    let popup_area = centered_rect(70, 80, area);
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(filter_title(
+            String::from("Help"),
+            &state.filter,
+            state.filtering,
+        ))
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut lines: Vec<Line> = Vec::new();
+    for (title, entries) in matching_shortcuts(&state.filter) {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(title, heading)));
+        for (keys, description) in entries {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<width$}", keys, width = KEYS_WIDTH),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(description),
+            ]));
+        }
+    }
+
+    // Keep the last line at the bottom at most
+    let height = chunks[0].height as usize;
+    state.page = height;
+    state.scroll = state.scroll.min(lines.len().saturating_sub(height));
+
+    if lines.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No shortcuts match").style(Style::default().fg(Color::DarkGray)),
+            chunks[0],
+        );
+    } else {
+        let line_count = lines.len();
+        frame.render_widget(
+            Paragraph::new(lines).scroll((state.scroll as u16, 0)),
+            chunks[0],
+        );
+        render_scrollbar(frame, popup_area, line_count, height, state.scroll);
+    }
+
+    let hint = if state.filtering {
+        format!("Filter: {}_   Enter: keep  Esc: clear", state.filter)
+    } else {
+        String::from("j/k: scroll  PgUp/PgDn: page  g/G: top/bottom  /: filter  Esc: close")
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(hint, Style::default().fg(Color::DarkGray))),
+        chunks[1],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_shortcuts() {
+        assert_eq!(matching_shortcuts("").len(), keymap::HELP.len() + 1);
+
+        // Matches descriptions, keeping only the sections they are in
+        let found = matching_shortcuts("SHUFFLE");
+        let titles: Vec<_> = found.iter().map(|(title, _)| *title).collect();
+        assert_eq!(titles, ["Playback", "Queue & Library"]);
+        assert_eq!(found[0].1, [(String::from("s"), "Toggle shuffle")]);
+
+        // A matching section title keeps the whole section
+        let found = matching_shortcuts("mouse");
+        assert_eq!(found[0].1.len(), MOUSE.len());
+
+        assert!(matching_shortcuts("no such shortcut").is_empty());
+    }
+
+    #[test]
+    fn test_rows_from_keymap() {
+        let label = |keymap: &Keymap, description: &str| {
+            rows(keymap)
+                .into_iter()
+                .find(|(_, d)| *d == description)
+                .map(|(keys, _)| keys)
+        };
+        assert_eq!(
+            label(&keymap::LIBRARY, "Remove selected from queue/playlist").as_deref(),
+            Some("d/Delete")
+        );
+        assert_eq!(
+            label(&keymap::PANELS, "Previous tab").as_deref(),
+            Some("Alt+h/Alt+←")
+        );
+        assert_eq!(label(&keymap::PANELS, "Quit").as_deref(), Some("q/Ctrl+c"));
+
+        // The ratings share a row, shown as a range
+        assert_eq!(
+            label(&keymap::TRACK_INFO, "Rate playing track").as_deref(),
+            Some("0-5")
+        );
+        assert_eq!(rows(&keymap::TRACK_INFO).len(), 8);
+    }
+}
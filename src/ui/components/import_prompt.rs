@@ -0,0 +1,93 @@
+//! Popup asking for the M3U file to import.
+
+use std::path::{Path, PathBuf};
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Import prompt state.
+#[derive(Debug, Default)]
+pub struct ImportPromptState {
+    /// Whether the popup is open
+    pub visible: bool,
+
+    /// Path of the file, as typed
+    pub path: String,
+}
+
+impl ImportPromptState {
+    /// Open the prompt with the path starting in `dir`.
+    pub fn open(&mut self, dir: &Path) {
+        self.path = dir.join("").display().to_string();
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.path.clear();
+    }
+
+    /// Get the typed path, with a leading `~` standing for the home directory.
+    pub fn expanded_path(&self) -> Option<PathBuf> {
+        let path = self.path.trim();
+        if path.is_empty() {
+            return None;
+        }
+        match path.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().map(|home| home.join(rest)),
+            None => Some(path.into()),
+        }
+    }
+}
+
+/// Render the import prompt popup.
+pub fn render_import_prompt(frame: &mut Frame, area: Rect, state: &ImportPromptState) {
+    let width = area.width.saturating_sub(4).min(70);
+    let popup_area = Rect {
+        x: area.x + (area.width.saturating_sub(width)) / 2,
+        y: area.y + area.height.saturating_sub(4) / 2,
+        width,
+        height: 4.min(area.height),
+    };
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Import M3U playlist")
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Length(1)])
+        .split(inner);
+
+    // Keep the end of a long path in view
+    let room = usize::from(chunks[0].width).saturating_sub(7);
+    let chars = state.path.chars().count();
+    let shown: String = state
+        .path
+        .chars()
+        .skip(chars.saturating_sub(room))
+        .collect();
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("File: ", Style::default().fg(Color::Cyan)),
+            Span::raw(format!("{}_", shown)),
+        ])),
+        chunks[0],
+    );
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            "Enter: import  Esc: cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+        chunks[1],
+    );
+}
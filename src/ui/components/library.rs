@@ -1,14 +1,117 @@
 //! Library browser component for artists, albums, and songs.
 
+use chrono::{DateTime, Utc};
+use image::DynamicImage;
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Flex, Layout, Rect},
     style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, Cell, ListState, Paragraph, Row, Table, TableState},
     Frame,
 };
+use ratatui_image::{picker::Picker, protocol::StatefulProtocol, StatefulImage};
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use super::filter::{filter_title, from_visible, step, step_in, to_visible, visible_indices};
+use crate::action::{QueueSource, SongList, Tab};
+use crate::client::api::RandomFilter;
+use crate::client::models::{Album, Artist, ArtistIndex, Genre, Playlist, Song};
+use crate::client::paging::{GenreSongs, PagedSongs, StarredSongs, PAGE_SIZE};
+use crate::columns::{default_song_columns, ColumnSpec, SongColumn};
+use crate::hidden::{HiddenItems, HideTarget};
+use crate::history::HistoryEntry;
+use crate::smart;
+use crate::sort::{
+    AlbumList, AlbumSort, ArtistSort, LibrarySorts, PlaylistSort, SongSort, SongsSource, TabSort,
+};
+use crate::state::LibraryView;
+use crate::ui::hit::{HitRegion, HitRegions};
+use crate::ui::icons::Icons;
+use crate::ui::scrollbar::render_scrollbar;
+
+/// Rows taken by the header above an album's songs.
+const ALBUM_HEADER_HEIGHT: u16 = 6;
+
+/// Width of the cover art thumbnail in the album header.
+const ALBUM_ART_WIDTH: u16 = 12;
+
+/// Rows taken by the similar albums below an album's songs: a gap, a title
+/// and the albums.
+const SIMILAR_ALBUMS_HEIGHT: u16 = 3;
+
+/// Cover art thumbnail for the album header.
+pub struct AlbumArt {
+    /// Cover art ID the image was loaded for
+    pub id: String,
+    pub protocol: StatefulProtocol,
+}
+
+impl std::fmt::Debug for AlbumArt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlbumArt").field("id", &self.id).finish()
+    }
+}
+
+/// A change to the open playlist, already shown, for the server to make.
+#[derive(Debug, Clone)]
+pub struct PlaylistEdit {
+    pub playlist_id: String,
+
+    /// IDs of the songs to append
+    pub add: Vec<String>,
+
+    /// Server indices of the entries to remove, taken before appending
+    pub remove: Vec<usize>,
+
+    /// The playlist as it was before the change
+    undo: PlaylistUndo,
+}
+
+impl PlaylistEdit {
+    /// Fold a later reorder of the same playlist into this one, so both go
+    /// to the server as one change. Both must come from `move_playlist_song`.
+    pub fn then_move(mut self, later: PlaylistEdit) -> PlaylistEdit {
+        // A reorder removes and adds back every entry from its first on
+        let start = |edit: &PlaylistEdit| edit.remove.first().copied().unwrap_or(0);
+        let (first, next) = (start(&self), start(&later));
+        if next <= first {
+            return PlaylistEdit {
+                undo: self.undo,
+                ..later
+            };
+        }
+        // The entries between the two starts weren't touched by the later one
+        self.add.truncate(next - first);
+        self.add.extend(later.add);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PlaylistUndo {
+    songs: Vec<Song>,
+    entries: Vec<Song>,
+    selected: Option<usize>,
+}
+
+/// Artists under one index letter of the Artists list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArtistGroup {
+    letter: String,
+
+    /// Index of the group's first artist
+    start: usize,
+
+    len: usize,
+}
 
-use crate::action::Tab;
-use crate::client::models::{Album, Artist, Genre, Playlist, Song};
+impl ArtistGroup {
+    fn contains(&self, artist: usize) -> bool {
+        (self.start..self.start + self.len).contains(&artist)
+    }
+}
 
 /// Library view state.
 #[derive(Debug, Default)]
@@ -16,10 +119,19 @@ pub struct LibraryState {
     /// Currently selected tab
     pub tab: Tab,
 
+    /// Tabs in the tab bar, in order
+    pub tabs: Vec<Tab>,
+
     /// Artists list
     pub artists: Vec<Artist>,
     pub artists_state: ListState,
 
+    /// Index letters of the artists, while they're in server order
+    artist_groups: Vec<ArtistGroup>,
+
+    /// Index letters whose artists are hidden under their header
+    collapsed_letters: HashSet<String>,
+
     /// Albums list
     pub albums: Vec<Album>,
     pub albums_state: ListState,
@@ -27,6 +139,11 @@ pub struct LibraryState {
     /// Songs list (from album or random)
     pub songs: Vec<Song>,
     pub songs_state: ListState,
+    pub songs_pages: Option<PagedSongs>,
+
+    /// IDs of the songs in the order their pages came in, to go back to when
+    /// the sort is turned off; random pages can't be fetched again
+    songs_order: Vec<String>,
 
     /// Playlists list
     pub playlists: Vec<Playlist>,
@@ -40,6 +157,11 @@ pub struct LibraryState {
     pub selected_genre: Option<Genre>,
     pub genre_albums: Vec<Album>,
     pub genre_albums_state: ListState,
+    pub genre_songs: Vec<Song>,
+    pub genre_songs_state: ListState,
+    pub genre_songs_pages: Option<PagedSongs>,
+    /// Current section in genre view (0=albums, 1=songs)
+    pub genre_section: u8,
 
     /// Favorites (starred items)
     pub favorites_artists: Vec<Artist>,
@@ -48,9 +170,14 @@ pub struct LibraryState {
     pub favorites_albums_state: ListState,
     pub favorites_songs: Vec<Song>,
     pub favorites_songs_state: ListState,
+    pub favorites_songs_pages: Option<PagedSongs>,
     /// Current section in favorites view (0=artists, 1=albums, 2=songs)
     pub favorites_section: u8,
 
+    /// Playback history (most recent first)
+    pub history: Vec<HistoryEntry>,
+    pub history_state: ListState,
+
     /// Currently selected artist (for drill-down)
     pub selected_artist: Option<Artist>,
     pub artist_albums: Vec<Album>,
@@ -61,16 +188,87 @@ pub struct LibraryState {
     pub album_songs: Vec<Song>,
     pub album_songs_state: ListState,
 
+    /// Songs of the open playlist in server order, hidden ones included
+    pub playlist_entries: Vec<Song>,
+
+    /// Cover art shown in the album header
+    pub album_art: Option<AlbumArt>,
+
+    /// Sort order for each sortable tab
+    pub sorts: LibrarySorts,
+
+    /// Server album list shown in the Albums tab
+    pub album_list: AlbumList,
+
+    /// Where the Songs tab gets its songs
+    pub songs_source: SongsSource,
+
+    /// Genre and years the Songs tab's random songs are limited to
+    pub songs_filter: RandomFilter,
+
+    /// Columns shown in song tables
+    pub song_columns: Vec<ColumnSpec>,
+
+    /// Songs and albums dropped from every list
+    pub hidden: HiddenItems,
+
+    /// ID of the song now playing, marked in song tables
+    pub playing_id: Option<String>,
+
+    /// Icons to draw with
+    pub icons: Icons,
+
+    /// Quick filter narrowing the displayed list (empty = show all)
+    pub filter: String,
+
+    /// Whether the filter is being typed
+    pub filtering: bool,
+
     /// View depth (0 = list, 1 = artist/album detail)
     pub view_depth: u8,
 
     /// Loading state
     pub loading: bool,
+
+    /// Tabs whose list is still being fetched
+    pub loading_tabs: HashSet<Tab>,
+
+    /// Item to select once the list at this view depth loads, restored from
+    /// the last session
+    pub pending_selection: Option<(u8, String)>,
+
+    /// Selection and scroll position of drill-down lists left behind, keyed
+    /// by what they showed (e.g. `album:<id>`), restored when reopened
+    positions: HashMap<String, ListState>,
+
+    /// Newest album seen at the end of the last session; albums added since
+    /// are badged as new (unset = first session, nothing is new)
+    pub new_since: Option<DateTime<Utc>>,
+
+    /// Newest album seen so far, saved for the next session
+    pub newest_album: Option<DateTime<Utc>>,
+
+    /// Top albums of artists like the open album's, with the ID of the album
+    /// they were found for
+    similar_albums: Option<(String, Vec<Album>)>,
+
+    /// Similar album picked, which Enter opens and `a` queues
+    similar_selected: Option<usize>,
 }
 
 impl LibraryState {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            tabs: Tab::all().to_vec(),
+            song_columns: default_song_columns(),
+            ..Default::default()
+        }
+    }
+
+    /// Get the tab `step` places along the tab bar from the current one,
+    /// wrapping around. From a tab not in the bar, goes to its first.
+    pub fn step_tab(&self, step: isize) -> Tab {
+        step_in(&self.tabs, self.tab, step)
     }
 
     /// Get the currently active list state based on tab and depth.
@@ -103,6 +301,8 @@ impl LibraryState {
             Tab::Genres => {
                 if self.view_depth == 0 {
                     &mut self.genres_state
+                } else if self.view_depth == 1 && self.genre_section == 1 {
+                    &mut self.genre_songs_state
                 } else if self.view_depth == 1 {
                     &mut self.genre_albums_state
                 } else {
@@ -125,6 +325,7 @@ impl LibraryState {
                     &mut self.album_songs_state
                 }
             }
+            Tab::History => &mut self.history_state,
         }
     }
 
@@ -158,6 +359,8 @@ impl LibraryState {
             Tab::Genres => {
                 if self.view_depth == 0 {
                     self.genres.len()
+                } else if self.view_depth == 1 && self.genre_section == 1 {
+                    self.genre_songs.len()
                 } else if self.view_depth == 1 {
                     self.genre_albums.len()
                 } else {
@@ -177,49 +380,266 @@ impl LibraryState {
                     self.album_songs.len()
                 }
             }
+            Tab::History => self.history.len(),
         }
     }
 
-    /// Move selection up.
-    pub fn select_previous(&mut self) {
-        let len = self.active_list_len();
-        if len == 0 {
-            return;
-        }
-
-        let selected = self.active_list_state().selected();
-        let i = match selected {
-            Some(i) => {
-                if i == 0 {
-                    len - 1
+    /// Get the indices of the active list's items that pass the filter.
+    pub fn active_visible(&self) -> Vec<usize> {
+        match self.tab {
+            Tab::Artists => {
+                if self.view_depth == 0 {
+                    match self.artist_layout() {
+                        Some(layout) => layout.selectable(),
+                        None => visible_indices(&self.artists, &self.filter),
+                    }
+                } else if self.view_depth == 1 {
+                    visible_indices(&self.artist_albums, &self.filter)
                 } else {
-                    i - 1
+                    visible_indices(&self.album_songs, &self.filter)
                 }
             }
-            None => 0,
+            Tab::Albums => {
+                if self.view_depth == 0 {
+                    visible_indices(&self.albums, &self.filter)
+                } else {
+                    visible_indices(&self.album_songs, &self.filter)
+                }
+            }
+            Tab::Songs => visible_indices(&self.songs, &self.filter),
+            Tab::Playlists => {
+                if self.view_depth == 0 {
+                    visible_indices(&self.playlists, &self.filter)
+                } else {
+                    visible_indices(&self.album_songs, &self.filter)
+                }
+            }
+            Tab::Genres => {
+                if self.view_depth == 0 {
+                    visible_indices(&self.genres, &self.filter)
+                } else if self.view_depth == 1 && self.genre_section == 1 {
+                    visible_indices(&self.genre_songs, &self.filter)
+                } else if self.view_depth == 1 {
+                    visible_indices(&self.genre_albums, &self.filter)
+                } else {
+                    visible_indices(&self.album_songs, &self.filter)
+                }
+            }
+            Tab::Favorites => {
+                if self.view_depth == 0 {
+                    match self.favorites_section {
+                        0 => visible_indices(&self.favorites_artists, &self.filter),
+                        1 => visible_indices(&self.favorites_albums, &self.filter),
+                        _ => visible_indices(&self.favorites_songs, &self.filter),
+                    }
+                } else if self.view_depth == 1 {
+                    visible_indices(&self.artist_albums, &self.filter)
+                } else {
+                    visible_indices(&self.album_songs, &self.filter)
+                }
+            }
+            Tab::History => visible_indices(&self.history, &self.filter),
+        }
+    }
+
+    /// Get the IDs of the items in the active list, in list order.
+    fn active_ids(&self) -> Vec<&str> {
+        fn ids<T>(items: &[T], id: fn(&T) -> &str) -> Vec<&str> {
+            items.iter().map(id).collect()
+        }
+        fn album(album: &Album) -> &str {
+            &album.id
+        }
+        fn song(song: &Song) -> &str {
+            &song.id
+        }
+        match self.tab {
+            Tab::Artists => match self.view_depth {
+                0 => ids(&self.artists, |artist| &artist.id),
+                1 => ids(&self.artist_albums, album),
+                _ => ids(&self.album_songs, song),
+            },
+            Tab::Albums => match self.view_depth {
+                0 => ids(&self.albums, album),
+                _ => ids(&self.album_songs, song),
+            },
+            Tab::Songs => ids(&self.songs, song),
+            Tab::Playlists => match self.view_depth {
+                0 => ids(&self.playlists, |playlist| &playlist.id),
+                _ => ids(&self.album_songs, song),
+            },
+            Tab::Genres => match self.view_depth {
+                0 => ids(&self.genres, |genre| &genre.value),
+                1 if self.genre_section == 1 => ids(&self.genre_songs, song),
+                1 => ids(&self.genre_albums, album),
+                _ => ids(&self.album_songs, song),
+            },
+            Tab::Favorites => match self.view_depth {
+                0 => match self.favorites_section {
+                    0 => ids(&self.favorites_artists, |artist| &artist.id),
+                    1 => ids(&self.favorites_albums, album),
+                    _ => ids(&self.favorites_songs, song),
+                },
+                1 => ids(&self.artist_albums, album),
+                _ => ids(&self.album_songs, song),
+            },
+            Tab::History => ids(&self.history, |entry| &entry.song.id),
+        }
+    }
+
+    /// Get the selected index of the active list.
+    fn active_selected(&self) -> Option<usize> {
+        let state = match self.tab {
+            Tab::Artists => match self.view_depth {
+                0 => &self.artists_state,
+                1 => &self.artist_albums_state,
+                _ => &self.album_songs_state,
+            },
+            Tab::Albums => match self.view_depth {
+                0 => &self.albums_state,
+                _ => &self.album_songs_state,
+            },
+            Tab::Songs => &self.songs_state,
+            Tab::Playlists => match self.view_depth {
+                0 => &self.playlists_state,
+                _ => &self.album_songs_state,
+            },
+            Tab::Genres => match self.view_depth {
+                0 => &self.genres_state,
+                1 if self.genre_section == 1 => &self.genre_songs_state,
+                1 => &self.genre_albums_state,
+                _ => &self.album_songs_state,
+            },
+            Tab::Favorites => match self.view_depth {
+                0 => match self.favorites_section {
+                    0 => &self.favorites_artists_state,
+                    1 => &self.favorites_albums_state,
+                    _ => &self.favorites_songs_state,
+                },
+                1 => &self.artist_albums_state,
+                _ => &self.album_songs_state,
+            },
+            Tab::History => &self.history_state,
         };
-        self.active_list_state().select(Some(i));
+        state.selected()
+    }
+
+    /// Get the open tab and drill-down path, to restore next session.
+    pub fn view(&self) -> LibraryView {
+        let drilled = self.view_depth > 0;
+        let artist_id = self
+            .selected_artist
+            .as_ref()
+            .filter(|_| drilled && matches!(self.tab, Tab::Artists | Tab::Favorites))
+            .map(|artist| artist.id.clone());
+        let album_id = self
+            .selected_album
+            .as_ref()
+            .filter(|_| self.album_songs_visible())
+            .map(|album| album.id.clone());
+        let genre = self
+            .selected_genre
+            .as_ref()
+            .filter(|_| drilled && self.tab == Tab::Genres)
+            .map(|genre| genre.value.clone());
+        let selected_id = self
+            .active_selected()
+            .and_then(|i| self.active_ids().get(i).map(|id| id.to_string()));
+        LibraryView {
+            tab: self.tab,
+            artist_id,
+            album_id,
+            genre,
+            selected_id,
+        }
+    }
+
+    /// Select the item restored from the last session once its list has loaded.
+    pub fn restore_pending_selection(&mut self) {
+        match &self.pending_selection {
+            Some((depth, _))
+                if !self.loading
+                    && !self.loading_tabs.contains(&self.tab)
+                    && *depth == self.view_depth
+                    && self.active_list_len() > 0 => {}
+            _ => return,
+        }
+        if let Some((_, id)) = self.pending_selection.take() {
+            if let Some(i) = self.active_ids().iter().position(|item| *item == id) {
+                self.active_list_state().select(Some(i));
+            }
+        }
+    }
+
+    /// Move selection up.
+    pub fn select_previous(&mut self) {
+        self.step_selection(-1, true);
     }
 
     /// Move selection down.
     pub fn select_next(&mut self) {
-        let len = self.active_list_len();
-        if len == 0 {
+        self.step_selection(1, true);
+    }
+
+    /// Move the selection by `delta` rows, skipping items hidden by the filter.
+    fn step_selection(&mut self, delta: isize, wrap: bool) {
+        let visible = self.active_visible();
+        let selected = self.active_list_state().selected();
+        if let Some(i) = step(&visible, selected, delta, wrap) {
+            self.active_list_state().select(Some(i));
+        }
+    }
+
+    /// Select the item shown on the given row of the active list.
+    pub fn select_row(&mut self, row: usize) {
+        // Clicks on the column headers of a sortable list select nothing
+        let row = if self.sort_headers().is_some() {
+            match row.checked_sub(1) {
+                Some(row) => row,
+                None => return,
+            }
+        } else {
+            row
+        };
+        let offset = self.active_list_state().offset();
+        if let Some(layout) = self.artist_layout() {
+            // Clicks on the header of an expanded letter select nothing
+            if let Some(i) = layout.artist_at(row + offset) {
+                self.artists_state.select(Some(i));
+            }
             return;
         }
+        // Clicks on the album header or a disc separator don't select a song
+        let row = if self.album_songs_visible() {
+            let rows = song_table_rows(
+                &self.album_songs,
+                &self.active_visible(),
+                self.groups_discs(),
+            );
+            let row = row
+                .checked_sub(ALBUM_HEADER_HEIGHT as usize)
+                .and_then(|row| rows.get(row + offset));
+            match row {
+                Some(SongRow::Song(position)) => *position,
+                _ => return,
+            }
+        } else {
+            row + offset
+        };
+        if let Some(i) = from_visible(&self.active_visible(), Some(row)) {
+            self.active_list_state().select(Some(i));
+        }
+    }
 
+    /// Select the first match if the filter hides the current selection.
+    pub fn refresh_filter(&mut self) {
+        let visible = self.active_visible();
         let selected = self.active_list_state().selected();
-        let i = match selected {
-            Some(i) => {
-                if i >= len - 1 {
-                    0
-                } else {
-                    i + 1
-                }
+        if to_visible(&visible, selected).is_none() {
+            if let Some(&first) = visible.first() {
+                self.active_list_state().select(Some(first));
             }
-            None => 0,
-        };
-        self.active_list_state().select(Some(i));
+        }
     }
 
     /// Get selected artist.
@@ -253,6 +673,32 @@ impl LibraryState {
         }
     }
 
+    /// Get selected song in the genre songs column.
+    pub fn selected_genre_song(&self) -> Option<&Song> {
+        self.genre_songs_state
+            .selected()
+            .and_then(|i| self.genre_songs.get(i))
+    }
+
+    /// Get selected history entry.
+    pub fn selected_history_item(&self) -> Option<&HistoryEntry> {
+        self.history_state
+            .selected()
+            .and_then(|i| self.history.get(i))
+    }
+
+    /// Set history and reset selection.
+    pub fn set_history(&mut self, history: Vec<HistoryEntry>) {
+        self.history = history;
+        self.history
+            .retain(|entry| !self.hidden.hides_song(&entry.song));
+        if self.history.is_empty() {
+            self.history_state.select(None);
+        } else {
+            self.history_state.select(Some(0));
+        }
+    }
+
     /// Get selected playlist.
     pub fn selected_playlist_item(&self) -> Option<&Playlist> {
         self.playlists_state
@@ -260,54 +706,388 @@ impl LibraryState {
             .and_then(|i| self.playlists.get(i))
     }
 
-    /// Set artists and reset selection.
+    /// Set artists, keeping the selected artist selected.
     pub fn set_artists(&mut self, artists: Vec<Artist>) {
+        let previous = selected_id(&self.artists, &self.artists_state, |a| &a.id);
         self.artists = artists;
-        if self.artists.is_empty() {
-            self.artists_state.select(None);
+        self.artist_groups.clear();
+        self.sorts.apply_artists(&mut self.artists);
+        reselect(&self.artists, &mut self.artists_state, previous, |a| &a.id);
+    }
+
+    /// Set artists from the server's index, listed under their index letters
+    /// while in server order.
+    pub fn set_artist_index(&mut self, index: Vec<ArtistIndex>) {
+        let mut groups = Vec::with_capacity(index.len());
+        let mut artists = Vec::with_capacity(index.iter().map(|group| group.artist.len()).sum());
+        for group in index.into_iter().filter(|group| !group.artist.is_empty()) {
+            groups.push(ArtistGroup {
+                letter: group.name,
+                start: artists.len(),
+                len: group.artist.len(),
+            });
+            artists.extend(group.artist);
+        }
+
+        self.set_artists(artists);
+        if self.sorts.artists == ArtistSort::Default && !self.sorts.is_reversed(Tab::Artists) {
+            self.collapsed_letters
+                .retain(|letter| groups.iter().any(|group| group.letter == *letter));
+            self.artist_groups = groups;
+            self.snap_artist_selection();
+        }
+    }
+
+    /// Lay out the Artists list under its index letters, unless it is sorted
+    /// another way or filtered.
+    fn artist_layout(&self) -> Option<ArtistLayout<'_>> {
+        (self.tab == Tab::Artists
+            && self.view_depth == 0
+            && self.filter.is_empty()
+            && !self.artist_groups.is_empty())
+        .then_some(ArtistLayout {
+            groups: &self.artist_groups,
+            collapsed: &self.collapsed_letters,
+        })
+    }
+
+    /// Get the index letter group of the selected artist.
+    fn selected_artist_group(&self) -> Option<&ArtistGroup> {
+        let selected = self.artists_state.selected()?;
+        self.artist_layout()?
+            .groups
+            .iter()
+            .find(|group| group.contains(selected))
+    }
+
+    /// Select the header of a collapsed letter instead of an artist hidden under it.
+    fn snap_artist_selection(&mut self) {
+        let snapped = self.artist_layout().and_then(|layout| {
+            let selected = self.artists_state.selected()?;
+            layout.artist_at(layout.row_of(selected)?)
+        });
+        if snapped.is_some() {
+            self.artists_state.select(snapped);
+        }
+    }
+
+    /// Collapse or expand the index letter of the selected artist.
+    pub fn toggle_artist_group(&mut self) {
+        let Some(letter) = self
+            .selected_artist_group()
+            .map(|group| group.letter.clone())
+        else {
+            return;
+        };
+        if !self.collapsed_letters.remove(&letter) {
+            self.collapsed_letters.insert(letter);
+        }
+        self.snap_artist_selection();
+    }
+
+    /// Collapse every index letter of the Artists list, or expand them all
+    /// if they already are.
+    pub fn toggle_artist_groups(&mut self) {
+        if self.artist_layout().is_none() {
+            return;
+        }
+        if self.collapsed_letters.len() < self.artist_groups.len() {
+            self.collapsed_letters = self
+                .artist_groups
+                .iter()
+                .map(|group| group.letter.clone())
+                .collect();
         } else {
-            self.artists_state.select(Some(0));
+            self.collapsed_letters.clear();
+        }
+        self.snap_artist_selection();
+    }
+
+    /// Expand the index letter whose header is selected. Returns whether it
+    /// was collapsed.
+    pub fn expand_selected_artist_group(&mut self) -> bool {
+        let Some(letter) = self
+            .selected_artist_group()
+            .map(|group| group.letter.clone())
+        else {
+            return false;
+        };
+        self.collapsed_letters.remove(&letter)
+    }
+
+    /// Whether an album was added since the last session.
+    pub fn is_new(&self, album: &Album) -> bool {
+        match (self.new_since, added_at(album)) {
+            (Some(since), Some(added)) => added > since,
+            _ => false,
         }
     }
 
-    /// Set albums and reset selection.
+    /// Set albums, keeping the selected album selected.
     pub fn set_albums(&mut self, albums: Vec<Album>) {
+        let previous = selected_id(&self.albums, &self.albums_state, |a| &a.id);
         self.albums = albums;
-        if self.albums.is_empty() {
-            self.albums_state.select(None);
+        let newest = self.albums.iter().filter_map(added_at).max();
+        self.newest_album = self.newest_album.max(newest);
+        self.albums.retain(|album| !self.hidden.hides_album(album));
+        self.sorts.apply_albums(&mut self.albums);
+        reselect(&self.albums, &mut self.albums_state, previous, |a| &a.id);
+    }
+
+    /// Get the paged song list currently on screen, if any.
+    pub fn active_paged_list(&self) -> Option<SongList> {
+        match self.tab {
+            Tab::Songs => Some(SongList::Songs),
+            Tab::Favorites if self.view_depth == 0 && self.favorites_section == 2 => {
+                Some(SongList::FavoriteSongs)
+            }
+            Tab::Genres if self.view_depth == 1 && self.genre_section == 1 => {
+                Some(SongList::GenreSongs)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the pagination cursor for a paged song list.
+    pub fn pages_mut(&mut self, list: SongList) -> Option<&mut PagedSongs> {
+        match list {
+            SongList::Songs => self.songs_pages.as_mut(),
+            SongList::FavoriteSongs => self.favorites_songs_pages.as_mut(),
+            SongList::GenreSongs => self.genre_songs_pages.as_mut(),
+            // Search results keep their own cursor
+            SongList::SearchSongs => None,
+        }
+    }
+
+    /// Check if a paged song list should fetch its next page.
+    pub fn wants_page(&self, list: SongList) -> bool {
+        let (songs, list_state, pages) = match list {
+            SongList::Songs => (&self.songs, &self.songs_state, &self.songs_pages),
+            SongList::FavoriteSongs => (
+                &self.favorites_songs,
+                &self.favorites_songs_state,
+                &self.favorites_songs_pages,
+            ),
+            SongList::GenreSongs => (
+                &self.genre_songs,
+                &self.genre_songs_state,
+                &self.genre_songs_pages,
+            ),
+            SongList::SearchSongs => return false,
+        };
+        pages
+            .as_ref()
+            .is_some_and(|p| p.should_prefetch(songs.len(), list_state.selected()))
+    }
+
+    /// Add a fetched page to a paged song list.
+    /// The first page replaces the list; later pages are appended, skipping duplicates.
+    pub fn add_song_page(&mut self, list: SongList, offset: u32, page: Vec<Song>) {
+        let (songs, list_state, pages) = match list {
+            SongList::Songs => (
+                &mut self.songs,
+                &mut self.songs_state,
+                &mut self.songs_pages,
+            ),
+            SongList::FavoriteSongs => (
+                &mut self.favorites_songs,
+                &mut self.favorites_songs_state,
+                &mut self.favorites_songs_pages,
+            ),
+            SongList::GenreSongs => (
+                &mut self.genre_songs,
+                &mut self.genre_songs_state,
+                &mut self.genre_songs_pages,
+            ),
+            SongList::SearchSongs => return,
+        };
+        let Some(pages) = pages else {
+            return;
+        };
+        if offset != pages.next_offset() {
+            // Stale page from before the list was reloaded
+            return;
+        }
+
+        let fetched = page.len();
+        let hidden = &self.hidden;
+        let page = page.into_iter().filter(|s| !hidden.hides_song(s));
+        if offset == 0 {
+            songs.clear();
+        }
+        let mut seen: HashSet<String> = songs.iter().map(|s| s.id.clone()).collect();
+        let before = songs.len();
+        songs.extend(page.filter(|s| seen.insert(s.id.clone())));
+        pages.page_loaded(offset, fetched, songs.len() - before);
+        if list == SongList::Songs {
+            if offset == 0 {
+                self.songs_order.clear();
+            }
+            self.songs_order
+                .extend(songs[before..].iter().map(|s| s.id.clone()));
+        }
+
+        // Sort before picking the row, so a fresh list starts on the first
+        // row shown and a later page keeps the selected song selected
+        let selected_id = list_state
+            .selected()
+            .filter(|_| offset != 0)
+            .and_then(|i| songs.get(i))
+            .map(|song| song.id.clone());
+        if list == SongList::Songs {
+            self.sorts.apply_songs(songs);
+        }
+        let selected = selected_id.and_then(|id| songs.iter().position(|song| song.id == id));
+        list_state.select((!songs.is_empty()).then(|| selected.unwrap_or(0)));
+    }
+
+    /// Re-sort the Songs tab, keeping the selected song selected.
+    fn sort_songs(&mut self) {
+        let selected_id = self.selected_song_item().map(|s| s.id.clone());
+        if self.sorts.songs == SongSort::Default {
+            let order: HashMap<&str, usize> = self
+                .songs_order
+                .iter()
+                .enumerate()
+                .map(|(i, id)| (id.as_str(), i))
+                .collect();
+            self.songs
+                .sort_by_key(|s| order.get(s.id.as_str()).copied().unwrap_or(usize::MAX));
         } else {
-            self.albums_state.select(Some(0));
+            self.sorts.apply_songs(&mut self.songs);
+        }
+        if let Some(id) = selected_id {
+            let index = self.songs.iter().position(|s| s.id == id);
+            self.songs_state.select(index);
+        }
+    }
+
+    /// Cycle the sort order of the current top-level list.
+    /// Returns `None` if the list is not sortable, otherwise whether the list
+    /// went back to server order and needs to be reloaded. The Songs tab
+    /// goes back to the order its pages came in instead.
+    pub fn cycle_sort(&mut self) -> Option<bool> {
+        if self.view_depth != 0 {
+            return None;
+        }
+
+        let sort = self.sorts.get(self.tab)?.next();
+        self.sorts.set(sort);
+        if sort.is_default() && self.tab != Tab::Songs {
+            return Some(true);
         }
+        self.resort();
+        Some(false)
     }
 
-    /// Set songs and reset selection.
-    pub fn set_songs(&mut self, songs: Vec<Song>) {
-        self.songs = songs;
-        if self.songs.is_empty() {
-            self.songs_state.select(None);
+    /// Sort the current top-level list by a column, or turn the order around
+    /// if it is already sorted by it. Returns whether the column is sortable.
+    pub fn sort_by_column(&mut self, column: usize) -> bool {
+        let Some(sort) = self
+            .sort_headers()
+            .and_then(|headers| headers.get(column).and_then(|(_, sort)| *sort))
+        else {
+            return false;
+        };
+        if self.sorts.get(self.tab) == Some(sort) {
+            self.sorts.toggle_reversed(self.tab);
         } else {
-            self.songs_state.select(Some(0));
+            self.sorts.set(sort);
+        }
+        self.resort();
+        true
+    }
+
+    /// Turn the sort order of the current top-level list around.
+    /// Returns whether it is sorted, and so could be reversed.
+    pub fn reverse_sort(&mut self) -> bool {
+        if self.view_depth != 0 || self.sorts.get(self.tab).is_none_or(TabSort::is_default) {
+            return false;
+        }
+        self.sorts.toggle_reversed(self.tab);
+        self.resort();
+        true
+    }
+
+    /// Sort the current top-level list again after its order changed.
+    fn resort(&mut self) {
+        match self.tab {
+            Tab::Artists => {
+                // Index letters only hold in server order
+                self.artist_groups.clear();
+                self.sorts.apply_artists(&mut self.artists);
+            }
+            Tab::Albums => self.sorts.apply_albums(&mut self.albums),
+            Tab::Songs => self.sort_songs(),
+            Tab::Playlists => self.sorts.apply_playlists(&mut self.playlists),
+            _ => return,
+        }
+        // Start from the top of the newly ordered list
+        let has_items = self.active_list_len() > 0;
+        self.active_list_state().select(has_items.then_some(0));
+    }
+
+    /// Get the column headers of the current top-level list with the sort
+    /// each one picks, or `None` if the list isn't sortable.
+    fn sort_headers(&self) -> Option<Vec<(&'static str, Option<TabSort>)>> {
+        if self.view_depth != 0 {
+            return None;
+        }
+        let headers = match self.tab {
+            Tab::Artists => vec![
+                ("Artist", Some(TabSort::Artists(ArtistSort::Name))),
+                ("Albums", Some(TabSort::Artists(ArtistSort::AlbumCount))),
+            ],
+            Tab::Albums => vec![
+                ("Album", Some(TabSort::Albums(AlbumSort::Name))),
+                ("Artist", Some(TabSort::Albums(AlbumSort::Artist))),
+                ("Year", Some(TabSort::Albums(AlbumSort::Year))),
+            ],
+            Tab::Songs => self
+                .song_columns
+                .iter()
+                .map(|spec| {
+                    (
+                        spec.column.header(),
+                        SongSort::for_column(spec.column).map(TabSort::Songs),
+                    )
+                })
+                .collect(),
+            Tab::Playlists => vec![
+                ("Playlist", Some(TabSort::Playlists(PlaylistSort::Name))),
+                ("Songs", Some(TabSort::Playlists(PlaylistSort::SongCount))),
+            ],
+            _ => return None,
+        };
+        Some(headers)
+    }
+
+    /// Get the label of the current tab's sort order, if sorted.
+    fn sort_label(&self) -> &'static str {
+        match self.tab {
+            Tab::Artists => self.sorts.artists.label(),
+            Tab::Albums => self.sorts.albums.label(),
+            Tab::Songs => self.sorts.songs.label(),
+            Tab::Playlists => self.sorts.playlists.label(),
+            _ => "",
         }
     }
 
-    /// Set playlists and reset selection.
+    /// Set playlists, keeping the selected playlist selected.
     pub fn set_playlists(&mut self, playlists: Vec<Playlist>) {
+        let previous = selected_id(&self.playlists, &self.playlists_state, |p| &p.id);
         self.playlists = playlists;
-        if self.playlists.is_empty() {
-            self.playlists_state.select(None);
-        } else {
-            self.playlists_state.select(Some(0));
-        }
+        self.sorts.apply_playlists(&mut self.playlists);
+        reselect(&self.playlists, &mut self.playlists_state, previous, |p| {
+            &p.id
+        });
     }
 
-    /// Set genres and reset selection.
+    /// Set genres, keeping the selected genre selected.
     pub fn set_genres(&mut self, genres: Vec<Genre>) {
+        let previous = selected_id(&self.genres, &self.genres_state, |g| &g.value);
         self.genres = genres;
-        if self.genres.is_empty() {
-            self.genres_state.select(None);
-        } else {
-            self.genres_state.select(Some(0));
-        }
+        reselect(&self.genres, &mut self.genres_state, previous, |g| &g.value);
     }
 
     /// Get selected genre.
@@ -326,32 +1106,61 @@ impl LibraryState {
 
     /// Enter genre detail view.
     pub fn enter_genre(&mut self, genre: Genre, albums: Vec<Album>) {
+        let key = format!("genre:{}", genre.value);
+        // Songs load a page at a time once the view is open
+        self.genre_songs.clear();
+        self.genre_songs_state.select(None);
+        self.genre_songs_pages = Some(PagedSongs::new(Arc::new(GenreSongs {
+            genre: genre.value.clone(),
+        })));
+        self.genre_section = 0;
         self.selected_genre = Some(genre);
         self.genre_albums = albums;
+        self.genre_albums
+            .retain(|album| !self.hidden.hides_album(album));
+        self.filter.clear();
         self.view_depth = 1;
-        if self.genre_albums.is_empty() {
-            self.genre_albums_state.select(None);
-        } else {
-            self.genre_albums_state.select(Some(0));
-        }
+        self.genre_albums_state = self.recall_position(&key, self.genre_albums.len());
     }
 
-    /// Set favorites and reset selection.
+    /// Set favorites, keeping the selected artist and album selected.
     pub fn set_favorites(&mut self, artists: Vec<Artist>, albums: Vec<Album>, songs: Vec<Song>) {
+        let previous_artist = selected_id(
+            &self.favorites_artists,
+            &self.favorites_artists_state,
+            |a| &a.id,
+        );
+        let previous_album =
+            selected_id(&self.favorites_albums, &self.favorites_albums_state, |a| {
+                &a.id
+            });
         self.favorites_artists = artists;
         self.favorites_albums = albums;
-        self.favorites_songs = songs;
-        // Reset selections - clear if empty, select first if not
-        if self.favorites_artists.is_empty() {
-            self.favorites_artists_state.select(None);
-        } else {
-            self.favorites_artists_state.select(Some(0));
-        }
-        if self.favorites_albums.is_empty() {
-            self.favorites_albums_state.select(None);
-        } else {
-            self.favorites_albums_state.select(Some(0));
-        }
+        self.favorites_albums
+            .retain(|album| !self.hidden.hides_album(album));
+        // Show the first page of songs; the rest are paged in from the same
+        // response as the list is scrolled
+        let first_page: Vec<Song> = songs.iter().take(PAGE_SIZE as usize).cloned().collect();
+        let mut pages = PagedSongs::new(Arc::new(StarredSongs::with_songs(songs)));
+        let fetched = first_page.len();
+        self.favorites_songs = first_page
+            .into_iter()
+            .filter(|song| !self.hidden.hides_song(song))
+            .collect();
+        pages.page_loaded(0, fetched, self.favorites_songs.len());
+        self.favorites_songs_pages = Some(pages);
+        reselect(
+            &self.favorites_artists,
+            &mut self.favorites_artists_state,
+            previous_artist,
+            |a| &a.id,
+        );
+        reselect(
+            &self.favorites_albums,
+            &mut self.favorites_albums_state,
+            previous_album,
+            |a| &a.id,
+        );
         if self.favorites_songs.is_empty() {
             self.favorites_songs_state.select(None);
         } else {
@@ -359,6 +1168,32 @@ impl LibraryState {
         }
     }
 
+    /// Show a song's new star state in the favorites without fetching them
+    /// again: a starred song is added at the top, an unstarred one removed.
+    pub fn patch_favorite_songs(&mut self, song: &Song) {
+        let add = song.starred.is_some() && !self.hidden.hides_song(song);
+        patch_list(
+            &mut self.favorites_songs,
+            &mut self.favorites_songs_state,
+            song,
+            add,
+            |s| &s.id,
+        );
+    }
+
+    /// Show an album's new star state in the favorites, like
+    /// [`Self::patch_favorite_songs`].
+    pub fn patch_favorite_albums(&mut self, album: &Album) {
+        let add = album.starred.is_some() && !self.hidden.hides_album(album);
+        patch_list(
+            &mut self.favorites_albums,
+            &mut self.favorites_albums_state,
+            album,
+            add,
+            |a| &a.id,
+        );
+    }
+
     /// Get selected favorite artist.
     pub fn selected_favorite_artist(&self) -> Option<&Artist> {
         self.favorites_artists_state
@@ -426,32 +1261,408 @@ impl LibraryState {
 
     /// Enter artist detail view.
     pub fn enter_artist(&mut self, artist: Artist, albums: Vec<Album>) {
+        let key = format!("artist:{}", artist.id);
         self.selected_artist = Some(artist);
         self.artist_albums = albums;
+        self.artist_albums
+            .retain(|album| !self.hidden.hides_album(album));
+        // Compilations the artist is on come after their own albums
+        self.artist_albums.sort_by_key(Album::is_compilation);
+        self.filter.clear();
         self.view_depth = 1;
-        if self.artist_albums.is_empty() {
-            self.artist_albums_state.select(None);
-        } else {
-            self.artist_albums_state.select(Some(0));
-        }
+        self.artist_albums_state = self.recall_position(&key, self.artist_albums.len());
     }
 
     /// Enter album detail view.
     pub fn enter_album(&mut self, album: Album, songs: Vec<Song>) {
+        if self.album_art.as_ref().map(|art| &art.id) != album.cover_art.as_ref() {
+            self.album_art = None;
+        }
+        let key = format!("album:{}", album.id);
         self.selected_album = Some(album);
+        self.similar_selected = None;
+        self.playlist_entries = if self.tab == Tab::Playlists {
+            songs.clone()
+        } else {
+            Vec::new()
+        };
         self.album_songs = songs;
+        self.album_songs
+            .retain(|song| !self.hidden.hides_song(song));
+        self.filter.clear();
         self.view_depth = if self.tab == Tab::Albums { 1 } else { 2 };
-        if self.album_songs.is_empty() {
-            self.album_songs_state.select(None);
+        self.album_songs_state = self.recall_position(&key, self.album_songs.len());
+    }
+
+    /// Remember where the open drill-down lists are, for when they are
+    /// reopened.
+    fn remember_positions(&mut self) {
+        if let Some(artist) = &self.selected_artist {
+            self.positions.insert(
+                format!("artist:{}", artist.id),
+                self.artist_albums_state.clone(),
+            );
+        }
+        if let Some(album) = &self.selected_album {
+            self.positions.insert(
+                format!("album:{}", album.id),
+                self.album_songs_state.clone(),
+            );
+        }
+        if let Some(genre) = &self.selected_genre {
+            self.positions.insert(
+                format!("genre:{}", genre.value),
+                self.genre_albums_state.clone(),
+            );
+        }
+    }
+
+    /// Get the remembered position of a drill-down list of `len` items, or
+    /// its first item.
+    fn recall_position(&self, key: &str, len: usize) -> ListState {
+        match self.positions.get(key) {
+            Some(state) if state.selected().is_some_and(|i| i < len) => state.clone(),
+            _ => ListState::default().with_selected((len > 0).then_some(0)),
+        }
+    }
+
+    /// Switch to `tab`, back at its top level list.
+    pub fn switch_tab(&mut self, tab: Tab) {
+        self.remember_positions();
+        self.tab = tab;
+        self.view_depth = 0;
+        self.filter.clear();
+        // Favorites always open on their artists
+        if tab == Tab::Favorites {
+            self.favorites_section = 0;
+        }
+    }
+
+    /// Get the open playlist's ID and the selected song's position in it.
+    pub fn selected_playlist_song(&self) -> Option<(String, usize)> {
+        if self.tab != Tab::Playlists || !self.album_songs_visible() {
+            return None;
+        }
+        let id = self.selected_album.as_ref()?.id.clone();
+        // Smart playlists follow their rules, not edits
+        if smart::is_smart(&id) {
+            return None;
+        }
+        Some((id, self.album_songs_state.selected()?))
+    }
+
+    /// Get the server's index of the `position`th song shown of the open
+    /// playlist, counting the hidden songs in between.
+    fn playlist_index(&self, position: usize) -> Option<usize> {
+        self.playlist_entries
+            .iter()
+            .enumerate()
+            .filter(|(_, song)| !self.hidden.hides_song(song))
+            .nth(position)
+            .map(|(i, _)| i)
+    }
+
+    /// Remove the selected song from the open playlist, returning the change
+    /// to make on the server.
+    pub fn remove_playlist_song(&mut self) -> Option<PlaylistEdit> {
+        let (playlist_id, selected) = self.selected_playlist_song()?;
+        let index = self.playlist_index(selected)?;
+        let undo = self.playlist_undo();
+
+        self.playlist_entries.remove(index);
+        self.album_songs.remove(selected);
+        if self.album_songs.is_empty() {
+            self.album_songs_state.select(None);
+        } else {
+            self.album_songs_state
+                .select(Some(selected.min(self.album_songs.len() - 1)));
+        }
+
+        Some(PlaylistEdit {
+            playlist_id,
+            add: Vec::new(),
+            remove: vec![index],
+            undo,
+        })
+    }
+
+    /// Move the selected song of the open playlist down (or up, for a
+    /// negative `delta`) past its neighbour, returning the change to make on
+    /// the server. Not while filtering, as the neighbour may not be shown.
+    pub fn move_playlist_song(&mut self, delta: isize) -> Option<PlaylistEdit> {
+        if !self.filter.is_empty() {
+            return None;
+        }
+        let (playlist_id, selected) = self.selected_playlist_song()?;
+        let target = selected
+            .checked_add_signed(delta.signum())
+            .filter(|&target| target < self.album_songs.len())?;
+        let from = self.playlist_index(selected)?;
+        let to = self.playlist_index(target)?;
+        let undo = self.playlist_undo();
+
+        self.album_songs.swap(selected, target);
+        self.playlist_entries.swap(from, to);
+        self.album_songs_state.select(Some(target));
+
+        // Songs can only be appended, so everything from the first of the two
+        // on is removed and added back in the new order
+        let start = from.min(to);
+        Some(PlaylistEdit {
+            playlist_id,
+            add: self.playlist_entries[start..]
+                .iter()
+                .map(|song| song.id.clone())
+                .collect(),
+            remove: (start..self.playlist_entries.len()).collect(),
+            undo,
+        })
+    }
+
+    fn playlist_undo(&self) -> PlaylistUndo {
+        PlaylistUndo {
+            songs: self.album_songs.clone(),
+            entries: self.playlist_entries.clone(),
+            selected: self.album_songs_state.selected(),
+        }
+    }
+
+    /// Put the open playlist back the way it was before a change the server
+    /// refused.
+    pub fn undo_playlist_edit(&mut self, edit: PlaylistEdit) {
+        let open = self.tab == Tab::Playlists
+            && self.selected_album.as_ref().map(|album| &album.id) == Some(&edit.playlist_id);
+        if !open {
+            return;
+        }
+        let PlaylistUndo {
+            songs,
+            entries,
+            selected,
+        } = edit.undo;
+        self.album_songs = songs;
+        self.album_songs
+            .retain(|song| !self.hidden.hides_song(song));
+        self.playlist_entries = entries;
+        self.album_songs_state
+            .select(selected.filter(|&i| i < self.album_songs.len()));
+    }
+
+    /// Check if the songs of `selected_album` are on screen.
+    pub fn album_songs_visible(&self) -> bool {
+        let depth = match self.tab {
+            Tab::Albums | Tab::Playlists => 1,
+            Tab::Artists | Tab::Genres | Tab::Favorites => 2,
+            Tab::Songs | Tab::History => return false,
+        };
+        self.selected_album.is_some() && self.view_depth >= depth
+    }
+
+    /// Albums like the open one, to show below its songs.
+    pub fn similar_albums(&self) -> &[Album] {
+        match (&self.similar_albums, &self.selected_album) {
+            (Some((id, albums)), Some(album))
+                if *id == album.id && self.tab != Tab::Playlists && self.album_songs_visible() =>
+            {
+                albums
+            }
+            _ => &[],
+        }
+    }
+
+    /// Whether albums like `album_id` still need looking for.
+    pub fn similar_albums_needed(&self, album_id: &str) -> bool {
+        self.similar_albums
+            .as_ref()
+            .is_none_or(|(id, _)| id != album_id)
+    }
+
+    /// Set the albums like `album_id`.
+    pub fn set_similar_albums(&mut self, album_id: String, mut albums: Vec<Album>) {
+        albums.retain(|album| !self.hidden.hides_album(album) && album.id != album_id);
+        self.similar_albums = Some((album_id, albums));
+        self.similar_selected = None;
+    }
+
+    /// Pick the next (or, with a negative `delta`, previous) similar album,
+    /// wrapping around.
+    pub fn step_similar(&mut self, delta: isize) {
+        let len = self.similar_albums().len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.similar_selected {
+            Some(i) => (i as isize + delta).rem_euclid(len as isize) as usize,
+            None if delta < 0 => len - 1,
+            None => 0,
+        };
+        self.similar_selected = Some(i);
+    }
+
+    /// Put the similar albums down, giving Enter and `a` back to the songs.
+    /// Returns whether one was picked.
+    pub fn unpick_similar(&mut self) -> bool {
+        self.similar_selected.take().is_some()
+    }
+
+    /// Get the similar album picked, if any.
+    pub fn selected_similar_album(&self) -> Option<&Album> {
+        self.similar_albums().get(self.similar_selected?)
+    }
+
+    /// Whether album songs are grouped by disc (albums only, not playlists).
+    fn groups_discs(&self) -> bool {
+        self.tab != Tab::Playlists
+    }
+
+    /// Cover art ID the album header is waiting for, if its art isn't loaded yet.
+    pub fn album_art_needed(&self) -> Option<&str> {
+        let cover_art = self.selected_album.as_ref()?.cover_art.as_deref()?;
+        match &self.album_art {
+            Some(art) if art.id == cover_art => None,
+            _ => Some(cover_art),
+        }
+    }
+
+    /// Set the album header art if it belongs to the selected album.
+    pub fn set_album_art(&mut self, id: &str, image: DynamicImage, picker: Option<&Picker>) {
+        if self.album_art_needed() != Some(id) {
+            return;
+        }
+        if let Some(picker) = picker {
+            self.album_art = Some(AlbumArt {
+                id: id.to_string(),
+                protocol: picker.new_resize_protocol(image),
+            });
+        }
+    }
+
+    /// Switch between the albums and songs columns of a genre.
+    pub fn toggle_genre_section(&mut self) {
+        self.genre_section = 1 - self.genre_section.min(1);
+    }
+
+    /// Get the song or album under the cursor, for hiding.
+    pub fn selected_hide_target(&self) -> Option<HideTarget> {
+        let song = |song: &Song| HideTarget::Song {
+            id: song.id.clone(),
+            title: song.title.clone(),
+        };
+        let album = |album: &Album| HideTarget::Album {
+            id: album.id.clone(),
+            name: album.name.clone(),
+        };
+
+        match (self.tab, self.view_depth) {
+            (Tab::Songs, _) => self.selected_song_item().map(song),
+            (Tab::History, _) => self.selected_history_item().map(|e| song(&e.song)),
+            (Tab::Albums, 0) | (Tab::Artists, 1) | (Tab::Favorites, 1) => {
+                self.selected_album_item().map(album)
+            }
+            (Tab::Genres, 1) if self.genre_section == 1 => self.selected_genre_song().map(song),
+            (Tab::Genres, 1) => self.selected_genre_album_item().map(album),
+            (Tab::Favorites, 0) => match self.favorites_section {
+                0 => None,
+                1 => self.selected_favorite_album().map(album),
+                _ => self.selected_favorite_song().map(song),
+            },
+            (Tab::Artists | Tab::Genres | Tab::Playlists, 0) => None,
+            _ => self.selected_song_item().map(song),
+        }
+    }
+
+    /// Hide an item and drop it (or an album's songs) from every list.
+    pub fn hide(&mut self, target: HideTarget) {
+        self.hidden.hide(target);
+        self.drop_hidden();
+    }
+
+    /// Replace the hidden items, dropping any newly hidden ones from the lists.
+    pub fn set_hidden(&mut self, hidden: HiddenItems) {
+        self.hidden = hidden;
+        self.drop_hidden();
+    }
+
+    /// Remove hidden items from all loaded lists.
+    fn drop_hidden(&mut self) {
+        let hidden = &self.hidden;
+        let song = |s: &Song| !hidden.hides_song(s);
+        let album = |a: &Album| !hidden.hides_album(a);
+
+        retain_list(&mut self.albums, &mut self.albums_state, album);
+        retain_list(
+            &mut self.artist_albums,
+            &mut self.artist_albums_state,
+            album,
+        );
+        retain_list(&mut self.genre_albums, &mut self.genre_albums_state, album);
+        retain_list(
+            &mut self.favorites_albums,
+            &mut self.favorites_albums_state,
+            album,
+        );
+        retain_list(&mut self.songs, &mut self.songs_state, song);
+        retain_list(&mut self.album_songs, &mut self.album_songs_state, song);
+        retain_list(&mut self.genre_songs, &mut self.genre_songs_state, song);
+        retain_list(
+            &mut self.favorites_songs,
+            &mut self.favorites_songs_state,
+            song,
+        );
+        retain_list(&mut self.history, &mut self.history_state, |e| {
+            song(&e.song)
+        });
+    }
+
+    /// Get the queue source for songs in the album (or playlist) detail view.
+    pub fn album_songs_source(&self) -> QueueSource {
+        let (id, name) = self
+            .selected_album
+            .as_ref()
+            .map(|album| (album.id.clone(), album.name.clone()))
+            .unwrap_or_default();
+        if self.tab == Tab::Playlists {
+            QueueSource::Playlist { id, name }
         } else {
-            self.album_songs_state.select(Some(0));
+            QueueSource::Album { id, name }
+        }
+    }
+
+    /// Limits for random songs drawn from what is being browsed: the open genre,
+    /// or the year range of the Albums tab. Empty for the whole library.
+    pub fn random_filter(&self) -> RandomFilter {
+        match (self.tab, &self.selected_genre, self.album_list) {
+            (Tab::Genres, Some(genre), _) if self.view_depth > 0 => RandomFilter {
+                genre: Some(genre.value.clone()),
+                ..Default::default()
+            },
+            (Tab::Albums, _, AlbumList::ByYear { from, to }) => RandomFilter {
+                from_year: Some(from.min(to)),
+                to_year: Some(from.max(to)),
+                ..Default::default()
+            },
+            _ => RandomFilter::default(),
+        }
+    }
+
+    /// Get the queue source for songs picked from the genre view.
+    pub fn genre_source(&self) -> QueueSource {
+        QueueSource::Genre {
+            name: self
+                .selected_genre
+                .as_ref()
+                .map(|genre| genre.value.clone())
+                .unwrap_or_default(),
         }
     }
 
     /// Go back to previous view.
     pub fn go_back(&mut self) {
         if self.view_depth > 0 {
+            self.remember_positions();
+            self.similar_selected = None;
             self.view_depth -= 1;
+            self.filter.clear();
             if self.view_depth == 0 {
                 self.selected_artist = None;
                 self.selected_album = None;
@@ -464,48 +1675,111 @@ impl LibraryState {
 
     /// Jump to the top of the current list.
     pub fn jump_to_top(&mut self) {
-        if self.active_list_len() > 0 {
-            self.active_list_state().select(Some(0));
+        if let Some(&first) = self.active_visible().first() {
+            self.active_list_state().select(Some(first));
         }
     }
 
     /// Jump to the bottom of the current list.
     pub fn jump_to_bottom(&mut self) {
-        let len = self.active_list_len();
-        if len > 0 {
-            self.active_list_state().select(Some(len - 1));
+        if let Some(&last) = self.active_visible().last() {
+            self.active_list_state().select(Some(last));
         }
     }
 
     /// Scroll half a page down.
     pub fn scroll_half_page_down(&mut self, page_size: usize) {
-        let len = self.active_list_len();
-        if len == 0 {
-            return;
-        }
-
-        let half_page = page_size / 2;
-        let current = self.active_list_state().selected().unwrap_or(0);
-        let new_index = (current + half_page).min(len - 1);
-        self.active_list_state().select(Some(new_index));
+        self.step_selection((page_size / 2) as isize, false);
     }
 
     /// Scroll half a page up.
     pub fn scroll_half_page_up(&mut self, page_size: usize) {
-        let len = self.active_list_len();
-        if len == 0 {
-            return;
-        }
+        self.step_selection(-((page_size / 2) as isize), false);
+    }
+}
+
+/// When an album was added to the library, if the server says.
+fn added_at(album: &Album) -> Option<DateTime<Utc>> {
+    let created = album.created.as_deref()?;
+    DateTime::parse_from_rfc3339(created)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Get the position and ID of the selected item.
+fn selected_id<T>(items: &[T], state: &ListState, id: fn(&T) -> &str) -> Option<(usize, String)> {
+    let i = state.selected()?;
+    items.get(i).map(|item| (i, id(item).to_string()))
+}
 
-        let half_page = page_size / 2;
-        let current = self.active_list_state().selected().unwrap_or(0);
-        let new_index = current.saturating_sub(half_page);
-        self.active_list_state().select(Some(new_index));
+/// Select the item that was `previous` again after the list was replaced, or
+/// whatever is now in its place if it is gone. The scroll offset is kept.
+fn reselect<T>(
+    items: &[T],
+    state: &mut ListState,
+    previous: Option<(usize, String)>,
+    id: fn(&T) -> &str,
+) {
+    if items.is_empty() {
+        state.select(None);
+        return;
+    }
+    let i = match previous {
+        Some((i, previous)) => items
+            .iter()
+            .position(|item| id(item) == previous)
+            .unwrap_or(i.min(items.len() - 1)),
+        None => 0,
+    };
+    state.select(Some(i));
+}
+
+/// Put `item` at the top of a list, or take it out, leaving the selection on
+/// the same item.
+fn patch_list<T: Clone>(
+    items: &mut Vec<T>,
+    state: &mut ListState,
+    item: &T,
+    add: bool,
+    id: fn(&T) -> &str,
+) {
+    let previous = selected_id(items, state, id);
+    items.retain(|other| id(other) != id(item));
+    if add {
+        items.insert(0, item.clone());
+    }
+    reselect(items, state, previous, id);
+}
+
+/// Keep only the items passing `keep`, leaving the selection on the same item
+/// (or the one that took its place if it was removed).
+fn retain_list<T>(items: &mut Vec<T>, state: &mut ListState, keep: impl Fn(&T) -> bool) {
+    let kept: Vec<bool> = items.iter().map(keep).collect();
+    if kept.iter().all(|&k| k) {
+        return;
+    }
+
+    let selected = state
+        .selected()
+        .map(|i| kept.iter().take(i).filter(|&&k| k).count());
+    let mut flags = kept.iter();
+    items.retain(|_| flags.next().copied().unwrap_or(true));
+
+    if items.is_empty() {
+        state.select(None);
+    } else {
+        state.select(selected.map(|i| i.min(items.len() - 1)));
     }
 }
 
 /// Render the library view.
-pub fn render_library(frame: &mut Frame, area: Rect, state: &mut LibraryState, focused: bool) {
+pub fn render_library(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut LibraryState,
+    focused: bool,
+    hits: &mut HitRegions,
+) {
     let title: String = match state.tab {
         Tab::Artists => {
             if state.view_depth == 0 {
@@ -527,7 +1801,7 @@ pub fn render_library(frame: &mut Frame, area: Rect, state: &mut LibraryState, f
         }
         Tab::Albums => {
             if state.view_depth == 0 {
-                String::from("Albums")
+                state.album_list.label()
             } else {
                 state
                     .selected_album
@@ -536,7 +1810,22 @@ pub fn render_library(frame: &mut Frame, area: Rect, state: &mut LibraryState, f
                     .unwrap_or_else(|| String::from("Album"))
             }
         }
-        Tab::Songs => String::from("Songs"),
+        Tab::Songs => {
+            let more = state
+                .songs_pages
+                .as_ref()
+                .is_some_and(|p| !p.is_exhausted());
+            let label = match (state.songs_source, state.songs_filter.describe()) {
+                (SongsSource::Random, Some(filter)) => format!("Random Songs · {}", filter),
+                (source, _) => source.label().to_string(),
+            };
+            format!(
+                "{} ({}{})",
+                label,
+                state.songs.len(),
+                if more { "+" } else { "" }
+            )
+        }
         Tab::Playlists => {
             if state.view_depth == 0 {
                 String::from("Playlists")
@@ -578,7 +1867,16 @@ pub fn render_library(frame: &mut Frame, area: Rect, state: &mut LibraryState, f
                     .unwrap_or_else(|| String::from("Album"))
             }
         }
+        Tab::History => format!("History ({})", state.history.len()),
+    };
+
+    let sort_label = state.sort_label();
+    let title = if state.view_depth == 0 && !sort_label.is_empty() {
+        format!("{} · by {}", title, sort_label)
+    } else {
+        title
     };
+    let title = filter_title(title, &state.filter, state.filtering);
 
     let border_color = if focused {
         Color::Cyan
@@ -590,7 +1888,8 @@ pub fn render_library(frame: &mut Frame, area: Rect, state: &mut LibraryState, f
         .title(title)
         .border_style(Style::default().fg(border_color));
 
-    if state.loading {
+    let tab_loading = state.view_depth == 0 && state.loading_tabs.contains(&state.tab);
+    if state.loading || tab_loading {
         let loading = Paragraph::new("Loading...")
             .style(Style::default().fg(Color::Yellow))
             .block(block);
@@ -599,76 +1898,129 @@ pub fn render_library(frame: &mut Frame, area: Rect, state: &mut LibraryState, f
     }
 
     match state.tab {
-        Tab::Artists => render_artists_view(frame, area, state, block),
-        Tab::Albums => render_albums_view(frame, area, state, block),
-        Tab::Songs => render_songs_view(frame, area, state, block),
-        Tab::Playlists => render_playlists_view(frame, area, state, block),
+        Tab::Artists => render_artists_view(frame, area, state, block, hits),
+        Tab::Albums => render_albums_view(frame, area, state, block, hits),
+        Tab::Songs => render_songs_view(frame, area, state, block, hits),
+        Tab::Playlists => render_playlists_view(frame, area, state, block, hits),
         Tab::Genres => render_genres_view(frame, area, state, block),
         Tab::Favorites => render_favorites_view(frame, area, state, block),
+        Tab::History => render_history_view(frame, area, state, block),
     }
 }
 
-fn render_artists_view(frame: &mut Frame, area: Rect, state: &mut LibraryState, block: Block) {
+fn render_artists_view(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut LibraryState,
+    block: Block,
+    hits: &mut HitRegions,
+) {
     if state.view_depth == 0 {
         // Artist list with columns: Artist Name | Album Count
-        let mut table_state = TableState::default();
-        table_state.select(state.artists_state.selected());
-        let selected_idx = table_state.selected();
-
-        let rows: Vec<Row> = state
-            .artists
-            .iter()
-            .enumerate()
-            .map(|(i, artist)| {
-                let is_selected = selected_idx == Some(i);
-                let album_count = artist
-                    .album_count
-                    .map(|c| format!("{} albums", c))
-                    .unwrap_or_default();
-
-                let (name_style, count_style) = if is_selected {
-                    (
-                        Style::default()
-                            .fg(Color::White)
-                            .add_modifier(Modifier::BOLD),
-                        Style::default().fg(Color::Gray),
-                    )
-                } else {
-                    (
-                        Style::default().fg(Color::White),
-                        Style::default().fg(Color::DarkGray),
-                    )
-                };
-
-                Row::new(vec![
-                    Cell::from(artist.name.clone()).style(name_style),
-                    Cell::from(album_count).style(count_style),
-                ])
+        let widths = [
+            Constraint::Percentage(75), // Artist name
+            Constraint::Percentage(25), // Album count
+        ];
+        let header = sort_header(state, block.inner(area), &widths, hits);
+        let height = block
+            .inner(area)
+            .height
+            .saturating_sub(u16::from(header.is_some())) as usize;
+        let selected_idx = state.artists_state.selected();
+
+        // Only the rows on screen are built
+        let layout = state.artist_layout();
+        let visible = match layout {
+            Some(_) => Vec::new(),
+            None => visible_indices(&state.artists, &state.filter),
+        };
+        let (row_count, selected_row) = match &layout {
+            Some(layout) => (layout.len(), selected_idx.and_then(|i| layout.row_of(i))),
+            None => (visible.len(), to_visible(&visible, selected_idx)),
+        };
+        let offset = scroll_offset(
+            state.artists_state.offset(),
+            selected_row,
+            height,
+            row_count,
+        );
+        let rows: Vec<Row> = (offset..row_count.min(offset + height))
+            .filter_map(|row| match &layout {
+                Some(layout) => layout.row(row),
+                None => Some(ArtistRow::Artist(visible[row])),
+            })
+            .map(|row| match row {
+                ArtistRow::Letter(position) => {
+                    let layout = layout
+                        .as_ref()
+                        .expect("letters are only laid out in groups");
+                    let group = &layout.groups[position];
+                    let icon = if layout.shown(group) == 0 {
+                        state.icons.collapsed
+                    } else {
+                        state.icons.expanded
+                    };
+                    Row::new(vec![
+                        Cell::from(format!("{} {}", icon, group.letter)).style(
+                            Style::default()
+                                .fg(Color::Yellow)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Cell::from(format!("{} artists", group.len))
+                            .style(Style::default().fg(Color::DarkGray)),
+                    ])
+                }
+                ArtistRow::Artist(i) => {
+                    let artist = &state.artists[i];
+                    let is_selected = selected_idx == Some(i);
+                    let album_count = artist
+                        .album_count
+                        .map(|c| format!("{} albums", c))
+                        .unwrap_or_default();
+
+                    let (name_style, count_style) = if is_selected {
+                        (
+                            Style::default()
+                                .fg(Color::White)
+                                .add_modifier(Modifier::BOLD),
+                            Style::default().fg(Color::Gray),
+                        )
+                    } else {
+                        (
+                            Style::default().fg(Color::White),
+                            Style::default().fg(Color::DarkGray),
+                        )
+                    };
+
+                    Row::new(vec![
+                        Cell::from(artist.name.clone()).style(name_style),
+                        Cell::from(album_count).style(count_style),
+                    ])
+                }
             })
             .collect();
 
-        let table = Table::new(
-            rows,
-            [
-                Constraint::Percentage(75), // Artist name
-                Constraint::Percentage(25), // Album count
-            ],
-        )
-        .block(block)
-        .row_highlight_style(Style::default().bg(Color::DarkGray));
+        let mut table_state =
+            TableState::default().with_selected(selected_row.map(|row| row - offset));
+        let mut table = Table::new(rows, widths)
+            .block(block)
+            .row_highlight_style(Style::default().bg(Color::DarkGray));
+        if let Some(header) = header {
+            table = table.header(header);
+        }
 
         frame.render_stateful_widget(table, area, &mut table_state);
-        *state.artists_state.selected_mut() = table_state.selected();
+        render_scrollbar(frame, area, row_count, height, offset);
+        *state.artists_state.offset_mut() = offset;
     } else if state.view_depth == 1 {
         // Artist albums with columns: Album Name | Year
-        let mut table_state = TableState::default();
-        table_state.select(state.artist_albums_state.selected());
-        let selected_idx = table_state.selected();
-
-        let rows: Vec<Row> = state
-            .artist_albums
+        let mut table_state = TableState::default().with_offset(state.artist_albums_state.offset());
+        let visible = visible_indices(&state.artist_albums, &state.filter);
+        table_state.select(to_visible(&visible, state.artist_albums_state.selected()));
+        let selected_idx = state.artist_albums_state.selected();
+        let rows: Vec<Row> = visible
             .iter()
-            .enumerate()
+            .map(|&i| (i, &state.artist_albums[i]))
             .map(|(i, album)| {
                 let is_selected = selected_idx == Some(i);
                 let year = album.year.map(|y| y.to_string()).unwrap_or_default();
@@ -688,7 +2040,7 @@ fn render_artists_view(frame: &mut Frame, area: Rect, state: &mut LibraryState,
                 };
 
                 Row::new(vec![
-                    Cell::from(album.name.clone()).style(name_style),
+                    Cell::from(artist_album_name(album)).style(name_style),
                     Cell::from(year).style(year_style),
                 ])
             })
@@ -705,33 +2057,34 @@ fn render_artists_view(frame: &mut Frame, area: Rect, state: &mut LibraryState,
         .row_highlight_style(Style::default().bg(Color::DarkGray));
 
         frame.render_stateful_widget(table, area, &mut table_state);
-        *state.artist_albums_state.selected_mut() = table_state.selected();
+        render_table_scrollbar(frame, area, &table_state, visible.len(), false);
+        *state.artist_albums_state.selected_mut() = from_visible(&visible, table_state.selected());
+        *state.artist_albums_state.offset_mut() = table_state.offset();
     } else {
         // Album songs (depth 2)
-        render_song_list(
-            frame,
-            area,
-            &state.album_songs,
-            &mut state.album_songs_state,
-            block,
-        );
+        render_album_songs(frame, area, state, block);
     }
 }
 
-fn render_albums_view(frame: &mut Frame, area: Rect, state: &mut LibraryState, block: Block) {
+fn render_albums_view(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut LibraryState,
+    block: Block,
+    hits: &mut HitRegions,
+) {
     if state.view_depth == 0 {
         // Album list with columns: Album Name | Artist | Year
-        let mut table_state = TableState::default();
-        table_state.select(state.albums_state.selected());
-        let selected_idx = table_state.selected();
-
-        let rows: Vec<Row> = state
-            .albums
+        let mut table_state = TableState::default().with_offset(state.albums_state.offset());
+        let visible = visible_indices(&state.albums, &state.filter);
+        table_state.select(to_visible(&visible, state.albums_state.selected()));
+        let selected_idx = state.albums_state.selected();
+        let rows: Vec<Row> = visible
             .iter()
-            .enumerate()
+            .map(|&i| (i, &state.albums[i]))
             .map(|(i, album)| {
                 let is_selected = selected_idx == Some(i);
-                let artist = album.artist.as_deref().unwrap_or("Unknown Artist");
+                let artist = album.album_artist().unwrap_or("Unknown Artist");
                 let year = album.year.map(|y| y.to_string()).unwrap_or_default();
 
                 let (name_style, artist_style, year_style) = if is_selected {
@@ -750,54 +2103,93 @@ fn render_albums_view(frame: &mut Frame, area: Rect, state: &mut LibraryState, b
                     )
                 };
 
+                let mut name = vec![Span::raw(album.name.clone())];
+                if state.is_new(album) {
+                    name.push(Span::styled(
+                        " NEW",
+                        Style::default()
+                            .fg(Color::LightGreen)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                }
+
                 Row::new(vec![
-                    Cell::from(album.name.clone()).style(name_style),
+                    Cell::from(Line::from(name)).style(name_style),
                     Cell::from(artist.to_string()).style(artist_style),
                     Cell::from(year).style(year_style),
                 ])
             })
             .collect();
 
-        let table = Table::new(
-            rows,
-            [
-                Constraint::Percentage(50), // Album name
-                Constraint::Percentage(40), // Artist
-                Constraint::Length(6),      // Year
-            ],
-        )
-        .block(block)
-        .row_highlight_style(Style::default().bg(Color::DarkGray));
+        let widths = [
+            Constraint::Percentage(50), // Album name
+            Constraint::Percentage(40), // Artist
+            Constraint::Length(6),      // Year
+        ];
+        let header = sort_header(state, block.inner(area), &widths, hits);
+        let mut table = Table::new(rows, widths)
+            .block(block)
+            .row_highlight_style(Style::default().bg(Color::DarkGray));
+        if let Some(header) = header {
+            table = table.header(header);
+        }
 
         frame.render_stateful_widget(table, area, &mut table_state);
-        *state.albums_state.selected_mut() = table_state.selected();
+        render_table_scrollbar(frame, area, &table_state, visible.len(), true);
+        *state.albums_state.selected_mut() = from_visible(&visible, table_state.selected());
+        *state.albums_state.offset_mut() = table_state.offset();
     } else {
         // Album songs
-        render_song_list(
-            frame,
-            area,
-            &state.album_songs,
-            &mut state.album_songs_state,
-            block,
-        );
+        render_album_songs(frame, area, state, block);
     }
 }
 
-fn render_songs_view(frame: &mut Frame, area: Rect, state: &mut LibraryState, block: Block) {
-    render_song_list(frame, area, &state.songs, &mut state.songs_state, block);
+fn render_songs_view(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut LibraryState,
+    block: Block,
+    hits: &mut HitRegions,
+) {
+    let widths: Vec<Constraint> = state
+        .song_columns
+        .iter()
+        .map(ColumnSpec::constraint)
+        .collect();
+    let table = SongTable {
+        filter: &state.filter,
+        columns: &state.song_columns,
+        playing_id: state.playing_id.as_deref(),
+        group_discs: false,
+        header: sort_header(state, block.inner(area), &widths, hits),
+        scrollbar: area,
+    };
+    render_song_list(
+        frame,
+        area,
+        &state.songs,
+        &mut state.songs_state,
+        table,
+        block,
+    );
 }
 
-fn render_playlists_view(frame: &mut Frame, area: Rect, state: &mut LibraryState, block: Block) {
+fn render_playlists_view(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut LibraryState,
+    block: Block,
+    hits: &mut HitRegions,
+) {
     if state.view_depth == 0 {
         // Playlist list with columns: Playlist Name | Song Count
-        let mut table_state = TableState::default();
-        table_state.select(state.playlists_state.selected());
-        let selected_idx = table_state.selected();
-
-        let rows: Vec<Row> = state
-            .playlists
+        let mut table_state = TableState::default().with_offset(state.playlists_state.offset());
+        let visible = visible_indices(&state.playlists, &state.filter);
+        table_state.select(to_visible(&visible, state.playlists_state.selected()));
+        let selected_idx = state.playlists_state.selected();
+        let rows: Vec<Row> = visible
             .iter()
-            .enumerate()
+            .map(|&i| (i, &state.playlists[i]))
             .map(|(i, playlist)| {
                 let is_selected = selected_idx == Some(i);
                 let count = playlist
@@ -826,113 +2218,642 @@ fn render_playlists_view(frame: &mut Frame, area: Rect, state: &mut LibraryState
             })
             .collect();
 
-        let table = Table::new(
-            rows,
-            [
-                Constraint::Percentage(75), // Playlist name
-                Constraint::Percentage(25), // Song count
-            ],
-        )
-        .block(block)
-        .row_highlight_style(Style::default().bg(Color::DarkGray));
+        let widths = [
+            Constraint::Percentage(75), // Playlist name
+            Constraint::Percentage(25), // Song count
+        ];
+        let header = sort_header(state, block.inner(area), &widths, hits);
+        let mut table = Table::new(rows, widths)
+            .block(block)
+            .row_highlight_style(Style::default().bg(Color::DarkGray));
+        if let Some(header) = header {
+            table = table.header(header);
+        }
 
         frame.render_stateful_widget(table, area, &mut table_state);
-        *state.playlists_state.selected_mut() = table_state.selected();
+        render_table_scrollbar(frame, area, &table_state, visible.len(), true);
+        *state.playlists_state.selected_mut() = from_visible(&visible, table_state.selected());
+        *state.playlists_state.offset_mut() = table_state.offset();
     } else {
         // Playlist songs
-        render_song_list(
-            frame,
-            area,
-            &state.album_songs,
-            &mut state.album_songs_state,
-            block,
-        );
+        render_album_songs(frame, area, state, block);
     }
 }
 
-fn render_song_list(
-    frame: &mut Frame,
-    area: Rect,
-    songs: &[Song],
-    list_state: &mut ListState,
-    block: Block,
-) {
-    // Convert ListState to TableState
-    let mut table_state = TableState::default();
-    table_state.select(list_state.selected());
+/// Render an album's (or playlist's) songs below a header with its art and details.
+fn render_album_songs(frame: &mut Frame, area: Rect, state: &mut LibraryState, block: Block) {
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
 
-    let selected_idx = table_state.selected();
+    let similar_height = if state.similar_albums().is_empty() {
+        0
+    } else {
+        SIMILAR_ALBUMS_HEIGHT
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(ALBUM_HEADER_HEIGHT),
+            Constraint::Min(0),
+            Constraint::Length(similar_height),
+        ])
+        .split(inner);
+    render_similar_albums(frame, chunks[2], state);
+
+    if let Some(album) = &state.selected_album {
+        let is_playlist = state.tab == Tab::Playlists;
+        let header = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(ALBUM_ART_WIDTH + 1), Constraint::Min(0)])
+            .split(chunks[0]);
 
-    let rows: Vec<Row> = songs
-        .iter()
-        .enumerate()
-        .map(|(i, song)| {
-            let is_selected = selected_idx == Some(i);
+        let art_area = Rect {
+            width: ALBUM_ART_WIDTH,
+            height: ALBUM_HEADER_HEIGHT - 1,
+            ..header[0]
+        };
+        match state.album_art.as_mut() {
+            Some(art) => {
+                frame.render_stateful_widget(StatefulImage::default(), art_area, &mut art.protocol)
+            }
+            None => frame.render_widget(
+                Paragraph::new(format!("\n  {}", state.icons.album))
+                    .style(Style::default().fg(Color::DarkGray))
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(Color::DarkGray)),
+                    ),
+                art_area,
+            ),
+        }
 
-            let track = song
-                .track
-                .map(|t| format!("{:02}", t))
-                .unwrap_or_else(|| format!("{:02}", i + 1));
-            let duration = song.duration_string();
-            let artist = song.display_artist();
+        let dim = Style::default().fg(Color::DarkGray);
+        let separator = || Span::styled(" · ", dim);
 
-            // Use brighter colors for selected row
-            let (track_style, title_style, artist_style, duration_style) = if is_selected {
-                (
-                    Style::default().fg(Color::Gray),
+        let mut details: Vec<Span> = Vec::new();
+        if let Some(year) = album.year {
+            details.push(Span::styled(
+                year.to_string(),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if let Some(genre) = &album.genre {
+            if !details.is_empty() {
+                details.push(separator());
+            }
+            details.push(Span::styled(
+                genre.clone(),
+                Style::default().fg(Color::Magenta),
+            ));
+        }
+        if !details.is_empty() {
+            details.push(separator());
+        }
+        let total: i32 = state.album_songs.iter().filter_map(|s| s.duration).sum();
+        details.push(Span::styled(
+            format!(
+                "{} songs, {}",
+                state.album_songs.len(),
+                format_total_duration(total)
+            ),
+            Style::default().fg(Color::Gray),
+        ));
+
+        let byline = if is_playlist { "by " } else { "" };
+        let mut lines = vec![
+            Line::from(Span::styled(
+                album.name.clone(),
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(vec![
+                Span::styled(byline, dim),
+                Span::styled(
+                    album.album_artist().unwrap_or_default().to_string(),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ]),
+            Line::from(details),
+        ];
+
+        let key = |k: &'static str| Span::styled(k, Style::default().fg(Color::Yellow));
+        let mut hints = vec![
+            key("P"),
+            Span::styled(" play  ", dim),
+            key("Z"),
+            Span::styled(" shuffle  ", dim),
+            key("A"),
+            Span::styled(" queue", dim),
+        ];
+        if !is_playlist {
+            lines.push(if album.starred.is_some() {
+                Line::from(Span::styled(
+                    "★ Starred",
+                    Style::default().fg(Color::Yellow),
+                ))
+            } else {
+                Line::from(Span::styled("☆ Not starred", dim))
+            });
+            hints.push(Span::styled("  ", dim));
+            hints.push(key("F"));
+            hints.push(Span::styled(" star", dim));
+            hints.push(Span::styled("  ", dim));
+            hints.push(key("i"));
+            hints.push(Span::styled(" info", dim));
+        }
+        lines.push(Line::from(hints));
+
+        frame.render_widget(Paragraph::new(lines), header[1]);
+    }
+
+    let table = SongTable {
+        filter: &state.filter,
+        columns: &state.song_columns,
+        playing_id: state.playing_id.as_deref(),
+        group_discs: state.groups_discs(),
+        header: None,
+        // The songs have no border of their own; use the panel's, below the album header
+        scrollbar: Rect {
+            y: chunks[1].y.saturating_sub(1),
+            height: chunks[1].height + 2,
+            ..area
+        },
+    };
+    render_song_list(
+        frame,
+        chunks[1],
+        &state.album_songs,
+        &mut state.album_songs_state,
+        table,
+        Block::default(),
+    );
+}
+
+/// Name of an album in an artist's albums, with compilations marked.
+fn artist_album_name(album: &Album) -> Line<'static> {
+    let mut name = vec![Span::raw(album.name.clone())];
+    if album.is_compilation() {
+        name.push(Span::styled(
+            " · compilation",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    Line::from(name)
+}
+
+/// Render the "You might also like" row of albums below an album's songs.
+fn render_similar_albums(frame: &mut Frame, area: Rect, state: &LibraryState) {
+    let albums = state.similar_albums();
+    if albums.is_empty() || area.height < SIMILAR_ALBUMS_HEIGHT {
+        return;
+    }
+    let dim = Style::default().fg(Color::DarkGray);
+    let key = |k: &'static str| Span::styled(k, Style::default().fg(Color::Yellow));
+    let mut title = vec![
+        Span::styled("You might also like  ", Style::default().fg(Color::Cyan)),
+        key(";"),
+        Span::styled(" pick", dim),
+    ];
+    if state.similar_selected.is_some() {
+        title.extend([
+            Span::styled("  ", dim),
+            key("Enter"),
+            Span::styled(" open  ", dim),
+            key("a"),
+            Span::styled(" queue  ", dim),
+            key("Esc"),
+            Span::styled(" back", dim),
+        ]);
+    }
+
+    const GAP: &str = "  ·  ";
+    let labels: Vec<String> = albums
+        .iter()
+        .map(|album| match &album.artist {
+            Some(artist) => format!("{} – {}", album.name, artist),
+            None => album.name.clone(),
+        })
+        .collect();
+    let widths: Vec<usize> = labels.iter().map(|label| label.chars().count()).collect();
+    let start = strip_start(
+        &widths,
+        state.similar_selected,
+        area.width as usize,
+        GAP.chars().count(),
+    );
+    let mut row = Vec::new();
+    for (i, label) in labels.into_iter().enumerate().skip(start) {
+        if i > start {
+            row.push(Span::styled(GAP, dim));
+        }
+        let style = if state.similar_selected == Some(i) {
+            Style::default()
+                .fg(Color::White)
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        row.push(Span::styled(label, style));
+    }
+
+    frame.render_widget(
+        Paragraph::new(vec![Line::default(), Line::from(title), Line::from(row)]),
+        area,
+    );
+}
+
+/// First item to show of a row of items `widths` wide, so the selected one
+/// fits in `width` columns.
+fn strip_start(widths: &[usize], selected: Option<usize>, width: usize, gap: usize) -> usize {
+    let Some(selected) = selected.filter(|&i| i < widths.len()) else {
+        return 0;
+    };
+    let mut start = 0;
+    while start < selected
+        && widths[start..=selected].iter().sum::<usize>() + gap * (selected - start) > width
+    {
+        start += 1;
+    }
+    start
+}
+
+/// Pick the album an artist is best known for: the most played, or the
+/// newest if none has been played.
+pub fn top_album(albums: Vec<Album>) -> Option<Album> {
+    albums
+        .into_iter()
+        .max_by_key(|album| (album.play_count.unwrap_or(0), album.year.unwrap_or(0)))
+}
+
+/// Format a total running time, e.g. "42 min" or "1 h 05 min".
+pub fn format_total_duration(secs: i32) -> String {
+    let mins = (secs + 30) / 60;
+    if mins < 60 {
+        format!("{} min", mins)
+    } else {
+        format!("{} h {:02} min", mins / 60, mins % 60)
+    }
+}
+
+/// A row of a song table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SongRow {
+    /// Separator above the songs of a disc
+    Disc(i32),
+    /// Song at this position among the visible songs
+    Song(usize),
+}
+
+/// Lay out the rows of a song table, adding a separator at the start of each
+/// disc when `group_discs` is set and the visible songs span more than one.
+fn song_table_rows(songs: &[Song], visible: &[usize], group_discs: bool) -> Vec<SongRow> {
+    let disc = |position: usize| songs[visible[position]].disc_number;
+    let multi_disc = group_discs
+        && visible
+            .iter()
+            .any(|&i| songs[i].disc_number.is_some() && songs[i].disc_number != disc(0));
+
+    let mut rows = Vec::with_capacity(visible.len());
+    for position in 0..visible.len() {
+        if multi_disc && (position == 0 || disc(position) != disc(position - 1)) {
+            rows.push(SongRow::Disc(disc(position).unwrap_or(1)));
+        }
+        rows.push(SongRow::Song(position));
+    }
+    rows
+}
+
+/// A row of the Artists list under its index letters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArtistRow {
+    /// Header of the group at this position
+    Letter(usize),
+    /// Artist at this index
+    Artist(usize),
+}
+
+/// The Artists list under its index letters. Rows are worked out from the
+/// groups as needed rather than laid out up front, so drawing a library of
+/// tens of thousands of artists only builds the rows on screen.
+struct ArtistLayout<'a> {
+    groups: &'a [ArtistGroup],
+    collapsed: &'a HashSet<String>,
+}
+
+impl ArtistLayout<'_> {
+    /// Number of artists shown under a group's header.
+    fn shown(&self, group: &ArtistGroup) -> usize {
+        if self.collapsed.contains(&group.letter) {
+            0
+        } else {
+            group.len
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.groups.iter().map(|group| 1 + self.shown(group)).sum()
+    }
+
+    fn row(&self, mut row: usize) -> Option<ArtistRow> {
+        for (position, group) in self.groups.iter().enumerate() {
+            if row == 0 {
+                return Some(ArtistRow::Letter(position));
+            }
+            row -= 1;
+            let shown = self.shown(group);
+            if row < shown {
+                return Some(ArtistRow::Artist(group.start + row));
+            }
+            row -= shown;
+        }
+        None
+    }
+
+    /// Get the row showing an artist: its own, or its letter's header when
+    /// the letter is collapsed.
+    fn row_of(&self, artist: usize) -> Option<usize> {
+        let mut row = 0;
+        for group in self.groups {
+            if group.contains(artist) {
+                return Some(if self.shown(group) == 0 {
+                    row
+                } else {
+                    row + 1 + artist - group.start
+                });
+            }
+            row += 1 + self.shown(group);
+        }
+        None
+    }
+
+    /// Get the artist selected on a row. The header of a collapsed letter
+    /// stands for its first artist; that of an expanded one selects nothing.
+    fn artist_at(&self, row: usize) -> Option<usize> {
+        match self.row(row)? {
+            ArtistRow::Artist(i) => Some(i),
+            ArtistRow::Letter(position) => {
+                let group = &self.groups[position];
+                (self.shown(group) == 0).then_some(group.start)
+            }
+        }
+    }
+
+    /// Get the artists that can be selected, in row order.
+    fn selectable(&self) -> Vec<usize> {
+        self.groups
+            .iter()
+            .flat_map(|group| group.start..group.start + self.shown(group).max(1))
+            .collect()
+    }
+}
+
+/// Scroll a list just enough to keep the selected row in view.
+fn scroll_offset(offset: usize, selected: Option<usize>, height: usize, rows: usize) -> usize {
+    let offset = match selected {
+        Some(row) if row < offset => row,
+        Some(row) if row >= offset + height => row + 1 - height,
+        _ => offset,
+    };
+    offset.min(rows.saturating_sub(height))
+}
+
+/// How a song table is displayed.
+struct SongTable<'a> {
+    /// Quick filter narrowing the rows
+    filter: &'a str,
+    columns: &'a [ColumnSpec],
+    /// Song to mark as playing
+    playing_id: Option<&'a str>,
+    /// Add disc separators (album views)
+    group_discs: bool,
+    /// Column headers, for sortable lists
+    header: Option<Row<'a>>,
+    /// Bordered area whose right border shows the scrollbar
+    scrollbar: Rect,
+}
+
+/// Draw the scrollbar of a table showing `rows` rows in the bordered `area`.
+fn render_table_scrollbar(
+    frame: &mut Frame,
+    area: Rect,
+    table_state: &TableState,
+    rows: usize,
+    header: bool,
+) {
+    let visible = area.height.saturating_sub(2 + u16::from(header)) as usize;
+    render_scrollbar(frame, area, rows, visible, table_state.offset());
+}
+
+/// Build the header row of a sortable list, registering the header of each
+/// sortable column so clicking it sorts by that column.
+fn sort_header(
+    state: &LibraryState,
+    inner: Rect,
+    widths: &[Constraint],
+    hits: &mut HitRegions,
+) -> Option<Row<'static>> {
+    let headers = state.sort_headers()?;
+    let current = state.sorts.get(state.tab);
+    let reversed = state.sorts.is_reversed(state.tab);
+
+    // Same column layout as the table
+    let row = Rect { height: 1, ..inner };
+    let areas = Layout::horizontal(widths.iter().copied())
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(row);
+
+    let cells: Vec<Cell> = headers
+        .into_iter()
+        .zip(areas.iter())
+        .enumerate()
+        .map(|(column, ((label, sort), area))| {
+            if sort.is_some() {
+                hits.add(HitRegion::SortHeader(column), *area);
+            }
+            match sort {
+                Some(sort) if current == Some(sort) => {
+                    let arrow = if sort.descending() != reversed {
+                        "▼"
+                    } else {
+                        "▲"
+                    };
+                    Cell::from(format!("{} {}", label, arrow)).style(
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                }
+                _ => Cell::from(label).style(
                     Style::default()
-                        .fg(Color::White)
+                        .fg(Color::Gray)
                         .add_modifier(Modifier::BOLD),
-                    Style::default().fg(Color::LightCyan),
-                    Style::default().fg(Color::Gray),
-                )
-            } else {
-                (
-                    Style::default().fg(Color::DarkGray),
-                    Style::default().fg(Color::White),
-                    Style::default().fg(Color::Cyan),
-                    Style::default().fg(Color::DarkGray),
-                )
-            };
+                ),
+            }
+        })
+        .collect();
+    Some(Row::new(cells))
+}
 
-            Row::new(vec![
-                Cell::from(track).style(track_style),
-                Cell::from(song.title.clone()).style(title_style),
-                Cell::from(artist).style(artist_style),
-                Cell::from(duration).style(duration_style),
-            ])
+/// Cell with a song's playing (▶) and starred (★) markers around `text`.
+fn marked_cell(text: String, song: &Song, playing_id: Option<&str>, style: Style) -> Cell<'static> {
+    Cell::from(marked_line(text, song, playing_id, style))
+}
+
+/// Line with a song's playing (▶) and starred (★) markers around `text`.
+pub fn marked_line(
+    text: String,
+    song: &Song,
+    playing_id: Option<&str>,
+    style: Style,
+) -> Line<'static> {
+    let mut spans = Vec::new();
+    if playing_id == Some(song.id.as_str()) {
+        spans.push(Span::styled("▶ ", Style::default().fg(Color::Green)));
+    }
+    spans.push(Span::styled(text, style));
+    if song.starred.is_some() {
+        spans.push(Span::styled(" ★", Style::default().fg(Color::Yellow)));
+    }
+    Line::from(spans)
+}
+
+fn render_song_list(
+    frame: &mut Frame,
+    area: Rect,
+    songs: &[Song],
+    list_state: &mut ListState,
+    table: SongTable,
+    block: Block,
+) {
+    let SongTable {
+        filter,
+        columns,
+        playing_id,
+        group_discs,
+        header,
+        scrollbar,
+    } = table;
+
+    // Convert ListState to TableState, counting only rows that pass the filter
+    // and skipping disc separators
+    let visible = visible_indices(songs, filter);
+    let layout = song_table_rows(songs, &visible, group_discs);
+    let mut table_state = TableState::default().with_offset(list_state.offset());
+    table_state.select(
+        to_visible(&visible, list_state.selected()).and_then(|position| {
+            layout
+                .iter()
+                .position(|row| *row == SongRow::Song(position))
+        }),
+    );
+
+    let selected_idx = list_state.selected();
+
+    let rows: Vec<Row> = layout
+        .iter()
+        .map(|row| match *row {
+            // Label under the title column so narrow leading columns don't cut it off
+            SongRow::Disc(disc) => Row::new(columns.iter().map(|spec| {
+                if spec.column == SongColumn::Title {
+                    Cell::from(format!("Disc {}", disc)).style(
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    Cell::from("")
+                }
+            })),
+            SongRow::Song(position) => {
+                let i = visible[position];
+                let song = &songs[i];
+                let is_selected = selected_idx == Some(i);
+
+                // Use brighter colors for selected row
+                let (title_style, name_style, detail_style) = if is_selected {
+                    (
+                        Style::default()
+                            .fg(Color::White)
+                            .add_modifier(Modifier::BOLD),
+                        Style::default().fg(Color::LightCyan),
+                        Style::default().fg(Color::Gray),
+                    )
+                } else {
+                    (
+                        Style::default().fg(Color::White),
+                        Style::default().fg(Color::Cyan),
+                        Style::default().fg(Color::DarkGray),
+                    )
+                };
+
+                // Markers go with the title, or the first column if it isn't shown
+                let marker_column = columns
+                    .iter()
+                    .position(|spec| spec.column == SongColumn::Title)
+                    .unwrap_or(0);
+
+                Row::new(columns.iter().enumerate().map(|(column, spec)| {
+                    let style = match spec.column {
+                        SongColumn::Title => title_style,
+                        SongColumn::Artist | SongColumn::Album => name_style,
+                        _ => detail_style,
+                    };
+                    if column == marker_column {
+                        marked_cell(spec.column.text(song, i), song, playing_id, style)
+                    } else {
+                        Cell::from(spec.column.text(song, i)).style(style)
+                    }
+                }))
+            }
         })
         .collect();
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(3),      // Track number
-            Constraint::Percentage(50), // Title
-            Constraint::Percentage(35), // Artist
-            Constraint::Length(6),      // Duration
-        ],
-    )
-    .block(block)
-    .row_highlight_style(Style::default().bg(Color::DarkGray));
+    let row_count = rows.len();
+    let visible_rows = block
+        .inner(area)
+        .height
+        .saturating_sub(u16::from(header.is_some())) as usize;
+    let mut table = Table::new(rows, columns.iter().map(ColumnSpec::constraint))
+        .block(block)
+        .row_highlight_style(Style::default().bg(Color::DarkGray));
+    if let Some(header) = header {
+        table = table.header(header);
+    }
 
     frame.render_stateful_widget(table, area, &mut table_state);
+    render_scrollbar(
+        frame,
+        scrollbar,
+        row_count,
+        visible_rows,
+        table_state.offset(),
+    );
 
     // Sync selection back to ListState
-    *list_state.selected_mut() = table_state.selected();
+    let position = table_state
+        .selected()
+        .and_then(|row| match layout.get(row) {
+            Some(SongRow::Song(position)) => Some(*position),
+            _ => None,
+        });
+    *list_state.selected_mut() = from_visible(&visible, position);
+    *list_state.offset_mut() = table_state.offset();
 }
 
 fn render_genres_view(frame: &mut Frame, area: Rect, state: &mut LibraryState, block: Block) {
     if state.view_depth == 0 {
         // Genre list with columns: Genre | Albums | Songs
-        let mut table_state = TableState::default();
-        table_state.select(state.genres_state.selected());
-        let selected_idx = table_state.selected();
-
-        let rows: Vec<Row> = state
-            .genres
+        let mut table_state = TableState::default().with_offset(state.genres_state.offset());
+        let visible = visible_indices(&state.genres, &state.filter);
+        table_state.select(to_visible(&visible, state.genres_state.selected()));
+        let selected_idx = state.genres_state.selected();
+        let rows: Vec<Row> = visible
             .iter()
-            .enumerate()
+            .map(|&i| (i, &state.genres[i]))
             .map(|(i, genre)| {
                 let is_selected = selected_idx == Some(i);
                 let album_count = genre
@@ -978,20 +2899,53 @@ fn render_genres_view(frame: &mut Frame, area: Rect, state: &mut LibraryState, b
         .row_highlight_style(Style::default().bg(Color::DarkGray));
 
         frame.render_stateful_widget(table, area, &mut table_state);
-        *state.genres_state.selected_mut() = table_state.selected();
+        render_table_scrollbar(frame, area, &table_state, visible.len(), false);
+        *state.genres_state.selected_mut() = from_visible(&visible, table_state.selected());
+        *state.genres_state.offset_mut() = table_state.offset();
     } else if state.view_depth == 1 {
-        // Genre albums with columns: Album | Artist
-        let mut table_state = TableState::default();
-        table_state.select(state.genre_albums_state.selected());
-        let selected_idx = table_state.selected();
+        // Two columns: the genre's albums and its songs
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(inner);
+        let section_color = |section: u8| {
+            if state.genre_section == section {
+                Color::Cyan
+            } else {
+                Color::DarkGray
+            }
+        };
 
-        let rows: Vec<Row> = state
-            .genre_albums
+        let albums_block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Albums ({})", state.genre_albums.len()))
+            .border_style(Style::default().fg(section_color(0)));
+        let more = state
+            .genre_songs_pages
+            .as_ref()
+            .is_some_and(|p| !p.is_exhausted());
+        let songs_block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(
+                "Songs ({}{})",
+                state.genre_songs.len(),
+                if more { "+" } else { "" }
+            ))
+            .border_style(Style::default().fg(section_color(1)));
+
+        // Genre albums with columns: Album | Artist
+        let mut table_state = TableState::default().with_offset(state.genre_albums_state.offset());
+        let visible = visible_indices(&state.genre_albums, &state.filter);
+        table_state.select(to_visible(&visible, state.genre_albums_state.selected()));
+        let selected_idx = state.genre_albums_state.selected();
+        let rows: Vec<Row> = visible
             .iter()
-            .enumerate()
+            .map(|&i| (i, &state.genre_albums[i]))
             .map(|(i, album)| {
                 let is_selected = selected_idx == Some(i);
-                let artist = album.artist.as_deref().unwrap_or("Unknown Artist");
+                let artist = album.album_artist().unwrap_or("Unknown Artist");
 
                 let (name_style, artist_style) = if is_selected {
                     (
@@ -1021,20 +2975,33 @@ fn render_genres_view(frame: &mut Frame, area: Rect, state: &mut LibraryState, b
                 Constraint::Percentage(40), // Artist
             ],
         )
-        .block(block)
+        .block(albums_block)
         .row_highlight_style(Style::default().bg(Color::DarkGray));
 
-        frame.render_stateful_widget(table, area, &mut table_state);
-        *state.genre_albums_state.selected_mut() = table_state.selected();
-    } else {
-        // Album songs (depth 2)
+        frame.render_stateful_widget(table, columns[0], &mut table_state);
+        render_table_scrollbar(frame, columns[0], &table_state, visible.len(), false);
+        *state.genre_albums_state.selected_mut() = from_visible(&visible, table_state.selected());
+        *state.genre_albums_state.offset_mut() = table_state.offset();
+
+        let table = SongTable {
+            filter: &state.filter,
+            columns: &state.song_columns,
+            playing_id: state.playing_id.as_deref(),
+            group_discs: false,
+            header: None,
+            scrollbar: columns[1],
+        };
         render_song_list(
             frame,
-            area,
-            &state.album_songs,
-            &mut state.album_songs_state,
-            block,
+            columns[1],
+            &state.genre_songs,
+            &mut state.genre_songs_state,
+            table,
+            songs_block,
         );
+    } else {
+        // Album songs (depth 2)
+        render_album_songs(frame, area, state, block);
     }
 }
 
@@ -1065,14 +3032,18 @@ fn render_favorites_view(frame: &mut Frame, area: Rect, state: &mut LibraryState
                 Color::DarkGray
             }));
 
-        let mut artists_table_state = TableState::default();
-        artists_table_state.select(state.favorites_artists_state.selected());
-        let artists_selected_idx = artists_table_state.selected();
+        let mut artists_table_state =
+            TableState::default().with_offset(state.favorites_artists_state.offset());
+        let artists_visible = visible_indices(&state.favorites_artists, &state.filter);
+        artists_table_state.select(to_visible(
+            &artists_visible,
+            state.favorites_artists_state.selected(),
+        ));
+        let artists_selected_idx = state.favorites_artists_state.selected();
 
-        let artist_rows: Vec<Row> = state
-            .favorites_artists
+        let artist_rows: Vec<Row> = artists_visible
             .iter()
-            .enumerate()
+            .map(|&i| (i, &state.favorites_artists[i]))
             .map(|(i, artist)| {
                 let is_selected = artists_selected_idx == Some(i);
                 let style = if is_selected {
@@ -1092,7 +3063,16 @@ fn render_favorites_view(frame: &mut Frame, area: Rect, state: &mut LibraryState
             .row_highlight_style(Style::default().bg(Color::DarkGray));
 
         frame.render_stateful_widget(artists_table, columns[0], &mut artists_table_state);
-        *state.favorites_artists_state.selected_mut() = artists_table_state.selected();
+        render_table_scrollbar(
+            frame,
+            columns[0],
+            &artists_table_state,
+            artists_visible.len(),
+            false,
+        );
+        *state.favorites_artists_state.selected_mut() =
+            from_visible(&artists_visible, artists_table_state.selected());
+        *state.favorites_artists_state.offset_mut() = artists_table_state.offset();
 
         // Render albums column
         let albums_block = Block::default()
@@ -1104,17 +3084,21 @@ fn render_favorites_view(frame: &mut Frame, area: Rect, state: &mut LibraryState
                 Color::DarkGray
             }));
 
-        let mut albums_table_state = TableState::default();
-        albums_table_state.select(state.favorites_albums_state.selected());
-        let albums_selected_idx = albums_table_state.selected();
+        let mut albums_table_state =
+            TableState::default().with_offset(state.favorites_albums_state.offset());
+        let albums_visible = visible_indices(&state.favorites_albums, &state.filter);
+        albums_table_state.select(to_visible(
+            &albums_visible,
+            state.favorites_albums_state.selected(),
+        ));
+        let albums_selected_idx = state.favorites_albums_state.selected();
 
-        let album_rows: Vec<Row> = state
-            .favorites_albums
+        let album_rows: Vec<Row> = albums_visible
             .iter()
-            .enumerate()
+            .map(|&i| (i, &state.favorites_albums[i]))
             .map(|(i, album)| {
                 let is_selected = albums_selected_idx == Some(i);
-                let artist = album.artist.as_deref().unwrap_or("Unknown");
+                let artist = album.album_artist().unwrap_or("Unknown");
 
                 let (name_style, artist_style) = if is_selected {
                     (
@@ -1145,7 +3129,16 @@ fn render_favorites_view(frame: &mut Frame, area: Rect, state: &mut LibraryState
         .row_highlight_style(Style::default().bg(Color::DarkGray));
 
         frame.render_stateful_widget(albums_table, columns[1], &mut albums_table_state);
-        *state.favorites_albums_state.selected_mut() = albums_table_state.selected();
+        render_table_scrollbar(
+            frame,
+            columns[1],
+            &albums_table_state,
+            albums_visible.len(),
+            false,
+        );
+        *state.favorites_albums_state.selected_mut() =
+            from_visible(&albums_visible, albums_table_state.selected());
+        *state.favorites_albums_state.offset_mut() = albums_table_state.offset();
 
         // Render songs column
         let songs_block = Block::default()
@@ -1157,66 +3150,31 @@ fn render_favorites_view(frame: &mut Frame, area: Rect, state: &mut LibraryState
                 Color::DarkGray
             }));
 
-        let mut songs_table_state = TableState::default();
-        songs_table_state.select(state.favorites_songs_state.selected());
-        let songs_selected_idx = songs_table_state.selected();
-
-        let song_rows: Vec<Row> = state
-            .favorites_songs
-            .iter()
-            .enumerate()
-            .map(|(i, song)| {
-                let is_selected = songs_selected_idx == Some(i);
-                let artist = song.display_artist();
-                let duration = song.duration_string();
-
-                let (title_style, artist_style, duration_style) = if is_selected {
-                    (
-                        Style::default()
-                            .fg(Color::White)
-                            .add_modifier(Modifier::BOLD),
-                        Style::default().fg(Color::LightCyan),
-                        Style::default().fg(Color::Gray),
-                    )
-                } else {
-                    (
-                        Style::default().fg(Color::White),
-                        Style::default().fg(Color::Cyan),
-                        Style::default().fg(Color::DarkGray),
-                    )
-                };
-
-                Row::new(vec![
-                    Cell::from(song.title.clone()).style(title_style),
-                    Cell::from(artist).style(artist_style),
-                    Cell::from(duration).style(duration_style),
-                ])
-            })
-            .collect();
-
-        let songs_table = Table::new(
-            song_rows,
-            [
-                Constraint::Percentage(50),
-                Constraint::Percentage(35),
-                Constraint::Length(6),
-            ],
-        )
-        .block(songs_block)
-        .row_highlight_style(Style::default().bg(Color::DarkGray));
-
-        frame.render_stateful_widget(songs_table, columns[2], &mut songs_table_state);
-        *state.favorites_songs_state.selected_mut() = songs_table_state.selected();
+        let table = SongTable {
+            filter: &state.filter,
+            columns: &state.song_columns,
+            playing_id: state.playing_id.as_deref(),
+            group_discs: false,
+            header: None,
+            scrollbar: columns[2],
+        };
+        render_song_list(
+            frame,
+            columns[2],
+            &state.favorites_songs,
+            &mut state.favorites_songs_state,
+            table,
+            songs_block,
+        );
     } else if state.view_depth == 1 {
         // Drill-down into artist -> albums with columns: Album | Year
-        let mut table_state = TableState::default();
-        table_state.select(state.artist_albums_state.selected());
-        let selected_idx = table_state.selected();
-
-        let rows: Vec<Row> = state
-            .artist_albums
+        let mut table_state = TableState::default().with_offset(state.artist_albums_state.offset());
+        let visible = visible_indices(&state.artist_albums, &state.filter);
+        table_state.select(to_visible(&visible, state.artist_albums_state.selected()));
+        let selected_idx = state.artist_albums_state.selected();
+        let rows: Vec<Row> = visible
             .iter()
-            .enumerate()
+            .map(|&i| (i, &state.artist_albums[i]))
             .map(|(i, album)| {
                 let is_selected = selected_idx == Some(i);
                 let year = album.year.map(|y| y.to_string()).unwrap_or_default();
@@ -1236,7 +3194,7 @@ fn render_favorites_view(frame: &mut Frame, area: Rect, state: &mut LibraryState
                 };
 
                 Row::new(vec![
-                    Cell::from(album.name.clone()).style(name_style),
+                    Cell::from(artist_album_name(album)).style(name_style),
                     Cell::from(year).style(year_style),
                 ])
             })
@@ -1253,15 +3211,471 @@ fn render_favorites_view(frame: &mut Frame, area: Rect, state: &mut LibraryState
         .row_highlight_style(Style::default().bg(Color::DarkGray));
 
         frame.render_stateful_widget(table, area, &mut table_state);
-        *state.artist_albums_state.selected_mut() = table_state.selected();
+        render_table_scrollbar(frame, area, &table_state, visible.len(), false);
+        *state.artist_albums_state.selected_mut() = from_visible(&visible, table_state.selected());
+        *state.artist_albums_state.offset_mut() = table_state.offset();
     } else {
         // Drill-down into album -> songs (depth 2)
-        render_song_list(
-            frame,
-            area,
-            &state.album_songs,
-            &mut state.album_songs_state,
-            block,
+        render_album_songs(frame, area, state, block);
+    }
+}
+
+fn render_history_view(frame: &mut Frame, area: Rect, state: &mut LibraryState, block: Block) {
+    // History list with columns: Played | Title | Artist | Duration
+    let mut table_state = TableState::default().with_offset(state.history_state.offset());
+    let visible = visible_indices(&state.history, &state.filter);
+    table_state.select(to_visible(&visible, state.history_state.selected()));
+    let selected_idx = state.history_state.selected();
+    let rows: Vec<Row> = visible
+        .iter()
+        .map(|&i| (i, &state.history[i]))
+        .map(|(i, entry)| {
+            let is_selected = selected_idx == Some(i);
+            let song = &entry.song;
+
+            let (time_style, title_style, artist_style, duration_style) = if is_selected {
+                (
+                    Style::default().fg(Color::Gray),
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                    Style::default().fg(Color::LightCyan),
+                    Style::default().fg(Color::Gray),
+                )
+            } else {
+                (
+                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(Color::White),
+                    Style::default().fg(Color::Cyan),
+                    Style::default().fg(Color::DarkGray),
+                )
+            };
+
+            Row::new(vec![
+                Cell::from(entry.played_at_string()).style(time_style),
+                marked_cell(
+                    song.title.clone(),
+                    song,
+                    state.playing_id.as_deref(),
+                    title_style,
+                ),
+                Cell::from(song.display_artist().to_string()).style(artist_style),
+                Cell::from(song.duration_string()).style(duration_style),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(13),     // Played at
+            Constraint::Percentage(45), // Title
+            Constraint::Percentage(35), // Artist
+            Constraint::Length(6),      // Duration
+        ],
+    )
+    .block(block)
+    .row_highlight_style(Style::default().bg(Color::DarkGray));
+
+    frame.render_stateful_widget(table, area, &mut table_state);
+    render_table_scrollbar(frame, area, &table_state, visible.len(), false);
+    *state.history_state.selected_mut() = from_visible(&visible, table_state.selected());
+    *state.history_state.offset_mut() = table_state.offset();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disc_separators() {
+        let songs: Vec<Song> = [1, 1, 2]
+            .iter()
+            .enumerate()
+            .map(|(i, disc)| {
+                serde_json::from_value(serde_json::json!({
+                    "id": i.to_string(),
+                    "title": "Song",
+                    "discNumber": disc,
+                }))
+                .unwrap()
+            })
+            .collect();
+
+        assert_eq!(
+            song_table_rows(&songs, &[0, 1, 2], true),
+            vec![
+                SongRow::Disc(1),
+                SongRow::Song(0),
+                SongRow::Song(1),
+                SongRow::Disc(2),
+                SongRow::Song(2),
+            ]
+        );
+        // A single disc left by the filter needs no separator
+        assert_eq!(
+            song_table_rows(&songs, &[0, 1], true),
+            vec![SongRow::Song(0), SongRow::Song(1)]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_column_header() {
+        let mut state = LibraryState::new();
+        state.tab = Tab::Albums;
+        let albums: Vec<Album> = [("B", 2001), ("A", 1999), ("C", 2010)]
+            .iter()
+            .enumerate()
+            .map(|(i, (name, year))| {
+                serde_json::from_value(serde_json::json!({
+                    "id": i.to_string(),
+                    "name": name,
+                    "year": year,
+                }))
+                .unwrap()
+            })
+            .collect();
+        state.set_albums(albums);
+        let names = |state: &LibraryState| -> Vec<String> {
+            state.albums.iter().map(|a| a.name.clone()).collect()
+        };
+
+        // Year sorts newest first, and a second click turns it around
+        assert!(state.sort_by_column(2));
+        assert_eq!(names(&state), ["C", "B", "A"]);
+        assert!(state.sort_by_column(2));
+        assert_eq!(names(&state), ["A", "B", "C"]);
+
+        // Picking another column starts it in its own direction
+        assert!(state.sort_by_column(0));
+        assert!(!state.sorts.is_reversed(Tab::Albums));
+        assert_eq!(names(&state), ["A", "B", "C"]);
+
+        assert!(!state.sort_by_column(3));
+    }
+
+    #[test]
+    fn test_view_and_restored_selection() {
+        let artist: Artist =
+            serde_json::from_value(serde_json::json!({"id": "ar1", "name": "Artist"})).unwrap();
+        let albums: Vec<Album> = ["al1", "al2"]
+            .iter()
+            .map(|id| serde_json::from_value(serde_json::json!({"id": id, "name": id})).unwrap())
+            .collect();
+        let mut state = LibraryState::new();
+        state.enter_artist(artist, albums.clone());
+        state.artist_albums_state.select(Some(1));
+
+        let view = state.view();
+        assert_eq!(view.tab, Tab::Artists);
+        assert_eq!(view.artist_id.as_deref(), Some("ar1"));
+        assert_eq!(view.album_id, None);
+        assert_eq!(view.selected_id.as_deref(), Some("al2"));
+
+        // Waits for the view it was saved in
+        let mut state = LibraryState::new();
+        state.pending_selection = Some((1, String::from("al2")));
+        state.set_artists(Vec::new());
+        state.restore_pending_selection();
+        assert!(state.pending_selection.is_some());
+        let artist = serde_json::from_value(serde_json::json!({"id": "ar1", "name": "A"})).unwrap();
+        state.enter_artist(artist, albums);
+        state.restore_pending_selection();
+        assert_eq!(state.artist_albums_state.selected(), Some(1));
+        assert!(state.pending_selection.is_none());
+    }
+
+    #[test]
+    fn test_remembered_positions() {
+        let artist = |id: &str| -> Artist {
+            serde_json::from_value(serde_json::json!({"id": id, "name": id})).unwrap()
+        };
+        let albums: Vec<Album> = ["al1", "al2", "al3"]
+            .iter()
+            .map(|id| serde_json::from_value(serde_json::json!({"id": id, "name": id})).unwrap())
+            .collect();
+        let mut state = LibraryState::new();
+        state.set_artists(vec![artist("ar1"), artist("ar2")]);
+        state.artists_state.select(Some(1));
+
+        // Back and forth returns to the same album
+        state.enter_artist(artist("ar2"), albums.clone());
+        state.artist_albums_state.select(Some(2));
+        state.go_back();
+        state.enter_artist(artist("ar1"), albums[..1].to_vec());
+        assert_eq!(state.artist_albums_state.selected(), Some(0));
+        state.switch_tab(Tab::Albums);
+        state.switch_tab(Tab::Artists);
+        state.enter_artist(artist("ar2"), albums);
+        assert_eq!(state.artist_albums_state.selected(), Some(2));
+
+        // Refreshing keeps the selected artist, wherever it moved
+        state.set_artists(vec![artist("ar0"), artist("ar1"), artist("ar2")]);
+        assert_eq!(state.artists_state.selected(), Some(2));
+    }
+
+    #[test]
+    fn test_artist_letters() {
+        let index: Vec<ArtistIndex> = serde_json::from_value(serde_json::json!([
+            {"name": "A", "artist": [{"id": "a1", "name": "A1"}, {"id": "a2", "name": "A2"}]},
+            {"name": "B", "artist": []},
+            {"name": "C", "artist": [{"id": "c1", "name": "C1"}]},
+        ]))
+        .unwrap();
+        let mut state = LibraryState::new();
+        state.set_artist_index(index);
+        state.artists_state.select(Some(1));
+
+        // Empty letters are left out, and headers are skipped over
+        assert_eq!(state.active_visible(), vec![0, 1, 2]);
+        let layout = state.artist_layout().unwrap();
+        assert_eq!(layout.len(), 5);
+        assert_eq!(layout.row(3), Some(ArtistRow::Letter(1)));
+        assert_eq!(layout.artist_at(0), None);
+        assert_eq!(layout.row_of(2), Some(4));
+
+        // A collapsed letter shows only its header, which stands for its artists
+        state.toggle_artist_group();
+        assert_eq!(state.artists_state.selected(), Some(0));
+        assert_eq!(state.active_visible(), vec![0, 2]);
+        let layout = state.artist_layout().unwrap();
+        assert_eq!(layout.len(), 3);
+        assert_eq!(layout.artist_at(0), Some(0));
+        assert_eq!(layout.row_of(2), Some(2));
+        assert!(state.expand_selected_artist_group());
+        assert!(!state.expand_selected_artist_group());
+
+        state.toggle_artist_groups();
+        assert_eq!(state.active_visible(), vec![0, 2]);
+        state.toggle_artist_groups();
+        assert_eq!(state.active_visible(), vec![0, 1, 2]);
+
+        // Letters only hold in server order
+        assert_eq!(state.cycle_sort(), Some(false));
+        assert!(state.artist_layout().is_none());
+    }
+
+    #[test]
+    fn test_sorted_song_pages() {
+        let page = |ids: &[&str]| -> Vec<Song> {
+            ids.iter()
+                .map(|id| {
+                    serde_json::from_value(serde_json::json!({"id": id, "title": id})).unwrap()
+                })
+                .collect()
+        };
+        let mut state = LibraryState::new();
+        state.sorts.songs = SongSort::Title;
+        state.songs_pages = Some(PagedSongs::new(Arc::new(StarredSongs::default())));
+
+        // A fresh list starts on the first row shown, not the first fetched
+        state.add_song_page(SongList::Songs, 0, page(&["c", "a"]));
+        assert_eq!(state.songs_state.selected(), Some(0));
+        assert_eq!(state.songs[0].id, "a");
+
+        // A later page keeps the selected song selected as it sorts in
+        state.songs_state.select(Some(1));
+        state.add_song_page(SongList::Songs, 2, page(&["b"]));
+        assert_eq!(state.songs_state.selected(), Some(2));
+        assert_eq!(state.songs[2].id, "c");
+
+        // Turning the sort off goes back to the order the pages came in
+        state.tab = Tab::Songs;
+        while state.sorts.songs != SongSort::Default {
+            assert_eq!(state.cycle_sort(), Some(false));
+        }
+        let ids: Vec<&str> = state.songs.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_playlist_edits() {
+        let songs: Vec<Song> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|id| serde_json::from_value(serde_json::json!({"id": id, "title": id})).unwrap())
+            .collect();
+        let playlist: Album =
+            serde_json::from_value(serde_json::json!({"id": "pl1", "name": "Mix"})).unwrap();
+        let mut state = LibraryState::new();
+        state.tab = Tab::Playlists;
+        state.hide(HideTarget::Song {
+            id: String::from("b"),
+            title: String::from("b"),
+        });
+        state.enter_album(playlist, songs);
+        let shown = |state: &LibraryState| -> Vec<String> {
+            state.album_songs.iter().map(|s| s.id.clone()).collect()
+        };
+        assert_eq!(shown(&state), ["a", "c", "d"]);
+
+        // Moving "a" past "c" swaps them around the hidden "b"
+        let edit = state.move_playlist_song(1).unwrap();
+        assert_eq!(shown(&state), ["c", "a", "d"]);
+        assert_eq!(state.album_songs_state.selected(), Some(1));
+        assert_eq!(edit.remove, [0, 1, 2, 3]);
+        assert_eq!(edit.add, ["c", "b", "a", "d"]);
+
+        // Moving it on past "d" goes to the server as one change
+        let next = state.move_playlist_song(1).unwrap();
+        assert_eq!(next.remove, [2, 3]);
+        let merged = edit.clone().then_move(next);
+        assert_eq!(merged.remove, [0, 1, 2, 3]);
+        assert_eq!(merged.add, ["c", "b", "d", "a"]);
+        state.undo_playlist_edit(merged);
+        assert_eq!(shown(&state), ["a", "c", "d"]);
+        let edit = state.move_playlist_song(1).unwrap();
+
+        // Removing counts the hidden song too
+        let removal = state.remove_playlist_song().unwrap();
+        assert_eq!(removal.remove, [2]);
+        assert_eq!(shown(&state), ["c", "d"]);
+
+        // A refused change puts back everything made after it
+        state.undo_playlist_edit(edit);
+        assert_eq!(shown(&state), ["a", "c", "d"]);
+        assert_eq!(state.album_songs_state.selected(), Some(0));
+        assert!(state.move_playlist_song(-1).is_none());
+    }
+
+    #[test]
+    fn test_patch_favorites() {
+        let song = |id: &str, starred: bool| -> Song {
+            let mut song: Song =
+                serde_json::from_value(serde_json::json!({"id": id, "title": id})).unwrap();
+            song.starred = starred.then(|| String::from("2024-01-01T00:00:00Z"));
+            song
+        };
+        let mut state = LibraryState::new();
+        state.set_favorites(vec![], vec![], vec![song("a", true), song("b", true)]);
+        state.favorites_songs_state.select(Some(1));
+        let shown = |state: &LibraryState| -> Vec<String> {
+            state.favorites_songs.iter().map(|s| s.id.clone()).collect()
+        };
+
+        // A new star goes on top, keeping the selected song selected
+        state.patch_favorite_songs(&song("c", true));
+        assert_eq!(shown(&state), ["c", "a", "b"]);
+        assert_eq!(state.favorites_songs_state.selected(), Some(2));
+        state.patch_favorite_songs(&song("c", true));
+        assert_eq!(shown(&state), ["c", "a", "b"]);
+
+        state.patch_favorite_songs(&song("a", false));
+        assert_eq!(shown(&state), ["c", "b"]);
+        assert_eq!(state.favorites_songs_state.selected(), Some(1));
+    }
+
+    #[test]
+    fn test_new_albums() {
+        let albums = || -> Vec<Album> {
+            [
+                ("a", "2024-03-01T10:00:00Z"),
+                ("b", "2024-05-01T10:00:00.000+02:00"),
+            ]
+            .iter()
+            .map(|(id, created)| {
+                serde_json::from_value(
+                    serde_json::json!({"id": id, "name": id, "created": created}),
+                )
+                .unwrap()
+            })
+            .collect()
+        };
+        let mut state = LibraryState::new();
+
+        // Nothing is new in the first session
+        state.set_albums(albums());
+        assert!(state.albums.iter().all(|album| !state.is_new(album)));
+        let newest = state.newest_album;
+        assert_eq!(newest, "2024-05-01T08:00:00Z".parse().ok());
+
+        state.new_since = "2024-04-01T00:00:00Z".parse().ok();
+        state.set_albums(albums());
+        let new: Vec<&str> = state
+            .albums
+            .iter()
+            .filter(|album| state.is_new(album))
+            .map(|album| album.id.as_str())
+            .collect();
+        assert_eq!(new, ["b"]);
+        assert_eq!(state.newest_album, newest);
+    }
+
+    #[test]
+    fn test_similar_albums() {
+        let album = |id: &str, plays: i64, year: i32| -> Album {
+            serde_json::from_value(serde_json::json!({
+                "id": id, "name": id, "playCount": plays, "year": year,
+            }))
+            .unwrap()
+        };
+        let top = top_album(vec![
+            album("a", 3, 1990),
+            album("b", 9, 1980),
+            album("c", 9, 1985),
+        ]);
+        assert_eq!(top.map(|album| album.id), Some(String::from("c")));
+        assert_eq!(top_album(Vec::new()), None);
+
+        let mut state = LibraryState::new();
+        state.tab = Tab::Albums;
+        state.enter_album(album("open", 0, 2000), Vec::new());
+        assert!(state.similar_albums_needed("open"));
+        state.set_similar_albums(
+            String::from("open"),
+            vec![album("x", 0, 0), album("open", 0, 0), album("y", 0, 0)],
+        );
+        assert!(!state.similar_albums_needed("open"));
+        assert_eq!(state.similar_albums().len(), 2);
+
+        state.step_similar(-1);
+        assert_eq!(
+            state.selected_similar_album().map(|a| a.id.as_str()),
+            Some("y")
+        );
+        state.step_similar(1);
+        assert_eq!(
+            state.selected_similar_album().map(|a| a.id.as_str()),
+            Some("x")
+        );
+        assert!(state.unpick_similar());
+        assert!(!state.unpick_similar());
+
+        // The row scrolls just far enough to show the picked album
+        assert_eq!(strip_start(&[10, 10, 10], Some(2), 25, 2), 1);
+        assert_eq!(strip_start(&[10, 10, 10], Some(0), 5, 2), 0);
+        assert_eq!(strip_start(&[10, 10, 10], None, 5, 2), 0);
+
+        state.go_back();
+        assert!(state.similar_albums().is_empty());
+    }
+
+    #[test]
+    fn test_compilations() {
+        let album = |id: &str, artist: &str, compilation: bool| -> Album {
+            serde_json::from_value(serde_json::json!({
+                "id": id, "name": id, "artist": artist, "isCompilation": compilation,
+            }))
+            .unwrap()
+        };
+        let hits = album("hits", "Some Singer", true);
+        assert!(hits.is_compilation());
+        assert_eq!(hits.album_artist(), Some("Various Artists"));
+        assert!(album("va", "various artists", false).is_compilation());
+        assert_eq!(album("own", "Singer", false).album_artist(), Some("Singer"));
+
+        // An artist's compilations come after their own albums
+        let artist: Artist =
+            serde_json::from_value(serde_json::json!({"id": "ar", "name": "Singer"})).unwrap();
+        let mut state = LibraryState::new();
+        state.enter_artist(
+            artist,
+            vec![
+                hits,
+                album("first", "Singer", false),
+                album("second", "Singer", false),
+            ],
         );
+        let ids: Vec<&str> = state.artist_albums.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["first", "second", "hits"]);
     }
 }
@@ -0,0 +1,213 @@
+//! Popup showing what the server's other users are listening to.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::avatar::{render_avatar, AvatarCache};
+use crate::client::models::NowPlayingEntry;
+
+/// Columns of a listener's avatar, beside the two lines of their row.
+const AVATAR_WIDTH: u16 = 4;
+
+/// Listeners popup state.
+#[derive(Debug, Default)]
+pub struct ListenersState {
+    /// Whether the popup is open
+    pub visible: bool,
+
+    /// Whether a refresh is in progress
+    pub loading: bool,
+
+    /// What others are playing, from the last refresh
+    pub entries: Vec<NowPlayingEntry>,
+
+    /// Error from the last refresh
+    pub error: Option<String>,
+
+    pub list_state: ListState,
+}
+
+impl ListenersState {
+    /// Show a refresh, keeping the selection in range.
+    pub fn set_entries(&mut self, entries: Vec<NowPlayingEntry>) {
+        self.entries = entries;
+        self.error = None;
+        self.loading = false;
+        let selected = match self.entries.len() {
+            0 => None,
+            n => Some(self.list_state.selected().unwrap_or(0).min(n - 1)),
+        };
+        self.list_state.select(selected);
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.entries.is_empty() {
+            let i = self.list_state.selected().map_or(0, |i| i + 1);
+            self.list_state.select(Some(i.min(self.entries.len() - 1)));
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        let i = self
+            .list_state
+            .selected()
+            .map_or(0, |i| i.saturating_sub(1));
+        self.list_state.select(Some(i));
+    }
+
+    pub fn selected(&self) -> Option<&NowPlayingEntry> {
+        self.list_state.selected().and_then(|i| self.entries.get(i))
+    }
+}
+
+/// Describe how long ago a song started.
+fn started(minutes_ago: i64) -> String {
+    match minutes_ago {
+        0 => String::from("just now"),
+        1 => String::from("1 minute ago"),
+        n if n < 60 => format!("{} minutes ago", n),
+        n => format!("{}h {}m ago", n / 60, n % 60),
+    }
+}
+
+/// Render the listeners popup.
+pub fn render_listeners(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut ListenersState,
+    avatars: &mut AvatarCache,
+) {
+    let width = (area.width * 3 / 5).max(50).min(area.width);
+    let height = (area.height * 3 / 5).max(8).min(area.height);
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, popup_area);
+
+    let title = if state.loading {
+        "Listening now · refreshing…"
+    } else {
+        "Listening now"
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_bottom(" Enter: go to album  a: queue  r: refresh  Esc: close ")
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    if state.entries.is_empty() {
+        let (text, color) = match &state.error {
+            Some(error) => (format!("Couldn't ask the server: {}", error), Color::Red),
+            None if state.loading => (String::from("Loading…"), Color::DarkGray),
+            None => (String::from("Nobody else is listening"), Color::DarkGray),
+        };
+        frame.render_widget(
+            Paragraph::new(text).style(Style::default().fg(color)),
+            inner,
+        );
+        return;
+    }
+
+    // Leave room for avatars on the left of each two-line row
+    let pad = " ".repeat(usize::from(AVATAR_WIDTH) + 1);
+    let items: Vec<ListItem> = state
+        .entries
+        .iter()
+        .map(|entry| {
+            let mut who = vec![
+                Span::raw(pad.clone()),
+                Span::styled(
+                    entry.username.clone(),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ];
+            if let Some(player) = &entry.player_name {
+                who.push(Span::styled(
+                    format!(" on {}", player),
+                    Style::default().fg(Color::Gray),
+                ));
+            }
+            who.push(Span::styled(
+                format!(" · {}", started(entry.minutes_ago)),
+                Style::default().fg(Color::DarkGray),
+            ));
+
+            let song = &entry.song;
+            let mut what = vec![
+                Span::raw(pad.clone()),
+                Span::raw(song.title.clone()),
+                Span::styled(
+                    format!(" – {}", song.display_artist()),
+                    Style::default().fg(Color::Gray),
+                ),
+            ];
+            if let Some(album) = &song.album {
+                what.push(Span::styled(
+                    format!(" · {}", album),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(vec![Line::from(who), Line::from(what)])
+        })
+        .collect();
+
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
+    frame.render_stateful_widget(list, inner, &mut state.list_state);
+
+    // Avatars of the rows in view
+    let offset = state.list_state.offset();
+    for (row, entry) in state.entries.iter().skip(offset).enumerate() {
+        let y = inner.y + row as u16 * 2;
+        if y + 2 > inner.bottom() {
+            break;
+        }
+        let avatar_area = Rect {
+            x: inner.x,
+            y,
+            width: AVATAR_WIDTH.min(inner.width),
+            height: 2,
+        };
+        render_avatar(frame, avatar_area, avatars, &entry.username);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_now_playing_entry() {
+        let entries: Vec<NowPlayingEntry> = serde_json::from_value(serde_json::json!([{
+            "id": "s1", "title": "Nardis", "artist": "Bill Evans",
+            "username": "ana", "minutesAgo": 75, "playerId": 3, "playerName": "Sonixd",
+        }]))
+        .unwrap();
+        assert_eq!(entries[0].song.title, "Nardis");
+        assert_eq!(entries[0].username, "ana");
+        assert_eq!(started(entries[0].minutes_ago), "1h 15m ago");
+
+        let mut state = ListenersState::default();
+        state.set_entries(entries);
+        assert_eq!(state.list_state.selected(), Some(0));
+        state.select_next();
+        assert_eq!(state.selected().map(|e| e.username.as_str()), Some("ana"));
+        state.set_entries(Vec::new());
+        assert_eq!(state.list_state.selected(), None);
+    }
+}
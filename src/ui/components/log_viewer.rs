@@ -0,0 +1,307 @@
+//! Overlay tailing the log file, with level filtering and search.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+/// Most log lines kept in memory.
+const MAX_LINES: usize = 5000;
+
+/// Severity of a log line, least severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LogLevel {
+    #[default]
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Trace => "TRACE",
+            Self::Debug => "DEBUG",
+            Self::Info => "INFO",
+            Self::Warn => "WARN",
+            Self::Error => "ERROR",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Trace | Self::Debug => Color::DarkGray,
+            Self::Info => Color::Green,
+            Self::Warn => Color::Yellow,
+            Self::Error => Color::Red,
+        }
+    }
+
+    /// Next level for the minimum level filter, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Trace => Self::Debug,
+            Self::Debug => Self::Info,
+            Self::Info => Self::Warn,
+            Self::Warn => Self::Error,
+            Self::Error => Self::Trace,
+        }
+    }
+}
+
+/// A line of the log with its level.
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub level: LogLevel,
+    pub text: String,
+}
+
+/// Get the level of a log line, e.g. "2024-05-01T10:00:00.000Z  WARN subsonic_tui::app: ...".
+pub fn parse_level(line: &str) -> Option<LogLevel> {
+    match line.split_whitespace().nth(1)? {
+        "TRACE" => Some(LogLevel::Trace),
+        "DEBUG" => Some(LogLevel::Debug),
+        "INFO" => Some(LogLevel::Info),
+        "WARN" => Some(LogLevel::Warn),
+        "ERROR" => Some(LogLevel::Error),
+        _ => None,
+    }
+}
+
+/// Log viewer state.
+#[derive(Debug, Default)]
+pub struct LogViewerState {
+    /// Whether the overlay is open
+    pub visible: bool,
+
+    /// Lines read so far, oldest first
+    lines: Vec<LogLine>,
+
+    /// Bytes of the log file read so far
+    read_offset: u64,
+
+    /// Only show lines at least this severe
+    pub min_level: LogLevel,
+
+    /// Only show lines containing this text
+    pub search: String,
+
+    /// Whether the search is being typed
+    pub searching: bool,
+
+    /// Whether to keep the newest line in view
+    pub follow: bool,
+
+    /// Selection among the visible lines
+    pub list_state: ListState,
+}
+
+impl LogViewerState {
+    pub fn open(&mut self, path: &Path) {
+        self.visible = true;
+        self.follow = true;
+        self.refresh(path);
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.searching = false;
+    }
+
+    /// Read lines appended to the log file since the last refresh.
+    pub fn refresh(&mut self, path: &Path) {
+        let Ok(mut file) = std::fs::File::open(path) else {
+            return;
+        };
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len < self.read_offset {
+            // The file was replaced by a newer run
+            self.lines.clear();
+            self.read_offset = 0;
+        }
+        if file.seek(SeekFrom::Start(self.read_offset)).is_err() {
+            return;
+        }
+
+        let mut bytes = Vec::new();
+        if file.read_to_end(&mut bytes).is_err() {
+            return;
+        }
+        // Leave a partly written last line for the next refresh
+        let complete = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        self.read_offset += complete as u64;
+        self.push_text(&String::from_utf8_lossy(&bytes[..complete]));
+
+        if self.follow {
+            self.select_last();
+        }
+    }
+
+    /// Add log text; lines without a level (e.g. wrapped messages) take the previous line's.
+    fn push_text(&mut self, text: &str) {
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let level = parse_level(line)
+                .or_else(|| self.lines.last().map(|l| l.level))
+                .unwrap_or_default();
+            self.lines.push(LogLine {
+                level,
+                text: line.to_string(),
+            });
+        }
+        if self.lines.len() > MAX_LINES {
+            self.lines.drain(..self.lines.len() - MAX_LINES);
+        }
+    }
+
+    /// Lines passing the level filter and search.
+    pub fn visible_lines(&self) -> Vec<&LogLine> {
+        let query = self.search.to_lowercase();
+        self.lines
+            .iter()
+            .filter(|line| line.level >= self.min_level)
+            .filter(|line| query.is_empty() || line.text.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    pub fn select_next(&mut self) {
+        let last = self.visible_lines().len().saturating_sub(1);
+        let i = self.list_state.selected().map_or(0, |i| (i + 1).min(last));
+        self.list_state.select(Some(i));
+        self.follow = i == last;
+    }
+
+    pub fn select_prev(&mut self) {
+        let i = self
+            .list_state
+            .selected()
+            .map_or(0, |i| i.saturating_sub(1));
+        self.list_state.select(Some(i));
+        self.follow = false;
+    }
+
+    pub fn select_first(&mut self) {
+        self.list_state.select(Some(0));
+        self.follow = false;
+    }
+
+    /// Jump to the newest line and keep following the log.
+    pub fn select_last(&mut self) {
+        let count = self.visible_lines().len();
+        self.list_state.select(count.checked_sub(1));
+        self.follow = true;
+    }
+
+    /// Show only more severe lines, wrapping back to everything.
+    pub fn cycle_level(&mut self) {
+        self.min_level = self.min_level.next();
+        self.select_last();
+    }
+}
+
+/// Render the log viewer overlay.
+pub fn render_log_viewer(frame: &mut Frame, area: Rect, state: &mut LogViewerState) {
+    let popup_area = Rect {
+        x: area.x + 2,
+        y: area.y + 1,
+        width: area.width.saturating_sub(4),
+        height: area.height.saturating_sub(2),
+    };
+    frame.render_widget(Clear, popup_area);
+
+    let mut title = format!("Log · {}+", state.min_level.label());
+    if !state.search.is_empty() || state.searching {
+        title.push_str(&format!(" · \"{}\"", state.search));
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    let items: Vec<ListItem> = state
+        .visible_lines()
+        .into_iter()
+        .map(|line| {
+            ListItem::new(Line::from(Span::styled(
+                line.text.clone(),
+                Style::default().fg(line.level.color()),
+            )))
+        })
+        .collect();
+    if items.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No log lines match").style(Style::default().fg(Color::DarkGray)),
+            chunks[0],
+        );
+    } else {
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+        frame.render_stateful_widget(list, chunks[0], &mut state.list_state);
+    }
+
+    let hint = if state.searching {
+        format!("Search: {}_   Enter: keep  Esc: clear", state.search)
+    } else {
+        String::from("j/k: scroll  g/G: top/bottom  l: level  /: search  Esc: close")
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(hint, Style::default().fg(Color::DarkGray))),
+        chunks[1],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_filter_and_search() {
+        let mut log = LogViewerState::default();
+        log.push_text(
+            "2024-05-01T10:00:00.000Z  INFO subsonic_tui::app: Connected\n\
+             2024-05-01T10:00:01.000Z  WARN subsonic_tui::app: Failed to load lyrics\n\
+             continued message\n\
+             2024-05-01T10:00:02.000Z ERROR subsonic_tui::player: Audio player error\n",
+        );
+
+        let levels: Vec<LogLevel> = log.lines.iter().map(|l| l.level).collect();
+        assert_eq!(
+            levels,
+            vec![
+                LogLevel::Info,
+                LogLevel::Warn,
+                LogLevel::Warn,
+                LogLevel::Error
+            ]
+        );
+
+        log.min_level = LogLevel::Warn;
+        assert_eq!(log.visible_lines().len(), 3);
+
+        log.search = String::from("PLAYER");
+        let found: Vec<&str> = log
+            .visible_lines()
+            .iter()
+            .map(|l| l.text.as_str())
+            .collect();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].ends_with("Audio player error"));
+    }
+}
@@ -0,0 +1,90 @@
+//! Password prompt, for when none is configured or the server rejects it.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Password prompt state.
+#[derive(Debug, Default)]
+pub struct LoginState {
+    /// Whether the prompt is open
+    pub visible: bool,
+
+    /// User the password is for
+    pub username: String,
+
+    /// Password typed so far
+    pub password: String,
+
+    /// Why the prompt opened, e.g. the server's rejection
+    pub error: Option<String>,
+}
+
+impl LoginState {
+    /// Ask for `username`'s password.
+    pub fn open(&mut self, username: &str, error: Option<String>) {
+        self.username = username.to_string();
+        self.password.clear();
+        self.error = error;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.password.clear();
+    }
+}
+
+/// Render the password prompt.
+pub fn render_login(frame: &mut Frame, area: Rect, state: &LoginState, server_url: &str) {
+    let width = 60.min(area.width);
+    let height = 9.min(area.height);
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Log in")
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let field = |name: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{:<10}", name), Style::default().fg(Color::Cyan)),
+            Span::raw(value),
+        ])
+    };
+
+    let mut lines = vec![
+        field("Server", server_url.to_string()),
+        field("User", state.username.clone()),
+        field(
+            "Password",
+            format!("{}_", "•".repeat(state.password.chars().count())),
+        ),
+    ];
+    if let Some(error) = &state.error {
+        lines.push(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter: connect  Esc: cancel",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, popup_area);
+}
@@ -1,5 +1,7 @@
 //! Lyrics display component.
 
+use std::collections::HashMap;
+
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
@@ -9,15 +11,33 @@ use ratatui::{
 };
 
 use crate::client::models::{LyricLine, StructuredLyrics};
+use crate::ui::scrollbar::render_scrollbar;
+
+/// Step the lyrics delay is nudged by, in milliseconds.
+pub const LYRICS_DELAY_STEP_MS: i64 = 100;
+
+/// Default width of the lyrics panel, in percent of the content area.
+pub const DEFAULT_WIDTH: u16 = 40;
 
 /// Lyrics display state.
 pub struct LyricsState {
     /// Whether lyrics panel is visible
     pub visible: bool,
 
+    /// Width of the panel in percent of the content area
+    pub width: u16,
+
     /// Current lyrics data
     pub lyrics: Option<StructuredLyrics>,
 
+    /// Every version of the song's lyrics (languages or sources), and which
+    /// of them is shown
+    pub versions: Vec<StructuredLyrics>,
+    pub version: usize,
+
+    /// Language shown when a song has lyrics in it
+    pub lang: Option<String>,
+
     /// Song ID for currently loaded lyrics
     pub song_id: Option<String>,
 
@@ -29,6 +49,13 @@ pub struct LyricsState {
 
     /// Scroll state for unsynced lyrics
     pub scroll_state: ListState,
+
+    /// Whether the user moved away from the current line of synced lyrics
+    /// to pick a line to jump to
+    pub browsing: bool,
+
+    /// Extra delay in milliseconds applied to each song's synced lyrics, keyed by song ID
+    pub delays: HashMap<String, i64>,
 }
 
 impl Default for LyricsState {
@@ -41,11 +68,17 @@ impl LyricsState {
     pub fn new() -> Self {
         Self {
             visible: false,
+            width: DEFAULT_WIDTH,
             lyrics: None,
+            versions: Vec::new(),
+            version: 0,
+            lang: None,
             song_id: None,
             loading: false,
             current_line: 0,
             scroll_state: ListState::default(),
+            browsing: false,
+            delays: HashMap::new(),
         }
     }
 
@@ -54,23 +87,79 @@ impl LyricsState {
         self.visible = !self.visible;
     }
 
-    /// Set lyrics for a song (picks the best from available options).
+    /// Set lyrics for a song, showing the best version of them: in the
+    /// preferred language if there is one, and synced over unsynced.
     pub fn set_lyrics(&mut self, song_id: String, lyrics_list: Vec<StructuredLyrics>) {
         self.song_id = Some(song_id);
-        // Prefer synced lyrics over unsynced, pick first available
-        self.lyrics = lyrics_list
-            .into_iter()
-            .max_by_key(|l| if l.synced { 1 } else { 0 });
         self.loading = false;
+        self.version = best_version(&lyrics_list, self.lang.as_deref());
+        self.versions = lyrics_list;
+        self.show_version();
+    }
+
+    /// Show the next version of the lyrics. Returns its language, to be
+    /// preferred from now on, or `None` if there's no other version.
+    pub fn cycle_version(&mut self) -> Option<&str> {
+        if self.versions.len() < 2 {
+            return None;
+        }
+        self.version = (self.version + 1) % self.versions.len();
+        self.show_version();
+        let lang = self.versions[self.version].lang.clone();
+        Some(self.lang.insert(lang))
+    }
+
+    fn show_version(&mut self) {
+        self.lyrics = self.versions.get(self.version).cloned();
         self.current_line = 0;
+        self.browsing = false;
         self.scroll_state.select(Some(0));
     }
 
     /// Clear lyrics.
     pub fn clear(&mut self) {
         self.lyrics = None;
+        self.versions.clear();
+        self.version = 0;
         self.song_id = None;
         self.current_line = 0;
+        self.browsing = false;
+    }
+
+    /// Delay applied to the current song's lyrics, in milliseconds.
+    pub fn delay_ms(&self) -> i64 {
+        self.song_id
+            .as_ref()
+            .and_then(|id| self.delays.get(id))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Shift the current song's lyrics later (or earlier, for a negative `delta_ms`).
+    /// Returns false if there are no synced lyrics to adjust.
+    pub fn nudge_delay(&mut self, delta_ms: i64) -> bool {
+        let Some(song_id) = &self.song_id else {
+            return false;
+        };
+        if !self.lyrics.as_ref().is_some_and(|l| l.synced) {
+            return false;
+        }
+
+        let delay = self.delays.get(song_id).copied().unwrap_or(0) + delta_ms;
+        if delay == 0 {
+            self.delays.remove(song_id);
+        } else {
+            self.delays.insert(song_id.clone(), delay);
+        }
+        true
+    }
+
+    /// Playback position (in milliseconds) at which the selected line starts,
+    /// when a synced line has been picked by scrolling.
+    pub fn selected_line_position(&self) -> Option<u64> {
+        let lyrics = self.lyrics.as_ref().filter(|l| l.synced && self.browsing)?;
+        let start = lyrics.line.get(self.scroll_state.selected()?)?.start?;
+        Some((start - lyrics.offset + self.delay_ms()).max(0) as u64)
     }
 
     /// Update current line based on playback position (in milliseconds).
@@ -80,7 +169,7 @@ impl LyricsState {
                 return;
             }
 
-            let offset = lyrics.offset;
+            let offset = lyrics.offset - self.delay_ms();
             let adjusted_pos = position_ms as i64 + offset;
 
             // Find the current line
@@ -97,16 +186,18 @@ impl LyricsState {
 
             if new_line != self.current_line {
                 self.current_line = new_line;
-                self.scroll_state.select(Some(new_line));
+                if !self.browsing {
+                    self.scroll_state.select(Some(new_line));
+                }
             }
         }
     }
 
-    /// Scroll up.
-    #[allow(dead_code)]
+    /// Scroll up. On synced lyrics this starts picking a line to jump to.
     pub fn scroll_up(&mut self) {
         if let Some(lyrics) = &self.lyrics {
             if !lyrics.line.is_empty() {
+                self.browsing = lyrics.synced;
                 let current = self.scroll_state.selected().unwrap_or(0);
                 let new = current.saturating_sub(1);
                 self.scroll_state.select(Some(new));
@@ -114,11 +205,11 @@ impl LyricsState {
         }
     }
 
-    /// Scroll down.
-    #[allow(dead_code)]
+    /// Scroll down. On synced lyrics this starts picking a line to jump to.
     pub fn scroll_down(&mut self) {
         if let Some(lyrics) = &self.lyrics {
             if !lyrics.line.is_empty() {
+                self.browsing = lyrics.synced;
                 let current = self.scroll_state.selected().unwrap_or(0);
                 let new = (current + 1).min(lyrics.line.len().saturating_sub(1));
                 self.scroll_state.select(Some(new));
@@ -127,15 +218,50 @@ impl LyricsState {
     }
 }
 
-/// Render the lyrics panel.
-pub fn render_lyrics(frame: &mut Frame, area: Rect, state: &mut LyricsState) {
+/// Pick the version of lyrics to show: one in `lang` if any, and synced
+/// over unsynced, else the first.
+fn best_version(versions: &[StructuredLyrics], lang: Option<&str>) -> usize {
+    let in_lang = |l: &StructuredLyrics| lang.is_some_and(|lang| l.lang.eq_ignore_ascii_case(lang));
+    (0..versions.len())
+        .max_by_key(|&i| (in_lang(&versions[i]), versions[i].synced, usize::MAX - i))
+        .unwrap_or(0)
+}
+
+/// Render the lyrics panel. Centered lyrics keep the current line in the middle.
+pub fn render_lyrics(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut LyricsState,
+    centered: bool,
+    focused: bool,
+) {
     // Clear background
     frame.render_widget(Clear, area);
 
+    let mut title = String::from("Lyrics");
+    if let (Some(lyrics), 2..) = (&state.lyrics, state.versions.len()) {
+        title.push_str(&format!(
+            " · {} {}/{}",
+            lyrics.lang,
+            state.version + 1,
+            state.versions.len()
+        ));
+    }
+    if state.delay_ms() != 0 {
+        title.push_str(&format!(
+            " · delay {:+.1}s",
+            state.delay_ms() as f64 / 1000.0
+        ));
+    }
+    title.push_str(" [L to close]");
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Lyrics [L to close]")
-        .border_style(Style::default().fg(Color::Cyan));
+        .title(title)
+        .border_style(Style::default().fg(if focused {
+            Color::Cyan
+        } else {
+            Color::DarkGray
+        }));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -155,16 +281,34 @@ pub fn render_lyrics(frame: &mut Frame, area: Rect, state: &mut LyricsState) {
         }
         Some(lyrics) => {
             if lyrics.synced {
+                if !state.browsing {
+                    // Keep the current line in view
+                    state.scroll_state.select(Some(state.current_line));
+                }
                 render_synced_lyrics(
                     frame,
                     inner,
                     &lyrics.line,
                     state.current_line,
                     &mut state.scroll_state,
+                    centered,
                 );
             } else {
-                render_unsynced_lyrics(frame, inner, &lyrics.line, &mut state.scroll_state);
+                render_unsynced_lyrics(
+                    frame,
+                    inner,
+                    &lyrics.line,
+                    &mut state.scroll_state,
+                    centered,
+                );
             }
+            render_scrollbar(
+                frame,
+                area,
+                lyrics.line.len(),
+                inner.height as usize,
+                state.scroll_state.offset(),
+            );
         }
     }
 }
@@ -176,6 +320,7 @@ fn render_synced_lyrics(
     lines: &[LyricLine],
     current_line: usize,
     scroll_state: &mut ListState,
+    centered: bool,
 ) {
     let items: Vec<ListItem> = lines
         .iter()
@@ -190,14 +335,17 @@ fn render_synced_lyrics(
             } else {
                 Style::default().fg(Color::White)
             };
-            ListItem::new(Line::from(Span::styled(&line.value, style)))
+            ListItem::new(align(
+                Line::from(Span::styled(&line.value, style)),
+                centered,
+            ))
         })
         .collect();
 
-    let list = List::new(items).highlight_style(Style::default().bg(Color::DarkGray));
-
-    // Center the current line in view
-    scroll_state.select(Some(current_line));
+    let mut list = List::new(items).highlight_style(Style::default().bg(Color::DarkGray));
+    if centered {
+        list = list.scroll_padding(area.height as usize / 2);
+    }
 
     frame.render_stateful_widget(list, area, scroll_state);
 }
@@ -208,14 +356,15 @@ fn render_unsynced_lyrics(
     area: Rect,
     lines: &[LyricLine],
     scroll_state: &mut ListState,
+    centered: bool,
 ) {
     let items: Vec<ListItem> = lines
         .iter()
         .map(|line| {
-            ListItem::new(Line::from(Span::styled(
-                &line.value,
-                Style::default().fg(Color::White),
-            )))
+            ListItem::new(align(
+                Line::from(Span::styled(&line.value, Style::default().fg(Color::White))),
+                centered,
+            ))
         })
         .collect();
 
@@ -223,3 +372,42 @@ fn render_unsynced_lyrics(
 
     frame.render_stateful_widget(list, area, scroll_state);
 }
+
+/// Center a lyrics line when requested.
+fn align(line: Line, centered: bool) -> Line {
+    if centered {
+        line.centered()
+    } else {
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lyrics(lang: &str, synced: bool) -> StructuredLyrics {
+        serde_json::from_value(serde_json::json!({ "lang": lang, "synced": synced })).unwrap()
+    }
+
+    #[test]
+    fn test_best_version() {
+        let versions = [
+            lyrics("eng", false),
+            lyrics("jpn", false),
+            lyrics("eng", true),
+            lyrics("jpn", true),
+        ];
+        assert_eq!(best_version(&versions, None), 2);
+        assert_eq!(best_version(&versions, Some("JPN")), 3);
+        assert_eq!(best_version(&versions[..2], Some("jpn")), 1);
+        assert_eq!(best_version(&versions[..2], Some("spa")), 0);
+        assert_eq!(best_version(&[], Some("eng")), 0);
+
+        let mut state = LyricsState::new();
+        state.set_lyrics(String::from("s"), versions.to_vec());
+        assert_eq!(state.cycle_version(), Some("jpn"));
+        assert_eq!(state.lyrics, Some(versions[3].clone()));
+        assert_eq!(state.lang.as_deref(), Some("jpn"));
+    }
+}
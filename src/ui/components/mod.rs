@@ -1,13 +1,42 @@
 //! UI components module.
 
+pub mod album_info;
+pub mod avatar;
+pub mod filter;
+pub mod help;
+pub mod import_prompt;
 pub mod library;
+pub mod listeners;
+pub mod log_viewer;
+pub mod login;
 pub mod lyrics;
+pub mod move_prompt;
 pub mod now_playing;
+pub mod playlist_picker;
 pub mod queue;
 pub mod search;
+pub mod server_status;
+pub mod songs_filter;
+pub mod toast;
+pub mod visualizer;
+pub mod year_picker;
 
+pub use album_info::{render_album_info, AlbumInfoState};
+pub use avatar::{render_avatar, AvatarCache};
+pub use help::{render_help, HelpState};
+pub use import_prompt::{render_import_prompt, ImportPromptState};
 pub use library::{render_library, LibraryState};
+pub use listeners::{render_listeners, ListenersState};
+pub use log_viewer::{render_log_viewer, LogViewerState};
+pub use login::{render_login, LoginState};
 pub use lyrics::{render_lyrics, LyricsState};
-pub use now_playing::{render_now_playing, NowPlayingState};
+pub use move_prompt::{render_move_prompt, MovePromptState};
+pub use now_playing::{render_now_playing, render_now_playing_fullscreen, NowPlayingState};
+pub use playlist_picker::{render_playlist_picker, PlaylistPickerState};
 pub use queue::{render_queue, QueueState};
 pub use search::{render_search, SearchState};
+pub use server_status::{render_server_status, LibraryCounts, ServerStatusState};
+pub use songs_filter::{render_songs_filter, SongsFilterState};
+pub use toast::{render_message_history, render_toasts, ToastState};
+pub use visualizer::{render_visualizer, VisualizerState};
+pub use year_picker::{render_year_picker, YearPickerState};
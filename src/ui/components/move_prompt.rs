@@ -0,0 +1,100 @@
+//! Popup asking for the queue position to move songs to.
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Move prompt state.
+#[derive(Debug, Default)]
+pub struct MovePromptState {
+    /// Whether the popup is open
+    pub visible: bool,
+
+    /// Queue rows being moved
+    pub rows: Vec<usize>,
+
+    /// Length of the queue
+    pub len: usize,
+
+    /// Position, as typed
+    pub position: String,
+}
+
+impl MovePromptState {
+    /// Open the prompt to move `rows` of a queue `len` songs long.
+    pub fn open(&mut self, rows: Vec<usize>, len: usize) {
+        self.rows = rows;
+        self.len = len;
+        self.position.clear();
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.rows.clear();
+        self.position.clear();
+    }
+
+    /// Add a typed character; only digits are taken.
+    pub fn input(&mut self, c: char) {
+        if c.is_ascii_digit() && self.position.len() < 6 {
+            self.position.push(c);
+        }
+    }
+
+    /// Get the typed position as a row (counting from 0), if it's one.
+    pub fn row(&self) -> Option<usize> {
+        let position: usize = self.position.parse().ok()?;
+        (1..=self.len).contains(&position).then(|| position - 1)
+    }
+}
+
+/// Render the move prompt popup.
+pub fn render_move_prompt(frame: &mut Frame, area: Rect, state: &MovePromptState) {
+    let width = area.width.saturating_sub(4).min(40);
+    let popup_area = Rect {
+        x: area.x + (area.width.saturating_sub(width)) / 2,
+        y: area.y + area.height.saturating_sub(4) / 2,
+        width,
+        height: 4.min(area.height),
+    };
+    frame.render_widget(Clear, popup_area);
+
+    let title = match state.rows.len() {
+        1 => String::from("Move song"),
+        count => format!("Move {} songs", count),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Length(1)])
+        .split(inner);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(
+                format!("To position (1-{}): ", state.len),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::raw(format!("{}_", state.position)),
+        ])),
+        chunks[0],
+    );
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            "Enter: move  Esc: cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+        chunks[1],
+    );
+}
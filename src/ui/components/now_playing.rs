@@ -1,16 +1,25 @@
 //! Now playing bar component.
 
+use image::DynamicImage;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
 use ratatui_image::{picker::Picker, protocol::StatefulProtocol, StatefulImage};
 
+use super::lyrics::{render_lyrics, LyricsState};
+use super::visualizer::{render_visualizer, VisualizerState};
 use crate::action::{PlayerState, RepeatMode};
 use crate::client::models::Song;
+use crate::ui::hit::{Control, HitRegion, HitRegions};
+use crate::ui::icons::Icons;
+use crate::ui::marquee::Marquee;
+
+/// Width of the volume bar in columns.
+const VOLUME_BAR_WIDTH: u16 = 10;
 
 /// Now playing state.
 pub struct NowPlayingState {
@@ -23,12 +32,30 @@ pub struct NowPlayingState {
     /// Current position in seconds
     pub position: u32,
 
+    /// Current position in milliseconds, as last reported by the player
+    pub position_ms: u64,
+
+    /// Position under the pointer while the progress bar is dragged
+    pub seek_preview: Option<u32>,
+
     /// Total duration in seconds
     pub duration: u32,
 
-    /// Volume (0-100)
+    /// Volume (0-100, or up to `max_volume` when amplified)
     pub volume: u8,
 
+    /// Highest volume, the right end of the volume bar
+    pub max_volume: u8,
+
+    /// Share of the track downloaded (0.0 to 1.0)
+    pub buffered: f32,
+
+    /// Frame of the buffering spinner
+    pub spinner: usize,
+
+    /// Playback speed (1.0 = normal)
+    pub speed: f32,
+
     /// Shuffle enabled
     pub shuffle: bool,
 
@@ -46,44 +73,76 @@ pub struct NowPlayingState {
 
     /// Whether scrobble was sent for current track
     pub scrobbled: bool,
+
+    /// Greeting shown while nothing is playing
+    pub greeting: Option<String>,
+
+    /// Scroll position of a title too long for the bar
+    pub marquee: Marquee,
+
+    /// Show the time remaining instead of elapsed
+    pub show_remaining: bool,
+
+    /// Icons to draw with
+    pub icons: Icons,
 }
 
 impl NowPlayingState {
-    pub fn new() -> Self {
-        // Try to create a picker for terminal graphics
-        let picker = Picker::from_query_stdio().ok();
-
+    /// Create the state, drawing images with `picker` if the terminal can.
+    pub fn new(picker: Option<Picker>) -> Self {
         Self {
             current_song: None,
             state: PlayerState::default(),
             position: 0,
+            position_ms: 0,
+            seek_preview: None,
             duration: 0,
             volume: 80,
+            max_volume: 100,
+            buffered: 0.0,
+            spinner: 0,
+            speed: 1.0,
             shuffle: false,
             repeat: RepeatMode::default(),
             album_art: None,
             album_art_id: None,
             picker,
             scrobbled: false,
+            greeting: None,
+            marquee: Marquee::default(),
+            show_remaining: false,
+            icons: Icons::default(),
         }
     }
 
+    /// Position to show: the seek preview while dragging, otherwise the playback position.
+    fn shown_position(&self) -> u32 {
+        self.seek_preview.unwrap_or(self.position)
+    }
+
     /// Get progress as a ratio (0.0 to 1.0).
     pub fn progress(&self) -> f64 {
         if self.duration == 0 {
             0.0
         } else {
-            (self.position as f64) / (self.duration as f64)
+            (self.shown_position() as f64) / (self.duration as f64)
         }
     }
 
     /// Format position as MM:SS.
     pub fn position_string(&self) -> String {
-        let mins = self.position / 60;
-        let secs = self.position % 60;
+        let position = self.shown_position();
+        let mins = position / 60;
+        let secs = position % 60;
         format!("{mins}:{secs:02}")
     }
 
+    /// Format the time remaining as -MM:SS.
+    pub fn remaining_string(&self) -> String {
+        let remaining = self.duration.saturating_sub(self.shown_position());
+        format!("-{}:{:02}", remaining / 60, remaining % 60)
+    }
+
     /// Format duration as MM:SS.
     pub fn duration_string(&self) -> String {
         let mins = self.duration / 60;
@@ -94,41 +153,41 @@ impl NowPlayingState {
     /// Get play/pause symbol.
     pub fn state_symbol(&self) -> &'static str {
         match self.state {
-            PlayerState::Playing => "",
-            PlayerState::Paused => "",
-            PlayerState::Stopped => "",
-            PlayerState::Buffering => "󰔟",
+            PlayerState::Playing => self.icons.playing,
+            PlayerState::Paused => self.icons.paused,
+            PlayerState::Stopped => self.icons.stopped,
+            PlayerState::Buffering => self.icons.buffering,
         }
     }
 
     /// Get shuffle symbol.
     pub fn shuffle_symbol(&self) -> &'static str {
         if self.shuffle {
-            "󰒟"
+            self.icons.shuffle_on
         } else {
-            "󰒞"
+            self.icons.shuffle_off
         }
     }
 
     /// Get volume symbol based on level.
     pub fn volume_symbol(&self) -> &'static str {
         if self.volume == 0 {
-            "󰝟"
+            self.icons.muted
         } else if self.volume < 30 {
-            "󰕿"
+            self.icons.volume_low
         } else if self.volume < 70 {
-            "󰖀"
+            self.icons.volume_mid
         } else {
-            "󰕾"
+            self.icons.volume_high
         }
     }
 
     /// Get repeat symbol.
     pub fn repeat_symbol(&self) -> &'static str {
         match self.repeat {
-            RepeatMode::Off => "󰑗",
-            RepeatMode::All => "󰑖",
-            RepeatMode::One => "󰑘",
+            RepeatMode::Off => self.icons.repeat_off,
+            RepeatMode::All => self.icons.repeat_all,
+            RepeatMode::One => self.icons.repeat_one,
         }
     }
 
@@ -136,7 +195,11 @@ impl NowPlayingState {
     pub fn set_song(&mut self, song: Song) {
         self.duration = song.duration.unwrap_or(0) as u32;
         self.position = 0;
+        self.position_ms = 0;
+        self.seek_preview = None;
+        self.buffered = 0.0;
         self.scrobbled = false;
+        self.marquee.reset();
         // Clear album art if it's a different album
         let new_art_id = song.cover_art.clone();
         if self.album_art_id != new_art_id {
@@ -146,12 +209,16 @@ impl NowPlayingState {
         self.current_song = Some(song);
     }
 
-    /// Set the album art image data.
-    pub fn set_album_art(&mut self, image_data: &[u8]) {
+    /// Set the position in seconds (e.g. after a seek).
+    pub fn set_position(&mut self, secs: u32) {
+        self.position = secs;
+        self.position_ms = secs as u64 * 1000;
+    }
+
+    /// Set the album art from a decoded image.
+    pub fn set_album_art(&mut self, image: DynamicImage) {
         if let Some(picker) = &self.picker {
-            if let Ok(dyn_image) = image::load_from_memory(image_data) {
-                self.album_art = Some(picker.new_resize_protocol(dyn_image));
-            }
+            self.album_art = Some(picker.new_resize_protocol(image));
         }
     }
 
@@ -174,6 +241,7 @@ impl NowPlayingState {
     pub fn clear(&mut self) {
         self.current_song = None;
         self.position = 0;
+        self.position_ms = 0;
         self.duration = 0;
         self.state = PlayerState::Stopped;
         self.album_art = None;
@@ -183,20 +251,31 @@ impl NowPlayingState {
 }
 
 /// Render the now playing bar.
-pub fn render_now_playing(frame: &mut Frame, area: Rect, state: &mut NowPlayingState) {
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::DarkGray));
-
-    let inner = block.inner(area);
-    frame.render_widget(block, area);
+/// In `compact` mode the bar is drawn without borders or album art, in 3 lines.
+pub fn render_now_playing(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut NowPlayingState,
+    compact: bool,
+    hits: &mut HitRegions,
+) {
+    let inner = if compact {
+        area
+    } else {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        inner
+    };
 
-    if area.height < 4 {
+    if inner.height < 2 {
         return;
     }
 
     // Layout: [album art] [info + progress]
-    let has_album_art = state.album_art.is_some() && state.picker.is_some();
+    let has_album_art = !compact && state.album_art.is_some() && state.picker.is_some();
     let art_width = if has_album_art { inner.height * 2 } else { 0 }; // Approximate square
 
     let main_chunks = Layout::default()
@@ -235,9 +314,13 @@ pub fn render_now_playing(frame: &mut Frame, area: Rect, state: &mut NowPlayingS
 
     // Row 1: Song title and artist
     if let Some(song) = &state.current_song {
-        let star = if song.starred.is_some() { "󰓎 " } else { "" };
+        let star = if song.starred.is_some() {
+            format!("{} ", state.icons.starred)
+        } else {
+            String::new()
+        };
 
-        let title_line = Line::from(vec![
+        let title_spans = vec![
             Span::styled(star, Style::default().fg(Color::Yellow)),
             Span::styled(
                 &song.title,
@@ -247,15 +330,22 @@ pub fn render_now_playing(frame: &mut Frame, area: Rect, state: &mut NowPlayingS
             ),
             Span::styled("  ", Style::default()),
             Span::styled(song.display_artist(), Style::default().fg(Color::Gray)),
-        ]);
+        ];
+        let title_line = if state.marquee.enabled {
+            state.marquee.line(title_spans, chunks[0].width as usize)
+        } else {
+            Line::from(title_spans)
+        };
         frame.render_widget(Paragraph::new(title_line), chunks[0]);
     } else {
+        let text = state.greeting.as_deref().unwrap_or("No track playing");
         let no_song = Line::from(vec![Span::styled(
-            "No track playing",
+            text,
             Style::default().fg(Color::DarkGray),
         )]);
         frame.render_widget(Paragraph::new(no_song), chunks[0]);
     }
+    hits.add(HitRegion::NowPlayingTitle, chunks[0]);
 
     // Row 2: Playback controls (left) + album/metadata (center) + volume (right)
     let controls_chunks = Layout::default()
@@ -288,13 +378,30 @@ pub fn render_now_playing(frame: &mut Frame, area: Rect, state: &mut NowPlayingS
     };
 
     let controls = Line::from(vec![
-        Span::styled("󰒮 ", Style::default().fg(normal_color)),
+        Span::styled(
+            format!("{} ", state.icons.previous),
+            Style::default().fg(normal_color),
+        ),
         Span::styled(state.state_symbol(), Style::default().fg(play_color)),
-        Span::styled(" 󰒭 ", Style::default().fg(normal_color)),
+        Span::styled(
+            format!(" {} ", state.icons.next),
+            Style::default().fg(normal_color),
+        ),
         Span::styled(state.shuffle_symbol(), Style::default().fg(shuffle_color)),
         Span::styled(" ", Style::default()),
         Span::styled(state.repeat_symbol(), Style::default().fg(repeat_color)),
     ]);
+
+    // Each button takes clicks on the spaces around it too
+    let buttons = [
+        Some(Control::Previous),
+        Some(Control::PlayPause),
+        Some(Control::Next),
+        Some(Control::Shuffle),
+        None,
+        Some(Control::Repeat),
+    ];
+    add_control_hits(hits, &controls, &buttons, controls_chunks[0]);
     frame.render_widget(Paragraph::new(controls), controls_chunks[0]);
 
     // Album + metadata
@@ -323,6 +430,9 @@ pub fn render_now_playing(frame: &mut Frame, area: Rect, state: &mut NowPlayingS
         if let Some(bitrate) = song.bit_rate {
             extra.push(format!("{}kbps", bitrate));
         }
+        if (state.speed - 1.0).abs() > f32::EPSILON {
+            extra.push(format!("{:.2}x", state.speed));
+        }
 
         if !extra.is_empty() {
             meta_spans.push(Span::styled(
@@ -335,8 +445,7 @@ pub fn render_now_playing(frame: &mut Frame, area: Rect, state: &mut NowPlayingS
     }
 
     // Volume bar (right side)
-    let vol_bar = render_volume_bar(state.volume);
-    let volume_line = Line::from(vec![
+    let mut volume_line = vec![
         Span::styled(
             state.volume_symbol(),
             Style::default().fg(if state.volume == 0 {
@@ -346,24 +455,242 @@ pub fn render_now_playing(frame: &mut Frame, area: Rect, state: &mut NowPlayingS
             }),
         ),
         Span::styled(" ", Style::default()),
-        vol_bar,
-        Span::styled(
-            format!(" {:>3}%", state.volume),
-            Style::default().fg(Color::DarkGray),
-        ),
-    ]);
+    ];
+    volume_line.extend(render_volume_bar(state.volume, state.max_volume));
+    volume_line.push(Span::styled(
+        format!(" {:>3}%", state.volume),
+        Style::default().fg(volume_color(state.volume, Color::DarkGray)),
+    ));
+    let volume_line = Line::from(volume_line);
     frame.render_widget(
         Paragraph::new(volume_line).alignment(Alignment::Right),
         controls_chunks[2],
     );
 
+    // The bar sits right before the " XXX%" percentage
+    let volume_area = controls_chunks[2];
+    let bar_end = volume_area.right().saturating_sub(5);
+    let bar_start = bar_end.saturating_sub(VOLUME_BAR_WIDTH).max(volume_area.x);
+    hits.add(
+        HitRegion::VolumeBar,
+        Rect {
+            x: bar_start,
+            width: bar_end.saturating_sub(bar_start),
+            ..volume_area
+        },
+    );
+
     // Row 3: Progress bar with timestamps
-    render_progress_bar(frame, chunks[2], state);
+    render_progress_bar(frame, chunks[2], state, hits);
+}
+
+/// Render the full-screen now playing view, with lyrics beside the player when given.
+pub fn render_now_playing_fullscreen(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut NowPlayingState,
+    lyrics: Option<&mut LyricsState>,
+    visualizer: Option<&mut VisualizerState>,
+    hits: &mut HitRegions,
+) {
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Now Playing [v to close]")
+        .border_style(Style::default().fg(Color::DarkGray));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let player_area = match lyrics {
+        Some(lyrics) => {
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(inner);
+            render_lyrics(frame, halves[1], lyrics, true, true);
+            halves[0]
+        }
+        None => inner,
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),                                        // Top margin
+            Constraint::Min(0),                                           // Album art
+            Constraint::Length(1),                                        // Spacing
+            Constraint::Length(1),                                        // Title
+            Constraint::Length(1),                                        // Artist
+            Constraint::Length(1),                                        // Album
+            Constraint::Length(1),                                        // Spacing
+            Constraint::Length(if visualizer.is_some() { 7 } else { 0 }), // Visualizer
+            Constraint::Length(1),                                        // Progress bar
+            Constraint::Length(1),                                        // Controls
+            Constraint::Length(1),                                        // Bottom margin
+        ])
+        .split(player_area);
+
+    // Album art, kept roughly square (terminal cells are about twice as tall as wide)
+    if let (Some(protocol), Some(_)) = (&mut state.album_art, &state.picker) {
+        let art_area = chunks[1];
+        let height = art_area.height.min(art_area.width / 2);
+        let width = height * 2;
+        let art_rect = Rect {
+            x: art_area.x + (art_area.width - width) / 2,
+            y: art_area.y,
+            width,
+            height,
+        };
+        frame.render_stateful_widget(StatefulImage::default(), art_rect, protocol);
+    }
+
+    let centered = |line: Line<'static>| Paragraph::new(line).alignment(Alignment::Center);
+    if let Some(song) = &state.current_song {
+        let star = if song.starred.is_some() {
+            format!("{} ", state.icons.starred)
+        } else {
+            String::new()
+        };
+        frame.render_widget(
+            centered(Line::from(vec![
+                Span::styled(star, Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    song.title.to_uppercase(),
+                    Style::default()
+                        .fg(Color::Reset)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ])),
+            chunks[3],
+        );
+        frame.render_widget(
+            centered(Line::from(Span::styled(
+                song.display_artist().to_string(),
+                Style::default()
+                    .fg(Color::Gray)
+                    .add_modifier(Modifier::BOLD),
+            ))),
+            chunks[4],
+        );
+        let album = match song.year {
+            Some(year) => format!("{} ({})", song.display_album(), year),
+            None => song.display_album().to_string(),
+        };
+        frame.render_widget(
+            centered(Line::from(Span::styled(
+                album,
+                Style::default().fg(Color::DarkGray),
+            ))),
+            chunks[5],
+        );
+        hits.add(
+            HitRegion::NowPlayingTitle,
+            Rect {
+                height: 3,
+                ..chunks[3]
+            }
+            .intersection(player_area),
+        );
+    } else {
+        let text = state.greeting.as_deref().unwrap_or("No track playing");
+        frame.render_widget(
+            centered(Line::from(Span::styled(
+                text.to_string(),
+                Style::default().fg(Color::DarkGray),
+            ))),
+            chunks[3],
+        );
+    }
+
+    // Wide progress bar with a small margin on each side
+    let margin = 2.min(chunks[8].width / 2);
+    let progress_area = Rect {
+        x: chunks[8].x + margin,
+        width: chunks[8].width - margin * 2,
+        ..chunks[8]
+    };
+
+    // Visualizer above it, with a blank line between
+    if let Some(visualizer) = visualizer {
+        let area = Rect {
+            height: chunks[7].height.saturating_sub(1),
+            ..progress_area
+        };
+        render_visualizer(frame, area, visualizer);
+    }
+    render_progress_bar(frame, progress_area, state, hits);
+
+    let controls = Line::from(vec![
+        Span::styled(state.state_symbol(), Style::default().fg(Color::Reset)),
+        Span::styled("   ", Style::default()),
+        Span::styled(
+            state.shuffle_symbol(),
+            Style::default().fg(if state.shuffle {
+                Color::Green
+            } else {
+                Color::DarkGray
+            }),
+        ),
+        Span::styled("  ", Style::default()),
+        Span::styled(
+            state.repeat_symbol(),
+            Style::default().fg(if state.repeat == RepeatMode::Off {
+                Color::DarkGray
+            } else {
+                Color::Green
+            }),
+        ),
+        Span::styled("   ", Style::default()),
+        Span::styled(state.volume_symbol(), Style::default().fg(Color::Gray)),
+        Span::styled(
+            format!(" {}%", state.volume),
+            Style::default().fg(volume_color(state.volume, Color::DarkGray)),
+        ),
+    ]);
+    let buttons = [
+        Some(Control::PlayPause),
+        None,
+        Some(Control::Shuffle),
+        None,
+        Some(Control::Repeat),
+    ];
+    let width = (controls.width() as u16).min(chunks[9].width);
+    let controls_area = Rect {
+        x: chunks[9].x + (chunks[9].width - width) / 2,
+        width,
+        ..chunks[9]
+    };
+    add_control_hits(hits, &controls, &buttons, controls_area);
+    frame.render_widget(Paragraph::new(controls), controls_area);
+}
+
+/// Register each span of a line of controls drawn at the left of `area` as
+/// the button it shows, if any.
+fn add_control_hits(
+    hits: &mut HitRegions,
+    controls: &Line,
+    buttons: &[Option<Control>],
+    area: Rect,
+) {
+    let mut x = area.x;
+    for (span, button) in controls.spans.iter().zip(buttons) {
+        let width = (span.width() as u16).min(area.right().saturating_sub(x));
+        if let Some(button) = *button {
+            hits.add(HitRegion::Control(button), Rect { x, width, ..area });
+        }
+        x += width;
+    }
 }
 
 /// Render a modern progress bar with timestamps.
-fn render_progress_bar(frame: &mut Frame, area: Rect, state: &NowPlayingState) {
-    let time_width = 6; // "MM:SS" + space
+fn render_progress_bar(
+    frame: &mut Frame,
+    area: Rect,
+    state: &NowPlayingState,
+    hits: &mut HitRegions,
+) {
+    let time_width = 7; // "-MM:SS" (remaining) + space
     let bar_width = area.width.saturating_sub(time_width * 2 + 2);
 
     let chunks = Layout::default()
@@ -375,15 +702,31 @@ fn render_progress_bar(frame: &mut Frame, area: Rect, state: &NowPlayingState) {
         ])
         .split(area);
 
-    // Current time (left)
-    let current_time =
-        Paragraph::new(state.position_string()).style(Style::default().fg(Color::Gray));
+    // Elapsed or remaining time (left), or how much is downloaded while buffering
+    let time = if state.state == PlayerState::Buffering {
+        let spinner = state.icons.spinner;
+        format!(
+            "{} {:>2.0}%",
+            spinner[state.spinner % spinner.len()],
+            state.buffered * 100.0
+        )
+    } else if state.show_remaining {
+        state.remaining_string()
+    } else {
+        state.position_string()
+    };
+    let current_time = Paragraph::new(time).style(Style::default().fg(Color::Gray));
     frame.render_widget(current_time, chunks[0]);
+    hits.add(HitRegion::TimeDisplay, chunks[0]);
 
-    // Progress bar (center)
+    // Progress bar (center), with the part downloaded but not played yet
+    // lighter than the rest
     let progress = state.progress();
     let filled_width = ((bar_width as f64) * progress) as usize;
-    let empty_width = bar_width as usize - filled_width;
+    let buffered_width = ((bar_width as f32 * state.buffered) as usize)
+        .clamp(filled_width, bar_width as usize)
+        - filled_width;
+    let empty_width = bar_width as usize - filled_width - buffered_width;
 
     // Use smooth block characters for gradient effect
     let filled_char = "━";
@@ -397,19 +740,30 @@ fn render_progress_bar(frame: &mut Frame, area: Rect, state: &NowPlayingState) {
                 Style::default().fg(Color::Magenta),
             ),
             Span::styled(handle, Style::default().fg(Color::Reset)),
+            Span::styled(
+                empty_char.repeat(buffered_width),
+                Style::default().fg(Color::Gray),
+            ),
             Span::styled(
                 empty_char.repeat(empty_width),
                 Style::default().fg(Color::DarkGray),
             ),
         ]
     } else {
-        vec![Span::styled(
-            empty_char.repeat(bar_width as usize),
-            Style::default().fg(Color::DarkGray),
-        )]
+        vec![
+            Span::styled(
+                empty_char.repeat(buffered_width),
+                Style::default().fg(Color::Gray),
+            ),
+            Span::styled(
+                empty_char.repeat(empty_width),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]
     };
 
     frame.render_widget(Paragraph::new(Line::from(bar_spans)), chunks[1]);
+    hits.add(HitRegion::ProgressBar, chunks[1]);
 
     // Total time (right)
     let total_time = Paragraph::new(format!(" {}", state.duration_string()))
@@ -418,18 +772,34 @@ fn render_progress_bar(frame: &mut Frame, area: Rect, state: &NowPlayingState) {
     frame.render_widget(total_time, chunks[2]);
 }
 
-/// Render a modern volume bar.
-fn render_volume_bar(volume: u8) -> Span<'static> {
-    let bar_width = 10;
-    let filled = (volume as usize * bar_width) / 100;
-    let empty = bar_width - filled;
-
-    let filled_str = "━".repeat(filled);
-    let empty_str = "─".repeat(empty);
+/// Render a modern volume bar running up to `max_volume`, with the part
+/// above 100% in a warning color.
+fn render_volume_bar(volume: u8, max_volume: u8) -> Vec<Span<'static>> {
+    let bar_width = VOLUME_BAR_WIDTH as usize;
+    let columns = |volume: u8| (volume as usize * bar_width) / max_volume.max(1) as usize;
+    let filled = columns(volume).min(bar_width);
+    let normal = filled.min(columns(100));
 
     // Use Cyan for volume bar to differentiate from progress bar
-    Span::styled(
-        format!("{}{}", filled_str, empty_str),
-        Style::default().fg(Color::Cyan),
-    )
+    vec![
+        Span::styled("━".repeat(normal), Style::default().fg(Color::Cyan)),
+        Span::styled(
+            "━".repeat(filled - normal),
+            Style::default().fg(Color::Yellow),
+        ),
+        Span::styled(
+            "─".repeat(bar_width - filled),
+            Style::default().fg(Color::Cyan),
+        ),
+    ]
+}
+
+/// Get the color of a volume readout: `color`, or a warning color when
+/// amplified past 100%.
+fn volume_color(volume: u8, color: Color) -> Color {
+    if volume > 100 {
+        Color::Yellow
+    } else {
+        color
+    }
 }
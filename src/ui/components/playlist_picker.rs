@@ -0,0 +1,188 @@
+//! Popup for adding songs to a playlist.
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::client::models::Playlist;
+use crate::smart;
+use crate::ui::centered_rect;
+
+/// Label of the row creating a playlist, after the existing ones.
+const NEW_PLAYLIST: &str = "New playlist…";
+
+/// Where the picked songs go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaylistChoice {
+    Existing { id: String, name: String },
+    New(String),
+}
+
+/// Playlist picker state.
+#[derive(Debug, Default)]
+pub struct PlaylistPickerState {
+    /// Whether the popup is open
+    pub visible: bool,
+
+    /// What is being added, e.g. `song "Title"`
+    pub label: String,
+
+    /// IDs of the songs to add
+    pub song_ids: Vec<String>,
+
+    /// Playlists to choose from
+    pub playlists: Vec<(String, String)>,
+
+    /// Selected row; one past the playlists is "New playlist…"
+    pub selected: usize,
+
+    /// Name of the new playlist, while it is being typed
+    pub new_name: Option<String>,
+}
+
+impl PlaylistPickerState {
+    /// Open the picker to add `song_ids` to one of `playlists`.
+    pub fn open(&mut self, label: String, song_ids: Vec<String>, playlists: &[Playlist]) {
+        self.label = label;
+        self.song_ids = song_ids;
+        self.playlists = playlists
+            .iter()
+            .filter(|p| !smart::is_smart(&p.id))
+            .map(|p| (p.id.clone(), p.name.clone()))
+            .collect();
+        self.selected = 0;
+        self.new_name = None;
+        self.visible = true;
+    }
+
+    /// Start naming a new playlist, suggesting `name`.
+    pub fn start_new(&mut self, name: String) {
+        self.selected = self.playlists.len();
+        self.new_name = Some(name);
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.new_name = None;
+        self.song_ids.clear();
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % (self.playlists.len() + 1);
+    }
+
+    pub fn select_prev(&mut self) {
+        let rows = self.playlists.len() + 1;
+        self.selected = (self.selected + rows - 1) % rows;
+    }
+
+    /// Choose the selected row: a playlist, or start naming a new one. While
+    /// naming, the typed name (if any) is chosen.
+    pub fn choose(&mut self) -> Option<PlaylistChoice> {
+        if let Some(name) = &self.new_name {
+            let name = name.trim();
+            return (!name.is_empty()).then(|| PlaylistChoice::New(name.to_string()));
+        }
+        match self.playlists.get(self.selected) {
+            Some((id, name)) => Some(PlaylistChoice::Existing {
+                id: id.clone(),
+                name: name.clone(),
+            }),
+            None => {
+                self.new_name = Some(String::new());
+                None
+            }
+        }
+    }
+}
+
+/// Render the playlist picker popup.
+pub fn render_playlist_picker(frame: &mut Frame, area: Rect, state: &PlaylistPickerState) {
+    let popup_area = centered_rect(50, 60, area);
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Add {} to", state.label))
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    let mut items: Vec<ListItem> = state
+        .playlists
+        .iter()
+        .map(|(_, name)| ListItem::new(name.as_str()))
+        .collect();
+    items.push(match &state.new_name {
+        Some(name) => ListItem::new(Line::from(vec![
+            Span::styled("Name: ", Style::default().fg(Color::Cyan)),
+            Span::raw(format!("{}_", name)),
+        ])),
+        None => ListItem::new(Span::styled(
+            NEW_PLAYLIST,
+            Style::default().fg(Color::Green),
+        )),
+    });
+
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut list_state = ListState::default().with_selected(Some(state.selected));
+    frame.render_stateful_widget(list, chunks[0], &mut list_state);
+
+    let hint = if state.new_name.is_some() {
+        "Enter: create  Esc: back"
+    } else {
+        "j/k: select  Enter: add  Esc: cancel"
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(hint, Style::default().fg(Color::DarkGray))),
+        chunks[1],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_playlist() {
+        let playlist: Playlist =
+            serde_json::from_value(serde_json::json!({"id": "pl1", "name": "Mix"})).unwrap();
+        let mut state = PlaylistPickerState::default();
+        state.open(
+            String::from("\"Song\""),
+            vec![String::from("s1")],
+            &[playlist],
+        );
+        assert_eq!(
+            state.choose(),
+            Some(PlaylistChoice::Existing {
+                id: String::from("pl1"),
+                name: String::from("Mix"),
+            })
+        );
+
+        // The last row asks for a name, and needs one
+        state.select_next();
+        assert_eq!(state.choose(), None);
+        assert_eq!(state.new_name.as_deref(), Some(""));
+        assert_eq!(state.choose(), None);
+        state.new_name = Some(String::from(" Road trip "));
+        assert_eq!(
+            state.choose(),
+            Some(PlaylistChoice::New(String::from("Road trip")))
+        );
+    }
+}
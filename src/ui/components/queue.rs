@@ -8,7 +8,20 @@ use ratatui::{
     Frame,
 };
 
+use std::collections::HashSet;
+
+use super::filter::{filter_title, from_visible, step, to_visible, visible_indices};
+use super::library::format_total_duration;
+use crate::action::{QueueSort, QueueSource};
 use crate::client::models::Song;
+use crate::config::ShuffleConfig;
+use crate::shuffle;
+use crate::ui::icons::Icons;
+use crate::ui::marquee::Marquee;
+use crate::ui::scrollbar::render_scrollbar;
+
+/// Default width of the queue panel, in percent of the content area.
+pub const DEFAULT_WIDTH: u16 = 30;
 
 /// Queue state.
 #[derive(Debug, Default)]
@@ -16,6 +29,9 @@ pub struct QueueState {
     /// Songs in the queue
     pub songs: Vec<Song>,
 
+    /// Where each song was queued from (parallel to `songs`)
+    pub sources: Vec<QueueSource>,
+
     /// Currently playing index
     pub current_index: Option<usize>,
 
@@ -24,30 +40,89 @@ pub struct QueueState {
 
     /// Whether the queue is visible
     pub visible: bool,
+
+    /// Width of the panel in percent of the content area
+    pub width: u16,
+
+    /// Whether finished tracks are removed from the queue
+    pub consume: bool,
+
+    /// Quick filter narrowing the displayed songs (empty = show all)
+    pub filter: String,
+
+    /// Whether the filter is being typed
+    pub filtering: bool,
+
+    /// Scroll position of titles too long for a row
+    pub marquee: Marquee,
+
+    /// Songs that couldn't be played, even transcoded by the server
+    pub unsupported: HashSet<String>,
+
+    /// Songs known to be in the track cache
+    pub cached: HashSet<String>,
+
+    /// Rows marked to be moved together
+    pub marked: HashSet<usize>,
+
+    /// Show the songs under a header for each album they're queued from
+    pub grouped: bool,
+
+    /// Albums whose songs are hidden under their header, by album ID
+    collapsed_albums: HashSet<String>,
+
+    pub icons: Icons,
 }
 
 impl QueueState {
     pub fn new() -> Self {
         Self {
             visible: true,
+            width: DEFAULT_WIDTH,
             ..Default::default()
         }
     }
 
     /// Add a song to the queue.
-    pub fn add(&mut self, song: Song) {
+    pub fn add(&mut self, song: Song, source: QueueSource) {
         self.songs.push(song);
+        self.sources.push(source);
     }
 
     /// Add multiple songs to the queue.
-    pub fn add_all(&mut self, songs: Vec<Song>) {
+    pub fn add_all(&mut self, songs: Vec<Song>, source: QueueSource) {
+        self.sources
+            .extend(std::iter::repeat_n(source, songs.len()));
         self.songs.extend(songs);
     }
 
+    /// Insert songs right after the current song, so they play next.
+    /// With nothing playing they go to the front of the queue.
+    pub fn insert_next(&mut self, songs: Vec<Song>, source: QueueSource) {
+        let at = self.current_index.map_or(0, |i| i + 1);
+        let count = songs.len();
+        self.sources
+            .splice(at..at, std::iter::repeat_n(source, count));
+        self.songs.splice(at..at, songs);
+        self.marked = self
+            .marked
+            .iter()
+            .map(|&i| if i >= at { i + count } else { i })
+            .collect();
+
+        if let Some(selected) = self.list_state.selected() {
+            if selected >= at {
+                self.list_state.select(Some(selected + count));
+            }
+        }
+    }
+
     /// Clear the queue.
     pub fn clear(&mut self) {
         self.songs.clear();
+        self.sources.clear();
         self.current_index = None;
+        self.marked.clear();
         self.list_state.select(None);
     }
 
@@ -55,6 +130,13 @@ impl QueueState {
     pub fn remove(&mut self, index: usize) {
         if index < self.songs.len() {
             self.songs.remove(index);
+            self.sources.remove(index);
+            self.marked = self
+                .marked
+                .iter()
+                .filter(|&&i| i != index)
+                .map(|&i| if i > index { i - 1 } else { i })
+                .collect();
 
             // Adjust current index if needed
             if let Some(current) = self.current_index {
@@ -89,6 +171,90 @@ impl QueueState {
         }
     }
 
+    /// Remove every song queued from the same source as the selected one.
+    /// Returns the number of songs removed.
+    pub fn remove_selected_source(&mut self) -> usize {
+        let Some(source) = self.selected_source().cloned() else {
+            return 0;
+        };
+        self.retain(|_, s| *s != source)
+    }
+
+    /// Keep only the songs passing `keep`. Returns the number of songs removed.
+    pub fn retain(&mut self, keep: impl Fn(&Song, &QueueSource) -> bool) -> usize {
+        let order: Vec<usize> = (0..self.songs.len())
+            .filter(|&i| keep(&self.songs[i], &self.sources[i]))
+            .collect();
+        let removed = self.songs.len() - order.len();
+        self.reorder(order);
+        removed
+    }
+
+    /// Move a song to a new position, keeping the current index on the same song.
+    pub fn move_item(&mut self, index: usize, new_index: usize) {
+        let mut order: Vec<usize> = (0..self.songs.len()).collect();
+        let moved = order.remove(index);
+        order.insert(new_index, moved);
+        self.reorder(order);
+    }
+
+    /// Mark the selected row to be moved with the others marked, or unmark
+    /// it, and step to the next row.
+    pub fn toggle_mark(&mut self) {
+        if let Some(index) = self.selected() {
+            if !self.marked.remove(&index) {
+                self.marked.insert(index);
+            }
+            self.select_next();
+        }
+    }
+
+    /// Get the marked rows in order, or the selected row if none are marked.
+    pub fn marked_or_selected(&self) -> Vec<usize> {
+        let mut rows: Vec<usize> = if self.marked.is_empty() {
+            self.selected().into_iter().collect()
+        } else {
+            self.marked.iter().copied().collect()
+        };
+        rows.sort_unstable();
+        rows
+    }
+
+    /// Move songs together, in their order, so the first lands at `position`.
+    pub fn move_rows(&mut self, rows: &[usize], position: usize) {
+        let mut order: Vec<usize> = (0..self.songs.len())
+            .filter(|i| !rows.contains(i))
+            .collect();
+        let at = position.min(order.len());
+        order.splice(
+            at..at,
+            rows.iter().copied().filter(|&i| i < self.songs.len()),
+        );
+        self.reorder(order);
+    }
+
+    /// Move each of the marked rows one step down (or up), past the next
+    /// unmarked row. Rows marked next to each other move as a block.
+    pub fn step_marked(&mut self, down: bool) {
+        let mut order: Vec<usize> = (0..self.songs.len()).collect();
+        let marked = |i: usize| self.marked.contains(&i);
+        let pairs: Vec<usize> = (0..order.len().saturating_sub(1)).collect();
+        if down {
+            for &i in pairs.iter().rev() {
+                if marked(order[i]) && !marked(order[i + 1]) {
+                    order.swap(i, i + 1);
+                }
+            }
+        } else {
+            for &i in &pairs {
+                if marked(order[i + 1]) && !marked(order[i]) {
+                    order.swap(i, i + 1);
+                }
+            }
+        }
+        self.reorder(order);
+    }
+
     /// Get the current song.
     pub fn current_song(&self) -> Option<&Song> {
         self.current_index.and_then(|i| self.songs.get(i))
@@ -121,6 +287,22 @@ impl QueueState {
         }
     }
 
+    /// Remove the current song and move to the one that followed it.
+    /// Falls back to a plain advance if nothing is currently playing.
+    pub fn consume_current(&mut self) -> Option<&Song> {
+        let Some(current) = self.current_index else {
+            return self.advance();
+        };
+
+        self.remove(current);
+        if current < self.songs.len() {
+            self.current_index = Some(current);
+            self.current_song()
+        } else {
+            None
+        }
+    }
+
     /// Move to the previous song.
     pub fn go_back(&mut self) -> Option<&Song> {
         if let Some((i, _)) = self.previous_song() {
@@ -142,23 +324,105 @@ impl QueueState {
     }
 
     /// Shuffle the queue (keeping current song if any).
-    pub fn shuffle(&mut self) {
-        use rand::seq::SliceRandom;
-
+    pub fn shuffle(&mut self, config: &ShuffleConfig) {
         if self.songs.len() <= 1 {
             return;
         }
 
-        let mut rng = rand::thread_rng();
-
+        // Keep current song, shuffle the rest
+        let rest: Vec<usize> = (0..self.songs.len())
+            .filter(|&i| Some(i) != self.current_index)
+            .collect();
+        let songs: Vec<&Song> = rest.iter().map(|&i| &self.songs[i]).collect();
+        let mut order: Vec<usize> = shuffle::order(&songs, config)
+            .into_iter()
+            .map(|i| rest[i])
+            .collect();
         if let Some(current_idx) = self.current_index {
-            // Keep current song, shuffle the rest
-            let current = self.songs.remove(current_idx);
-            self.songs.shuffle(&mut rng);
-            self.songs.insert(0, current);
-            self.current_index = Some(0);
-        } else {
-            self.songs.shuffle(&mut rng);
+            order.insert(0, current_idx);
+        }
+
+        let selected = self.list_state.selected();
+        self.reorder(order);
+        // The cursor stays on its row rather than following the song
+        self.list_state.select(selected);
+    }
+
+    /// Remove duplicate songs (by id), keeping the first occurrence.
+    /// The currently playing entry is always the one kept for its song.
+    /// Returns the number of songs removed.
+    pub fn dedup(&mut self) -> usize {
+        let current_id = self.current_song().map(|s| s.id.clone());
+        let mut seen = HashSet::new();
+
+        let order: Vec<usize> = (0..self.songs.len())
+            .filter(|&i| {
+                let id = &self.songs[i].id;
+                if Some(i) == self.current_index {
+                    true
+                } else if current_id.as_ref() == Some(id) {
+                    false
+                } else {
+                    seen.insert(id.clone())
+                }
+            })
+            .collect();
+
+        let removed = self.songs.len() - order.len();
+        self.reorder(order);
+        removed
+    }
+
+    /// Sort the queue, keeping the current song and selection on the same entries.
+    pub fn sort(&mut self, sort: QueueSort) {
+        let mut order: Vec<usize> = (0..self.songs.len()).collect();
+        let songs = &self.songs;
+
+        match sort {
+            QueueSort::ArtistAlbumTrack => order.sort_by_cached_key(|&i| {
+                let song = &songs[i];
+                (
+                    song.display_artist().to_lowercase(),
+                    song.album.as_deref().unwrap_or_default().to_lowercase(),
+                    song.disc_number.unwrap_or(0),
+                    song.track.unwrap_or(0),
+                )
+            }),
+            QueueSort::Title => order.sort_by_cached_key(|&i| songs[i].title.to_lowercase()),
+        }
+
+        self.reorder(order);
+    }
+
+    /// Rebuild the queue from original indices in their new order, dropping any not listed.
+    /// Remaps the current index and selection to follow their songs.
+    fn reorder(&mut self, order: Vec<usize>) {
+        let position = |old: usize| order.iter().position(|&i| i == old);
+        let new_current = self.current_index.and_then(position);
+        let selected = self.list_state.selected();
+        let new_selected = selected.and_then(position);
+        let new_marked = self.marked.iter().filter_map(|&i| position(i)).collect();
+
+        let mut old_songs: Vec<Option<Song>> = self.songs.drain(..).map(Some).collect();
+        self.songs = order
+            .iter()
+            .filter_map(|&i| old_songs.get_mut(i).and_then(Option::take))
+            .collect();
+        let mut old_sources: Vec<Option<QueueSource>> = self.sources.drain(..).map(Some).collect();
+        self.sources = order
+            .iter()
+            .filter_map(|&i| old_sources.get_mut(i).and_then(Option::take))
+            .collect();
+
+        self.current_index = new_current;
+        self.marked = new_marked;
+        if self.songs.is_empty() {
+            self.list_state.select(None);
+        } else if let Some(i) = new_selected {
+            self.list_state.select(Some(i));
+        } else if let Some(i) = selected {
+            // Selected entry was dropped; keep the cursor in place
+            self.list_state.select(Some(i.min(self.songs.len() - 1)));
         }
     }
 
@@ -173,42 +437,111 @@ impl QueueState {
         self.songs.is_empty()
     }
 
-    /// Move selection up.
-    pub fn select_previous(&mut self) {
-        if self.songs.is_empty() {
+    /// Get the indices of the songs that pass the filter, or that can be
+    /// selected when grouped by album.
+    pub fn visible(&self) -> Vec<usize> {
+        match self.layout() {
+            Some(layout) => layout.selectable(),
+            None => visible_indices(&self.songs, &self.filter),
+        }
+    }
+
+    /// Lay out the queue under album headers, if grouped and not filtered.
+    fn layout(&self) -> Option<QueueLayout<'_>> {
+        (self.grouped && self.filter.is_empty()).then(|| QueueLayout {
+            groups: album_groups(&self.songs),
+            collapsed: &self.collapsed_albums,
+        })
+    }
+
+    /// Select the header of a collapsed album instead of a song hidden under it.
+    fn snap_selection(&mut self) {
+        let snapped = self.layout().and_then(|layout| {
+            let selected = self.list_state.selected()?;
+            layout.song_at(layout.row_of(selected)?)
+        });
+        if snapped.is_some() {
+            self.list_state.select(snapped);
+        }
+    }
+
+    /// Group the queue by album, or stop grouping it.
+    pub fn toggle_grouped(&mut self) {
+        self.grouped = !self.grouped;
+        self.snap_selection();
+    }
+
+    /// Collapse or expand the album of the selected song.
+    pub fn toggle_album_group(&mut self) {
+        let Some(key) = self.layout().and_then(|layout| {
+            let selected = self.list_state.selected()?;
+            let group = layout
+                .groups
+                .iter()
+                .find(|group| group.contains(selected))?;
+            Some(group.key.clone())
+        }) else {
             return;
+        };
+        if !self.collapsed_albums.remove(&key) {
+            self.collapsed_albums.insert(key);
         }
+        self.snap_selection();
+    }
 
-        let i = match self.list_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.songs.len() - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
+    /// Collapse every album in the queue, or expand them all if they
+    /// already are.
+    pub fn toggle_album_groups(&mut self) {
+        let Some(layout) = self.layout() else {
+            return;
         };
-        self.list_state.select(Some(i));
+        let keys: HashSet<String> = layout.groups.into_iter().map(|group| group.key).collect();
+        if keys.is_subset(&self.collapsed_albums) {
+            self.collapsed_albums.clear();
+        } else {
+            self.collapsed_albums = keys;
+        }
+        self.snap_selection();
+    }
+
+    /// Move selection up.
+    pub fn select_previous(&mut self) {
+        self.step_selection(-1, true);
     }
 
     /// Move selection down.
     pub fn select_next(&mut self) {
-        if self.songs.is_empty() {
-            return;
+        self.step_selection(1, true);
+    }
+
+    /// Move the selection by `delta` rows, skipping songs hidden by the filter.
+    fn step_selection(&mut self, delta: isize, wrap: bool) {
+        if let Some(i) = step(&self.visible(), self.list_state.selected(), delta, wrap) {
+            self.list_state.select(Some(i));
         }
+    }
 
-        let i = match self.list_state.selected() {
-            Some(i) => {
-                if i >= self.songs.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+    /// Select the song shown on the given row, returning its queue index.
+    pub fn select_row(&mut self, row: usize) -> Option<usize> {
+        let index = match self.layout() {
+            Some(layout) => layout.song_at(row),
+            None => from_visible(&self.visible(), Some(row)),
         };
-        self.list_state.select(Some(i));
+        if index.is_some() {
+            self.list_state.select(index);
+        }
+        index
+    }
+
+    /// Select the first match if the filter hides the current selection.
+    pub fn refresh_filter(&mut self) {
+        let visible = self.visible();
+        if to_visible(&visible, self.list_state.selected()).is_none() {
+            if let Some(&first) = visible.first() {
+                self.list_state.select(Some(first));
+            }
+        }
+        self.snap_selection();
     }
 
     /// Get selected index.
@@ -221,17 +554,22 @@ impl QueueState {
         self.list_state.selected().and_then(|i| self.songs.get(i))
     }
 
+    /// Get the source of the selected song.
+    pub fn selected_source(&self) -> Option<&QueueSource> {
+        self.list_state.selected().and_then(|i| self.sources.get(i))
+    }
+
     /// Jump to the top of the queue.
     pub fn jump_to_top(&mut self) {
-        if !self.songs.is_empty() {
-            self.list_state.select(Some(0));
+        if let Some(&first) = self.visible().first() {
+            self.list_state.select(Some(first));
         }
     }
 
     /// Jump to the bottom of the queue.
     pub fn jump_to_bottom(&mut self) {
-        if !self.songs.is_empty() {
-            self.list_state.select(Some(self.songs.len() - 1));
+        if let Some(&last) = self.visible().last() {
+            self.list_state.select(Some(last));
         }
     }
 
@@ -239,37 +577,29 @@ impl QueueState {
     pub fn jump_to_current(&mut self) {
         if let Some(idx) = self.current_index {
             self.list_state.select(Some(idx));
+            self.snap_selection();
         }
     }
 
     /// Scroll half a page down.
     pub fn scroll_half_page_down(&mut self, page_size: usize) {
-        if self.songs.is_empty() {
-            return;
-        }
-
-        let half_page = page_size / 2;
-        let current = self.list_state.selected().unwrap_or(0);
-        let new_index = (current + half_page).min(self.songs.len() - 1);
-        self.list_state.select(Some(new_index));
+        self.step_selection((page_size / 2) as isize, false);
     }
 
     /// Scroll half a page up.
     pub fn scroll_half_page_up(&mut self, page_size: usize) {
-        if self.songs.is_empty() {
-            return;
-        }
-
-        let half_page = page_size / 2;
-        let current = self.list_state.selected().unwrap_or(0);
-        let new_index = current.saturating_sub(half_page);
-        self.list_state.select(Some(new_index));
+        self.step_selection(-((page_size / 2) as isize), false);
     }
 }
 
 /// Render the queue panel.
 pub fn render_queue(frame: &mut Frame, area: Rect, state: &mut QueueState, focused: bool) {
-    let title = format!("Queue ({})", state.songs.len());
+    let title = if state.consume {
+        format!("Queue ({}) [consume]", state.songs.len())
+    } else {
+        format!("Queue ({})", state.songs.len())
+    };
+    let title = filter_title(title, &state.filter, state.filtering);
 
     let border_color = if focused {
         Color::Cyan
@@ -290,150 +620,595 @@ pub fn render_queue(frame: &mut Frame, area: Rect, state: &mut QueueState, focus
     // Get the currently selected index
     let selected_index = state.list_state.selected();
 
-    let items: Vec<ListItem> = state
-        .songs
-        .iter()
-        .enumerate()
-        .map(|(i, song)| {
-            let is_current = state.current_index == Some(i);
-            let is_selected = selected_index == Some(i);
-
-            let prefix = if is_current { "▶ " } else { "  " };
-
-            // Determine styles based on selection and current playing state
-            let (title_style, duration_style) = if is_selected {
-                // Selected item - use brighter colors for contrast on dark gray background
-                if is_current {
-                    (
-                        Style::default()
-                            .fg(Color::LightGreen)
-                            .add_modifier(Modifier::BOLD),
-                        Style::default().fg(Color::White),
-                    )
-                } else {
-                    (
-                        Style::default()
-                            .fg(Color::White)
-                            .add_modifier(Modifier::BOLD),
-                        Style::default().fg(Color::Gray),
-                    )
-                }
+    let layout = state.layout();
+    let visible = match layout {
+        Some(_) => Vec::new(),
+        None => state.visible(),
+    };
+    let song_item = |i: usize| {
+        let song = &state.songs[i];
+        let is_current = state.current_index == Some(i);
+        let is_selected = selected_index == Some(i);
+        let is_marked = state.marked.contains(&i);
+
+        let unsupported = state.unsupported.contains(&song.id);
+        let cached = state.cached.contains(&song.id);
+        let prefix = if is_current {
+            "▶ "
+        } else if is_marked {
+            "+ "
+        } else if unsupported {
+            "✗ "
+        } else if cached {
+            "• "
+        } else {
+            "  "
+        };
+
+        // Determine styles based on selection and current playing state
+        let (title_style, duration_style) = if is_selected {
+            // Selected item - use brighter colors for contrast on dark gray background
+            if is_current {
+                (
+                    Style::default()
+                        .fg(Color::LightGreen)
+                        .add_modifier(Modifier::BOLD),
+                    Style::default().fg(Color::White),
+                )
             } else {
-                // Not selected
-                if is_current {
-                    (
-                        Style::default()
-                            .fg(Color::Green)
-                            .add_modifier(Modifier::BOLD),
-                        Style::default().fg(Color::DarkGray),
-                    )
-                } else {
-                    (
-                        Style::default().fg(Color::White),
-                        Style::default().fg(Color::DarkGray),
-                    )
-                }
-            };
+                (
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                    Style::default().fg(Color::Gray),
+                )
+            }
+        } else {
+            // Not selected
+            if is_current {
+                (
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                    Style::default().fg(Color::DarkGray),
+                )
+            } else {
+                (
+                    Style::default().fg(Color::White),
+                    Style::default().fg(Color::DarkGray),
+                )
+            }
+        };
+
+        // Marked rows stand out; tracks that couldn't be played are greyed out
+        let title_style = if is_marked && !is_current {
+            title_style.fg(Color::Yellow)
+        } else if unsupported && !is_current {
+            title_style
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::CROSSED_OUT)
+        } else {
+            title_style
+        };
+        let prefix_style = if cached && !is_current && !is_marked && !unsupported {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            title_style
+        };
+
+        let duration = song.duration_string();
+        let duration_len = duration.chars().count();
+
+        // Use char count for proper width calculation
+        let title_char_count = song.title.chars().count();
+        let prefix_len = 2; // "▶ " or "  "
+
+        // Space needed: prefix + title + at least 1 space + duration
+        // Available: text_width
+        // Title can use: text_width - duration_len - 1 (for spacing)
+        let title_max_width = text_width.saturating_sub(duration_len + 1 + prefix_len);
+
+        if title_char_count <= title_max_width && title_max_width > 0 {
+            // Title fits on one line with duration
+            let padding = text_width.saturating_sub(prefix_len + title_char_count + duration_len);
 
-            let duration = song.duration_string();
-            let duration_len = duration.chars().count();
-
-            // Use char count for proper width calculation
-            let title_char_count = song.title.chars().count();
-            let prefix_len = 2; // "▶ " or "  "
-
-            // Space needed: prefix + title + at least 1 space + duration
-            // Available: text_width
-            // Title can use: text_width - duration_len - 1 (for spacing)
-            let title_max_width = text_width.saturating_sub(duration_len + 1 + prefix_len);
-
-            if title_char_count <= title_max_width && title_max_width > 0 {
-                // Title fits on one line with duration
-                let padding =
-                    text_width.saturating_sub(prefix_len + title_char_count + duration_len);
-                let spaces = " ".repeat(padding);
-                ListItem::new(Line::from(vec![
-                    Span::styled(prefix, title_style),
-                    Span::styled(song.title.clone(), title_style),
-                    Span::raw(spaces),
-                    Span::styled(duration, duration_style),
-                ]))
+            // Show where the song was queued from in the gap, if there's room
+            let source = state
+                .sources
+                .get(i)
+                .map(|source| source_label(&source.label(), padding))
+                .unwrap_or_default();
+            let spaces = " ".repeat(padding - source.chars().count());
+            ListItem::new(Line::from(vec![
+                Span::styled(prefix, prefix_style),
+                Span::styled(song.title.clone(), title_style),
+                Span::styled(source, Style::default().fg(Color::DarkGray)),
+                Span::raw(spaces),
+                Span::styled(duration, duration_style),
+            ]))
+        } else if state.marquee.enabled {
+            // Keep to one line, scrolling the title of the selected and playing rows
+            let title = if is_selected || is_current {
+                state.marquee.line(
+                    vec![Span::styled(song.title.clone(), title_style)],
+                    title_max_width,
+                )
             } else {
-                // Title needs to wrap - create multiple lines
-                let mut lines = Vec::new();
-                let title_chars: Vec<char> = song.title.chars().collect();
-
-                // First line width: what's available after prefix
-                let first_line_max = text_width.saturating_sub(prefix_len);
-                let first_chunk: String = title_chars.iter().take(first_line_max).collect();
-                let mut pos = first_chunk.chars().count();
-
-                lines.push(Line::from(vec![
-                    Span::styled(prefix, title_style),
-                    Span::styled(first_chunk, title_style),
-                ]));
-
-                // Continuation lines (indented with same prefix width)
-                let continuation_indent = "  "; // Same as prefix width
-                let continuation_width = text_width.saturating_sub(2);
-
-                while pos < title_chars.len() {
-                    let remaining_chars = title_chars.len() - pos;
-                    let is_last_chunk = remaining_chars <= continuation_width;
-
-                    if is_last_chunk {
-                        // Last chunk - try to fit duration on same line
-                        let chunk: String = title_chars.iter().skip(pos).collect();
-                        let chunk_len = chunk.chars().count();
-                        let space_for_duration = continuation_width.saturating_sub(chunk_len);
-
-                        if space_for_duration > duration_len {
-                            // Duration fits on this line
-                            let padding = space_for_duration.saturating_sub(duration_len);
-                            let spaces = " ".repeat(padding);
-                            lines.push(Line::from(vec![
-                                Span::raw(continuation_indent),
-                                Span::styled(chunk, title_style),
-                                Span::raw(spaces),
-                                Span::styled(duration.clone(), duration_style),
-                            ]));
-                        } else {
-                            // Duration doesn't fit, put it on next line
-                            lines.push(Line::from(vec![
-                                Span::raw(continuation_indent),
-                                Span::styled(chunk, title_style),
-                            ]));
-                            let duration_padding = text_width.saturating_sub(duration_len);
-                            let duration_spaces = " ".repeat(duration_padding);
-                            lines.push(Line::from(vec![
-                                Span::raw(duration_spaces),
-                                Span::styled(duration.clone(), duration_style),
-                            ]));
-                        }
-                        break;
-                    } else {
-                        let chunk: String = title_chars
-                            .iter()
-                            .skip(pos)
-                            .take(continuation_width)
-                            .collect();
-                        pos += chunk.chars().count();
+                let cut: String = song
+                    .title
+                    .chars()
+                    .take(title_max_width.saturating_sub(1))
+                    .collect();
+                Line::from(Span::styled(format!("{}…", cut), title_style))
+            };
+            let padding = text_width.saturating_sub(prefix_len + title.width() + duration_len);
+            let mut spans = vec![Span::styled(prefix, prefix_style)];
+            spans.extend(title.spans);
+            spans.push(Span::raw(" ".repeat(padding)));
+            spans.push(Span::styled(duration, duration_style));
+            ListItem::new(Line::from(spans))
+        } else {
+            // Title needs to wrap - create multiple lines
+            let mut lines = Vec::new();
+            let title_chars: Vec<char> = song.title.chars().collect();
+
+            // First line width: what's available after prefix
+            let first_line_max = text_width.saturating_sub(prefix_len);
+            let first_chunk: String = title_chars.iter().take(first_line_max).collect();
+            let mut pos = first_chunk.chars().count();
+
+            lines.push(Line::from(vec![
+                Span::styled(prefix, prefix_style),
+                Span::styled(first_chunk, title_style),
+            ]));
+
+            // Continuation lines (indented with same prefix width)
+            let continuation_indent = "  "; // Same as prefix width
+            let continuation_width = text_width.saturating_sub(2);
+
+            while pos < title_chars.len() {
+                let remaining_chars = title_chars.len() - pos;
+                let is_last_chunk = remaining_chars <= continuation_width;
+
+                if is_last_chunk {
+                    // Last chunk - try to fit duration on same line
+                    let chunk: String = title_chars.iter().skip(pos).collect();
+                    let chunk_len = chunk.chars().count();
+                    let space_for_duration = continuation_width.saturating_sub(chunk_len);
 
+                    if space_for_duration > duration_len {
+                        // Duration fits on this line
+                        let padding = space_for_duration.saturating_sub(duration_len);
+                        let spaces = " ".repeat(padding);
                         lines.push(Line::from(vec![
                             Span::raw(continuation_indent),
                             Span::styled(chunk, title_style),
+                            Span::raw(spaces),
+                            Span::styled(duration.clone(), duration_style),
+                        ]));
+                    } else {
+                        // Duration doesn't fit, put it on next line
+                        lines.push(Line::from(vec![
+                            Span::raw(continuation_indent),
+                            Span::styled(chunk, title_style),
+                        ]));
+                        let duration_padding = text_width.saturating_sub(duration_len);
+                        let duration_spaces = " ".repeat(duration_padding);
+                        lines.push(Line::from(vec![
+                            Span::raw(duration_spaces),
+                            Span::styled(duration.clone(), duration_style),
                         ]));
                     }
-                }
+                    break;
+                } else {
+                    let chunk: String = title_chars
+                        .iter()
+                        .skip(pos)
+                        .take(continuation_width)
+                        .collect();
+                    pos += chunk.chars().count();
 
-                ListItem::new(lines)
+                    lines.push(Line::from(vec![
+                        Span::raw(continuation_indent),
+                        Span::styled(chunk, title_style),
+                    ]));
+                }
             }
-        })
-        .collect();
+
+            ListItem::new(lines)
+        }
+    };
+    let (items, rows, selected_row): (Vec<ListItem>, usize, Option<usize>) = match &layout {
+        Some(layout) => (
+            (0..layout.len())
+                .filter_map(|row| layout.row(row))
+                .map(|row| match row {
+                    QueueRow::Album(position) => {
+                        album_header(state, layout, position, content_width)
+                    }
+                    QueueRow::Song(i) => song_item(i),
+                })
+                .collect(),
+            layout.len(),
+            selected_index.and_then(|i| layout.row_of(i)),
+        ),
+        None => (
+            visible.iter().map(|&i| song_item(i)).collect(),
+            visible.len(),
+            to_visible(&visible, selected_index),
+        ),
+    };
+    let grouped = layout.is_some();
 
     let list = List::new(items)
         .block(block)
         .highlight_style(Style::default().bg(Color::DarkGray));
 
-    frame.render_stateful_widget(list, area, &mut state.list_state);
+    let offset = if state.filter.is_empty() && !grouped {
+        frame.render_stateful_widget(list, area, &mut state.list_state);
+        state.list_state.offset()
+    } else {
+        // Rows no longer line up with queue indices, so render from a mapped selection
+        let mut list_state = ListState::default().with_selected(selected_row);
+        frame.render_stateful_widget(list, area, &mut list_state);
+        list_state.offset()
+    };
+    render_scrollbar(
+        frame,
+        area,
+        rows,
+        area.height.saturating_sub(2) as usize,
+        offset,
+    );
+}
+
+/// Songs queued one after another from the same album.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AlbumGroup {
+    /// Album ID, or name if the songs have no ID
+    key: String,
+    start: usize,
+    len: usize,
+}
+
+impl AlbumGroup {
+    fn contains(&self, index: usize) -> bool {
+        (self.start..self.start + self.len).contains(&index)
+    }
+}
+
+/// Split the queue into runs of songs from the same album.
+fn album_groups(songs: &[Song]) -> Vec<AlbumGroup> {
+    let key = |song: &Song| {
+        song.album_id
+            .clone()
+            .or_else(|| song.album.clone())
+            .unwrap_or_default()
+    };
+    let mut groups: Vec<AlbumGroup> = Vec::new();
+    for (i, song) in songs.iter().enumerate() {
+        let key = key(song);
+        match groups.last_mut() {
+            Some(group) if group.key == key => group.len += 1,
+            _ => groups.push(AlbumGroup {
+                key,
+                start: i,
+                len: 1,
+            }),
+        }
+    }
+    groups
+}
+
+/// A row of the queue grouped by album.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QueueRow {
+    /// Header of the group at this position
+    Album(usize),
+    /// Song at this index
+    Song(usize),
+}
+
+/// The queue under album headers.
+struct QueueLayout<'a> {
+    groups: Vec<AlbumGroup>,
+    collapsed: &'a HashSet<String>,
+}
+
+impl QueueLayout<'_> {
+    /// Number of songs shown under a group's header.
+    fn shown(&self, group: &AlbumGroup) -> usize {
+        if self.collapsed.contains(&group.key) {
+            0
+        } else {
+            group.len
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.groups.iter().map(|group| 1 + self.shown(group)).sum()
+    }
+
+    fn row(&self, mut row: usize) -> Option<QueueRow> {
+        for (position, group) in self.groups.iter().enumerate() {
+            if row == 0 {
+                return Some(QueueRow::Album(position));
+            }
+            row -= 1;
+            let shown = self.shown(group);
+            if row < shown {
+                return Some(QueueRow::Song(group.start + row));
+            }
+            row -= shown;
+        }
+        None
+    }
+
+    /// Get the row showing a song: its own, or its album's header when the
+    /// album is collapsed.
+    fn row_of(&self, index: usize) -> Option<usize> {
+        let mut row = 0;
+        for group in &self.groups {
+            if group.contains(index) {
+                return Some(if self.shown(group) == 0 {
+                    row
+                } else {
+                    row + 1 + index - group.start
+                });
+            }
+            row += 1 + self.shown(group);
+        }
+        None
+    }
+
+    /// Get the song selected on a row. The header of a collapsed album
+    /// stands for its first song; that of an expanded one selects nothing.
+    fn song_at(&self, row: usize) -> Option<usize> {
+        match self.row(row)? {
+            QueueRow::Song(i) => Some(i),
+            QueueRow::Album(position) => {
+                let group = &self.groups[position];
+                (self.shown(group) == 0).then_some(group.start)
+            }
+        }
+    }
+
+    /// Get the songs that can be selected, in row order.
+    fn selectable(&self) -> Vec<usize> {
+        self.groups
+            .iter()
+            .flat_map(|group| group.start..group.start + self.shown(group).max(1))
+            .collect()
+    }
+}
+
+/// Build the header of an album group: its name and artist, and the length
+/// of its songs at the right.
+fn album_header(
+    state: &QueueState,
+    layout: &QueueLayout,
+    position: usize,
+    width: usize,
+) -> ListItem<'static> {
+    let group = &layout.groups[position];
+    let songs = &state.songs[group.start..group.start + group.len];
+    let icon = if layout.shown(group) == 0 {
+        state.icons.collapsed
+    } else {
+        state.icons.expanded
+    };
+    let first = &songs[0];
+    let album = first.album.as_deref().unwrap_or("Unknown album");
+    let title = match first.artist.as_deref() {
+        Some(artist) => format!("{} {} · {}", icon, album, artist),
+        None => format!("{} {}", icon, album),
+    };
+    let seconds = songs.iter().filter_map(|song| song.duration).sum();
+    let length = format!(" {}", format_total_duration(seconds));
+
+    let room = width.saturating_sub(length.chars().count());
+    let title: String = if title.chars().count() > room {
+        let cut: String = title.chars().take(room.saturating_sub(1)).collect();
+        format!("{}…", cut)
+    } else {
+        title
+    };
+    let padding = width.saturating_sub(title.chars().count() + length.chars().count());
+
+    // The album playing stands out while its songs are hidden
+    let playing =
+        layout.shown(group) == 0 && state.current_index.is_some_and(|i| group.contains(i));
+    let color = if playing { Color::Green } else { Color::Yellow };
+    ListItem::new(Line::from(vec![
+        Span::styled(
+            title,
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(" ".repeat(padding)),
+        Span::styled(length, Style::default().fg(Color::DarkGray)),
+    ]))
+}
+
+/// Fit a source label into `width` columns as "  label ", truncating with an ellipsis.
+/// Returns an empty string when there isn't room for a useful amount of it.
+fn source_label(label: &str, width: usize) -> String {
+    const MIN_CHARS: usize = 6;
+    // Two spaces before the label and one after to separate it from the duration
+    let room = width.saturating_sub(3);
+    if room < MIN_CHARS {
+        return String::new();
+    }
+
+    if label.chars().count() <= room {
+        format!("  {} ", label)
+    } else {
+        let truncated: String = label.chars().take(room - 1).collect();
+        format!("  {}… ", truncated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(id: &str, title: &str) -> Song {
+        serde_json::from_value(serde_json::json!({ "id": id, "title": title })).unwrap()
+    }
+
+    fn album(id: &str) -> QueueSource {
+        QueueSource::Album {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+        }
+    }
+
+    fn ids(queue: &QueueState) -> Vec<&str> {
+        queue.songs.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_dedup_keeps_current_entry() {
+        let mut queue = QueueState::new();
+        queue.add_all(
+            vec![
+                song("a", "A"),
+                song("b", "B"),
+                song("a", "A"),
+                song("c", "C"),
+                song("b", "B"),
+            ],
+            QueueSource::Songs,
+        );
+        queue.current_index = Some(2);
+
+        assert_eq!(queue.dedup(), 2);
+        assert_eq!(ids(&queue), vec!["b", "a", "c"]);
+        assert_eq!(queue.current_index, Some(1));
+    }
+
+    #[test]
+    fn test_sort_remaps_current_index() {
+        let mut queue = QueueState::new();
+        queue.add_all(
+            vec![song("1", "Zebra"), song("2", "apple"), song("3", "Mango")],
+            QueueSource::Songs,
+        );
+        queue.current_index = Some(0);
+        queue.list_state.select(Some(2));
+
+        queue.sort(QueueSort::Title);
+        assert_eq!(ids(&queue), vec!["2", "3", "1"]);
+        assert_eq!(queue.current_index, Some(2));
+        assert_eq!(queue.selected(), Some(1));
+    }
+
+    #[test]
+    fn test_remove_selected_source() {
+        let mut queue = QueueState::new();
+        queue.add_all(vec![song("1", "One"), song("2", "Two")], album("x"));
+        queue.add(song("3", "Three"), QueueSource::Search);
+        queue.add_all(vec![song("4", "Four")], album("x"));
+        queue.current_index = Some(2);
+        queue.list_state.select(Some(1));
+
+        assert_eq!(queue.remove_selected_source(), 3);
+        assert_eq!(ids(&queue), vec!["3"]);
+        assert_eq!(queue.sources, vec![QueueSource::Search]);
+        assert_eq!(queue.current_index, Some(0));
+    }
+
+    #[test]
+    fn test_insert_next_after_current() {
+        let mut queue = QueueState::new();
+        queue.add_all(vec![song("1", "One"), song("2", "Two")], album("x"));
+        queue.current_index = Some(0);
+        queue.list_state.select(Some(1));
+
+        queue.insert_next(
+            vec![song("3", "Three"), song("4", "Four")],
+            QueueSource::Search,
+        );
+        assert_eq!(ids(&queue), vec!["1", "3", "4", "2"]);
+        assert_eq!(queue.sources[1], QueueSource::Search);
+        assert_eq!(queue.current_index, Some(0));
+        assert_eq!(queue.selected(), Some(3));
+    }
+
+    #[test]
+    fn test_move_marked_rows() {
+        let mut queue = QueueState::new();
+        queue.add_all(
+            ["1", "2", "3", "4", "5", "6"]
+                .map(|id| song(id, id))
+                .to_vec(),
+            QueueSource::Songs,
+        );
+        queue.current_index = Some(2);
+        queue.marked = HashSet::from([1, 2, 4]);
+
+        queue.step_marked(true);
+        assert_eq!(ids(&queue), vec!["1", "4", "2", "3", "6", "5"]);
+        assert_eq!(queue.current_index, Some(3));
+        assert_eq!(queue.marked_or_selected(), [2, 3, 5]);
+
+        queue.move_rows(&queue.marked_or_selected(), 0);
+        assert_eq!(ids(&queue), vec!["2", "3", "5", "1", "4", "6"]);
+        assert_eq!(queue.current_index, Some(1));
+
+        queue.step_marked(false);
+        assert_eq!(ids(&queue), vec!["2", "3", "5", "1", "4", "6"]);
+
+        queue.remove(0);
+        assert_eq!(queue.marked_or_selected(), [0, 1]);
+        queue.move_rows(&[0, 1], 99);
+        assert_eq!(ids(&queue), vec!["1", "4", "6", "3", "5"]);
+        assert_eq!(queue.current_index, Some(3));
+    }
+
+    #[test]
+    fn test_grouped_by_album() {
+        let track = |id: &str, album: &str| {
+            serde_json::from_value(serde_json::json!({ "id": id, "title": id, "albumId": album }))
+                .unwrap()
+        };
+        let mut queue = QueueState::new();
+        queue.add_all(
+            vec![
+                track("1", "x"),
+                track("2", "x"),
+                track("3", "y"),
+                track("4", "x"),
+            ],
+            QueueSource::Songs,
+        );
+        queue.grouped = true;
+        queue.list_state.select(Some(1));
+
+        let layout = queue.layout().unwrap();
+        assert_eq!(layout.groups.len(), 3);
+        assert_eq!(layout.len(), 7);
+        assert_eq!(layout.row(3), Some(QueueRow::Album(1)));
+        assert_eq!(layout.song_at(3), None);
+        assert_eq!(layout.row_of(3), Some(6));
+
+        // A collapsed album shows only its header, which stands for its songs
+        queue.toggle_album_group();
+        assert_eq!(queue.selected(), Some(0));
+        assert_eq!(queue.visible(), [0, 2, 3]);
+        assert_eq!(queue.select_row(0), Some(0));
+        assert_eq!(queue.select_row(2), Some(2));
+
+        queue.toggle_album_groups();
+        assert_eq!(queue.visible(), [0, 2, 3]);
+        assert_eq!(queue.layout().unwrap().len(), 3);
+        queue.toggle_album_groups();
+        assert_eq!(queue.visible(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_source_label_truncates() {
+        assert_eq!(source_label("Abbey Road", 20), "  Abbey Road ");
+        assert_eq!(source_label("Abbey Road", 10), "  Abbey … ");
+        assert_eq!(source_label("Abbey Road", 5), "");
+    }
 }
@@ -1,15 +1,21 @@
 //! Search component.
 
+use std::collections::HashSet;
 use std::time::Instant;
 
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    text::Line,
     widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
     Frame,
 };
 
-use crate::client::models::{Album, Artist, Song};
+use super::library::marked_line;
+use crate::client::models::{Album, Artist, Playlist, Song};
+use crate::client::paging::PagedSongs;
+use crate::hidden::HiddenItems;
+use crate::ui::scrollbar::render_scrollbar;
 
 /// Debounce delay in milliseconds.
 const DEBOUNCE_MS: u128 = 300;
@@ -17,6 +23,12 @@ const DEBOUNCE_MS: u128 = 300;
 /// Minimum query length to trigger search.
 const MIN_QUERY_LENGTH: usize = 2;
 
+/// Results requested per section, and per "load more".
+pub const SEARCH_PAGE_SIZE: u32 = 20;
+
+/// Number of result sections: artists, albums, songs, playlists.
+const SECTIONS: usize = 4;
+
 /// Search state.
 #[derive(Debug, Default)]
 pub struct SearchState {
@@ -35,13 +47,17 @@ pub struct SearchState {
     /// Search results - songs
     pub songs: Vec<Song>,
 
-    /// Currently focused section (0=artists, 1=albums, 2=songs)
+    /// Search results - playlists, matched locally against the loaded playlists
+    pub playlists: Vec<Playlist>,
+
+    /// Currently focused section (0=artists, 1=albums, 2=songs, 3=playlists)
     pub focus: usize,
 
     /// List states for each section
     pub artists_state: ListState,
     pub albums_state: ListState,
     pub songs_state: ListState,
+    pub playlists_state: ListState,
 
     /// Is searching (loading)
     pub searching: bool,
@@ -51,6 +67,20 @@ pub struct SearchState {
 
     /// The query that was last searched (to avoid duplicate searches)
     last_searched_query: String,
+
+    /// Result of the last queue action, shown until the query changes
+    pub status: Option<String>,
+
+    /// Results fetched so far per section, before hidden items were dropped
+    /// (the offset "load more" continues from)
+    pub fetched: [u32; SECTIONS],
+
+    /// Whether each section may have more results on the server
+    pub more: [bool; SECTIONS],
+
+    /// Pagination cursor over the song results, which load further pages
+    /// as the list scrolls instead of through "load more"
+    pub songs_pages: Option<PagedSongs>,
 }
 
 impl SearchState {
@@ -81,41 +111,106 @@ impl SearchState {
         self.artists.clear();
         self.albums.clear();
         self.songs.clear();
-        self.artists_state.select(None);
-        self.albums_state.select(None);
-        self.songs_state.select(None);
+        self.playlists.clear();
+        for section in 0..SECTIONS {
+            self.section_state(section).select(None);
+        }
         self.focus = 0;
+        self.fetched = [0; SECTIONS];
+        self.more = [false; SECTIONS];
+        self.songs_pages = None;
+    }
+
+    /// Number of results in a section.
+    fn section_len(&self, section: usize) -> usize {
+        match section {
+            0 => self.artists.len(),
+            1 => self.albums.len(),
+            2 => self.songs.len(),
+            _ => self.playlists.len(),
+        }
+    }
+
+    /// List state of a section.
+    fn section_state(&mut self, section: usize) -> &mut ListState {
+        match section {
+            0 => &mut self.artists_state,
+            1 => &mut self.albums_state,
+            2 => &mut self.songs_state,
+            _ => &mut self.playlists_state,
+        }
+    }
+
+    /// Record how many results a section's page returned, before hidden items were dropped.
+    pub fn page_fetched(&mut self, section: usize, count: usize) {
+        self.fetched[section] += count as u32;
+        self.more[section] = count == SEARCH_PAGE_SIZE as usize;
+
+        // Keep the selection valid if the "load more" entry went away
+        let last = (self.section_len(section) + usize::from(self.more[section])).checked_sub(1);
+        let state = self.section_state(section);
+        if let (Some(selected), Some(last)) = (state.selected(), last) {
+            state.select(Some(selected.min(last)));
+        }
+    }
+
+    /// Check if the song results should fetch their next page.
+    pub fn wants_song_page(&self) -> bool {
+        self.focus == 2
+            && self
+                .songs_pages
+                .as_ref()
+                .is_some_and(|p| p.should_prefetch(self.songs.len(), self.songs_state.selected()))
+    }
+
+    /// Append a fetched page of song results, skipping hidden songs and duplicates.
+    pub fn add_song_page(&mut self, offset: u32, page: Vec<Song>, hidden: &HiddenItems) {
+        let Some(pages) = &mut self.songs_pages else {
+            return;
+        };
+        if offset != pages.next_offset() {
+            // Stale page from an earlier query
+            return;
+        }
+
+        let fetched = page.len();
+        let mut seen: HashSet<String> = self.songs.iter().map(|s| s.id.clone()).collect();
+        let before = self.songs.len();
+        self.songs.extend(
+            page.into_iter()
+                .filter(|s| !hidden.hides_song(s) && seen.insert(s.id.clone())),
+        );
+        pages.page_loaded(offset, fetched, self.songs.len() - before);
+    }
+
+    /// Section whose "load more" entry is selected, if any.
+    pub fn load_more_selected(&mut self) -> Option<usize> {
+        let (focus, len) = (self.focus, self.section_len(self.focus));
+        let selected = self.section_state(focus).selected();
+        (self.more[focus] && selected == Some(len)).then_some(focus)
     }
 
     /// Set search results.
-    pub fn set_results(&mut self, artists: Vec<Artist>, albums: Vec<Album>, songs: Vec<Song>) {
+    pub fn set_results(
+        &mut self,
+        artists: Vec<Artist>,
+        albums: Vec<Album>,
+        songs: Vec<Song>,
+        playlists: Vec<Playlist>,
+    ) {
         self.artists = artists;
         self.albums = albums;
         self.songs = songs;
+        self.playlists = playlists;
         self.searching = false;
 
         // Select first item in first non-empty section, or clear all if empty
-        if !self.artists.is_empty() {
-            self.artists_state.select(Some(0));
-            self.albums_state.select(None);
-            self.songs_state.select(None);
-            self.focus = 0;
-        } else if !self.albums.is_empty() {
-            self.artists_state.select(None);
-            self.albums_state.select(Some(0));
-            self.songs_state.select(None);
-            self.focus = 1;
-        } else if !self.songs.is_empty() {
-            self.artists_state.select(None);
-            self.albums_state.select(None);
-            self.songs_state.select(Some(0));
-            self.focus = 2;
-        } else {
-            // All results are empty - clear all selection states
-            self.artists_state.select(None);
-            self.albums_state.select(None);
-            self.songs_state.select(None);
-            self.focus = 0;
+        self.focus = (0..SECTIONS)
+            .find(|&section| self.section_len(section) > 0)
+            .unwrap_or(0);
+        for section in 0..SECTIONS {
+            let selected = (section == self.focus && self.section_len(section) > 0).then_some(0);
+            self.section_state(section).select(selected);
         }
     }
 
@@ -123,12 +218,14 @@ impl SearchState {
     pub fn input(&mut self, c: char) {
         self.query.push(c);
         self.last_query_change = Some(Instant::now());
+        self.status = None;
     }
 
     /// Remove last character from query and mark as changed.
     pub fn backspace(&mut self) {
         if self.query.pop().is_some() {
             self.last_query_change = Some(Instant::now());
+            self.status = None;
         }
     }
 
@@ -158,6 +255,11 @@ impl SearchState {
         }
     }
 
+    /// Query the current results are for.
+    pub fn last_searched_query(&self) -> &str {
+        &self.last_searched_query
+    }
+
     /// Mark that a search has been initiated.
     pub fn mark_search_started(&mut self) {
         self.last_searched_query = self.query.clone();
@@ -174,20 +276,12 @@ impl SearchState {
 
     /// Get current list state based on focus.
     pub fn active_list_state(&mut self) -> &mut ListState {
-        match self.focus {
-            0 => &mut self.artists_state,
-            1 => &mut self.albums_state,
-            _ => &mut self.songs_state,
-        }
+        self.section_state(self.focus)
     }
 
-    /// Get current list length based on focus.
+    /// Get current list length based on focus, including the "load more" entry.
     fn active_list_len(&self) -> usize {
-        match self.focus {
-            0 => self.artists.len(),
-            1 => self.albums.len(),
-            _ => self.songs.len(),
-        }
+        self.section_len(self.focus) + usize::from(self.more[self.focus])
     }
 
     /// Move selection up.
@@ -224,7 +318,7 @@ impl SearchState {
 
     /// Switch to next section.
     pub fn next_section(&mut self) {
-        self.focus = (self.focus + 1) % 3;
+        self.focus = (self.focus + 1) % SECTIONS;
 
         // Select first item in new section, or clear selection if empty
         let len = self.active_list_len();
@@ -237,7 +331,7 @@ impl SearchState {
 
     /// Switch to previous section.
     pub fn prev_section(&mut self) {
-        self.focus = if self.focus == 0 { 2 } else { self.focus - 1 };
+        self.focus = (self.focus + SECTIONS - 1) % SECTIONS;
 
         // Select first item in new section, or clear selection if empty
         let len = self.active_list_len();
@@ -279,16 +373,38 @@ impl SearchState {
         }
     }
 
+    /// Get selected playlist.
+    pub fn selected_playlist(&self) -> Option<&Playlist> {
+        if self.focus == 3 {
+            self.playlists_state
+                .selected()
+                .and_then(|i| self.playlists.get(i))
+        } else {
+            None
+        }
+    }
+
     /// Check if there are any results.
     pub fn has_results(&self) -> bool {
-        !self.artists.is_empty() || !self.albums.is_empty() || !self.songs.is_empty()
+        (0..SECTIONS).any(|section| self.section_len(section) > 0)
     }
 }
 
 /// Render the search overlay.
-pub fn render_search(frame: &mut Frame, area: Rect, state: &mut SearchState) {
+/// In `compact` mode the popup fills the screen and shows only the focused column.
+pub fn render_search(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut SearchState,
+    playing_id: Option<&str>,
+    compact: bool,
+) {
     // Create a centered popup
-    let popup_area = centered_rect(80, 80, area);
+    let popup_area = if compact {
+        area
+    } else {
+        centered_rect(80, 80, area)
+    };
 
     // Clear the area behind the popup
     frame.render_widget(Clear, popup_area);
@@ -311,9 +427,13 @@ pub fn render_search(frame: &mut Frame, area: Rect, state: &mut SearchState) {
         .split(inner);
 
     // Search input
+    let title = match &state.status {
+        Some(status) => format!("Query · {}", status),
+        None => String::from("Query · Ctrl+a: add to queue  Ctrl+n: play next"),
+    };
     let input_block = Block::default()
         .borders(Borders::ALL)
-        .title("Query")
+        .title(title)
         .border_style(Style::default().fg(Color::Cyan));
 
     let cursor_symbol = if state.searching { "..." } else { "_" };
@@ -324,48 +444,93 @@ pub fn render_search(frame: &mut Frame, area: Rect, state: &mut SearchState) {
 
     frame.render_widget(input, chunks[0]);
 
-    // Results (3 columns)
+    // Results (4 columns)
     if state.has_results() || state.searching {
+        let constraints = if compact {
+            // Tab moves between the columns
+            [0, 1, 2, 3].map(|section| {
+                if section == state.focus {
+                    Constraint::Fill(1)
+                } else {
+                    Constraint::Length(0)
+                }
+            })
+        } else {
+            [
+                Constraint::Percentage(22),
+                Constraint::Percentage(28),
+                Constraint::Percentage(30),
+                Constraint::Percentage(20),
+            ]
+        };
         let result_chunks = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(33),
-                Constraint::Percentage(34),
-                Constraint::Percentage(33),
-            ])
+            .constraints(constraints)
             .split(chunks[1]);
 
         // Artists column
         render_search_column(
             frame,
             result_chunks[0],
-            "Artists",
+            SearchColumn {
+                title: "Artists",
+                focused: state.focus == 0,
+                more: state.more[0],
+            },
             &state.artists,
             &mut state.artists_state,
-            state.focus == 0,
-            |a| a.name.clone(),
+            |a| Line::from(a.name.clone()),
         );
 
         // Albums column
         render_search_column(
             frame,
             result_chunks[1],
-            "Albums",
+            SearchColumn {
+                title: "Albums",
+                focused: state.focus == 1,
+                more: state.more[1],
+            },
             &state.albums,
             &mut state.albums_state,
-            state.focus == 1,
-            |a| format!("{} - {}", a.name, a.artist.as_deref().unwrap_or("Unknown")),
+            |a| {
+                Line::from(format!(
+                    "{} - {}",
+                    a.name,
+                    a.artist.as_deref().unwrap_or("Unknown")
+                ))
+            },
         );
 
         // Songs column
         render_search_column(
             frame,
             result_chunks[2],
-            "Songs",
+            SearchColumn {
+                title: "Songs",
+                focused: state.focus == 2,
+                more: state.more[2],
+            },
             &state.songs,
             &mut state.songs_state,
-            state.focus == 2,
-            |s| format!("{} - {}", s.title, s.artist.as_deref().unwrap_or("Unknown")),
+            |s| {
+                let text = format!("{} - {}", s.title, s.artist.as_deref().unwrap_or("Unknown"));
+                marked_line(text, s, playing_id, Style::default())
+            },
+        );
+
+        // Playlists column
+        render_search_column(
+            frame,
+            result_chunks[3],
+            SearchColumn {
+                title: "Playlists",
+                focused: state.focus == 3,
+                more: false,
+            },
+            &state.playlists,
+            &mut state.playlists_state,
+            |p| Line::from(p.name.clone()),
         );
     } else if !state.query.is_empty() {
         let hint = if state.query.len() < MIN_QUERY_LENGTH {
@@ -385,17 +550,30 @@ pub fn render_search(frame: &mut Frame, area: Rect, state: &mut SearchState) {
     }
 }
 
+/// How a search results column is drawn.
+struct SearchColumn<'a> {
+    title: &'a str,
+    focused: bool,
+
+    /// Whether to end the list with a "load more" entry
+    more: bool,
+}
+
 fn render_search_column<T, F>(
     frame: &mut Frame,
     area: Rect,
-    title: &str,
+    column: SearchColumn,
     items: &[T],
     state: &mut ListState,
-    focused: bool,
     format_fn: F,
 ) where
-    F: Fn(&T) -> String,
+    F: Fn(&T) -> Line<'static>,
 {
+    let SearchColumn {
+        title,
+        focused,
+        more,
+    } = column;
     let border_color = if focused {
         Color::Yellow
     } else {
@@ -403,13 +581,27 @@ fn render_search_column<T, F>(
     };
     let block = Block::default()
         .borders(Borders::ALL)
-        .title(format!("{} ({})", title, items.len()))
+        .title(format!(
+            "{} ({}{})",
+            title,
+            items.len(),
+            if more { "+" } else { "" }
+        ))
         .border_style(Style::default().fg(border_color));
 
+    let load_more = more.then(|| {
+        ListItem::new("Load more…").style(
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        )
+    });
     let list_items: Vec<ListItem> = items
         .iter()
         .map(|item| ListItem::new(format_fn(item)))
+        .chain(load_more)
         .collect();
+    let len = list_items.len();
 
     let list = List::new(list_items)
         .block(block)
@@ -421,6 +613,13 @@ fn render_search_column<T, F>(
         .highlight_symbol("> ");
 
     frame.render_stateful_widget(list, area, state);
+    render_scrollbar(
+        frame,
+        area,
+        len,
+        area.height.saturating_sub(2) as usize,
+        state.offset(),
+    );
 }
 
 /// Create a centered rectangle.
@@ -0,0 +1,220 @@
+//! Popup with the server's identity, health and library totals.
+
+use std::time::Duration;
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::client::api::ServerInfo;
+use crate::client::models::{License, OpenSubsonicExtension, Role, ScanStatus, User};
+
+/// What the server reported when last asked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerStatus {
+    pub info: ServerInfo,
+
+    /// Authentication mode in use, e.g. "token"
+    pub auth_mode: &'static str,
+
+    /// Round trip time of a ping
+    pub latency: Duration,
+
+    /// OpenSubsonic extensions (empty on plain Subsonic servers)
+    pub extensions: Vec<OpenSubsonicExtension>,
+
+    /// License status, if the server reports one
+    pub license: Option<License>,
+
+    /// Scan status, if the server reports one
+    pub scan: Option<ScanStatus>,
+
+    /// Account signed in with, if the server would say
+    pub user: Option<User>,
+}
+
+/// Library totals known locally.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LibraryCounts {
+    pub artists: usize,
+    pub albums: u32,
+    pub songs: u32,
+}
+
+/// Server status popup state.
+#[derive(Debug, Default)]
+pub struct ServerStatusState {
+    /// Whether the popup is open
+    pub visible: bool,
+
+    /// Whether a refresh is in progress
+    pub loading: bool,
+
+    /// Status from the last successful refresh
+    pub status: Option<ServerStatus>,
+
+    /// Error from the last refresh
+    pub error: Option<String>,
+}
+
+/// Render the server status popup.
+pub fn render_server_status(
+    frame: &mut Frame,
+    area: Rect,
+    state: &ServerStatusState,
+    server_url: &str,
+    counts: LibraryCounts,
+) {
+    let width = 64.min(area.width);
+    let height = 17.min(area.height);
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, popup_area);
+
+    let title = if state.loading {
+        "Server · refreshing…"
+    } else {
+        "Server"
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let field = |name: &str, value: String, color: Color| {
+        Line::from(vec![
+            Span::styled(format!("{:<12}", name), Style::default().fg(Color::Cyan)),
+            Span::styled(value, Style::default().fg(color)),
+        ])
+    };
+
+    let mut lines = vec![field("URL", server_url.to_string(), Color::Reset)];
+
+    if let Some(error) = &state.error {
+        lines.push(field(
+            "Status",
+            format!("unreachable: {}", error),
+            Color::Red,
+        ));
+    }
+
+    if let Some(status) = &state.status {
+        let info = &status.info;
+        let name = match (&info.server_type, &info.server_version) {
+            (Some(server), Some(version)) => format!("{} {}", server, version),
+            (Some(server), None) => server.clone(),
+            _ => String::from("Subsonic server"),
+        };
+        lines.push(field("Server", name, Color::Reset));
+        lines.push(field("Auth", status.auth_mode.to_string(), Color::Gray));
+        if let Some(user) = &status.user {
+            let denied: Vec<&str> = Role::ALL
+                .iter()
+                .filter(|&&role| !user.has(role))
+                .map(|role| role.name())
+                .collect();
+            let mut account = user.username.clone();
+            if user.is_admin() {
+                account.push_str(" (admin)");
+            }
+            if !denied.is_empty() {
+                account.push_str(&format!(" · can't: {}", denied.join(", ")));
+            }
+            let color = if denied.is_empty() {
+                Color::Reset
+            } else {
+                Color::Yellow
+            };
+            lines.push(field("Account", account, color));
+        }
+        lines.push(field(
+            "API",
+            format!(
+                "{}{}",
+                info.api_version,
+                if info.open_subsonic {
+                    " (OpenSubsonic)"
+                } else {
+                    ""
+                }
+            ),
+            Color::Gray,
+        ));
+
+        let latency_ms = status.latency.as_millis();
+        let latency_color = match latency_ms {
+            0..=150 => Color::Green,
+            151..=500 => Color::Yellow,
+            _ => Color::Red,
+        };
+        lines.push(field("Ping", format!("{} ms", latency_ms), latency_color));
+
+        let license = match &status.license {
+            Some(license) if license.valid => match &license.license_expires {
+                Some(expires) => (format!("valid until {}", expires), Color::Green),
+                None => (String::from("valid"), Color::Green),
+            },
+            Some(_) => (String::from("not valid"), Color::Yellow),
+            None => (String::from("unknown"), Color::DarkGray),
+        };
+        lines.push(field("License", license.0, license.1));
+
+        let scan = match &status.scan {
+            Some(scan) if scan.scanning => (
+                format!("scanning… {} files", scan.count.unwrap_or(0)),
+                Color::Yellow,
+            ),
+            Some(scan) => {
+                let mut text = String::from("idle");
+                if let Some(count) = scan.count {
+                    text.push_str(&format!(", {} files", count));
+                }
+                if let Some(last) = &scan.last_scan {
+                    text.push_str(&format!(", last scan {}", last));
+                }
+                (text, Color::Gray)
+            }
+            None => (String::from("unknown"), Color::DarkGray),
+        };
+        lines.push(field("Scan", scan.0, scan.1));
+
+        let extensions = if status.extensions.is_empty() {
+            String::from("none")
+        } else {
+            status
+                .extensions
+                .iter()
+                .map(|e| e.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        lines.push(field("Extensions", extensions, Color::Gray));
+    }
+
+    lines.push(field(
+        "Library",
+        format!(
+            "{} artists, {} albums, {} songs",
+            counts.artists, counts.albums, counts.songs
+        ),
+        Color::Gray,
+    ));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "r: refresh  Esc: close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, popup_area);
+}
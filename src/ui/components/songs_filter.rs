@@ -0,0 +1,165 @@
+//! Popup for choosing the Songs tab source and the random songs filter.
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::year_picker::parse_year_range;
+use crate::client::api::RandomFilter;
+use crate::sort::SongsSource;
+
+/// Rows of the popup, top to bottom.
+const ROWS: [&str; 3] = ["Source", "Genre", "Years"];
+
+/// Songs filter popup state.
+#[derive(Debug, Default)]
+pub struct SongsFilterState {
+    /// Whether the popup is open
+    pub visible: bool,
+
+    /// Selected row (0 = source, 1 = genre, 2 = years)
+    pub row: usize,
+
+    /// Source being chosen
+    pub source: SongsSource,
+
+    /// Genre text
+    pub genre: String,
+
+    /// Year range text, e.g. "1990-1999"
+    pub years: String,
+}
+
+impl SongsFilterState {
+    /// Open the popup showing the current source and filter.
+    pub fn open(&mut self, source: SongsSource, filter: &RandomFilter) {
+        self.source = source;
+        self.genre = filter.genre.clone().unwrap_or_default();
+        self.years = match (filter.from_year, filter.to_year) {
+            (Some(from), Some(to)) if from == to => from.to_string(),
+            (Some(from), Some(to)) => format!("{}-{}", from, to),
+            _ => String::new(),
+        };
+        self.row = 0;
+        self.visible = true;
+    }
+
+    pub fn select_next(&mut self) {
+        self.row = (self.row + 1) % ROWS.len();
+    }
+
+    pub fn select_prev(&mut self) {
+        self.row = (self.row + ROWS.len() - 1) % ROWS.len();
+    }
+
+    /// Type into the selected text row.
+    pub fn push(&mut self, c: char) {
+        match self.row {
+            1 => self.genre.push(c),
+            2 if c.is_ascii_digit() || c == '-' || c == ' ' => self.years.push(c),
+            _ => {}
+        }
+    }
+
+    pub fn pop(&mut self) {
+        match self.row {
+            1 => self.genre.pop(),
+            2 => self.years.pop(),
+            _ => None,
+        };
+    }
+
+    /// The filter entered, or `None` if the years don't parse.
+    pub fn filter(&self) -> Option<RandomFilter> {
+        let genre = self.genre.trim();
+        let (from_year, to_year) = if self.years.trim().is_empty() {
+            (None, None)
+        } else {
+            let (from, to) = parse_year_range(&self.years)?;
+            (Some(from.min(to)), Some(from.max(to)))
+        };
+
+        Some(RandomFilter {
+            genre: (!genre.is_empty()).then(|| genre.to_string()),
+            from_year,
+            to_year,
+        })
+    }
+}
+
+/// Render the songs filter popup.
+pub fn render_songs_filter(frame: &mut Frame, area: Rect, state: &SongsFilterState) {
+    let height = 8.min(area.height);
+    let width = 52.min(area.width);
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Songs Tab")
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(ROWS.len() as u16),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ])
+        .split(inner);
+
+    let values = [
+        format!("‹ {} ›", state.source.label()),
+        state.genre.clone(),
+        state.years.clone(),
+    ];
+    let lines: Vec<Line> = ROWS
+        .iter()
+        .zip(values)
+        .enumerate()
+        .map(|(i, (name, value))| {
+            let selected = i == state.row;
+            let cursor = if selected && i > 0 { "_" } else { "" };
+            let style = if selected {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(format!("{:<8}", name), Style::default().fg(Color::Cyan)),
+                Span::styled(format!("{}{}", value, cursor), style),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+    let invalid = state.filter().is_none();
+    let hints = vec![
+        Line::from(Span::styled(
+            if invalid {
+                "Years: e.g. 1990 or 1990-1999"
+            } else {
+                "Genre and years apply to random songs"
+            },
+            Style::default().fg(if invalid { Color::Red } else { Color::DarkGray }),
+        )),
+        Line::from(Span::styled(
+            "↑/↓: row  ←/→: source  Enter: apply  Esc: cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    frame.render_widget(Paragraph::new(hints), chunks[2]);
+}
@@ -0,0 +1,277 @@
+//! Toast notifications stacked in a corner, and the history of past messages.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+/// Most toasts shown at once; older ones are dropped first.
+const MAX_TOASTS: usize = 5;
+
+/// Most messages kept in the history.
+const HISTORY_LIMIT: usize = 200;
+
+/// Width of a toast, including borders.
+const TOAST_WIDTH: u16 = 48;
+
+/// Most message lines shown in a toast.
+const TOAST_LINES: u16 = 3;
+
+/// How serious a message is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl ToastLevel {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Info => "Info",
+            Self::Warning => "Warning",
+            Self::Error => "Error",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Info => Color::Cyan,
+            Self::Warning => Color::Yellow,
+            Self::Error => Color::Red,
+        }
+    }
+
+    /// How long a toast of this level stays up.
+    fn duration(self) -> Duration {
+        match self {
+            Self::Info => Duration::from_secs(3),
+            Self::Warning => Duration::from_secs(5),
+            Self::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+/// A message shown to the user.
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub level: ToastLevel,
+    pub text: String,
+    pub time: DateTime<Local>,
+}
+
+/// Toasts currently shown, and the message history.
+#[derive(Debug, Default)]
+pub struct ToastState {
+    /// Messages currently shown as toasts, oldest first, with when they appeared
+    toasts: VecDeque<(Message, Instant)>,
+
+    /// Every message so far, oldest first
+    history: VecDeque<Message>,
+
+    /// Whether the history popup is open
+    pub show_history: bool,
+
+    /// Selection in the history popup (newest message first)
+    pub history_state: ListState,
+
+    /// Keep errors to the history, e.g. while the server is unreachable and
+    /// every request fails
+    pub quiet_errors: bool,
+}
+
+impl ToastState {
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(ToastLevel::Info, text.into());
+    }
+
+    pub fn warn(&mut self, text: impl Into<String>) {
+        self.push(ToastLevel::Warning, text.into());
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(ToastLevel::Error, text.into());
+    }
+
+    /// Show a message. A repeat of the newest toast just keeps it up longer.
+    pub fn push(&mut self, level: ToastLevel, text: String) {
+        let message = Message {
+            level,
+            text,
+            time: Local::now(),
+        };
+
+        self.history.push_back(message.clone());
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        if level == ToastLevel::Error && self.quiet_errors {
+            return;
+        }
+
+        if let Some((last, shown)) = self.toasts.back_mut() {
+            if last.level == level && last.text == message.text {
+                *shown = Instant::now();
+                return;
+            }
+        }
+        self.toasts.push_back((message, Instant::now()));
+        if self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+    }
+
+    /// Remove toasts that have been up long enough, returning whether any were.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        let shown = self.toasts.len();
+        self.toasts
+            .retain(|(message, shown)| now.duration_since(*shown) < message.level.duration());
+        self.toasts.len() != shown
+    }
+
+    /// Dismiss all toasts (they stay in the history).
+    pub fn dismiss(&mut self) {
+        self.toasts.clear();
+    }
+
+    pub fn open_history(&mut self) {
+        self.show_history = true;
+        self.history_state.select(Some(0));
+    }
+
+    pub fn history_next(&mut self) {
+        let last = self.history.len().saturating_sub(1);
+        let i = self
+            .history_state
+            .selected()
+            .map_or(0, |i| (i + 1).min(last));
+        self.history_state.select(Some(i));
+    }
+
+    pub fn history_prev(&mut self) {
+        let i = self
+            .history_state
+            .selected()
+            .map_or(0, |i| i.saturating_sub(1));
+        self.history_state.select(Some(i));
+    }
+}
+
+/// Render the toasts stacked down from the top-right corner of `area`.
+pub fn render_toasts(frame: &mut Frame, area: Rect, state: &ToastState) {
+    let width = TOAST_WIDTH.min(area.width);
+    let text_width = width.saturating_sub(2).max(1) as usize;
+    let mut y = area.y + 1;
+
+    // Newest on top
+    for (message, _) in state.toasts.iter().rev() {
+        let lines =
+            (message.text.chars().count().div_ceil(text_width) as u16).clamp(1, TOAST_LINES);
+        let height = lines + 2;
+        if y + height > area.bottom() {
+            break;
+        }
+
+        let toast_area = Rect {
+            x: area.right() - width,
+            y,
+            width,
+            height,
+        };
+        frame.render_widget(Clear, toast_area);
+
+        let color = message.level.color();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(message.level.label())
+            .border_style(Style::default().fg(color));
+        let paragraph = Paragraph::new(message.text.as_str())
+            .block(block)
+            .wrap(Wrap { trim: true });
+        frame.render_widget(paragraph, toast_area);
+
+        y += height;
+    }
+}
+
+/// Render the message history popup.
+pub fn render_message_history(frame: &mut Frame, area: Rect, state: &mut ToastState) {
+    let width = (area.width * 4 / 5).max(40).min(area.width);
+    let height = (area.height * 3 / 5).max(8).min(area.height);
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Messages [M/Esc to close]")
+        .border_style(Style::default().fg(Color::Cyan));
+
+    if state.history.is_empty() {
+        let empty = Paragraph::new("No messages yet")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        frame.render_widget(empty, popup_area);
+        return;
+    }
+
+    let items: Vec<ListItem> = state
+        .history
+        .iter()
+        .rev()
+        .map(|message| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    message.time.format("%H:%M:%S ").to_string(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!("{:<8}", message.level.label()),
+                    Style::default().fg(message.level.color()),
+                ),
+                Span::raw(message.text.as_str()),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(block).highlight_style(
+        Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
+    frame.render_stateful_widget(list, popup_area, &mut state.history_state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toasts_expire_by_level_and_collapse_repeats() {
+        let mut toasts = ToastState::default();
+        toasts.info("saved");
+        toasts.error("failed");
+        toasts.error("failed");
+        assert_eq!(toasts.toasts.len(), 2);
+        assert_eq!(toasts.history.len(), 3);
+
+        toasts.expire(Instant::now() + Duration::from_secs(4));
+        let left: Vec<&str> = toasts.toasts.iter().map(|(m, _)| m.text.as_str()).collect();
+        assert_eq!(left, vec!["failed"]);
+
+        toasts.expire(Instant::now() + Duration::from_secs(9));
+        assert!(toasts.toasts.is_empty());
+        assert_eq!(toasts.history.len(), 3);
+    }
+}
@@ -0,0 +1,78 @@
+//! Spectrum visualizer bars.
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::Line,
+    widgets::Paragraph,
+    Frame,
+};
+
+/// Block characters for eighths of a cell, from empty to full.
+const LEVELS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Share of its level a bar keeps each frame when the sound gets quieter.
+const FALL_OFF: f32 = 0.85;
+
+/// Spectrum visualizer state.
+#[derive(Debug, Default)]
+pub struct VisualizerState {
+    /// Bar levels (0.0 to 1.0), low frequencies first
+    pub bars: Vec<f32>,
+
+    /// Width of the area last rendered to, which sets how many bars to compute
+    pub width: u16,
+}
+
+impl VisualizerState {
+    /// Number of bars that fit the area last rendered to, one column each with a gap.
+    pub fn bar_count(&self) -> usize {
+        self.width.div_ceil(2) as usize
+    }
+
+    /// Take new levels. Bars jump up but fall back slowly, so they don't flicker.
+    pub fn update(&mut self, levels: &[f32]) {
+        self.bars.resize(levels.len(), 0.0);
+        for (bar, &level) in self.bars.iter_mut().zip(levels) {
+            *bar = level.max(*bar * FALL_OFF);
+        }
+    }
+
+    /// Let the bars fall back while nothing is playing.
+    pub fn decay(&mut self) {
+        for bar in &mut self.bars {
+            *bar *= FALL_OFF;
+        }
+    }
+}
+
+/// Render the bars, growing up from the bottom of `area`.
+pub fn render_visualizer(frame: &mut Frame, area: Rect, state: &mut VisualizerState) {
+    state.width = area.width;
+    if area.height == 0 {
+        return;
+    }
+
+    let eighths = area.height as f32 * 8.0;
+    let lines: Vec<Line> = (0..area.height)
+        .map(|row| {
+            // Eighths of a cell below this row
+            let floor = (area.height - row - 1) as f32 * 8.0;
+            let text: String = state
+                .bars
+                .iter()
+                .flat_map(|&level| {
+                    let filled = (level * eighths - floor).clamp(0.0, 8.0) as usize;
+                    [LEVELS[filled], ' ']
+                })
+                .take(area.width as usize)
+                .collect();
+            Line::from(text)
+        })
+        .collect();
+
+    frame.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(Color::Magenta)),
+        area,
+    );
+}
@@ -0,0 +1,178 @@
+//! Popup for choosing the year range the Albums tab is limited to.
+
+use chrono::Datelike;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+/// First decade offered by the picker.
+const FIRST_DECADE: i32 = 1950;
+
+/// Year picker state.
+#[derive(Debug, Default)]
+pub struct YearPickerState {
+    /// Whether the picker is open
+    pub visible: bool,
+
+    /// Start years of the decades offered, oldest first
+    decades: Vec<i32>,
+
+    /// Selection among the decades, followed by the custom range entry
+    pub list_state: ListState,
+
+    /// Whether a custom range is being typed
+    pub editing: bool,
+
+    /// Custom range text, e.g. "1994-1999"
+    pub input: String,
+}
+
+impl YearPickerState {
+    /// Open the picker with decades up to the current one.
+    pub fn open(&mut self) {
+        let current_decade = chrono::Local::now().year() / 10 * 10;
+        self.decades = (FIRST_DECADE..=current_decade).step_by(10).collect();
+        self.list_state
+            .select(Some(self.decades.len().saturating_sub(1)));
+        self.editing = false;
+        self.input.clear();
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.editing = false;
+    }
+
+    /// Number of entries, including the custom range entry.
+    fn len(&self) -> usize {
+        self.decades.len() + 1
+    }
+
+    pub fn select_next(&mut self) {
+        let i = self
+            .list_state
+            .selected()
+            .map_or(0, |i| (i + 1) % self.len());
+        self.list_state.select(Some(i));
+    }
+
+    pub fn select_prev(&mut self) {
+        let i = self
+            .list_state
+            .selected()
+            .map_or(0, |i| (i + self.len() - 1) % self.len());
+        self.list_state.select(Some(i));
+    }
+
+    /// Whether the custom range entry is selected.
+    pub fn custom_selected(&self) -> bool {
+        self.list_state.selected() == Some(self.decades.len())
+    }
+
+    /// The chosen range: the selected decade, or the typed range once it parses.
+    pub fn selected_range(&self) -> Option<(i32, i32)> {
+        match self.list_state.selected() {
+            Some(i) if i < self.decades.len() => Some((self.decades[i], self.decades[i] + 9)),
+            _ => parse_year_range(&self.input),
+        }
+    }
+}
+
+/// Parse a year range typed as "1994", "1994-1999" or "1994 1999".
+/// The years are kept in the order given: a reversed range lists newest first.
+pub fn parse_year_range(input: &str) -> Option<(i32, i32)> {
+    let years: Vec<i32> = input
+        .split(|c: char| c == '-' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().ok().filter(|year| (1..=9999).contains(year)))
+        .collect::<Option<_>>()?;
+
+    match years[..] {
+        [year] => Some((year, year)),
+        [from, to] => Some((from, to)),
+        _ => None,
+    }
+}
+
+/// Render the year picker popup.
+pub fn render_year_picker(frame: &mut Frame, area: Rect, state: &mut YearPickerState) {
+    let height = (state.len() as u16 + 5).min(area.height);
+    let width = 44.min(area.width);
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Albums by Year")
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let custom = if state.editing {
+        format!("Custom range: {}_", state.input)
+    } else {
+        String::from("Custom range…")
+    };
+    let items: Vec<ListItem> = state
+        .decades
+        .iter()
+        .map(|decade| ListItem::new(format!("{}s", decade)))
+        .chain(std::iter::once(ListItem::new(custom)))
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(list, chunks[0], &mut state.list_state);
+
+    let hint = if state.editing {
+        "e.g. 1994-1999   Enter: apply  Esc: back"
+    } else {
+        "Enter: choose  Esc: close"
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(Span::styled(
+            hint,
+            Style::default().fg(Color::DarkGray),
+        ))),
+        chunks[2],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_year_range() {
+        assert_eq!(parse_year_range("1994"), Some((1994, 1994)));
+        assert_eq!(parse_year_range(" 1994 - 1999 "), Some((1994, 1999)));
+        assert_eq!(parse_year_range("2010 2000"), Some((2010, 2000)));
+        assert_eq!(parse_year_range(""), None);
+        assert_eq!(parse_year_range("1994-1999-2004"), None);
+        assert_eq!(parse_year_range("nineties"), None);
+    }
+}
@@ -0,0 +1,115 @@
+//! Time-of-day greeting with library totals.
+
+use crate::config::GreetingConfig;
+use crate::state::LibraryStats;
+
+/// Build a greeting such as "Good evening, maarten — 312 albums, 4,102 songs".
+pub fn greeting(config: &GreetingConfig, name: &str, stats: &LibraryStats, hour: u32) -> String {
+    let phrase = match hour {
+        5..=11 => &config.morning,
+        12..=16 => &config.afternoon,
+        17..=21 => &config.evening,
+        _ => &config.night,
+    };
+
+    let mut text = phrase.clone();
+    if !name.is_empty() {
+        text = config
+            .with_name
+            .replace("{greeting}", &text)
+            .replace("{name}", name);
+    }
+
+    let total = |count: u32, one: &str, many: &str| {
+        let template = if count == 1 { one } else { many };
+        template.replace("{count}", &format_count(count))
+    };
+    let mut totals = Vec::new();
+    if stats.albums > 0 {
+        totals.push(total(stats.albums, &config.album, &config.albums));
+    }
+    if stats.songs > 0 {
+        totals.push(total(stats.songs, &config.song, &config.songs));
+    }
+    if !totals.is_empty() {
+        text = config
+            .with_totals
+            .replace("{greeting}", &text)
+            .replace("{totals}", &totals.join(&config.totals_separator));
+    }
+
+    text
+}
+
+/// Format a number with thousands separators.
+fn format_count(n: u32) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(312), "312");
+        assert_eq!(format_count(4102), "4,102");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn test_greeting() {
+        let config = GreetingConfig::default();
+        let stats = LibraryStats {
+            albums: 312,
+            songs: 4102,
+        };
+        assert_eq!(
+            greeting(&config, "maarten", &stats, 19),
+            "Good evening, maarten — 312 albums, 4,102 songs"
+        );
+        assert_eq!(
+            greeting(&config, "", &LibraryStats::default(), 8),
+            "Good morning"
+        );
+        // One of each is singular
+        let one = LibraryStats {
+            albums: 1,
+            songs: 1,
+        };
+        assert_eq!(
+            greeting(&config, "", &one, 13),
+            "Good afternoon — 1 album, 1 song"
+        );
+    }
+
+    #[test]
+    fn test_greeting_templates() {
+        let config = GreetingConfig {
+            morning: String::from("Guten Morgen"),
+            with_name: String::from("{name}: {greeting}"),
+            with_totals: String::from("{greeting} ({totals})"),
+            albums: String::from("{count} Alben"),
+            songs: String::from("{count} Lieder"),
+            totals_separator: String::from(" / "),
+            ..GreetingConfig::default()
+        };
+        let stats = LibraryStats {
+            albums: 12,
+            songs: 140,
+        };
+        assert_eq!(
+            greeting(&config, "maarten", &stats, 9),
+            "maarten: Guten Morgen (12 Alben / 140 Lieder)"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Screen regions that take mouse input, registered while rendering.
+//!
+//! Each frame starts with an empty [`HitRegions`] and widgets add the areas
+//! they actually drew, so mouse handling follows the layout without
+//! recomputing it.
+
+use ratatui::layout::{Position, Rect};
+
+use crate::action::Tab;
+
+/// Something on screen that reacts to the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitRegion {
+    Tab(Tab),
+    /// Library panel, including its borders
+    Library,
+    /// Queue panel, including its borders
+    Queue,
+    /// Lyrics panel beside the library
+    Lyrics,
+    /// Header of a sortable library column, by column index
+    SortHeader(usize),
+    /// Title row of the now playing bar
+    NowPlayingTitle,
+    Control(Control),
+    VolumeBar,
+    ProgressBar,
+    /// Elapsed or remaining time left of the progress bar
+    TimeDisplay,
+    /// Account and server in the tab bar
+    Account,
+}
+
+/// Playback control buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Previous,
+    PlayPause,
+    Next,
+    Shuffle,
+    Repeat,
+}
+
+/// Regions drawn in the last frame, in drawing order.
+#[derive(Debug, Default)]
+pub struct HitRegions {
+    regions: Vec<(HitRegion, Rect)>,
+}
+
+impl HitRegions {
+    /// Forget the regions of the previous frame.
+    pub fn clear(&mut self) {
+        self.regions.clear();
+    }
+
+    /// Register a region. Later regions are on top of earlier ones.
+    pub fn add(&mut self, region: HitRegion, area: Rect) {
+        if !area.is_empty() {
+            self.regions.push((region, area));
+        }
+    }
+
+    /// Get the topmost region at a position, with its area.
+    pub fn at(&self, x: u16, y: u16) -> Option<(HitRegion, Rect)> {
+        self.regions
+            .iter()
+            .rev()
+            .find(|(_, area)| area.contains(Position::new(x, y)))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topmost_region_wins() {
+        let mut regions = HitRegions::default();
+        regions.add(HitRegion::Library, Rect::new(0, 0, 40, 20));
+        regions.add(HitRegion::ProgressBar, Rect::new(10, 5, 20, 1));
+        regions.add(HitRegion::Queue, Rect::new(50, 0, 0, 20));
+
+        assert_eq!(
+            regions.at(15, 5),
+            Some((HitRegion::ProgressBar, Rect::new(10, 5, 20, 1)))
+        );
+        assert_eq!(regions.at(15, 6).map(|(r, _)| r), Some(HitRegion::Library));
+        assert_eq!(regions.at(50, 3), None);
+
+        regions.clear();
+        assert_eq!(regions.at(15, 5), None);
+    }
+}
@@ -0,0 +1,115 @@
+//! Icon sets: Nerd Font glyphs, or plain ASCII for terminals without a patched font.
+
+use crate::config::IconSet;
+
+/// Symbols used across the UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Icons {
+    pub playing: &'static str,
+    pub paused: &'static str,
+    pub stopped: &'static str,
+    pub buffering: &'static str,
+    pub previous: &'static str,
+    pub next: &'static str,
+    pub shuffle_on: &'static str,
+    pub shuffle_off: &'static str,
+    pub repeat_off: &'static str,
+    pub repeat_all: &'static str,
+    pub repeat_one: &'static str,
+    pub muted: &'static str,
+    pub volume_low: &'static str,
+    pub volume_mid: &'static str,
+    pub volume_high: &'static str,
+    pub starred: &'static str,
+    pub album: &'static str,
+    pub collapsed: &'static str,
+    pub expanded: &'static str,
+    /// Frames of the buffering spinner
+    pub spinner: &'static [&'static str],
+}
+
+/// Nerd Font glyphs.
+pub const NERD: Icons = Icons {
+    playing: "",
+    paused: "",
+    stopped: "",
+    buffering: "󰔟",
+    previous: "󰒮",
+    next: "󰒭",
+    shuffle_on: "󰒟",
+    shuffle_off: "󰒞",
+    repeat_off: "󰑗",
+    repeat_all: "󰑖",
+    repeat_one: "󰑘",
+    muted: "󰝟",
+    volume_low: "󰕿",
+    volume_mid: "󰖀",
+    volume_high: "󰕾",
+    starred: "󰓎",
+    album: "󰀥",
+    collapsed: "▸",
+    expanded: "▾",
+    spinner: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+};
+
+/// Plain ASCII. Playback controls are two columns wide, like Nerd Font glyphs
+/// usually render, so the controls stay where mouse clicks expect them.
+pub const ASCII: Icons = Icons {
+    playing: "|>",
+    paused: "||",
+    stopped: "[]",
+    buffering: "..",
+    previous: "|<",
+    next: ">|",
+    shuffle_on: "S+",
+    shuffle_off: "S-",
+    repeat_off: "R-",
+    repeat_all: "R*",
+    repeat_one: "R1",
+    muted: "M",
+    volume_low: "V",
+    volume_mid: "V",
+    volume_high: "V",
+    starred: "*",
+    album: "(o)",
+    collapsed: "+",
+    expanded: "-",
+    spinner: &["|", "/", "-", "\\"],
+};
+
+impl Default for Icons {
+    fn default() -> Self {
+        NERD
+    }
+}
+
+impl Icons {
+    /// Get the icons for a configured set, guessing for `auto`.
+    pub fn for_set(set: IconSet) -> Self {
+        match set {
+            IconSet::Nerd => NERD,
+            IconSet::Ascii => ASCII,
+            IconSet::Auto if plain_terminal() => ASCII,
+            IconSet::Auto => NERD,
+        }
+    }
+}
+
+/// Whether the terminal is unlikely to show anything but ASCII: the Linux
+/// console, or a locale that isn't UTF-8.
+fn plain_terminal() -> bool {
+    if std::env::var("TERM").is_ok_and(|term| term == "linux") {
+        return true;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_lowercase();
+            !(locale.contains("utf-8") || locale.contains("utf8"))
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,104 @@
+//! Horizontally scrolling text for lines too long for their space.
+
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use unicode_width::UnicodeWidthChar;
+
+/// Ticks the text stays still at the start of each pass.
+const PAUSE_TICKS: u64 = 20;
+
+/// Ticks per character scrolled.
+const TICKS_PER_STEP: u64 = 2;
+
+/// Gap between the end of the text and its start coming around again.
+const GAP: &str = "   ";
+
+/// Scroll position of overflowing text, advanced on the tick timer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Marquee {
+    /// Whether overflowing text scrolls (otherwise it is cut off or wrapped)
+    pub enabled: bool,
+
+    /// Ticks since the text was last shown from its start
+    ticks: u64,
+}
+
+impl Marquee {
+    pub fn tick(&mut self) {
+        self.ticks = self.ticks.wrapping_add(1);
+    }
+
+    /// Start again from the beginning of the text, e.g. when it changes.
+    pub fn reset(&mut self) {
+        self.ticks = 0;
+    }
+
+    /// Get the part of `spans` to show in `width` columns.
+    pub fn line<'a>(&self, spans: Vec<Span<'a>>, width: usize) -> Line<'a> {
+        scroll(spans, width, self.ticks)
+    }
+}
+
+/// Get the window of `spans` shown in `width` columns after `ticks` ticks.
+/// Spans that fit are returned unchanged.
+pub fn scroll<'a>(spans: Vec<Span<'a>>, width: usize, ticks: u64) -> Line<'a> {
+    let text_width: usize = spans.iter().map(|span| span.width()).sum();
+    if text_width <= width {
+        return Line::from(spans);
+    }
+
+    let cells: Vec<(char, Style)> = spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .chain(GAP.chars().map(|c| (c, Style::default())))
+        .collect();
+
+    // Pause at the start, then scroll all the way round once
+    let period = PAUSE_TICKS + cells.len() as u64 * TICKS_PER_STEP;
+    let offset = ((ticks % period).saturating_sub(PAUSE_TICKS) / TICKS_PER_STEP) as usize;
+
+    let mut window: Vec<Span<'a>> = Vec::new();
+    let mut used = 0;
+    for &(c, style) in cells.iter().cycle().skip(offset) {
+        let c_width = c.width().unwrap_or(0);
+        if used + c_width > width {
+            break;
+        }
+        used += c_width;
+        match window.last_mut() {
+            Some(last) if last.style == style => last.content.to_mut().push(c),
+            _ => window.push(Span::styled(c.to_string(), style)),
+        }
+    }
+    Line::from(window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_scroll_overflowing_text() {
+        let spans = || vec![Span::raw("Hello"), Span::raw(" world")];
+
+        assert_eq!(text(&scroll(spans(), 20, 500)), "Hello world");
+        assert_eq!(text(&scroll(spans(), 5, 0)), "Hello");
+        assert_eq!(
+            text(&scroll(spans(), 5, PAUSE_TICKS + 2 * TICKS_PER_STEP)),
+            "llo w"
+        );
+
+        // Wraps around through the gap
+        let ticks = PAUSE_TICKS + 9 * TICKS_PER_STEP;
+        assert_eq!(text(&scroll(spans(), 7, ticks)), "ld   He");
+    }
+}
@@ -1,5 +1,7 @@
 //! Main UI layout and rendering.
 
+use std::collections::HashSet;
+
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -9,87 +11,145 @@ use ratatui::{
 };
 
 use crate::action::Tab;
-use crate::app::App;
+use crate::app::{App, Connection};
+use crate::client::models::{ReplayGain, Song};
+use crate::config::{ImageProtocol, UiConfig, VisualizerMode};
+use crate::focus::Focus;
+use hit::{HitRegion, HitRegions};
+use ratatui_image::picker::{Picker, ProtocolType};
 
 pub mod components;
+pub mod greeting;
+pub mod hit;
+pub mod icons;
+pub mod marquee;
+pub mod scrollbar;
+pub mod status_bar;
+pub mod title;
 
 pub use components::*;
 
+/// Font size assumed when the terminal can't be asked for it.
+const FALLBACK_FONT_SIZE: (u16, u16) = (8, 16);
+
+/// Terminals with fewer rows than this get the compact layout.
+const COMPACT_HEIGHT: u16 = 25;
+
+/// Terminals with fewer columns than this get the compact layout.
+const COMPACT_WIDTH: u16 = 80;
+
+/// Columns of the user's avatar at the right end of the tab bar.
+const TAB_AVATAR_WIDTH: u16 = 2;
+
+/// Create the picker for terminal graphics, using the protocol set in the
+/// config or, by default, the one the terminal reports. `None` when the
+/// terminal can't be queried and no protocol is set.
+pub fn image_picker(config: &UiConfig) -> Option<Picker> {
+    let protocol = match config.image_protocol {
+        ImageProtocol::Auto => None,
+        ImageProtocol::Sixel => Some(ProtocolType::Sixel),
+        ImageProtocol::Kitty => Some(ProtocolType::Kitty),
+        ImageProtocol::Iterm2 => Some(ProtocolType::Iterm2),
+        ImageProtocol::Halfblocks => Some(ProtocolType::Halfblocks),
+    };
+    let mut picker = match (Picker::from_query_stdio(), protocol) {
+        (Ok(picker), _) => picker,
+        (Err(e), Some(_)) => {
+            tracing::warn!("Terminal graphics query failed: {}", e);
+            Picker::from_fontsize(FALLBACK_FONT_SIZE)
+        }
+        (Err(e), None) => {
+            tracing::warn!("Terminal graphics query failed, images disabled: {}", e);
+            return None;
+        }
+    };
+    if let Some(protocol) = protocol {
+        picker.set_protocol_type(protocol);
+    }
+    Some(picker)
+}
+
+/// Whether `area` is small enough for the compact layout: a 3-line now
+/// playing bar, no queue panel and single-column search.
+fn is_compact(area: Rect) -> bool {
+    area.height < COMPACT_HEIGHT || area.width < COMPACT_WIDTH
+}
+
 /// Render the entire UI.
 pub fn render(frame: &mut Frame, app: &mut App) {
     let area = frame.area();
+    app.hit_regions.clear();
+
+    if app.fullscreen {
+        let lyrics = app.lyrics.visible.then_some(&mut app.lyrics);
+        let visualizer =
+            (app.config.ui.visualizer == VisualizerMode::Fullscreen).then_some(&mut app.visualizer);
+        render_now_playing_fullscreen(
+            frame,
+            area,
+            &mut app.now_playing,
+            lyrics,
+            visualizer,
+            &mut app.hit_regions,
+        );
+        render_overlays(frame, area, app);
+        return;
+    }
 
-    // Main layout: [tabs] [content + queue] [now playing]
+    // Main layout: [tabs] [content + queue] [now playing] [status bar]
+    let compact = is_compact(area);
+    let status_height = if app.config.ui.show_status_bar { 1 } else { 0 };
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Tabs
-            Constraint::Min(10),   // Content
-            Constraint::Length(5), // Now playing
+            Constraint::Length(3),                           // Tabs
+            Constraint::Min(if compact { 3 } else { 10 }),   // Content
+            Constraint::Length(if compact { 3 } else { 5 }), // Now playing
+            Constraint::Length(status_height),               // Status bar
         ])
         .split(area);
 
-    // Store layout areas for mouse detection
-    app.layout.tabs = main_chunks[0];
-    app.layout.now_playing = main_chunks[2];
-
-    // Calculate album art offset for controls positioning
-    // Album art takes up space on the left when present
-    let now_playing_inner_height = main_chunks[2].height.saturating_sub(2); // minus borders
-    let has_album_art = app.now_playing.album_art.is_some() && app.now_playing.picker.is_some();
-    let art_width = if has_album_art {
-        (now_playing_inner_height * 2).min(8) // Same calculation as in now_playing.rs
+    // Visualizer to the right of the now playing bar
+    let show_visualizer = !compact && app.config.ui.visualizer == VisualizerMode::Bar;
+    let (now_playing_area, visualizer_area) = if show_visualizer {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(40), Constraint::Length(26)])
+            .split(main_chunks[2]);
+        (chunks[0], Some(chunks[1]))
     } else {
-        0
-    };
-    let info_area_x = main_chunks[2].x + 1 + art_width; // +1 for border, +art_width for album art
-
-    // Progress bar is at the bottom of now_playing area (row 3 = last content row)
-    // New layout: row 0 = title, row 1 = controls, row 2 = progress bar
-    // With border, progress bar is at y + 3
-    app.layout.progress_bar = Rect {
-        x: info_area_x + 6,      // Skip time display (6 chars)
-        y: main_chunks[2].y + 3, // Row 2 within now_playing (after top border)
-        width: main_chunks[2].width.saturating_sub(16 + art_width), // Minus borders, time displays, and art
-        height: 1,
-    };
-    // Volume bar is at the right side of row 1 (controls row)
-    // Layout: controls(14) + album(min 10) + volume(18)
-    // Volume content (right-aligned): "icon  ━━━━━━━━━━  XX%"
-    // The bar is 10 chars, followed by space + 3-4 char percentage
-    // So bar ends at (width - 1 border - 5 for " XXX%") and starts 10 chars before that
-    let volume_section_end = main_chunks[2].x + main_chunks[2].width - 1; // -1 for right border
-    let bar_end = volume_section_end - 5; // " XXX%" is 5 chars
-    let bar_start = bar_end - 10; // bar is 10 chars
-    app.layout.volume_bar = Rect {
-        x: bar_start,
-        y: main_chunks[2].y + 2, // Row 1 within now_playing (controls row)
-        width: 10,               // "━━━━━━━━━━" is 10 chars
-        height: 1,
-    };
-    // Playback controls area: "󰒮 ▶ 󰒭 󰒟 󰑖" in first 14 chars of controls row
-    // controls_chunks[0] starts at info_area.x which is inside the border
-    app.layout.controls = Rect {
-        x: info_area_x.saturating_sub(1), // Adjust for alignment
-        y: main_chunks[2].y + 2,          // Row 1 within now_playing (controls row)
-        width: 18,                        // Extended to capture all controls including repeat
-        height: 1,
+        (main_chunks[2], None)
     };
 
     // Render tabs
-    render_tabs(frame, main_chunks[0], app.library.tab);
+    let account = account_title(app);
+    render_tabs(
+        frame,
+        main_chunks[0],
+        (&app.library.tabs, app.library.tab),
+        &app.library.loading_tabs,
+        account,
+        (&mut app.avatars, &app.config.server.username),
+        &mut app.hit_regions,
+    );
 
     // Content area: [library] [queue/lyrics]
     let content_chunks = if app.lyrics.visible {
         // Show lyrics panel instead of queue
         Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .constraints([
+                Constraint::Percentage(100 - app.lyrics.width),
+                Constraint::Percentage(app.lyrics.width),
+            ])
             .split(main_chunks[1])
-    } else if app.queue.visible {
+    } else if app.queue.visible && !compact {
         Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+            .constraints([
+                Constraint::Percentage(100 - app.queue.width),
+                Constraint::Percentage(app.queue.width),
+            ])
             .split(main_chunks[1])
     } else {
         Layout::default()
@@ -98,37 +158,95 @@ pub fn render(frame: &mut Frame, app: &mut App) {
             .split(main_chunks[1])
     };
 
-    // Store library area
-    app.layout.library = content_chunks[0];
-
-    // Store queue area if visible
-    if app.queue.visible && content_chunks.len() > 1 && !app.lyrics.visible {
-        app.layout.queue = Some(content_chunks[1]);
-    } else {
-        app.layout.queue = None;
+    app.hit_regions.add(HitRegion::Library, content_chunks[0]);
+    if app.lyrics.visible && content_chunks.len() > 1 {
+        app.hit_regions.add(HitRegion::Lyrics, content_chunks[1]);
+    } else if app.queue.visible && content_chunks.len() > 1 {
+        app.hit_regions.add(HitRegion::Queue, content_chunks[1]);
     }
 
     // Render library with focus indicator
-    render_library(frame, content_chunks[0], &mut app.library, app.focus == 0);
+    render_library(
+        frame,
+        content_chunks[0],
+        &mut app.library,
+        app.focus == Focus::Library,
+        &mut app.hit_regions,
+    );
 
     // Render queue or lyrics (if visible)
     if app.lyrics.visible && content_chunks.len() > 1 {
-        render_lyrics(frame, content_chunks[1], &mut app.lyrics);
+        let focused = app.focus == Focus::Lyrics;
+        render_lyrics(frame, content_chunks[1], &mut app.lyrics, false, focused);
     } else if app.queue.visible && content_chunks.len() > 1 {
-        render_queue(frame, content_chunks[1], &mut app.queue, app.focus == 1);
+        render_queue(
+            frame,
+            content_chunks[1],
+            &mut app.queue,
+            app.focus == Focus::Queue,
+        );
     }
 
     // Render now playing bar
-    render_now_playing(frame, main_chunks[2], &mut app.now_playing);
+    render_now_playing(
+        frame,
+        now_playing_area,
+        &mut app.now_playing,
+        compact,
+        &mut app.hit_regions,
+    );
+    if let Some(visualizer_area) = visualizer_area {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray));
+        let inner = block.inner(visualizer_area);
+        frame.render_widget(block, visualizer_area);
+        render_visualizer(frame, inner, &mut app.visualizer);
+    }
+
+    if app.config.ui.show_status_bar {
+        status_bar::render_status_bar(frame, main_chunks[3], app);
+    }
+
+    render_overlays(frame, area, app);
+}
 
+/// Render the popups drawn over the rest of the UI.
+fn render_overlays(frame: &mut Frame, area: Rect, app: &mut App) {
     // Render search overlay if active
     if app.search.active {
-        render_search(frame, area, &mut app.search);
+        render_search(
+            frame,
+            area,
+            &mut app.search,
+            app.library.playing_id.as_deref(),
+            is_compact(area),
+        );
+    }
+
+    if app.year_picker.visible {
+        render_year_picker(frame, area, &mut app.year_picker);
+    }
+
+    if app.songs_filter.visible {
+        render_songs_filter(frame, area, &app.songs_filter);
+    }
+
+    if app.playlist_picker.visible {
+        render_playlist_picker(frame, area, &app.playlist_picker);
+    }
+
+    if app.import_prompt.visible {
+        render_import_prompt(frame, area, &app.import_prompt);
+    }
+
+    if app.move_prompt.visible {
+        render_move_prompt(frame, area, &app.move_prompt);
     }
 
     // Render help overlay if active
-    if app.show_help {
-        render_help(frame, area);
+    if app.help.visible {
+        render_help(frame, area, &mut app.help);
     }
 
     // Render track info popup if active
@@ -136,15 +254,96 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         render_track_info(frame, area, &app.now_playing);
     }
 
-    // Render error message if present
-    if let Some(error) = &app.error_message {
-        render_error(frame, area, error);
+    if app.toasts.show_history {
+        render_message_history(frame, area, &mut app.toasts);
+    }
+
+    if app.server_status.visible {
+        let counts = LibraryCounts {
+            artists: app.library.artists.len(),
+            albums: app.library_stats.albums,
+            songs: app.library_stats.songs,
+        };
+        render_server_status(
+            frame,
+            area,
+            &app.server_status,
+            &app.config.server.url,
+            counts,
+        );
+    }
+
+    if app.listeners.visible {
+        render_listeners(frame, area, &mut app.listeners, &mut app.avatars);
+    }
+
+    if app.album_info.visible {
+        render_album_info(frame, area, &mut app.album_info);
+    }
+
+    if app.log_viewer.visible {
+        render_log_viewer(frame, area, &mut app.log_viewer);
+    }
+
+    if let Some(confirm) = &app.confirm {
+        render_confirm(frame, area, &confirm.prompt());
+    }
+
+    if app.login.visible {
+        render_login(frame, area, &app.login, &app.config.server.url);
+    }
+
+    render_toasts(frame, area, &app.toasts);
+}
+
+/// Build the tab bar's title: a connection dot, the username and the server.
+fn account_title(app: &App) -> Line<'static> {
+    let (dot, color) = if app.connection != Connection::Online {
+        ("◌", Color::Yellow)
+    } else if app.client.is_some() {
+        ("●", Color::Green)
+    } else {
+        ("○", Color::Red)
+    };
+    let server = app
+        .config
+        .server
+        .url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/')
+        .to_string();
+    let mut spans = vec![
+        Span::raw(" "),
+        Span::styled(dot, Style::default().fg(color)),
+        Span::raw(" "),
+    ];
+    if !app.config.server.username.is_empty() {
+        spans.push(Span::styled(
+            app.config.server.username.clone(),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled(" @ ", Style::default().fg(Color::DarkGray)));
     }
+    spans.push(Span::styled(server, Style::default().fg(Color::Gray)));
+    spans.push(Span::raw(" "));
+    Line::from(spans)
 }
 
-/// Render the tab bar.
-fn render_tabs(frame: &mut Frame, area: Rect, current_tab: Tab) {
-    let titles: Vec<Line> = Tab::all()
+/// Render the tab bar, with the account signed in with along its top
+/// border and the user's avatar, if they have one, at its right end.
+fn render_tabs(
+    frame: &mut Frame,
+    area: Rect,
+    (tabs, current_tab): (&[Tab], Tab),
+    loading: &HashSet<Tab>,
+    account: Line<'static>,
+    (avatars, username): (&mut AvatarCache, &str),
+    hits: &mut HitRegions,
+) {
+    let titles: Vec<Line> = tabs
         .iter()
         .map(|t| {
             let style = if *t == current_tab {
@@ -154,18 +353,51 @@ fn render_tabs(frame: &mut Frame, area: Rect, current_tab: Tab) {
             } else {
                 Style::default().fg(Color::White)
             };
-            Line::from(Span::styled(t.title(), style))
+            let mut spans = vec![Span::styled(t.title(), style)];
+            if loading.contains(t) {
+                spans.push(Span::styled("…", Style::default().fg(Color::DarkGray)));
+            }
+            Line::from(spans)
         })
         .collect();
 
+    // Each tab is drawn as " Title " followed by a one column divider
+    let account_width = (account.width() as u16).min(area.width.saturating_sub(2));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(account.right_aligned())
+        .border_style(Style::default().fg(Color::Blue));
+    let inner = block.inner(area);
+
+    // Clicking the account opens the server status
+    hits.add(
+        HitRegion::Account,
+        Rect {
+            x: inner.right().saturating_sub(account_width),
+            y: area.y,
+            width: account_width,
+            height: 1,
+        },
+    );
+
+    let mut x = inner.x;
+    for (tab, title) in tabs.iter().zip(&titles) {
+        let width = (title.width() as u16 + 2).min(inner.right().saturating_sub(x));
+        hits.add(
+            HitRegion::Tab(*tab),
+            Rect {
+                x,
+                width,
+                height: 1,
+                ..inner
+            },
+        );
+        x += width + 1;
+    }
+
     let tabs = Tabs::new(titles)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("subsonic-tui")
-                .border_style(Style::default().fg(Color::Blue)),
-        )
-        .select(current_tab.index())
+        .block(block)
+        .select(tabs.iter().position(|tab| *tab == current_tab))
         .style(Style::default().fg(Color::White))
         .highlight_style(
             Style::default()
@@ -174,116 +406,107 @@ fn render_tabs(frame: &mut Frame, area: Rect, current_tab: Tab) {
         );
 
     frame.render_widget(tabs, area);
-}
-
-/// Render the help overlay.
-fn render_help(frame: &mut Frame, area: Rect) {
-    let popup_area = centered_rect(70, 80, area);
-    frame.render_widget(Clear, popup_area);
-
-    let help_text = vec![
-        Line::from(Span::styled(
-            "Keyboard Shortcuts",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        )),
-        Line::from(""),
-        Line::from(Span::styled(
-            "Navigation",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )),
-        Line::from("  j/k or ↑/↓    Move up/down"),
-        Line::from("  h/l or ←/→    Switch focus / navigate"),
-        Line::from("  Enter         Select item"),
-        Line::from("  Esc/Backspace Go back"),
-        Line::from("  g/G           Jump to top/bottom"),
-        Line::from("  Ctrl+d/u      Scroll half page down/up"),
-        Line::from("  1-6           Switch tabs (Artists/Albums/Songs/Playlists/Genres/Favorites)"),
-        Line::from("  Tab/Shift+Tab Cycle through tabs"),
-        Line::from(""),
-        Line::from(Span::styled(
-            "Playback",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )),
-        Line::from("  Space         Play/Pause"),
-        Line::from("  n/p           Next/Previous track"),
-        Line::from("  ,/.           Seek backward/forward (10s)"),
-        Line::from("  +/-           Volume up/down"),
-        Line::from("  s             Toggle shuffle"),
-        Line::from("  r             Cycle repeat mode"),
-        Line::from(""),
-        Line::from(Span::styled(
-            "Queue & Library",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )),
-        Line::from("  a             Add to queue (without playing)"),
-        Line::from("  c             Clear queue"),
-        Line::from("  d/Delete      Remove selected from queue"),
-        Line::from("  o             Jump to current track in queue"),
-        Line::from("  J/K           Move queue item down/up"),
-        Line::from("  *             Toggle star on current song"),
-        Line::from("  R             Refresh library"),
-        Line::from(""),
-        Line::from(Span::styled(
-            "Other",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )),
-        Line::from("  /             Search"),
-        Line::from("  L             Toggle lyrics panel"),
-        Line::from("  i             Show track info"),
-        Line::from("  ?             Show this help"),
-        Line::from("  x             Clear error message"),
-        Line::from("  q             Quit"),
-        Line::from(""),
-        Line::from(Span::styled(
-            "Mouse",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )),
-        Line::from("  Click         Select item / Switch focus"),
-        Line::from("  Double-click  Play item"),
-        Line::from("  Click tab     Switch to tab"),
-        Line::from("  Click prog    Seek in track"),
-        Line::from("  Click vol     Set volume"),
-        Line::from("  Click ctrl    Playback controls"),
-        Line::from("  Scroll        Navigate list"),
-        Line::from("  Scroll vol    Adjust volume"),
-        Line::from(""),
-        Line::from(Span::styled(
-            "Press Esc or ? to close",
-            Style::default().fg(Color::DarkGray),
-        )),
-    ];
-
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .title("Help")
-        .border_style(Style::default().fg(Color::Cyan));
-
-    let paragraph = Paragraph::new(help_text)
-        .block(block)
-        .wrap(Wrap { trim: false });
 
-    frame.render_widget(paragraph, popup_area);
+    let avatar_area = Rect {
+        x: inner.right().saturating_sub(TAB_AVATAR_WIDTH),
+        width: TAB_AVATAR_WIDTH.min(inner.width),
+        height: 1.min(inner.height),
+        ..inner
+    };
+    if !username.is_empty() && render_avatar(frame, avatar_area, avatars, username) {
+        hits.add(HitRegion::Account, avatar_area);
+    }
 }
 
 /// Render the track info popup.
 fn render_track_info(frame: &mut Frame, area: Rect, now_playing: &NowPlayingState) {
-    let popup_area = centered_rect(60, 50, area);
+    let popup_area = centered_rect(60, 80, area);
     frame.render_widget(Clear, popup_area);
 
     let info_lines = if let Some(song) = &now_playing.current_song {
-        vec![
+        let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        let mut fields = vec![
+            ("Title", song.title.clone()),
+            ("Artist", song.display_artist().to_string()),
+            (
+                "Album",
+                song.album.clone().unwrap_or_else(|| "Unknown".to_string()),
+            ),
+            ("Duration", song.duration_string()),
+            ("Track", or_dash(song.track.map(|t| t.to_string()))),
+            ("Year", or_dash(song.year.map(|y| y.to_string()))),
+            ("Genre", or_dash(song.genre.clone())),
+            (
+                "Bitrate",
+                or_dash(song.bit_rate.map(|b| format!("{} kbps", b))),
+            ),
+            ("Format", or_dash(song.suffix.clone())),
+            ("Size", or_dash(song.size.map(|s| format_size(s as u64)))),
+            (
+                "Play Count",
+                or_dash(song.play_count.map(|c| c.to_string())),
+            ),
+            (
+                "Rating",
+                match song.user_rating.unwrap_or(0).clamp(0, 5) {
+                    0 => "-".to_string(),
+                    stars => "★".repeat(stars as usize),
+                },
+            ),
+            (
+                "Starred",
+                if song.starred.is_some() { "Yes" } else { "No" }.to_string(),
+            ),
+        ];
+
+        // Only shown when the server reports them
+        let extra = [
+            (
+                "Sample Rate",
+                song.sampling_rate
+                    .map(|rate| format!("{} kHz", rate as f64 / 1000.0)),
+            ),
+            (
+                "Bit Depth",
+                song.bit_depth.map(|bits| format!("{}-bit", bits)),
+            ),
+            (
+                "Channels",
+                song.channel_count.map(|channels| match channels {
+                    1 => String::from("1 (mono)"),
+                    2 => String::from("2 (stereo)"),
+                    n => n.to_string(),
+                }),
+            ),
+            (
+                "BPM",
+                song.bpm.filter(|&bpm| bpm > 0).map(|bpm| bpm.to_string()),
+            ),
+            (
+                "Track Gain",
+                replay_gain(song, |rg| rg.track_gain, |rg| rg.track_peak),
+            ),
+            (
+                "Album Gain",
+                replay_gain(song, |rg| rg.album_gain, |rg| rg.album_peak),
+            ),
+            (
+                "Added",
+                song.created
+                    .as_deref()
+                    .map(|date| date_part(date).to_string()),
+            ),
+            ("MusicBrainz", song.music_brainz_id.clone()),
+            ("Comment", song.comment.clone()),
+            ("Path", song.path.clone()),
+        ];
+        fields.extend(
+            extra
+                .into_iter()
+                .filter_map(|(name, value)| Some((name, value.filter(|v| !v.is_empty())?))),
+        );
+
+        let mut lines = vec![
             Line::from(Span::styled(
                 "Track Information",
                 Style::default()
@@ -291,76 +514,25 @@ fn render_track_info(frame: &mut Frame, area: Rect, now_playing: &NowPlayingStat
                     .add_modifier(Modifier::BOLD),
             )),
             Line::from(""),
+        ];
+        lines.extend(fields.into_iter().map(|(name, value)| {
             Line::from(vec![
-                Span::styled("Title: ", Style::default().fg(Color::Cyan)),
-                Span::raw(&song.title),
-            ]),
-            Line::from(vec![
-                Span::styled("Artist: ", Style::default().fg(Color::Cyan)),
-                Span::raw(song.display_artist()),
-            ]),
-            Line::from(vec![
-                Span::styled("Album: ", Style::default().fg(Color::Cyan)),
-                Span::raw(song.album.as_deref().unwrap_or("Unknown")),
-            ]),
-            Line::from(vec![
-                Span::styled("Duration: ", Style::default().fg(Color::Cyan)),
-                Span::raw(song.duration_string()),
-            ]),
-            Line::from(vec![
-                Span::styled("Track: ", Style::default().fg(Color::Cyan)),
-                Span::raw(
-                    song.track
-                        .map(|t| t.to_string())
-                        .unwrap_or_else(|| "-".to_string()),
-                ),
-            ]),
-            Line::from(vec![
-                Span::styled("Year: ", Style::default().fg(Color::Cyan)),
-                Span::raw(
-                    song.year
-                        .map(|y| y.to_string())
-                        .unwrap_or_else(|| "-".to_string()),
-                ),
-            ]),
-            Line::from(vec![
-                Span::styled("Genre: ", Style::default().fg(Color::Cyan)),
-                Span::raw(song.genre.as_deref().unwrap_or("-")),
-            ]),
-            Line::from(vec![
-                Span::styled("Bitrate: ", Style::default().fg(Color::Cyan)),
-                Span::raw(
-                    song.bit_rate
-                        .map(|b| format!("{} kbps", b))
-                        .unwrap_or_else(|| "-".to_string()),
-                ),
-            ]),
-            Line::from(vec![
-                Span::styled("Format: ", Style::default().fg(Color::Cyan)),
-                Span::raw(song.suffix.as_deref().unwrap_or("-")),
-            ]),
-            Line::from(vec![
-                Span::styled("Size: ", Style::default().fg(Color::Cyan)),
-                Span::raw(
-                    song.size
-                        .map(|s| format_size(s as u64))
-                        .unwrap_or_else(|| "-".to_string()),
-                ),
-            ]),
-            Line::from(vec![
-                Span::styled("Play Count: ", Style::default().fg(Color::Cyan)),
-                Span::raw(
-                    song.play_count
-                        .map(|c| c.to_string())
-                        .unwrap_or_else(|| "-".to_string()),
-                ),
-            ]),
+                Span::styled(format!("{}: ", name), Style::default().fg(Color::Cyan)),
+                Span::raw(value),
+            ])
+        }));
+        lines.extend([
             Line::from(""),
             Line::from(Span::styled(
-                "Press Esc or i to close",
+                "a: go to album  r: go to artist  *: star  0-5: rate  Esc/i: close",
                 Style::default().fg(Color::DarkGray),
             )),
-        ]
+            Line::from(Span::styled(
+                "c: copy artist – title  u: copy stream URL  w: copy web URL",
+                Style::default().fg(Color::DarkGray),
+            )),
+        ]);
+        lines
     } else {
         vec![
             Line::from(Span::styled(
@@ -387,6 +559,26 @@ fn render_track_info(frame: &mut Frame, area: Rect, now_playing: &NowPlayingStat
     frame.render_widget(paragraph, popup_area);
 }
 
+/// Format a ReplayGain value and its peak, e.g. "-6.20 dB (peak 0.98)".
+fn replay_gain(
+    song: &Song,
+    gain: impl Fn(&ReplayGain) -> Option<f64>,
+    peak: impl Fn(&ReplayGain) -> Option<f64>,
+) -> Option<String> {
+    let replay_gain = song.replay_gain.as_ref()?;
+    let gain = format!("{:+.2} dB", gain(replay_gain)?);
+    Some(match peak(replay_gain) {
+        Some(peak) => format!("{} (peak {:.2})", gain, peak),
+        None => gain,
+    })
+}
+
+/// Get the date of an ISO 8601 timestamp, e.g. "2024-03-01" of
+/// "2024-03-01T12:00:00Z".
+fn date_part(timestamp: &str) -> &str {
+    timestamp.split('T').next().unwrap_or(timestamp)
+}
+
 /// Format file size in human-readable format.
 fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
@@ -404,23 +596,25 @@ fn format_size(bytes: u64) -> String {
     }
 }
 
-/// Render an error message overlay.
-fn render_error(frame: &mut Frame, area: Rect, message: &str) {
-    // Create a centered popup
-    let popup_area = centered_rect(60, 20, area);
-
-    // Clear the area behind the popup
+/// Render a yes/no confirmation popup.
+fn render_confirm(frame: &mut Frame, area: Rect, prompt: &str) {
+    let popup_area = centered_rect(50, 20, area);
     frame.render_widget(Clear, popup_area);
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Error")
-        .border_style(Style::default().fg(Color::Red));
+        .title("Confirm")
+        .border_style(Style::default().fg(Color::Yellow));
 
-    let paragraph = Paragraph::new(message)
-        .style(Style::default().fg(Color::Red))
-        .block(block)
-        .wrap(Wrap { trim: true });
+    let text = vec![
+        Line::from(prompt.to_string()),
+        Line::from(""),
+        Line::from(Span::styled(
+            "y: yes   n/Esc: no",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
 
     frame.render_widget(paragraph, popup_area);
 }
@@ -0,0 +1,39 @@
+//! Vertical scrollbar drawn over the right border of a panel.
+
+use ratatui::{
+    layout::{Margin, Rect},
+    style::{Color, Style},
+    widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState},
+    Frame,
+};
+
+/// Draw a scrollbar on the right border of `area`, a bordered panel showing
+/// `visible` of `len` items (or lines) with `position` the selected or topmost
+/// one. Nothing is drawn when everything fits.
+pub fn render_scrollbar(
+    frame: &mut Frame,
+    area: Rect,
+    len: usize,
+    visible: usize,
+    position: usize,
+) {
+    if len <= visible {
+        return;
+    }
+
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .track_style(Style::default().fg(Color::DarkGray))
+        .thumb_style(Style::default().fg(Color::Gray));
+    let mut state = ScrollbarState::new(len)
+        .viewport_content_length(visible)
+        .position(position);
+
+    // Keep the border's corners
+    let track = area.inner(Margin {
+        vertical: 1,
+        horizontal: 0,
+    });
+    frame.render_stateful_widget(scrollbar, track, &mut state);
+}
@@ -0,0 +1,238 @@
+//! One-line status bar with the current mode and the keys that matter in it.
+
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::action::Tab;
+use crate::app::{App, Connection};
+use crate::focus::Focus;
+use crate::sort::{AlbumList, SongsSource};
+
+/// What the keyboard is driving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Normal,
+    Login,
+    Search,
+    Filter,
+    Pick,
+    Confirm,
+    View,
+    Playing,
+    Lyrics,
+}
+
+impl Mode {
+    /// Get the mode, checking popups in the order keys are handled in.
+    fn of(app: &App) -> Self {
+        if app.login.visible {
+            Self::Login
+        } else if app.search.active {
+            Self::Search
+        } else if app.library.filtering || app.queue.filtering {
+            Self::Filter
+        } else if app.year_picker.visible
+            || app.songs_filter.visible
+            || app.playlist_picker.visible
+            || app.import_prompt.visible
+            || app.move_prompt.visible
+        {
+            Self::Pick
+        } else if app.confirm.is_some() {
+            Self::Confirm
+        } else if app.help.visible
+            || app.toasts.show_history
+            || app.log_viewer.visible
+            || app.server_status.visible
+            || app.listeners.visible
+            || app.album_info.visible
+            || app.show_track_info
+        {
+            Self::View
+        } else if app.fullscreen {
+            Self::Playing
+        } else if app.focus == Focus::Lyrics {
+            Self::Lyrics
+        } else {
+            Self::Normal
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Normal => "NORMAL",
+            Self::Login => "LOGIN",
+            Self::Search => "SEARCH",
+            Self::Filter => "FILTER",
+            Self::Pick => "PICK",
+            Self::Confirm => "CONFIRM",
+            Self::View => "VIEW",
+            Self::Playing => "PLAYING",
+            Self::Lyrics => "LYRICS",
+        }
+    }
+}
+
+/// The most useful keys for the current mode, as (key, description) pairs.
+fn hints(app: &App) -> &'static [(&'static str, &'static str)] {
+    match Mode::of(app) {
+        Mode::Search => &[
+            ("Enter", "open"),
+            ("Tab", "column"),
+            ("^a", "queue"),
+            ("^n", "play next"),
+            ("Esc", "close"),
+        ],
+        Mode::Login => &[("Enter", "connect"), ("Esc", "cancel")],
+        Mode::Filter => &[("Enter", "keep"), ("Esc", "clear"), ("↑/↓", "move")],
+        Mode::Pick => &[("↑/↓", "choose"), ("Enter", "apply"), ("Esc", "cancel")],
+        Mode::Confirm => &[("y", "yes"), ("n", "no")],
+        Mode::View => &[("Esc", "close")],
+        Mode::Playing => &[
+            ("Space", "play/pause"),
+            ("n/p", "next/prev"),
+            ("L", "lyrics"),
+            ("v", "close"),
+        ],
+        Mode::Lyrics => &[
+            ("j/k", "scroll"),
+            ("Enter", "jump"),
+            ("(/)", "delay"),
+            ("e", "export"),
+            ("l", "language"),
+            ("Tab", "focus"),
+            ("L", "close"),
+        ],
+        Mode::Normal if app.focus == Focus::Queue => &[
+            ("Enter", "play"),
+            ("d", "remove"),
+            ("J/K", "move"),
+            ("f", "filter"),
+            ("?", "help"),
+        ],
+        Mode::Normal => &[
+            ("Enter", "open"),
+            ("a", "queue"),
+            ("/", "search"),
+            ("f", "filter"),
+            ("?", "help"),
+        ],
+    }
+}
+
+/// Filters narrowing what the library or queue shows.
+fn active_filters(app: &App) -> Vec<String> {
+    let mut filters = Vec::new();
+    if !app.library.filter.is_empty() {
+        filters.push(format!("filter \"{}\"", app.library.filter));
+    }
+    if !app.queue.filter.is_empty() {
+        filters.push(format!("queue \"{}\"", app.queue.filter));
+    }
+    match app.library.tab {
+        Tab::Albums => {
+            if let AlbumList::ByYear { .. } | AlbumList::Compilations = app.library.album_list {
+                filters.push(app.library.album_list.label());
+            }
+        }
+        Tab::Songs if app.library.songs_source == SongsSource::Random => {
+            if let Some(filter) = app.library.songs_filter.describe() {
+                filters.push(filter);
+            }
+        }
+        _ => {}
+    }
+    filters
+}
+
+/// Render the status bar.
+pub fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let mut left = vec![
+        Span::styled(
+            format!(" {} ", Mode::of(app).label()),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(" {}", app.focus.title()),
+            Style::default().fg(Color::Gray),
+        ),
+    ];
+
+    let server = app
+        .config
+        .server
+        .url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    left.push(Span::styled(" · ", dim));
+    if app.connection != Connection::Online {
+        left.push(Span::styled(
+            "◌ reconnecting…",
+            Style::default().fg(Color::Yellow),
+        ));
+    } else if app.client.is_some() {
+        left.push(Span::styled("● ", Style::default().fg(Color::Green)));
+        left.push(Span::styled(
+            server.to_string(),
+            Style::default().fg(Color::Gray),
+        ));
+    } else {
+        left.push(Span::styled("○ offline", Style::default().fg(Color::Red)));
+    }
+
+    if let Some(name) = app.output_name() {
+        left.push(Span::styled(" · ", dim));
+        left.push(Span::styled(
+            format!("⇢ {}", name),
+            Style::default().fg(Color::Magenta),
+        ));
+    }
+
+    for filter in active_filters(app) {
+        left.push(Span::styled(" · ", dim));
+        left.push(Span::styled(filter, Style::default().fg(Color::Yellow)));
+    }
+
+    let mut right = Vec::new();
+    if let Some(count) = app.count.get() {
+        right.push(Span::styled(
+            format!("{}  ", count),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    for (key, description) in hints(app) {
+        right.push(Span::styled(
+            *key,
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ));
+        right.push(Span::styled(format!(" {}  ", description), dim));
+    }
+    let right_width = right.iter().map(|span| span.width()).sum::<usize>() as u16;
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(right_width.min(area.width * 2 / 3)),
+        ])
+        .split(area);
+
+    frame.render_widget(Paragraph::new(Line::from(left)), chunks[0]);
+    frame.render_widget(
+        Paragraph::new(Line::from(right)).alignment(Alignment::Right),
+        chunks[1],
+    );
+}
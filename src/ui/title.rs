@@ -0,0 +1,57 @@
+//! Terminal window title announcing the playing track.
+
+use crate::action::PlayerState;
+use crate::client::models::Song;
+
+/// Title while nothing is playing.
+const IDLE_TITLE: &str = "subsonic-tui";
+
+/// Fill in a title template such as `{artist} – {title} [{state}]` for the
+/// playing song. Placeholders are `{artist}`, `{title}`, `{album}` and
+/// `{state}`.
+pub fn window_title(template: &str, song: Option<&Song>, state: PlayerState) -> String {
+    let Some(song) = song.filter(|_| state != PlayerState::Stopped) else {
+        return IDLE_TITLE.to_string();
+    };
+    let state = match state {
+        PlayerState::Playing => "playing",
+        PlayerState::Paused => "paused",
+        PlayerState::Buffering => "buffering",
+        PlayerState::Stopped => "stopped",
+    };
+    template
+        .replace("{artist}", song.display_artist())
+        .replace("{title}", &song.title)
+        .replace("{album}", song.album.as_deref().unwrap_or_default())
+        .replace("{state}", state)
+        // Control characters would end the escape sequence early
+        .replace(char::is_control, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_title() {
+        let song: Song = serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "title": "Windowlicker",
+            "artist": "Aphex Twin",
+        }))
+        .unwrap();
+        let template = "{artist} – {title} [{state}]";
+        assert_eq!(
+            window_title(template, Some(&song), PlayerState::Paused),
+            "Aphex Twin – Windowlicker [paused]"
+        );
+        assert_eq!(
+            window_title(template, Some(&song), PlayerState::Stopped),
+            IDLE_TITLE
+        );
+        assert_eq!(
+            window_title(template, None, PlayerState::Playing),
+            IDLE_TITLE
+        );
+    }
+}